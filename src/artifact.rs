@@ -0,0 +1,113 @@
+//! `verify-artifact`: checks a compiled spec artifact, the JSON a build produces from a source spec
+//! after applying overlays and interpolating values, before it is deployed. The artifact must pass
+//! the schema and the rules like any spec, and its `lineage` must name the source it was built from:
+//!
+//! ```json
+//! "lineage": {
+//!   "source": "specs/support.yml",
+//!   "source_sha256": "<canonical SHA-256 of the source spec>",
+//!   "sha256": "<canonical SHA-256 of the artifact without its lineage>"
+//! }
+//! ```
+//!
+//! Hashes are taken of the canonical form [`publish`](crate::registry) uses, so reformatting the
+//! source does not change its hash. `sha256` is optional; when the build records it, an artifact
+//! edited after the build is caught even when it still validates. Nothing is written.
+
+use crate::{
+    diff, display_input,
+    locations::{self, Locations},
+    parse_documents, read_input, registry,
+    reporter::Reporter,
+    validate_document, Args, InputFormat, Source,
+};
+use serde_json::Value as JsonValue;
+use std::{path::Path, process::ExitCode};
+
+/// Validates the artifact in `file` and checks that its lineage names the spec in `source`.
+pub fn verify(args: &Args, file: &Path, source: &Path) -> ExitCode {
+    match try_verify(args, file, source) {
+        Ok(code) => code,
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn try_verify(args: &Args, file: &Path, source: &Path) -> Result<ExitCode, String> {
+    let name = display_input(file);
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let text = read_input(file)?;
+    let mut documents =
+        parse_documents(&text, format).map_err(|e| format!("Error: {name}: {e}"))?;
+    if documents.len() != 1 {
+        return Err(format!(
+            "Error: {name} holds {} documents; expected a single artifact",
+            documents.len()
+        ));
+    }
+    let mut artifact = documents.remove(0);
+    let lineage = artifact
+        .as_object_mut()
+        .and_then(|members| members.remove("lineage"));
+    let source_sha256 = registry::canonical_sha256(&diff::load(args, source)?);
+
+    // The artifact is validated as the spec it is, without the lineage the build added.
+    let locations = match format {
+        InputFormat::Yaml | InputFormat::Json => locations::yaml_documents(&text),
+        InputFormat::Toml => Vec::new(),
+    };
+    let no_locations = Locations::default();
+    let valid = validate_document(
+        args,
+        &Source {
+            path: file,
+            text: &text,
+            locations: locations.first().unwrap_or(&no_locations),
+            schema: None,
+            spec_version: None,
+            previous: None,
+        },
+        &artifact,
+    ) == ExitCode::SUCCESS;
+    args.reporters.finish();
+
+    let Some(lineage) = lineage else {
+        errln!("❌ {name} has no lineage; it cannot be traced to a source spec.");
+        return Ok(ExitCode::from(1));
+    };
+    let field = |key: &str| lineage.get(key).and_then(JsonValue::as_str);
+    let Some(recorded) = field("source_sha256") else {
+        errln!("❌ {name}: lineage.source_sha256 is missing or not a string.");
+        return Ok(ExitCode::from(1));
+    };
+    let mut traced = true;
+    if recorded != source_sha256 {
+        errln!(
+            "❌ {name} was not built from {}: lineage.source_sha256 is {recorded}, the source \
+             hashes to {source_sha256}.",
+            display_input(source)
+        );
+        traced = false;
+    }
+    if let Some(recorded) = field("sha256") {
+        let actual = registry::canonical_sha256(&artifact);
+        if recorded != actual {
+            errln!(
+                "❌ {name} was modified after it was built: lineage.sha256 is {recorded}, the \
+                 artifact hashes to {actual}."
+            );
+            traced = false;
+        }
+    }
+    if traced {
+        outln!(
+            "✅ {name} was built from {} (sha256 {source_sha256}).",
+            display_input(source)
+        );
+    }
+    Ok(ExitCode::from(if valid && traced { 0 } else { 1 }))
+}
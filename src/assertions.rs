@@ -0,0 +1,579 @@
+//! Declarative rules from the `assertions` of the configuration file: a JSONPath selector and an
+//! expression every selected node must satisfy, for policies too simple to be worth a script.
+//!
+//! Selectors are the [JSONPath subset](crate::jsonpath) that `query` takes too. Expressions are
+//! evaluated with the selected node as `value`:
+//!
+//! - literals: numbers, `'strings'` or `"strings"`, `true`, `false`, `null`
+//! - `value`, `value.field.field`, `length` and `type` (of `value`)
+//! - `length(x)`, `type(x)`, `contains(x, y)` and `matches(x, 'regex')`
+//! - `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&`, `||`, `!` and parentheses
+
+use crate::{
+    config::AssertionConfig,
+    context::type_name,
+    diagnostics::{Diagnostic, Severity},
+    jsonpath::{self, Step},
+    rules::{Diagnostics, Rule, SpecModel},
+};
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+
+/// ID prefix of the findings of assertions.
+const ID: &str = "CFG";
+
+/// Name under which assertions are configured, selected and reported.
+const NAME: &str = "assertions";
+
+enum Expr {
+    Literal(JsonValue),
+    /// `value` followed by member names.
+    Value(Vec<String>),
+    Length(Box<Expr>),
+    Type(Box<Expr>),
+    Contains(Box<Expr>, Box<Expr>),
+    Matches(Box<Expr>, Regex),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, &'static str, Box<Expr>),
+}
+
+struct Assertion {
+    code: &'static str,
+    path: String,
+    selector: Vec<Step>,
+    source: String,
+    expr: Expr,
+    message: Option<String>,
+    severity: Option<Severity>,
+}
+
+/// The rule checking every assertion of the configuration.
+pub struct AssertionRule {
+    assertions: Vec<Assertion>,
+    codes: Vec<(&'static str, &'static str)>,
+}
+
+impl AssertionRule {
+    /// Compiles the `assertions` of the configuration file. Assertions without a `code` are
+    /// numbered `CFG001`, `CFG002`, … in order.
+    pub fn compile(configs: &[AssertionConfig]) -> Result<Self, String> {
+        let mut assertions = Vec::new();
+        let mut codes: Vec<(&'static str, &'static str)> = Vec::new();
+        for (index, config) in configs.iter().enumerate() {
+            let invalid = |e: String| format!("Error: invalid assertion #{}: {e}", index + 1);
+            let numbered = |code: &str| {
+                code.strip_prefix(ID)
+                    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            };
+            let code = match &config.code {
+                Some(code) if !numbered(code) => {
+                    return Err(invalid(format!(
+                        "code '{code}' must be {ID} followed by a number, e.g. {ID}001"
+                    )))
+                }
+                Some(code) => code.clone(),
+                None => format!("{ID}{:03}", index + 1),
+            };
+            if codes.iter().any(|(known, _)| *known == code) {
+                return Err(invalid(format!("code {code} is used by another assertion")));
+            }
+            let selector = jsonpath::parse(&config.path).map_err(invalid)?;
+            let expr = Parser::new(&config.assertion)
+                .and_then(|parser| parser.parse())
+                .map_err(|e| invalid(format!("in assert '{}': {e}", config.assertion)))?;
+            let code: &'static str = Box::leak(code.into_boxed_str());
+            let summary = format!("{} must satisfy {}", config.path, config.assertion);
+            codes.push((code, Box::leak(summary.into_boxed_str())));
+            assertions.push(Assertion {
+                code,
+                path: config.path.clone(),
+                selector,
+                source: config.assertion.clone(),
+                expr,
+                message: config.message.clone(),
+                severity: config.severity,
+            });
+        }
+        Ok(Self { assertions, codes })
+    }
+}
+
+impl Rule for AssertionRule {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn label(&self) -> &'static str {
+        "assertion"
+    }
+
+    fn category(&self) -> &'static str {
+        "policy"
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        self.codes.clone()
+    }
+
+    fn check(&self, spec: &SpecModel, diagnostics: &mut Diagnostics) {
+        for assertion in &self.assertions {
+            for (at, value) in jsonpath::select(&assertion.selector, spec.doc) {
+                let message = match eval(&assertion.expr, value) {
+                    Ok(JsonValue::Bool(true)) => continue,
+                    Ok(JsonValue::Bool(false)) => match &assertion.message {
+                        Some(template) => fill(template, &at, value),
+                        None => format!(
+                            "{} fails the assertion {}",
+                            display_path(&at),
+                            assertion.source
+                        ),
+                    },
+                    Ok(other) => format!(
+                        "assert '{}' evaluates to {other} at {}, not to true or false",
+                        assertion.source,
+                        display_path(&at)
+                    ),
+                    Err(e) => format!(
+                        "assert '{}' cannot be evaluated at {}: {e}",
+                        assertion.source,
+                        display_path(&at)
+                    ),
+                };
+                let severity = assertion.severity.unwrap_or(Severity::Error);
+                diagnostics.push(
+                    Diagnostic::new(assertion.code, severity, message)
+                        .at(at)
+                        .with("path", assertion.path.clone()),
+                );
+            }
+        }
+    }
+}
+
+fn display_path(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "the spec"
+    } else {
+        pointer
+    }
+}
+
+/// `template` with `{value}`, `{length}` and `{pointer}` filled in.
+fn fill(template: &str, at: &str, value: &JsonValue) -> String {
+    let shown = match value {
+        JsonValue::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    let length = length(value).map_or_else(|| "-".to_string(), |n| n.to_string());
+    template
+        .replace("{value}", &shown)
+        .replace("{length}", &length)
+        .replace("{pointer}", at)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+/// The tokens of an expression; [graph conditions](crate::conditions) are read with them too.
+pub fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    const OPERATORS: [&str; 15] = [
+        "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", ",", ".", "-", "$",
+    ];
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if c == '\'' || c == '"' {
+            let mut value = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, q)) if q == c => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => value.push(escaped),
+                        None => return Err("unterminated string".to_string()),
+                    },
+                    Some((_, other)) => value.push(other),
+                    None => return Err("unterminated string".to_string()),
+                }
+            };
+            tokens.push(Token::Str(value));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("unexpected '{c}'"));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens of an expression.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, String> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            next: 0,
+        })
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.or()?;
+        match self.tokens.get(self.next) {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {}", describe(token))),
+        }
+    }
+
+    fn peek_op(&self, op: &str) -> bool {
+        matches!(self.tokens.get(self.next), Some(Token::Op(next)) if *next == op)
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.peek_op(op) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(match self.tokens.get(self.next) {
+                Some(token) => format!("expected '{op}', found {}", describe(token)),
+                None => format!("expected '{op}' at the end"),
+            })
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek_op("||") {
+            self.next += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.peek_op("&&") {
+            self.next += 1;
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek_op("!") {
+            self.next += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.primary()?;
+        if let Some(Token::Op(op @ ("==" | "!=" | "<" | "<=" | ">" | ">="))) =
+            self.tokens.get(self.next)
+        {
+            let op = *op;
+            self.next += 1;
+            return Ok(Expr::Compare(Box::new(left), op, Box::new(self.primary()?)));
+        }
+        Ok(left)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.tokens.get(self.next).cloned() else {
+            return Err("unexpected end of the expression".to_string());
+        };
+        self.next += 1;
+        match token {
+            Token::Number(number) => Ok(Expr::Literal(number.into())),
+            Token::Str(text) => Ok(Expr::Literal(text.into())),
+            Token::Op("-") => match self.tokens.get(self.next) {
+                Some(Token::Number(number)) => {
+                    self.next += 1;
+                    Ok(Expr::Literal((-number).into()))
+                }
+                _ => Err("'-' must be followed by a number".to_string()),
+            },
+            Token::Op("(") => {
+                let expr = self.or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.peek_op("(") => {
+                self.next += 1;
+                let mut args = vec![self.or()?];
+                while self.peek_op(",") {
+                    self.next += 1;
+                    args.push(self.or()?);
+                }
+                self.expect(")")?;
+                function(&name, args)
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(true.into())),
+                "false" => Ok(Expr::Literal(false.into())),
+                "null" => Ok(Expr::Literal(JsonValue::Null)),
+                "length" => Ok(Expr::Length(Box::new(Expr::Value(Vec::new())))),
+                "type" => Ok(Expr::Type(Box::new(Expr::Value(Vec::new())))),
+                "value" => {
+                    let mut fields = Vec::new();
+                    while self.peek_op(".") {
+                        self.next += 1;
+                        match self.tokens.get(self.next) {
+                            Some(Token::Ident(field)) => fields.push(field.clone()),
+                            _ => return Err("expected a member name after '.'".to_string()),
+                        }
+                        self.next += 1;
+                    }
+                    Ok(Expr::Value(fields))
+                }
+                other => Err(format!(
+                    "unknown name '{other}' (expected value, length, type, true, false or null)"
+                )),
+            },
+            other => Err(format!("unexpected {}", describe(&other))),
+        }
+    }
+}
+
+pub fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(number) => format!("number {number}"),
+        Token::Str(text) => format!("string '{text}'"),
+        Token::Ident(name) => format!("'{name}'"),
+        Token::Op(op) => format!("'{op}'"),
+    }
+}
+
+fn function(name: &str, mut args: Vec<Expr>) -> Result<Expr, String> {
+    let arity = match name {
+        "length" | "type" => 1,
+        "contains" | "matches" => 2,
+        other => {
+            return Err(format!(
+                "unknown function '{other}' (expected length, type, contains or matches)"
+            ))
+        }
+    };
+    if args.len() != arity {
+        return Err(format!("{name} takes {arity} argument(s)"));
+    }
+    let first = Box::new(args.remove(0));
+    Ok(match name {
+        "length" => Expr::Length(first),
+        "type" => Expr::Type(first),
+        "contains" => Expr::Contains(first, Box::new(args.remove(0))),
+        _ => match args.remove(0) {
+            Expr::Literal(JsonValue::String(pattern)) => Expr::Matches(
+                first,
+                Regex::new(&pattern).map_err(|e| format!("invalid regex '{pattern}': {e}"))?,
+            ),
+            _ => return Err("the pattern of matches must be a string".to_string()),
+        },
+    })
+}
+
+fn length(value: &JsonValue) -> Option<usize> {
+    match value {
+        JsonValue::Array(items) => Some(items.len()),
+        JsonValue::Object(members) => Some(members.len()),
+        JsonValue::String(text) => Some(text.chars().count()),
+        _ => None,
+    }
+}
+
+fn boolean(expr: &Expr, value: &JsonValue) -> Result<bool, String> {
+    match eval(expr, value)? {
+        JsonValue::Bool(b) => Ok(b),
+        other => Err(format!("{other} is not true or false")),
+    }
+}
+
+/// `expr` evaluated with `value` as the selected node. Missing members are `null`.
+fn eval(expr: &Expr, value: &JsonValue) -> Result<JsonValue, String> {
+    Ok(match expr {
+        Expr::Literal(literal) => literal.clone(),
+        Expr::Value(fields) => fields
+            .iter()
+            .try_fold(value, |node, field| node.get(field))
+            .cloned()
+            .unwrap_or(JsonValue::Null),
+        Expr::Length(of) => {
+            let of = eval(of, value)?;
+            match length(&of) {
+                Some(n) => n.into(),
+                None => return Err(format!("a {} has no length", type_name(&of))),
+            }
+        }
+        Expr::Type(of) => type_name(&eval(of, value)?).into(),
+        Expr::Contains(haystack, needle) => {
+            let needle = eval(needle, value)?;
+            match eval(haystack, value)? {
+                JsonValue::Array(items) => items.contains(&needle).into(),
+                JsonValue::Object(members) => match &needle {
+                    JsonValue::String(name) => members.contains_key(name).into(),
+                    _ => false.into(),
+                },
+                JsonValue::String(text) => match &needle {
+                    JsonValue::String(part) => text.contains(part.as_str()).into(),
+                    _ => false.into(),
+                },
+                other => {
+                    return Err(format!(
+                        "contains needs an array, object or string, not a {}",
+                        type_name(&other)
+                    ))
+                }
+            }
+        }
+        Expr::Matches(text, pattern) => match eval(text, value)? {
+            JsonValue::String(text) => pattern.is_match(&text).into(),
+            other => {
+                return Err(format!(
+                    "matches needs a string, not a {}",
+                    type_name(&other)
+                ))
+            }
+        },
+        Expr::Not(inner) => (!boolean(inner, value)?).into(),
+        Expr::And(left, right) => (boolean(left, value)? && boolean(right, value)?).into(),
+        Expr::Or(left, right) => (boolean(left, value)? || boolean(right, value)?).into(),
+        Expr::Compare(left, op, right) => {
+            let (left, right) = (eval(left, value)?, eval(right, value)?);
+            let order = match (&left, &right) {
+                (JsonValue::Number(a), JsonValue::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+                (JsonValue::String(a), JsonValue::String(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            match (*op, order) {
+                ("==", Some(order)) => (order == Ordering::Equal).into(),
+                ("!=", Some(order)) => (order != Ordering::Equal).into(),
+                ("==", None) => (left == right).into(),
+                ("!=", None) => (left != right).into(),
+                (_, None) => {
+                    return Err(format!(
+                        "cannot compare a {} with a {}",
+                        type_name(&left),
+                        type_name(&right)
+                    ))
+                }
+                ("<", Some(order)) => (order == Ordering::Less).into(),
+                ("<=", Some(order)) => (order != Ordering::Greater).into(),
+                (">", Some(order)) => (order == Ordering::Greater).into(),
+                (_, Some(order)) => (order != Ordering::Less).into(),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// `expr` evaluated with `value` as the selected node.
+    fn evaluate(expr: &str, value: JsonValue) -> Result<JsonValue, String> {
+        eval(&Parser::new(expr)?.parse()?, &value)
+    }
+
+    #[test]
+    fn evaluates_values_functions_and_operators() {
+        let port = json!({ "name": "severity", "schema": { "type": "number" }, "tags": ["a"] });
+        for (expr, expected) in [
+            ("value.name == 'severity'", json!(true)),
+            ("value.schema.type", json!("number")),
+            ("value.missing", json!(null)),
+            (
+                "length(value.tags) >= 1 && type(value.tags) == 'array'",
+                json!(true),
+            ),
+            ("length", json!(3)),
+            ("type == \"object\"", json!(true)),
+            (
+                "contains(value.tags, 'a') && contains(value, 'schema')",
+                json!(true),
+            ),
+            (
+                "contains(value.name, 'ever') && !contains(value.tags, 'b')",
+                json!(true),
+            ),
+            ("matches(value.name, '^[a-z_]+$')", json!(true)),
+            ("1 < 2 || value.missing.deeper", json!(true)),
+            ("!(1.5 > 2) && 'a' <= 'b'", json!(true)),
+            ("value.missing == null", json!(true)),
+        ] {
+            assert_eq!(evaluate(expr, port.clone()), Ok(expected), "{expr}");
+        }
+    }
+
+    #[test]
+    fn reports_expressions_that_do_not_parse() {
+        for (expr, error) in [
+            ("value ==", "unexpected end of the expression"),
+            ("value.name 'x'", "unexpected string 'x'"),
+            ("'unterminated", "unterminated string"),
+            ("value # 1", "unexpected '#'"),
+            (
+                "size(value)",
+                "unknown function 'size' (expected length, type, contains or matches)",
+            ),
+            ("contains(value)", "contains takes 2 argument(s)"),
+            (
+                "matches(value, value.name)",
+                "the pattern of matches must be a string",
+            ),
+        ] {
+            let parsed = Parser::new(expr).and_then(Parser::parse);
+            assert_eq!(parsed.err().as_deref(), Some(error), "{expr}");
+        }
+    }
+
+    #[test]
+    fn reports_values_of_the_wrong_type() {
+        assert_eq!(
+            evaluate("length(value) > 0", json!(3)),
+            Err("a number has no length".to_string())
+        );
+        assert_eq!(
+            evaluate("value < 'a'", json!(3)),
+            Err("cannot compare a number with a string".to_string())
+        );
+        assert_eq!(
+            evaluate("value && true", json!("yes")),
+            Err("\"yes\" is not true or false".to_string())
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! `--report-suppressed`: an audit of the findings a run did not report because a baseline, an
+//! inline annotation, `--select`/`--ignore` or a disabled rule waived them, with how long each
+//! waiver has been in place.
+
+use serde_json::{json, Value as JsonValue};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// What silenced a finding.
+#[derive(Clone, Debug)]
+pub enum Waiver {
+    /// An entry of the `--baseline` file.
+    Baseline { path: PathBuf },
+    /// `x-verify-ignore` (or `meta.verify_ignore`) at this pointer of the spec.
+    Inline { pointer: String },
+    /// The rule ID is not selected by `--select`, or matches this `--ignore` prefix.
+    Selection { ignored_by: Option<String> },
+    /// The rule is disabled in this configuration file.
+    Disabled { rule: String, config: PathBuf },
+}
+
+impl Waiver {
+    /// Source of the waiver as printed, e.g. `x-verify-ignore at /implementation/...`.
+    pub fn describe(&self) -> String {
+        match self {
+            Waiver::Baseline { path } => format!("baseline {}", path.display()),
+            Waiver::Inline { pointer } if pointer == "/meta/verify_ignore" => {
+                "meta.verify_ignore".to_string()
+            }
+            Waiver::Inline { pointer } => {
+                let node = pointer.strip_suffix("/x-verify-ignore").unwrap_or(pointer);
+                format!("x-verify-ignore at {node}")
+            }
+            Waiver::Selection {
+                ignored_by: Some(prefix),
+            } => format!("--ignore {prefix}"),
+            Waiver::Selection { ignored_by: None } => "not matched by --select".to_string(),
+            Waiver::Disabled { rule, config } => {
+                format!("rules.{rule}.enabled: false in {}", config.display())
+            }
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Waiver::Baseline { .. } => "baseline",
+            Waiver::Inline { .. } => "inline",
+            Waiver::Selection { .. } => "selection",
+            Waiver::Disabled { .. } => "config",
+        }
+    }
+}
+
+/// One finding that was not reported.
+#[derive(Clone, Debug)]
+pub struct Suppressed {
+    /// Input as displayed; `None` for the cross-spec checks.
+    pub file: Option<String>,
+    pub code: String,
+    pub pointer: Option<String>,
+    pub message: String,
+    pub waiver: Waiver,
+    /// When the waiver was introduced, if known.
+    pub since: Option<SystemTime>,
+}
+
+/// Suppressed findings collected during a run, in the order they were found.
+#[derive(Debug, Default)]
+pub struct SuppressionAudit {
+    entries: Mutex<Vec<Suppressed>>,
+}
+
+impl SuppressionAudit {
+    pub fn record(&self, suppressed: Suppressed) {
+        self.entries.lock().unwrap().push(suppressed);
+    }
+
+    /// Forgets the findings of previous runs (in `--watch` mode).
+    pub fn start_run(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Prints the audit for people, grouped by waiver, oldest waivers first.
+    pub fn print(&self) {
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.sort_by(|a, b| {
+            (a.waiver.kind(), a.since, a.waiver.describe()).cmp(&(
+                b.waiver.kind(),
+                b.since,
+                b.waiver.describe(),
+            ))
+        });
+        outln!("── {} suppressed finding(s) ──", entries.len());
+        let now = SystemTime::now();
+        let mut current = None;
+        for entry in &entries {
+            let source = entry.waiver.describe();
+            if current.as_ref() != Some(&source) {
+                match (&entry.waiver, entry.since) {
+                    // Command-line selections have no history to date them by.
+                    (Waiver::Selection { .. }, _) => outln!("{source}"),
+                    (_, Some(since)) => outln!("{source} ({})", age(now, since)),
+                    (_, None) => outln!("{source} (age unknown)"),
+                }
+                current = Some(source);
+            }
+            let at = match (&entry.file, &entry.pointer) {
+                (Some(file), Some(pointer)) if !pointer.is_empty() => format!("{file} {pointer}"),
+                (Some(file), _) => file.clone(),
+                (None, _) => "(cross-spec)".to_string(),
+            };
+            outln!("  [{}] {at}: {}", entry.code, entry.message);
+        }
+    }
+
+    /// Emits one JSON object per suppressed finding (for `--format json`).
+    pub fn emit(&self) {
+        for entry in self.entries.lock().unwrap().iter() {
+            let since = entry
+                .since
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            let object = json!({
+                "file": entry.file,
+                "pointer": entry.pointer,
+                "code": entry.code,
+                "message": entry.message,
+                "suppressed": {
+                    "kind": entry.waiver.kind(),
+                    "source": entry.waiver.describe(),
+                    "since": since,
+                },
+            });
+            crate::output::emit(&strip_nulls(object).to_string());
+        }
+    }
+}
+
+/// Drops `null` fields, like the finding objects of the JSON reporter.
+fn strip_nulls(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(map) => JsonValue::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, strip_nulls(v)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// `3 day(s) old`, `5 month(s) old`, … for the time between `since` and `now`.
+fn age(now: SystemTime, since: SystemTime) -> String {
+    let days = now
+        .duration_since(since)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+        / 86_400;
+    match days {
+        0 => "added today".to_string(),
+        1..=59 => format!("{days} day(s) old"),
+        60..=729 => format!("{} month(s) old", days / 30),
+        _ => format!("{} year(s) old", days / 365),
+    }
+}
+
+/// When `line` (1-based) of `path` was last changed according to `git blame`, falling back to the
+/// file's modification time outside a git checkout or for uncommitted lines.
+pub fn line_time(path: &Path, line: usize) -> Option<SystemTime> {
+    let range = format!("{line},{line}");
+    let blame = git(path, &["blame", "--porcelain", "-L", &range, "--"]);
+    // Uncommitted lines are blamed on the all-zero commit with the current time.
+    let committed = blame
+        .as_deref()
+        .filter(|out| !out.starts_with("0000000000000000000000000000000000000000"));
+    committed
+        .and_then(|out| {
+            out.lines()
+                .find_map(|l| l.strip_prefix("author-time "))
+                .and_then(|t| t.trim().parse().ok())
+        })
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .or_else(|| modified(path))
+}
+
+/// When `path` was last committed, falling back to its modification time.
+pub fn file_time(path: &Path) -> Option<SystemTime> {
+    git(path, &["log", "-1", "--format=%ct", "--"])
+        .and_then(|out| out.trim().parse().ok())
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .or_else(|| modified(path))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|m| m.modified()).ok()
+}
+
+/// Runs `git <args> <file name>` in the directory of `path`; `None` when git fails or prints
+/// nothing.
+fn git(path: &Path, args: &[&str]) -> Option<String> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let output = Command::new("git")
+        .args(args)
+        .arg(path.file_name()?)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !text.trim().is_empty()).then_some(text)
+}
@@ -0,0 +1,106 @@
+//! `serve --audit-log`: an append-only JSON Lines record of the validation decisions of the server
+//! (who submitted which spec, by hash, which schemas it was checked against and the outcome), for
+//! change management around spec uploads. The file is rotated when it grows past a size limit:
+//! `FILE` becomes `FILE.1`, `FILE.1` becomes `FILE.2` and so on, and the oldest is dropped.
+
+use serde_json::Value as JsonValue;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+pub struct AuditLog {
+    path: PathBuf,
+    /// Size past which the file is rotated before the next entry.
+    max_size: u64,
+    /// Rotated files kept.
+    keep: usize,
+    /// Serializes writers, so that entries are never interleaved and rotation is not raced.
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// The log at `path`, which is created if needed and must be writable.
+    pub fn open(path: &Path, max_size: u64, keep: usize) -> Result<Self, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Error: cannot write audit log {}: {e}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            keep,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Appends `entry` as one line and flushes it to disk.
+    pub fn record(&self, entry: &JsonValue) -> Result<(), String> {
+        let line = format!("{entry}\n");
+        let _guard = self.lock.lock().unwrap();
+        let written = (|| {
+            let size = fs::metadata(&self.path).map_or(0, |m| m.len());
+            if size > 0 && size + line.len() as u64 > self.max_size {
+                self.rotate()?;
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            file.write_all(line.as_bytes())?;
+            file.sync_data()
+        })();
+        written.map_err(|e| {
+            format!(
+                "Error: failed to write audit log {}: {e}",
+                self.path.display()
+            )
+        })
+    }
+
+    fn rotated(&self, number: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{number}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        for number in (1..self.keep).rev() {
+            let from = self.rotated(number);
+            if from.exists() {
+                fs::rename(from, self.rotated(number + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+}
+
+/// Parses a size such as `10M`, `512K`, `1G` or a number of bytes; units are powers of 1024.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{text}' (expected e.g. 10M, 512K, 1G)"))?;
+    let factor: u64 = match unit.trim().trim_end_matches("iB").trim_end_matches('B') {
+        "" => 1,
+        "K" | "k" => 1 << 10,
+        "M" | "m" => 1 << 20,
+        "G" | "g" => 1 << 30,
+        other => {
+            return Err(format!(
+                "unknown size unit '{other}' in '{text}' (expected K, M or G)"
+            ))
+        }
+    };
+    number
+        .checked_mul(factor)
+        .filter(|size| *size > 0)
+        .ok_or_else(|| format!("invalid size '{text}'"))
+}
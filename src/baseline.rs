@@ -0,0 +1,180 @@
+//! Baseline files: snapshots of known findings that later runs stop reporting, so legacy spec
+//! repositories can adopt the validator and only fail on new violations.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Format version written to (and required from) baseline files.
+const BASELINE_VERSION: u32 = 1;
+
+/// Identity of one finding. Locations are JSON Pointers rather than line numbers so that
+/// unrelated edits to a spec do not invalidate its baseline.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Finding {
+    /// Spec file as given on the command line; empty for cross-spec findings.
+    pub file: String,
+    /// Rule ID, or `schema` for JSON Schema violations.
+    pub code: String,
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(file: Option<&Path>, code: &str, pointer: &str, message: &str) -> Self {
+        Self {
+            file: file.map(file_label).unwrap_or_default(),
+            code: code.to_string(),
+            pointer: pointer.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    findings: Vec<Entry>,
+}
+
+/// A finding as stored, with the time (seconds since the Unix epoch) it was first recorded.
+/// Baselines written before `since` existed leave it out.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    #[serde(flatten)]
+    finding: Finding,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    since: Option<u64>,
+}
+
+/// Known findings loaded from `--baseline`, plus the findings observed during the current run
+/// (written out by `--write-baseline`).
+#[derive(Debug, Default)]
+pub struct Baseline {
+    known: HashMap<Finding, usize>,
+    since: HashMap<Finding, u64>,
+    remaining: HashMap<Finding, usize>,
+    matched: usize,
+    observed: Vec<Finding>,
+    /// Neither hides nor records findings (see [`Baseline::disabled`]).
+    disabled: bool,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Error: failed to read baseline {}: {e}", path.display()))?;
+        let file: BaselineFile = serde_json::from_str(&text)
+            .map_err(|e| format!("Error: invalid baseline {}: {e}", path.display()))?;
+        if file.version != BASELINE_VERSION {
+            return Err(format!(
+                "Error: baseline {} has version {}, expected {BASELINE_VERSION}; regenerate it with --write-baseline",
+                path.display(),
+                file.version
+            ));
+        }
+        let mut known = HashMap::new();
+        let mut since = HashMap::new();
+        for entry in file.findings {
+            if let Some(time) = entry.since {
+                let first = since.entry(entry.finding.clone()).or_insert(time);
+                *first = (*first).min(time);
+            }
+            *known.entry(entry.finding).or_default() += 1;
+        }
+        Ok(Self {
+            known,
+            since,
+            ..Default::default()
+        })
+    }
+
+    /// A baseline that hides and records nothing, for `serve`: its requests are validated
+    /// concurrently and none of them writes the findings out.
+    pub fn disabled() -> Self {
+        Self {
+            disabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// Forgets what previous runs (in `--watch` mode) matched or observed.
+    pub fn start_run(&mut self) {
+        self.remaining = self.known.clone();
+        self.matched = 0;
+        self.observed.clear();
+    }
+
+    /// Records `finding` and returns whether the baseline already knows it. Each baseline entry
+    /// absorbs one occurrence, so a violation that appears once more than before is reported.
+    pub fn absorb(&mut self, finding: Finding) -> bool {
+        if self.disabled {
+            return false;
+        }
+        self.observed.push(finding.clone());
+        match self.remaining.get_mut(&finding) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                self.matched += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// When `finding` was first recorded in the baseline, if the file says so.
+    pub fn since(&self, finding: &Finding) -> Option<SystemTime> {
+        self.since
+            .get(finding)
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(*secs))
+    }
+
+    /// Findings hidden by the baseline in this run.
+    pub fn matched(&self) -> usize {
+        self.matched
+    }
+
+    /// Baseline entries that did not occur in this run (fixed since the snapshot).
+    pub fn stale(&self) -> usize {
+        self.remaining.values().sum()
+    }
+
+    /// Writes every finding observed in this run, sorted for stable diffs. Findings the file at
+    /// `path` already recorded keep their `since`; new ones are stamped with the current time.
+    pub fn write(&self, path: &Path) -> Result<usize, String> {
+        let mut findings = self.observed.clone();
+        findings.sort();
+        let count = findings.len();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let previous = Self::load(path).map(|b| b.since).unwrap_or_default();
+        let findings = findings
+            .into_iter()
+            .map(|finding| Entry {
+                since: Some(previous.get(&finding).copied().unwrap_or(now)),
+                finding,
+            })
+            .collect();
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            findings,
+        };
+        let text = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("Error: failed to serialize baseline: {e}"))?;
+        fs::write(path, text + "\n")
+            .map_err(|e| format!("Error: failed to write baseline {}: {e}", path.display()))?;
+        Ok(count)
+    }
+}
+
+/// Spec path as stored in a baseline: as given, without a leading `./`, with `/` separators.
+fn file_label(path: &Path) -> String {
+    let label = path.to_string_lossy().replace('\\', "/");
+    label.strip_prefix("./").unwrap_or(&label).to_string()
+}
@@ -0,0 +1,189 @@
+//! `schema bundle`: a single self-contained schema with every `$ref` (local, file or URL) replaced by
+//! the schema it points to. Circular references cannot be expanded and are reported instead.
+
+use crate::{
+    schemas::{ResolvedSchema, SchemaRequest, SchemaResolvers},
+    Args,
+};
+use serde_json::{Map, Value as JsonValue};
+use std::{collections::HashMap, fs, path::Path, process::ExitCode};
+use url::Url;
+
+/// Keywords whose values are data rather than schemas, so a `$ref` key inside them is left alone.
+const DATA_KEYWORDS: [&str; 4] = ["enum", "const", "default", "examples"];
+
+/// Prints the bundled schema for `spec_version` (or `--schema`), or writes it to `output`.
+pub fn bundle(args: &Args, spec_version: Option<&str>, output: Option<&Path>) -> ExitCode {
+    let request = SchemaRequest {
+        input: Path::new("."),
+        spec_version,
+    };
+    let explicit = args.schema.as_ref().map(|p| p.to_string_lossy());
+    let bundled = args
+        .schemas
+        .resolve(explicit.as_deref(), &request)
+        .and_then(|resolved| bundled(args, resolved));
+    let bundled = match bundled {
+        Ok(bundled) => serde_json::to_string_pretty(&bundled).unwrap(),
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    match output {
+        Some(path) => match fs::write(path, bundled + "\n") {
+            Ok(()) => {
+                outln!("📦 Wrote the bundled schema to {}.", path.display());
+                ExitCode::from(0)
+            }
+            Err(e) => {
+                failln!("Error: failed to write {}: {e}", path.display());
+                ExitCode::from(1)
+            }
+        },
+        None => {
+            outln!("{bundled}");
+            ExitCode::from(0)
+        }
+    }
+}
+
+/// `resolved` with every `$ref` inlined.
+pub fn bundled(args: &Args, resolved: ResolvedSchema) -> Result<JsonValue, String> {
+    let root = document_key(resolved.base.as_ref());
+    let mut bundler = Bundler {
+        resolvers: &args.schemas,
+        documents: HashMap::from([(root.clone(), resolved.schema.clone())]),
+        expanding: Vec::new(),
+    };
+    let mut schema = resolved.schema;
+    // Every reference gets inlined, so the definitions are no longer needed.
+    if let Some(root) = schema.as_object_mut() {
+        root.remove("definitions");
+        root.remove("$defs");
+    }
+    bundler.inline(&schema, &root, resolved.base.as_ref())
+}
+
+struct Bundler<'a> {
+    resolvers: &'a SchemaResolvers,
+    /// Schema documents by [`document_key`].
+    documents: HashMap<String, JsonValue>,
+    /// `(target, $ref as written)` of the references being expanded, outermost first.
+    expanding: Vec<(String, String)>,
+}
+
+impl Bundler<'_> {
+    /// `node` with every `$ref` inlined. `document` is the key of the document containing `node`
+    /// and `base` its location, which relative references are resolved against.
+    fn inline(
+        &mut self,
+        node: &JsonValue,
+        document: &str,
+        base: Option<&Url>,
+    ) -> Result<JsonValue, String> {
+        match node {
+            JsonValue::Object(map) => {
+                let mut inlined = Map::new();
+                for (key, value) in map {
+                    if key == "$ref" {
+                        continue;
+                    }
+                    let value = if DATA_KEYWORDS.contains(&key.as_str()) {
+                        value.clone()
+                    } else {
+                        self.inline(value, document, base)?
+                    };
+                    inlined.insert(key.clone(), value);
+                }
+                let Some(reference) = map.get("$ref").and_then(|r| r.as_str()) else {
+                    return Ok(JsonValue::Object(inlined));
+                };
+                let target = self.dereference(reference, document, base)?;
+                if inlined.is_empty() {
+                    return Ok(target);
+                }
+                // Keywords next to a `$ref` still apply to the instance.
+                inlined.insert("allOf".into(), JsonValue::Array(vec![target]));
+                Ok(JsonValue::Object(inlined))
+            }
+            JsonValue::Array(items) => items
+                .iter()
+                .map(|item| self.inline(item, document, base))
+                .collect(),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// The inlined schema `reference` points to.
+    fn dereference(
+        &mut self,
+        reference: &str,
+        document: &str,
+        base: Option<&Url>,
+    ) -> Result<JsonValue, String> {
+        let (location, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let (document, base) = if location.is_empty() {
+            (document.to_string(), base.cloned())
+        } else {
+            let url = match base {
+                Some(base) => base.join(location),
+                None => Url::parse(location),
+            }
+            .map_err(|_| {
+                format!("Error: cannot resolve $ref '{reference}': the schema has no location to resolve it against")
+            })?;
+            let key = document_key(Some(&url));
+            if !self.documents.contains_key(&key) {
+                let schema = self.load(&url)?;
+                self.documents.insert(key.clone(), schema);
+            }
+            (key, Some(url))
+        };
+
+        let target = format!("{document}#{fragment}");
+        if let Some(start) = self.expanding.iter().position(|(t, _)| *t == target) {
+            let cycle: Vec<&str> = self.expanding[start..]
+                .iter()
+                .map(|(_, written)| written.as_str())
+                .chain([reference])
+                .collect();
+            return Err(format!(
+                "Error: circular $ref cannot be bundled: {}",
+                cycle.join(" → ")
+            ));
+        }
+        let node = self.documents[&document]
+            .pointer(fragment)
+            .cloned()
+            .ok_or_else(|| format!("Error: $ref '{reference}' points to nothing"))?;
+        self.expanding.push((target, reference.to_string()));
+        let inlined = self.inline(&node, &document, base.as_ref());
+        self.expanding.pop();
+        inlined
+    }
+
+    fn load(&self, url: &Url) -> Result<JsonValue, String> {
+        let uri = match url.scheme() {
+            "file" => url
+                .to_file_path()
+                .map_err(|()| format!("Error: invalid file URL '{url}'"))?
+                .display()
+                .to_string(),
+            _ => url.as_str().to_string(),
+        };
+        Ok(self.resolvers.by_uri(&uri)?.schema)
+    }
+}
+
+/// Identifies a schema document: its location without the fragment, or `""` for a schema that has
+/// no location (the embedded one).
+fn document_key(location: Option<&Url>) -> String {
+    location
+        .map(|url| {
+            let mut url = url.clone();
+            url.set_fragment(None);
+            url.to_string()
+        })
+        .unwrap_or_default()
+}
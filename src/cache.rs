@@ -0,0 +1,283 @@
+//! On-disk caches: parsed schemas, so repeated invocations (e.g. a pre-commit hook running once
+//! per file) skip parsing large YAML schemas again, and copies of everything downloaded over
+//! HTTP(S), for `--offline` runs. Also the in-memory [`Memo`] that worker threads share within a
+//! run, and the hit and miss counters of every cache layer that `--timings` shows.
+//!
+//! Parsed schemas are keyed by a SHA-256 of the tool version and the schema text, so an edited
+//! schema or a new release never sees a stale entry. The caches are best effort: unreadable entries
+//! are treated as missing and failed writes are ignored. Compiled validators cannot be stored, so
+//! schemas are still compiled once per run.
+
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    env,
+    fmt::Write,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, RwLock,
+    },
+};
+
+/// Hit and miss counters of one cache layer, counted since the process started.
+pub struct CacheStats {
+    pub name: &'static str,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheStats {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `(hits, misses)` so far.
+    pub fn counts(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+pub static COMPILED_SCHEMAS: CacheStats = CacheStats::new("compiled schemas");
+pub static FETCHED_SCHEMAS: CacheStats = CacheStats::new("fetched schemas");
+pub static PARSED_SCHEMAS: CacheStats = CacheStats::new("parsed schemas (disk)");
+pub static DOWNLOADED_COPIES: CacheStats = CacheStats::new("downloads (disk)");
+
+/// Results of `serve` for repeated uploads; only the server has this layer, so it is not part of
+/// [`STATISTICS`].
+pub static VALIDATION_RESULTS: CacheStats = CacheStats::new("validation results");
+
+/// Every cache layer, from the innermost (per run, in memory) to the on-disk ones.
+pub static STATISTICS: [&CacheStats; 4] = [
+    &COMPILED_SCHEMAS,
+    &FETCHED_SCHEMAS,
+    &PARSED_SCHEMAS,
+    &DOWNLOADED_COPIES,
+];
+
+/// Number of independently locked parts of a [`Memo`].
+const SHARDS: usize = 16;
+
+/// Values computed once per key and shared between threads. Keys are spread over [`SHARDS`]
+/// locks that are only held to look up or add an entry, never while a value is computed, so
+/// threads needing different keys never wait for each other, and threads needing the same key
+/// wait for the one computing it rather than computing it again.
+pub struct Memo<V> {
+    shards: [RwLock<HashMap<String, Arc<OnceLock<V>>>>; SHARDS],
+    stats: &'static CacheStats,
+}
+
+impl<V: Clone> Memo<V> {
+    /// An empty memo counting its hits and misses in `stats`.
+    pub fn new(stats: &'static CacheStats) -> Self {
+        Self {
+            shards: std::array::from_fn(|_| RwLock::default()),
+            stats,
+        }
+    }
+
+    fn shard(&self, key: &str) -> &RwLock<HashMap<String, Arc<OnceLock<V>>>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % SHARDS]
+    }
+
+    /// The value of `key`, computed by `compute` if no thread has computed it yet.
+    pub fn get_or_compute(&self, key: &str, compute: impl FnOnce() -> V) -> V {
+        let shard = self.shard(key);
+        let existing = shard.read().unwrap().get(key).cloned();
+        let cell = existing.unwrap_or_else(|| {
+            shard
+                .write()
+                .unwrap()
+                .entry(key.to_string())
+                .or_default()
+                .clone()
+        });
+        let mut computed = false;
+        let value = cell
+            .get_or_init(|| {
+                computed = true;
+                compute()
+            })
+            .clone();
+        self.stats.count(!computed);
+        value
+    }
+
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.write().unwrap().clear();
+        }
+    }
+}
+
+impl<V> std::fmt::Debug for Memo<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Memo({})", self.stats.name)
+    }
+}
+
+/// Location of the cache when enabled.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaCache {
+    dir: Option<PathBuf>,
+}
+
+impl SchemaCache {
+    /// The cache under [`cache_root`], or a disabled cache when there is no such directory.
+    pub fn standard() -> Self {
+        Self {
+            dir: cache_root().map(|root| root.join(SCHEMAS_DIR)),
+        }
+    }
+
+    /// A cache that never stores anything (`--no-cache`).
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// The schema parsed from `text`: the cached copy if there is one, otherwise the result of
+    /// `parse`, which is then stored.
+    pub fn parsed(
+        &self,
+        text: &str,
+        parse: impl FnOnce() -> Result<JsonValue, String>,
+    ) -> Result<JsonValue, String> {
+        let Some(dir) = &self.dir else {
+            return parse();
+        };
+        let entry = dir.join(format!(
+            "{}.json",
+            digest(&[env!("CARGO_PKG_VERSION"), text])
+        ));
+        let cached = fs::read(&entry)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        PARSED_SCHEMAS.count(cached.is_some());
+        if let Some(schema) = cached {
+            return Ok(schema);
+        }
+        let schema = parse()?;
+        if let Ok(bytes) = serde_json::to_vec(&schema) {
+            store(dir, &entry, &bytes);
+        }
+        Ok(schema)
+    }
+}
+
+/// Copies of files downloaded over HTTP(S), keyed by URL.
+#[derive(Clone, Debug, Default)]
+pub struct Downloads {
+    dir: Option<PathBuf>,
+}
+
+impl Downloads {
+    /// The copies under [`cache_root`], or none when there is no such directory.
+    pub fn standard() -> Self {
+        Self {
+            dir: cache_root().map(|root| root.join(DOWNLOADS_DIR)),
+        }
+    }
+
+    /// Keeps no copies (`--no-cache`).
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// The last downloaded content of `url`, if any.
+    pub fn get(&self, url: &str) -> Option<String> {
+        let text = fs::read_to_string(self.entry(url)?).ok();
+        DOWNLOADED_COPIES.count(text.is_some());
+        text
+    }
+
+    pub fn store(&self, url: &str, text: &str) {
+        if let (Some(dir), Some(entry)) = (&self.dir, self.entry(url)) {
+            store(dir, &entry, text.as_bytes());
+        }
+    }
+
+    fn entry(&self, url: &str) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(digest(&[url])))
+    }
+}
+
+/// Subdirectories of the cache root holding parsed schemas and downloads.
+const SCHEMAS_DIR: &str = "schemas";
+const DOWNLOADS_DIR: &str = "downloads";
+
+/// `$XDG_CACHE_HOME/program-verify`, falling back to `~/.cache/program-verify`.
+pub fn cache_root() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("program-verify"))
+}
+
+/// Removes every cached schema and download; returns how many entries there were.
+pub fn clear() -> Result<usize, String> {
+    let root =
+        cache_root().ok_or("Error: no cache directory (neither XDG_CACHE_HOME nor HOME is set)")?;
+    let mut removed = 0;
+    for dir in [SCHEMAS_DIR, DOWNLOADS_DIR].map(|name| root.join(name)) {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Error: failed to read {}: {e}", dir.display())),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            fs::remove_file(&path)
+                .map_err(|e| format!("Error: failed to remove {}: {e}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Writes `entry` under a temporary name first so concurrent runs never read a partial entry.
+fn store(dir: &Path, entry: &Path, bytes: &[u8]) {
+    let mut partial = entry.as_os_str().to_owned();
+    partial.push(format!(".{}", process::id()));
+    let stored = fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&partial, bytes))
+        .and_then(|()| fs::rename(&partial, entry));
+    if stored.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+}
+
+/// Hex SHA-256 of `parts`, separated so that different splits never collide.
+fn digest(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
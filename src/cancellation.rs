@@ -0,0 +1,27 @@
+//! Cooperative cancellation of a validation run. The run checks its [`CancellationToken`] between
+//! files, documents, the schema check and every rule, so a run nobody waits for anymore (in
+//! `--watch` mode: the inputs changed again) stops at the next checkpoint instead of finishing.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Shared flag asking a run to stop. Clones observe the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clears the flag before the next run.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
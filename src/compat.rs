@@ -0,0 +1,495 @@
+//! `compat`: classifies the contract changes between two versions of a spec as breaking or
+//! non-breaking for the callers and consumers of its phases.
+//!
+//! Breaking: removed or renamed phases, removed phase or algorithm outputs, output schemas that
+//! accept more than before, new required inputs, input schemas that accept less than before, and
+//! removed error codes the old spec still refers to (in retry policies, fallbacks, conditions, …).
+//! Everything else that changes a contract is reported as non-breaking.
+
+use crate::{
+    diagnostics::pointer,
+    diff::{load, phases},
+    display_input, Args,
+};
+use serde_json::Value as JsonValue;
+use std::{path::Path, process::ExitCode};
+
+/// One classified contract change.
+struct Change {
+    breaking: bool,
+    message: String,
+}
+
+/// Prints the contract changes from `old` to `new`; fails on breaking ones unless
+/// `allow_breaking`.
+pub fn compat(args: &Args, old: &Path, new: &Path, allow_breaking: bool) -> ExitCode {
+    let specs = load(args, old).and_then(|old_doc| Ok((old_doc, load(args, new)?)));
+    let (old_doc, new_doc) = match specs {
+        Ok(specs) => specs,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let mut changes = Vec::new();
+    compare_phases(&old_doc, &new_doc, &mut changes);
+    compare_algorithm_outputs(&old_doc, &new_doc, &mut changes);
+    // Breaking changes first, each group in the order found.
+    changes.sort_by_key(|change| !change.breaking);
+
+    let breaking = changes.iter().filter(|c| c.breaking).count();
+    outln!(
+        "── {} → {}: {breaking} breaking, {} non-breaking change(s) ──",
+        display_input(old),
+        display_input(new),
+        changes.len() - breaking
+    );
+    for change in &changes {
+        if change.breaking {
+            outln!("❌ Breaking: {}", change.message);
+        } else {
+            outln!("✅ Non-breaking: {}", change.message);
+        }
+    }
+    match breaking {
+        0 => {
+            outln!("✅ No breaking changes.");
+            ExitCode::from(0)
+        }
+        _ if allow_breaking => {
+            outln!("⚠️ {breaking} breaking change(s) allowed by --allow-breaking.");
+            ExitCode::from(0)
+        }
+        _ => {
+            outln!("❌ {breaking} breaking change(s); pass --allow-breaking to accept them.");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn contract<'a>(doc: &'a JsonValue, phase: &str) -> Option<&'a JsonValue> {
+    doc.pointer(&pointer(&["implementation", "phase_contracts", phase]))
+}
+
+/// Removed, renamed and added phases, and the contract changes of the phases both specs have. A
+/// removed phase whose contract reappears unchanged under an added name counts as renamed.
+fn compare_phases(old: &JsonValue, new: &JsonValue, changes: &mut Vec<Change>) {
+    let before: Vec<String> = phases(old).into_iter().map(|(name, _)| name).collect();
+    let after: Vec<String> = phases(new).into_iter().map(|(name, _)| name).collect();
+    let mut added: Vec<&String> = after.iter().filter(|name| !before.contains(name)).collect();
+
+    for name in &before {
+        if after.contains(name) {
+            if let (Some(a), Some(b)) = (contract(old, name), contract(new, name)) {
+                compare_contracts(old, name, a, b, changes);
+            }
+            continue;
+        }
+        let renamed = contract(old, name).and_then(|a| {
+            added
+                .iter()
+                .position(|added| contract(new, added) == Some(a))
+        });
+        match renamed {
+            Some(index) => {
+                let to = added.remove(index);
+                changes.push(Change {
+                    breaking: true,
+                    message: format!("Phase '{name}' renamed to '{to}'"),
+                });
+            }
+            None => changes.push(Change {
+                breaking: true,
+                message: format!("Phase '{name}' removed"),
+            }),
+        }
+    }
+    for name in added {
+        changes.push(Change {
+            breaking: false,
+            message: format!("Phase '{name}' added"),
+        });
+    }
+}
+
+/// Ports of a contract (`inputs` or `outputs`) by name.
+fn ports<'a>(contract: &'a JsonValue, field: &str) -> Vec<(&'a str, &'a JsonValue)> {
+    contract
+        .get(field)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|port| Some((port.get("name")?.as_str()?, port)))
+        .collect()
+}
+
+fn optional(port: &JsonValue) -> bool {
+    port.get("optional").and_then(|o| o.as_bool()) == Some(true) || port.get("default").is_some()
+}
+
+fn compare_contracts(
+    old_doc: &JsonValue,
+    phase: &str,
+    old: &JsonValue,
+    new: &JsonValue,
+    changes: &mut Vec<Change>,
+) {
+    let schema = |port: &JsonValue| port.get("schema").cloned().unwrap_or_default();
+
+    let (old_outputs, new_outputs) = (ports(old, "outputs"), ports(new, "outputs"));
+    for (name, port) in &old_outputs {
+        match new_outputs.iter().find(|(n, _)| n == name) {
+            None => changes.push(Change {
+                breaking: true,
+                message: format!("Phase '{phase}' output '{name}' removed"),
+            }),
+            Some((_, new_port)) => {
+                // Consumers break when an output produces values they did not expect.
+                let mut widened = Vec::new();
+                restrictions(
+                    &schema(new_port),
+                    &schema(port),
+                    Direction::Widened,
+                    "",
+                    &mut widened,
+                );
+                for reason in widened {
+                    changes.push(Change {
+                        breaking: true,
+                        message: format!("Phase '{phase}' output '{name}' widened: {reason}"),
+                    });
+                }
+            }
+        }
+    }
+    for (name, _) in &new_outputs {
+        if !old_outputs.iter().any(|(n, _)| n == name) {
+            changes.push(Change {
+                breaking: false,
+                message: format!("Phase '{phase}' output '{name}' added"),
+            });
+        }
+    }
+
+    let (old_inputs, new_inputs) = (ports(old, "inputs"), ports(new, "inputs"));
+    for (name, port) in &new_inputs {
+        let Some((_, old_port)) = old_inputs.iter().find(|(n, _)| n == name) else {
+            changes.push(Change {
+                breaking: !optional(port),
+                message: if optional(port) {
+                    format!("Phase '{phase}' optional input '{name}' added")
+                } else {
+                    format!("Phase '{phase}' required input '{name}' added")
+                },
+            });
+            continue;
+        };
+        if optional(old_port) && !optional(port) {
+            changes.push(Change {
+                breaking: true,
+                message: format!("Phase '{phase}' input '{name}' is now required"),
+            });
+        }
+        let mut narrowed = Vec::new();
+        restrictions(
+            &schema(old_port),
+            &schema(port),
+            Direction::Narrowed,
+            "",
+            &mut narrowed,
+        );
+        for reason in narrowed {
+            changes.push(Change {
+                breaking: true,
+                message: format!("Phase '{phase}' input '{name}' narrowed: {reason}"),
+            });
+        }
+    }
+    for (name, _) in &old_inputs {
+        if !new_inputs.iter().any(|(n, _)| n == name) {
+            changes.push(Change {
+                breaking: false,
+                message: format!("Phase '{phase}' input '{name}' removed"),
+            });
+        }
+    }
+
+    let codes = |contract: &JsonValue| -> Vec<String> {
+        contract
+            .get("errors")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|error| Some(error.get("code")?.as_str()?.to_string()))
+            .collect()
+    };
+    let (old_codes, new_codes) = (codes(old), codes(new));
+    for code in &old_codes {
+        if new_codes.contains(code) {
+            continue;
+        }
+        let mut references = Vec::new();
+        find_references(old_doc, code, String::new(), &mut references);
+        changes.push(match references.first() {
+            Some(at) => Change {
+                breaking: true,
+                message: format!(
+                    "Phase '{phase}' error code '{code}' removed; referenced at {at}{}",
+                    match references.len() {
+                        1 => String::new(),
+                        n => format!(" and {} more place(s)", n - 1),
+                    }
+                ),
+            },
+            None => Change {
+                breaking: false,
+                message: format!(
+                    "Phase '{phase}' error code '{code}' removed; nothing refers to it"
+                ),
+            },
+        });
+    }
+    for code in &new_codes {
+        if !old_codes.contains(code) {
+            changes.push(Change {
+                breaking: false,
+                message: format!("Phase '{phase}' error code '{code}' added"),
+            });
+        }
+    }
+}
+
+/// Pointers of the strings in `value` that mention `code`, other than the `code` of an `errors`
+/// entry, which declares it.
+fn find_references(value: &JsonValue, code: &str, at: String, found: &mut Vec<String>) {
+    match value {
+        JsonValue::String(text) => {
+            let declaration = at.ends_with("/code") && at.contains("/errors/");
+            let mentions = text
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|word| word == code);
+            if mentions && !declaration {
+                found.push(at);
+            }
+        }
+        JsonValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                find_references(item, code, format!("{at}/{index}"), found);
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, item) in map {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                find_references(item, code, format!("{at}/{key}"), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn compare_algorithm_outputs(old: &JsonValue, new: &JsonValue, changes: &mut Vec<Change>) {
+    let names = |doc: &JsonValue| -> Vec<String> {
+        doc.pointer("/algorithm/outputs")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|output| Some(output.get("name")?.as_str()?.to_string()))
+            .collect()
+    };
+    let (before, after) = (names(old), names(new));
+    for name in &before {
+        if !after.contains(name) {
+            changes.push(Change {
+                breaking: true,
+                message: format!("Algorithm output '{name}' removed"),
+            });
+        }
+    }
+    for name in &after {
+        if !before.contains(name) {
+            changes.push(Change {
+                breaking: false,
+                message: format!("Algorithm output '{name}' added"),
+            });
+        }
+    }
+}
+
+/// JSON types a schema allows; `None` when it does not restrict them.
+fn types(schema: &JsonValue) -> Option<Vec<&str>> {
+    match schema.get("type")? {
+        JsonValue::String(name) => Some(vec![name.as_str()]),
+        JsonValue::Array(names) => Some(names.iter().filter_map(|n| n.as_str()).collect()),
+        _ => None,
+    }
+}
+
+/// Which of the two compared schemas is the new one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// The new schema is the narrower one (inputs).
+    Narrowed,
+    /// The new schema is the wider one (outputs).
+    Widened,
+}
+
+/// Reasons why `narrow` accepts fewer values than `wide`, for the keywords that commonly change
+/// between contract versions, worded from the old schema to the new one; `at` names the nested
+/// property or items being compared.
+fn restrictions(
+    wide: &JsonValue,
+    narrow: &JsonValue,
+    direction: Direction,
+    at: &str,
+    reasons: &mut Vec<String>,
+) {
+    let widened = direction == Direction::Widened;
+    let mut reason = |text: String| {
+        reasons.push(if at.is_empty() {
+            text
+        } else {
+            format!("{at}: {text}")
+        })
+    };
+    // `wide → narrow` for narrowed schemas, `narrow → wide` for widened ones.
+    let arrow = |wide: String, narrow: String| {
+        if widened {
+            format!("{narrow} → {wide}")
+        } else {
+            format!("{wide} → {narrow}")
+        }
+    };
+    let phrase = |narrowed: String, widening: String| if widened { widening } else { narrowed };
+
+    match (types(wide), types(narrow)) {
+        (None, Some(narrow_types)) => reason(phrase(
+            format!("type restricted to {}", narrow_types.join(", ")),
+            format!("type no longer restricted to {}", narrow_types.join(", ")),
+        )),
+        (Some(wide_types), Some(narrow_types)) => {
+            let dropped = wide_types.iter().any(|t| {
+                // A number type still accepts every integer.
+                let kept = narrow_types.contains(t)
+                    || (*t == "integer" && narrow_types.contains(&"number"));
+                !kept
+            });
+            if dropped {
+                reason(format!(
+                    "type {}",
+                    arrow(wide_types.join(", "), narrow_types.join(", "))
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    let values = |schema: &JsonValue| schema.get("enum").and_then(|e| e.as_array()).cloned();
+    match (values(wide), values(narrow)) {
+        (None, Some(_)) => reason(phrase("enum added".to_string(), "enum removed".to_string())),
+        (Some(wide_values), Some(narrow_values)) => {
+            for value in wide_values.iter().filter(|v| !narrow_values.contains(v)) {
+                reason(phrase(
+                    format!("enum value {value} removed"),
+                    format!("enum value {value} added"),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    for (keyword, lower_bound) in [
+        ("minimum", true),
+        ("exclusiveMinimum", true),
+        ("minLength", true),
+        ("minItems", true),
+        ("maximum", false),
+        ("exclusiveMaximum", false),
+        ("maxLength", false),
+        ("maxItems", false),
+    ] {
+        let bound = |schema: &JsonValue| schema.get(keyword).and_then(|v| v.as_f64());
+        let tighter = match (bound(wide), bound(narrow)) {
+            (None, Some(_)) => true,
+            (Some(a), Some(b)) => (lower_bound && b > a) || (!lower_bound && b < a),
+            _ => false,
+        };
+        if tighter {
+            let wide_bound = wide
+                .get(keyword)
+                .map_or("none".to_string(), |v| v.to_string());
+            reason(format!(
+                "{keyword} {}",
+                arrow(wide_bound, narrow[keyword].to_string())
+            ));
+        }
+    }
+
+    if let Some(pattern) = narrow.get("pattern") {
+        match wide.get("pattern") {
+            None => reason(phrase(
+                format!("pattern {pattern} added"),
+                format!("pattern {pattern} removed"),
+            )),
+            Some(other) if other != pattern => reason(format!(
+                "pattern {}",
+                arrow(other.to_string(), pattern.to_string())
+            )),
+            Some(_) => {}
+        }
+    }
+
+    let required = |schema: &JsonValue| -> Vec<String> {
+        schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|n| n.as_str().map(str::to_string))
+            .collect()
+    };
+    let wide_required = required(wide);
+    for name in required(narrow) {
+        if !wide_required.contains(&name) {
+            reason(phrase(
+                format!("property '{name}' now required"),
+                format!("property '{name}' no longer required"),
+            ));
+        }
+    }
+
+    if narrow.get("additionalProperties") == Some(&JsonValue::Bool(false))
+        && wide.get("additionalProperties") != Some(&JsonValue::Bool(false))
+    {
+        reason(phrase(
+            "additional properties no longer allowed".to_string(),
+            "additional properties now allowed".to_string(),
+        ));
+    }
+
+    let nested = |name: &str| {
+        if at.is_empty() {
+            name.to_string()
+        } else {
+            format!("{at}.{name}")
+        }
+    };
+    if let (Some(wide_props), Some(narrow_props)) = (
+        wide.get("properties").and_then(|p| p.as_object()),
+        narrow.get("properties").and_then(|p| p.as_object()),
+    ) {
+        for (name, wide_schema) in wide_props {
+            if let Some(narrow_schema) = narrow_props.get(name) {
+                restrictions(
+                    wide_schema,
+                    narrow_schema,
+                    direction,
+                    &nested(name),
+                    reasons,
+                );
+            }
+        }
+    }
+    if let (Some(wide_items), Some(narrow_items)) = (wide.get("items"), narrow.get("items")) {
+        restrictions(wide_items, narrow_items, direction, &nested("[]"), reasons);
+    }
+}
@@ -0,0 +1,155 @@
+//! `schema completions`: a completion model of a schema for editor plugins.
+//!
+//! For every field path (named as in `report schema-usage`: `implementation.phase_contracts.*`,
+//! `algorithm.outputs[]`) the model lists the keys an object there may have, which of them are
+//! required, whether other keys are allowed, the JSON types and the allowed values (`enum`/`const`).
+
+use crate::{
+    schemas::SchemaRequest,
+    usage::{item_schemas, join, map_value_schemas, required, SchemaWalker},
+    Args,
+};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    process::ExitCode,
+};
+
+/// What may appear at one field path.
+#[derive(Serialize, Default, Debug)]
+pub struct PathModel {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub types: BTreeSet<String>,
+    /// Declared property names.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub keys: BTreeSet<String>,
+    /// Keys that must always be present (`required` outside of `anyOf`/`oneOf`/`then`/`else`).
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub required: BTreeSet<String>,
+    /// Other keys are allowed too; their values are described under `<path>.*`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub other_keys: bool,
+    /// Allowed values, from `enum` and `const`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<JsonValue>,
+}
+
+/// Builds the completion model of `schema`, keyed by field path (`""` is the document root).
+pub fn completion_model(schema: &JsonValue) -> BTreeMap<String, PathModel> {
+    let mut model = BTreeMap::new();
+    collect(&mut SchemaWalker::new(schema), schema, "", true, &mut model);
+    model
+}
+
+/// Adds what `node` allows at `path`. `certain` is false below `anyOf`/`oneOf`/`then`/`else`,
+/// whose `required` lists only apply in some cases.
+fn collect<'a>(
+    walker: &mut SchemaWalker<'a>,
+    node: &'a JsonValue,
+    path: &str,
+    certain: bool,
+    model: &mut BTreeMap<String, PathModel>,
+) {
+    if let Some((reference, target)) = walker.reference(node) {
+        walker.refs.push(reference);
+        collect(walker, target, path, certain, model);
+        walker.refs.pop();
+    }
+    for (key, branch_certain) in [
+        ("allOf", certain),
+        ("anyOf", false),
+        ("oneOf", false),
+        ("then", false),
+        ("else", false),
+    ] {
+        let branches = match node.get(key) {
+            Some(JsonValue::Array(items)) => items.iter().collect(),
+            Some(sub @ JsonValue::Object(_)) => vec![sub],
+            _ => Vec::new(),
+        };
+        for branch in branches {
+            collect(walker, branch, path, branch_certain, model);
+        }
+    }
+
+    let entry = model.entry(path.to_string()).or_default();
+    let text = |key: &str| node.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    entry.title = entry.title.take().or_else(|| text("title"));
+    entry.description = entry.description.take().or_else(|| text("description"));
+    match node.get("type") {
+        Some(JsonValue::String(kind)) => {
+            entry.types.insert(kind.clone());
+        }
+        Some(JsonValue::Array(kinds)) => {
+            entry
+                .types
+                .extend(kinds.iter().filter_map(|k| k.as_str()).map(str::to_string));
+        }
+        _ => {}
+    }
+    let values = node
+        .get("enum")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .chain(node.get("const"));
+    for value in values {
+        if !entry.values.contains(value) {
+            entry.values.push(value.clone());
+        }
+    }
+    if certain {
+        entry
+            .required
+            .extend(required(node).into_iter().map(str::to_string));
+    }
+    let properties = node.get("properties").and_then(|v| v.as_object());
+    entry.keys.extend(
+        properties
+            .into_iter()
+            .flatten()
+            .map(|(name, _)| name.clone()),
+    );
+    let map_values = map_value_schemas(node);
+    entry.other_keys |=
+        !map_values.is_empty() || node.get("additionalProperties") == Some(&JsonValue::Bool(true));
+
+    for (name, sub) in properties.into_iter().flatten() {
+        collect(walker, sub, &join(path, name), true, model);
+    }
+    for sub in map_values {
+        collect(walker, sub, &join(path, "*"), true, model);
+    }
+    for sub in item_schemas(node) {
+        collect(walker, sub, &format!("{path}[]"), true, model);
+    }
+}
+
+/// Prints the completion model of the schema for `spec_version` (or of `--schema`) as JSON.
+pub fn completions(args: &Args, spec_version: Option<&str>) -> ExitCode {
+    let request = SchemaRequest {
+        input: Path::new("."),
+        spec_version,
+    };
+    let explicit = args.schema.as_ref().map(|p| p.to_string_lossy());
+    let resolved = match args.schemas.resolve(explicit.as_deref(), &request) {
+        Ok(resolved) => resolved,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let model = serde_json::json!({
+        "schema": resolved.origin,
+        "spec_version": spec_version,
+        "paths": completion_model(&resolved.schema),
+    });
+    outln!("{}", serde_json::to_string_pretty(&model).unwrap());
+    ExitCode::from(0)
+}
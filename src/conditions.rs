@@ -0,0 +1,212 @@
+//! Conditions of `algorithm.graph` — the `condition` of nodes and edges and the `until` of loop
+//! nodes — read as expressions over phase outputs, `implementation.parameters` and instance paths:
+//!
+//! - literals: numbers, `'strings'` or `"strings"`, `true`, `false`, `null`
+//! - names: `output.field`, `phase.output.field`, `parameter` or an instance path `$.field.field`
+//! - `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&` (`and`), `||` (`or`), `!` (`not`) and parentheses
+//!
+//! The tokens are those of the [assertions](crate::assertions). The schema allows conditions in
+//! natural language too; a condition of several words without an operator, a quote, a dotted name
+//! or a number is taken as such and not parsed.
+
+use crate::assertions::{describe, tokenize, Token};
+
+/// Whether `text` is a sentence rather than an expression: several words, no operator or quote,
+/// and none of them a dotted name or a number, which a mistyped expression (`severity 1`) has.
+pub fn is_prose(text: &str) -> bool {
+    text.split_whitespace().nth(1).is_some()
+        && !text.contains(['=', '<', '>', '!', '&', '|', '(', ')', '\'', '"', '$'])
+        && !text
+            .split_whitespace()
+            .any(|word| word.parse::<f64>().is_ok() || is_dotted_name(word))
+}
+
+/// `output.field`, as opposed to a word at the end of a sentence or an abbreviation (`e.g.`).
+fn is_dotted_name(word: &str) -> bool {
+    word.contains('.')
+        && word.split('.').all(|segment| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+        })
+}
+
+/// The names the condition `text` reads, each as its dotted segments (`$` first for an instance
+/// path), or why it is not an expression.
+pub fn names(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        next: 0,
+        names: Vec::new(),
+    };
+    parser.or()?;
+    match parser.tokens.get(parser.next) {
+        None => Ok(parser.names),
+        Some(token) => Err(format!("unexpected {}", describe(token))),
+    }
+}
+
+/// Recursive descent over the tokens of a condition, collecting the names it reads.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+    names: Vec<Vec<String>>,
+}
+
+impl Parser {
+    fn peek_op(&self, op: &str) -> bool {
+        matches!(self.tokens.get(self.next), Some(Token::Op(next)) if *next == op)
+    }
+
+    /// Consumes the operator `op` or its spelling as a `word`.
+    fn eat(&mut self, op: &str, word: &str) -> bool {
+        let found = match self.tokens.get(self.next) {
+            Some(Token::Op(next)) => *next == op,
+            Some(Token::Ident(next)) => next == word,
+            _ => false,
+        };
+        self.next += usize::from(found);
+        found
+    }
+
+    fn or(&mut self) -> Result<(), String> {
+        self.and()?;
+        while self.eat("||", "or") {
+            self.and()?;
+        }
+        Ok(())
+    }
+
+    fn and(&mut self) -> Result<(), String> {
+        self.not()?;
+        while self.eat("&&", "and") {
+            self.not()?;
+        }
+        Ok(())
+    }
+
+    fn not(&mut self) -> Result<(), String> {
+        if self.eat("!", "not") {
+            return self.not();
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<(), String> {
+        self.primary()?;
+        if let Some(Token::Op("==" | "!=" | "<" | "<=" | ">" | ">=")) = self.tokens.get(self.next) {
+            self.next += 1;
+            self.primary()?;
+        }
+        Ok(())
+    }
+
+    fn primary(&mut self) -> Result<(), String> {
+        let Some(token) = self.tokens.get(self.next).cloned() else {
+            return Err("unexpected end of the condition".to_string());
+        };
+        self.next += 1;
+        match token {
+            Token::Number(_) | Token::Str(_) => Ok(()),
+            Token::Op("-") => match self.tokens.get(self.next) {
+                Some(Token::Number(_)) => {
+                    self.next += 1;
+                    Ok(())
+                }
+                _ => Err("'-' must be followed by a number".to_string()),
+            },
+            Token::Op("(") => {
+                self.or()?;
+                if !self.peek_op(")") {
+                    return Err(match self.tokens.get(self.next) {
+                        Some(token) => format!("expected ')', found {}", describe(token)),
+                        None => "expected ')' at the end".to_string(),
+                    });
+                }
+                self.next += 1;
+                Ok(())
+            }
+            Token::Op("$") => {
+                if !self.peek_op(".") {
+                    return Err("expected a member name after '$'".to_string());
+                }
+                self.name("$".to_string())
+            }
+            Token::Ident(word) => match word.as_str() {
+                "true" | "false" | "null" => Ok(()),
+                "and" | "or" | "not" => Err(format!("unexpected '{word}'")),
+                _ => self.name(word),
+            },
+            other => Err(format!("unexpected {}", describe(&other))),
+        }
+    }
+
+    /// The rest of a dotted name starting with `first`.
+    fn name(&mut self, first: String) -> Result<(), String> {
+        let mut segments = vec![first];
+        while self.peek_op(".") {
+            self.next += 1;
+            match self.tokens.get(self.next) {
+                Some(Token::Ident(segment)) => segments.push(segment.clone()),
+                _ => return Err("expected a member name after '.'".to_string()),
+            }
+            self.next += 1;
+        }
+        self.names.push(segments);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The names `text` reads, dotted again.
+    fn dotted(text: &str) -> Vec<String> {
+        names(text).unwrap().iter().map(|n| n.join(".")).collect()
+    }
+
+    #[test]
+    fn collects_the_names_read() {
+        assert_eq!(dotted("severity >= 0.8"), ["severity"]);
+        assert_eq!(
+            dotted("analyze.severity > threshold && !escalated"),
+            ["analyze.severity", "threshold", "escalated"]
+        );
+        assert_eq!(
+            dotted("not ($.customer.tier == 'vip' or retries < -1) and done == true"),
+            ["$.customer.tier", "retries", "done"]
+        );
+        assert!(dotted("1 == 1 and 'a' != \"b\" or null").is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        for text in [
+            "",
+            "severity >=",
+            "(severity > 1",
+            "severity > 1)",
+            "severity 1",
+            "analyze.",
+            "$ == 1",
+            "- x",
+            "and done",
+            "a == b == c",
+        ] {
+            assert!(names(text).is_err(), "'{text}' parsed");
+        }
+    }
+
+    #[test]
+    fn tells_prose_from_expressions() {
+        assert!(is_prose("the customer is angry"));
+        assert!(!is_prose("approved"));
+        assert!(!is_prose("severity > 3"));
+        assert!(!is_prose("status is 'open'"));
+        assert!(!is_prose("approved and not escalated or (retry)"));
+        assert!(is_prose("the customer is angry."));
+        assert!(is_prose("tickets, e.g. refunds"));
+        assert!(!is_prose("severity 1"));
+        assert!(!is_prose("analyze.severity 0.8"));
+        assert!(!is_prose("analyze.severity high"));
+    }
+}
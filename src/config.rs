@@ -0,0 +1,430 @@
+//! Project configuration loaded from `.program-verify.yaml`.
+
+use crate::{diagnostics::Severity, schemas::SchemaDraft};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// File name looked up in the input's directory and all of its ancestors.
+pub const CONFIG_FILE_NAME: &str = ".program-verify.yaml";
+
+/// Settings read from the configuration file. Relative paths are resolved against the directory
+/// containing the file; command-line flags always take precedence over these values.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Default for `--schema`.
+    pub schema: Option<PathBuf>,
+    /// Default for `--versions-map`.
+    pub versions_map: Option<PathBuf>,
+    /// Default for `--fail-on`.
+    pub fail_on: Option<Severity>,
+    /// Default for `--draft`.
+    pub draft: Option<SchemaDraft>,
+    /// Custom `format`s to check (`duration`, `identifier`, `semver`, `cron`); all when omitted.
+    pub custom_formats: Option<Vec<String>>,
+    /// Directories searched for contract libraries after any `--library-path`.
+    #[serde(default)]
+    pub library_paths: Vec<PathBuf>,
+    /// Glob patterns (`*`, `**`, `?`) of spec files to skip, relative to the config directory.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// JSON Pointer globs of the values `scrub` replaces, in addition to its `--path` patterns.
+    #[serde(default)]
+    pub scrub_paths: Vec<String>,
+    /// How rules compare phase, port and algorithm names.
+    pub identifiers: Option<IdentifierCase>,
+    /// Line endings `fmt` writes; LF when omitted.
+    pub line_endings: Option<LineEnding>,
+    /// Default for the `--registry` of `publish` and `verify-published`: a URL or a directory.
+    pub registry: Option<PathBuf>,
+    /// Default for `--owners`: the file assigning spec files to owners for `--split-report-by owner`.
+    pub owners: Option<PathBuf>,
+    /// Directory of Rhai rule scripts; `rules/` next to the configuration file when omitted.
+    pub rule_scripts: Option<PathBuf>,
+    /// Per-rule settings keyed by rule name.
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleConfig>,
+    /// Declarative rules, checked as the `assertions` rule.
+    #[serde(default)]
+    pub assertions: Vec<AssertionConfig>,
+    /// Where the configuration was loaded from (`None` for the built-in defaults).
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+/// Settings for a single rule.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// Set to `false` to skip the rule entirely.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Reports every finding of the rule with this severity instead of the built-in one.
+    pub severity: Option<Severity>,
+    /// Reports every finding of the rule as a warning until this date or validator version, and
+    /// as an error from then on.
+    pub becomes_error: Option<Escalation>,
+    /// Message templates by rule ID, replacing the built-in message of those findings. `{message}`
+    /// stands for the built-in message, `{phase}`, `{port}`, `{code}` and `{pointer}` for details of
+    /// the finding.
+    #[serde(default)]
+    pub messages: BTreeMap<String, String>,
+    /// How `title-vs-algorithm` derives the expected `algorithm.name`; other rules reject it.
+    #[serde(rename = "match")]
+    pub matching: Option<IdentityMatch>,
+    /// Regular expressions `naming` checks identifiers against; other rules reject them.
+    pub patterns: Option<NamingPatterns>,
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: None,
+            becomes_error: None,
+            messages: BTreeMap::new(),
+            matching: None,
+            patterns: None,
+        }
+    }
+}
+
+/// A declarative rule: every node `path` selects must satisfy `assert`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssertionConfig {
+    /// Rule ID of the findings, `CFG` followed by digits; numbered by position when omitted.
+    pub code: Option<String>,
+    /// JSONPath selector of the nodes to check, e.g. `$.algorithm.phases`.
+    pub path: String,
+    /// Expression over the selected node, e.g. `length <= 20`.
+    #[serde(rename = "assert")]
+    pub assertion: String,
+    /// Message of the findings; `{value}`, `{length}` and `{pointer}` are filled in.
+    pub message: Option<String>,
+    /// Severity of the findings; error when omitted.
+    pub severity: Option<Severity>,
+}
+
+/// Patterns of the `naming` rule by kind of identifier; kinds without one are not checked.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamingPatterns {
+    /// Phase names, in `algorithm.phases`, graph nodes and `phase_contracts`.
+    pub phase: Option<String>,
+    /// Input and output names of phase contracts.
+    pub port: Option<String>,
+    /// Error codes of phase contracts.
+    pub error_code: Option<String>,
+}
+
+/// How rules compare identifiers such as phase and port names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdentifierCase {
+    /// Exactly as written.
+    #[default]
+    Sensitive,
+    /// Ignoring the case of ASCII letters only, the same in every locale.
+    Insensitive,
+    /// Ignoring case after Unicode case folding (`Straße` ~ `STRASSE`).
+    Casefold,
+}
+
+/// Line endings of formatted spec files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+        }
+    }
+}
+
+/// How the `title-vs-algorithm` rule matches `algorithm.name` against the spec's title.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "serde_yaml::Value")]
+pub enum IdentityMatch {
+    /// The text of `meta.title` before the first opening parenthesis.
+    #[default]
+    Prefix,
+    /// The whole `meta.title`.
+    Exact,
+    /// `meta.title`, compared after both are slugified (`Customer Support` ~ `customer_support`).
+    Slug,
+    /// The first capture group (or the group named `name`) of this pattern in `meta.title`.
+    Regex(String),
+    /// A declared `meta.slug`, compared with the slugified `algorithm.name`.
+    MetaSlug,
+}
+
+/// `prefix`, `exact`, `slug`, `meta-slug` or `{regex: PATTERN}`.
+impl TryFrom<serde_yaml::Value> for IdentityMatch {
+    type Error = String;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, String> {
+        let expected = "expected prefix, exact, slug, meta-slug or {regex: PATTERN}";
+        match &value {
+            serde_yaml::Value::String(name) => match name.as_str() {
+                "prefix" => Ok(IdentityMatch::Prefix),
+                "exact" => Ok(IdentityMatch::Exact),
+                "slug" => Ok(IdentityMatch::Slug),
+                "meta-slug" => Ok(IdentityMatch::MetaSlug),
+                other => Err(format!("unknown match '{other}', {expected}")),
+            },
+            serde_yaml::Value::Mapping(map) if map.len() == 1 => match map.get("regex") {
+                Some(serde_yaml::Value::String(pattern)) => {
+                    Ok(IdentityMatch::Regex(pattern.clone()))
+                }
+                _ => Err(format!("invalid match, {expected}")),
+            },
+            _ => Err(format!("invalid match, {expected}")),
+        }
+    }
+}
+
+/// When the findings of a newly rolled out rule turn from warnings into errors.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "serde_yaml::Value")]
+pub enum Escalation {
+    /// On this day (`YYYY-MM-DD`, UTC), given as days since the Unix epoch.
+    On(String, i64),
+    /// From this version of the validator on, given as its numeric components.
+    In(String, Vec<u64>),
+}
+
+impl Escalation {
+    /// The cutoff as written in the configuration file.
+    pub fn cutoff(&self) -> &str {
+        match self {
+            Escalation::On(text, _) | Escalation::In(text, _) => text,
+        }
+    }
+
+    /// Whether the cutoff has passed: the day has begun, or this validator is that version or later.
+    pub fn reached(&self) -> bool {
+        match self {
+            Escalation::On(_, day) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                now / 86_400 >= *day as u64
+            }
+            Escalation::In(_, version) => {
+                parse_version(env!("CARGO_PKG_VERSION")).is_some_and(|current| current >= *version)
+            }
+        }
+    }
+
+    /// The severity of the rule's findings today.
+    pub fn severity(&self) -> Severity {
+        if self.reached() {
+            Severity::Error
+        } else {
+            Severity::Warning
+        }
+    }
+}
+
+/// A `YYYY-MM-DD` date or a validator version such as `0.2.0` (a leading `v` is allowed).
+impl TryFrom<serde_yaml::Value> for Escalation {
+    type Error = String;
+
+    fn try_from(value: serde_yaml::Value) -> Result<Self, String> {
+        let text = match value {
+            serde_yaml::Value::String(text) => text,
+            serde_yaml::Value::Number(number) => number.to_string(),
+            _ => {
+                return Err("invalid becomes_error, expected a YYYY-MM-DD date or a version".into())
+            }
+        };
+        if let Some(day) = parse_date(&text) {
+            return Ok(Escalation::On(text, day));
+        }
+        match parse_version(&text) {
+            Some(version) => Ok(Escalation::In(text, version)),
+            None => Err(format!(
+                "invalid becomes_error '{text}', expected a YYYY-MM-DD date or a version such as 0.2.0"
+            )),
+        }
+    }
+}
+
+/// Days since the Unix epoch of a `YYYY-MM-DD` date (Howard Hinnant's days-from-civil).
+fn parse_date(text: &str) -> Option<i64> {
+    let mut parts = text.splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i64, i64, i64) =
+        (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+/// The numeric components of a version such as `v0.2` or `0.2.0`, padded to three.
+fn parse_version(text: &str) -> Option<Vec<u64>> {
+    let text = text.strip_prefix('v').unwrap_or(text);
+    let mut parts = text
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    if parts.len() > 3 {
+        return None;
+    }
+    parts.resize(3, 0);
+    Some(parts)
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Config {
+    /// Loads `explicit` when given, otherwise the nearest `.program-verify.yaml` found by walking
+    /// up from `start` (a spec path or directory). Returns the defaults when no file exists.
+    pub fn load(explicit: Option<&Path>, start: &Path) -> Result<Config, String> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => match discover(start) {
+                Some(path) => path,
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Error: failed to read config {}: {e}", path.display()))?;
+        let mut config: Config = serde_yaml::from_str(&text)
+            .map_err(|e| format!("Error: invalid config {}: {e}", path.display()))?;
+        crate::provenance::record_config(&path, &text);
+
+        let base = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        // A schema URL is kept as written.
+        config.schema = config.schema.map(|p| match p.to_str() {
+            Some(uri) if crate::schemas::is_url(uri) => p,
+            _ => base.join(p),
+        });
+        config.versions_map = config.versions_map.map(|p| base.join(p));
+        config.registry = config.registry.map(|p| match p.to_str() {
+            Some(uri) if crate::schemas::is_url(uri) => p,
+            _ => base.join(p),
+        });
+        config.owners = config.owners.map(|p| base.join(p));
+        config.library_paths = config.library_paths.iter().map(|p| base.join(p)).collect();
+        config.rule_scripts = match config.rule_scripts {
+            Some(dir) => Some(base.join(dir)),
+            None => Some(base.join("rules")).filter(|dir| dir.is_dir()),
+        };
+        config.path = Some(path);
+        Ok(config)
+    }
+
+    /// Settings for rule `name`, if the configuration mentions it.
+    /// Whether `file` matches one of the `exclude` patterns.
+    pub fn is_excluded(&self, file: &Path) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| self.excluded_by(pattern, file))
+    }
+
+    /// Whether `file` matches the exclude `pattern`, resolved against the config directory.
+    pub fn excluded_by(&self, pattern: &str, file: &Path) -> bool {
+        let base = self
+            .path
+            .as_ref()
+            .and_then(|p| p.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let absolute = |p: &Path| {
+            if p.is_absolute() {
+                p.to_path_buf()
+            } else {
+                env::current_dir().unwrap_or_default().join(p)
+            }
+        };
+        let file = absolute(file);
+        let base = absolute(&base);
+        let relative = file.strip_prefix(&base).unwrap_or(&file);
+        glob_match(pattern, &relative.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+/// Walks up from `start` looking for the configuration file.
+fn discover(start: &Path) -> Option<PathBuf> {
+    let dir = if start.as_os_str() == "-" || start.is_dir() {
+        start
+    } else {
+        start.parent().unwrap_or(Path::new(""))
+    };
+    // stdin (`-`) and bare file names start from the current directory.
+    let start = if dir.as_os_str().is_empty() || dir.as_os_str() == "-" {
+        env::current_dir().ok()?
+    } else {
+        dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+    };
+
+    let mut dir = Some(start.as_path());
+    while let Some(current) = dir {
+        let candidate = current.join(CONFIG_FILE_NAME);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        dir = current.parent();
+    }
+    None
+}
+
+/// Minimal glob matcher: `*` matches within a path segment, `**` across segments, `?` one character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[char], t: &[char]) -> bool {
+        match p.first() {
+            None => t.is_empty(),
+            Some('*') if p.get(1) == Some(&'*') => {
+                if p.get(2) == Some(&'/') {
+                    // `**/` matches zero or more whole directories.
+                    (0..=t.len())
+                        .filter(|&i| i == 0 || t[i - 1] == '/')
+                        .any(|i| matches(&p[3..], &t[i..]))
+                } else {
+                    (0..=t.len()).any(|i| matches(&p[2..], &t[i..]))
+                }
+            }
+            Some('*') => (0..=t.len())
+                .take_while(|&i| i == 0 || t[i - 1] != '/')
+                .any(|i| matches(&p[1..], &t[i..])),
+            Some('?') => !t.is_empty() && t[0] != '/' && matches(&p[1..], &t[1..]),
+            Some(c) => t.first() == Some(c) && matches(&p[1..], &t[1..]),
+        }
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    matches(&p, &t)
+}
@@ -0,0 +1,642 @@
+//! The resolved view of a spec that rules work on: algorithm phases with where they are declared,
+//! phase contracts indexed by phase with their ports, the dataflow between phases, and the nodes and
+//! edges of `algorithm.graph`. Built in a single pass by [`SpecContext::new`]; the
+//! [dependency graph](SpecContext::dependencies) combining the graph and the dataflow is built on
+//! demand.
+//!
+//! Sections of the wrong type (say, `phase_contracts` written as a list) are left out of the view
+//! and recorded in [`SpecContext::malformed`], so that every rule works on the rest of the spec and
+//! the problem is reported once.
+//!
+//! Lookups compare phase and port names under the configured
+//! [`IdentifierCase`](crate::config::IdentifierCase).
+
+use crate::{
+    diagnostics::pointer,
+    rules::{did_you_mean, ident, parse_semver_major},
+};
+use serde_json::Value as JsonValue;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+/// Where an algorithm phase is declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhaseSource<'a> {
+    /// Entry `index` of `algorithm.phases`.
+    Listed(usize),
+    /// The `algorithm.graph` node with this id.
+    Node(&'a str),
+}
+
+/// An algorithm phase, named as first written.
+pub struct Phase<'a> {
+    pub name: &'a str,
+    /// Every declaration of the phase, in document order.
+    pub sources: Vec<PhaseSource<'a>>,
+}
+
+impl Phase<'_> {
+    /// Whether the phase is listed in `algorithm.phases`.
+    pub fn listed(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|source| matches!(source, PhaseSource::Listed(_)))
+    }
+}
+
+/// A named input or output of a phase contract.
+pub struct Port<'a> {
+    pub name: &'a str,
+    /// Position in the `inputs` or `outputs` list.
+    pub index: usize,
+    pub value: &'a JsonValue,
+}
+
+/// An `implementation.phase_contracts` entry.
+pub struct Contract<'a> {
+    pub phase: &'a str,
+    pub value: &'a JsonValue,
+    pub inputs: Vec<Port<'a>>,
+    pub outputs: Vec<Port<'a>>,
+    /// False when the contract or its `outputs` has the wrong type, so that its outputs are not
+    /// known.
+    pub outputs_known: bool,
+}
+
+impl<'a> Contract<'a> {
+    pub fn input(&self, name: &str) -> Option<&Port<'a>> {
+        find_port(&self.inputs, name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<&Port<'a>> {
+        find_port(&self.outputs, name)
+    }
+}
+
+/// A phase input whose `source` reads another phase's output (`kind: phase_output`).
+pub struct Dataflow<'a> {
+    pub consumer: &'a str,
+    pub input: &'a str,
+    pub producer: &'a str,
+    pub port: Option<&'a str>,
+}
+
+/// A node of `algorithm.graph`.
+pub struct GraphNode<'a> {
+    pub id: &'a str,
+    pub kind: Option<&'a str>,
+    /// The phase the node runs, for nodes of type `phase`.
+    pub phase: Option<&'a str>,
+    /// Names of the `branches` of an `if` node.
+    pub branches: Vec<&'a str>,
+    pub condition: Option<&'a str>,
+    /// Termination condition of a `loop` node.
+    pub until: Option<&'a str>,
+}
+
+/// An edge of `algorithm.graph`.
+pub struct GraphEdge<'a> {
+    pub from: Option<&'a str>,
+    pub to: Option<&'a str>,
+    pub kind: Option<&'a str>,
+    /// The branch of the decision node the edge leaves by, or the condition under which it is
+    /// taken.
+    pub condition: Option<&'a str>,
+}
+
+/// A vertex of the [dependency graph](SpecContext::dependencies).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vertex<'a> {
+    Phase(&'a str),
+    /// A graph node that runs no phase (`if`, `parallel`, `end`), with its type.
+    Node(&'a str, Option<&'a str>),
+}
+
+/// Why one vertex of the dependency graph runs before another.
+#[derive(Clone, Copy, Debug)]
+pub enum Dependency {
+    /// Entry `n` of `algorithm.graph.edges`.
+    Edge(usize),
+    /// Entry `n` of [`SpecContext::dataflow`]: the consumer reads an output of the producer.
+    Dataflow(usize),
+}
+
+/// What runs before what: vertices in document order and, for each, the vertices that must run
+/// after it.
+#[derive(Default)]
+pub struct DependencyGraph<'a> {
+    pub vertices: Vec<Vertex<'a>>,
+    pub successors: Vec<Vec<(usize, Dependency)>>,
+    index: HashMap<String, usize>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    /// The strongly connected components (Tarjan), each in ascending vertex order, ordered by their
+    /// first vertex.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let successors = &self.successors[..];
+        struct Search<'s> {
+            successors: &'s [Vec<(usize, Dependency)>],
+            order: Vec<Option<usize>>,
+            low: Vec<usize>,
+            visited: usize,
+            stack: Vec<usize>,
+            on_stack: Vec<bool>,
+            components: Vec<Vec<usize>>,
+        }
+
+        impl Search<'_> {
+            fn visit(&mut self, vertex: usize) {
+                self.order[vertex] = Some(self.visited);
+                self.low[vertex] = self.visited;
+                self.visited += 1;
+                self.stack.push(vertex);
+                self.on_stack[vertex] = true;
+                let successors = self.successors;
+                for &(next, _) in &successors[vertex] {
+                    match self.order[next] {
+                        None => {
+                            self.visit(next);
+                            self.low[vertex] = self.low[vertex].min(self.low[next]);
+                        }
+                        Some(order) if self.on_stack[next] => {
+                            self.low[vertex] = self.low[vertex].min(order);
+                        }
+                        Some(_) => {}
+                    }
+                }
+                if Some(self.low[vertex]) == self.order[vertex] {
+                    let mut component = Vec::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack[member] = false;
+                        component.push(member);
+                        if member == vertex {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    self.components.push(component);
+                }
+            }
+        }
+
+        let count = successors.len();
+        let mut search = Search {
+            successors,
+            order: vec![None; count],
+            low: vec![0; count],
+            visited: 0,
+            stack: Vec::new(),
+            on_stack: vec![false; count],
+            components: Vec::new(),
+        };
+        for vertex in 0..count {
+            if search.order[vertex].is_none() {
+                search.visit(vertex);
+            }
+        }
+        search
+            .components
+            .sort_unstable_by_key(|component| component[0]);
+        search.components
+    }
+
+    /// The shortest cycle from the first vertex of `component` back to it, as `(from, to, why)`
+    /// steps, or `None` when the component is a single vertex that does not depend on itself.
+    pub fn shortest_cycle(&self, component: &[usize]) -> Option<Vec<(usize, usize, Dependency)>> {
+        let successors = &self.successors;
+        let start = component[0];
+        let mut previous: HashMap<usize, (usize, Dependency)> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(vertex) = queue.pop_front() {
+            for &(next, dependency) in &successors[vertex] {
+                if next == start {
+                    let mut cycle = vec![(vertex, start, dependency)];
+                    let mut at = vertex;
+                    while at != start {
+                        let (before, dependency) = previous[&at];
+                        cycle.push((before, at, dependency));
+                        at = before;
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                if component.binary_search(&next).is_ok() && !previous.contains_key(&next) {
+                    previous.insert(next, (vertex, dependency));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// The vertices in an order where each comes after every vertex it depends on, taking the
+    /// available vertex with the smallest `rank` first; `None` when the graph has a cycle.
+    pub fn topological_order<R: Ord>(&self, rank: impl Fn(usize) -> R) -> Option<Vec<usize>> {
+        let mut waiting = vec![0; self.vertices.len()];
+        for &(next, _) in self.successors.iter().flatten() {
+            waiting[next] += 1;
+        }
+        let mut ready: BinaryHeap<Reverse<(R, usize)>> = (0..self.vertices.len())
+            .filter(|&vertex| waiting[vertex] == 0)
+            .map(|vertex| Reverse((rank(vertex), vertex)))
+            .collect();
+        let mut order = Vec::with_capacity(self.vertices.len());
+        while let Some(Reverse((_, vertex))) = ready.pop() {
+            order.push(vertex);
+            for &(next, _) in &self.successors[vertex] {
+                waiting[next] -= 1;
+                if waiting[next] == 0 {
+                    ready.push(Reverse((rank(next), next)));
+                }
+            }
+        }
+        (order.len() == self.vertices.len()).then_some(order)
+    }
+
+    fn key(vertex: Vertex) -> String {
+        match vertex {
+            Vertex::Phase(name) => format!("phase {}", ident(name)),
+            Vertex::Node(id, _) => format!("node {id}"),
+        }
+    }
+
+    /// The index of `vertex`, added if needed.
+    pub fn vertex(&mut self, vertex: Vertex<'a>) -> usize {
+        let next = self.vertices.len();
+        let index = *self.index.entry(Self::key(vertex)).or_insert(next);
+        if index == next {
+            self.vertices.push(vertex);
+            self.successors.push(Vec::new());
+        }
+        index
+    }
+
+    fn depend(&mut self, from: Vertex<'a>, to: Vertex<'a>, dependency: Dependency) {
+        let (from, to) = (self.vertex(from), self.vertex(to));
+        self.successors[from].push((to, dependency));
+    }
+}
+
+/// A section of the spec that does not have the type the rules read it as.
+pub struct Malformed {
+    /// JSON Pointer of the section.
+    pub at: String,
+    /// How the section is named in messages, e.g. `implementation.phase_contracts`.
+    pub section: String,
+    /// `object` or `array`.
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+/// Nodes and edges of `algorithm.graph`, in document order.
+#[derive(Default)]
+pub struct GraphIndex<'a> {
+    pub entry: Option<&'a str>,
+    pub nodes: Vec<GraphNode<'a>>,
+    pub edges: Vec<GraphEdge<'a>>,
+}
+
+/// The resolved phases, contracts, dataflow and graph of one spec.
+#[derive(Default)]
+pub struct SpecContext<'a> {
+    /// Major version of `spec_version`.
+    pub major: Option<u64>,
+    /// Phases of the algorithm in declaration order: `algorithm.phases` first, then phases that
+    /// only appear as graph nodes.
+    pub phases: Vec<Phase<'a>>,
+    phase_index: HashMap<String, usize>,
+    /// Phase contracts in document order.
+    pub contracts: Vec<Contract<'a>>,
+    contract_index: HashMap<String, usize>,
+    /// Phase inputs read from other phases' outputs, in document order.
+    pub dataflow: Vec<Dataflow<'a>>,
+    pub graph: GraphIndex<'a>,
+    /// Sections left out because of their type, in document order.
+    pub malformed: Vec<Malformed>,
+}
+
+impl<'a> SpecContext<'a> {
+    pub fn new(doc: &'a JsonValue) -> Self {
+        let mut context = SpecContext {
+            major: doc
+                .get("spec_version")
+                .and_then(|v| v.as_str())
+                .and_then(parse_semver_major),
+            ..Default::default()
+        };
+
+        let algorithm = context.section(doc.get("algorithm"), &["algorithm"], "object");
+        let listed = context
+            .section(
+                algorithm.and_then(|a| a.get("phases")),
+                &["algorithm", "phases"],
+                "array",
+            )
+            .and_then(|v| v.as_array());
+        for (index, item) in listed.into_iter().flatten().enumerate() {
+            if let Some(name) = item.as_str() {
+                context.add_phase(name, PhaseSource::Listed(index));
+            }
+        }
+
+        let graph = context.section(
+            algorithm.and_then(|a| a.get("graph")),
+            &["algorithm", "graph"],
+            "object",
+        );
+        context.graph.entry = graph.and_then(|g| g.get("entry")).and_then(|e| e.as_str());
+        let nodes = context
+            .section(
+                graph.and_then(|g| g.get("nodes")),
+                &["algorithm", "graph", "nodes"],
+                "object",
+            )
+            .and_then(|n| n.as_object());
+        for (id, node) in nodes.into_iter().flatten() {
+            let kind = node.get("type").and_then(|t| t.as_str());
+            let phase = (kind == Some("phase"))
+                .then(|| node.get("phase").and_then(|p| p.as_str()).unwrap_or(id));
+            if let Some(phase) = phase {
+                context.add_phase(phase, PhaseSource::Node(id));
+            }
+            let branches = node.get("branches").and_then(|b| b.as_array());
+            let branches = branches
+                .into_iter()
+                .flatten()
+                .filter_map(|branch| branch.get("name").and_then(|n| n.as_str()))
+                .collect();
+            context.graph.nodes.push(GraphNode {
+                id,
+                kind,
+                phase,
+                branches,
+                condition: node.get("condition").and_then(|c| c.as_str()),
+                until: node.get("until").and_then(|u| u.as_str()),
+            });
+        }
+        let edges = context
+            .section(
+                graph.and_then(|g| g.get("edges")),
+                &["algorithm", "graph", "edges"],
+                "array",
+            )
+            .and_then(|e| e.as_array());
+        for edge in edges.into_iter().flatten() {
+            let field = |name: &str| edge.get(name).and_then(|v| v.as_str());
+            context.graph.edges.push(GraphEdge {
+                from: field("from"),
+                to: field("to"),
+                kind: field("kind"),
+                condition: field("condition"),
+            });
+        }
+
+        let implementation =
+            context.section(doc.get("implementation"), &["implementation"], "object");
+        let phase_contracts = context
+            .section(
+                implementation.and_then(|i| i.get("phase_contracts")),
+                &["implementation", "phase_contracts"],
+                "object",
+            )
+            .and_then(|v| v.as_object());
+        for (phase, value) in phase_contracts.into_iter().flatten() {
+            // A contract of the wrong type is still indexed, so that rules do not also report its
+            // phase as having no contract.
+            let at = ["implementation", "phase_contracts", phase];
+            let well_formed = context.section(Some(value), &at, "object").is_some();
+            let mut ports = |field| {
+                if well_formed {
+                    context.ports(value, &at, field)
+                } else {
+                    Vec::new()
+                }
+            };
+            let (inputs, outputs) = (ports("inputs"), ports("outputs"));
+            let outputs_known = well_formed
+                && value
+                    .get("outputs")
+                    .is_none_or(|outputs| outputs.is_array());
+            for input in &inputs {
+                let Some(source) = input
+                    .value
+                    .get("source")
+                    .filter(|s| s.get("kind").and_then(|k| k.as_str()) == Some("phase_output"))
+                else {
+                    continue;
+                };
+                if let Some(producer) = source.get("phase").and_then(|p| p.as_str()) {
+                    context.dataflow.push(Dataflow {
+                        consumer: phase,
+                        input: input.name,
+                        producer,
+                        port: source.get("port").and_then(|p| p.as_str()),
+                    });
+                }
+            }
+            context
+                .contract_index
+                .entry(ident(phase).into_owned())
+                .or_insert(context.contracts.len());
+            context.contracts.push(Contract {
+                phase,
+                value,
+                inputs,
+                outputs,
+                outputs_known,
+            });
+        }
+        context
+    }
+
+    /// `value` if it is absent or of the `expected` type (`object` or `array`); otherwise records it
+    /// as malformed and returns `None`.
+    fn section(
+        &mut self,
+        value: Option<&'a JsonValue>,
+        at: &[&str],
+        expected: &'static str,
+    ) -> Option<&'a JsonValue> {
+        let value = value?;
+        if type_name(value) == expected {
+            return Some(value);
+        }
+        let section = match at {
+            ["implementation", "phase_contracts", phase] => format!("phase contract '{phase}'"),
+            ["implementation", "phase_contracts", phase, rest @ ..] => {
+                format!("{} of phase contract '{phase}'", rest.join("."))
+            }
+            _ => at.join("."),
+        };
+        self.malformed.push(Malformed {
+            at: pointer(at),
+            section,
+            expected,
+            found: type_name(value),
+        });
+        None
+    }
+
+    /// The named entries of the `field` list of `contract`, which is at `at`.
+    fn ports(&mut self, contract: &'a JsonValue, at: &[&str], field: &str) -> Vec<Port<'a>> {
+        let items = self
+            .section(contract.get(field), &[at, &[field]].concat(), "array")
+            .and_then(|v| v.as_array());
+        items
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let name = value.get("name")?.as_str()?;
+                Some(Port { name, index, value })
+            })
+            .collect()
+    }
+
+    fn add_phase(&mut self, name: &'a str, source: PhaseSource<'a>) {
+        let key = ident(name).into_owned();
+        match self.phase_index.get(&key) {
+            Some(&index) => self.phases[index].sources.push(source),
+            None => {
+                self.phase_index.insert(key, self.phases.len());
+                self.phases.push(Phase {
+                    name,
+                    sources: vec![source],
+                });
+            }
+        }
+    }
+
+    /// Whether v3+ rules apply, which require a contract for every phase.
+    pub fn needs_contracts(&self) -> bool {
+        self.major.is_some_and(|major| major >= 3)
+    }
+
+    pub fn phase(&self, name: &str) -> Option<&Phase<'a>> {
+        self.phase_index
+            .get(ident(name).as_ref())
+            .map(|&index| &self.phases[index])
+    }
+
+    /// The contract of `phase`, preferring an entry spelled exactly the same.
+    pub fn contract(&self, phase: &str) -> Option<&Contract<'a>> {
+        self.contracts
+            .iter()
+            .find(|contract| contract.phase == phase)
+            .or_else(|| {
+                self.contract_index
+                    .get(ident(phase).as_ref())
+                    .map(|&index| &self.contracts[index])
+            })
+    }
+
+    /// A "did you mean" hint naming the phases closest to the unknown phase `name`, if any.
+    pub fn phase_suggestion(&self, name: &str) -> String {
+        did_you_mean(name, self.phases.iter().map(|phase| phase.name))
+    }
+
+    /// A "did you mean" hint naming the outputs of `phase` closest to the undeclared `port`.
+    pub fn output_suggestion(&self, phase: &str, port: &str) -> String {
+        let outputs = self.contract(phase).map_or(&[][..], |c| &c.outputs[..]);
+        did_you_mean(port, outputs.iter().map(|output| output.name))
+    }
+
+    /// Output `port` declared by the contract of `phase`.
+    pub fn output(&self, phase: &str, port: &str) -> Option<&Port<'a>> {
+        self.contract(phase)?.output(port)
+    }
+
+    /// The graph edges and the dataflow as one "runs before" relation between phases and the graph
+    /// nodes that run no phase. Loops are intended cycles, so `loop` edges and `loop` nodes are left
+    /// out, and so are reads between phases that share a cycle of the graph (through a loop), which
+    /// read the output of an earlier iteration. Edges between unknown nodes and reads from unknown
+    /// phases are left out too.
+    pub fn dependencies(&self) -> DependencyGraph<'a> {
+        // Component of every vertex that is part of a cycle of the graph.
+        let control = self.control_flow(true);
+        let mut repeated = HashMap::new();
+        for (number, component) in control.components().iter().enumerate() {
+            if control.shortest_cycle(component).is_some() {
+                repeated.extend(component.iter().map(|&vertex| (vertex, number)));
+            }
+        }
+        let iteration = |phase: &str| {
+            let vertex = control
+                .index
+                .get(&DependencyGraph::key(Vertex::Phase(phase)))?;
+            repeated.get(vertex)
+        };
+
+        let mut graph = self.control_flow(false);
+        for (position, flow) in self.dataflow.iter().enumerate() {
+            let (Some(producer), Some(consumer)) =
+                (self.phase(flow.producer), self.phase(flow.consumer))
+            else {
+                continue;
+            };
+            let loop_of_producer = iteration(producer.name);
+            if loop_of_producer.is_some() && loop_of_producer == iteration(consumer.name) {
+                continue;
+            }
+            graph.depend(
+                Vertex::Phase(producer.name),
+                Vertex::Phase(consumer.name),
+                Dependency::Dataflow(position),
+            );
+        }
+        graph
+    }
+
+    /// The edges of `algorithm.graph` between known nodes, with `loop` edges and nodes when `loops`
+    /// is set.
+    fn control_flow(&self, loops: bool) -> DependencyGraph<'a> {
+        let mut graph = DependencyGraph::default();
+        let node = |id: Option<&str>| {
+            let node = self.graph.nodes.iter().find(|node| Some(node.id) == id)?;
+            match (node.kind, node.phase) {
+                (Some("loop"), _) if !loops => None,
+                (_, Some(phase)) => {
+                    Some(Vertex::Phase(self.phase(phase).map_or(phase, |p| p.name)))
+                }
+                (kind, None) => Some(Vertex::Node(node.id, kind)),
+            }
+        };
+        for (position, edge) in self.graph.edges.iter().enumerate() {
+            if edge.kind == Some("loop") && !loops {
+                continue;
+            }
+            if let (Some(from), Some(to)) = (node(edge.from), node(edge.to)) {
+                graph.depend(from, to, Dependency::Edge(position));
+            }
+        }
+        graph
+    }
+
+    /// Whether `phase` has a contract whose outputs are known and do not include `port`.
+    pub fn undeclared_output(&self, phase: &str, port: &str) -> bool {
+        self.contract(phase)
+            .is_some_and(|contract| contract.outputs_known && contract.output(port).is_none())
+    }
+}
+
+/// The JSON type of `value`: `null`, `boolean`, `number`, `string`, `array` or `object`.
+pub fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn find_port<'p, 'a>(ports: &'p [Port<'a>], name: &str) -> Option<&'p Port<'a>> {
+    ports
+        .iter()
+        .find(|port| port.name == name)
+        .or_else(|| ports.iter().find(|port| ident(port.name) == ident(name)))
+}
@@ -0,0 +1,179 @@
+//! `schema coverage`: which parts of a schema a spec corpus exercises. The regions counted are
+//! declared properties, `enum` values and `oneOf`/`anyOf` arms, named by their JSON Pointer in the
+//! schema document. A property is exercised when a spec sets it where the schema node applies, an
+//! enum value when a spec uses it there, and an arm when a spec value validates against it.
+
+use crate::{
+    trace::{escape, Tracer},
+    usage::{group_schema, specs_by_version},
+    Args,
+};
+use serde_json::Value as JsonValue;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+/// Kinds of schema regions and their summary labels, in report order.
+const KINDS: [(&str, &str); 3] = [
+    ("property", "properties"),
+    ("enum value", "enum values"),
+    ("arm", "oneOf/anyOf arms"),
+];
+
+/// Keywords holding a single subschema. (`if` and `not` only test values, so their regions are not
+/// counted.)
+const SCHEMA_KEYWORDS: [&str; 5] = [
+    "additionalProperties",
+    "items",
+    "additionalItems",
+    "then",
+    "else",
+];
+
+/// Keywords holding a map of subschemas.
+const SCHEMA_MAP_KEYWORDS: [&str; 4] = ["properties", "patternProperties", "definitions", "$defs"];
+
+/// Prints, per spec version, how much of the schema the specs exercise and the regions they do not.
+pub fn coverage(args: &Args, paths: &[PathBuf]) -> ExitCode {
+    let groups = match specs_by_version(args, paths) {
+        Ok(groups) => groups,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut failed = false;
+    for (version, specs) in &groups {
+        let resolved = match group_schema(args, version, specs) {
+            Ok(resolved) => resolved,
+            Err(msg) => {
+                errln!("── {version} — {} spec(s) ──", specs.len());
+                failln!("{msg}");
+                failed = true;
+                continue;
+            }
+        };
+
+        let mut regions = BTreeMap::new();
+        collect_regions(&resolved.schema, String::new(), &mut regions);
+        // Properties and arms are exercised when visited, enum values when a visit matches one.
+        let mut exercised = BTreeSet::new();
+        let mut tracer = Tracer::new(args, &resolved);
+        for (_, doc) in specs {
+            tracer.trace(doc, &mut |visit| {
+                exercised.insert(visit.pointer.to_string());
+                if let Some(values) = visit.node.get("enum").and_then(|v| v.as_array()) {
+                    if let Some(i) = values.iter().position(|value| value == visit.instance) {
+                        exercised.insert(format!("{}/enum/{i}", visit.pointer));
+                    }
+                }
+            });
+        }
+
+        outln!(
+            "── {version} — {} spec(s), {} ──",
+            specs.len(),
+            resolved.origin
+        );
+        for (kind, label) in KINDS {
+            let total = regions.values().filter(|(k, _)| *k == kind).count();
+            let exercised = regions
+                .iter()
+                .filter(|(pointer, (k, _))| *k == kind && exercised.contains(*pointer))
+                .count();
+            if let Some(percent) = (exercised * 100).checked_div(total) {
+                outln!(
+                    "  {:<18} {exercised:>4} of {total:<4} exercised ({percent}%)",
+                    format!("{label}:")
+                );
+            }
+        }
+        let untested: Vec<_> = regions
+            .iter()
+            .filter(|(pointer, _)| !exercised.contains(*pointer))
+            .collect();
+        if !untested.is_empty() {
+            outln!("  Not exercised:");
+            for (pointer, (kind, detail)) in untested {
+                outln!("    #{pointer} ({kind}{detail})");
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::from(0)
+    }
+}
+
+/// Collects the regions of the schema node at `pointer`: pointer → (kind, detail for the report).
+fn collect_regions(
+    node: &JsonValue,
+    pointer: String,
+    regions: &mut BTreeMap<String, (&'static str, String)>,
+) {
+    let Some(map) = node.as_object() else {
+        return;
+    };
+    if let Some(properties) = map.get("properties").and_then(|v| v.as_object()) {
+        for name in properties.keys() {
+            regions.insert(
+                format!("{pointer}/properties/{}", escape(name)),
+                ("property", String::new()),
+            );
+        }
+    }
+    if let Some(values) = map.get("enum").and_then(|v| v.as_array()) {
+        for (i, value) in values.iter().enumerate() {
+            regions.insert(
+                format!("{pointer}/enum/{i}"),
+                ("enum value", format!(" {value}")),
+            );
+        }
+    }
+    for key in ["oneOf", "anyOf"] {
+        for i in 0..map.get(key).and_then(|v| v.as_array()).map_or(0, Vec::len) {
+            regions.insert(
+                format!("{pointer}/{key}/{i}"),
+                ("arm", format!(" of {key}")),
+            );
+        }
+    }
+
+    for key in SCHEMA_KEYWORDS {
+        match map.get(key) {
+            Some(JsonValue::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    collect_regions(item, format!("{pointer}/{key}/{i}"), regions);
+                }
+            }
+            Some(sub) => collect_regions(sub, format!("{pointer}/{key}"), regions),
+            None => {}
+        }
+    }
+    for key in ["allOf", "anyOf", "oneOf"] {
+        for (i, item) in map
+            .get(key)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            collect_regions(item, format!("{pointer}/{key}/{i}"), regions);
+        }
+    }
+    for key in SCHEMA_MAP_KEYWORDS {
+        for (name, sub) in map
+            .get(key)
+            .and_then(|v| v.as_object())
+            .into_iter()
+            .flatten()
+        {
+            collect_regions(sub, format!("{pointer}/{key}/{}", escape(name)), regions);
+        }
+    }
+}
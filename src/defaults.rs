@@ -0,0 +1,91 @@
+//! `--apply-defaults`: fills in the `default` of every schema property a spec leaves out before the
+//! domain rules run, so that they check the spec the executor will run rather than the one written
+//! down. Defaults are taken from the schema nodes that apply to each object of the spec (see
+//! [`crate::trace`]), so a default behind a `$ref`, in an `allOf` arm or in the `oneOf` arm the object
+//! matches is found too. The schema itself checks the spec as written.
+
+use crate::{schemas::ResolvedSchema, trace::escape, trace::Tracer, usage::SchemaWalker, Args};
+use serde_json::Value as JsonValue;
+
+/// Fill-in passes at most; each pass reaches into the objects the previous one added.
+const MAX_PASSES: usize = 16;
+
+/// `instance` with the schema defaults filled in, and the JSON Pointers of the values filled in.
+pub fn apply(
+    args: &Args,
+    resolved: &ResolvedSchema,
+    instance: &JsonValue,
+) -> (JsonValue, Vec<String>) {
+    let mut defaulted = instance.clone();
+    let mut applied = Vec::new();
+    if !declares_defaults(&resolved.schema) {
+        return (defaulted, applied);
+    }
+    for _ in 0..MAX_PASSES {
+        let missing = missing_defaults(args, resolved, &defaulted);
+        let mut added = false;
+        for (at, key, value) in missing {
+            let Some(JsonValue::Object(members)) = defaulted.pointer_mut(&at) else {
+                continue;
+            };
+            // Two schema nodes of the same object may both default a property; the first one wins.
+            if !members.contains_key(&key) {
+                applied.push(format!("{at}/{}", escape(&key)));
+                members.insert(key, value);
+                added = true;
+            }
+        }
+        if !added {
+            break;
+        }
+    }
+    (defaulted, applied)
+}
+
+/// Properties missing from the objects of `instance` whose schema gives a default, as the pointer
+/// of the object, the property name and the default.
+fn missing_defaults(
+    args: &Args,
+    resolved: &ResolvedSchema,
+    instance: &JsonValue,
+) -> Vec<(String, String, JsonValue)> {
+    let schema = &resolved.schema;
+    let mut walker = SchemaWalker::new(schema);
+    let mut missing = Vec::new();
+    Tracer::new(args, resolved).trace(instance, &mut |visit| {
+        let (Some(members), Some(properties)) = (
+            visit.instance.as_object(),
+            visit.node.get("properties").and_then(|p| p.as_object()),
+        ) else {
+            return;
+        };
+        for key in properties.keys() {
+            if members.contains_key(key) {
+                continue;
+            }
+            let property = format!("{}/properties/{}", visit.pointer, escape(key));
+            let Some(node) = schema.pointer(&property) else {
+                continue;
+            };
+            if let Some(default) = walker
+                .applicable(node)
+                .into_iter()
+                .find_map(|node| node.get("default"))
+            {
+                missing.push((visit.at.to_string(), key.clone(), default.clone()));
+            }
+        }
+    });
+    missing
+}
+
+/// Whether some node of `schema` may declare a default (a property named `default` counts too).
+fn declares_defaults(schema: &JsonValue) -> bool {
+    match schema {
+        JsonValue::Object(map) => map
+            .iter()
+            .any(|(key, value)| key == "default" || declares_defaults(value)),
+        JsonValue::Array(items) => items.iter().any(declares_defaults),
+        _ => false,
+    }
+}
@@ -0,0 +1,120 @@
+//! Findings produced by schema validation and the domain rules.
+
+use clap::ValueEnum;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// How serious a finding is. Ordered from least to most severe so thresholds can be compared.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Marker printed in front of a finding of this severity.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ️",
+            Severity::Warning => "⚠️",
+            Severity::Error => "❌",
+        }
+    }
+
+    /// Lowercase name, as accepted by `--fail-on` and the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A single finding reported by a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Stable identifier of the check that produced the finding, e.g. `PV010`.
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// JSON Pointer to the node the finding is about, when the rule knows it.
+    pub pointer: Option<String>,
+    /// Values for the placeholders of message templates (`phase`, `port`, …).
+    pub params: Vec<(&'static str, String)>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity,
+            message: message.into(),
+            pointer: None,
+            params: Vec::new(),
+        }
+    }
+
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Error, message)
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Warning, message)
+    }
+
+    pub fn info(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Info, message)
+    }
+
+    /// Attaches the JSON Pointer of the offending node.
+    pub fn at(mut self, pointer: impl Into<String>) -> Self {
+        self.pointer = Some(pointer.into());
+        self
+    }
+
+    /// Sets the value of placeholder `name` unless it already has one.
+    pub fn with(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        if self.param(name).is_none() {
+            self.params.push((name, value.into()));
+        }
+        self
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `template` with `{message}`, `{code}`, `{pointer}` and the parameters filled in. Unknown
+    /// placeholders are kept as written.
+    pub fn render(&self, template: &str) -> String {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| Regex::new(r"\{([a-z_]+)\}").unwrap());
+        re.replace_all(template, |captures: &Captures| {
+            let name = &captures[1];
+            match name {
+                "message" => Some(self.message.as_str()),
+                "code" => Some(self.code),
+                "pointer" => self.pointer.as_deref(),
+                _ => self.param(name),
+            }
+            .unwrap_or(&captures[0])
+            .to_string()
+        })
+        .into_owned()
+    }
+}
+
+/// Builds a JSON Pointer (RFC 6901) from path segments, escaping `~` and `/`.
+pub fn pointer(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
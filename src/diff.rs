@@ -0,0 +1,495 @@
+//! `diff`: a structural comparison of two specs. Phases, phase contracts (down to their ports),
+//! algorithm outputs, the return contract and the graph are matched by name rather than by
+//! position, so reordered keys, reformatting or a YAML/JSON conversion produce no changes.
+//!
+//! Both specs are compared the way they are validated: inline suppressions are removed and
+//! contract libraries are merged in first.
+
+use crate::{
+    diagnostics::pointer, display_input, parse_documents, prepare_document, read_input, Args,
+    InputFormat,
+};
+use clap::ValueEnum;
+use serde_json::{json, Value as JsonValue};
+use std::{path::Path, process::ExitCode};
+
+/// How `diff` prints the changes.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// Changes grouped by section, for people.
+    #[default]
+    Human,
+    /// One JSON object per change and line.
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    fn name(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        }
+    }
+}
+
+/// One difference between the two specs.
+#[derive(Debug)]
+struct Change {
+    section: &'static str,
+    kind: ChangeKind,
+    /// What changed: a phase name, `from → to` for an edge, a field for `meta`.
+    subject: String,
+    /// Location in the new spec, or in the old one for removals.
+    pointer: String,
+    /// What changed inside the subject, e.g. `inputs.question changed (schema)`.
+    details: Vec<String>,
+}
+
+/// Sections in the order they are printed.
+const SECTIONS: [&str; 7] = [
+    "meta",
+    "phases",
+    "contracts",
+    "outputs",
+    "return contract",
+    "graph nodes",
+    "graph edges",
+];
+
+/// Prints the structural differences between `old` and `new`.
+pub fn diff(args: &Args, old: &Path, new: &Path, format: DiffFormat) -> ExitCode {
+    let specs = load(args, old).and_then(|old_doc| Ok((old_doc, load(args, new)?)));
+    let (old_doc, new_doc) = match specs {
+        Ok(specs) => specs,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let changes = changes(&old_doc, &new_doc);
+    match format {
+        DiffFormat::Human => print(&changes, &display_input(old), &display_input(new)),
+        DiffFormat::Json => {
+            for change in &changes {
+                let object = json!({
+                    "section": change.section,
+                    "change": change.kind.name(),
+                    "subject": change.subject,
+                    "pointer": change.pointer,
+                    "details": change.details,
+                });
+                crate::output::emit(&object.to_string());
+            }
+        }
+    }
+    ExitCode::from(0)
+}
+
+/// The single spec in `file`, prepared for validation.
+pub fn load(args: &Args, file: &Path) -> Result<JsonValue, String> {
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let text = read_input(file)?;
+    let mut documents = parse_documents(&text, format)
+        .map_err(|e| format!("Error: {}: {e}", display_input(file)))?;
+    if documents.len() != 1 {
+        return Err(format!(
+            "Error: {} holds {} documents; expected a file with a single spec",
+            display_input(file),
+            documents.len()
+        ));
+    }
+    let doc = documents.remove(0);
+    Ok(prepare_document(args, file, &doc).0)
+}
+
+/// Prints the structural differences between the specs `old` and `new`, titled `old_name` and
+/// `new_name`.
+pub fn print_differences(old: &JsonValue, new: &JsonValue, old_name: &str, new_name: &str) {
+    print(&changes(old, new), old_name, new_name);
+}
+
+/// The changes from `old` to `new`, by section.
+fn changes(old: &JsonValue, new: &JsonValue) -> Vec<Change> {
+    let mut changes = compare(old, new);
+    changes.sort_by_key(|change| SECTIONS.iter().position(|s| *s == change.section));
+    changes
+}
+
+fn print(changes: &[Change], old: &str, new: &str) {
+    if changes.is_empty() {
+        outln!("✅ {old} and {new} are structurally identical.");
+        return;
+    }
+    outln!("── {old} → {new}: {} change(s) ──", changes.len());
+    let mut section = None;
+    for change in changes {
+        if section != Some(change.section) {
+            outln!("{}:", change.section);
+            section = Some(change.section);
+        }
+        let symbol = change.kind.symbol();
+        if change.details.is_empty() {
+            outln!("  {symbol} {}", change.subject);
+        } else {
+            outln!(
+                "  {symbol} {}: {}",
+                change.subject,
+                change.details.join("; ")
+            );
+        }
+    }
+}
+
+fn compare(old: &JsonValue, new: &JsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    for path in [
+        &["spec_version"][..],
+        &["meta", "title"],
+        &["meta", "version"],
+        &["algorithm", "name"],
+        &["algorithm", "graph", "entry"],
+    ] {
+        let at = pointer(path);
+        let (before, after) = (old.pointer(&at), new.pointer(&at));
+        if before != after {
+            let (kind, details) = match (before, after) {
+                (None, Some(value)) => (ChangeKind::Added, vec![display(value)]),
+                (Some(value), None) => (ChangeKind::Removed, vec![display(value)]),
+                (Some(a), Some(b)) => (
+                    ChangeKind::Changed,
+                    vec![format!("{} → {}", display(a), display(b))],
+                ),
+                (None, None) => unreachable!(),
+            };
+            changes.push(Change {
+                section: "meta",
+                kind,
+                subject: path.join("."),
+                pointer: at,
+                details,
+            });
+        }
+    }
+
+    compare_phases(old, new, &mut changes);
+    compare_keyed(
+        "contracts",
+        &contracts(old),
+        &contracts(new),
+        contract_details,
+        &mut changes,
+    );
+    compare_keyed(
+        "outputs",
+        &named_items(old, &["algorithm", "outputs"]),
+        &named_items(new, &["algorithm", "outputs"]),
+        field_details,
+        &mut changes,
+    );
+    compare_keyed(
+        "return contract",
+        &return_contract(old),
+        &return_contract(new),
+        field_details,
+        &mut changes,
+    );
+    compare_keyed(
+        "graph nodes",
+        &members(old, &["algorithm", "graph", "nodes"]),
+        &members(new, &["algorithm", "graph", "nodes"]),
+        field_details,
+        &mut changes,
+    );
+    compare_keyed(
+        "graph edges",
+        &edges(old),
+        &edges(new),
+        field_details,
+        &mut changes,
+    );
+
+    changes
+}
+
+/// Phases declared in `algorithm.phases` or as `phase` nodes of the graph, added or removed, and
+/// a change of the order of `algorithm.phases`.
+fn compare_phases(old: &JsonValue, new: &JsonValue, changes: &mut Vec<Change>) {
+    let (before, after) = (phases(old), phases(new));
+    for (name, at) in &after {
+        if !before.iter().any(|(n, _)| n == name) {
+            changes.push(Change {
+                section: "phases",
+                kind: ChangeKind::Added,
+                subject: name.clone(),
+                pointer: at.clone(),
+                details: Vec::new(),
+            });
+        }
+    }
+    for (name, at) in &before {
+        if !after.iter().any(|(n, _)| n == name) {
+            changes.push(Change {
+                section: "phases",
+                kind: ChangeKind::Removed,
+                subject: name.clone(),
+                pointer: at.clone(),
+                details: Vec::new(),
+            });
+        }
+    }
+
+    // Reordering only matters for the phases both specs keep.
+    let kept = |list: &[(String, String)], other: &[(String, String)]| -> Vec<String> {
+        list.iter()
+            .filter(|(name, at)| {
+                at.starts_with("/algorithm/phases/") && other.iter().any(|(n, _)| n == name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    };
+    let (kept_before, kept_after) = (kept(&before, &after), kept(&after, &before));
+    if kept_before != kept_after {
+        changes.push(Change {
+            section: "phases",
+            kind: ChangeKind::Changed,
+            subject: "order".to_string(),
+            pointer: pointer(&["algorithm", "phases"]),
+            details: vec![format!(
+                "{} → {}",
+                kept_before.join(", "),
+                kept_after.join(", ")
+            )],
+        });
+    }
+}
+
+/// Phase names with their pointers: `algorithm.phases` first, then graph phase nodes not listed
+/// there.
+pub fn phases(doc: &JsonValue) -> Vec<(String, String)> {
+    let mut phases: Vec<(String, String)> = Vec::new();
+    let listed = doc
+        .pointer("/algorithm/phases")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten();
+    for (index, item) in listed.enumerate() {
+        if let Some(name) = item.as_str() {
+            phases.push((
+                name.to_string(),
+                pointer(&["algorithm", "phases", &index.to_string()]),
+            ));
+        }
+    }
+    for (id, node, at) in members(doc, &["algorithm", "graph", "nodes"]) {
+        if node.get("type").and_then(|t| t.as_str()) != Some("phase") {
+            continue;
+        }
+        let name = node
+            .get("phase")
+            .and_then(|p| p.as_str())
+            .map(str::to_string)
+            .unwrap_or(id);
+        if !phases.iter().any(|(n, _)| *n == name) {
+            phases.push((name, at));
+        }
+    }
+    phases
+}
+
+/// An item matched by name between the specs: its name, value and pointer.
+type Keyed<'a> = (String, &'a JsonValue, String);
+
+/// Adds the items of `after` missing from `before`, the items of `before` missing from `after`,
+/// and the items of both whose `details` are not empty.
+fn compare_keyed(
+    section: &'static str,
+    before: &[Keyed],
+    after: &[Keyed],
+    details: fn(&JsonValue, &JsonValue) -> Vec<String>,
+    changes: &mut Vec<Change>,
+) {
+    for (name, value, at) in after {
+        let (kind, details) = match before.iter().find(|(n, ..)| n == name) {
+            None => (ChangeKind::Added, Vec::new()),
+            Some((_, old, _)) if old == value => continue,
+            Some((_, old, _)) => (ChangeKind::Changed, details(old, value)),
+        };
+        changes.push(Change {
+            section,
+            kind,
+            subject: name.clone(),
+            pointer: at.clone(),
+            details,
+        });
+    }
+    for (name, _, at) in before {
+        if !after.iter().any(|(n, ..)| n == name) {
+            changes.push(Change {
+                section,
+                kind: ChangeKind::Removed,
+                subject: name.clone(),
+                pointer: at.clone(),
+                details: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Members of the object at `path`.
+fn members<'a>(doc: &'a JsonValue, path: &[&str]) -> Vec<Keyed<'a>> {
+    doc.pointer(&pointer(path))
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| {
+            let at = pointer(&[path, &[name.as_str()]].concat());
+            (name.clone(), value, at)
+        })
+        .collect()
+}
+
+/// Items of the list at `path`, by their `name` (`#<index>` for unnamed items).
+fn named_items<'a>(doc: &'a JsonValue, path: &[&str]) -> Vec<Keyed<'a>> {
+    doc.pointer(&pointer(path))
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, item)| {
+            let index = index.to_string();
+            let name = item
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("#{index}"));
+            (name, item, pointer(&[path, &[index.as_str()]].concat()))
+        })
+        .collect()
+}
+
+fn contracts(doc: &JsonValue) -> Vec<Keyed<'_>> {
+    members(doc, &["implementation", "phase_contracts"])
+}
+
+/// `implementation.return_contract`, as the only item of its section.
+fn return_contract(doc: &JsonValue) -> Vec<Keyed<'_>> {
+    let at = pointer(&["implementation", "return_contract"]);
+    doc.pointer(&at)
+        .map(|value| ("return_contract".to_string(), value, at.clone()))
+        .into_iter()
+        .collect()
+}
+
+/// Graph edges, identified by `from → to`.
+fn edges(doc: &JsonValue) -> Vec<Keyed<'_>> {
+    doc.pointer("/algorithm/graph/edges")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, edge)| {
+            let end = |field: &str| edge.get(field).and_then(|v| v.as_str()).unwrap_or("?");
+            let name = format!("{} → {}", end("from"), end("to"));
+            let at = pointer(&["algorithm", "graph", "edges", &index.to_string()]);
+            (name, edge, at)
+        })
+        .collect()
+}
+
+/// Top-level fields that differ between two objects, e.g. `retry_policy added`,
+/// `kind changed (normal → fallback)`; only scalar values are shown.
+fn field_details(old: &JsonValue, new: &JsonValue) -> Vec<String> {
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return vec![format!("{} → {}", display(old), display(new))];
+    };
+    let mut details = Vec::new();
+    for (field, value) in new {
+        match old.get(field) {
+            None => details.push(format!("{field} added")),
+            Some(before) if before != value => {
+                if is_scalar(before) && is_scalar(value) {
+                    let values = format!("{} → {}", display(before), display(value));
+                    details.push(format!("{field} changed ({values})"));
+                } else {
+                    details.push(format!("{field} changed"));
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    for field in old.keys() {
+        if !new.contains_key(field) {
+            details.push(format!("{field} removed"));
+        }
+    }
+    details
+}
+
+/// Like [`field_details`], with `inputs` and `outputs` compared port by port.
+fn contract_details(old: &JsonValue, new: &JsonValue) -> Vec<String> {
+    let mut details = Vec::new();
+    for field in field_details(old, new) {
+        let ports = ["inputs", "outputs"]
+            .into_iter()
+            .find(|ports| field == format!("{ports} changed"));
+        let Some(ports) = ports else {
+            details.push(field);
+            continue;
+        };
+        let (before, after) = (named_items(old, &[ports]), named_items(new, &[ports]));
+        let mut port_changes = Vec::new();
+        compare_keyed(ports, &before, &after, field_details, &mut port_changes);
+        for change in port_changes {
+            let mut detail = format!("{ports}.{} {}", change.subject, change.kind.name());
+            if !change.details.is_empty() {
+                let fields: Vec<&str> = change
+                    .details
+                    .iter()
+                    .map(|d| d.split_once(' ').map_or(d.as_str(), |(f, _)| f))
+                    .collect();
+                detail.push_str(&format!(" ({})", fields.join(", ")));
+            }
+            details.push(detail);
+        }
+        if before
+            .iter()
+            .map(|(n, ..)| n)
+            .ne(after.iter().map(|(n, ..)| n))
+            && details.iter().all(|d| !d.starts_with(&format!("{ports}.")))
+        {
+            details.push(format!("{ports} reordered"));
+        }
+    }
+    details
+}
+
+fn is_scalar(value: &JsonValue) -> bool {
+    !value.is_object() && !value.is_array()
+}
+
+/// A value as shown in a change: strings without quotes, anything else as compact JSON.
+fn display(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,589 @@
+//! `docs generate`: renders a Markdown reference page per spec from the spec itself, so that the
+//! documentation of phases, their ports, errors, retries and fallbacks, and the return contract
+//! cannot drift from what the spec declares.
+//!
+//! With `--per-phase`, every phase gets a page of its own next to the spec's page, with the phases
+//! it reads from and the phases reading its outputs linked, and anchors on its ports and error
+//! codes (`#input-NAME`, `#output-NAME`, `#error-CODE`) that runbooks can link to.
+
+use crate::{
+    context::{Contract, Port, SpecContext},
+    diff::load,
+    display_input,
+    rules::ident,
+    Args,
+};
+use serde_json::Value as JsonValue;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// How pages link to the documentation of a phase.
+#[derive(Clone, Copy)]
+enum Links<'a> {
+    /// To its section of the spec's page.
+    Sections,
+    /// To its page `<stem>/<phase>.md`, from the spec's page `<stem>.md` or, with `from_phase`,
+    /// from the page of another phase.
+    Pages { stem: &'a str, from_phase: bool },
+}
+
+impl Links<'_> {
+    /// A link to the documentation of `phase`; on its own page, to the element `target` of it.
+    fn to(self, phase: &str, target: Option<&str>) -> String {
+        let href = match self {
+            Links::Sections => format!("#{}", anchor(phase)),
+            Links::Pages { stem, from_phase } => {
+                let mut href = format!("{}.md", anchor(phase));
+                if !from_phase {
+                    href = format!("{}/{href}", stem.replace(' ', "%20"));
+                }
+                if let Some(target) = target {
+                    href = format!("{href}#{target}");
+                }
+                href
+            }
+        };
+        format!("[{}]({href})", code(phase))
+    }
+
+    /// An anchor for `id` in front of a table cell, on phase pages only.
+    fn target(self, id: &str) -> String {
+        match self {
+            Links::Sections => String::new(),
+            Links::Pages { .. } => format!("<a id=\"{id}\"></a>"),
+        }
+    }
+}
+
+/// `meta.title`, else `algorithm.name`.
+fn title(doc: &JsonValue) -> &str {
+    let text = |path: &str| doc.pointer(path).and_then(|v| v.as_str());
+    text("/meta/title")
+        .or(text("/algorithm/name"))
+        .unwrap_or("Untitled spec")
+}
+
+/// Phases in algorithm order, then contracts for phases the algorithm does not list.
+fn phases<'c, 'a>(context: &'c SpecContext<'a>) -> Vec<(&'a str, Option<&'c Contract<'a>>)> {
+    let mut phases: Vec<_> = context
+        .phases
+        .iter()
+        .map(|phase| (phase.name, context.contract(phase.name)))
+        .collect();
+    for contract in &context.contracts {
+        if context.phase(contract.phase).is_none() {
+            phases.push((contract.phase, Some(contract)));
+        }
+    }
+    phases
+}
+
+/// Renders the documentation page of `doc`; with [`Links::Pages`], without the phase sections.
+fn render(doc: &JsonValue, links: Links) -> String {
+    let context = SpecContext::new(doc);
+    let text = |path: &str| doc.pointer(path).and_then(|v| v.as_str());
+    let name = text("/algorithm/name");
+    let mut page = format!("# {}\n\n", title(doc));
+    if let Some(purpose) = text("/meta/purpose") {
+        page.push_str(&format!("{}\n\n", purpose.trim()));
+    }
+
+    let mut summary = Vec::new();
+    for (label, value) in [
+        ("Algorithm", name.map(code)),
+        ("Version", text("/meta/version").map(code)),
+        ("Spec version", text("/spec_version").map(code)),
+        (
+            "Entry",
+            context.graph.entry.map(|entry| links.to(entry, None)),
+        ),
+    ] {
+        if let Some(value) = value {
+            summary.push(format!("| {label} | {value} |\n"));
+        }
+    }
+    if !summary.is_empty() {
+        page.push_str("| | |\n|---|---|\n");
+        page.extend(summary);
+        page.push('\n');
+    }
+
+    let phases = phases(&context);
+    if !phases.is_empty() {
+        page.push_str("## Phases\n\n| Phase | Inputs | Outputs | Errors |\n|---|---|---|---|\n");
+        for (phase, contract) in &phases {
+            let Some(contract) = contract else {
+                page.push_str(&format!("| {} | — | — | — |\n", links.to(phase, None)));
+                continue;
+            };
+            let names = |ports: &[Port]| list(ports.iter().map(|port| code(port.name)));
+            let errors = list(error_entries(contract.value).map(|(code_, _, _)| code(code_)));
+            page.push_str(&format!(
+                "| {} | {} | {} | {errors} |\n",
+                links.to(phase, None),
+                names(&contract.inputs),
+                names(&contract.outputs)
+            ));
+        }
+        page.push('\n');
+        if let Links::Sections = links {
+            for (phase, contract) in &phases {
+                page.push_str(&format!("### {}\n\n", code(phase)));
+                page.push_str(&phase_section(&context, phase, *contract, links));
+            }
+        }
+    }
+
+    if let Some(return_contract) = doc.pointer("/implementation/return_contract") {
+        page.push_str(&return_section(return_contract, links));
+    }
+    page
+}
+
+/// The page of `phase` of the spec `doc`, documented as `<stem>.md`. `others` are the other specs
+/// of the run, with their stems, and the page lists those that declare the phase too.
+fn phase_page(
+    doc: &JsonValue,
+    context: &SpecContext,
+    stem: &str,
+    phase: &str,
+    contract: Option<&Contract>,
+    others: &[(&str, &JsonValue, &SpecContext)],
+) -> String {
+    let mut page = format!(
+        "# {}\n\nPhase of [{}](../{}.md).\n\n",
+        code(phase),
+        cell(title(doc)),
+        stem.replace(' ', "%20")
+    );
+    let links = Links::Pages {
+        stem,
+        from_phase: true,
+    };
+    page.push_str(&phase_section(context, phase, contract, links));
+    let shared: Vec<String> = others
+        .iter()
+        .filter(|(_, _, other)| other.phase(phase).is_some() || other.contract(phase).is_some())
+        .map(|(other, doc, _)| {
+            format!(
+                "[{}](../{}/{}.md)",
+                cell(title(doc)),
+                other.replace(' ', "%20"),
+                anchor(phase)
+            )
+        })
+        .collect();
+    if !shared.is_empty() {
+        page.push_str(&format!("**Also in:** {}\n\n", shared.join(", ")));
+    }
+    page
+}
+
+/// The documentation of one phase, below its heading.
+fn phase_section(
+    context: &SpecContext,
+    phase: &str,
+    contract: Option<&Contract>,
+    links: Links,
+) -> String {
+    let mut section = String::new();
+    let Some(contract) = contract else {
+        section.push_str("_No phase contract._\n\n");
+        return section;
+    };
+    if let Some(description) = contract.value.get("description").and_then(|d| d.as_str()) {
+        section.push_str(&format!("{}\n\n", description.trim()));
+    }
+
+    if !contract.inputs.is_empty() {
+        section
+            .push_str("**Inputs**\n\n| Name | Type | Source | Description |\n|---|---|---|---|\n");
+        for input in &contract.inputs {
+            let mut name = links.target(&format!("input-{}", anchor(input.name)));
+            name.push_str(&code(input.name));
+            if input.value.get("optional").and_then(|o| o.as_bool()) == Some(true) {
+                name.push_str(" (optional)");
+            }
+            section.push_str(&format!(
+                "| {name} | {} | {} | {} |\n",
+                schema_type(input.value.get("schema")),
+                source(input.value.get("source"), links),
+                description(input.value)
+            ));
+        }
+        section.push('\n');
+    }
+
+    if !contract.outputs.is_empty() {
+        // Phase pages also link the inputs that read each output.
+        let pages = matches!(links, Links::Pages { .. });
+        section.push_str(if pages {
+            "**Outputs**\n\n| Name | Type | Read by | Description |\n|---|---|---|---|\n"
+        } else {
+            "**Outputs**\n\n| Name | Type | Description |\n|---|---|---|\n"
+        });
+        for output in &contract.outputs {
+            let mut readers = String::new();
+            if pages {
+                let flows = context.dataflow.iter().filter(|flow| {
+                    ident(flow.producer) == ident(phase)
+                        && flow
+                            .port
+                            .is_some_and(|port| ident(port) == ident(output.name))
+                });
+                let inputs = list(flows.map(|flow| {
+                    let target = format!("input-{}", anchor(flow.input));
+                    format!(
+                        "{} of {}",
+                        code(flow.input),
+                        links.to(flow.consumer, Some(&target))
+                    )
+                }));
+                readers = format!(" {inputs} |");
+            }
+            section.push_str(&format!(
+                "| {}{} | {} |{readers} {} |\n",
+                links.target(&format!("output-{}", anchor(output.name))),
+                code(output.name),
+                schema_type(output.value.get("schema")),
+                description(output.value)
+            ));
+        }
+        section.push('\n');
+    }
+
+    let errors: Vec<_> = error_entries(contract.value).collect();
+    if !errors.is_empty() {
+        section.push_str("**Errors**\n\n| Code | Severity | Description |\n|---|---|---|\n");
+        for (code_, severity, description) in errors {
+            section.push_str(&format!(
+                "| {}{} | {} | {} |\n",
+                links.target(&format!("error-{}", anchor(code_))),
+                code(code_),
+                severity.unwrap_or("—"),
+                cell(description.unwrap_or(""))
+            ));
+        }
+        section.push('\n');
+    }
+
+    if let Some(retry) = contract.value.get("retry_policy") {
+        section.push_str(&format!("**Retries:** {}.\n\n", retries(retry)));
+    }
+    if let Some(fallback) = contract.value.get("fallback") {
+        let target = fallback.get("phase").and_then(|p| p.as_str());
+        let target = target.map(|phase| links.to(phase, None));
+        let mut line = format!("**Fallback:** {}", target.unwrap_or("—".into()));
+        if let Some(reason) = fallback.get("reason").and_then(|r| r.as_str()) {
+            line.push_str(&format!(" — {}", reason.trim()));
+        }
+        section.push_str(&format!("{line}\n\n"));
+    }
+    section
+}
+
+/// Describes a `retry_policy`: attempts, retried error codes and backoff.
+fn retries(retry: &JsonValue) -> String {
+    let mut text = match retry.get("max_attempts").and_then(|m| m.as_u64()) {
+        Some(attempts) => format!("up to {attempts} attempts"),
+        None => "retried".to_string(),
+    };
+    let codes: Vec<String> = retry
+        .get("retryable_errors")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c.as_str())
+        .map(code)
+        .collect();
+    if !codes.is_empty() {
+        text.push_str(&format!(" on {}", codes.join(", ")));
+    }
+    if let Some(backoff) = retry.get("backoff") {
+        let strategy = backoff.get("strategy").and_then(|s| s.as_str());
+        let delay = |field: &str| backoff.get(field).and_then(|d| d.as_u64());
+        text.push_str(&format!(", {} backoff", strategy.unwrap_or("unspecified")));
+        if let Some(initial) = delay("initial_delay_ms") {
+            text.push_str(&format!(" from {initial} ms"));
+        }
+        if let Some(max) = delay("max_delay_ms") {
+            text.push_str(&format!(" up to {max} ms"));
+        }
+    }
+    text
+}
+
+/// The section documenting `implementation.return_contract`.
+fn return_section(return_contract: &JsonValue, links: Links) -> String {
+    let mut section = "## Return contract\n\n".to_string();
+    if let Some(produced_by) = return_contract.get("produced_by") {
+        let field = |name: &str| produced_by.get(name).and_then(|v| v.as_str());
+        if let Some(phase) = field("phase") {
+            let port = field("port");
+            let target = port.map(|port| format!("output-{}", anchor(port)));
+            let port = port
+                .map(|port| format!("output {} of ", code(port)))
+                .unwrap_or_default();
+            let phase = links.to(phase, target.as_deref());
+            section.push_str(&format!("Produced by {port}phase {phase}.\n\n"));
+        }
+    }
+    if let Some(schema) = return_contract.get("schema") {
+        let yaml = serde_yaml::to_string(schema).unwrap_or_default();
+        section.push_str(&format!("```yaml\n{}\n```\n\n", yaml.trim_end()));
+    }
+    section
+}
+
+/// `(code, severity, description)` of the `errors` a contract declares.
+fn error_entries(contract: &JsonValue) -> impl Iterator<Item = (&str, Option<&str>, Option<&str>)> {
+    contract
+        .get("errors")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|error| {
+            let field = |name: &str| error.get(name).and_then(|v| v.as_str());
+            Some((field("code")?, field("severity"), field("description")))
+        })
+}
+
+/// The `type` of a port schema, or the `$ref` it points to.
+fn schema_type(schema: Option<&JsonValue>) -> String {
+    let Some(schema) = schema else {
+        return "—".to_string();
+    };
+    match (schema.get("type"), schema.get("$ref")) {
+        (Some(JsonValue::String(kind)), _) => kind.clone(),
+        (Some(JsonValue::Array(kinds)), _) => kinds
+            .iter()
+            .filter_map(|k| k.as_str())
+            .collect::<Vec<_>>()
+            .join(" \\| "),
+        (_, Some(JsonValue::String(reference))) => code(reference),
+        _ => "—".to_string(),
+    }
+}
+
+/// Where an input reads from: another phase's output or an instance or global path.
+fn source(source: Option<&JsonValue>, links: Links) -> String {
+    let Some(source) = source else {
+        return "—".to_string();
+    };
+    let field = |name: &str| source.get(name).and_then(|v| v.as_str());
+    match (field("kind"), field("phase"), field("path")) {
+        (Some("phase_output"), Some(phase), _) => {
+            let port = field("port");
+            let target = port.map(|port| format!("output-{}", anchor(port)));
+            format!(
+                "{} of {}",
+                port.map(code).unwrap_or("output".into()),
+                links.to(phase, target.as_deref())
+            )
+        }
+        (Some(kind), _, Some(path)) => format!("{kind} {}", code(path)),
+        (Some(kind), _, _) => kind.to_string(),
+        _ => "—".to_string(),
+    }
+}
+
+fn description(value: &JsonValue) -> String {
+    cell(
+        value
+            .get("description")
+            .and_then(|d| d.as_str())
+            .unwrap_or(""),
+    )
+}
+
+/// `text` made safe for a table cell.
+fn cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', " ")
+}
+
+fn code(text: &str) -> String {
+    format!("`{}`", text.replace('`', "'"))
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+    let items: Vec<String> = items.collect();
+    if items.is_empty() {
+        "—".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// The anchor GitHub and GitLab give a `### `name`` heading; also the file name of a phase page.
+fn anchor(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Generates the documentation of `files`: printed, written to `<output>/<file stem>.md` (with
+/// `per_phase`, plus `<output>/<file stem>/<phase>.md` for every phase), or, with `check`, compared
+/// with the pages already in `output`.
+pub fn generate(
+    args: &Args,
+    files: &[PathBuf],
+    output: Option<&Path>,
+    check: bool,
+    per_phase: bool,
+) -> ExitCode {
+    let mut failed = false;
+    let mut specs = Vec::new();
+    for file in files {
+        match load(args, file) {
+            Ok(doc) => specs.push((file, doc)),
+            Err(msg) => {
+                failln!("{msg}");
+                failed = true;
+            }
+        }
+    }
+    let Some(dir) = output else {
+        for (index, (_, doc)) in specs.iter().enumerate() {
+            if index > 0 {
+                outln!("");
+            }
+            outln!("{}", render(doc, Links::Sections).trim_end());
+        }
+        return ExitCode::from(u8::from(failed));
+    };
+
+    let stems: Vec<String> = specs
+        .iter()
+        .map(|(file, _)| {
+            file.file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "spec".to_string())
+        })
+        .collect();
+    let contexts: Vec<SpecContext> = specs.iter().map(|(_, doc)| SpecContext::new(doc)).collect();
+    for (index, (file, doc)) in specs.iter().enumerate() {
+        let stem = stems[index].as_str();
+        let target = dir.join(format!("{stem}.md"));
+        if !per_phase {
+            let page = render(doc, Links::Sections);
+            if check {
+                if check_page(&target, &page, file) {
+                    outln!("✅ {} is up to date.", target.display());
+                } else {
+                    failed = true;
+                }
+            } else if write_page(&target, &page) {
+                outln!(
+                    "📝 Wrote the documentation of {} to {}.",
+                    display_input(file),
+                    target.display()
+                );
+            } else {
+                failed = true;
+            }
+            continue;
+        }
+
+        let links = Links::Pages {
+            stem,
+            from_phase: false,
+        };
+        let others: Vec<_> = (0..specs.len())
+            .filter(|&other| other != index)
+            .map(|other| (stems[other].as_str(), &specs[other].1, &contexts[other]))
+            .collect();
+        let phase_dir = dir.join(stem);
+        let mut pages = vec![(target.clone(), render(doc, links))];
+        for (phase, contract) in phases(&contexts[index]) {
+            let page = phase_page(doc, &contexts[index], stem, phase, contract, &others);
+            pages.push((phase_dir.join(format!("{}.md", anchor(phase))), page));
+        }
+        let stale: Vec<PathBuf> = fs::read_dir(&phase_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "md"))
+            .filter(|path| !pages.iter().any(|(page, _)| page == path))
+            .collect();
+        let mut ok = true;
+        for (path, page) in &pages {
+            ok &= if check {
+                check_page(path, page, file)
+            } else {
+                write_page(path, page)
+            };
+        }
+        for path in &stale {
+            if check {
+                errln!(
+                    "❌ {} documents no phase of {}; run docs generate to remove it.",
+                    path.display(),
+                    display_input(file)
+                );
+                ok = false;
+            } else if let Err(e) = fs::remove_file(path) {
+                failln!("Error: failed to remove {}: {e}", path.display());
+                ok = false;
+            }
+        }
+        failed |= !ok;
+        if !ok {
+            continue;
+        }
+        if check {
+            outln!(
+                "✅ {} and its {} phase page(s) in {} are up to date.",
+                target.display(),
+                pages.len() - 1,
+                phase_dir.display()
+            );
+        } else {
+            outln!(
+                "📝 Wrote the documentation of {} to {} and {} phase page(s) to {}.",
+                display_input(file),
+                target.display(),
+                pages.len() - 1,
+                phase_dir.display()
+            );
+        }
+    }
+    ExitCode::from(u8::from(failed))
+}
+
+/// Compares `target` with the freshly generated `page` of `file`, reporting a difference.
+fn check_page(target: &Path, page: &str, file: &Path) -> bool {
+    match fs::read_to_string(target) {
+        Ok(existing) if existing == page => true,
+        Ok(_) => {
+            errln!(
+                "❌ {} is out of date with {}; run docs generate to update it.",
+                target.display(),
+                display_input(file)
+            );
+            false
+        }
+        Err(e) => {
+            errln!("❌ {} cannot be read: {e}", target.display());
+            false
+        }
+    }
+}
+
+fn write_page(target: &Path, page: &str) -> bool {
+    let dir = target.parent().unwrap_or(Path::new("."));
+    match fs::create_dir_all(dir).and_then(|()| fs::write(target, page)) {
+        Ok(()) => true,
+        Err(e) => {
+            failln!("Error: failed to write {}: {e}", target.display());
+            false
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! `doctor`: checks the environment the validator runs in rather than any spec. It reads the
+//! configuration file, resolves the version map from the current directory, compiles the schema of
+//! every known version, tries the on-disk cache and reaches every remote schema, and prints what it
+//! found with a suggested fix for every problem.
+
+use crate::{
+    cache,
+    diagnostics::Severity,
+    resolve_versions_map_path,
+    schemas::{is_url, map_target, ResolvedSchema, SchemaRequest},
+    Args,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+};
+
+/// The outcome of one check: `None` when it passed.
+struct Check {
+    severity: Option<Severity>,
+    area: &'static str,
+    message: String,
+    /// What to do about it.
+    fix: Option<&'static str>,
+}
+
+impl Check {
+    fn passed(area: &'static str, message: String) -> Self {
+        Self {
+            severity: None,
+            area,
+            message,
+            fix: None,
+        }
+    }
+
+    fn failed(severity: Severity, area: &'static str, message: String, fix: &'static str) -> Self {
+        Self {
+            severity: Some(severity),
+            area,
+            message,
+            fix: Some(fix),
+        }
+    }
+}
+
+/// Runs every check and prints the outcomes; exits with 1 when one found an error.
+pub fn doctor(args: &Args) -> ExitCode {
+    let mut checks = Vec::new();
+    check_config(args, &mut checks);
+    let map = check_version_map(args, &mut checks);
+    check_schemas(args, map.as_ref(), &mut checks);
+    check_cache(args, &mut checks);
+    check_network(args, map.as_ref(), &mut checks);
+
+    let mut failed = false;
+    for check in &checks {
+        let icon = check.severity.map_or("✅", Severity::icon);
+        outln!("{icon} {}: {}", check.area, check.message);
+        if let Some(fix) = check.fix {
+            outln!("   → {fix}");
+        }
+        failed |= check.severity == Some(Severity::Error);
+    }
+    ExitCode::from(if failed { 1 } else { 0 })
+}
+
+fn check_config(args: &Args, checks: &mut Vec<Check>) {
+    let area = "Configuration";
+    checks.push(match (&args.config_error, &args.settings.path) {
+        (Some(msg), _) => Check::failed(
+            Severity::Error,
+            area,
+            msg.trim_start_matches("Error: ").to_string(),
+            "fix the entry named above, or point --config at another file; the other checks ran \
+             with the default settings",
+        ),
+        (None, Some(path)) => Check::passed(area, format!("{} is valid", path.display())),
+        (None, None) => Check::passed(
+            area,
+            "no .program-verify.yaml in this directory or its parents; the defaults apply"
+                .to_string(),
+        ),
+    });
+}
+
+/// The version map found from the current directory, with its entries.
+type VersionMap = (PathBuf, BTreeMap<String, String>);
+
+fn check_version_map(args: &Args, checks: &mut Vec<Check>) -> Option<VersionMap> {
+    let area = "Version map";
+    let path = match resolve_versions_map_path(args.versions_map(), Path::new(".")) {
+        Ok(path) => path,
+        Err(msg) => {
+            let severity = match args.versions_map {
+                Some(_) => Severity::Error,
+                None => Severity::Warning,
+            };
+            checks.push(Check::failed(
+                severity,
+                area,
+                msg.trim_start_matches("Error: ").to_string(),
+                "run from the project root, or pass --versions-map (or set versions_map in the \
+                 configuration file); without a map only specs without a spec_version validate",
+            ));
+            return None;
+        }
+    };
+    let entries = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_yaml::from_str(&text).map_err(|e| e.to_string()));
+    match entries {
+        Ok(entries) => {
+            let entries: BTreeMap<String, String> = entries;
+            let message = format!("{} maps {} version(s)", path.display(), entries.len());
+            checks.push(Check::passed(area, message));
+            Some((path, entries))
+        }
+        Err(e) => {
+            checks.push(Check::failed(
+                Severity::Error,
+                area,
+                format!("{} is not a 'version: path' mapping: {e}", path.display()),
+                "write one entry per line, e.g. `v4.0.0: schemas/v4.json`",
+            ));
+            None
+        }
+    }
+}
+
+/// Compiles the schema of every version of the map, the `--schema` (or configured) schema and the
+/// embedded schema, as validation would.
+fn check_schemas(args: &Args, map: Option<&VersionMap>, checks: &mut Vec<Check>) {
+    let area = "Schemas";
+    let mut schemas: Vec<(String, Result<ResolvedSchema, String>)> = Vec::new();
+    if let Some((path, entries)) = map {
+        for (version, target) in entries {
+            let resolved = args.schemas.by_uri(&map_target(path, target));
+            schemas.push((format!("version {version} ({target})"), resolved));
+        }
+    }
+    if let Some(schema) = &args.schema {
+        let resolved = args.schemas.by_uri(&schema.to_string_lossy());
+        schemas.push((format!("--schema {}", schema.display()), resolved));
+    }
+    let request = SchemaRequest {
+        input: Path::new("."),
+        spec_version: None,
+    };
+    let embedded = args.schemas.resolve(None, &request);
+    schemas.push((
+        "the schema for specs without a spec_version".to_string(),
+        embedded,
+    ));
+
+    let mut compiled = 0;
+    for (label, resolved) in &schemas {
+        let error = match resolved {
+            Ok(resolved) => args.compiled_schema(resolved).err(),
+            Err(msg) => Some(msg.clone()),
+        };
+        match error {
+            Some(msg) => checks.push(Check::failed(
+                Severity::Error,
+                area,
+                format!("{label}: {}", msg.trim_start_matches("Error: ")),
+                "fix the schema file or its version map entry; specs of this version cannot be \
+                 validated until then",
+            )),
+            None => compiled += 1,
+        }
+    }
+    if compiled == schemas.len() {
+        let message = format!("all {compiled} schema(s) compile");
+        checks.push(Check::passed(area, message));
+    }
+}
+
+/// Writes and removes a file in the cache directory.
+fn check_cache(args: &Args, checks: &mut Vec<Check>) {
+    let area = "Cache";
+    if args.no_cache {
+        checks.push(Check::passed(area, "disabled by --no-cache".to_string()));
+        return;
+    }
+    let Some(root) = cache::cache_root() else {
+        checks.push(Check::failed(
+            Severity::Warning,
+            area,
+            "neither XDG_CACHE_HOME nor HOME is set, so nothing is cached".to_string(),
+            "set XDG_CACHE_HOME to a writable directory",
+        ));
+        return;
+    };
+    let probe = root.join(format!(".doctor.{}", process::id()));
+    let written = fs::create_dir_all(&root)
+        .and_then(|()| fs::write(&probe, b"probe"))
+        .and_then(|()| fs::remove_file(&probe));
+    checks.push(match written {
+        Ok(()) => Check::passed(area, format!("{} is writable", root.display())),
+        Err(e) => Check::failed(
+            Severity::Warning,
+            area,
+            format!("{} is not writable: {e}", root.display()),
+            "fix the directory's permissions or set XDG_CACHE_HOME to a writable directory; \
+             until then schemas are parsed on every run and --offline has no downloads to use",
+        ),
+    });
+}
+
+/// Downloads every remote schema of the map and `--schema`, bypassing the downloaded copies.
+fn check_network(args: &Args, map: Option<&VersionMap>, checks: &mut Vec<Check>) {
+    let area = "Network";
+    let mut urls: Vec<String> = map
+        .into_iter()
+        .flat_map(|(_, entries)| entries.values())
+        .filter(|target| is_url(target))
+        .cloned()
+        .collect();
+    if let Some(schema) = args.schema.as_ref().and_then(|s| s.to_str()) {
+        if is_url(schema) {
+            urls.push(schema.to_string());
+        }
+    }
+    urls.sort();
+    urls.dedup();
+    if urls.is_empty() {
+        checks.push(Check::passed(
+            area,
+            "no remote schemas to reach".to_string(),
+        ));
+        return;
+    }
+    if args.offline {
+        let message = format!(
+            "--offline is set; {} remote schema(s) are read from earlier downloads",
+            urls.len()
+        );
+        checks.push(Check::passed(area, message));
+        return;
+    }
+    for url in urls {
+        checks.push(match args.fetcher.reach(&url) {
+            Ok(()) => Check::passed(area, format!("{url} is reachable")),
+            Err(e) => Check::failed(
+                Severity::Error,
+                area,
+                format!("cannot download {url}: {e}"),
+                "check the URL, the proxy settings (HTTPS_PROXY) and --fetch-timeout; with copies \
+                 downloaded earlier, --offline works without the network",
+            ),
+        });
+    }
+}
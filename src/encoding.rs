@@ -0,0 +1,175 @@
+//! Decoding of the files the validator reads. Specs authored on Windows often start with a byte
+//! order mark or are saved as UTF-16; such files are transcoded to UTF-8, and bytes that are not
+//! valid UTF-8 are reported with their offset rather than as a generic read or parse failure.
+
+use std::{fmt, fs, io, path::Path};
+
+/// An encoding other than plain UTF-8 that a file was decoded from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 preceded by a byte order mark.
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8Bom => "UTF-8 with a byte order mark",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+        })
+    }
+}
+
+/// The text of a file and the encoding it was transcoded from, if not plain UTF-8.
+pub struct Decoded {
+    pub text: String,
+    pub encoding: Option<Encoding>,
+}
+
+/// Warns that the file `origin` was decoded from `encoding`.
+pub fn warn_converted(origin: &str, encoding: Encoding) {
+    errln!(
+        "⚠️ {origin} is encoded in {encoding}; it was converted to UTF-8 for reading. Save it as UTF-8 without a byte order mark."
+    );
+}
+
+/// Reads `path` and [decodes](decode) it. The error is the reason only; callers say what was
+/// being read.
+pub fn read(path: &Path) -> Result<Decoded, String> {
+    decode(fs::read(path).map_err(|e| e.to_string())?)
+}
+
+/// Reads `reader` to the end and [decodes](decode) it.
+pub fn read_from(mut reader: impl io::Read) -> Result<Decoded, String> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    decode(bytes)
+}
+
+/// Decodes `bytes` as UTF-8, or as UTF-16 or UTF-32 when a byte order mark says so. UTF-16 without
+/// a byte order mark is recognized by the zero bytes of its ASCII characters.
+pub fn decode(bytes: Vec<u8>) -> Result<Decoded, String> {
+    let (encoding, bom) = match bytes.as_slice() {
+        [0xEF, 0xBB, 0xBF, ..] => (Some(Encoding::Utf8Bom), 3),
+        [0xFF, 0xFE, 0, 0, ..] => (Some(Encoding::Utf32Le), 4),
+        [0, 0, 0xFE, 0xFF, ..] => (Some(Encoding::Utf32Be), 4),
+        [0xFF, 0xFE, ..] => (Some(Encoding::Utf16Le), 2),
+        [0xFE, 0xFF, ..] => (Some(Encoding::Utf16Be), 2),
+        _ => (utf16_without_bom(&bytes), 0),
+    };
+    let body = &bytes[bom..];
+    let text = match encoding {
+        None | Some(Encoding::Utf8Bom) => match std::str::from_utf8(body) {
+            Ok(text) => text.to_string(),
+            Err(e) => return Err(invalid_utf8(body, e, bom)),
+        },
+        Some(Encoding::Utf16Le | Encoding::Utf16Be) => {
+            let big_endian = encoding == Some(Encoding::Utf16Be);
+            if !body.len().is_multiple_of(2) {
+                return Err(format!(
+                    "the file looks like {} but ends in the middle of a character",
+                    encoding.unwrap()
+                ));
+            }
+            let units = body.chunks_exact(2).map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if big_endian {
+                    u16::from_be_bytes(pair)
+                } else {
+                    u16::from_le_bytes(pair)
+                }
+            });
+            let mut text = String::with_capacity(body.len() / 2);
+            for (index, unit) in char::decode_utf16(units).enumerate() {
+                match unit {
+                    Ok(c) => text.push(c),
+                    Err(e) => {
+                        return Err(format!(
+                            "unpaired surrogate 0x{:04X} at byte offset {} is not valid {}",
+                            e.unpaired_surrogate(),
+                            bom + 2 * index,
+                            encoding.unwrap()
+                        ))
+                    }
+                }
+            }
+            text
+        }
+        Some(Encoding::Utf32Le | Encoding::Utf32Be) => {
+            let big_endian = encoding == Some(Encoding::Utf32Be);
+            if !body.len().is_multiple_of(4) {
+                return Err(format!(
+                    "the file looks like {} but ends in the middle of a character",
+                    encoding.unwrap()
+                ));
+            }
+            let mut text = String::with_capacity(body.len() / 4);
+            for (index, quad) in body.chunks_exact(4).enumerate() {
+                let quad = [quad[0], quad[1], quad[2], quad[3]];
+                let value = if big_endian {
+                    u32::from_be_bytes(quad)
+                } else {
+                    u32::from_le_bytes(quad)
+                };
+                match char::from_u32(value) {
+                    Some(c) => text.push(c),
+                    None => {
+                        return Err(format!(
+                            "0x{value:08X} at byte offset {} is not a valid {} character",
+                            bom + 4 * index,
+                            encoding.unwrap()
+                        ))
+                    }
+                }
+            }
+            text
+        }
+    };
+    Ok(Decoded { text, encoding })
+}
+
+/// UTF-16 without a byte order mark: the first characters of a spec are ASCII, so every other
+/// byte is zero.
+fn utf16_without_bom(bytes: &[u8]) -> Option<Encoding> {
+    let pairs: Vec<&[u8]> = bytes.chunks_exact(2).take(32).collect();
+    if pairs.len() < 2 {
+        return None;
+    }
+    if pairs.iter().all(|pair| pair[0] != 0 && pair[1] == 0) {
+        Some(Encoding::Utf16Le)
+    } else if pairs.iter().all(|pair| pair[0] == 0 && pair[1] != 0) {
+        Some(Encoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// Where UTF-8 decoding of `body` (which started `skipped` bytes into the file) failed.
+fn invalid_utf8(body: &[u8], error: std::str::Utf8Error, skipped: usize) -> String {
+    let offset = error.valid_up_to();
+    let valid = std::str::from_utf8(&body[..offset]).unwrap_or_default();
+    let line = valid.matches('\n').count() + 1;
+    let column = valid
+        .rsplit('\n')
+        .next()
+        .map_or(0, |last| last.chars().count())
+        + 1;
+    let length = error.error_len().unwrap_or(body.len() - offset).min(4);
+    let bytes: Vec<String> = body[offset..offset + length]
+        .iter()
+        .map(|byte| format!("0x{byte:02X}"))
+        .collect();
+    format!(
+        "invalid UTF-8 at byte offset {} (line {line}, column {column}): {}; the file may be saved \
+         in a legacy encoding such as Windows-1252, save it as UTF-8",
+        skipped + offset,
+        bytes.join(" ")
+    )
+}
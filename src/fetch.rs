@@ -0,0 +1,77 @@
+//! Downloads over HTTP(S): schemas, their `$ref` targets and contract libraries. Every request is
+//! bounded by `--fetch-timeout`, and every download is kept on disk: `--offline` runs use those
+//! copies instead of the network, and so do online runs while the server cannot be reached.
+
+use crate::cache::Downloads;
+use std::time::Duration;
+
+/// `--fetch-timeout` default.
+pub const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the run may reach the network.
+#[derive(Clone, Debug)]
+pub struct Fetcher {
+    timeout: Duration,
+    /// Never touch the network; only the downloaded copies are used.
+    offline: bool,
+    downloads: Downloads,
+}
+
+impl Default for Fetcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_FETCH_TIMEOUT, false, Downloads::disabled())
+    }
+}
+
+impl Fetcher {
+    pub fn new(timeout: Duration, offline: bool, downloads: Downloads) -> Self {
+        Self {
+            timeout,
+            offline,
+            downloads,
+        }
+    }
+
+    /// The body of `url`. The error is the reason only; callers say what was being fetched.
+    pub fn get(&self, url: &str) -> Result<String, String> {
+        if self.offline {
+            return self
+                .downloads
+                .get(url)
+                .ok_or_else(|| "--offline is set and it was never downloaded before".to_string());
+        }
+        match self.download(url) {
+            Ok(text) => {
+                self.downloads.store(url, &text);
+                Ok(text)
+            }
+            Err(reason) => {
+                match self.downloads.get(url) {
+                    Some(text) => {
+                        errln!("⚠️ Failed to fetch {url} ({reason}); using the copy downloaded earlier.");
+                        Ok(text)
+                    }
+                    None => Err(reason),
+                }
+            }
+        }
+    }
+
+    /// Whether `url` can be downloaded now; downloaded copies are neither used nor updated.
+    pub fn reach(&self, url: &str) -> Result<(), String> {
+        self.download(url).map(drop)
+    }
+
+    fn download(&self, url: &str) -> Result<String, String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| format!("failed to prepare HTTP client: {e}"))?;
+        client
+            .get(url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .map_err(|e| e.to_string())
+    }
+}
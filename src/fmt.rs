@@ -0,0 +1,195 @@
+//! `fmt`: normalizes the whitespace of spec files so that contributions from different platforms
+//! do not churn diffs — line endings as configured (`line_endings`, LF by default), no trailing
+//! whitespace and a final newline. Files read through a byte order mark or as UTF-16/UTF-32 are
+//! written back as plain UTF-8.
+//!
+//! Trailing whitespace can be part of a value (YAML block scalars, TOML multi-line strings); when
+//! stripping it would change what the file parses to, it is left in place.
+
+use crate::{
+    config::LineEnding, display_input, encoding, expand_inputs, is_stdin, parse_documents, Args,
+    InputFormat,
+};
+use std::{fs, path::PathBuf, process::ExitCode};
+
+/// What normalizing one file changed.
+#[derive(Default)]
+struct Changes {
+    /// Lines that ended with the other line ending.
+    line_endings: usize,
+    /// 1-based numbers of the lines with trailing whitespace, stripped or kept.
+    trailing: Vec<usize>,
+    missing_final_newline: bool,
+}
+
+/// `text` with every line ending in `eol`, without trailing whitespace except on the lines in `keep`,
+/// and with a final newline.
+fn normalize(text: &str, eol: LineEnding, keep: &[usize]) -> (String, Changes) {
+    let mut changes = Changes::default();
+    if text.is_empty() {
+        return (String::new(), changes);
+    }
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    // What follows the last line break; empty when the file ends with one.
+    let last = lines.pop().unwrap_or_default();
+    let mut out = String::with_capacity(text.len());
+    let mut push = |number: usize, line: &str, ended: Option<bool>| {
+        let crlf = ended == Some(true);
+        let line = if crlf { &line[..line.len() - 1] } else { line };
+        if ended.is_some() && crlf != (eol == LineEnding::Crlf) {
+            changes.line_endings += 1;
+        }
+        let trimmed = line.trim_end_matches([' ', '\t']);
+        let line = if trimmed.len() < line.len() {
+            changes.trailing.push(number);
+            if keep.contains(&number) {
+                line
+            } else {
+                trimmed
+            }
+        } else {
+            line
+        };
+        out.push_str(line);
+        out.push_str(eol.as_str());
+    };
+    for (index, line) in lines.iter().enumerate() {
+        push(index + 1, line, Some(line.ends_with('\r')));
+    }
+    if !last.is_empty() {
+        changes.missing_final_newline = true;
+        push(lines.len() + 1, last, None);
+    }
+    (out, changes)
+}
+
+/// Normalizes `paths` (directories are searched like validation inputs) in place, or with `check`
+/// only reports the files that need it.
+pub fn fmt(args: &Args, paths: &[PathBuf], check: bool) -> ExitCode {
+    let files = match expand_inputs(paths) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    if files.iter().any(|file| is_stdin(file)) {
+        failln!("Error: fmt rewrites files in place and cannot read standard input");
+        return ExitCode::from(1);
+    }
+    let files: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|file| !args.settings.is_excluded(file))
+        .collect();
+    let eol = args.settings.line_endings.unwrap_or_default();
+
+    let (mut failed, mut unformatted) = (false, 0);
+    for file in &files {
+        let name = display_input(file);
+        let decoded = match encoding::read(file) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                failln!("Error: failed to read file {name}: {e}");
+                failed = true;
+                continue;
+            }
+        };
+        let text = decoded.text;
+        let format = args
+            .input_format
+            .unwrap_or_else(|| InputFormat::from_path(file));
+        let parsed = match parse_documents(&text, format) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                failln!("Error: cannot format {name}: {e}");
+                failed = true;
+                continue;
+            }
+        };
+        let unchanged = |text: &str| parse_documents(text, format).ok().as_ref() == Some(&parsed);
+        let (mut formatted, mut changes) = normalize(&text, eol, &[]);
+        let mut kept = Vec::new();
+        if !changes.trailing.is_empty() && !unchanged(&formatted) {
+            // Find the lines whose trailing whitespace is part of a value, one at a time.
+            kept = changes.trailing.clone();
+            for line in &changes.trailing {
+                let candidate: Vec<usize> = kept.iter().copied().filter(|l| l != line).collect();
+                if unchanged(&normalize(&text, eol, &candidate).0) {
+                    kept = candidate;
+                }
+            }
+            (formatted, changes) = normalize(&text, eol, &kept);
+        }
+        let stripped: Vec<usize> = changes
+            .trailing
+            .iter()
+            .copied()
+            .filter(|line| !kept.contains(line))
+            .collect();
+
+        let mut problems = Vec::new();
+        if let Some(encoding) = decoded.encoding {
+            problems.push(format!("encoded in {encoding}"));
+        }
+        if changes.line_endings > 0 {
+            problems.push(format!(
+                "{} line(s) not ending in {}",
+                changes.line_endings,
+                eol.name()
+            ));
+        }
+        if !stripped.is_empty() {
+            let mut lines: Vec<String> = stripped.iter().take(5).map(usize::to_string).collect();
+            if stripped.len() > 5 {
+                lines.push("…".to_string());
+            }
+            problems.push(format!(
+                "trailing whitespace on {} line(s) ({})",
+                stripped.len(),
+                lines.join(", ")
+            ));
+        }
+        if changes.missing_final_newline {
+            problems.push("no newline at end of file".to_string());
+        }
+        if !kept.is_empty() {
+            outln!(
+                "⚠️ {name}: trailing whitespace on {} line(s) ({}) is part of a value and is kept.",
+                kept.len(),
+                kept.iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        if problems.is_empty() {
+            continue;
+        }
+        unformatted += 1;
+        let problems = problems.join("; ");
+        if check {
+            errln!("❌ {name}: {problems}.");
+            continue;
+        }
+        match fs::write(file, formatted) {
+            Ok(()) => outln!("📝 Formatted {name}: {problems}."),
+            Err(e) => {
+                failln!("Error: failed to write {name}: {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if check && unformatted > 0 {
+        errln!(
+            "❌ {unformatted} of {} file(s) need formatting; run fmt to fix them.",
+            files.len()
+        );
+        failed = true;
+    } else if check {
+        outln!("✅ All {} file(s) are formatted.", files.len());
+    } else {
+        outln!("✅ Formatted {unformatted} of {} file(s).", files.len());
+    }
+    ExitCode::from(u8::from(failed))
+}
@@ -0,0 +1,141 @@
+//! `format` implementations for domain strings that JSON Schema does not define (or, for
+//! `duration`, only defines from draft 2019-09 on): `duration`, `identifier`, `semver` and `cron`.
+//! The `custom_formats` configuration setting picks which of them are enabled; all are by default.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A custom `format`: its name and check.
+pub type CustomFormat = (&'static str, fn(&str) -> bool);
+
+/// Every custom format, in documentation order.
+pub const CUSTOM_FORMATS: [CustomFormat; 4] = [
+    ("duration", is_duration),
+    ("identifier", is_identifier),
+    ("semver", is_semver),
+    ("cron", is_cron),
+];
+
+/// The custom formats named in `names`, or all of them when `names` is `None`.
+pub fn enabled(names: Option<&[String]>) -> Result<Vec<CustomFormat>, String> {
+    let Some(names) = names else {
+        return Ok(CUSTOM_FORMATS.to_vec());
+    };
+    names
+        .iter()
+        .map(|name| {
+            CUSTOM_FORMATS
+                .iter()
+                .find(|(known, _)| known == name)
+                .copied()
+                .ok_or_else(|| {
+                    let known: Vec<&str> = CUSTOM_FORMATS.iter().map(|(n, _)| *n).collect();
+                    format!(
+                        "Error: unknown custom format '{name}' (known formats: {})",
+                        known.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+/// ISO 8601 duration: `P3D`, `PT1H30M`, `PT0.5S`, `P2W`. At least one component is required, and at
+/// least one time component after `T`.
+fn is_duration(text: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^P(\d+Y)?(\d+M)?(\d+W)?(\d+D)?(T(\d+H)?(\d+M)?(\d+([.,]\d+)?S)?)?$").unwrap()
+    });
+    let Some(captures) = re.captures(text) else {
+        return false;
+    };
+    let date = (1..=4).any(|i| captures.get(i).is_some());
+    let time = (6..=8).any(|i| captures.get(i).is_some());
+    match captures.get(5) {
+        Some(_) => time,
+        None => date,
+    }
+}
+
+/// snake_case identifier: lowercase words of letters and digits joined by single underscores,
+/// starting with a letter, e.g. `collect_issue`.
+fn is_identifier(text: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^[a-z][a-z0-9]*(_[a-z0-9]+)*$").unwrap())
+        .is_match(text)
+}
+
+/// Semantic Version 2.0.0, optionally prefixed with `v` as spec versions are (`v4.0.0`).
+fn is_semver(text: &str) -> bool {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^v?(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(-((0|[1-9]\d*|\d*[A-Za-z-][0-9A-Za-z-]*)(\.(0|[1-9]\d*|\d*[A-Za-z-][0-9A-Za-z-]*))*))?(\+[0-9A-Za-z-]+(\.[0-9A-Za-z-]+)*)?$",
+        )
+        .unwrap()
+    })
+    .is_match(text)
+}
+
+/// Five-field cron expression (minute, hour, day of month, month, day of week) with `*`, lists,
+/// ranges, steps and month/weekday names, or one of the `@hourly`-style macros.
+fn is_cron(text: &str) -> bool {
+    const MACROS: [&str; 7] = [
+        "@yearly",
+        "@annually",
+        "@monthly",
+        "@weekly",
+        "@daily",
+        "@midnight",
+        "@hourly",
+    ];
+    const MONTHS: [&str; 12] = [
+        "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+    ];
+    const DAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+    if MACROS.contains(&text) {
+        return true;
+    }
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    let limits: [(u32, u32, &[&str]); 5] = [
+        (0, 59, &[]),
+        (0, 23, &[]),
+        (1, 31, &[]),
+        (1, 12, &MONTHS),
+        (0, 7, &DAYS),
+    ];
+    fields.len() == 5
+        && fields
+            .iter()
+            .zip(limits)
+            .all(|(field, (min, max, names))| is_cron_field(field, min, max, names))
+}
+
+/// One cron field: a comma-separated list of `*`, `N` or `N-M`, each optionally followed by `/STEP`.
+/// `names` stand for the values starting at `min` (months from 1, weekdays from 0).
+fn is_cron_field(field: &str, min: u32, max: u32, names: &[&str]) -> bool {
+    let value = |text: &str| -> Option<u32> {
+        let number = match text.parse::<u32>() {
+            Ok(number) => number,
+            Err(_) => {
+                let upper = text.to_ascii_uppercase();
+                min + names.iter().position(|name| *name == upper)? as u32
+            }
+        };
+        (min..=max).contains(&number).then_some(number)
+    };
+    field.split(',').all(|item| {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (item, None),
+        };
+        let step_ok = step.is_none_or(|s| s.parse::<u32>().is_ok_and(|s| s > 0));
+        let range_ok = match range.split_once('-') {
+            _ if range == "*" => true,
+            Some((low, high)) => matches!((value(low), value(high)), (Some(l), Some(h)) if l <= h),
+            None => value(range).is_some(),
+        };
+        step_ok && range_ok
+    })
+}
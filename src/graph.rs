@@ -0,0 +1,731 @@
+//! `graph export` renders `algorithm.graph` together with the dataflow between phases implied by
+//! the `phase_output` sources of `implementation.phase_contracts`, for visualizing a pipeline.
+//!
+//! References that do not resolve (edges to undeclared nodes, inputs reading from unknown phases
+//! or undeclared output ports) are kept in the picture and highlighted.
+//!
+//! `graph order` prints the phases in an order that respects the same dependencies, for schedulers.
+
+use crate::{
+    context::{SpecContext, Vertex},
+    diff::load,
+    display_input,
+    html::escape,
+    rules, Args,
+};
+use clap::ValueEnum;
+use serde_json::Value as JsonValue;
+use std::{collections::HashMap, fs, path::Path, process::ExitCode};
+
+/// Pixel sizes of the SVG layout.
+const MARGIN: usize = 30;
+const NODE_HEIGHT: usize = 44;
+const ROW: usize = 80;
+const GAP: usize = 70;
+
+/// Output format of `graph export`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT, for `dot -Tsvg`.
+    #[default]
+    Dot,
+    /// A fenced Mermaid `flowchart` block, for Markdown docs rendered by GitHub or GitLab.
+    Mermaid,
+    /// A standalone SVG image, laid out by the validator itself.
+    Svg,
+}
+
+/// A node of the exported graph.
+struct Node {
+    id: String,
+    /// Node `type` from the graph (`phase`, `if`, `loop`, `end`, …).
+    kind: String,
+    /// Phase the node runs, for phase nodes.
+    phase: Option<String>,
+    entry: bool,
+    /// Referenced but never declared.
+    unresolved: bool,
+}
+
+/// How an edge connects two nodes.
+enum Flow {
+    /// A graph edge, with its `kind` (`normal`, `failure`, `fallback`, …).
+    Control(String),
+    /// A phase input reading another phase's output.
+    Data,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    flow: Flow,
+    label: Option<String>,
+    unresolved: bool,
+}
+
+/// Nodes and edges of a spec's pipeline.
+struct Graph {
+    name: String,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Graph {
+    fn from_spec(doc: &JsonValue) -> Self {
+        let context = SpecContext::new(doc);
+        let mut graph = Graph {
+            name: doc
+                .pointer("/algorithm/name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("algorithm")
+                .to_string(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        let entry = context.graph.entry;
+
+        for node in &context.graph.nodes {
+            graph.nodes.push(Node {
+                id: node.id.to_string(),
+                kind: node.kind.unwrap_or("phase").to_string(),
+                phase: node.phase.map(str::to_string),
+                entry: entry == Some(node.id),
+                unresolved: false,
+            });
+        }
+        // Specs without a graph (or with phases missing from it) still show every phase.
+        for phase in context.phases.iter().filter(|phase| phase.listed()) {
+            let phase = phase.name;
+            if graph.phase_node(phase).is_none() && graph.node(phase).is_none() {
+                graph.nodes.push(Node {
+                    id: phase.to_string(),
+                    kind: "phase".to_string(),
+                    phase: Some(phase.to_string()),
+                    entry: entry == Some(phase),
+                    unresolved: false,
+                });
+            }
+        }
+
+        for edge in &context.graph.edges {
+            let (from, to) = (edge.from.unwrap_or("?"), edge.to.unwrap_or("?"));
+            let kind = edge.kind.unwrap_or("normal");
+            let unresolved = graph.node(from).is_none() || graph.node(to).is_none();
+            let (from, to) = (graph.resolve(from), graph.resolve(to));
+            graph.edges.push(Edge {
+                from,
+                to,
+                label: (kind != "normal").then(|| kind.to_string()),
+                flow: Flow::Control(kind.to_string()),
+                unresolved,
+            });
+        }
+
+        for flow in &context.dataflow {
+            let port = flow.port.unwrap_or("?");
+            let declared = flow
+                .port
+                .is_some_and(|port| context.output(flow.producer, port).is_some());
+            let known_producer = graph.phase_node(flow.producer).is_some();
+            let from = graph.resolve_phase(flow.producer);
+            let to = graph.resolve_phase(flow.consumer);
+            let mut label = if port == flow.input {
+                port.to_string()
+            } else {
+                format!("{port} → {}", flow.input)
+            };
+            if known_producer && !declared {
+                label.push_str(" (undeclared)");
+            }
+            graph.edges.push(Edge {
+                from,
+                to,
+                flow: Flow::Data,
+                label: Some(label),
+                unresolved: !known_producer || !declared,
+            });
+        }
+        graph
+    }
+
+    fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// The node running `phase`.
+    fn phase_node(&self, phase: &str) -> Option<&Node> {
+        self.nodes
+            .iter()
+            .find(|node| node.phase.as_deref() == Some(phase))
+    }
+
+    /// `id`, adding an unresolved node for it when no node has it.
+    fn resolve(&mut self, id: &str) -> String {
+        if self.node(id).is_none() {
+            self.nodes.push(Node {
+                id: id.to_string(),
+                kind: "unknown".to_string(),
+                phase: None,
+                entry: false,
+                unresolved: true,
+            });
+        }
+        id.to_string()
+    }
+
+    /// The id of the node running `phase`, adding an unresolved node when none does.
+    fn resolve_phase(&mut self, phase: &str) -> String {
+        match self.phase_node(phase) {
+            Some(node) => node.id.clone(),
+            None => self.resolve(phase),
+        }
+    }
+
+    /// Removes the declared nodes that do not run a phase (`if`, `loop`, `end`, …), connecting each
+    /// of their predecessors to each of their successors. Unresolved nodes are kept.
+    fn collapse(&mut self) {
+        while let Some(index) = self
+            .nodes
+            .iter()
+            .position(|node| node.phase.is_none() && !node.unresolved)
+        {
+            let id = self.nodes.remove(index).id;
+            let (touching, mut edges): (Vec<Edge>, Vec<Edge>) = std::mem::take(&mut self.edges)
+                .into_iter()
+                .partition(|edge| edge.from == id || edge.to == id);
+            for incoming in touching
+                .iter()
+                .filter(|edge| edge.to == id && edge.from != id)
+            {
+                for outgoing in touching
+                    .iter()
+                    .filter(|edge| edge.from == id && edge.to != id)
+                {
+                    // The kind of the branch taken out of the node says more than the way in.
+                    let kind = match (&outgoing.flow, &incoming.flow) {
+                        (Flow::Control(kind), _) if kind != "normal" => kind.clone(),
+                        (_, Flow::Control(kind)) => kind.clone(),
+                        _ => "normal".to_string(),
+                    };
+                    let duplicate = edges.iter().any(|edge| {
+                        edge.from == incoming.from
+                            && edge.to == outgoing.to
+                            && matches!(&edge.flow, Flow::Control(k) if *k == kind)
+                    });
+                    if !duplicate {
+                        edges.push(Edge {
+                            from: incoming.from.clone(),
+                            to: outgoing.to.clone(),
+                            label: (kind != "normal").then(|| kind.clone()),
+                            flow: Flow::Control(kind),
+                            unresolved: incoming.unresolved || outgoing.unresolved,
+                        });
+                    }
+                }
+            }
+            self.edges = edges;
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = format!("digraph {} {{\n", quote(&self.name));
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [shape=box, style=rounded, fontname=\"Helvetica\"];\n");
+        dot.push_str("  edge [fontname=\"Helvetica\", fontsize=10];\n");
+        for node in &self.nodes {
+            let mut label = node.id.clone();
+            match (&node.phase, node.kind.as_str()) {
+                (Some(phase), _) if *phase != node.id => {
+                    label.push_str(&format!("\nphase: {phase}"))
+                }
+                (Some(_), _) => {}
+                (None, "unknown") => label.push_str("\n(unresolved)"),
+                (None, kind) => label.push_str(&format!("\n({kind})")),
+            }
+            let mut attributes = vec![format!("label={}", quote(&label))];
+            let shape = match node.kind.as_str() {
+                "phase" | "unknown" => None,
+                "if" | "switch" => Some("diamond"),
+                "loop" => Some("hexagon"),
+                "parallel" | "join" => Some("parallelogram"),
+                "end" => Some("doublecircle"),
+                _ => Some("ellipse"),
+            };
+            if let Some(shape) = shape {
+                attributes.push(format!("shape={shape}"));
+            }
+            if node.entry {
+                attributes.push("penwidth=2".to_string());
+            }
+            if node.unresolved {
+                attributes.push("style=\"rounded,dashed\", color=red, fontcolor=red".to_string());
+            }
+            dot.push_str(&format!(
+                "  {} [{}];\n",
+                quote(&node.id),
+                attributes.join(", ")
+            ));
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label={}", quote(label)));
+            }
+            match &edge.flow {
+                Flow::Data => attributes.push("style=dotted".to_string()),
+                Flow::Control(kind) if kind == "failure" || kind == "fallback" => {
+                    attributes.push("style=dashed".to_string())
+                }
+                Flow::Control(_) => {}
+            }
+            let color = match edge.flow {
+                _ if edge.unresolved => Some("red"),
+                Flow::Data => Some("blue"),
+                Flow::Control(_) => None,
+            };
+            if let Some(color) = color {
+                attributes.push(format!("color={color}, fontcolor={color}"));
+            }
+            if edge.unresolved {
+                attributes.push("penwidth=2".to_string());
+            }
+            let attributes = if attributes.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attributes.join(", "))
+            };
+            dot.push_str(&format!(
+                "  {} -> {}{attributes};\n",
+                quote(&edge.from),
+                quote(&edge.to)
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_mermaid(&self) -> String {
+        // Node ids are numbered: graph ids may be Mermaid keywords such as `end`.
+        let index = |id: &str| self.nodes.iter().position(|node| node.id == id);
+        let mut mermaid = String::from("```mermaid\n---\n");
+        mermaid.push_str(&format!(
+            "title: {}\n---\nflowchart LR\n",
+            mermaid_text(&self.name)
+        ));
+        for (i, node) in self.nodes.iter().enumerate() {
+            let mut label = mermaid_text(&node.id);
+            match (&node.phase, node.kind.as_str()) {
+                (Some(phase), _) if *phase != node.id => {
+                    label.push_str(&format!("<br/>phase: {}", mermaid_text(phase)))
+                }
+                (Some(_), _) => {}
+                (None, "unknown") => label.push_str("<br/>(unresolved)"),
+                (None, kind) => label.push_str(&format!("<br/>({})", mermaid_text(kind))),
+            }
+            let (open, close) = match node.kind.as_str() {
+                "phase" | "unknown" => ("(", ")"),
+                "if" | "switch" => ("{", "}"),
+                "loop" => ("{{", "}}"),
+                "parallel" | "join" => ("[/", "/]"),
+                "end" => ("(((", ")))"),
+                _ => ("([", "])"),
+            };
+            mermaid.push_str(&format!("  n{i}{open}\"{label}\"{close}\n"));
+        }
+        let (mut unresolved, mut data) = (Vec::new(), Vec::new());
+        for (i, edge) in self.edges.iter().enumerate() {
+            let arrow = match &edge.flow {
+                Flow::Control(kind) if kind == "failure" || kind == "fallback" => "-.->",
+                Flow::Control(_) => "-->",
+                Flow::Data => "-.->",
+            };
+            let label = match &edge.label {
+                Some(label) => format!("|\"{}\"|", mermaid_text(label)),
+                None => String::new(),
+            };
+            mermaid.push_str(&format!(
+                "  n{} {arrow}{label} n{}\n",
+                index(&edge.from).unwrap_or_default(),
+                index(&edge.to).unwrap_or_default()
+            ));
+            match edge.flow {
+                _ if edge.unresolved => unresolved.push(i.to_string()),
+                Flow::Data => data.push(i.to_string()),
+                Flow::Control(_) => {}
+            }
+        }
+        for (links, style) in [
+            (data, "stroke:blue,color:blue"),
+            (unresolved, "stroke:red,color:red,stroke-width:2px"),
+        ] {
+            if !links.is_empty() {
+                mermaid.push_str(&format!("  linkStyle {} {style}\n", links.join(",")));
+            }
+        }
+        for (class, style, member) in [
+            (
+                "entry",
+                "stroke-width:3px",
+                (|node: &Node| node.entry) as fn(&Node) -> bool,
+            ),
+            (
+                "unresolved",
+                "stroke:red,color:red,stroke-dasharray:5 5",
+                |node: &Node| node.unresolved,
+            ),
+        ] {
+            let members: Vec<String> = self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| member(node))
+                .map(|(i, _)| format!("n{i}"))
+                .collect();
+            if !members.is_empty() {
+                mermaid.push_str(&format!("  classDef {class} {style}\n"));
+                mermaid.push_str(&format!("  class {} {class}\n", members.join(",")));
+            }
+        }
+        mermaid.push_str("```\n");
+        mermaid
+    }
+
+    /// The column of every node: control edges point right except those closing a cycle, found by
+    /// a depth-first walk from the entry node. Data edges do not move nodes.
+    fn ranks(&self) -> Vec<usize> {
+        let index = |id: &str| self.nodes.iter().position(|node| node.id == id);
+        let control: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .filter(|edge| matches!(edge.flow, Flow::Control(_)))
+            .filter_map(|edge| Some((index(&edge.from)?, index(&edge.to)?)))
+            .collect();
+        // 0: unvisited, 1: on the walk's path, 2: done.
+        let mut state = vec![0u8; self.nodes.len()];
+        let mut forward = Vec::new();
+        let entry = self.nodes.iter().position(|node| node.entry);
+        for start in entry.into_iter().chain(0..self.nodes.len()) {
+            if state[start] != 0 {
+                continue;
+            }
+            state[start] = 1;
+            let mut path = vec![(start, 0)];
+            while let Some((node, next)) = path.last_mut() {
+                let node = *node;
+                match control[*next..].iter().position(|&(from, _)| from == node) {
+                    Some(offset) => {
+                        let edge = *next + offset;
+                        *next = edge + 1;
+                        let to = control[edge].1;
+                        if state[to] == 1 {
+                            continue;
+                        }
+                        forward.push(control[edge]);
+                        if state[to] == 0 {
+                            state[to] = 1;
+                            path.push((to, 0));
+                        }
+                    }
+                    None => {
+                        state[node] = 2;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        let mut ranks = vec![0; self.nodes.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(from, to) in &forward {
+                if ranks[to] < ranks[from] + 1 {
+                    ranks[to] = ranks[from] + 1;
+                    changed = true;
+                }
+            }
+        }
+        ranks
+    }
+
+    /// The graph as an SVG image; `ids` prefixes its element ids, which must be unique in a page
+    /// holding several images.
+    fn to_svg(&self, ids: &str) -> String {
+        let labels: Vec<(String, Option<String>)> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let detail = match (&node.phase, node.kind.as_str()) {
+                    (Some(phase), _) if *phase != node.id => Some(format!("phase: {phase}")),
+                    (Some(_), _) => None,
+                    (None, "unknown") => Some("(unresolved)".to_string()),
+                    (None, kind) => Some(format!("({kind})")),
+                };
+                (node.id.clone(), detail)
+            })
+            .collect();
+        let longest = labels
+            .iter()
+            .flat_map(|(id, detail)| std::iter::once(id).chain(detail))
+            .map(|text| text.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = (longest * 7 + 24).max(100);
+        let column = width + GAP;
+
+        let ranks = self.ranks();
+        let mut rows = vec![0; self.nodes.len()];
+        let mut filled: Vec<usize> = Vec::new();
+        for (i, &rank) in ranks.iter().enumerate() {
+            if filled.len() <= rank {
+                filled.resize(rank + 1, 0);
+            }
+            rows[i] = filled[rank];
+            filled[rank] += 1;
+        }
+        let position = |i: usize| (MARGIN + ranks[i] * column, MARGIN + rows[i] * ROW);
+        let image_width = 2 * MARGIN + (filled.len() * column).saturating_sub(GAP);
+        // Grows with the arcs drawn underneath the last row.
+        let mut bottom = MARGIN + (filled.iter().max().unwrap_or(&1) - 1) * ROW + NODE_HEIGHT;
+
+        let mut svg = format!("  <title>{}</title>\n  <defs>\n", escape(&self.name));
+        for color in ["black", "blue", "red"] {
+            svg.push_str(&format!(
+                "    <marker id=\"{ids}arrow-{color}\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+                 markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">\
+                 <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"{color}\"/></marker>\n"
+            ));
+        }
+        svg.push_str("  </defs>\n");
+
+        let index = |id: &str| self.nodes.iter().position(|node| node.id == id);
+        let mut arcs: HashMap<(usize, usize), usize> = HashMap::new();
+        for edge in &self.edges {
+            let (Some(from), Some(to)) = (index(&edge.from), index(&edge.to)) else {
+                continue;
+            };
+            let ((x1, y1), (x2, y2)) = (position(from), position(to));
+            let adjacent = ranks[to] == ranks[from] + 1 && matches!(edge.flow, Flow::Control(_));
+            let (path, label_at) = if adjacent {
+                let (x1, y1, y2) = (x1 + width, y1 + NODE_HEIGHT / 2, y2 + NODE_HEIGHT / 2);
+                let bend = GAP / 2;
+                (
+                    format!(
+                        "M {x1} {y1} C {} {y1}, {} {y2}, {x2} {y2}",
+                        x1 + bend,
+                        x2 - bend
+                    ),
+                    ((x1 + x2) / 2, (y1 + y2) / 2 - 4),
+                )
+            } else {
+                // Dataflow, edges skipping columns and edges going back: an arc underneath the
+                // nodes, deeper the farther it reaches, so it does not cross the nodes in between.
+                let (x1, x2) = (x1 + width / 2, x2 + width / 2);
+                let (y1, y2) = (y1 + NODE_HEIGHT, y2 + NODE_HEIGHT);
+                let span = ranks[from].abs_diff(ranks[to]);
+                // Arcs between the same nodes are spread apart.
+                let earlier = arcs.entry((from, to)).or_default();
+                let low = y1.max(y2) + (12 + 8 * span).min(ROW / 2) + 12 * *earlier;
+                *earlier += 1;
+                bottom = bottom.max(low + 12);
+                (
+                    format!("M {x1} {y1} C {x1} {low}, {x2} {low}, {x2} {y2}"),
+                    ((x1 + x2) / 2, low),
+                )
+            };
+            let color = match edge.flow {
+                _ if edge.unresolved => "red",
+                Flow::Data => "blue",
+                Flow::Control(_) => "black",
+            };
+            let dash = match &edge.flow {
+                Flow::Data => " stroke-dasharray=\"2 3\"",
+                Flow::Control(kind) if kind == "failure" || kind == "fallback" => {
+                    " stroke-dasharray=\"6 4\""
+                }
+                Flow::Control(_) => "",
+            };
+            let stroke = if edge.unresolved { 2 } else { 1 };
+            svg.push_str(&format!(
+                "  <path d=\"{path}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"{stroke}\"{dash} \
+                 marker-end=\"url(#{ids}arrow-{color})\"/>\n"
+            ));
+            if let Some(label) = &edge.label {
+                svg.push_str(&format!(
+                    "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" \
+                     fill=\"{color}\">{}</text>\n",
+                    label_at.0,
+                    label_at.1,
+                    escape(label)
+                ));
+            }
+        }
+
+        for (i, (node, (id, detail))) in self.nodes.iter().zip(&labels).enumerate() {
+            let (x, y) = position(i);
+            let (center_x, center_y) = (x + width / 2, y + NODE_HEIGHT / 2);
+            let mut style = String::from("fill=\"white\"");
+            style.push_str(if node.unresolved {
+                " stroke=\"red\" stroke-dasharray=\"5 5\""
+            } else {
+                " stroke=\"black\""
+            });
+            style.push_str(if node.entry {
+                " stroke-width=\"3\""
+            } else {
+                " stroke-width=\"1\""
+            });
+            let shape = match node.kind.as_str() {
+                "phase" | "unknown" => format!(
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{NODE_HEIGHT}\" rx=\"10\" {style}/>"
+                ),
+                "if" | "switch" => format!(
+                    "<polygon points=\"{center_x},{y} {},{center_y} {center_x},{} {x},{center_y}\" {style}/>",
+                    x + width,
+                    y + NODE_HEIGHT
+                ),
+                _ => format!(
+                    "<ellipse cx=\"{center_x}\" cy=\"{center_y}\" rx=\"{}\" ry=\"{}\" {style}/>",
+                    width / 2,
+                    NODE_HEIGHT / 2
+                ),
+            };
+            svg.push_str(&format!("  {shape}\n"));
+            let fill = if node.unresolved { "red" } else { "black" };
+            let text = |line_y: usize, text: &str, size: usize| {
+                format!(
+                    "  <text x=\"{center_x}\" y=\"{line_y}\" text-anchor=\"middle\" \
+                     font-size=\"{size}\" fill=\"{fill}\">{}</text>\n",
+                    escape(text)
+                )
+            };
+            match detail {
+                Some(detail) => {
+                    svg.push_str(&text(center_y - 2, id, 12));
+                    svg.push_str(&text(center_y + 12, detail, 10));
+                }
+                None => svg.push_str(&text(center_y + 4, id, 12)),
+            }
+        }
+        let image_height = bottom + MARGIN;
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{image_width}\" \
+             height=\"{image_height}\" viewBox=\"0 0 {image_width} {image_height}\" \
+             font-family=\"Helvetica, Arial, sans-serif\" font-size=\"12\">\n{svg}</svg>\n"
+        )
+    }
+}
+
+/// The pipeline of `doc` as an SVG image, with element ids starting with `ids`.
+pub fn svg(doc: &JsonValue, ids: &str) -> String {
+    Graph::from_spec(doc).to_svg(ids)
+}
+
+/// A DOT string literal.
+pub(crate) fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+/// Text for a Mermaid label, with the characters Mermaid would parse written as entity codes.
+pub(crate) fn mermaid_text(text: &str) -> String {
+    text.replace('&', "#amp;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', " ")
+}
+
+/// Prints the graph of `file` in `format`, or writes it to `output`. With `collapse`, only phase
+/// nodes are kept.
+pub fn export(
+    args: &Args,
+    file: &Path,
+    format: GraphFormat,
+    collapse: bool,
+    output: Option<&Path>,
+) -> ExitCode {
+    let doc = match load(args, file) {
+        Ok(doc) => doc,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let mut graph = Graph::from_spec(&doc);
+    if collapse {
+        graph.collapse();
+    }
+    let text = match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+        GraphFormat::Svg => graph.to_svg(""),
+    };
+    match output {
+        Some(path) => match fs::write(path, text) {
+            Ok(()) => {
+                outln!(
+                    "🗺️ Wrote the graph of {} node(s) and {} edge(s) to {}.",
+                    graph.nodes.len(),
+                    graph.edges.len(),
+                    path.display()
+                );
+                ExitCode::from(0)
+            }
+            Err(e) => {
+                failln!("Error: failed to write {}: {e}", path.display());
+                ExitCode::from(1)
+            }
+        },
+        None => {
+            outln!("{}", text.trim_end());
+            ExitCode::from(0)
+        }
+    }
+}
+
+/// `graph order`: prints the phases of `file` one per line in an order that runs every phase after
+/// the phases it depends on through graph edges and dataflow (the dependencies of the cycles
+/// rule). Among the phases that are ready, the one declared first runs first. When the
+/// dependencies form a cycle, reports every cycle instead and exits with 1.
+pub fn order(args: &Args, file: &Path) -> ExitCode {
+    let doc = match load(args, file) {
+        Ok(doc) => doc,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let context = SpecContext::new(&doc);
+    let mut graph = context.dependencies();
+    // Phases nothing depends on and that depend on nothing have no vertex yet.
+    for phase in &context.phases {
+        graph.vertex(Vertex::Phase(phase.name));
+    }
+    let position = |vertex: usize| match graph.vertices[vertex] {
+        Vertex::Phase(name) => context
+            .phases
+            .iter()
+            .position(|phase| phase.name == name)
+            .unwrap_or(usize::MAX),
+        // Nodes that run no phase are passed through as soon as they are ready.
+        Vertex::Node(..) => 0,
+    };
+    let Some(order) = graph.topological_order(position) else {
+        failln!("Error: {} has no execution order:", display_input(file));
+        for component in graph.components() {
+            if let Some(cycle) = graph.shortest_cycle(&component) {
+                failln!("  {}", rules::describe_cycle(&context, &graph, &cycle));
+            }
+        }
+        return ExitCode::from(1);
+    };
+    for vertex in order {
+        if let Vertex::Phase(name) = graph.vertices[vertex] {
+            outln!("{name}");
+        }
+    }
+    ExitCode::from(0)
+}
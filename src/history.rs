@@ -0,0 +1,134 @@
+//! `--changed-since REV`: only the spec files that differ from their content at a git revision are
+//! validated, and the `metadata-bumps` rule compares each of them with that content to keep
+//! `meta.version` and `meta.updated` honest.
+
+use crate::{
+    diagnostics::{pointer, Diagnostic},
+    suppressions::Suppressions,
+};
+use serde_json::Value as JsonValue;
+use std::{fs, path::Path, process::Command};
+
+/// Fields of `meta` that record a change rather than describe the spec.
+const BUMP_FIELDS: [&str; 2] = ["version", "updated"];
+
+/// The text of `path` at git revision `rev`, or `None` when the file did not exist there.
+pub fn previous_text(path: &Path, rev: &str) -> Result<Option<String>, String> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .map_err(|e| format!("Error: failed to run git for --changed-since: {e}"))
+    };
+    let verified = git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{rev}^{{commit}}"),
+    ])?;
+    if !verified.status.success() {
+        return Err(format!(
+            "Error: --changed-since {rev} is not a commit of the git repository of {}",
+            path.display()
+        ));
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let shown = git(&["show", &format!("{rev}:./{name}")])?;
+    Ok(shown
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&shown.stdout).into_owned()))
+}
+
+/// Whether `path` differs from its content at `rev`; files new since then have changed.
+pub fn changed(path: &Path, rev: &str) -> Result<bool, String> {
+    let current = fs::read_to_string(path)
+        .map_err(|e| format!("Error: failed to read {}: {e}", path.display()))?;
+    Ok(previous_text(path, rev)?.is_none_or(|previous| previous != current))
+}
+
+/// Compares a spec with its content at `rev`: a change without a new `meta.version` or
+/// `meta.updated` is `PV230`, and a new `meta.version` without a change outside `meta` is `PV231`.
+/// Formatting, comments and inline suppressions do not count as changes.
+pub fn metadata_findings(previous: &JsonValue, current: &JsonValue, rev: &str) -> Vec<Diagnostic> {
+    let (mut previous, mut current) = (previous.clone(), current.clone());
+    Suppressions::extract(&mut previous);
+    Suppressions::extract(&mut current);
+    let field = |doc: &JsonValue, name: &str| doc.get("meta").and_then(|m| m.get(name)).cloned();
+    let bumped: Vec<&str> = BUMP_FIELDS
+        .into_iter()
+        .filter(|name| field(&previous, name) != field(&current, name))
+        .collect();
+
+    let mut findings = Vec::new();
+    if bumped.is_empty() && without_bumps(&previous) != without_bumps(&current) {
+        let stale: Vec<String> = BUMP_FIELDS
+            .iter()
+            .filter_map(|name| Some(format!("meta.{name} ({})", shown(&field(&current, name)?))))
+            .collect();
+        let message = match stale.as_slice() {
+            [] => format!(
+                "The spec changed since {rev}, but it has neither meta.version nor meta.updated"
+            ),
+            _ => format!(
+                "The spec changed since {rev}, but {} did not",
+                stale.join(" and ")
+            ),
+        };
+        let at = if field(&current, "version").is_some() {
+            pointer(&["meta", "version"])
+        } else {
+            pointer(&["meta"])
+        };
+        findings.push(Diagnostic::warning("PV230", message).at(at));
+    }
+    if let (true, Some(before), Some(after)) = (
+        bumped.contains(&"version"),
+        field(&previous, "version"),
+        field(&current, "version"),
+    ) {
+        if without_meta(&previous) == without_meta(&current) {
+            findings.push(
+                Diagnostic::warning(
+                    "PV231",
+                    format!(
+                        "meta.version went from {} to {} since {rev}, but nothing outside meta changed",
+                        shown(&before),
+                        shown(&after)
+                    ),
+                )
+                .at(pointer(&["meta", "version"])),
+            );
+        }
+    }
+    findings
+}
+
+/// `doc` without the fields of `meta` that record a change.
+fn without_bumps(doc: &JsonValue) -> JsonValue {
+    let mut doc = doc.clone();
+    if let Some(meta) = doc.get_mut("meta").and_then(|m| m.as_object_mut()) {
+        for name in BUMP_FIELDS {
+            meta.remove(name);
+        }
+    }
+    doc
+}
+
+/// `doc` without `meta`.
+fn without_meta(doc: &JsonValue) -> JsonValue {
+    let mut doc = doc.clone();
+    if let Some(spec) = doc.as_object_mut() {
+        spec.remove("meta");
+    }
+    doc
+}
+
+fn shown(value: &JsonValue) -> String {
+    value.as_str().map_or(value.to_string(), str::to_string)
+}
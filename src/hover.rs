@@ -0,0 +1,203 @@
+//! Documentation of a spec node taken from its schema: the `title`, `description` and constraints
+//! of the schema node describing the value at a JSON Pointer. Used for the `--verbose` annotations of
+//! schema errors and by `schema hover`, which editors call for hover text.
+
+use crate::{
+    extract_spec_version, parse_documents, read_input, schemas::SchemaRequest, Args, InputFormat,
+};
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::{path::Path, process::ExitCode};
+
+/// Keywords shown as constraints, in display order.
+const CONSTRAINT_KEYWORDS: [&str; 19] = [
+    "type",
+    "enum",
+    "const",
+    "format",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+    "required",
+    "default",
+];
+
+/// What the schema says about one node.
+#[derive(Debug, Default)]
+pub struct Hover {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Whether the parent object requires the node; `None` for the root and array items.
+    pub required: Option<bool>,
+    /// `(keyword, value)` for every constraint keyword, values rendered for display.
+    pub constraints: Vec<(&'static str, String)>,
+}
+
+impl Hover {
+    /// `title: description`, or whichever of the two the schema gives.
+    pub fn summary(&self) -> Option<String> {
+        match (&self.title, &self.description) {
+            (Some(title), Some(description)) => Some(format!("{title}: {description}")),
+            (title, description) => title.clone().or_else(|| description.clone()),
+        }
+    }
+}
+
+/// Documentation of the value at `pointer`, found by following `properties`, `patternProperties`,
+/// `additionalProperties`, `items` and local `$ref`s; `allOf` branches of the final node add to it.
+/// `None` when the schema does not describe the pointer. (A schema error's own schema path is
+/// relative to the last `$ref`, so it cannot be used instead.)
+pub fn hover(schema: &JsonValue, pointer: &str) -> Option<Hover> {
+    let mut node = resolve(schema, schema);
+    let mut required = None;
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        let pattern_match = || {
+            node.get("patternProperties")?
+                .as_object()?
+                .iter()
+                .find(|(pattern, _)| Regex::new(pattern).is_ok_and(|re| re.is_match(&segment)))
+                .map(|(_, sub)| sub)
+        };
+        let property = node.get("properties").and_then(|p| p.get(&segment));
+        let items = || match node.get("items")? {
+            JsonValue::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            items => Some(items),
+        };
+        let next = property
+            .or_else(pattern_match)
+            .or_else(|| node.get("additionalProperties").filter(|v| v.is_object()))
+            .or_else(items)?;
+        required = node.get("properties").is_some().then(|| {
+            node.get("required")
+                .and_then(|r| r.as_array())
+                .is_some_and(|r| r.iter().any(|name| name.as_str() == Some(&segment)))
+        });
+        node = resolve(schema, next);
+    }
+
+    let mut nodes = vec![node];
+    if let Some(branches) = node.get("allOf").and_then(|v| v.as_array()) {
+        nodes.extend(branches.iter().map(|branch| resolve(schema, branch)));
+    }
+    let mut hover = Hover {
+        required,
+        ..Hover::default()
+    };
+    for node in nodes {
+        let text = |key: &str| node.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        hover.title = hover.title.take().or_else(|| text("title"));
+        hover.description = hover.description.take().or_else(|| text("description"));
+        for keyword in CONSTRAINT_KEYWORDS {
+            if let Some(value) = node.get(keyword) {
+                if !hover.constraints.iter().any(|(k, _)| *k == keyword) {
+                    hover.constraints.push((keyword, display(value)));
+                }
+            }
+        }
+    }
+    Some(hover)
+}
+
+/// Follows local `$ref`s from `node`.
+fn resolve<'a>(schema: &'a JsonValue, mut node: &'a JsonValue) -> &'a JsonValue {
+    // Bounded, so that a `$ref` cycle cannot loop forever.
+    for _ in 0..32 {
+        match node
+            .get("$ref")
+            .and_then(|r| r.as_str())
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|r| schema.pointer(r))
+        {
+            Some(target) => node = target,
+            None => break,
+        }
+    }
+    node
+}
+
+/// Strings as is, lists of strings comma-separated, anything else as JSON.
+fn display(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Array(items) if items.iter().all(JsonValue::is_string) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// `schema hover`: prints the documentation of the node at `pointer` in document `document`
+/// (1-based) of `file`, from the schema the document is validated against.
+pub fn print_hover(args: &Args, file: &Path, pointer: &str, document: usize) -> ExitCode {
+    let hover = match document_hover(args, file, pointer, document) {
+        Ok(hover) => hover,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    for text in [&hover.title, &hover.description].into_iter().flatten() {
+        outln!("{text}");
+    }
+    if let Some(required) = hover.required {
+        outln!("  required: {}", if required { "yes" } else { "no" });
+    }
+    for (keyword, value) in &hover.constraints {
+        let keyword = if *keyword == "required" {
+            "required keys"
+        } else {
+            keyword
+        };
+        outln!("  {keyword}: {value}");
+    }
+    ExitCode::from(0)
+}
+
+fn document_hover(
+    args: &Args,
+    file: &Path,
+    pointer: &str,
+    document: usize,
+) -> Result<Hover, String> {
+    let text = read_input(file)?;
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let documents = parse_documents(&text, format).map_err(|e| format!("Error: {e}"))?;
+    let count = documents.len();
+    let doc = documents
+        .into_iter()
+        .nth(document.saturating_sub(1))
+        .ok_or_else(|| {
+            format!(
+                "Error: {} has {count} document(s), not {document}",
+                file.display()
+            )
+        })?;
+    let spec_version = match &args.spec_version {
+        Some(version) => Some(version.clone()),
+        None => extract_spec_version(&doc).map_err(|e| format!("Error: {e}"))?,
+    };
+    let request = SchemaRequest {
+        input: file,
+        spec_version: spec_version.as_deref(),
+    };
+    let explicit = args.schema.as_ref().map(|p| p.to_string_lossy());
+    let schema = args.schemas.resolve(explicit.as_deref(), &request)?.schema;
+    hover(&schema, pointer)
+        .ok_or_else(|| format!("Error: the schema does not describe '{pointer}'"))
+}
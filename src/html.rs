@@ -0,0 +1,216 @@
+//! `--format html`: a single self-contained HTML page covering every input of the run — a list of
+//! all findings, the pipeline graph of each spec, and its findings grouped by rule in collapsible
+//! sections — meant to be attached to CI runs as an artifact.
+
+use crate::{
+    diagnostics::Severity,
+    graph, output, provenance,
+    reporter::{Report, Reporter, SCHEMA_CODE},
+};
+use serde_json::Value as JsonValue;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+const STYLE: &str = "
+body { font-family: Helvetica, Arial, sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f3f3f3; }
+details { margin: 0.5em 0; }
+summary { cursor: pointer; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+.graph { overflow-x: auto; border: 1px solid #eee; padding: 0.5em; }
+.error { color: #b00020; }
+.warning { color: #a05a00; }
+.info { color: #0050a0; }
+";
+
+/// What the report shows about one input.
+#[derive(Default)]
+struct Input {
+    /// SVG graphs of the documents in the file.
+    graphs: Vec<String>,
+    findings: Vec<Report>,
+}
+
+/// Collects the specs and findings of the run and prints the page once it finishes.
+#[derive(Default)]
+pub struct HtmlReporter {
+    /// Inputs in the order they were first seen; the cross-spec checks go under `None`, last.
+    inputs: Mutex<Vec<(Option<String>, Input)>>,
+    /// Graphs rendered so far, numbering their element ids.
+    graphs: AtomicUsize,
+}
+
+impl HtmlReporter {
+    fn with_input(&self, file: Option<&str>, update: impl FnOnce(&mut Input)) {
+        let mut inputs = self.inputs.lock().unwrap();
+        let index = match inputs.iter().position(|(name, _)| name.as_deref() == file) {
+            Some(index) => index,
+            None => {
+                inputs.push((file.map(str::to_string), Input::default()));
+                inputs.len() - 1
+            }
+        };
+        update(&mut inputs[index].1);
+    }
+}
+
+impl Reporter for HtmlReporter {
+    fn spec(&self, file: &str, doc: &JsonValue) {
+        let ids = format!("graph{}-", self.graphs.fetch_add(1, Ordering::Relaxed));
+        let svg = graph::svg(doc, &ids);
+        self.with_input(Some(file), |input| input.graphs.push(svg));
+    }
+
+    fn report(&self, report: &Report) {
+        self.with_input(report.file.as_deref(), |input| {
+            input.findings.push(report.clone())
+        });
+    }
+
+    fn finish(&self) {
+        let mut inputs = std::mem::take(&mut *self.inputs.lock().unwrap());
+        inputs.sort_by_key(|(file, _)| file.is_none());
+        output::emit(&render(&inputs));
+    }
+}
+
+fn render(inputs: &[(Option<String>, Input)]) -> String {
+    let findings: Vec<&Report> = inputs.iter().flat_map(|(_, i)| &i.findings).collect();
+    let count = |severity: Severity| findings.iter().filter(|r| r.severity == severity).count();
+    let files = inputs.iter().filter(|(file, _)| file.is_some()).count();
+
+    let mut page = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    page.push_str("<meta charset=\"utf-8\">\n<title>Validation report</title>\n");
+    page.push_str(&format!("<style>{STYLE}</style>\n</head>\n<body>\n"));
+    page.push_str("<h1>Validation report</h1>\n");
+    page.push_str(&format!(
+        "<p>{files} file(s): <span class=\"error\">{} error(s)</span>, \
+         <span class=\"warning\">{} warning(s)</span>, <span class=\"info\">{} info</span>. \
+         Generated by {} {}.</p>\n",
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Info),
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    page.push_str("<h2>Findings</h2>\n");
+    if findings.is_empty() {
+        page.push_str("<p>No findings.</p>\n");
+    } else {
+        page.push_str(
+            "<table>\n<tr><th>Severity</th><th>Location</th><th>Code</th><th>Message</th></tr>\n",
+        );
+        for (index, (file, input)) in inputs.iter().enumerate() {
+            for report in &input.findings {
+                let name = report.severity.name();
+                page.push_str(&format!(
+                    "<tr><td class=\"{name}\">{name}</td><td><a href=\"#input-{index}\">{}</a></td>\
+                     <td>{}</td><td>{}</td></tr>\n",
+                    escape(&location(file.as_deref(), report)),
+                    escape(&report.code),
+                    escape(&report.message)
+                ));
+            }
+        }
+        page.push_str("</table>\n");
+    }
+
+    for (index, (file, input)) in inputs.iter().enumerate() {
+        let title = file.as_deref().unwrap_or("Checks across specs");
+        page.push_str(&format!(
+            "<h2 id=\"input-{index}\">{}</h2>\n",
+            escape(title)
+        ));
+        let errors = input
+            .findings
+            .iter()
+            .filter(|r| r.severity == Severity::Error)
+            .count();
+        page.push_str(&match (input.findings.len(), errors) {
+            (0, _) => "<p>No findings.</p>\n".to_string(),
+            (total, errors) => format!("<p>{total} finding(s), {errors} of them error(s).</p>\n"),
+        });
+        for (number, svg) in input.graphs.iter().enumerate() {
+            let label = if input.graphs.len() > 1 {
+                format!("Dataflow graph of document #{}", number + 1)
+            } else {
+                "Dataflow graph".to_string()
+            };
+            page.push_str(&format!(
+                "<details open>\n<summary>{label}</summary>\n<div class=\"graph\">\n{svg}</div>\n</details>\n"
+            ));
+        }
+
+        // Findings by rule, schema violations first.
+        let mut rules: BTreeMap<(bool, &str), Vec<&Report>> = BTreeMap::new();
+        for report in &input.findings {
+            let rule = report.rule.unwrap_or("JSON Schema");
+            rules
+                .entry((report.code != SCHEMA_CODE, rule))
+                .or_default()
+                .push(report);
+        }
+        for ((_, rule), reports) in rules {
+            let worst = reports
+                .iter()
+                .map(|r| r.severity)
+                .max()
+                .unwrap_or(Severity::Info);
+            page.push_str(&format!(
+                "<details>\n<summary><span class=\"{}\">{}</span> — {} finding(s)</summary>\n<ul>\n",
+                worst.name(),
+                escape(rule),
+                reports.len()
+            ));
+            for report in reports {
+                page.push_str(&format!(
+                    "<li><span class=\"{0}\">{0}</span> [{1}] {2}",
+                    report.severity.name(),
+                    escape(&report.code),
+                    escape(&report.message)
+                ));
+                if let Some(excerpt) = &report.excerpt {
+                    page.push_str(&format!("\n<pre>{}</pre>", escape(excerpt)));
+                } else if let Some(pointer) = &report.pointer {
+                    page.push_str(&format!(" <code>{}</code>", escape(pointer)));
+                }
+                page.push_str("</li>\n");
+            }
+            page.push_str("</ul>\n</details>\n");
+        }
+    }
+
+    let provenance = serde_json::to_string_pretty(&provenance::to_json()).unwrap();
+    page.push_str(&format!(
+        "<h2>Provenance</h2>\n<details>\n<summary>Configuration, schemas, libraries and rules of \
+         the run</summary>\n<pre>{}</pre>\n</details>\n",
+        escape(&provenance)
+    ));
+    page.push_str("</body>\n</html>");
+    page
+}
+
+/// `file:line:column` of a finding, as far as it is known.
+fn location(file: Option<&str>, report: &Report) -> String {
+    let file = file.unwrap_or("(across specs)");
+    match report.location {
+        Some(at) => format!("{file}:{at}"),
+        None => file.to_string(),
+    }
+}
+
+/// `text` with the characters HTML and XML give a meaning written as entities.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,138 @@
+//! `introspect`: what this build of the validator supports — input and output formats, rule IDs,
+//! schema drafts and subcommands — so that wrapper tooling and editor plugins can detect features
+//! instead of comparing version numbers. Rules registered by the configuration file (scripts,
+//! assertions) are listed with the built-in ones.
+
+use crate::{
+    diff::DiffFormat,
+    graph::GraphFormat,
+    reporter::{ReportFormat, SCHEMA_CODE},
+    rules,
+    schemas::SchemaDraft,
+    workspace::WorkspaceFormat,
+    Args, InputFormat,
+};
+use clap::{CommandFactory, ValueEnum};
+use serde_json::{json, Value as JsonValue};
+use std::process::ExitCode;
+
+/// How `introspect` prints the capabilities.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntrospectFormat {
+    /// A short summary, for people.
+    #[default]
+    Human,
+    /// One JSON object, for tools.
+    Json,
+}
+
+/// Prints the capabilities of the validator in `format`.
+pub fn introspect(format: IntrospectFormat) -> ExitCode {
+    let capabilities = capabilities();
+    match format {
+        IntrospectFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(&capabilities).unwrap())
+        }
+        IntrospectFormat::Human => print_summary(&capabilities),
+    }
+    ExitCode::from(0)
+}
+
+/// The capabilities as one JSON object.
+fn capabilities() -> JsonValue {
+    json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "input_formats": values::<InputFormat>(),
+        "output_formats": {
+            "report": values::<ReportFormat>(),
+            "diff": values::<DiffFormat>(),
+            "graph": values::<GraphFormat>(),
+            "workspace_graph": values::<WorkspaceFormat>(),
+            "introspect": values::<IntrospectFormat>(),
+        },
+        "rules": rule_descriptors(),
+        "schema_drafts": values::<SchemaDraft>(),
+        "subcommands": subcommands(&Args::command(), ""),
+    })
+}
+
+/// The names of the values of a `ValueEnum` option, as accepted on the command line.
+fn values<T: ValueEnum>() -> Vec<String> {
+    T::value_variants()
+        .iter()
+        .filter_map(|value| value.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+/// Every finding ID with its summary and, where a per-document rule reports it, the rule's name,
+/// category and default severity — the same metadata as the rule descriptors of SARIF output.
+fn rule_descriptors() -> Vec<JsonValue> {
+    let registry = rules::registered();
+    std::iter::once((SCHEMA_CODE, "JSON Schema violation"))
+        .chain(rules::rule_codes())
+        .map(|(id, summary)| {
+            let mut descriptor = json!({ "id": id, "summary": summary });
+            if let Some(name) = rules::rule_for_code(id) {
+                descriptor["rule"] = json!(name);
+                if let Some(rule) = registry.get(name) {
+                    descriptor["category"] = json!(rule.category());
+                    descriptor["default_severity"] = json!(rule.default_severity().name());
+                    descriptor["builtin"] = json!(registry.is_builtin(name));
+                }
+            }
+            descriptor
+        })
+        .collect()
+}
+
+/// The subcommands below `command`, depth first, each named by its full path (`graph export`).
+fn subcommands(command: &clap::Command, prefix: &str) -> Vec<JsonValue> {
+    let mut found = Vec::new();
+    for sub in command.get_subcommands() {
+        if sub.get_name() == "help" {
+            continue;
+        }
+        let name = format!("{prefix}{}", sub.get_name());
+        if sub.has_subcommands() {
+            found.extend(subcommands(sub, &format!("{name} ")));
+        } else {
+            let about = sub.get_about().map(|about| about.to_string());
+            found.push(json!({ "name": name, "about": about }));
+        }
+    }
+    found
+}
+
+fn print_summary(capabilities: &JsonValue) {
+    let list = |value: &JsonValue| {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item.as_str().or_else(|| item["name"].as_str()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    outln!(
+        "{} {}",
+        capabilities["name"].as_str().unwrap_or_default(),
+        capabilities["version"].as_str().unwrap_or_default()
+    );
+    outln!("Input formats: {}", list(&capabilities["input_formats"]));
+    if let Some(outputs) = capabilities["output_formats"].as_object() {
+        for (option, formats) in outputs {
+            outln!("Output formats ({option}): {}", list(formats));
+        }
+    }
+    outln!("Schema drafts: {}", list(&capabilities["schema_drafts"]));
+    outln!("Subcommands: {}", list(&capabilities["subcommands"]));
+    let ids: Vec<&str> = capabilities["rules"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|rule| rule["id"].as_str())
+        .collect();
+    outln!("Rule IDs: {}", ids.join(", "));
+}
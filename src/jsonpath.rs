@@ -0,0 +1,221 @@
+//! The subset of JSONPath that assertions and `query` understand: `$` (the whole spec), `.name` and
+//! `['name']` (a member), `[0]` (an array element), `.*` and `[*]` (every element or member) and
+//! `..name` (the member `name` at any depth).
+
+use crate::diagnostics::pointer;
+use serde_json::Value as JsonValue;
+
+/// One step of a selector.
+pub enum Step {
+    /// `.name` or `['name']`.
+    Field(String),
+    /// `[n]`.
+    Index(usize),
+    /// `.*` or `[*]`: every element or member.
+    Children,
+    /// `..name`: the member `name` of the node and of every node below it.
+    Descendants(String),
+}
+
+/// Parses `path` into its steps.
+pub fn parse(path: &str) -> Result<Vec<Step>, String> {
+    let invalid = |why: &str| format!("invalid path '{path}': {why}");
+    let Some(mut rest) = path.strip_prefix('$') else {
+        return Err(invalid("it must start with $"));
+    };
+    let name_end = |text: &str| text.find(['.', '[']).unwrap_or(text.len());
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            let end = name_end(after);
+            if end == 0 {
+                return Err(invalid("expected a member name after .."));
+            }
+            steps.push(Step::Descendants(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let end = name_end(after);
+            steps.push(match &after[..end] {
+                "" => return Err(invalid("expected a member name after .")),
+                "*" => Step::Children,
+                name => Step::Field(name.to_string()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let Some(end) = after.find(']') else {
+                return Err(invalid("unclosed ["));
+            };
+            let inside = after[..end].trim();
+            let quoted = inside
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inside.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+            steps.push(match (inside, quoted) {
+                (_, Some(name)) => Step::Field(name.to_string()),
+                ("*", None) => Step::Children,
+                (index, None) => Step::Index(
+                    index
+                        .parse()
+                        .map_err(|_| invalid(&format!("'[{index}]' is not an index")))?,
+                ),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid(&format!("unexpected '{rest}'")));
+        }
+    }
+    Ok(steps)
+}
+
+/// The nodes `steps` select in `doc`, with their JSON Pointers, in document order.
+pub fn select<'a>(steps: &[Step], doc: &'a JsonValue) -> Vec<(String, &'a JsonValue)> {
+    let mut nodes = vec![(String::new(), doc)];
+    for step in steps {
+        let mut next = Vec::new();
+        for (at, node) in nodes {
+            match step {
+                Step::Field(name) => {
+                    if let Some(child) = node.get(name) {
+                        next.push((format!("{at}{}", pointer(&[name])), child));
+                    }
+                }
+                Step::Index(index) => {
+                    if let Some(child) = node.as_array().and_then(|items| items.get(*index)) {
+                        next.push((format!("{at}/{index}"), child));
+                    }
+                }
+                Step::Children => children(&at, node, &mut next),
+                Step::Descendants(name) => descendants(name, &at, node, &mut next),
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+fn children<'a>(at: &str, node: &'a JsonValue, out: &mut Vec<(String, &'a JsonValue)>) {
+    match node {
+        JsonValue::Array(items) => out.extend(
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| (format!("{at}/{index}"), item)),
+        ),
+        JsonValue::Object(members) => out.extend(
+            members
+                .iter()
+                .map(|(name, member)| (format!("{at}{}", pointer(&[name])), member)),
+        ),
+        _ => {}
+    }
+}
+
+fn descendants<'a>(
+    name: &str,
+    at: &str,
+    node: &'a JsonValue,
+    out: &mut Vec<(String, &'a JsonValue)>,
+) {
+    if let Some(member) = node.as_object().and_then(|members| members.get(name)) {
+        out.push((format!("{at}{}", pointer(&[name])), member));
+    }
+    let mut below = Vec::new();
+    children(at, node, &mut below);
+    for (child_at, child) in below {
+        descendants(name, &child_at, child, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The pointers `path` selects in `doc`.
+    fn pointers(path: &str, doc: &JsonValue) -> Vec<String> {
+        let steps = parse(path).unwrap();
+        select(&steps, doc).into_iter().map(|(at, _)| at).collect()
+    }
+
+    fn spec() -> JsonValue {
+        json!({
+            "algorithm": { "phases": ["collect", "analyze"] },
+            "implementation": {
+                "phase_contracts": {
+                    "analyze": { "outputs": [{ "name": "severity" }] },
+                    "collect": { "outputs": [{ "name": "message" }, { "name": "a/b" }] }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn selects_members_and_elements() {
+        let doc = spec();
+        assert_eq!(pointers("$", &doc), [""]);
+        assert_eq!(
+            pointers("$.algorithm.phases[1]", &doc),
+            ["/algorithm/phases/1"]
+        );
+        assert_eq!(
+            pointers("$['implementation'][\"phase_contracts\"].collect", &doc),
+            ["/implementation/phase_contracts/collect"]
+        );
+        let steps = parse("$.algorithm.phases[0]").unwrap();
+        assert_eq!(select(&steps, &doc)[0].1, "collect");
+    }
+
+    #[test]
+    fn selects_every_child_and_descendant() {
+        let doc = spec();
+        assert_eq!(
+            pointers("$.algorithm.phases[*]", &doc),
+            ["/algorithm/phases/0", "/algorithm/phases/1"]
+        );
+        assert_eq!(
+            pointers("$.implementation.phase_contracts.*.outputs[0].name", &doc),
+            [
+                "/implementation/phase_contracts/analyze/outputs/0/name",
+                "/implementation/phase_contracts/collect/outputs/0/name"
+            ]
+        );
+        assert_eq!(
+            pointers("$..name", &doc),
+            [
+                "/implementation/phase_contracts/analyze/outputs/0/name",
+                "/implementation/phase_contracts/collect/outputs/0/name",
+                "/implementation/phase_contracts/collect/outputs/1/name"
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_pointers_and_skips_missing_nodes() {
+        let doc = json!({ "a/b": { "c~d": 1 }, "list": [1] });
+        assert_eq!(pointers("$['a/b']['c~d']", &doc), ["/a~1b/c~0d"]);
+        assert!(pointers("$.missing.deeper", &doc).is_empty());
+        assert!(pointers("$.list[3]", &doc).is_empty());
+        assert!(pointers("$.list.name", &doc).is_empty());
+        assert!(pointers("$['a/b'][0]", &doc).is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        for path in [
+            "algorithm",
+            "$.",
+            "$..",
+            "$..[0]",
+            "$[0",
+            "$[x]",
+            "$[-1]",
+            "$algorithm",
+        ] {
+            let error = parse(path).err().unwrap_or_else(|| panic!("{path} parsed"));
+            assert!(
+                error.starts_with(&format!("invalid path '{path}'")),
+                "{error}"
+            );
+        }
+    }
+}
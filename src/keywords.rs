@@ -0,0 +1,226 @@
+//! Custom schema keywords: semantics JSON Schema cannot express, checked after JSON Schema
+//! validation wherever a schema node carrying the keyword applies to a spec value (see
+//! [`crate::trace`]). A keyword is a [`Keyword`] implementation registered in [`SchemaKeywords`];
+//! the standard set has `x-unique-items-by` and `x-max-phase-count`.
+
+use crate::{
+    diagnostics::Diagnostic,
+    schemas::ResolvedSchema,
+    trace::{escape, Tracer},
+    Args,
+};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+
+/// A schema keyword with a validator written in Rust.
+pub trait Keyword: Send + Sync {
+    /// The keyword as written in schemas; use an `x-` prefix so that other tools ignore it.
+    fn name(&self) -> &'static str;
+
+    /// Problems with `instance`, a spec value described by a schema node where the keyword has the
+    /// value `value`.
+    fn check(&self, value: &JsonValue, instance: &JsonValue) -> Vec<String>;
+}
+
+/// A violation of a custom keyword.
+pub struct KeywordError {
+    /// JSON Pointer of the spec value.
+    pub instance_path: String,
+    /// JSON Pointer of the keyword in the schema.
+    pub schema_path: String,
+    pub message: String,
+}
+
+/// The registered keywords.
+pub struct SchemaKeywords {
+    keywords: Vec<Box<dyn Keyword>>,
+}
+
+impl SchemaKeywords {
+    pub fn new(keywords: Vec<Box<dyn Keyword>>) -> Self {
+        Self { keywords }
+    }
+
+    /// The keywords built into the validator.
+    pub fn standard() -> Self {
+        Self::new(vec![Box::new(UniqueItemsBy), Box::new(MaxPhaseCount)])
+    }
+
+    pub fn register(&mut self, keyword: Box<dyn Keyword>) {
+        self.keywords.push(keyword);
+    }
+
+    /// Violations of the registered keywords in `instance`.
+    pub fn check(
+        &self,
+        args: &Args,
+        resolved: &ResolvedSchema,
+        instance: &JsonValue,
+    ) -> Vec<KeywordError> {
+        // Most schemas use none of them, and tracing a spec through its schema is not free.
+        if !self.used_in(&resolved.schema) {
+            return Vec::new();
+        }
+        let mut errors = Vec::new();
+        Tracer::new(args, resolved).trace(instance, &mut |visit| {
+            for keyword in &self.keywords {
+                if let Some(value) = visit.node.get(keyword.name()) {
+                    for message in keyword.check(value, visit.instance) {
+                        errors.push(KeywordError {
+                            instance_path: visit.at.to_string(),
+                            schema_path: format!("{}/{}", visit.pointer, escape(keyword.name())),
+                            message,
+                        });
+                    }
+                }
+            }
+        });
+        errors
+    }
+
+    fn used_in(&self, schema: &JsonValue) -> bool {
+        match schema {
+            JsonValue::Object(map) => map.iter().any(|(key, value)| {
+                self.keywords.iter().any(|k| k.name() == key) || self.used_in(value)
+            }),
+            JsonValue::Array(items) => items.iter().any(|item| self.used_in(item)),
+            _ => false,
+        }
+    }
+}
+
+impl Default for SchemaKeywords {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl std::fmt::Debug for SchemaKeywords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.keywords.iter().map(|k| k.name()).collect();
+        f.debug_tuple("SchemaKeywords").field(&names).finish()
+    }
+}
+
+/// Spec values described by a schema node annotated `deprecated: true`, as `PV102` findings: the
+/// schema still accepts them, but they are on their way out. The node's `description`, if any, is
+/// quoted in the message.
+pub fn deprecated_fields(
+    args: &Args,
+    resolved: &ResolvedSchema,
+    instance: &JsonValue,
+) -> Vec<Diagnostic> {
+    if !marks_deprecated(&resolved.schema) {
+        return Vec::new();
+    }
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    Tracer::new(args, resolved).trace(instance, &mut |visit| {
+        if visit.at.is_empty()
+            || visit.node.get("deprecated") != Some(&JsonValue::Bool(true))
+            || !seen.insert(visit.at.to_string())
+        {
+            return;
+        }
+        let detail = match visit.node.get("description").and_then(|d| d.as_str()) {
+            Some(description) => format!(" ({})", description.trim()),
+            None => String::new(),
+        };
+        found.push(
+            Diagnostic::info(
+                "PV102",
+                format!("'{}' is deprecated by the schema{detail}", visit.at),
+            )
+            .at(visit.at),
+        );
+    });
+    found
+}
+
+/// Whether some node of `schema` carries `deprecated: true`.
+fn marks_deprecated(schema: &JsonValue) -> bool {
+    match schema {
+        JsonValue::Object(map) => map.iter().any(|(key, value)| {
+            (key == "deprecated" && value == &JsonValue::Bool(true)) || marks_deprecated(value)
+        }),
+        JsonValue::Array(items) => items.iter().any(marks_deprecated),
+        _ => false,
+    }
+}
+
+/// `x-unique-items-by: name` (or a list of names): no two items of the array have the same values
+/// for these properties. Items missing all of them are not compared.
+struct UniqueItemsBy;
+
+impl Keyword for UniqueItemsBy {
+    fn name(&self) -> &'static str {
+        "x-unique-items-by"
+    }
+
+    fn check(&self, value: &JsonValue, instance: &JsonValue) -> Vec<String> {
+        let keys: Vec<&str> = match value {
+            JsonValue::String(key) => vec![key],
+            JsonValue::Array(keys) => keys.iter().filter_map(|k| k.as_str()).collect(),
+            _ => {
+                return vec![format!(
+                    "{} must be a property name or a list of them",
+                    self.name()
+                )]
+            }
+        };
+        let Some(items) = instance.as_array() else {
+            return Vec::new();
+        };
+        let mut seen: Vec<(Vec<Option<&JsonValue>>, usize)> = Vec::new();
+        let mut errors = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let values: Vec<Option<&JsonValue>> = keys.iter().map(|key| item.get(key)).collect();
+            if values.iter().all(Option::is_none) {
+                continue;
+            }
+            match seen.iter().find(|(other, _)| *other == values) {
+                Some((_, first)) => {
+                    let shown: Vec<String> = keys
+                        .iter()
+                        .zip(&values)
+                        .map(|(key, value)| match value {
+                            Some(value) => format!("{key} {value}"),
+                            None => format!("no {key}"),
+                        })
+                        .collect();
+                    errors.push(format!(
+                        "Items {first} and {index} have the same {}",
+                        shown.join(", ")
+                    ));
+                }
+                None => seen.push((values, index)),
+            }
+        }
+        errors
+    }
+}
+
+/// `x-max-phase-count: N`: an algorithm (an object with a `phases` list, or the list itself) has
+/// at most N phases.
+struct MaxPhaseCount;
+
+impl Keyword for MaxPhaseCount {
+    fn name(&self) -> &'static str {
+        "x-max-phase-count"
+    }
+
+    fn check(&self, value: &JsonValue, instance: &JsonValue) -> Vec<String> {
+        let Some(max) = value.as_u64() else {
+            return vec![format!("{} must be a non-negative integer", self.name())];
+        };
+        let phases = instance.get("phases").unwrap_or(instance);
+        match phases.as_array() {
+            Some(phases) if phases.len() as u64 > max => vec![format!(
+                "{} phases declared, more than the {max} allowed by {}",
+                phases.len(),
+                self.name()
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
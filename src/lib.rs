@@ -0,0 +1,2364 @@
+//! Validation of program specifications: JSON Schema plus the domain rules.
+//!
+//! The `program-verify` binary is a thin wrapper around [`cli`]. Embedders validate specs
+//! in-process with a [`Validator`], and add checks of their own by implementing [`Rule`] and
+//! registering it in the [`registry`] (or in a [`Profile`] of their own) before validating.
+
+#[macro_use]
+mod output;
+
+mod artifact;
+mod assertions;
+mod audit;
+mod audit_log;
+mod baseline;
+mod bundle;
+mod cache;
+mod cancellation;
+mod compat;
+mod completions;
+mod conditions;
+mod config;
+mod context;
+mod coverage;
+mod defaults;
+mod diagnostics;
+mod diff;
+mod docs;
+mod doctor;
+mod encoding;
+mod fetch;
+mod fmt;
+mod formats;
+mod graph;
+mod history;
+mod hover;
+mod html;
+mod introspect;
+mod jsonpath;
+mod keywords;
+mod libraries;
+mod locations;
+mod migrate;
+mod mutants;
+mod owners;
+mod port_types;
+mod post;
+mod provenance;
+mod query;
+mod readiness;
+mod reduce;
+mod registry;
+mod reporter;
+mod rule_report;
+mod rules;
+mod schemas;
+mod scripts;
+mod scrub;
+mod server;
+mod show;
+mod signals;
+mod supervisor;
+mod suppressions;
+mod trace;
+mod tui;
+mod usage;
+mod validator;
+mod versions;
+mod workspace;
+
+use audit::{Suppressed, SuppressionAudit, Waiver};
+use audit_log::AuditLog;
+use baseline::{Baseline, Finding};
+use cache::{Downloads, Memo, SchemaCache};
+pub use cancellation::CancellationToken;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use config::Config;
+pub use config::RuleConfig;
+pub use context::SpecContext;
+pub use diagnostics::{Diagnostic, Severity};
+use diff::DiffFormat;
+use fetch::{Fetcher, DEFAULT_FETCH_TIMEOUT};
+use formats::CustomFormat;
+use graph::GraphFormat;
+use introspect::IntrospectFormat;
+use jsonschema::JSONSchema;
+pub use keywords::Keyword;
+use keywords::{KeywordError, SchemaKeywords};
+use locations::Locations;
+use output::ColorChoice;
+use owners::{OwnerReporter, Owners, SplitBy};
+use post::ResultsPoster;
+use rayon::prelude::*;
+pub use reporter::{Report, Reporter};
+use reporter::{ReportFormat, Reporters, SCHEMA_CODE};
+pub use rules::{registry, with_profile, Diagnostics, Profile, Rule, RuleRegistry, SpecModel};
+pub use schemas::{ResolvedSchema, SchemaRequest, SchemaResolver};
+use schemas::{SchemaDraft, SchemaResolvers};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use supervisor::Progress;
+use suppressions::Suppressions;
+use tui::TuiReporter;
+pub use validator::{Validation, Validator};
+use workspace::WorkspaceFormat;
+
+/// Simple YAML program validator that checks JSON Schema plus extra domain rules.
+#[derive(Parser, Debug)]
+#[command(
+    name = "program-verify",
+    author,
+    version,
+    about,
+    subcommand_negates_reqs = true
+)]
+struct Args {
+    /// Program specifications (YAML, JSON or TOML) to validate. Directories are searched recursively
+    /// for `.yml`, `.yaml`, `.json` and `.toml` files; `-` reads a spec from standard input.
+    #[arg(required = true, value_name = "INPUT")]
+    inputs: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Format of the input document. Detected from the file extension when omitted
+    /// (`.json`, `.toml`, anything else is treated as YAML).
+    #[arg(long = "input-format", value_enum, value_name = "FORMAT")]
+    input_format: Option<InputFormat>,
+
+    /// Optional custom JSON Schema file instead of the embedded one.
+    #[arg(long)]
+    schema: Option<PathBuf>,
+
+    /// Print the YAML converted to JSON (debug).
+    #[arg(long)]
+    show_json: bool,
+
+    /// Fill in the `default` of every schema property a spec leaves out before the domain rules
+    /// run, so that they check the spec as the executor will see it.
+    #[arg(long = "apply-defaults")]
+    apply_defaults: bool,
+
+    /// Print each document with the schema defaults filled in (implies `--apply-defaults`).
+    #[arg(long = "show-defaults")]
+    show_defaults: bool,
+
+    /// Specification version key, e.g. "v1" or "v2.1" — used to pick a schema from version_map.yaml.
+    /// (Do not confuse with clap's --version flag.)
+    #[arg(long = "spec-version", short = 'v', value_name = "NAME")]
+    spec_version: Option<String>,
+
+    /// Path to the YAML file that maps specification versions to schema files
+    /// [default: version_map.yaml].
+    /// Relative paths within that file are resolved relative to the map file location.
+    #[arg(long = "versions-map", value_name = "FILE", global = true)]
+    versions_map: Option<PathBuf>,
+
+    /// Directory searched for shared contract libraries referenced by `implementation.uses`.
+    /// May be repeated; searched before the `library_paths` of the config file.
+    #[arg(long = "library-path", value_name = "DIR", global = true)]
+    library_paths: Vec<PathBuf>,
+
+    /// Configuration file to use instead of the nearest `.program-verify.yaml`
+    /// found next to the first input or in one of its parent directories.
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+
+    /// Keep running and re-validate whenever the input, the schema, or the version map changes.
+    #[arg(long)]
+    watch: bool,
+
+    /// Lowest severity that makes the run fail (non-zero exit code) [default: error].
+    #[arg(long = "fail-on", value_enum, value_name = "SEVERITY", global = true)]
+    fail_on: Option<Severity>,
+
+    /// Only report findings whose rule ID starts with one of these (e.g. `PV010` or `PV01`).
+    /// May be repeated or comma-separated.
+    #[arg(long, value_name = "ID", value_delimiter = ',', global = true)]
+    select: Vec<String>,
+
+    /// Never report findings whose rule ID starts with one of these.
+    /// May be repeated or comma-separated.
+    #[arg(long, value_name = "ID", value_delimiter = ',', global = true)]
+    ignore: Vec<String>,
+
+    /// Hide the findings recorded in this baseline file and fail only on new ones.
+    #[arg(long = "baseline", value_name = "FILE")]
+    baseline_path: Option<PathBuf>,
+
+    /// Record every current finding in this baseline file and exit successfully.
+    #[arg(long, value_name = "FILE", conflicts_with = "baseline_path")]
+    write_baseline: Option<PathBuf>,
+
+    /// After the run, list every finding that a baseline, an inline suppression, `--select`,
+    /// `--ignore` or a disabled rule kept from being reported, with the source and age of the
+    /// waiver.
+    #[arg(long = "report-suppressed")]
+    report_suppressed: bool,
+
+    /// Only validate the spec files that changed since this git revision (files new since then
+    /// included), and check with the `metadata-bumps` rule that their `meta.version` and
+    /// `meta.updated` changed along with them.
+    #[arg(long = "changed-since", value_name = "REV")]
+    changed_since: Option<String>,
+
+    /// Cancel the run when it takes longer than this (e.g. `30s`, `2m`) and exit with code 124.
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = supervisor::parse_duration,
+        global = true
+    )]
+    timeout: Option<Duration>,
+
+    /// Cancel the run when a single file takes longer than this to validate.
+    #[arg(
+        long = "file-timeout",
+        value_name = "DURATION",
+        value_parser = supervisor::parse_duration,
+        conflicts_with = "watch"
+    )]
+    file_timeout: Option<Duration>,
+
+    /// When to color the output.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto, global = true)]
+    color: ColorChoice,
+
+    /// Print nothing; only the exit code reports the result.
+    #[arg(long, short = 'q', global = true)]
+    quiet: bool,
+
+    /// Also print which schema each document is checked against and the schema's title and
+    /// description for every violation.
+    #[arg(long, conflicts_with = "quiet", global = true)]
+    verbose: bool,
+
+    /// Neither read nor store parsed schemas and downloads in the on-disk cache.
+    #[arg(long = "no-cache", global = true)]
+    no_cache: bool,
+
+    /// Never access the network; schemas, `$ref` targets and libraries given by URL are taken
+    /// from the copies downloaded by earlier runs.
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Give up on an HTTP(S) download after this long [default: 10s].
+    #[arg(
+        long = "fetch-timeout",
+        value_name = "DURATION",
+        value_parser = supervisor::parse_duration,
+        global = true
+    )]
+    fetch_timeout: Option<Duration>,
+
+    /// Compile schemas as this JSON Schema draft instead of inferring it from `$schema`, and fail
+    /// when a schema declares a different one.
+    #[arg(long, value_enum, value_name = "DRAFT", global = true)]
+    draft: Option<SchemaDraft>,
+
+    /// After validating, browse the files and their findings in a terminal UI instead of printing
+    /// them: expand findings, filter them by severity or rule, and see them in the source.
+    #[arg(long, conflicts_with_all = ["quiet", "watch", "format"])]
+    tui: bool,
+
+    /// After validating, POST a JSON summary of the run (files, counts by severity and findings) to
+    /// this URL, with the `PROGRAM_VERIFY_RESULTS_TOKEN` environment variable as bearer token.
+    #[arg(long = "post-results", value_name = "URL")]
+    post_results: Option<String>,
+
+    /// How findings are reported: for people, as JSON Lines, or as a SARIF log.
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = ReportFormat::Human)]
+    format: ReportFormat,
+
+    /// Also write a Markdown and a JSON report per owner into `--split-report-dir`, with the spec
+    /// files the owners file assigns to the owner and their findings.
+    #[arg(long = "split-report-by", value_enum, value_name = "KEY")]
+    split_report_by: Option<SplitBy>,
+
+    /// CODEOWNERS-like file assigning spec files to owners [default: `owners` from the
+    /// configuration file].
+    #[arg(long, value_name = "FILE")]
+    owners: Option<PathBuf>,
+
+    /// Directory the per-owner reports are written to.
+    #[arg(
+        long = "split-report-dir",
+        value_name = "DIR",
+        default_value = "reports",
+        requires = "split_report_by"
+    )]
+    split_report_dir: PathBuf,
+
+    /// Number of files validated in parallel [default: one per CPU].
+    #[arg(
+        long,
+        short = 'j',
+        value_name = "N",
+        default_value_t = 0,
+        hide_default_value = true
+    )]
+    jobs: usize,
+
+    /// After validating, print how long the run took and the hit and miss counts of every cache
+    /// layer during the run.
+    #[arg(long)]
+    timings: bool,
+
+    /// Schemas compiled during the current run, by origin, shared by the worker threads.
+    #[arg(skip = Memo::new(&cache::COMPILED_SCHEMAS))]
+    compiled_schemas: Memo<Result<Arc<JSONSchema>, String>>,
+
+    /// Custom `format` checks passed to the schema compiler (filled in by `main`).
+    #[arg(skip)]
+    custom_formats: Vec<CustomFormat>,
+
+    /// Custom schema keywords checked after JSON Schema validation.
+    #[arg(skip)]
+    keywords: SchemaKeywords,
+
+    /// Where findings go (filled in by `main`).
+    #[arg(skip)]
+    reporters: Reporters,
+
+    /// Network access for schemas and libraries given by URL (filled in by `main`).
+    #[arg(skip)]
+    fetcher: Fetcher,
+
+    /// Parsed schemas kept between runs (filled in by `main`).
+    #[arg(skip)]
+    cache: SchemaCache,
+
+    /// Where schemas come from (filled in by `main`).
+    #[arg(skip)]
+    schemas: SchemaResolvers,
+
+    /// Settings loaded from the configuration file (filled in by `main`).
+    #[arg(skip)]
+    settings: Config,
+
+    /// Specs come from network clients (`serve`): they may not make the validator read files or
+    /// fetch URLs of their choosing, so libraries are only looked up in the library directories.
+    #[arg(skip)]
+    remote_specs: bool,
+
+    /// Known findings from `--baseline` and the findings seen in the current run.
+    #[arg(skip)]
+    baseline: Mutex<Baseline>,
+
+    /// Findings kept from being reported in the current run (for `--report-suppressed`).
+    #[arg(skip)]
+    audit: SuppressionAudit,
+
+    /// Stops the current validation run early (used by `--watch` when the inputs change mid-run).
+    #[arg(skip)]
+    cancellation: CancellationToken,
+
+    /// Where to report validation progress to the supervising thread.
+    #[arg(skip)]
+    progress: Option<Sender<Progress>>,
+
+    /// Why the configuration could not be applied, for `doctor`, which then runs with the defaults.
+    #[arg(skip)]
+    config_error: Option<String>,
+
+    /// The command line the options were parsed from, for [`Args::command_line`].
+    #[arg(skip)]
+    matches: ArgMatches,
+}
+
+/// Maintenance commands; without one, the inputs are validated.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspect the version map.
+    Versions {
+        #[command(subcommand)]
+        action: VersionsCommand,
+    },
+    /// Summarize a spec corpus.
+    Report {
+        #[command(subcommand)]
+        action: ReportCommand,
+    },
+    /// Inspect the schema of a spec version.
+    Schema {
+        #[command(subcommand)]
+        action: SchemaCommand,
+    },
+    /// Manage the on-disk schema cache.
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    /// Generate documentation from specs.
+    Docs {
+        #[command(subcommand)]
+        action: DocsCommand,
+    },
+    /// Check the environment: configuration file, version map, schemas, cache directory and
+    /// network access to remote schemas.
+    Doctor,
+    /// Describe what this build supports: input and output formats, rule IDs, schema drafts and
+    /// subcommands.
+    Introspect {
+        /// How the capabilities are printed: for people, or as one JSON object for tools.
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = IntrospectFormat::Human)]
+        format: IntrospectFormat,
+    },
+    /// Serve `POST /validate` over HTTP, reusing resolved and compiled schemas across requests.
+    Serve {
+        /// Address to listen on; `0.0.0.0` accepts connections from other hosts.
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Port to listen on.
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Serve a named configuration file as a tenant, selected per request with
+        /// `/tenants/NAME/validate` or the `X-Tenant` header. May be repeated.
+        #[arg(long = "tenant", value_name = "NAME=CONFIG", value_parser = server::parse_tenant)]
+        tenants: Vec<(String, PathBuf)>,
+        /// Append a JSON line to this file for every validation request: client, spec hash,
+        /// schemas and outcome.
+        #[arg(long, value_name = "FILE")]
+        audit_log: Option<PathBuf>,
+        /// Rotate the audit log once it grows past this size (`K`, `M` and `G` suffixes).
+        #[arg(
+            long,
+            value_name = "SIZE",
+            default_value = "10M",
+            value_parser = audit_log::parse_size,
+            requires = "audit_log"
+        )]
+        audit_log_max_size: u64,
+        /// Number of rotated audit logs kept (`FILE.1` is the most recent).
+        #[arg(long, value_name = "N", default_value_t = 5, requires = "audit_log")]
+        audit_log_keep: usize,
+        /// How long the result for a spec is reused for identical uploads.
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "5m",
+            value_parser = supervisor::parse_duration
+        )]
+        result_cache_ttl: Duration,
+        /// Results kept at most; 0 disables the result cache.
+        #[arg(long, value_name = "N", default_value_t = 1000)]
+        result_cache_size: usize,
+    },
+    /// Normalize line endings, strip trailing whitespace and end spec files with a newline.
+    Fmt {
+        /// Spec files or directories to format in place.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Only report the files that need formatting, failing when there are any.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Validate a spec and publish it in canonical form, with its hash, to a spec registry.
+    Publish {
+        /// Spec file to publish.
+        file: PathBuf,
+        /// Registry to publish to: a directory or an HTTP(S) URL [default: `registry` from the
+        /// configuration file].
+        #[arg(long, value_name = "LOCATION")]
+        registry: Option<PathBuf>,
+        /// Sign the spec with this Ed25519 private key (PKCS#8, PEM or DER).
+        #[arg(long, value_name = "FILE")]
+        sign_key: Option<PathBuf>,
+    },
+    /// Check that a deployed spec is the one published under its name and version.
+    VerifyPublished {
+        /// Deployed spec file.
+        file: PathBuf,
+        /// Registry the spec was published to [default: `registry` from the configuration file].
+        #[arg(long, value_name = "LOCATION")]
+        registry: Option<PathBuf>,
+        /// Require a signature made with this Ed25519 public key (PEM, DER or 32 raw bytes).
+        #[arg(long, value_name = "FILE")]
+        verify_key: Option<PathBuf>,
+    },
+    /// Validate a compiled spec artifact and check that its lineage names the given source spec.
+    VerifyArtifact {
+        /// Compiled artifact (JSON) with a `lineage` field.
+        file: PathBuf,
+        /// Source spec the artifact must have been built from.
+        #[arg(long, value_name = "FILE")]
+        source: PathBuf,
+    },
+    /// Visualize the pipeline of a spec.
+    Graph {
+        #[command(subcommand)]
+        action: GraphCommand,
+    },
+    /// Show which specs depend on which shared files across a workspace.
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceCommand,
+    },
+    /// Classify the contract changes between two versions of a spec as breaking or non-breaking.
+    Compat {
+        /// The spec before the change.
+        old: PathBuf,
+        /// The spec after the change.
+        new: PathBuf,
+        /// Exit with 0 even when there are breaking changes.
+        #[arg(long)]
+        allow_breaking: bool,
+    },
+    /// Print the values a JSONPath selects in a spec, one per line as JSON.
+    Query {
+        /// JSONPath of the values, e.g. `$.algorithm.phases[*]`.
+        path: String,
+        /// Spec file to query (`-` for stdin).
+        file: PathBuf,
+        /// Print strings without quotes.
+        #[arg(short, long)]
+        raw: bool,
+    },
+    /// Compare two specs structurally: phases, contracts, outputs and the graph.
+    Diff {
+        /// The spec before the change.
+        old: PathBuf,
+        /// The spec after the change.
+        new: PathBuf,
+        /// How the changes are printed: for people, or as JSON Lines.
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = DiffFormat::Human)]
+        format: DiffFormat,
+    },
+    /// Upgrade a spec to a later spec version and list what needs to be finished by hand.
+    Migrate {
+        /// Spec file to migrate.
+        file: PathBuf,
+        /// Major version to migrate to, e.g. `v3` [default: the latest one a migration reaches].
+        #[arg(long, value_name = "VERSION")]
+        to: Option<String>,
+        /// Migration file to use in addition to the built-in ones; replaces a built-in migration
+        /// from the same major version. May be repeated.
+        #[arg(long = "rules", value_name = "FILE")]
+        rules: Vec<PathBuf>,
+        /// Write the migrated spec to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// List, per spec, what has to be added or changed before it passes a later spec version.
+    Readiness {
+        /// Directories or spec files to check.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+        /// Spec version to check against, e.g. `v3` or `v3.0.0`.
+        #[arg(long, value_name = "VERSION")]
+        target: String,
+    },
+    /// Shrink a failing spec to a minimal reproducer that still reports the same finding.
+    Reduce {
+        /// Failing spec file.
+        file: PathBuf,
+        /// Keep a finding with this code (`PV014`, `schema`); the spec's first finding when omitted.
+        #[arg(long, value_name = "CODE")]
+        code: Option<String>,
+        /// Keep a finding whose message contains this text (with `--code`).
+        #[arg(long, value_name = "TEXT", requires = "code")]
+        message: Option<String>,
+        /// Write the reproducer to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Print a copy of a spec with the values at sensitive paths replaced by placeholders, for
+    /// sharing in bug reports.
+    Scrub {
+        /// Spec file to scrub.
+        file: PathBuf,
+        /// JSON Pointer glob of a sensitive path, e.g. `/implementation/phase_contracts/*/inputs/**`;
+        /// added to `scrub_paths` from the configuration file.
+        #[arg(long = "path", value_name = "PATTERN")]
+        paths: Vec<String>,
+        /// Write the scrubbed spec to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Workspace paths the command operates on; the first one anchors config discovery.
+    fn paths(&self) -> &[PathBuf] {
+        match self {
+            Command::Versions {
+                action: VersionsCommand::Check { paths },
+            }
+            | Command::Report {
+                action: ReportCommand::SchemaUsage { paths },
+            }
+            | Command::Report {
+                action: ReportCommand::Rules { paths, .. },
+            }
+            | Command::Report {
+                action: ReportCommand::Mutants { paths },
+            }
+            | Command::Schema {
+                action: SchemaCommand::Coverage { paths },
+            }
+            | Command::Docs {
+                action: DocsCommand::Generate { files: paths, .. },
+            }
+            | Command::Workspace {
+                action: WorkspaceCommand::Graph { paths, .. },
+            }
+            | Command::Readiness { paths, .. }
+            | Command::Fmt { paths, .. } => paths,
+            Command::Schema {
+                action: SchemaCommand::Hover { file, .. },
+            }
+            | Command::Migrate { file, .. }
+            | Command::Compat { new: file, .. }
+            | Command::Graph {
+                action: GraphCommand::Export { file, .. } | GraphCommand::Order { file },
+            }
+            | Command::Diff { new: file, .. }
+            | Command::Query { file, .. }
+            | Command::Reduce { file, .. }
+            | Command::Publish { file, .. }
+            | Command::VerifyPublished { file, .. }
+            | Command::VerifyArtifact { file, .. }
+            | Command::Scrub { file, .. } => std::slice::from_ref(file),
+            Command::Schema {
+                action: SchemaCommand::Show { file, .. },
+            } => file.as_slice(),
+            Command::Schema { .. }
+            | Command::Cache { .. }
+            | Command::Serve { .. }
+            | Command::Doctor
+            | Command::Introspect { .. } => &[],
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum DocsCommand {
+    /// Render a Markdown page per spec: meta summary, phases with their inputs, outputs, errors,
+    /// retries and fallbacks, and the return contract.
+    Generate {
+        /// Spec files to document.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Write `<file stem>.md` pages into this directory instead of printing them.
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+        /// Compare the pages in the output directory with freshly generated ones and fail when
+        /// any is out of date, without writing.
+        #[arg(long, requires = "output")]
+        check: bool,
+        /// Also write a page per phase to `<file stem>/<phase>.md`, with its dataflow linked and
+        /// stable anchors on its ports and error codes; the spec's page links to them.
+        #[arg(long, requires = "output")]
+        per_phase: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum WorkspaceCommand {
+    /// Render which specs depend on which contract libraries (`implementation.uses`) and files
+    /// (`$ref`), followed transitively, and report files that import each other in a cycle.
+    Graph {
+        /// Directories or spec files of the workspace.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+        /// Output format.
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = WorkspaceFormat::Dot)]
+        format: WorkspaceFormat,
+        /// Write the graph to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum GraphCommand {
+    /// Render `algorithm.graph` and the dataflow between phase contracts, with unresolved
+    /// references highlighted.
+    Export {
+        /// Spec file to render.
+        file: PathBuf,
+        /// Output format.
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+        /// Leave out the nodes that do not run a phase, connecting the phases around them.
+        #[arg(long)]
+        collapse: bool,
+        /// Write the graph to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Print the phases one per line in an order that runs every phase after the phases it
+    /// depends on through graph edges and dataflow, or the cycles that prevent one.
+    Order {
+        /// Spec file to order.
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum VersionsCommand {
+    /// Report version map entries that are broken or used by no spec, versions missing from the
+    /// map, and configuration entries that no longer have any effect.
+    Check {
+        /// Workspace directories or spec files to scan for `spec_version` references.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ReportCommand {
+    /// Show, per spec version, which optional schema fields the specs use and how often.
+    SchemaUsage {
+        /// Directories or spec files to scan.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+    },
+    /// Show which rules fire most across a spec corpus, with their top offending files.
+    Rules {
+        /// Directories or spec files to scan.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// Number of offending files listed per rule.
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        top: usize,
+    },
+    /// Mutation-test the per-document rules: mutate known-good specs and check that the rule each
+    /// mutation targets catches it.
+    Mutants {
+        /// Directories or spec files to mutate.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SchemaCommand {
+    /// Print a completion model of the schema as JSON: the keys, required keys, types and
+    /// allowed values at every field path, for editor plugins.
+    Completions {
+        /// Spec version whose schema to describe (looked up in the version map); the embedded
+        /// schema when omitted. `--schema` takes precedence.
+        #[arg(value_name = "SPEC_VERSION")]
+        spec_version: Option<String>,
+    },
+    /// Print the title, description and constraints the schema gives the node at a JSON Pointer
+    /// of a spec, for editor hovers.
+    Hover {
+        /// Spec file; its `spec_version` selects the schema.
+        file: PathBuf,
+        /// JSON Pointer of the node, e.g. `/implementation/phase_contracts/collect_issue`.
+        pointer: String,
+        /// Document of a multi-document YAML file (1-based).
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        document: usize,
+    },
+    /// Show which properties, enum values and oneOf/anyOf arms of the schema a spec corpus
+    /// exercises, and list the ones it does not.
+    Coverage {
+        /// Directories or spec files to scan.
+        #[arg(value_name = "PATH", default_value = ".")]
+        paths: Vec<PathBuf>,
+    },
+    /// Write the schema as one self-contained file, with every `$ref` (local, to another file or
+    /// to a URL) replaced by the schema it points to. Circular references are reported.
+    Bundle {
+        /// Spec version whose schema to bundle (looked up in the version map); the embedded
+        /// schema when omitted. `--schema` takes precedence.
+        #[arg(value_name = "SPEC_VERSION")]
+        spec_version: Option<String>,
+        /// Write the bundle to this file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Print the schema that validating the given spec would use, picked the same way as during
+    /// validation: `--schema`, then `--spec-version` or the spec's `spec_version`, then the
+    /// embedded schema.
+    Show {
+        /// Spec whose `spec_version` selects the schema; the embedded schema (or `--schema`, or
+        /// `--spec-version`) when omitted.
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Inline every `$ref`, as `schema bundle` does.
+        #[arg(long)]
+        bundle: bool,
+        /// Print YAML instead of JSON.
+        #[arg(long)]
+        yaml: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Remove every cached schema and download.
+    Clear,
+}
+
+/// Version map used when neither `--versions-map` nor the config file names one.
+const DEFAULT_VERSIONS_MAP: &str = "version_map.yaml";
+
+impl Args {
+    /// Loads the configuration file and fills in every option not given on the command line.
+    /// A copy of the options as given on the command line, before the configuration file was
+    /// applied and with fresh runtime state, to apply another configuration to (`serve --tenant`).
+    fn command_line(&self) -> Args {
+        let mut args =
+            Args::from_arg_matches(&self.matches).expect("the options were parsed from these");
+        args.matches = self.matches.clone();
+        args
+    }
+
+    fn apply_config(&mut self) -> Result<(), String> {
+        let start = match &self.command {
+            Some(command) => command
+                .paths()
+                .first()
+                .map_or(Path::new("."), |p| p.as_path()),
+            None => &self.inputs[0],
+        };
+        let config = match &self.config_error {
+            Some(_) => Config::default(),
+            None => Config::load(self.config.as_deref(), start)?,
+        };
+        if let Some(dir) = &config.rule_scripts {
+            scripts::register(&mut rules::registry().write().unwrap(), dir)?;
+        }
+        if !config.assertions.is_empty() {
+            let rule = assertions::AssertionRule::compile(&config.assertions).map_err(|e| {
+                let path = config.path.as_deref().unwrap_or(Path::new("?"));
+                format!("{e} in config {}", path.display())
+            })?;
+            rules::registry()
+                .write()
+                .unwrap()
+                .register(Box::new(rule))?;
+        }
+        let known = rules::rule_names();
+        for (name, settings) in &config.rules {
+            if !known.contains(&name.as_str()) {
+                return Err(format!(
+                    "Error: unknown rule '{name}' in config {} (known rules: {})",
+                    config.path.as_deref().unwrap_or(Path::new("?")).display(),
+                    known.join(", ")
+                ));
+            }
+            rules::registry()
+                .write()
+                .unwrap()
+                .configure(name, settings.clone())?;
+        }
+        let codes = rules::rule_codes();
+        for selector in self.select.iter_mut().chain(self.ignore.iter_mut()) {
+            *selector = selector.to_ascii_uppercase();
+            if !codes
+                .iter()
+                .any(|(code, _)| code.starts_with(selector.as_str()))
+            {
+                return Err(format!(
+                    "Error: '{selector}' does not match any rule ID (see the rule list in the README)"
+                ));
+            }
+        }
+        self.schema = self.schema.take().or_else(|| config.schema.clone());
+        self.versions_map = self
+            .versions_map
+            .take()
+            .or_else(|| config.versions_map.clone());
+        self.fail_on = self.fail_on.or(config.fail_on);
+        self.draft = self.draft.or(config.draft);
+        if let Some(case) = config.identifiers {
+            rules::set_identifier_case(case);
+        }
+        self.custom_formats = formats::enabled(config.custom_formats.as_deref())?;
+        self.library_paths
+            .extend(config.library_paths.iter().cloned());
+        self.remote_specs = matches!(self.command, Some(Command::Serve { .. }));
+        if self.remote_specs {
+            if self.baseline_path.is_some() || self.write_baseline.is_some() {
+                return Err(
+                    "Error: --baseline and --write-baseline cannot be used with serve".to_string(),
+                );
+            }
+            self.baseline = Mutex::new(Baseline::disabled());
+        }
+        if let Some(path) = &self.baseline_path {
+            self.baseline = Mutex::new(Baseline::load(path)?);
+        }
+        if self.report_suppressed && matches!(self.format, ReportFormat::Sarif | ReportFormat::Html)
+        {
+            return Err(
+                "Error: --report-suppressed needs --format human or --format json".to_string(),
+            );
+        }
+        let (cache, downloads) = if self.no_cache {
+            (SchemaCache::disabled(), Downloads::disabled())
+        } else {
+            (SchemaCache::standard(), Downloads::standard())
+        };
+        let timeout = self.fetch_timeout.unwrap_or(DEFAULT_FETCH_TIMEOUT);
+        self.fetcher = Fetcher::new(timeout, self.offline, downloads);
+        self.schemas =
+            SchemaResolvers::standard(self.versions_map(), cache.clone(), self.fetcher.clone());
+        self.cache = cache;
+        let mut reporters = vec![self.format.reporter()];
+        if self.tui {
+            if !io::stdout().is_terminal() {
+                return Err("Error: --tui needs a terminal".to_string());
+            }
+            reporters = vec![Box::new(TuiReporter::default())];
+        }
+        if let Some(url) = self.post_results.take() {
+            if self.offline {
+                return Err("Error: --post-results cannot be used with --offline".to_string());
+            }
+            reporters.push(Box::new(ResultsPoster::new(url, timeout)));
+        }
+        if self.split_report_by == Some(SplitBy::Owner) {
+            let Some(path) = self.owners.take().or_else(|| config.owners.clone()) else {
+                return Err(
+                    "Error: --split-report-by owner needs an owners file (--owners or `owners` in \
+                     the configuration file)"
+                        .to_string(),
+                );
+            };
+            let owners = Owners::load(&path)?;
+            let dir = self.split_report_dir.clone();
+            reporters.push(Box::new(OwnerReporter::new(owners, dir)));
+        }
+        self.reporters = Reporters::new(reporters);
+        self.settings = config;
+        Ok(())
+    }
+
+    fn versions_map(&self) -> &Path {
+        self.versions_map
+            .as_deref()
+            .unwrap_or(Path::new(DEFAULT_VERSIONS_MAP))
+    }
+
+    fn fail_on(&self) -> Severity {
+        self.fail_on.unwrap_or(Severity::Error)
+    }
+
+    /// The compiled form of `schema`. Every distinct schema is compiled once per run, however many
+    /// documents and threads use it.
+    fn compiled_schema(&self, schema: &ResolvedSchema) -> Result<Arc<JSONSchema>, String> {
+        self.compiled_schemas.get_or_compute(&schema.origin, || {
+            let compiled = schemas::compile(
+                schema,
+                &self.cache,
+                &self.fetcher,
+                self.draft,
+                &self.custom_formats,
+            )?;
+            provenance::record_schema(schema);
+            Ok(Arc::new(compiled))
+        })
+    }
+
+    /// Tells the supervising thread (timeouts, signals) how far the run got.
+    fn report_progress(&self, event: Progress) {
+        if let Some(progress) = &self.progress {
+            let _ = progress.send(event);
+        }
+    }
+}
+
+/// Serialization formats accepted for program specifications.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum InputFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl InputFormat {
+    /// Guesses the format from the file extension; stdin and unknown extensions default to YAML.
+    fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("json") => InputFormat::Json,
+            Some("toml") => InputFormat::Toml,
+            _ => InputFormat::Yaml,
+        }
+    }
+}
+
+/// Runs the `program-verify` command line on the arguments of the process.
+pub fn cli() -> ExitCode {
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    args.matches = matches;
+    let machine = args.command.is_none() && (args.format != ReportFormat::Human || args.tui);
+    output::init(args.color, args.quiet, args.verbose, machine);
+    if let Err(msg) = args.apply_config() {
+        // `doctor` reports the error and checks the rest of the environment without the file.
+        if !matches!(args.command, Some(Command::Doctor)) {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+        args.config_error = Some(msg);
+        if let Err(msg) = args.apply_config() {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    }
+    if args.timeout.is_some() && args.watch {
+        failln!("Error: --timeout cannot be combined with --watch");
+        return ExitCode::from(1);
+    }
+    supervisor::supervise(args, run)
+}
+
+/// Runs the selected subcommand, or validates the inputs.
+fn run(args: &Args) -> ExitCode {
+    match &args.command {
+        Some(Command::Versions {
+            action: VersionsCommand::Check { paths },
+        }) => return versions::check(args, paths),
+        Some(Command::Report {
+            action: ReportCommand::SchemaUsage { paths },
+        }) => return usage::schema_usage(args, paths),
+        Some(Command::Report {
+            action: ReportCommand::Rules { paths, top },
+        }) => return rule_report::rules(args, paths, *top),
+        Some(Command::Report {
+            action: ReportCommand::Mutants { paths },
+        }) => return mutants::mutants(args, paths),
+        Some(Command::Schema {
+            action: SchemaCommand::Completions { spec_version },
+        }) => return completions::completions(args, spec_version.as_deref()),
+        Some(Command::Schema {
+            action:
+                SchemaCommand::Hover {
+                    file,
+                    pointer,
+                    document,
+                },
+        }) => return hover::print_hover(args, file, pointer, *document),
+        Some(Command::Schema {
+            action: SchemaCommand::Coverage { paths },
+        }) => return coverage::coverage(args, paths),
+        Some(Command::Schema {
+            action:
+                SchemaCommand::Bundle {
+                    spec_version,
+                    output,
+                },
+        }) => return bundle::bundle(args, spec_version.as_deref(), output.as_deref()),
+        Some(Command::Schema {
+            action: SchemaCommand::Show { file, bundle, yaml },
+        }) => return show::show(args, file.as_deref(), *bundle, *yaml),
+        Some(Command::Cache {
+            action: CacheCommand::Clear,
+        }) => return clear_cache(),
+        Some(Command::Docs {
+            action:
+                DocsCommand::Generate {
+                    files,
+                    output,
+                    check,
+                    per_phase,
+                },
+        }) => return docs::generate(args, files, output.as_deref(), *check, *per_phase),
+        Some(Command::Fmt { paths, check }) => return fmt::fmt(args, paths, *check),
+        Some(Command::Doctor) => return doctor::doctor(args),
+        Some(Command::Introspect { format }) => return introspect::introspect(*format),
+        Some(Command::Serve {
+            host,
+            port,
+            tenants,
+            audit_log,
+            audit_log_max_size,
+            audit_log_keep,
+            result_cache_ttl,
+            result_cache_size,
+        }) => {
+            let audit = audit_log
+                .as_deref()
+                .map(|path| AuditLog::open(path, *audit_log_max_size, *audit_log_keep))
+                .transpose();
+            return match audit {
+                Ok(audit) => {
+                    let results = server::ResultCache::new(*result_cache_ttl, *result_cache_size);
+                    server::serve(args, host, *port, tenants, audit, results)
+                }
+                Err(msg) => {
+                    failln!("{msg}");
+                    ExitCode::from(1)
+                }
+            };
+        }
+        Some(Command::Publish {
+            file,
+            registry,
+            sign_key,
+        }) => return registry::publish(args, file, registry.as_deref(), sign_key.as_deref()),
+        Some(Command::VerifyPublished {
+            file,
+            registry,
+            verify_key,
+        }) => return registry::verify(args, file, registry.as_deref(), verify_key.as_deref()),
+        Some(Command::VerifyArtifact { file, source }) => {
+            return artifact::verify(args, file, source)
+        }
+        Some(Command::Graph {
+            action:
+                GraphCommand::Export {
+                    file,
+                    format,
+                    collapse,
+                    output,
+                },
+        }) => return graph::export(args, file, *format, *collapse, output.as_deref()),
+        Some(Command::Graph {
+            action: GraphCommand::Order { file },
+        }) => return graph::order(args, file),
+        Some(Command::Workspace {
+            action:
+                WorkspaceCommand::Graph {
+                    paths,
+                    format,
+                    output,
+                },
+        }) => return workspace::graph(args, paths, *format, output.as_deref()),
+        Some(Command::Compat {
+            old,
+            new,
+            allow_breaking,
+        }) => return compat::compat(args, old, new, *allow_breaking),
+        Some(Command::Diff { old, new, format }) => return diff::diff(args, old, new, *format),
+        Some(Command::Query { path, file, raw }) => return query::query(args, path, file, *raw),
+        Some(Command::Migrate {
+            file,
+            to,
+            rules,
+            output,
+        }) => return migrate::migrate(args, file, to.as_deref(), rules, output.as_deref()),
+        Some(Command::Readiness { paths, target }) => {
+            return readiness::readiness(args, paths, target)
+        }
+        Some(Command::Reduce {
+            file,
+            code,
+            message,
+            output,
+        }) => {
+            return reduce::reduce(
+                args,
+                file,
+                code.as_deref(),
+                message.as_deref(),
+                output.as_deref(),
+            )
+        }
+        Some(Command::Scrub {
+            file,
+            paths,
+            output,
+        }) => return scrub::scrub(args, file, paths, output.as_deref()),
+        None => {}
+    }
+    if args.watch {
+        if args.inputs.iter().any(|p| is_stdin(p)) {
+            failln!("Error: --watch cannot be combined with reading the spec from stdin ('-')");
+            return ExitCode::from(1);
+        }
+        return watch(args);
+    }
+    validate(args)
+}
+
+/// `cache clear`: empties the schema cache.
+fn clear_cache() -> ExitCode {
+    match cache::clear() {
+        Ok(removed) => {
+            outln!("🧹 Removed {removed} cached file(s).");
+            ExitCode::from(0)
+        }
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Interval between two checks of the watched files' modification times.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Re-runs `validate` every time one of the watched files changes. A run still in progress when
+/// they change again is cancelled and started over. Never returns on its own.
+fn watch(args: &Args) -> ExitCode {
+    let mut last_seen: Option<Vec<(PathBuf, Option<SystemTime>)>> = None;
+    loop {
+        let snapshot = watch_snapshot(args);
+
+        if last_seen.as_ref() != Some(&snapshot) {
+            args.cancellation.reset();
+            let (finished, stop) = mpsc::channel::<()>();
+            let code = thread::scope(|scope| {
+                let snapshot = &snapshot;
+                scope.spawn(move || {
+                    while stop.recv_timeout(WATCH_POLL_INTERVAL) == Err(RecvTimeoutError::Timeout) {
+                        if watch_snapshot(args) != *snapshot {
+                            args.cancellation.cancel();
+                            return;
+                        }
+                    }
+                });
+                let code = validate(args);
+                drop(finished);
+                code
+            });
+            if args.cancellation.is_cancelled() {
+                outln!(
+                    "[{}] inputs changed during validation; starting over…",
+                    utc_timestamp(SystemTime::now())
+                );
+                last_seen = None;
+                continue;
+            }
+            let status = if code == ExitCode::SUCCESS {
+                "passed"
+            } else {
+                "failed"
+            };
+            outln!(
+                "[{}] validation {status}; watching {} file(s) for changes…",
+                utc_timestamp(SystemTime::now()),
+                snapshot.len()
+            );
+            last_seen = Some(snapshot);
+        }
+
+        thread::sleep(WATCH_POLL_INTERVAL);
+    }
+}
+
+/// Modification times of the watched files.
+fn watch_snapshot(args: &Args) -> Vec<(PathBuf, Option<SystemTime>)> {
+    watched_paths(args)
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// Files whose modification should trigger a new validation run in `--watch` mode:
+/// the inputs (re-expanded, so new files in watched directories are picked up), the explicit
+/// schema, the version map and every schema it lists.
+fn watched_paths(args: &Args) -> Vec<PathBuf> {
+    let mut paths = expand_inputs(&args.inputs).unwrap_or_else(|_| args.inputs.clone());
+    if let Some(schema) = &args.schema {
+        paths.push(schema.clone());
+    }
+    if let Some(config) = &args.settings.path {
+        paths.push(config.clone());
+    }
+    if let Ok(map_path) = resolve_versions_map_path(args.versions_map(), &args.inputs[0]) {
+        if let Ok(map) = fs::read_to_string(&map_path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                serde_yaml::from_str::<HashMap<String, String>>(&text).map_err(|e| e.to_string())
+            })
+        {
+            let mut targets: Vec<PathBuf> = map
+                .values()
+                .map(|target| schemas::map_target(&map_path, target))
+                .filter(|target| !schemas::is_url(target))
+                .map(PathBuf::from)
+                .collect();
+            targets.sort();
+            paths.extend(targets);
+        }
+        paths.push(map_path);
+    }
+    paths
+}
+
+/// Formats a point in time as `YYYY-MM-DD HH:MM:SS UTC` without pulling in a date crate.
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Runs the full pipeline (parse → schema → domain rules) once for every input file, followed by
+/// the checks that compare specs with each other.
+fn validate(args: &Args) -> ExitCode {
+    let files = match expand_inputs(&args.inputs) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let files: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|file| is_stdin(file) || !args.settings.is_excluded(file))
+        .collect();
+    if files.is_empty() {
+        failln!("Error: every input is excluded by the configuration file");
+        return ExitCode::from(1);
+    }
+    let files = match &args.changed_since {
+        Some(rev) => {
+            let mut changed = Vec::new();
+            for file in files {
+                if is_stdin(&file) {
+                    changed.push(file);
+                    continue;
+                }
+                match history::changed(&file, rev) {
+                    Ok(true) => changed.push(file),
+                    Ok(false) => {}
+                    Err(msg) => {
+                        failln!("{msg}");
+                        return ExitCode::from(1);
+                    }
+                }
+            }
+            if changed.is_empty() {
+                outln!("✅ No spec file changed since {rev}.");
+                return ExitCode::from(0);
+            }
+            changed
+        }
+        None => files,
+    };
+
+    args.baseline.lock().unwrap().start_run();
+    args.audit.start_run();
+    args.report_progress(Progress::Planned(files.clone()));
+    args.compiled_schemas.clear();
+    provenance::start_run();
+    let started = Instant::now();
+    let cache_counts = cache::STATISTICS.map(|stats| stats.counts());
+    let mut failed_files = 0;
+    let mut corpus: Vec<(String, JsonValue)> = Vec::new();
+    for (file, (ok, documents)) in files.iter().zip(validate_files(args, &files)) {
+        if !ok {
+            failed_files += 1;
+        }
+        let multi = documents.len() > 1;
+        corpus.extend(documents.into_iter().enumerate().map(|(index, mut doc)| {
+            Suppressions::extract(&mut doc);
+            let label = if multi {
+                format!("{} (document #{})", display_input(file), index + 1)
+            } else {
+                display_input(file)
+            };
+            (label, doc)
+        }));
+    }
+
+    let mut cross_spec = Tally::default();
+    if corpus.len() > 1 && !args.cancellation.is_cancelled() {
+        for ((name, label), check) in rules::CROSS_SPEC_RULES {
+            if !rule_enabled(name) {
+                continue;
+            }
+            for diagnostic in check(&corpus) {
+                let finding = RuleFinding {
+                    name,
+                    label,
+                    diagnostic,
+                    waiver: None,
+                };
+                report_rule_finding(args, None, finding, &mut cross_spec);
+            }
+        }
+    }
+    let cross_spec_errors = cross_spec.failed;
+    if args.cancellation.is_cancelled() {
+        return ExitCode::from(1);
+    }
+    args.reporters.finish();
+    if args.timings {
+        print_timings(files.len(), started.elapsed(), cache_counts);
+    }
+
+    if let Some(path) = &args.write_baseline {
+        return match args.baseline.lock().unwrap().write(path) {
+            Ok(count) => {
+                outln!("📝 Recorded {count} finding(s) in {}.", path.display());
+                ExitCode::from(0)
+            }
+            Err(msg) => {
+                failln!("{msg}");
+                ExitCode::from(1)
+            }
+        };
+    }
+    if let Some(path) = &args.baseline_path {
+        let baseline = args.baseline.lock().unwrap();
+        outln!(
+            "ℹ️ {} known finding(s) hidden by the baseline {}.",
+            baseline.matched(),
+            path.display()
+        );
+        if baseline.stale() > 0 {
+            outln!(
+                "ℹ️ {} baseline entries no longer occur; refresh the file with --write-baseline.",
+                baseline.stale()
+            );
+        }
+    }
+    if args.report_suppressed {
+        match args.format {
+            ReportFormat::Json => args.audit.emit(),
+            _ => args.audit.print(),
+        }
+    }
+
+    if files.len() > 1 {
+        if failed_files > 0 {
+            errln!(
+                "❌ {failed_files} of {} files failed validation.",
+                files.len()
+            );
+        } else if !cross_spec_errors {
+            outln!("✅ All {} files match the specification.", files.len());
+        }
+    }
+
+    if failed_files > 0 || cross_spec_errors {
+        ExitCode::from(1)
+    } else {
+        ExitCode::from(0)
+    }
+}
+
+/// `--timings`: the duration of a run and what each cache layer contributed to it, given the
+/// cache counts when the run started.
+fn print_timings(files: usize, elapsed: Duration, before: [(u64, u64); 4]) {
+    errln!(
+        "⏱️ Validated {files} file(s) in {:.3} s.",
+        elapsed.as_secs_f64()
+    );
+    for (stats, (hits_before, misses_before)) in cache::STATISTICS.iter().zip(before) {
+        let (hits, misses) = stats.counts();
+        let (hits, misses) = (hits - hits_before, misses - misses_before);
+        let rate = match hits + misses {
+            0 => "unused".to_string(),
+            lookups => format!("{:.0}% hits", hits as f64 * 100.0 / lookups as f64),
+        };
+        errln!(
+            "   {:<22} {hits:>6} hit(s) {misses:>6} miss(es)  {rate}",
+            stats.name
+        );
+    }
+}
+
+/// Validates `files` on a pool of `--jobs` threads. The output of every file is held back and
+/// printed in input order as soon as all earlier files are done; results come back in that order.
+fn validate_files(args: &Args, files: &[PathBuf]) -> Vec<(bool, Vec<JsonValue>)> {
+    let validate_one = |file: &PathBuf| {
+        output::capture(|| {
+            if args.cancellation.is_cancelled() {
+                return (false, Vec::new());
+            }
+            if files.len() > 1 {
+                outln!("── {} ──", display_input(file));
+            }
+            args.report_progress(Progress::Started(file.clone()));
+            let result = validate_file(args, file);
+            args.report_progress(Progress::Finished(file.clone(), result.0));
+            result
+        })
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs)
+        .build()
+        .expect("failed to start the validation thread pool");
+
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            pool.install(|| {
+                files
+                    .par_iter()
+                    .enumerate()
+                    .for_each_with(sender, |sender, (index, file)| {
+                        let _ = sender.send((index, validate_one(file)));
+                    })
+            })
+        });
+        let mut pending = BTreeMap::new();
+        let mut results = Vec::with_capacity(files.len());
+        for (index, done) in receiver {
+            pending.insert(index, done);
+            while let Some((result, captured)) = pending.remove(&results.len()) {
+                captured.replay(&args.reporters);
+                results.push(result);
+            }
+        }
+        results
+    })
+}
+
+/// Spec file extensions picked up when an input is a directory.
+const SPEC_EXTENSIONS: [&str; 4] = ["yml", "yaml", "json", "toml"];
+
+/// Expands directories into the spec files they contain (recursively, skipping hidden entries)
+/// and returns every input in a stable, sorted order. Explicit file arguments are kept as given.
+fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    fn walk(dir: &Path, acc: &mut Vec<PathBuf>) -> Result<(), String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Error: failed to read directory {}: {e}", dir.display()))?;
+        let mut found = Vec::new();
+        for entry in entries {
+            let entry = entry
+                .map_err(|e| format!("Error: failed to read directory {}: {e}", dir.display()))?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            found.push(entry.path());
+        }
+        found.sort();
+        for path in found {
+            if path.is_dir() {
+                walk(&path, acc)?;
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| SPEC_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+                .unwrap_or(false)
+            {
+                acc.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for input in inputs {
+        if !is_stdin(input) && input.is_dir() {
+            let before = files.len();
+            walk(input, &mut files)?;
+            if files.len() == before {
+                return Err(format!(
+                    "Error: directory {} contains no spec files ({})",
+                    input.display(),
+                    SPEC_EXTENSIONS.join(", ")
+                ));
+            }
+        } else {
+            files.push(input.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Parsed documents of every spec file in `files` not excluded by the configuration, for the
+/// workspace commands. Files that cannot be read or parsed are skipped; a regular validation run
+/// reports them.
+fn workspace_documents(args: &Args, files: &[PathBuf]) -> Vec<(PathBuf, JsonValue)> {
+    let mut documents = Vec::new();
+    for file in files {
+        if args.settings.is_excluded(file) {
+            continue;
+        }
+        let Ok(text) = encoding::read(file).map(|decoded| decoded.text) else {
+            continue;
+        };
+        let format = args
+            .input_format
+            .unwrap_or_else(|| InputFormat::from_path(file));
+        if let Ok(docs) = parse_documents(&text, format) {
+            documents.extend(docs.into_iter().map(|doc| (file.clone(), doc)));
+        }
+    }
+    documents
+}
+
+/// Whether a parsed document looks like a program spec rather than a schema, version map or other
+/// data file that happens to live in the same workspace.
+fn is_program_spec(doc: &JsonValue) -> bool {
+    doc.get("meta").is_some() || doc.get("algorithm").is_some()
+}
+
+/// Human-readable name of an input path (`<stdin>` for `-`).
+fn display_input(path: &Path) -> String {
+    if is_stdin(path) {
+        "<stdin>".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// Validates every document of one input file. Returns whether all of them passed, plus the
+/// parsed documents for the cross-spec checks.
+fn validate_file(args: &Args, path: &Path) -> (bool, Vec<JsonValue>) {
+    let text = match read_input(path) {
+        Ok(s) => s,
+        Err(msg) => {
+            failln!("{msg}");
+            return (false, Vec::new());
+        }
+    };
+
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(path));
+    let documents = match parse_documents(&text, format) {
+        Ok(docs) => docs,
+        Err(msg) => {
+            failln!("Error: {msg}");
+            return (false, Vec::new());
+        }
+    };
+
+    let locations = match format {
+        InputFormat::Yaml | InputFormat::Json => locations::yaml_documents(&text),
+        InputFormat::Toml => Vec::new(),
+    };
+    let no_locations = Locations::default();
+    // Documents are matched with those of the earlier revision by their position in the stream.
+    let previous = match &args.changed_since {
+        Some(rev) if !is_stdin(path) => history::previous_text(path, rev)
+            .ok()
+            .flatten()
+            .and_then(|text| parse_documents(&text, format).ok())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let source_of = |index: usize| Source {
+        path,
+        text: &text,
+        locations: locations.get(index).unwrap_or(&no_locations),
+        schema: None,
+        spec_version: None,
+        previous: previous.get(index),
+    };
+
+    // A single document keeps the historical output; streams get one section per document.
+    if documents.len() == 1 {
+        let ok = validate_document(args, &source_of(0), &documents[0]) == ExitCode::SUCCESS;
+        return (ok, documents);
+    }
+
+    let mut failed = 0;
+    for (index, instance) in documents.iter().enumerate() {
+        if args.cancellation.is_cancelled() {
+            return (false, documents);
+        }
+        outln!("── Document #{} of {} ──", index + 1, documents.len());
+        if validate_document(args, &source_of(index), instance) != ExitCode::SUCCESS {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        errln!(
+            "❌ {failed} of {} documents failed validation.",
+            documents.len()
+        );
+    }
+    (failed == 0, documents)
+}
+
+/// Parses the input text according to `format`. Only YAML supports multiple documents per file.
+fn parse_documents(text: &str, format: InputFormat) -> Result<Vec<JsonValue>, String> {
+    match format {
+        InputFormat::Yaml => parse_yaml_documents(text),
+        InputFormat::Json => serde_json::from_str(text)
+            .map(|doc| vec![doc])
+            .map_err(|e| format!("invalid JSON: {e}")),
+        InputFormat::Toml => toml::from_str::<toml::Value>(text)
+            .map(|doc| vec![toml_to_json(doc)])
+            .map_err(|e| format!("invalid TOML: {e}")),
+    }
+}
+
+/// `documents` written back in the format they were read in.
+fn serialize_documents(documents: &[JsonValue], format: InputFormat) -> Result<String, String> {
+    match format {
+        InputFormat::Yaml => {
+            let texts: Result<Vec<String>, String> = documents
+                .iter()
+                .map(|doc| serde_yaml::to_string(doc).map_err(|e| format!("Error: {e}")))
+                .collect();
+            Ok(texts?.join("---\n"))
+        }
+        InputFormat::Json => Ok(documents
+            .iter()
+            .map(|doc| serde_json::to_string_pretty(doc).unwrap() + "\n")
+            .collect()),
+        InputFormat::Toml => documents
+            .iter()
+            .map(|doc| toml::to_string(doc).map_err(|e| format!("Error: {e}")))
+            .collect(),
+    }
+}
+
+/// Converts a TOML value to JSON; datetimes become their RFC 3339 string form.
+fn toml_to_json(value: toml::Value) -> JsonValue {
+    match value {
+        toml::Value::String(s) => JsonValue::String(s),
+        toml::Value::Integer(i) => JsonValue::from(i),
+        toml::Value::Float(f) => JsonValue::from(f),
+        toml::Value::Boolean(b) => JsonValue::Bool(b),
+        toml::Value::Datetime(dt) => JsonValue::String(dt.to_string()),
+        toml::Value::Array(items) => items.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => JsonValue::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Splits a YAML stream on `---` separators and converts every document to JSON.
+/// Empty documents (e.g. a trailing `---`) are skipped.
+fn parse_yaml_documents(text: &str) -> Result<Vec<JsonValue>, String> {
+    let mut documents = Vec::new();
+    for (index, de) in serde_yaml::Deserializer::from_str(text).enumerate() {
+        let yaml_value = serde_yaml::Value::deserialize(de)
+            .map_err(|e| format!("invalid YAML in document #{}: {e}", index + 1))?;
+        if yaml_value.is_null() {
+            continue;
+        }
+        let instance = serde_json::to_value(yaml_value).map_err(|e| {
+            format!(
+                "YAML→JSON conversion failed in document #{}: {e}",
+                index + 1
+            )
+        })?;
+        documents.push(instance);
+    }
+    if documents.is_empty() {
+        return Err("invalid YAML: the input contains no documents".into());
+    }
+    Ok(documents)
+}
+
+/// The spec version `instance` is validated as and the schema for it (priority: `--schema` >
+/// spec_version → version_map.yaml > embedded).
+fn resolve_schema(
+    args: &Args,
+    source: &Source,
+    instance: &JsonValue,
+) -> Result<(Option<String>, ResolvedSchema), String> {
+    let from_doc = extract_spec_version(instance).map_err(|msg| format!("Error: {msg}"))?;
+    let spec_version = source
+        .spec_version
+        .or(args.spec_version.as_deref())
+        .map(str::to_string)
+        .or(from_doc);
+    let request = SchemaRequest {
+        input: source.path,
+        spec_version: spec_version.as_deref(),
+    };
+    let schema = match source.schema {
+        Some(schema) => Some(schema.into()),
+        None => args.schema.as_ref().map(|p| p.to_string_lossy()),
+    };
+    let resolved = args.schemas.resolve(schema.as_deref(), &request)?;
+    Ok((spec_version, resolved))
+}
+
+/// Validates one parsed document: schema selection, JSON Schema and the domain rules.
+fn validate_document(args: &Args, source: &Source, instance: &JsonValue) -> ExitCode {
+    let mut tally = Tally::default();
+    let input = source.path;
+
+    let raw = instance;
+    let (instance, mut suppressions, findings) = prepare_document(args, input, instance);
+    output::spec(&args.reporters, &display_input(input), &instance);
+    for finding in findings {
+        report_rule_finding(args, Some(source), finding, &mut tally);
+    }
+    let instance = &instance;
+
+    if args.show_json {
+        outln!("{}", serde_json::to_string_pretty(instance).unwrap());
+    }
+
+    let (combined_spec_version, resolved) = match resolve_schema(args, source, instance) {
+        Ok(resolved) => resolved,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let (origin, schema_json) = (&resolved.origin, &resolved.schema);
+    if output::verbose() {
+        match &combined_spec_version {
+            Some(version) => outln!("ℹ️ Checking against {origin} (spec_version {version})."),
+            None => outln!("ℹ️ Checking against {origin}."),
+        }
+    }
+
+    if args.cancellation.is_cancelled() {
+        return ExitCode::from(1);
+    }
+
+    // 3) JSON Schema validation
+    // The draft is inferred from `$schema` unless `--draft` enforces one.
+    let compiled = match args.compiled_schema(&resolved) {
+        Ok(c) => c,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut errors: Vec<KeywordError> = match compiled.validate(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|err| KeywordError {
+                instance_path: err.instance_path.to_string(),
+                schema_path: err.schema_path.to_string(),
+                message: err.to_string(),
+            })
+            .collect(),
+    };
+    // Custom keywords (`x-unique-items-by`, …) are checked as part of the schema.
+    errors.extend(args.keywords.check(args, &resolved, instance));
+    errors.retain(|err| {
+        let finding = Finding::new(Some(input), "schema", &err.instance_path, &err.message);
+        let mut baseline = args.baseline.lock().unwrap();
+        if !baseline.absorb(finding.clone()) {
+            return true;
+        }
+        if let Some(path) = &args.baseline_path {
+            let suppressed = Suppressed {
+                file: Some(display_input(input)),
+                code: SCHEMA_CODE.to_string(),
+                pointer: Some(err.instance_path.clone()),
+                message: err.message.clone(),
+                waiver: Waiver::Baseline { path: path.clone() },
+                since: baseline.since(&finding),
+            };
+            record_suppressed(args, Some(source), suppressed);
+        }
+        false
+    });
+    if !errors.is_empty() {
+        errln!("❌ JSON Schema validation failed:");
+        tally.failed = true;
+    }
+    for err in errors {
+        let report = Report {
+            file: Some(display_input(input)),
+            code: SCHEMA_CODE.to_string(),
+            rule: None,
+            severity: Severity::Error,
+            message: err.message,
+            location: source.locations.locate(&err.instance_path),
+            excerpt: source.excerpt(&err.instance_path),
+            schema_path: Some(err.schema_path),
+            annotation: hover::hover(schema_json, &err.instance_path).and_then(|h| h.summary()),
+            pointer: Some(err.instance_path),
+        };
+        output::report(&args.reporters, report);
+    }
+
+    if args.cancellation.is_cancelled() {
+        return ExitCode::from(1);
+    }
+
+    // 4) Additional domain-specific rules (beyond JSON Schema)
+    let defaulted;
+    let instance = if args.apply_defaults || args.show_defaults {
+        let applied;
+        (defaulted, applied) = defaults::apply(args, &resolved, instance);
+        if output::verbose() && !applied.is_empty() {
+            outln!(
+                "ℹ️ Applied {} schema default(s): {}.",
+                applied.len(),
+                applied.join(", ")
+            );
+        }
+        if args.show_defaults {
+            let format = args
+                .input_format
+                .unwrap_or_else(|| InputFormat::from_path(input));
+            match serialize_documents(std::slice::from_ref(&defaulted), format) {
+                Ok(text) => outln!("{}", text.trim_end()),
+                Err(msg) => failln!("{msg}"),
+            }
+        }
+        &defaulted
+    } else {
+        instance
+    };
+    let (name, label) = rules::OBSERVATIONS_RULE;
+    if rule_enabled(name) {
+        for diagnostic in keywords::deprecated_fields(args, &resolved, instance) {
+            let waiver = suppressions
+                .suppressed_by(&diagnostic)
+                .map(|pointer| Waiver::Inline { pointer });
+            let finding = RuleFinding {
+                name,
+                label,
+                diagnostic,
+                waiver,
+            };
+            report_rule_finding(args, Some(source), finding, &mut tally);
+        }
+    }
+    for finding in document_rule_findings(args, instance, &mut suppressions) {
+        report_rule_finding(args, Some(source), finding, &mut tally);
+    }
+    let (name, label) = rules::METADATA_RULE;
+    if let (Some(rev), Some(previous)) = (&args.changed_since, source.previous) {
+        if rule_enabled(name) {
+            for diagnostic in history::metadata_findings(previous, raw, rev) {
+                let waiver = suppressions
+                    .suppressed_by(&diagnostic)
+                    .map(|pointer| Waiver::Inline { pointer });
+                let finding = RuleFinding {
+                    name,
+                    label,
+                    diagnostic,
+                    waiver,
+                };
+                report_rule_finding(args, Some(source), finding, &mut tally);
+            }
+        }
+    }
+
+    if tally.failed {
+        ExitCode::from(1)
+    } else {
+        if tally.warnings > 0 {
+            outln!(
+                "✅ OK — the document matches the specification ({} warning(s)).",
+                tally.warnings
+            );
+        } else {
+            outln!("✅ OK — the document matches the specification.");
+        }
+        ExitCode::from(0)
+    }
+}
+
+/// The input a document came from, for locating findings in its text.
+struct Source<'a> {
+    path: &'a Path,
+    text: &'a str,
+    locations: &'a Locations,
+    /// Schema and spec version requested for this input, over `--schema` and `--spec-version`.
+    schema: Option<&'a str>,
+    spec_version: Option<&'a str>,
+    /// The same document at the `--changed-since` revision, when the file existed there.
+    previous: Option<&'a JsonValue>,
+}
+
+impl Source<'_> {
+    /// `file:line:column` of the node at `pointer` (or of its closest located ancestor), followed
+    /// by the source line with the node underlined.
+    fn excerpt(&self, pointer: &str) -> Option<String> {
+        let location = self.locations.locate(pointer)?;
+        Some(locations::excerpt(
+            &display_input(self.path),
+            self.text,
+            location,
+        ))
+    }
+}
+
+/// A finding together with the name and label of the rule that produced it.
+struct RuleFinding {
+    name: &'static str,
+    label: &'static str,
+    diagnostic: Diagnostic,
+    /// What keeps the finding from being reported, if anything.
+    waiver: Option<Waiver>,
+}
+
+/// Strips the suppression annotations (the schemas do not allow the extra keys) and merges
+/// imported contract libraries, so every later step sees the effective spec. Returns that spec,
+/// its suppressions and the findings of both steps.
+fn prepare_document(
+    args: &Args,
+    input: &Path,
+    instance: &JsonValue,
+) -> (JsonValue, Suppressions, Vec<RuleFinding>) {
+    let mut findings = Vec::new();
+    let mut instance = instance.clone();
+    let (mut suppressions, annotation_findings) = Suppressions::extract(&mut instance);
+    let (name, label) = rules::SUPPRESSIONS_RULE;
+    if rule_enabled(name) {
+        findings.extend(
+            annotation_findings
+                .into_iter()
+                .map(|diagnostic| RuleFinding {
+                    name,
+                    label,
+                    diagnostic,
+                    waiver: None,
+                }),
+        );
+    }
+
+    if instance.pointer("/implementation/uses").is_some() {
+        let spec_dir = match input.parent() {
+            Some(dir) if !is_stdin(input) && !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let (merged, diagnostics) = libraries::resolve(
+            &instance,
+            &spec_dir,
+            &args.library_paths,
+            &args.fetcher,
+            !args.remote_specs,
+        );
+        let (name, label) = rules::LIBRARIES_RULE;
+        for diagnostic in diagnostics {
+            let waiver = suppressions
+                .suppressed_by(&diagnostic)
+                .map(|pointer| Waiver::Inline { pointer });
+            findings.push(RuleFinding {
+                name,
+                label,
+                diagnostic,
+                waiver,
+            });
+        }
+        instance = merged;
+    }
+    (instance, suppressions, findings)
+}
+
+/// Runs the enabled per-document rules on a prepared spec, marking deselected and suppressed
+/// findings with their waiver, and finally reports the suppressions that matched nothing. With
+/// `--report-suppressed`, disabled rules run too and their findings are marked as well.
+fn document_rule_findings(
+    args: &Args,
+    instance: &JsonValue,
+    suppressions: &mut Suppressions,
+) -> Vec<RuleFinding> {
+    let mut findings = Vec::new();
+    let spec = SpecModel::new(instance);
+    for rule in rules::registered().rules() {
+        if args.cancellation.is_cancelled() {
+            return findings;
+        }
+        let enabled = rule_enabled(rule.name());
+        // Opt-in rules that nothing enabled were not disabled either, so they do not run at all.
+        let reported =
+            args.report_suppressed && rules::registered().settings(rule.name()).is_some();
+        if !enabled && !reported {
+            continue;
+        }
+        for diagnostic in rules::check(rule.as_ref(), &spec) {
+            let waiver = if !enabled {
+                Some(Waiver::Disabled {
+                    rule: rule.name().to_string(),
+                    config: args.settings.path.clone().unwrap_or_default(),
+                })
+            } else if !rules::code_selected(diagnostic.code, &args.select, &args.ignore) {
+                Some(selection_waiver(args, diagnostic.code))
+            } else {
+                suppressions
+                    .suppressed_by(&diagnostic)
+                    .map(|pointer| Waiver::Inline { pointer })
+            };
+            findings.push(RuleFinding {
+                name: rule.name(),
+                label: rule.label(),
+                diagnostic,
+                waiver,
+            });
+        }
+    }
+
+    // Cross-spec findings are not matched against inline suppressions, so those IDs are skipped.
+    let checked = |code: &str| {
+        rules::code_selected(code, &args.select, &args.ignore)
+            && rules::rule_for_code(code).is_some_and(|rule| {
+                rules::CROSS_SPEC_RULES
+                    .iter()
+                    .all(|((name, _), _)| rule != *name)
+                    && rule != rules::SUPPRESSIONS_RULE.0
+                    && rule_enabled(rule)
+            })
+    };
+    let (name, label) = rules::SUPPRESSIONS_RULE;
+    if rule_enabled(name) {
+        findings.extend(
+            suppressions
+                .unused(checked)
+                .into_iter()
+                .map(|diagnostic| RuleFinding {
+                    name,
+                    label,
+                    diagnostic,
+                    waiver: None,
+                }),
+        );
+    }
+    findings
+}
+
+/// Why `--select`/`--ignore` drop findings with rule ID `code`.
+fn selection_waiver(args: &Args, code: &str) -> Waiver {
+    Waiver::Selection {
+        ignored_by: args
+            .ignore
+            .iter()
+            .find(|prefix| code.starts_with(prefix.as_str()))
+            .cloned(),
+    }
+}
+
+/// Whether the configuration leaves rule `name` enabled.
+fn rule_enabled(name: &str) -> bool {
+    rules::registered().enabled(name)
+}
+
+/// Severity of a finding of rule `name` after applying the configured override or escalation
+/// schedule.
+fn configured_severity(name: &str, severity: Severity) -> Severity {
+    rules::registered()
+        .settings(name)
+        .and_then(|r| {
+            r.severity
+                .or_else(|| r.becomes_error.as_ref().map(|e| e.severity()))
+        })
+        .unwrap_or(severity)
+}
+
+/// Message of a finding of rule `name`: the configured template for its ID, if any, filled in.
+fn configured_message(name: &str, diagnostic: &Diagnostic) -> String {
+    rules::registered()
+        .settings(name)
+        .and_then(|r| r.messages.get(diagnostic.code))
+        .map_or_else(
+            || diagnostic.message.clone(),
+            |template| diagnostic.render(template),
+        )
+}
+
+/// Findings reported for one document (or for the cross-spec checks).
+#[derive(Default)]
+struct Tally {
+    /// Some finding reached the `--fail-on` threshold.
+    failed: bool,
+    warnings: usize,
+}
+
+/// Prints a rule finding of `input` (with any configured severity override applied) and adds it
+/// to `tally`. Waived findings, those filtered out by `--select`/`--ignore` and those known to the
+/// baseline are neither printed nor counted, only recorded for `--report-suppressed`.
+fn report_rule_finding(
+    args: &Args,
+    source: Option<&Source>,
+    finding: RuleFinding,
+    tally: &mut Tally,
+) {
+    let RuleFinding {
+        name,
+        label,
+        diagnostic,
+        waiver,
+    } = finding;
+    let waiver = waiver.or_else(|| {
+        (!rules::code_selected(diagnostic.code, &args.select, &args.ignore))
+            .then(|| selection_waiver(args, diagnostic.code))
+    });
+    let suppressed = |waiver, since| Suppressed {
+        file: source.map(|source| display_input(source.path)),
+        code: diagnostic.code.to_string(),
+        pointer: diagnostic.pointer.clone(),
+        message: diagnostic.message.clone(),
+        waiver,
+        since,
+    };
+    if let Some(waiver) = waiver {
+        record_suppressed(args, source, suppressed(waiver, None));
+        return;
+    }
+    let finding = Finding::new(
+        source.map(|source| source.path),
+        diagnostic.code,
+        diagnostic.pointer.as_deref().unwrap_or_default(),
+        &diagnostic.message,
+    );
+    let mut baseline = args.baseline.lock().unwrap();
+    if baseline.absorb(finding.clone()) {
+        let since = baseline.since(&finding);
+        drop(baseline);
+        if let Some(path) = &args.baseline_path {
+            let waiver = Waiver::Baseline { path: path.clone() };
+            record_suppressed(args, source, suppressed(waiver, since));
+        }
+        return;
+    }
+    drop(baseline);
+    let severity = configured_severity(name, diagnostic.severity);
+    let located = source.zip(diagnostic.pointer.as_deref());
+    let report = Report {
+        file: source.map(|source| display_input(source.path)),
+        code: diagnostic.code.to_string(),
+        rule: Some(label),
+        severity,
+        message: configured_message(name, &diagnostic),
+        location: located.and_then(|(source, pointer)| source.locations.locate(pointer)),
+        excerpt: located.and_then(|(source, pointer)| source.excerpt(pointer)),
+        schema_path: None,
+        annotation: None,
+        pointer: diagnostic.pointer,
+    };
+    output::report(&args.reporters, report);
+    tally.failed |= severity >= args.fail_on();
+    if severity == Severity::Warning {
+        tally.warnings += 1;
+    }
+}
+
+/// Records a finding that was not reported for `--report-suppressed`, working out when its waiver
+/// was introduced unless that is already known: from `git blame` of the annotation for inline
+/// suppressions, and from the history of the file otherwise.
+fn record_suppressed(args: &Args, source: Option<&Source>, mut suppressed: Suppressed) {
+    if !args.report_suppressed {
+        return;
+    }
+    if suppressed.since.is_none() {
+        suppressed.since = match &suppressed.waiver {
+            Waiver::Baseline { path } => audit::file_time(path),
+            Waiver::Inline { pointer } => source.and_then(|source| {
+                let location = source.locations.locate(pointer)?;
+                audit::line_time(source.path, location.line)
+            }),
+            Waiver::Disabled { config, .. } => audit::file_time(config),
+            Waiver::Selection { .. } => None,
+        };
+    }
+    args.audit.record(suppressed);
+}
+
+/// `-` as the input path means "read the spec from standard input".
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Reads the spec text from a file or, for `-`, from standard input.
+fn read_input(path: &Path) -> Result<String, String> {
+    let decoded = if is_stdin(path) {
+        encoding::read_from(io::stdin())
+            .map_err(|e| format!("Error: failed to read spec from stdin: {e}"))?
+    } else {
+        encoding::read(path)
+            .map_err(|e| format!("Error: failed to read file {}: {e}", path.display()))?
+    };
+    if let Some(encoding) = decoded.encoding {
+        encoding::warn_converted(&display_input(path), encoding);
+    }
+    Ok(decoded.text)
+}
+
+/// Attempts to extract spec_version from the document. Returns None when the field is absent.
+fn extract_spec_version(doc: &JsonValue) -> Result<Option<String>, String> {
+    match doc.get("spec_version") {
+        Some(JsonValue::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err("Field 'spec_version' exists but is not a string.".into()),
+        None => Ok(None),
+    }
+}
+
+/// Searches for the `version_map` file in several locations so the program works regardless of the working directory.
+fn resolve_versions_map_path(original: &Path, input: &Path) -> Result<PathBuf, String> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    // 1) User-provided path (absolute or relative to the current working directory)
+    if original.is_absolute() {
+        candidates.push(original.to_path_buf());
+    } else {
+        if let Ok(cwd) = env::current_dir() {
+            candidates.push(cwd.join(original));
+        }
+        candidates.push(PathBuf::from(original));
+    }
+
+    // 2) Directory of the input document (stdin has none — the cwd candidates above cover it)
+    if !is_stdin(input) {
+        if let Some(input_dir) = input.parent() {
+            candidates.push(input_dir.join(original));
+        }
+    }
+
+    // 3) Binary directory and its ancestors (target/release -> target -> project root)
+    if let Ok(mut exe_path) = env::current_exe() {
+        if exe_path.pop() {
+            let mut dir_opt = Some(exe_path);
+            while let Some(dir) = dir_opt {
+                candidates.push(dir.join(original));
+                dir_opt = dir.parent().map(Path::to_path_buf);
+            }
+        }
+    }
+
+    // Remove duplicates while keeping order
+    let mut unique = Vec::new();
+    for candidate in candidates {
+        if !unique.iter().any(|p: &PathBuf| p == &candidate) {
+            unique.push(candidate);
+        }
+    }
+
+    let mut tried = Vec::new();
+    for candidate in unique {
+        tried.push(candidate.display().to_string());
+        if candidate.exists() {
+            return candidate.canonicalize().map_err(|e| {
+                format!(
+                    "Error: failed to canonicalize path {}: {e}",
+                    candidate.display()
+                )
+            });
+        }
+    }
+
+    Err(format!(
+        "Error: could not find the version map '{}' in any location. Checked:\n  - {}",
+        original.display(),
+        tried.join("\n  - ")
+    ))
+}
+
+// ▼ Embedded fallback schema lives in src/specyfication.json (used when neither version nor --schema is provided)
+const EMBEDDED_SCHEMA: &str = include_str!("specyfication.json");
@@ -0,0 +1,575 @@
+//! Shared contract libraries imported through `implementation.uses`.
+//!
+//! A library bundle is a YAML or JSON document of the form
+//!
+//! ```yaml
+//! library: common-contracts
+//! version: 2.3.1
+//! phase_contracts: { fetch_data: { ... } }
+//! types: { Customer: { ... } }
+//! ```
+//!
+//! Bundles are found through an explicit `path`/`url` on the `uses` entry or by searching the
+//! library directories for `<library>.{yml,yaml,json}` and `<library>/*.{yml,yaml,json}`.
+
+use crate::{
+    diagnostics::{pointer, Diagnostic},
+    encoding,
+    fetch::Fetcher,
+};
+use serde_json::Value as JsonValue;
+use std::{
+    cmp::Ordering,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Extensions of bundle files picked up from the library directories.
+const BUNDLE_EXTENSIONS: [&str; 3] = ["yml", "yaml", "json"];
+
+/// Resolves every `implementation.uses` entry of `doc` and returns the document with the imported
+/// phase contracts and types merged in (and `uses` removed), plus the findings of the resolution.
+///
+/// Only contracts for phases the spec actually declares are imported; contracts defined locally
+/// take precedence over library ones. Unless `explicit`, entries may not name their bundle by
+/// `path` or `url` and libraries are only looked up in `search_paths`.
+pub fn resolve(
+    doc: &JsonValue,
+    spec_dir: &Path,
+    search_paths: &[PathBuf],
+    fetcher: &Fetcher,
+    explicit: bool,
+) -> (JsonValue, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+    let mut merged = doc.clone();
+
+    let Some(uses) = merged
+        .get_mut("implementation")
+        .and_then(|i| i.as_object_mut())
+        .and_then(|i| i.remove("uses"))
+    else {
+        return (merged, diagnostics);
+    };
+    let Some(entries) = uses.as_array() else {
+        diagnostics.push(
+            Diagnostic::error(
+                "PV070",
+                "implementation.uses must be a list of {library, version} entries",
+            )
+            .at("/implementation/uses"),
+        );
+        return (merged, diagnostics);
+    };
+
+    let declared_phases = declared_phases(doc);
+
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(name) = entry.get("library").and_then(|l| l.as_str()) else {
+            diagnostics.push(
+                Diagnostic::error(
+                    "PV070",
+                    format!("implementation.uses[{index}] must name a library"),
+                )
+                .at(pointer(&["implementation", "uses", &index.to_string()])),
+            );
+            continue;
+        };
+        let constraint =
+            match entry.get("version") {
+                None => VersionReq::any(),
+                Some(value) => {
+                    let text = match value {
+                        JsonValue::String(s) => s.clone(),
+                        JsonValue::Number(n) => n.to_string(),
+                        _ => String::new(),
+                    };
+                    match VersionReq::parse(&text) {
+                        Some(req) => req,
+                        None => {
+                            diagnostics.push(Diagnostic::error(
+                            "PV070",
+                            format!("Library '{name}' has an invalid version constraint '{value}'"),
+                        ).at(pointer(&["implementation", "uses", &index.to_string(), "version"])));
+                            continue;
+                        }
+                    }
+                }
+            };
+
+        let found = load_bundle(
+            name,
+            entry,
+            &constraint,
+            spec_dir,
+            search_paths,
+            fetcher,
+            explicit,
+        );
+        let bundle = match found {
+            Ok((origin, bundle)) => {
+                crate::provenance::record_library(name, &origin, &bundle);
+                bundle
+            }
+            Err(msg) => {
+                diagnostics.push(Diagnostic::error("PV071", msg).at(pointer(&[
+                    "implementation",
+                    "uses",
+                    &index.to_string(),
+                ])));
+                continue;
+            }
+        };
+
+        let implementation = merged
+            .as_object_mut()
+            .map(|root| {
+                root.entry("implementation")
+                    .or_insert_with(|| JsonValue::Object(Default::default()))
+            })
+            .and_then(|i| i.as_object_mut());
+        let Some(implementation) = implementation else {
+            continue;
+        };
+
+        if let Some(contracts) = bundle.get("phase_contracts").and_then(|c| c.as_object()) {
+            let target = implementation
+                .entry("phase_contracts")
+                .or_insert_with(|| JsonValue::Object(Default::default()));
+            if let Some(target) = target.as_object_mut() {
+                for (phase, contract) in contracts {
+                    if !declared_phases.iter().any(|p| p == phase) {
+                        continue;
+                    }
+                    if target.contains_key(phase) {
+                        diagnostics.push(Diagnostic::warning("PV072", format!(
+                            "Phase '{phase}' is defined locally and overrides the contract from library '{name}'"
+                        )).at(pointer(&["implementation", "phase_contracts", phase])));
+                        continue;
+                    }
+                    target.insert(phase.clone(), contract.clone());
+                }
+            }
+        }
+
+        if let Some(types) = bundle.get("types").and_then(|t| t.as_object()) {
+            let target = implementation
+                .entry("types")
+                .or_insert_with(|| JsonValue::Object(Default::default()));
+            if let Some(target) = target.as_object_mut() {
+                for (type_name, definition) in types {
+                    target
+                        .entry(type_name.clone())
+                        .or_insert_with(|| definition.clone());
+                }
+            }
+        }
+    }
+
+    (merged, diagnostics)
+}
+
+/// A library bundle that an `implementation.uses` entry resolves to.
+pub struct Import {
+    pub library: String,
+    /// The version constraint of the entry, `*` when it has none.
+    pub constraint: String,
+    pub version: Version,
+    /// The bundle file, or the URL it was fetched from.
+    pub origin: String,
+}
+
+/// The bundle every `implementation.uses` entry of `doc` resolves to, without merging anything:
+/// by entry, the library name (when the entry has one) and the import or why it does not resolve.
+/// `explicit` is as for [`resolve`].
+pub fn locate(
+    doc: &JsonValue,
+    spec_dir: &Path,
+    search_paths: &[PathBuf],
+    fetcher: &Fetcher,
+    explicit: bool,
+) -> Vec<(Option<String>, Result<Import, String>)> {
+    let entries = doc
+        .pointer("/implementation/uses")
+        .and_then(|u| u.as_array());
+    entries
+        .into_iter()
+        .flatten()
+        .map(|entry| {
+            let Some(name) = entry.get("library").and_then(|l| l.as_str()) else {
+                return (None, Err("the entry names no library".to_string()));
+            };
+            let text = match entry.get("version") {
+                None => "*".to_string(),
+                Some(JsonValue::String(s)) => s.clone(),
+                Some(JsonValue::Number(n)) => n.to_string(),
+                Some(_) => String::new(),
+            };
+            let Some(constraint) = VersionReq::parse(&text) else {
+                let msg = format!("Library '{name}' has an invalid version constraint '{text}'");
+                return (Some(name.to_string()), Err(msg));
+            };
+            let found = load_bundle(
+                name,
+                entry,
+                &constraint,
+                spec_dir,
+                search_paths,
+                fetcher,
+                explicit,
+            )
+            .and_then(|(origin, bundle)| {
+                Ok(Import {
+                    library: name.to_string(),
+                    constraint: constraint.to_string(),
+                    version: bundle_version(&bundle, &origin)?,
+                    origin,
+                })
+            });
+            (Some(name.to_string()), found)
+        })
+        .collect()
+}
+
+/// Phases listed in `algorithm.phases` or as phase nodes of `algorithm.graph`.
+fn declared_phases(doc: &JsonValue) -> Vec<String> {
+    let mut phases: Vec<String> = doc
+        .pointer("/algorithm/phases")
+        .and_then(|p| p.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|i| i.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(nodes) = doc
+        .pointer("/algorithm/graph/nodes")
+        .and_then(|n| n.as_object())
+    {
+        for (node_id, node) in nodes {
+            if node.get("type").and_then(|t| t.as_str()) == Some("phase") {
+                let phase = node
+                    .get("phase")
+                    .and_then(|p| p.as_str())
+                    .unwrap_or(node_id);
+                phases.push(phase.to_string());
+            }
+        }
+    }
+    phases
+}
+
+/// Finds the bundle for one `uses` entry and checks its version against `constraint`. Returns
+/// where the bundle was found along with it.
+fn load_bundle(
+    name: &str,
+    entry: &JsonValue,
+    constraint: &VersionReq,
+    spec_dir: &Path,
+    search_paths: &[PathBuf],
+    fetcher: &Fetcher,
+    explicit: bool,
+) -> Result<(String, JsonValue), String> {
+    if !explicit {
+        if entry.get("path").is_some() || entry.get("url").is_some() {
+            return Err(format!(
+                "Library '{name}' is given by path or url, which this server does not follow; \
+                 install it in a library directory"
+            ));
+        }
+        // The name is joined to the library directories; it must not lead out of them.
+        if !matches!(
+            Path::new(name).components().collect::<Vec<_>>()[..],
+            [Component::Normal(_)]
+        ) {
+            return Err(format!("Library name '{name}' is not a plain name"));
+        }
+    }
+    let explicit = if let Some(path) = entry.get("path").and_then(|p| p.as_str()) {
+        let path = spec_dir.join(path);
+        Some((path.display().to_string(), read_bundle_file(&path)?))
+    } else if let Some(url) = entry.get("url").and_then(|u| u.as_str()) {
+        Some((url.to_string(), fetch_bundle(url, fetcher)?))
+    } else {
+        None
+    };
+
+    if let Some((origin, bundle)) = explicit {
+        let version = bundle_version(&bundle, &origin)?;
+        check_bundle_name(&bundle, name, &origin)?;
+        if !constraint.matches(&version) {
+            return Err(format!(
+                "Library '{name}' from {origin} has version {version}, which does not satisfy '{constraint}'"
+            ));
+        }
+        return Ok((origin, bundle));
+    }
+
+    let mut candidates: Vec<(Version, String, JsonValue)> = Vec::new();
+    let mut found_versions: Vec<Version> = Vec::new();
+    for dir in search_paths {
+        for path in bundle_candidates(dir, name) {
+            let bundle = read_bundle_file(&path)?;
+            if bundle.get("library").and_then(|l| l.as_str()) != Some(name) {
+                continue;
+            }
+            let origin = path.display().to_string();
+            let version = bundle_version(&bundle, &origin)?;
+            found_versions.push(version);
+            if constraint.matches(&version) {
+                candidates.push((version, origin, bundle));
+            }
+        }
+    }
+
+    if let Some((_, origin, bundle)) = candidates.into_iter().max_by(|a, b| a.0.cmp(&b.0)) {
+        return Ok((origin, bundle));
+    }
+
+    if found_versions.is_empty() {
+        let searched: Vec<String> = search_paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        Err(format!(
+            "Library '{name}' was not found (searched: {})",
+            if searched.is_empty() {
+                "no library directories configured".to_string()
+            } else {
+                searched.join(", ")
+            }
+        ))
+    } else {
+        found_versions.sort();
+        let available: Vec<String> = found_versions.iter().map(Version::to_string).collect();
+        Err(format!(
+            "No version of library '{name}' satisfies '{constraint}' (available: {})",
+            available.join(", ")
+        ))
+    }
+}
+
+/// Bundle files in `dir` that may contain library `name`.
+fn bundle_candidates(dir: &Path, name: &str) -> Vec<PathBuf> {
+    let is_bundle = |path: &Path| {
+        path.is_file()
+            && path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| BUNDLE_EXTENSIONS.contains(&e))
+                .unwrap_or(false)
+    };
+
+    let mut paths: Vec<PathBuf> = BUNDLE_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{name}.{ext}")))
+        .filter(|p| is_bundle(p))
+        .collect();
+    if let Ok(entries) = fs::read_dir(dir.join(name)) {
+        let mut nested: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| is_bundle(p))
+            .collect();
+        nested.sort();
+        paths.extend(nested);
+    }
+    paths
+}
+
+fn read_bundle_file(path: &Path) -> Result<JsonValue, String> {
+    let origin = path.display().to_string();
+    let decoded =
+        encoding::read(path).map_err(|e| format!("Failed to read library bundle {origin}: {e}"))?;
+    if let Some(encoding) = decoded.encoding {
+        encoding::warn_converted(&origin, encoding);
+    }
+    parse_bundle(&decoded.text, &origin)
+}
+
+fn fetch_bundle(url: &str, fetcher: &Fetcher) -> Result<JsonValue, String> {
+    let text = fetcher
+        .get(url)
+        .map_err(|e| format!("Failed to fetch library bundle {url}: {e}"))?;
+    parse_bundle(&text, url)
+}
+
+/// Bundles are YAML (which also covers JSON).
+fn parse_bundle(text: &str, origin: &str) -> Result<JsonValue, String> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(text)
+        .map_err(|e| format!("Library bundle {origin} is not valid YAML/JSON: {e}"))?;
+    serde_json::to_value(yaml)
+        .map_err(|e| format!("Library bundle {origin} could not be converted to JSON: {e}"))
+}
+
+fn bundle_version(bundle: &JsonValue, origin: &str) -> Result<Version, String> {
+    bundle
+        .get("version")
+        .and_then(|v| v.as_str())
+        .and_then(Version::parse)
+        .ok_or_else(|| format!("Library bundle {origin} must declare a semantic 'version'"))
+}
+
+fn check_bundle_name(bundle: &JsonValue, name: &str, origin: &str) -> Result<(), String> {
+    match bundle.get("library").and_then(|l| l.as_str()) {
+        Some(found) if found == name => Ok(()),
+        Some(found) => Err(format!(
+            "Library bundle {origin} provides '{found}', expected '{name}'"
+        )),
+        None => Err(format!(
+            "Library bundle {origin} does not declare 'library'"
+        )),
+    }
+}
+
+/// A `major.minor.patch` version; a leading `v` and pre-release/build suffixes are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> Option<Version> {
+        let (major, minor, patch) = parse_partial(text)?;
+        Some(Version {
+            major,
+            minor: minor.unwrap_or(0),
+            patch: patch.unwrap_or(0),
+        })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Parses `1`, `1.2` or `1.2.3` (optionally `v`-prefixed) keeping track of omitted parts.
+fn parse_partial(text: &str) -> Option<(u64, Option<u64>, Option<u64>)> {
+    let text = text.trim();
+    let text = text.strip_prefix('v').unwrap_or(text);
+    let core = text.split(['-', '+']).next()?;
+    let mut parts = core.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = match parts.next() {
+        None | Some("x") | Some("*") => None,
+        Some(p) => Some(p.parse().ok()?),
+    };
+    let patch = match parts.next() {
+        None | Some("x") | Some("*") => None,
+        Some(p) => Some(p.parse().ok()?),
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// A cargo-style version requirement: comma-separated comparators using `^`, `~`, `=`, `>`, `>=`,
+/// `<`, `<=` or `*`. A bare version behaves like `^`.
+#[derive(Debug, Clone)]
+pub struct VersionReq {
+    text: String,
+    comparators: Vec<(Op, Version, Version)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    /// Inclusive lower bound and exclusive upper bound.
+    Range,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl VersionReq {
+    pub fn any() -> Self {
+        VersionReq {
+            text: "*".to_string(),
+            comparators: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<VersionReq> {
+        let mut comparators = Vec::new();
+        for part in text.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                return None;
+            }
+            if part == "*" {
+                continue;
+            }
+            let (op, rest) = [">=", "<=", ">", "<", "=", "^", "~"]
+                .iter()
+                .find_map(|op| part.strip_prefix(op).map(|rest| (*op, rest)))
+                .unwrap_or(("^", part));
+            let (major, minor, patch) = parse_partial(rest)?;
+            let low = Version {
+                major,
+                minor: minor.unwrap_or(0),
+                patch: patch.unwrap_or(0),
+            };
+            let bump = |major: u64, minor: u64, patch: u64| Version {
+                major,
+                minor,
+                patch,
+            };
+            let comparator = match op {
+                ">=" => (Op::GreaterEq, low, low),
+                ">" => (Op::Greater, low, low),
+                "<" => (Op::Less, low, low),
+                "<=" => (Op::LessEq, low, low),
+                "=" => {
+                    let high = match (minor, patch) {
+                        (None, _) => bump(major + 1, 0, 0),
+                        (Some(m), None) => bump(major, m + 1, 0),
+                        (Some(m), Some(p)) => bump(major, m, p + 1),
+                    };
+                    (Op::Range, low, high)
+                }
+                "~" => {
+                    let high = match minor {
+                        None => bump(major + 1, 0, 0),
+                        Some(m) => bump(major, m + 1, 0),
+                    };
+                    (Op::Range, low, high)
+                }
+                _ => {
+                    let high = match (major, minor, patch) {
+                        (0, None, _) => bump(1, 0, 0),
+                        (0, Some(0), None) => bump(0, 1, 0),
+                        (0, Some(0), Some(p)) => bump(0, 0, p + 1),
+                        (0, Some(m), _) => bump(0, m + 1, 0),
+                        (maj, _, _) => bump(maj + 1, 0, 0),
+                    };
+                    (Op::Range, low, high)
+                }
+            };
+            comparators.push(comparator);
+        }
+        Some(VersionReq {
+            text: text.trim().to_string(),
+            comparators,
+        })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|(op, low, high)| match op {
+            Op::Range => version >= low && version < high,
+            Op::Greater => version.cmp(low) == Ordering::Greater,
+            Op::GreaterEq => version >= low,
+            Op::Less => version < low,
+            Op::LessEq => version <= low,
+        })
+    }
+}
+
+impl std::fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
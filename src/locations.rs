@@ -0,0 +1,218 @@
+//! Source locations of spec nodes, so findings can point at `file:line:column` instead of only a
+//! JSON Pointer.
+//!
+//! serde_yaml does not keep spans on its values, so the text is parsed a second time with libyaml's
+//! event parser and every node's start mark is recorded under its JSON Pointer. JSON input goes
+//! through the same parser (JSON is flow-style YAML); TOML input has no locations.
+
+use crate::diagnostics::pointer;
+use std::{collections::HashMap, fmt, mem::MaybeUninit, slice};
+
+/// One-based line and column of a node in its source text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Locations of the nodes of one document, keyed by JSON Pointer. Mapping entries are located at
+/// their key, so a finding about `/meta/title` points at the `title:` line.
+#[derive(Debug, Default)]
+pub struct Locations {
+    nodes: HashMap<String, Location>,
+}
+
+impl Locations {
+    /// Location of the node at `pointer`, or of its closest ancestor present in the source (nodes
+    /// merged in from contract libraries, or the parent of a missing property).
+    pub fn locate(&self, pointer: &str) -> Option<Location> {
+        let mut current = pointer;
+        loop {
+            if let Some(location) = self.nodes.get(current) {
+                return Some(*location);
+            }
+            current = &current[..current.rfind('/')?];
+        }
+    }
+}
+
+/// Renders `location` rustc-style: a `--> file:line:column` header and the source line with the
+/// token starting at the location underlined.
+pub fn excerpt(file: &str, text: &str, location: Location) -> String {
+    let number = location.line.to_string();
+    let gutter = " ".repeat(number.len());
+    let mut out = format!("{gutter}--> {file}:{location}");
+    let Some(line) = text.lines().nth(location.line - 1) else {
+        return out;
+    };
+    let line = line.trim_end();
+    // Keep tabs in the padding so the caret lines up with the source line.
+    let padding: String = line
+        .chars()
+        .take(location.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let token = line
+        .chars()
+        .skip(location.column - 1)
+        .take_while(|c| !c.is_whitespace())
+        .count()
+        .max(1);
+    let mut token_text: String = line.chars().skip(location.column - 1).take(token).collect();
+    // A mapping key is located at the key; leave its `:` out of the underline.
+    if token > 1 && token_text.ends_with(':') {
+        token_text.pop();
+    }
+    let underline = "^".repeat(token_text.chars().count());
+    out.push_str(&format!(
+        "\n{gutter} |\n{number} | {line}\n{gutter} | {padding}{underline}"
+    ));
+    out
+}
+
+/// Nesting level of the walk over the event stream.
+enum Frame {
+    Mapping {
+        segments: Vec<String>,
+        /// Key (and its location) waiting for its value.
+        key: Option<(String, Location)>,
+    },
+    Sequence {
+        segments: Vec<String>,
+        next: usize,
+    },
+    /// A mapping or sequence used as a mapping key; nothing inside it is addressable.
+    ComplexKey,
+}
+
+/// Locations for every non-empty document in `text`, in the order
+/// [`parse_documents`](crate::parse_documents) returns them. Returns what was collected before
+/// the first syntax error; the regular parser reports the error itself.
+pub fn yaml_documents(text: &str) -> Vec<Locations> {
+    let mut documents = Vec::new();
+    let mut current = Locations::default();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut empty = false;
+
+    let mut parser = Box::new(MaybeUninit::<unsafe_libyaml::yaml_parser_t>::uninit());
+    // SAFETY: the parser is heap-allocated so it never moves while libyaml holds pointers into
+    // it, `text` outlives it, and every event is deleted after being read.
+    unsafe {
+        let parser = parser.as_mut_ptr();
+        if unsafe_libyaml::yaml_parser_initialize(parser).fail {
+            return documents;
+        }
+        unsafe_libyaml::yaml_parser_set_encoding(parser, unsafe_libyaml::YAML_UTF8_ENCODING);
+        unsafe_libyaml::yaml_parser_set_input_string(parser, text.as_ptr(), text.len() as u64);
+        loop {
+            let mut event = MaybeUninit::<unsafe_libyaml::yaml_event_t>::uninit();
+            let event = event.as_mut_ptr();
+            if unsafe_libyaml::yaml_parser_parse(parser, event).fail {
+                break;
+            }
+            let mark = (*event).start_mark;
+            let location = Location {
+                line: mark.line as usize + 1,
+                column: mark.column as usize + 1,
+            };
+            let kind = (*event).type_;
+            match kind {
+                unsafe_libyaml::YAML_DOCUMENT_START_EVENT => {
+                    current = Locations::default();
+                    stack.clear();
+                    empty = false;
+                }
+                unsafe_libyaml::YAML_DOCUMENT_END_EVENT if !empty => {
+                    documents.push(std::mem::take(&mut current));
+                }
+                unsafe_libyaml::YAML_SCALAR_EVENT => {
+                    let scalar = (*event).data.scalar;
+                    let value = slice::from_raw_parts(scalar.value, scalar.length as usize);
+                    let value = String::from_utf8_lossy(value).into_owned();
+                    if stack.is_empty() {
+                        // Documents that are just a null are skipped by the regular parser too.
+                        empty = scalar.style == unsafe_libyaml::YAML_PLAIN_SCALAR_STYLE
+                            && matches!(value.as_str(), "" | "~" | "null" | "Null" | "NULL");
+                    }
+                    if let Some(Frame::Mapping {
+                        key: key @ None, ..
+                    }) = stack.last_mut()
+                    {
+                        *key = Some((value, location));
+                    } else {
+                        node(&mut stack, &mut current, location);
+                    }
+                }
+                unsafe_libyaml::YAML_ALIAS_EVENT => {
+                    node(&mut stack, &mut current, location);
+                }
+                unsafe_libyaml::YAML_MAPPING_START_EVENT
+                | unsafe_libyaml::YAML_SEQUENCE_START_EVENT => {
+                    let frame = match node(&mut stack, &mut current, location) {
+                        None => Frame::ComplexKey,
+                        Some(segments) if kind == unsafe_libyaml::YAML_MAPPING_START_EVENT => {
+                            Frame::Mapping {
+                                segments,
+                                key: None,
+                            }
+                        }
+                        Some(segments) => Frame::Sequence { segments, next: 0 },
+                    };
+                    stack.push(frame);
+                }
+                unsafe_libyaml::YAML_MAPPING_END_EVENT
+                | unsafe_libyaml::YAML_SEQUENCE_END_EVENT => {
+                    if let Some(Frame::ComplexKey) = stack.pop() {
+                        if let Some(Frame::Mapping {
+                            key: key @ None, ..
+                        }) = stack.last_mut()
+                        {
+                            *key = Some((String::new(), location));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let done = kind == unsafe_libyaml::YAML_STREAM_END_EVENT;
+            unsafe_libyaml::yaml_event_delete(event);
+            if done {
+                break;
+            }
+        }
+        unsafe_libyaml::yaml_parser_delete(parser);
+    }
+    documents
+}
+
+/// Records a value node starting at `location` under its parent frame and returns its pointer
+/// segments, or `None` when the node is (part of) a mapping key and has no pointer.
+fn node(stack: &mut [Frame], locations: &mut Locations, location: Location) -> Option<Vec<String>> {
+    let (segments, location) = match stack.last_mut() {
+        None => (Vec::new(), location),
+        Some(Frame::ComplexKey) | Some(Frame::Mapping { key: None, .. }) => return None,
+        Some(Frame::Mapping { segments, key }) => {
+            let (name, key_location) = key.take()?;
+            let mut child = segments.clone();
+            child.push(name);
+            (child, key_location)
+        }
+        Some(Frame::Sequence { segments, next }) => {
+            let mut child = segments.clone();
+            child.push(next.to_string());
+            *next += 1;
+            (child, location)
+        }
+    };
+    let segment_refs: Vec<&str> = segments.iter().map(String::as_str).collect();
+    locations
+        .nodes
+        .entry(pointer(&segment_refs))
+        .or_insert(location);
+    Some(segments)
+}
@@ -0,0 +1,629 @@
+//! `migrate`: upgrades a spec to a later spec version. Each upgrade from one major version to the
+//! next is a declarative migration (`src/migrations/*.yaml`, plus any `--rules` files) listing
+//! steps such as `rename`, `remove`, `default` or `scaffold` that apply at JSON Pointer globs
+//! (`*` matches one segment). Migrations are chained, so a v2 spec can be taken to v3 and beyond.
+//! Whatever a step cannot decide on its own is collected as a manual follow-up.
+
+use crate::{
+    diagnostics::pointer, extract_spec_version, parse_documents, read_input,
+    rules::parse_semver_major, schemas::SchemaRequest, serialize_documents, Args, InputFormat,
+};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::{Map, Value as JsonValue};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::OnceLock,
+};
+
+/// Migrations built into the validator, by file name.
+const BUILTIN_MIGRATIONS: &[(&str, &str)] =
+    &[("v2-to-v3.yaml", include_str!("migrations/v2-to-v3.yaml"))];
+
+/// Upgrade of specs from major version `from` to the version `to`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Migration {
+    /// Major version migrated from, e.g. `v2`.
+    from: String,
+    /// `spec_version` written to migrated specs, e.g. `v3.0.0`.
+    to: String,
+    steps: Vec<Step>,
+}
+
+/// One step of a migration. Which fields it needs depends on `op`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Step {
+    op: Op,
+    /// JSON Pointer glob of the values the step applies to.
+    at: String,
+    value: Option<JsonValue>,
+    to: Option<String>,
+    key: Option<String>,
+    #[serde(default)]
+    invert: bool,
+    for_each: Option<String>,
+    /// Reported for every place the step changed (for `expect`: every place it found missing).
+    follow_up: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Op {
+    /// Sets the value at `at` to `value`.
+    Set,
+    /// Sets the value at `at` to `value` where it is missing.
+    Default,
+    /// Removes the value at `at`.
+    Remove,
+    /// Renames the member at `at` to `to`, negating booleans when `invert` is set.
+    Rename,
+    /// Turns the list at `at` into a map under `to`, keyed by each item's `key` member.
+    IndexBy,
+    /// For every string matched by `for_each`, adds `value` at `at` (with `{value}` replaced by the
+    /// string) where nothing is there yet.
+    Scaffold,
+    /// Only reports the follow-up where `at` matches nothing.
+    Expect,
+}
+
+impl Op {
+    /// The operation as written in migration files.
+    fn name(self) -> &'static str {
+        match self {
+            Op::Set => "set",
+            Op::Default => "default",
+            Op::Remove => "remove",
+            Op::Rename => "rename",
+            Op::IndexBy => "index_by",
+            Op::Scaffold => "scaffold",
+            Op::Expect => "expect",
+        }
+    }
+}
+
+/// Something the migration could not do on its own.
+struct FollowUp {
+    pointer: String,
+    message: String,
+}
+
+/// A value matched by a pointer glob.
+struct Match {
+    pointer: String,
+    /// The segments matched by the `*`s of the glob, in order.
+    captures: Vec<String>,
+}
+
+impl Migration {
+    fn parse(text: &str, origin: &str) -> Result<Self, String> {
+        let migration: Migration = serde_yaml::from_str(text)
+            .map_err(|e| format!("Error: invalid migration {origin}: {e}"))?;
+        for (version, field) in [(&migration.from, "from"), (&migration.to, "to")] {
+            if parse_semver_major(version).is_none() {
+                return Err(format!(
+                    "Error: migration {origin}: '{field}' must be a version like v3, got '{version}'"
+                ));
+            }
+        }
+        for (index, step) in migration.steps.iter().enumerate() {
+            let missing = match step.op {
+                Op::Set | Op::Default if step.value.is_none() => Some("value"),
+                Op::Rename if step.to.is_none() => Some("to"),
+                Op::IndexBy if step.key.is_none() => Some("key"),
+                Op::IndexBy if step.to.is_none() => Some("to"),
+                Op::Scaffold if step.for_each.is_none() => Some("for_each"),
+                Op::Scaffold if step.value.is_none() => Some("value"),
+                Op::Expect if step.follow_up.is_none() => Some("follow_up"),
+                _ => None,
+            };
+            if let Some(field) = missing {
+                return Err(format!(
+                    "Error: migration {origin}: step {} ({}) needs '{field}'",
+                    index + 1,
+                    step.op.name()
+                ));
+            }
+        }
+        Ok(migration)
+    }
+
+    fn source_major(&self) -> u64 {
+        parse_semver_major(&self.from).unwrap_or_default()
+    }
+
+    fn target_major(&self) -> u64 {
+        parse_semver_major(&self.to).unwrap_or_default()
+    }
+
+    /// Applies every step to `doc` and sets its `spec_version`.
+    fn apply(&self, doc: &mut JsonValue, follow_ups: &mut Vec<FollowUp>) {
+        for step in &self.steps {
+            step.apply(doc, follow_ups);
+        }
+        if let Some(map) = doc.as_object_mut() {
+            map.insert("spec_version".into(), self.to.clone().into());
+        }
+    }
+}
+
+impl Step {
+    fn apply(&self, doc: &mut JsonValue, follow_ups: &mut Vec<FollowUp>) {
+        let mut note = |doc: &JsonValue, at: &Match, extra: Option<&str>| {
+            let message = extra.map(str::to_string).or_else(|| {
+                self.follow_up
+                    .as_deref()
+                    .map(|template| render(template, doc, at, None))
+            });
+            if let Some(message) = message {
+                follow_ups.push(FollowUp {
+                    pointer: at.pointer.clone(),
+                    message,
+                });
+            }
+        };
+        match self.op {
+            Op::Set | Op::Default => {
+                for at in expand(doc, &self.at) {
+                    if self.op == Op::Default && doc.pointer(&at.pointer).is_some() {
+                        continue;
+                    }
+                    let value = render_value(self.value.as_ref().unwrap(), doc, &at, None);
+                    if insert(doc, &at.pointer, value) {
+                        note(doc, &at, None);
+                    }
+                }
+            }
+            Op::Remove => {
+                // Last match first, so removing list items does not shift the later matches.
+                for at in expand(doc, &self.at).into_iter().rev() {
+                    if doc.pointer(&at.pointer).is_none() {
+                        continue;
+                    }
+                    note(doc, &at, None);
+                    remove(doc, &at.pointer);
+                }
+            }
+            Op::Rename => {
+                for at in expand(doc, &self.at) {
+                    let Some(mut value) = doc.pointer(&at.pointer).cloned() else {
+                        continue;
+                    };
+                    let (parent, _) = split(&at.pointer);
+                    let name = render(self.to.as_deref().unwrap(), doc, &at, None);
+                    let target = format!("{parent}{}", pointer(&[&name]));
+                    if doc.pointer(&target).is_some() {
+                        let message = format!("'{name}' already exists; rename by hand");
+                        note(doc, &at, Some(&message));
+                        continue;
+                    }
+                    if let (true, Some(flag)) = (self.invert, value.as_bool()) {
+                        value = (!flag).into();
+                    }
+                    note(doc, &at, None);
+                    remove(doc, &at.pointer);
+                    insert(doc, &target, value);
+                }
+            }
+            Op::IndexBy => {
+                for at in expand(doc, &self.at) {
+                    self.index_by(doc, &at, &mut note);
+                }
+            }
+            Op::Scaffold => {
+                for item in expand(doc, self.for_each.as_deref().unwrap()) {
+                    let Some(name) = doc.pointer(&item.pointer).and_then(|v| v.as_str()) else {
+                        continue;
+                    };
+                    let name = name.to_string();
+                    let target = render_pointer(&self.at, doc, &item, Some(&name));
+                    if doc.pointer(&target).is_some() {
+                        continue;
+                    }
+                    let at = Match {
+                        pointer: target,
+                        captures: item.captures.clone(),
+                    };
+                    let value = render_value(self.value.as_ref().unwrap(), doc, &at, Some(&name));
+                    if insert(doc, &at.pointer, value) {
+                        if let Some(template) = &self.follow_up {
+                            let message = render(template, doc, &at, Some(&name));
+                            note(doc, &at, Some(&message));
+                        }
+                    }
+                }
+            }
+            Op::Expect => {
+                let matches = expand(doc, &self.at);
+                if matches.iter().all(|at| doc.pointer(&at.pointer).is_none()) {
+                    let at = Match {
+                        pointer: self.at.clone(),
+                        captures: Vec::new(),
+                    };
+                    note(doc, &at, None);
+                }
+            }
+        }
+    }
+
+    /// Moves the list at `at` into the map under `to`, unless some item lacks a string `key`.
+    fn index_by(
+        &self,
+        doc: &mut JsonValue,
+        at: &Match,
+        note: &mut impl FnMut(&JsonValue, &Match, Option<&str>),
+    ) {
+        let key = self.key.as_deref().unwrap();
+        let Some(items) = doc.pointer(&at.pointer).and_then(|v| v.as_array()) else {
+            return;
+        };
+        let mut entries = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let mut item = item.clone();
+            match item
+                .as_object_mut()
+                .and_then(|map| map.remove(key))
+                .and_then(|name| name.as_str().map(str::to_string))
+            {
+                Some(name) => entries.push((name, item)),
+                None => {
+                    let message =
+                        format!("item {index} has no '{key}' to index it by; left unchanged");
+                    note(doc, at, Some(&message));
+                    return;
+                }
+            }
+        }
+        let target = render_pointer(self.to.as_deref().unwrap(), doc, at, None);
+        remove(doc, &at.pointer);
+        insert(doc, &target, JsonValue::Object(Map::new()));
+        for (name, item) in entries {
+            let entry = format!("{target}{}", pointer(&[&name]));
+            if doc.pointer(&entry).is_some() {
+                let message = format!("'{name}' already exists under {target}; merge it by hand");
+                note(doc, at, Some(&message));
+                continue;
+            }
+            insert(doc, &entry, item);
+            note(
+                doc,
+                &Match {
+                    pointer: entry,
+                    captures: at.captures.clone(),
+                },
+                None,
+            );
+        }
+    }
+}
+
+/// Every pointer the glob `pattern` matches in `doc`. The last segment may name a member that does
+/// not exist yet, as long as its parent does.
+fn expand(doc: &JsonValue, pattern: &str) -> Vec<Match> {
+    let segments: Vec<&str> = pattern.split('/').skip(1).collect();
+    let mut matches = vec![Match {
+        pointer: String::new(),
+        captures: Vec::new(),
+    }];
+    for (index, segment) in segments.iter().enumerate() {
+        let last = index + 1 == segments.len();
+        let mut next = Vec::new();
+        for at in matches {
+            let Some(value) = doc.pointer(&at.pointer) else {
+                continue;
+            };
+            let children: Vec<String> = match (value, *segment) {
+                (JsonValue::Object(map), "*") => map.keys().cloned().collect(),
+                (JsonValue::Array(items), "*") => (0..items.len()).map(|i| i.to_string()).collect(),
+                (JsonValue::Object(_) | JsonValue::Array(_), name) => vec![name.to_string()],
+                _ => Vec::new(),
+            };
+            for child in children {
+                let pointer = format!("{}{}", at.pointer, pointer(&[&child]));
+                if !last && doc.pointer(&pointer).is_none() {
+                    continue;
+                }
+                let mut captures = at.captures.clone();
+                if *segment == "*" {
+                    captures.push(child);
+                }
+                next.push(Match { pointer, captures });
+            }
+        }
+        matches = next;
+    }
+    matches
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{([a-z0-9_]+)\}").unwrap())
+}
+
+/// Fills in `template`: `{1}`, `{2}`, … are the segments matched by the glob's `*`s, `{name}` the
+/// `name` of the object holding the matched value, `{value}` the `for_each` item of `scaffold`
+/// and `{path}` the matched pointer. Unknown placeholders are kept as written.
+fn render(template: &str, doc: &JsonValue, at: &Match, value: Option<&str>) -> String {
+    let (parent, _) = split(&at.pointer);
+    placeholder_regex()
+        .replace_all(template, |caps: &Captures| {
+            let name = &caps[1];
+            let filled = match name {
+                "name" => doc
+                    .pointer(parent)
+                    .and_then(|p| p.get("name"))
+                    .and_then(|n| n.as_str())
+                    .map(str::to_string),
+                "value" => value.map(str::to_string),
+                "path" => Some(at.pointer.clone()),
+                _ => name
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| at.captures.get(n.checked_sub(1)?).cloned()),
+            };
+            filled.unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// [`render`] for a pointer template, escaping the filled-in segments.
+fn render_pointer(template: &str, doc: &JsonValue, at: &Match, value: Option<&str>) -> String {
+    let segments: Vec<String> = template
+        .split('/')
+        .skip(1)
+        .map(|segment| render(segment, doc, at, value))
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    pointer(&segments)
+}
+
+/// [`render`] applied to every string (and member name) in `template`.
+fn render_value(
+    template: &JsonValue,
+    doc: &JsonValue,
+    at: &Match,
+    value: Option<&str>,
+) -> JsonValue {
+    match template {
+        JsonValue::String(s) => render(s, doc, at, value).into(),
+        JsonValue::Array(items) => items
+            .iter()
+            .map(|item| render_value(item, doc, at, value))
+            .collect(),
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(k, v)| (render(k, doc, at, value), render_value(v, doc, at, value)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+/// Parent pointer and (escaped) last segment of `pointer`.
+fn split(pointer: &str) -> (&str, &str) {
+    pointer.rsplit_once('/').unwrap_or(("", pointer))
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+/// Sets the value at `at`, creating missing parent objects. Returns `false` if a parent is not an
+/// object (or, for list items, not a list with that index).
+fn insert(doc: &mut JsonValue, at: &str, value: JsonValue) -> bool {
+    let (parent, last) = split(at);
+    if doc.pointer(parent).is_none() && !insert(doc, parent, JsonValue::Object(Map::new())) {
+        return false;
+    }
+    match doc.pointer_mut(parent) {
+        Some(JsonValue::Object(map)) => {
+            map.insert(unescape(last), value);
+            true
+        }
+        Some(JsonValue::Array(items)) => match last.parse::<usize>() {
+            Ok(index) if index < items.len() => {
+                items[index] = value;
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn remove(doc: &mut JsonValue, at: &str) {
+    let (parent, last) = split(at);
+    match doc.pointer_mut(parent) {
+        Some(JsonValue::Object(map)) => {
+            map.remove(&unescape(last));
+        }
+        Some(JsonValue::Array(items)) => {
+            if let Ok(index) = last.parse::<usize>() {
+                if index < items.len() {
+                    items.remove(index);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The built-in migrations followed by those in `files`; a later migration from the same major
+/// version replaces an earlier one.
+fn load_migrations(files: &[PathBuf]) -> Result<Vec<Migration>, String> {
+    let mut migrations: Vec<Migration> = Vec::new();
+    let mut add = |migration: Migration| {
+        migrations.retain(|m| m.source_major() != migration.source_major());
+        migrations.push(migration);
+    };
+    for (name, text) in BUILTIN_MIGRATIONS {
+        add(Migration::parse(text, name)?);
+    }
+    for file in files {
+        let text = fs::read_to_string(file)
+            .map_err(|e| format!("Error: failed to read migration {}: {e}", file.display()))?;
+        add(Migration::parse(&text, &file.display().to_string())?);
+    }
+    Ok(migrations)
+}
+
+/// The migrations that take a spec from major version `from` to `target` (as far as possible when
+/// `target` is `None`).
+fn chain(
+    migrations: &[Migration],
+    from: u64,
+    target: Option<u64>,
+) -> Result<Vec<&Migration>, String> {
+    let mut steps = Vec::new();
+    let mut current = from;
+    while target.is_none_or(|target| current < target) {
+        match migrations.iter().find(|m| m.source_major() == current) {
+            Some(migration) if migration.target_major() > current => {
+                current = migration.target_major();
+                steps.push(migration);
+            }
+            _ => break,
+        }
+    }
+    match target {
+        Some(target) if current != target => Err(format!(
+            "Error: no migration path from v{from} to v{target} (available: {})",
+            migrations
+                .iter()
+                .map(|m| format!("{} → {}", m.from, m.to))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        _ => Ok(steps),
+    }
+}
+
+/// Prints `file` migrated to `to` (the latest reachable version when `None`), or writes it to
+/// `output`, and lists the manual follow-ups on stderr.
+pub fn migrate(
+    args: &Args,
+    file: &Path,
+    to: Option<&str>,
+    rules: &[PathBuf],
+    output: Option<&Path>,
+) -> ExitCode {
+    match migrated(args, file, to, rules) {
+        Ok((text, summary)) => match output {
+            Some(path) => match fs::write(path, text) {
+                Ok(()) => {
+                    outln!(
+                        "🛠️ Wrote the migrated spec to {} ({summary}).",
+                        path.display()
+                    );
+                    ExitCode::from(0)
+                }
+                Err(e) => {
+                    failln!("Error: failed to write {}: {e}", path.display());
+                    ExitCode::from(1)
+                }
+            },
+            None => {
+                outln!("{}", text.trim_end());
+                ExitCode::from(0)
+            }
+        },
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// The migrated text of `file` and a one-line summary of what happened.
+fn migrated(
+    args: &Args,
+    file: &Path,
+    to: Option<&str>,
+    rules: &[PathBuf],
+) -> Result<(String, String), String> {
+    let target = match to {
+        Some(version) => Some(
+            parse_semver_major(version)
+                .ok_or_else(|| format!("Error: --to must be a version like v3, got '{version}'"))?,
+        ),
+        None => None,
+    };
+    let migrations = load_migrations(rules)?;
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let text = read_input(file)?;
+    let mut documents = parse_documents(&text, format).map_err(|e| format!("Error: {e}"))?;
+
+    let mut follow_ups: Vec<FollowUp> = Vec::new();
+    let mut summary = Vec::new();
+    for (index, doc) in documents.iter_mut().enumerate() {
+        let label = if index > 0 {
+            format!(" (document #{})", index + 1)
+        } else {
+            String::new()
+        };
+        let version = extract_spec_version(doc)
+            .map_err(|e| format!("Error: {e}"))?
+            .ok_or_else(|| format!("Error: {}{label} has no spec_version", file.display()))?;
+        let from = parse_semver_major(&version).ok_or_else(|| {
+            format!(
+                "Error: {}{label} has an invalid spec_version '{version}'",
+                file.display()
+            )
+        })?;
+        let steps = chain(&migrations, from, target)?;
+        if steps.is_empty() {
+            summary.push(format!("{version}{label} needs no migration"));
+            continue;
+        }
+        let before = follow_ups.len();
+        for migration in &steps {
+            migration.apply(doc, &mut follow_ups);
+        }
+        let migrated_to = &steps.last().unwrap().to;
+        summary.push(format!("{version} → {migrated_to}{label}"));
+        if follow_ups.len() == before {
+            check_migrated(args, file, doc, migrated_to);
+        }
+    }
+
+    if !follow_ups.is_empty() {
+        errln!("📝 {} manual follow-up(s):", follow_ups.len());
+        for follow_up in &follow_ups {
+            errln!("  • {}: {}", follow_up.pointer, follow_up.message);
+        }
+    }
+    let text = serialize_documents(&documents, format)?;
+    summary.push(format!("{} follow-up(s)", follow_ups.len()));
+    Ok((text, summary.join(", ")))
+}
+
+/// Warns when a migrated document without follow-ups still fails the schema of its new version.
+fn check_migrated(args: &Args, file: &Path, doc: &JsonValue, version: &str) {
+    let request = SchemaRequest {
+        input: file,
+        spec_version: Some(version),
+    };
+    let Ok(resolved) = args.schemas.resolve(None, &request) else {
+        return;
+    };
+    let Ok(compiled) = args.compiled_schema(&resolved) else {
+        return;
+    };
+    let errors: Vec<String> = match compiled.validate(doc) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|error| format!("  • {error} (instance: {})", error.instance_path))
+            .collect(),
+    };
+    if !errors.is_empty() {
+        errln!(
+            "⚠️ The migrated spec does not pass {} yet:",
+            resolved.origin
+        );
+        errln!("{}", errors.join("\n"));
+    }
+}
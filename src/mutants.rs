@@ -0,0 +1,1488 @@
+//! `report mutants`: mutation testing of the per-document rules. Known-good specs are mutated the
+//! way specs typically break (a phase renamed, an output dropped, an error code duplicated, …) and
+//! each mutant must make the rule ID its mutation targets report something the original spec does
+//! not. A mutant that slips through points at a rule that has silently stopped working.
+
+use crate::{
+    diagnostics::{pointer, Diagnostic},
+    display_input, expand_inputs, is_program_spec,
+    port_types::PortType,
+    prepare_document, rule_enabled,
+    rules::{self, SpecModel},
+    workspace_documents, Args,
+};
+use jsonschema::JSONSchema;
+use serde_json::{json, Map, Value as JsonValue};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+/// A mutated copy of a spec and where it was changed.
+struct Mutant {
+    site: String,
+    doc: JsonValue,
+}
+
+/// A systematic mutation and the rule ID that must catch it.
+struct Operator {
+    name: &'static str,
+    code: &'static str,
+    /// One mutant per place in the spec where the mutation applies.
+    mutate: fn(&JsonValue) -> Vec<Mutant>,
+}
+
+/// Every mutation operator, in rule ID order.
+const OPERATORS: &[Operator] = &[
+    Operator {
+        name: "rename-algorithm",
+        code: "PV001",
+        mutate: rename_algorithm,
+    },
+    Operator {
+        name: "drop-title",
+        code: "PV002",
+        mutate: drop_title,
+    },
+    Operator {
+        name: "drop-algorithm-name",
+        code: "PV003",
+        mutate: drop_algorithm_name,
+    },
+    Operator {
+        name: "rename-contract",
+        code: "PV010",
+        mutate: rename_contract,
+    },
+    Operator {
+        name: "drop-contracts",
+        code: "PV011",
+        mutate: drop_contracts,
+    },
+    Operator {
+        name: "rename-phase",
+        code: "PV012",
+        mutate: rename_phase,
+    },
+    Operator {
+        name: "duplicate-error-code",
+        code: "PV013",
+        mutate: duplicate_error_code,
+    },
+    Operator {
+        name: "duplicate-output",
+        code: "PV013",
+        mutate: duplicate_output,
+    },
+    Operator {
+        name: "unknown-source-phase",
+        code: "PV014",
+        mutate: unknown_source_phase,
+    },
+    Operator {
+        name: "drop-output",
+        code: "PV016",
+        mutate: drop_output,
+    },
+    Operator {
+        name: "drop-source-path",
+        code: "PV017",
+        mutate: drop_source_path,
+    },
+    Operator {
+        name: "unknown-retryable-error",
+        code: "PV018",
+        mutate: unknown_retryable_error,
+    },
+    Operator {
+        name: "drop-source-port",
+        code: "PV019",
+        mutate: drop_source_port,
+    },
+    Operator {
+        name: "unknown-classification",
+        code: "PV021",
+        mutate: unknown_classification,
+    },
+    Operator {
+        name: "non-boolean-flag",
+        code: "PV030",
+        mutate: non_boolean_flag,
+    },
+    Operator {
+        name: "duplicate-side-effect",
+        code: "PV031",
+        mutate: duplicate_side_effect,
+    },
+    Operator {
+        name: "mark-non-idempotent",
+        code: "PV032",
+        mutate: mark_non_idempotent,
+    },
+    Operator {
+        name: "retry-non-idempotent",
+        code: "PV033",
+        mutate: retry_non_idempotent,
+    },
+    Operator {
+        name: "drop-idempotency-key",
+        code: "PV040",
+        mutate: drop_idempotency_key,
+    },
+    Operator {
+        name: "unknown-idempotency-key",
+        code: "PV042",
+        mutate: unknown_idempotency_key,
+    },
+    Operator {
+        name: "rename-metric",
+        code: "PV050",
+        mutate: rename_metric,
+    },
+    Operator {
+        name: "unknown-metric-type",
+        code: "PV052",
+        mutate: unknown_metric_type,
+    },
+    Operator {
+        name: "duplicate-metric",
+        code: "PV053",
+        mutate: duplicate_metric,
+    },
+    Operator {
+        name: "unknown-alert-metric",
+        code: "PV055",
+        mutate: unknown_alert_metric,
+    },
+    Operator {
+        name: "deprecate-phase",
+        code: "PV101",
+        mutate: deprecate_phase,
+    },
+    Operator {
+        name: "list-contracts",
+        code: "PV120",
+        mutate: list_contracts,
+    },
+    Operator {
+        name: "reverse-graph-edge",
+        code: "PV130",
+        mutate: reverse_graph_edge,
+    },
+    Operator {
+        name: "drop-upstream-output",
+        code: "PV150",
+        mutate: drop_upstream_output,
+    },
+    Operator {
+        name: "add-unread-output",
+        code: "PV140",
+        mutate: add_unread_output,
+    },
+    Operator {
+        name: "retype-output",
+        code: "PV160",
+        mutate: retype_output,
+    },
+    Operator {
+        name: "break-example",
+        code: "PV170",
+        mutate: break_example,
+    },
+    Operator {
+        name: "zero-max-attempts",
+        code: "PV180",
+        mutate: zero_max_attempts,
+    },
+    Operator {
+        name: "unknown-backoff-strategy",
+        code: "PV181",
+        mutate: unknown_backoff_strategy,
+    },
+    Operator {
+        name: "invert-backoff-delays",
+        code: "PV182",
+        mutate: invert_backoff_delays,
+    },
+    Operator {
+        name: "drop-initial-delay",
+        code: "PV183",
+        mutate: drop_initial_delay,
+    },
+    Operator {
+        name: "exceed-time-budget",
+        code: "PV190",
+        mutate: exceed_time_budget,
+    },
+    Operator {
+        name: "malform-timeout",
+        code: "PV191",
+        mutate: malform_timeout,
+    },
+    Operator {
+        name: "self-fallback",
+        code: "PV200",
+        mutate: self_fallback,
+    },
+    Operator {
+        name: "fallback-cycle",
+        code: "PV201",
+        mutate: fallback_cycle,
+    },
+    Operator {
+        name: "near-copy-contract",
+        code: "PV210",
+        mutate: near_copy_contract,
+    },
+    Operator {
+        name: "unbuilt-output",
+        code: "PV240",
+        mutate: unbuilt_output,
+    },
+    Operator {
+        name: "repeat-algorithm-output",
+        code: "PV241",
+        mutate: repeat_algorithm_output,
+    },
+    Operator {
+        name: "return-built-output",
+        code: "PV242",
+        mutate: return_built_output,
+    },
+    Operator {
+        name: "unknown-edge-node",
+        code: "PV250",
+        mutate: unknown_edge_node,
+    },
+    Operator {
+        name: "repeat-graph-edge",
+        code: "PV251",
+        mutate: repeat_graph_edge,
+    },
+    Operator {
+        name: "unhandled-branch",
+        code: "PV252",
+        mutate: unhandled_branch,
+    },
+    Operator {
+        name: "unlisted-branch-edge",
+        code: "PV253",
+        mutate: unlisted_branch_edge,
+    },
+    Operator {
+        name: "unknown-entry",
+        code: "PV260",
+        mutate: unknown_entry,
+    },
+    Operator {
+        name: "second-entry",
+        code: "PV261",
+        mutate: second_entry,
+    },
+    Operator {
+        name: "loop-back-terminals",
+        code: "PV262",
+        mutate: loop_back_terminals,
+    },
+    Operator {
+        name: "early-exit",
+        code: "PV263",
+        mutate: early_exit,
+    },
+    Operator {
+        name: "dangling-condition",
+        code: "PV270",
+        mutate: dangling_condition,
+    },
+    Operator {
+        name: "unknown-condition-name",
+        code: "PV271",
+        mutate: unknown_condition_name,
+    },
+];
+
+/// Mutants and kills of one operator.
+#[derive(Default)]
+struct OperatorTally {
+    mutants: usize,
+    killed: usize,
+}
+
+/// Mutates the specs under `paths` with every operator whose rule is enabled and selected, and
+/// prints how many mutants each operator produced and how many the rules caught, the surviving
+/// mutants, and the rule IDs no mutant exercised. Fails when a mutant survives.
+pub fn mutants(args: &Args, paths: &[PathBuf]) -> ExitCode {
+    let files = match expand_inputs(paths) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let operators: Vec<&Operator> = OPERATORS
+        .iter()
+        .filter(|op| rules::code_selected(op.code, &args.select, &args.ignore))
+        .filter(|op| rules::rule_for_code(op.code).is_some_and(rule_enabled))
+        .collect();
+
+    let mut specs = 0;
+    let mut tallies: BTreeMap<&str, OperatorTally> = BTreeMap::new();
+    let mut killers = BTreeSet::new();
+    let mut survivors = Vec::new();
+    for (file, doc) in workspace_documents(args, &files) {
+        if !is_program_spec(&doc) {
+            continue;
+        }
+        specs += 1;
+        let label = display_input(&file);
+        let (instance, _, _) = prepare_document(args, &file, &doc);
+        let original = findings(&instance);
+        for op in &operators {
+            for mutant in (op.mutate)(&instance) {
+                let tally = tallies.entry(op.name).or_default();
+                tally.mutants += 1;
+                if killed(&original, &findings(&mutant.doc), op.code) {
+                    tally.killed += 1;
+                    killers.insert(op.code);
+                } else {
+                    survivors.push((label.clone(), op, mutant.site));
+                }
+            }
+        }
+    }
+    if specs == 0 {
+        failln!("Error: no program specs found in the given paths");
+        return ExitCode::from(1);
+    }
+
+    let total: usize = tallies.values().map(|t| t.mutants).sum();
+    let killed: usize = tallies.values().map(|t| t.killed).sum();
+    outln!(
+        "── {specs} spec(s), {total} mutant(s): {killed} killed, {} survived ──",
+        total - killed
+    );
+    if !tallies.is_empty() {
+        outln!(
+            "  {:>6}  {:>7}  {:<24} rule",
+            "killed",
+            "mutants",
+            "operator"
+        );
+    }
+    for op in &operators {
+        if let Some(tally) = tallies.get(op.name) {
+            outln!(
+                "  {:>6}  {:>7}  {:<24} {} {}",
+                tally.killed,
+                tally.mutants,
+                op.name,
+                op.code,
+                summary(op.code)
+            );
+        }
+    }
+    if !survivors.is_empty() {
+        outln!("  Survived:");
+        for (file, op, site) in &survivors {
+            outln!("    {file}: {} at {site} (expected {})", op.name, op.code);
+        }
+    }
+
+    // Per-document rule IDs that could have fired in this run but that no mutant made fire.
+    let registry = rules::registered();
+    let unkilled: Vec<&str> = registry
+        .rules()
+        .iter()
+        .filter(|rule| rule_enabled(rule.name()))
+        .flat_map(|rule| rule.codes())
+        .map(|(code, _)| code)
+        .filter(|code| rules::code_selected(code, &args.select, &args.ignore))
+        .filter(|code| !killers.contains(code))
+        .collect();
+    if !unkilled.is_empty() {
+        outln!("  No killing mutant: {}", unkilled.join(", "));
+    }
+
+    if survivors.is_empty() {
+        ExitCode::from(0)
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+/// Findings of every per-document rule, regardless of configuration.
+fn findings(doc: &JsonValue) -> Vec<Diagnostic> {
+    let spec = SpecModel::new(doc);
+    rules::registered()
+        .rules()
+        .iter()
+        .flat_map(|rule| rules::check(rule.as_ref(), &spec))
+        .collect()
+}
+
+/// Whether the mutant reports a `code` finding that the original spec does not.
+fn killed(original: &[Diagnostic], mutant: &[Diagnostic], code: &str) -> bool {
+    let known: BTreeSet<&str> = original
+        .iter()
+        .filter(|d| d.code == code)
+        .map(|d| d.message.as_str())
+        .collect();
+    mutant
+        .iter()
+        .any(|d| d.code == code && !known.contains(d.message.as_str()))
+}
+
+fn summary(code: &str) -> &'static str {
+    rules::rule_codes()
+        .into_iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, summary)| summary)
+        .unwrap_or_default()
+}
+
+/// A copy of `doc` with `edit` applied to the value at `at`, or `None` if there is no such value or
+/// `edit` declines it.
+fn edited(
+    doc: &JsonValue,
+    at: &str,
+    site: String,
+    edit: impl FnOnce(&mut JsonValue) -> bool,
+) -> Option<Mutant> {
+    let mut mutant = doc.clone();
+    edit(mutant.pointer_mut(at)?).then_some(Mutant { site, doc: mutant })
+}
+
+/// Names of the phase contracts of `doc`.
+fn contract_names(doc: &JsonValue) -> Vec<String> {
+    doc.pointer("/implementation/phase_contracts")
+        .and_then(|v| v.as_object())
+        .map(|contracts| contracts.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// One mutant per phase contract that `edit` applies to.
+fn per_contract(
+    doc: &JsonValue,
+    edit: impl Fn(&mut Map<String, JsonValue>) -> bool,
+) -> Vec<Mutant> {
+    contract_names(doc)
+        .into_iter()
+        .filter_map(|phase| {
+            let at = pointer(&["implementation", "phase_contracts", &phase]);
+            edited(doc, &at, format!("phase '{phase}'"), |contract| {
+                contract.as_object_mut().is_some_and(&edit)
+            })
+        })
+        .collect()
+}
+
+/// One mutant per item of the list `field` of each phase contract that `edit` applies to.
+fn per_contract_item(
+    doc: &JsonValue,
+    field: &[&str],
+    noun: &str,
+    edit: impl Fn(&mut JsonValue) -> bool,
+) -> Vec<Mutant> {
+    let mut mutants = Vec::new();
+    for phase in contract_names(doc) {
+        let list = pointer(&[&["implementation", "phase_contracts", &phase], field].concat());
+        let count = doc
+            .pointer(&list)
+            .and_then(|v| v.as_array())
+            .map_or(0, Vec::len);
+        for index in 0..count {
+            let at = format!("{list}/{index}");
+            let name = doc
+                .pointer(&format!("{at}/name"))
+                .and_then(|n| n.as_str())
+                .map_or(index.to_string(), |name| format!("'{name}'"));
+            let site = format!("phase '{phase}' {noun} {name}");
+            mutants.extend(edited(doc, &at, site, &edit));
+        }
+    }
+    mutants
+}
+
+/// Whether phase contracts are mandatory for `doc` (spec_version v3 or later).
+fn needs_contracts(doc: &JsonValue) -> bool {
+    doc.get("spec_version")
+        .and_then(|v| v.as_str())
+        .and_then(rules::parse_semver_major)
+        .is_some_and(|major| major >= 3)
+}
+
+/// Appends a copy of the first item of the list `field`, if it has one.
+fn duplicate_first(contract: &mut Map<String, JsonValue>, field: &str) -> bool {
+    match contract.get_mut(field).and_then(|v| v.as_array_mut()) {
+        Some(items) if !items.is_empty() => {
+            items.push(items[0].clone());
+            true
+        }
+        _ => false,
+    }
+}
+
+fn rename_algorithm(doc: &JsonValue) -> Vec<Mutant> {
+    if doc.pointer("/meta/title").is_none() {
+        return Vec::new();
+    }
+    edited(doc, "/algorithm/name", "algorithm.name".into(), |name| {
+        let renamed = format!("{}Mutant", name.as_str().unwrap_or_default());
+        *name = renamed.into();
+        true
+    })
+    .into_iter()
+    .collect()
+}
+
+fn drop_title(doc: &JsonValue) -> Vec<Mutant> {
+    edited(doc, "/meta", "meta.title".into(), |meta| {
+        meta.as_object_mut()
+            .is_some_and(|m| m.remove("title").is_some())
+    })
+    .into_iter()
+    .collect()
+}
+
+fn drop_algorithm_name(doc: &JsonValue) -> Vec<Mutant> {
+    // Without a title the rule stops at PV002.
+    if doc.pointer("/meta/title").is_none() {
+        return Vec::new();
+    }
+    edited(doc, "/algorithm", "algorithm.name".into(), |algorithm| {
+        algorithm
+            .as_object_mut()
+            .is_some_and(|a| a.remove("name").is_some())
+    })
+    .into_iter()
+    .collect()
+}
+
+fn rename_contract(doc: &JsonValue) -> Vec<Mutant> {
+    contract_names(doc)
+        .into_iter()
+        .filter_map(|phase| {
+            let site = format!("phase '{phase}'");
+            edited(doc, "/implementation/phase_contracts", site, |contracts| {
+                let contracts = contracts.as_object_mut().unwrap();
+                let contract = contracts.remove(&phase).unwrap();
+                contracts.insert(format!("{phase}_mutant"), contract);
+                true
+            })
+        })
+        .collect()
+}
+
+fn drop_contracts(doc: &JsonValue) -> Vec<Mutant> {
+    if !needs_contracts(doc) {
+        return Vec::new();
+    }
+    let site = "implementation.phase_contracts".to_string();
+    edited(doc, "/implementation", site, |implementation| {
+        implementation
+            .as_object_mut()
+            .is_some_and(|i| i.remove("phase_contracts").is_some())
+    })
+    .into_iter()
+    .collect()
+}
+
+fn rename_phase(doc: &JsonValue) -> Vec<Mutant> {
+    if !needs_contracts(doc) {
+        return Vec::new();
+    }
+    let count = doc
+        .pointer("/algorithm/phases")
+        .and_then(|v| v.as_array())
+        .map_or(0, Vec::len);
+    (0..count)
+        .filter_map(|index| {
+            let at = format!("/algorithm/phases/{index}");
+            let phase = doc.pointer(&at)?.as_str()?;
+            edited(doc, &at, format!("phase '{phase}'"), |name| {
+                *name = format!("{phase}_mutant").into();
+                true
+            })
+        })
+        .collect()
+}
+
+fn duplicate_error_code(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| duplicate_first(contract, "errors"))
+}
+
+fn duplicate_output(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| duplicate_first(contract, "outputs"))
+}
+
+/// Whether `input` reads from another phase's output.
+fn is_phase_output(input: &JsonValue) -> bool {
+    input.pointer("/source/kind").and_then(|k| k.as_str()) == Some("phase_output")
+}
+
+fn unknown_source_phase(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract_item(doc, &["inputs"], "input", |input| {
+        if !is_phase_output(input) {
+            return false;
+        }
+        input["source"]["phase"] = "unknown_phase_mutant".into();
+        true
+    })
+}
+
+/// Every `phase_output` read of `doc` as `(consumer, producer, port, required)`.
+fn phase_output_reads(doc: &JsonValue) -> Vec<(String, String, String, bool)> {
+    let mut reads = Vec::new();
+    for phase in contract_names(doc) {
+        let inputs = pointer(&["implementation", "phase_contracts", &phase, "inputs"]);
+        for input in doc
+            .pointer(&inputs)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|input| is_phase_output(input))
+        {
+            let source = &input["source"];
+            if let (Some(from), Some(port)) = (source["phase"].as_str(), source["port"].as_str()) {
+                let required = input["optional"] != JsonValue::Bool(true);
+                reads.push((phase.clone(), from.to_string(), port.to_string(), required));
+            }
+        }
+    }
+    reads
+}
+
+/// `doc` without output `port` of `phase`.
+fn without_output(doc: &JsonValue, phase: &str, port: &str) -> Option<Mutant> {
+    let at = pointer(&["implementation", "phase_contracts", phase, "outputs"]);
+    let site = format!("phase '{phase}' output '{port}'");
+    edited(doc, &at, site, |outputs| {
+        let Some(outputs) = outputs.as_array_mut() else {
+            return false;
+        };
+        let before = outputs.len();
+        outputs.retain(|output| output["name"].as_str() != Some(port));
+        outputs.len() < before
+    })
+}
+
+fn drop_output(doc: &JsonValue) -> Vec<Mutant> {
+    // Each output read by some input, once.
+    let read: BTreeSet<_> = phase_output_reads(doc)
+        .into_iter()
+        .map(|(_, phase, port, _)| (phase, port))
+        .collect();
+    read.into_iter()
+        .filter_map(|(phase, port)| without_output(doc, &phase, &port))
+        .collect()
+}
+
+/// Drops an output required by a phase whose own outputs are required in turn, which breaks the
+/// chain of producers of the phases further down.
+fn drop_upstream_output(doc: &JsonValue) -> Vec<Mutant> {
+    let reads: Vec<_> = phase_output_reads(doc)
+        .into_iter()
+        .filter(|(.., required)| *required)
+        .collect();
+    let producers: BTreeSet<&str> = reads.iter().map(|(_, phase, ..)| phase.as_str()).collect();
+    let upstream: BTreeSet<_> = reads
+        .iter()
+        .filter(|(consumer, ..)| producers.contains(consumer.as_str()))
+        .map(|(_, phase, port, _)| (phase.as_str(), port.as_str()))
+        .collect();
+    upstream
+        .into_iter()
+        .filter_map(|(phase, port)| without_output(doc, phase, port))
+        .collect()
+}
+
+fn drop_source_path(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract_item(doc, &["inputs"], "input", |input| {
+        let kind = input.pointer("/source/kind").and_then(|k| k.as_str());
+        matches!(kind, Some("instance" | "global"))
+            && input["source"]
+                .as_object_mut()
+                .is_some_and(|s| s.remove("path").is_some())
+    })
+}
+
+fn drop_source_port(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract_item(doc, &["inputs"], "input", |input| {
+        input.pointer("/source/kind").and_then(|k| k.as_str()) == Some("phase_output")
+            && input["source"]
+                .as_object_mut()
+                .is_some_and(|s| s.remove("port").is_some())
+    })
+}
+
+fn unknown_retryable_error(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        match contract
+            .get_mut("retry_policy")
+            .and_then(|p| p.get_mut("retryable_errors"))
+            .and_then(|v| v.as_array_mut())
+        {
+            Some(codes) => {
+                codes.push("MUTANT_ERROR".into());
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+fn unknown_classification(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        contract.insert("data_classification".into(), "mutant".into());
+        true
+    })
+}
+
+fn non_boolean_flag(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        contract.insert("deterministic".into(), "mutant".into());
+        true
+    })
+}
+
+fn duplicate_side_effect(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| duplicate_first(contract, "side_effects"))
+}
+
+fn mark_non_idempotent(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        let has_effects = contract
+            .get("side_effects")
+            .and_then(|v| v.as_array())
+            .is_some_and(|effects| !effects.is_empty());
+        if has_effects || contract.get("idempotent") == Some(&false.into()) {
+            return false;
+        }
+        contract.insert("idempotent".into(), false.into());
+        true
+    })
+}
+
+fn retry_non_idempotent(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        if !contract.contains_key("retry_policy")
+            || contract.get("idempotent") == Some(&false.into())
+        {
+            return false;
+        }
+        contract.insert("idempotent".into(), false.into());
+        true
+    })
+}
+
+fn drop_idempotency_key(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        let has_effects = contract
+            .get("side_effects")
+            .and_then(|v| v.as_array())
+            .is_some_and(|effects| effects.iter().any(JsonValue::is_string));
+        has_effects
+            && contract.contains_key("retry_policy")
+            && contract.remove("idempotency_key").is_some()
+    })
+}
+
+fn unknown_idempotency_key(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        contract.insert("idempotency_key".into(), "unknown_input_mutant".into());
+        true
+    })
+}
+
+fn rename_metric(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract_item(
+        doc,
+        &["observability", "metrics"],
+        "metric",
+        |metric| match metric.get_mut("name") {
+            Some(name) => {
+                *name = "Mutant Metric".into();
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+fn unknown_metric_type(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract_item(
+        doc,
+        &["observability", "metrics"],
+        "metric",
+        |metric| match metric.get_mut("type") {
+            Some(kind) => {
+                *kind = "mutant".into();
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+fn duplicate_metric(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        contract
+            .get_mut("observability")
+            .and_then(|o| o.as_object_mut())
+            .is_some_and(|o| duplicate_first(o, "metrics"))
+    })
+}
+
+fn unknown_alert_metric(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract_item(
+        doc,
+        &["observability", "alerts"],
+        "alert",
+        |alert| match alert.get_mut("metric") {
+            Some(metric) => {
+                *metric = "mutant.metric".into();
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+fn deprecate_phase(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        if contract.contains_key("deprecation_plan") {
+            return false;
+        }
+        contract.insert(
+            "deprecation_plan".into(),
+            serde_json::json!({ "status": "deprecated" }),
+        );
+        true
+    })
+}
+
+/// Writes `phase_contracts` as a list of its entries, a common slip when converting specs.
+fn list_contracts(doc: &JsonValue) -> Vec<Mutant> {
+    let site = "implementation.phase_contracts".to_string();
+    edited(doc, "/implementation/phase_contracts", site, |contracts| {
+        let Some(entries) = contracts.as_object() else {
+            return false;
+        };
+        *contracts = entries.values().cloned().collect();
+        true
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Number of `algorithm.graph` edges of `doc`.
+fn graph_edge_count(doc: &JsonValue) -> usize {
+    doc.pointer("/algorithm/graph/edges")
+        .and_then(|v| v.as_array())
+        .map_or(0, Vec::len)
+}
+
+/// Adds the reverse of a graph edge, so that its two ends depend on each other.
+fn reverse_graph_edge(doc: &JsonValue) -> Vec<Mutant> {
+    (0..graph_edge_count(doc))
+        .filter_map(|index| {
+            let site = format!("algorithm.graph edge #{index}");
+            edited(doc, "/algorithm/graph/edges", site, |edges| {
+                let Some(edges) = edges.as_array_mut() else {
+                    return false;
+                };
+                let edge = &edges[index];
+                let (Some(from), Some(to)) = (edge["from"].as_str(), edge["to"].as_str()) else {
+                    return false;
+                };
+                if edge["kind"].as_str() == Some("loop") {
+                    return false;
+                }
+                let reversed = serde_json::json!({ "from": to, "to": from });
+                edges.push(reversed);
+                true
+            })
+        })
+        .collect()
+}
+
+fn add_unread_output(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        let Some(outputs) = contract.get_mut("outputs").and_then(|o| o.as_array_mut()) else {
+            return false;
+        };
+        outputs.push(
+            serde_json::json!({ "name": "unread_output_mutant", "schema": { "type": "object" } }),
+        );
+        true
+    })
+}
+
+/// Gives an output read by a typed input a schema type the input does not accept.
+fn retype_output(doc: &JsonValue) -> Vec<Mutant> {
+    let mut mutants = Vec::new();
+    for phase in contract_names(doc) {
+        let inputs = pointer(&["implementation", "phase_contracts", &phase, "inputs"]);
+        for input in doc
+            .pointer(&inputs)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let source = &input["source"];
+            let (Some(from), Some(port)) = (source["phase"].as_str(), source["port"].as_str())
+            else {
+                continue;
+            };
+            let expected = PortType::of_port(input);
+            let Some(schema) = ["boolean", "string"]
+                .into_iter()
+                .map(|name| serde_json::json!({ "type": name }))
+                .find(|schema| !expected.accepts(&PortType::from_schema(schema)))
+            else {
+                continue;
+            };
+            let at = pointer(&["implementation", "phase_contracts", from, "outputs"]);
+            let site = format!("phase '{from}' output '{port}' read by phase '{phase}'");
+            mutants.extend(edited(doc, &at, site, |outputs| {
+                let Some(output) = outputs
+                    .as_array_mut()
+                    .into_iter()
+                    .flatten()
+                    .find(|output| output["name"].as_str() == Some(port))
+                    .and_then(|output| output.as_object_mut())
+                else {
+                    return false;
+                };
+                output.remove("type");
+                output.insert("schema".to_string(), schema);
+                true
+            }));
+        }
+    }
+    mutants
+}
+
+/// Replaces the `example` of a port with a value its schema rejects.
+fn break_example(doc: &JsonValue) -> Vec<Mutant> {
+    let mut mutants = Vec::new();
+    for phase in contract_names(doc) {
+        for section in ["inputs", "outputs"] {
+            let at = pointer(&["implementation", "phase_contracts", &phase, section]);
+            let ports = doc.pointer(&at).and_then(|v| v.as_array());
+            for (index, port) in ports.into_iter().flatten().enumerate() {
+                let (Some(schema), Some(_)) = (port.get("schema"), port.get("example")) else {
+                    continue;
+                };
+                if !schema.is_object() || rules::leaves_fragment(schema) {
+                    continue;
+                }
+                let Ok(compiled) = JSONSchema::compile(schema) else {
+                    continue;
+                };
+                let candidates = [
+                    JsonValue::Null,
+                    JsonValue::Bool(true),
+                    JsonValue::from("example_mutant"),
+                    JsonValue::from(0),
+                ];
+                let Some(broken) = candidates.into_iter().find(|v| !compiled.is_valid(v)) else {
+                    continue;
+                };
+                let at = format!("{at}/{index}/example");
+                let site = format!("phase '{phase}' {section}[{index}] example");
+                mutants.extend(edited(doc, &at, site, |example| {
+                    *example = broken;
+                    true
+                }));
+            }
+        }
+    }
+    mutants
+}
+
+fn zero_max_attempts(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        let Some(policy) = contract
+            .get_mut("retry_policy")
+            .and_then(|p| p.as_object_mut())
+        else {
+            return false;
+        };
+        policy.insert("max_attempts".into(), 0.into());
+        true
+    })
+}
+
+/// The `retry_policy.backoff` object of a phase contract.
+fn backoff(contract: &mut Map<String, JsonValue>) -> Option<&mut Map<String, JsonValue>> {
+    contract
+        .get_mut("retry_policy")?
+        .get_mut("backoff")?
+        .as_object_mut()
+}
+
+fn unknown_backoff_strategy(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        backoff(contract).is_some_and(|backoff| {
+            backoff.insert("strategy".into(), "strategy_mutant".into());
+            true
+        })
+    })
+}
+
+fn invert_backoff_delays(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        let Some(backoff) = backoff(contract) else {
+            return false;
+        };
+        let initial = backoff
+            .get("initial_delay_ms")
+            .and_then(|d| d.as_u64())
+            .unwrap_or(1);
+        backoff.insert("max_delay_ms".into(), initial.saturating_sub(1).into());
+        backoff.insert("initial_delay_ms".into(), initial.max(1).into());
+        true
+    })
+}
+
+fn drop_initial_delay(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        backoff(contract).is_some_and(|backoff| {
+            let strategy = backoff.get("strategy").and_then(|s| s.as_str());
+            let known = rules::BACKOFF_STRATEGIES
+                .iter()
+                .any(|(name, _)| Some(*name) == strategy);
+            known && backoff.remove("initial_delay_ms").is_some()
+        })
+    })
+}
+
+/// Gives a phase a timeout longer than a total budget that is set for the occasion.
+fn exceed_time_budget(doc: &JsonValue) -> Vec<Mutant> {
+    contract_names(doc)
+        .into_iter()
+        .filter_map(|phase| {
+            edited(
+                doc,
+                "/implementation",
+                format!("phase '{phase}'"),
+                |implementation| {
+                    let Some(implementation) = implementation.as_object_mut() else {
+                        return false;
+                    };
+                    implementation.insert("total_timeout".into(), "1s".into());
+                    implementation
+                        .get_mut("phase_contracts")
+                        .and_then(|contracts| contracts.get_mut(&phase))
+                        .and_then(|contract| contract.as_object_mut())
+                        .is_some_and(|contract| {
+                            contract.insert("timeout".into(), "2s".into()).is_none()
+                        })
+                },
+            )
+        })
+        .collect()
+}
+
+fn malform_timeout(doc: &JsonValue) -> Vec<Mutant> {
+    per_contract(doc, |contract| {
+        contract.insert("timeout".into(), "soon".into());
+        true
+    })
+}
+
+fn self_fallback(doc: &JsonValue) -> Vec<Mutant> {
+    contract_names(doc)
+        .into_iter()
+        .filter_map(|phase| {
+            let at = pointer(&["implementation", "phase_contracts", &phase]);
+            edited(doc, &at, format!("phase '{phase}'"), |contract| {
+                set_fallback(contract, &phase)
+            })
+        })
+        .collect()
+}
+
+/// Makes each pair of neighbouring phase contracts fall back to each other.
+fn fallback_cycle(doc: &JsonValue) -> Vec<Mutant> {
+    let phases = contract_names(doc);
+    phases
+        .windows(2)
+        .filter_map(|pair| {
+            let site = format!("phases '{}' and '{}'", pair[0], pair[1]);
+            edited(doc, "/implementation/phase_contracts", site, |contracts| {
+                let mut set = |phase: &str, target: &str| {
+                    contracts
+                        .get_mut(phase)
+                        .is_some_and(|contract| set_fallback(contract, target))
+                };
+                set(&pair[0], &pair[1]) && set(&pair[1], &pair[0])
+            })
+        })
+        .collect()
+}
+
+/// Points the `fallback` of `contract` at `phase`, keeping the rest of an existing fallback.
+fn set_fallback(contract: &mut JsonValue, phase: &str) -> bool {
+    let Some(contract) = contract.as_object_mut() else {
+        return false;
+    };
+    let fallback = contract
+        .entry("fallback")
+        .or_insert_with(|| JsonValue::Object(Map::new()));
+    match fallback.as_object_mut() {
+        Some(fallback) => {
+            fallback.insert("phase".into(), phase.into());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Replaces each phase contract with a copy of the one before it that renames its first output,
+/// the way a copied contract is edited incompletely.
+fn near_copy_contract(doc: &JsonValue) -> Vec<Mutant> {
+    let phases = contract_names(doc);
+    phases
+        .windows(2)
+        .filter_map(|pair| {
+            let site = format!("phase '{}' copied from '{}'", pair[1], pair[0]);
+            edited(doc, "/implementation/phase_contracts", site, |contracts| {
+                let Some(mut copy) = contracts.get(&pair[0]).cloned() else {
+                    return false;
+                };
+                let Some(JsonValue::String(name)) = copy.pointer_mut("/outputs/0/name") else {
+                    return false;
+                };
+                name.push_str("_copy");
+                contracts[&pair[1]] = copy;
+                true
+            })
+        })
+        .collect()
+}
+
+/// Names of the `algorithm.outputs` of `doc` by position, when it has a return_contract too.
+fn algorithm_outputs(doc: &JsonValue) -> Vec<(usize, String)> {
+    if doc.pointer("/implementation/return_contract").is_none() {
+        return Vec::new();
+    }
+    let outputs = doc.pointer("/algorithm/outputs").and_then(|v| v.as_array());
+    outputs
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, output)| Some((index, output.get("name")?.as_str()?.to_string())))
+        .collect()
+}
+
+/// Renames each algorithm output and drops its `build`, leaving nothing that produces it.
+fn unbuilt_output(doc: &JsonValue) -> Vec<Mutant> {
+    algorithm_outputs(doc)
+        .into_iter()
+        .filter_map(|(index, name)| {
+            let at = format!("/algorithm/outputs/{index}");
+            edited(doc, &at, format!("algorithm output '{name}'"), |output| {
+                let Some(output) = output.as_object_mut() else {
+                    return false;
+                };
+                output.insert("name".into(), format!("{name}_unbuilt").into());
+                output.remove("build").is_some()
+            })
+        })
+        .collect()
+}
+
+fn repeat_algorithm_output(doc: &JsonValue) -> Vec<Mutant> {
+    algorithm_outputs(doc)
+        .into_iter()
+        .filter_map(|(index, name)| {
+            edited(
+                doc,
+                "/algorithm/outputs",
+                format!("algorithm output '{name}'"),
+                |outputs| {
+                    let Some(outputs) = outputs.as_array_mut() else {
+                        return false;
+                    };
+                    let mut copy = outputs[index].clone();
+                    copy["description"] = "A second declaration.".into();
+                    outputs.push(copy);
+                    true
+                },
+            )
+        })
+        .collect()
+}
+
+/// Adds a property named after each built algorithm output to the return_contract's schema.
+fn return_built_output(doc: &JsonValue) -> Vec<Mutant> {
+    algorithm_outputs(doc)
+        .into_iter()
+        .filter(|(index, _)| {
+            doc.pointer(&format!("/algorithm/outputs/{index}/build"))
+                .is_some()
+        })
+        .filter_map(|(_, name)| {
+            edited(
+                doc,
+                "/implementation/return_contract/schema",
+                format!("algorithm output '{name}'"),
+                |schema| {
+                    let Some(schema) = schema.as_object_mut() else {
+                        return false;
+                    };
+                    let properties = schema
+                        .entry("properties")
+                        .or_insert_with(|| JsonValue::Object(Map::new()));
+                    match properties.as_object_mut() {
+                        Some(properties) => properties
+                            .insert(name.clone(), json!({ "type": "object" }))
+                            .is_none(),
+                        None => false,
+                    }
+                },
+            )
+        })
+        .collect()
+}
+
+/// Points each graph edge at a node that does not exist.
+fn unknown_edge_node(doc: &JsonValue) -> Vec<Mutant> {
+    (0..graph_edge_count(doc))
+        .filter_map(|index| {
+            let at = format!("/algorithm/graph/edges/{index}/to");
+            edited(doc, &at, format!("algorithm.graph edge #{index}"), |to| {
+                let Some(id) = to.as_str() else {
+                    return false;
+                };
+                *to = format!("{id}_missing").into();
+                true
+            })
+        })
+        .collect()
+}
+
+fn repeat_graph_edge(doc: &JsonValue) -> Vec<Mutant> {
+    (0..graph_edge_count(doc))
+        .filter_map(|index| {
+            let site = format!("algorithm.graph edge #{index}");
+            edited(doc, "/algorithm/graph/edges", site, |edges| {
+                let Some(edges) = edges.as_array_mut() else {
+                    return false;
+                };
+                edges.push(edges[index].clone());
+                true
+            })
+        })
+        .collect()
+}
+
+/// Ids of the `if` nodes of `algorithm.graph` that declare branches.
+fn decision_nodes(doc: &JsonValue) -> Vec<String> {
+    let nodes = doc
+        .pointer("/algorithm/graph/nodes")
+        .and_then(|v| v.as_object());
+    nodes
+        .into_iter()
+        .flatten()
+        .filter(|(_, node)| node["type"] == "if" && node["branches"].is_array())
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Declares one more branch on each decision node, which no edge leaves by.
+fn unhandled_branch(doc: &JsonValue) -> Vec<Mutant> {
+    decision_nodes(doc)
+        .into_iter()
+        .filter_map(|id| {
+            let at = pointer(&["algorithm", "graph", "nodes", &id, "branches"]);
+            edited(doc, &at, format!("decision node '{id}'"), |branches| {
+                let Some(branches) = branches.as_array_mut() else {
+                    return false;
+                };
+                branches.push(json!({ "name": "unhandled" }));
+                true
+            })
+        })
+        .collect()
+}
+
+/// Renames the condition of each edge leaving a decision node, as if its branch were misspelled.
+fn unlisted_branch_edge(doc: &JsonValue) -> Vec<Mutant> {
+    let decisions = decision_nodes(doc);
+    (0..graph_edge_count(doc))
+        .filter(|index| {
+            let from = doc.pointer(&format!("/algorithm/graph/edges/{index}/from"));
+            from.and_then(|f| f.as_str())
+                .is_some_and(|from| decisions.iter().any(|id| id == from))
+        })
+        .filter_map(|index| {
+            let at = format!("/algorithm/graph/edges/{index}/condition");
+            edited(
+                doc,
+                &at,
+                format!("algorithm.graph edge #{index}"),
+                |condition| {
+                    let Some(name) = condition.as_str() else {
+                        return false;
+                    };
+                    *condition = format!("{name}_unlisted").into();
+                    true
+                },
+            )
+        })
+        .collect()
+}
+
+fn unknown_entry(doc: &JsonValue) -> Vec<Mutant> {
+    edited(
+        doc,
+        "/algorithm/graph/entry",
+        "algorithm.graph.entry".into(),
+        |entry| {
+            let Some(id) = entry.as_str() else {
+                return false;
+            };
+            *entry = format!("{id}_missing").into();
+            true
+        },
+    )
+    .into_iter()
+    .collect()
+}
+
+/// Drops the graph's `entry` and adds two nodes no edge leads to, either of which could start it.
+fn second_entry(doc: &JsonValue) -> Vec<Mutant> {
+    edited(doc, "/algorithm/graph", "algorithm.graph".into(), |graph| {
+        let Some(graph) = graph.as_object_mut() else {
+            return false;
+        };
+        graph.remove("entry");
+        let Some(nodes) = graph.get_mut("nodes").and_then(|n| n.as_object_mut()) else {
+            return false;
+        };
+        for id in ["orphan_start", "other_start"] {
+            nodes.insert(id.into(), json!({ "type": "end" }));
+        }
+        true
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Leads every terminal node of the graph back to its entry, turning `end` nodes into `parallel`
+/// ones, so that the graph never ends.
+fn loop_back_terminals(doc: &JsonValue) -> Vec<Mutant> {
+    let Some(entry) = doc
+        .pointer("/algorithm/graph/entry")
+        .and_then(|e| e.as_str())
+    else {
+        return Vec::new();
+    };
+    edited(doc, "/algorithm/graph", "algorithm.graph".into(), |graph| {
+        let Some(nodes) = graph.get_mut("nodes").and_then(|n| n.as_object_mut()) else {
+            return false;
+        };
+        let ids: Vec<String> = nodes.keys().cloned().collect();
+        for node in nodes.values_mut() {
+            if node["type"] == "end" {
+                node["type"] = "parallel".into();
+            }
+        }
+        let Some(edges) = graph.get_mut("edges").and_then(|e| e.as_array_mut()) else {
+            return false;
+        };
+        for id in ids {
+            if !edges.iter().any(|edge| edge["from"] == id.as_str()) {
+                edges.push(json!({ "from": id, "to": entry, "kind": "normal" }));
+            }
+        }
+        true
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Adds an edge from the graph's entry straight to a new `end` node, skipping the phase that
+/// produces the return_contract.
+fn early_exit(doc: &JsonValue) -> Vec<Mutant> {
+    let (Some(entry), Some(producer)) = (
+        doc.pointer("/algorithm/graph/entry")
+            .and_then(|e| e.as_str()),
+        doc.pointer("/implementation/return_contract/produced_by/phase")
+            .and_then(|p| p.as_str()),
+    ) else {
+        return Vec::new();
+    };
+    if entry == producer {
+        return Vec::new();
+    }
+    edited(doc, "/algorithm/graph", "algorithm.graph".into(), |graph| {
+        let Some(nodes) = graph.get_mut("nodes").and_then(|n| n.as_object_mut()) else {
+            return false;
+        };
+        nodes.insert("early_exit".into(), json!({ "type": "end" }));
+        let Some(edges) = graph.get_mut("edges").and_then(|e| e.as_array_mut()) else {
+            return false;
+        };
+        edges.push(json!({ "from": entry, "to": "early_exit", "kind": "failure" }));
+        true
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Pointers to the `condition` and `until` of the graph nodes, with their sites.
+fn node_conditions(doc: &JsonValue) -> Vec<(String, String)> {
+    let nodes = doc
+        .pointer("/algorithm/graph/nodes")
+        .and_then(|v| v.as_object());
+    nodes
+        .into_iter()
+        .flatten()
+        .flat_map(|(id, node)| {
+            ["condition", "until"]
+                .into_iter()
+                .filter(|field| node[field].is_string())
+                .map(move |field| {
+                    let at = pointer(&["algorithm", "graph", "nodes", id, field]);
+                    (at, format!("{field} of graph node '{id}'"))
+                })
+        })
+        .collect()
+}
+
+/// Ends each condition of a graph node with an operator missing its right-hand side.
+fn dangling_condition(doc: &JsonValue) -> Vec<Mutant> {
+    node_conditions(doc)
+        .into_iter()
+        .filter_map(|(at, site)| {
+            edited(doc, &at, site, |condition| {
+                let Some(text) = condition.as_str() else {
+                    return false;
+                };
+                *condition = format!("{text} &&").into();
+                true
+            })
+        })
+        .collect()
+}
+
+/// Makes each condition of a graph node also read a name the spec does not declare.
+fn unknown_condition_name(doc: &JsonValue) -> Vec<Mutant> {
+    node_conditions(doc)
+        .into_iter()
+        .filter_map(|(at, site)| {
+            edited(doc, &at, site, |condition| {
+                let Some(text) = condition.as_str() else {
+                    return false;
+                };
+                *condition = format!("({text}) || undeclared_signal").into();
+                true
+            })
+        })
+        .collect()
+}
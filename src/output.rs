@@ -0,0 +1,275 @@
+//! Terminal output: `--color`, `--quiet` and `--verbose`.
+//!
+//! Everything user-facing is printed through [`outln!`] (stdout), [`errln!`] (stderr) and
+//! [`failln!`] (errors on stderr), which drop the text in quiet mode and color each line by the
+//! severity icon it starts with. With a machine-readable `--format`, stdout carries only the
+//! [emitted](emit) report and everything else but errors is dropped. Output and findings of work
+//! running in parallel are [captured](capture) as written and replayed in a deterministic order.
+
+use crate::reporter::{Report, Reporter};
+use clap::ValueEnum;
+use serde_json::Value as JsonValue;
+use std::{
+    cell::RefCell,
+    env,
+    io::{self, IsTerminal, Write},
+    sync::OnceLock,
+};
+
+/// When to emit ANSI colors.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color output written to a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+struct Settings {
+    quiet: bool,
+    verbose: bool,
+    color_stdout: bool,
+    color_stderr: bool,
+    /// Findings are rendered for tools rather than people.
+    machine: bool,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+thread_local! {
+    /// Messages held back by [`capture`] on this thread.
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+/// What a message is, which decides where it goes and when it is dropped.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Progress and results on stdout.
+    Out,
+    /// Notes and warnings on stderr.
+    Err,
+    /// An error on stderr, printed even with a machine-readable `--format`.
+    Error,
+    /// A machine-readable report on stdout, printed as is.
+    Emitted,
+}
+
+/// Something held back by [`capture`].
+enum Held {
+    /// A message as it was written, before the output settings are applied.
+    Text(Kind, String),
+    Finding(Report),
+    /// A validated document and the input it came from.
+    Spec(String, JsonValue),
+}
+
+/// Messages and findings produced while [`capture`] was active.
+#[derive(Default)]
+pub struct Captured(Vec<Held>);
+
+impl Captured {
+    /// Prints the held-back messages and hands the findings to `reporter`, in their original order.
+    pub fn replay(self, reporter: &dyn Reporter) {
+        for held in self.0 {
+            match held {
+                Held::Text(kind, text) => show(kind, &text),
+                Held::Finding(report) => reporter.report(&report),
+                Held::Spec(file, doc) => reporter.spec(&file, &doc),
+            }
+        }
+    }
+
+    /// The held-back errors, uncolored and whether or not the output settings would print them.
+    pub fn errors(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .filter_map(|held| match held {
+                Held::Text(Kind::Error, text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Hands the held-back documents and findings to `reporter`, in their original order, and
+    /// returns the findings; the messages are dropped.
+    pub fn deliver(self, reporter: &dyn Reporter) -> Vec<Report> {
+        let mut findings = Vec::new();
+        for held in self.0 {
+            match held {
+                Held::Finding(report) => {
+                    reporter.report(&report);
+                    findings.push(report);
+                }
+                Held::Spec(file, doc) => reporter.spec(&file, &doc),
+                Held::Text(..) => {}
+            }
+        }
+        findings
+    }
+
+    /// The held-back findings; the messages are dropped.
+    pub fn findings(self) -> Vec<Report> {
+        self.0
+            .into_iter()
+            .filter_map(|held| match held {
+                Held::Finding(report) => Some(report),
+                Held::Text(..) | Held::Spec(..) => None,
+            })
+            .collect()
+    }
+}
+
+/// Holds `held` back if [`capture`] is active on this thread; gives it back otherwise.
+fn hold(held: Held) -> Option<Held> {
+    CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(captured) => {
+            captured.0.push(held);
+            None
+        }
+        None => Some(held),
+    })
+}
+
+/// Hands a finding to `reporter`, or holds it back until the captured output is replayed.
+pub fn report(reporter: &dyn Reporter, report: Report) {
+    if let Some(Held::Finding(report)) = hold(Held::Finding(report)) {
+        reporter.report(&report);
+    }
+}
+
+/// Hands a validated document to `reporter`, or holds it back until the captured output is
+/// replayed.
+pub fn spec(reporter: &dyn Reporter, file: &str, doc: &JsonValue) {
+    if let Some(Held::Spec(file, doc)) = hold(Held::Spec(file.to_string(), doc.clone())) {
+        reporter.spec(&file, &doc);
+    }
+}
+
+/// Prints machine-readable output to stdout as is, whatever the output settings.
+pub fn emit(text: &str) {
+    print(Kind::Emitted, text);
+}
+
+/// Runs `work`, holding back everything it prints on the current thread.
+pub fn capture<T>(work: impl FnOnce() -> T) -> (T, Captured) {
+    let outer = CAPTURED.with(|c| c.replace(Some(Captured::default())));
+    let result = work();
+    let captured = CAPTURED.with(|c| c.replace(outer)).unwrap_or_default();
+    (result, captured)
+}
+
+/// Fixes the output settings for the rest of the process.
+pub fn init(color: ColorChoice, quiet: bool, verbose: bool, machine: bool) {
+    let enabled = |terminal: bool| match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+    };
+    let _ = SETTINGS.set(Settings {
+        quiet,
+        verbose,
+        color_stdout: enabled(io::stdout().is_terminal()),
+        color_stderr: enabled(io::stderr().is_terminal()),
+        machine,
+    });
+}
+
+/// Whether `--verbose` asked for additional context.
+pub fn verbose() -> bool {
+    SETTINGS.get().is_some_and(|s| s.verbose && !s.quiet)
+}
+
+/// Prints one message (possibly spanning several lines), or holds it back if [`capture`] is
+/// active.
+pub fn print(kind: Kind, text: &str) {
+    if let Some(Held::Text(kind, text)) = hold(Held::Text(kind, text.to_string())) {
+        show(kind, &text);
+    }
+}
+
+/// Writes a message as the output settings ask for it.
+fn show(kind: Kind, text: &str) {
+    let stderr = matches!(kind, Kind::Err | Kind::Error);
+    if kind == Kind::Emitted {
+        return write(false, text);
+    }
+    let settings = SETTINGS.get();
+    if settings.is_some_and(|s| s.quiet || (s.machine && kind != Kind::Error)) {
+        return;
+    }
+    let color = settings.is_some_and(|s| {
+        if stderr {
+            s.color_stderr
+        } else {
+            s.color_stdout
+        }
+    });
+    if color {
+        write(stderr, &paint(text));
+    } else {
+        write(stderr, text);
+    }
+}
+
+fn write(stderr: bool, text: &str) {
+    if stderr {
+        eprintln!("{text}");
+        return;
+    }
+    match writeln!(io::stdout(), "{text}") {
+        Ok(()) => {}
+        // The reader went away (`program-verify query … | head -1`): stop as SIGPIPE would.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => std::process::exit(141),
+        Err(e) => panic!("failed printing to stdout: {e}"),
+    }
+}
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const GREEN: &str = "\x1b[32m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Colors every line by its leading icon: errors red, warnings yellow, success green, notes dim.
+fn paint(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let color = if trimmed.starts_with('❌') || trimmed.starts_with("Error") {
+                RED
+            } else if trimmed.starts_with('⚠') {
+                YELLOW
+            } else if trimmed.starts_with('✅') {
+                GREEN
+            } else if trimmed.starts_with('ℹ') {
+                DIM
+            } else {
+                return line.to_string();
+            };
+            format!("{color}{line}{RESET}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `println!` that honors `--quiet` and `--color`.
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        $crate::output::print($crate::output::Kind::Out, &format!($($arg)*))
+    };
+}
+
+/// `eprintln!` that honors `--quiet` and `--color`.
+macro_rules! errln {
+    ($($arg:tt)*) => {
+        $crate::output::print($crate::output::Kind::Err, &format!($($arg)*))
+    };
+}
+
+/// [`errln!`] for errors, which are printed even with a machine-readable `--format`.
+macro_rules! failln {
+    ($($arg:tt)*) => {
+        $crate::output::print($crate::output::Kind::Error, &format!($($arg)*))
+    };
+}
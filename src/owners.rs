@@ -0,0 +1,265 @@
+//! `--split-report-by owner`: one report per owning team. Owners are assigned with a
+//! CODEOWNERS-like file, and once the run finishes every owner gets a Markdown and a JSON report
+//! covering its spec files and their findings.
+
+use crate::{
+    config::glob_match,
+    diagnostics::Severity,
+    reporter::{to_json, Report, Reporter},
+};
+use clap::ValueEnum;
+use serde_json::{json, Value as JsonValue};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Owner of the files no rule of the owners file matches, and of the cross-spec findings.
+pub const UNOWNED: &str = "unowned";
+
+/// How `--split-report-by` groups the findings.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitBy {
+    /// By the owners the owners file assigns to each spec file.
+    Owner,
+}
+
+/// The rules of an owners file: `PATTERN OWNER...` per line, `#` starting a comment. As in
+/// CODEOWNERS, the last matching rule decides. A pattern without a `/` matches a file name in any
+/// directory, one ending in `/` everything below a directory, and any other pattern a path
+/// relative to the owners file's directory (`*`, `**` and `?` as in `exclude`).
+#[derive(Debug, Default)]
+pub struct Owners {
+    base: PathBuf,
+    rules: Vec<(String, Vec<String>)>,
+}
+
+impl Owners {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Error: failed to read owners file {}: {e}", path.display()))?;
+        let mut rules = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners: Vec<String> = fields.map(str::to_string).collect();
+            if owners.is_empty() {
+                return Err(format!(
+                    "Error: {}:{}: pattern '{pattern}' names no owner",
+                    path.display(),
+                    number + 1
+                ));
+            }
+            rules.push((glob(pattern), owners));
+        }
+        let base = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Ok(Self { base, rules })
+    }
+
+    /// The owners of `file`, or [`UNOWNED`] when no rule matches it.
+    pub fn of(&self, file: &Path) -> Vec<String> {
+        let absolute = |p: &Path| {
+            if p.is_absolute() {
+                p.to_path_buf()
+            } else {
+                env::current_dir().unwrap_or_default().join(p)
+            }
+        };
+        let (file, base) = (absolute(file), absolute(&self.base));
+        let relative = file.strip_prefix(&base).unwrap_or(&file);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| glob_match(pattern, &relative))
+            .map_or_else(|| vec![UNOWNED.to_string()], |(_, owners)| owners.clone())
+    }
+}
+
+/// The `exclude`-style glob of a CODEOWNERS pattern.
+fn glob(pattern: &str) -> String {
+    let anchored = pattern.trim_start_matches('/');
+    let glob = if let Some(dir) = anchored.strip_suffix('/') {
+        format!("{dir}/**")
+    } else {
+        anchored.to_string()
+    };
+    if pattern.trim_end_matches('/').contains('/') {
+        glob
+    } else {
+        format!("**/{glob}")
+    }
+}
+
+/// What one owner's report covers.
+#[derive(Default)]
+struct Section {
+    files: Vec<String>,
+    findings: Vec<Report>,
+}
+
+/// Collects the files and findings of the run by owner and writes the reports once it finishes.
+pub struct OwnerReporter {
+    owners: Owners,
+    dir: PathBuf,
+    sections: Mutex<BTreeMap<String, Section>>,
+}
+
+impl OwnerReporter {
+    pub fn new(owners: Owners, dir: PathBuf) -> Self {
+        Self {
+            owners,
+            dir,
+            sections: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn owners_of(&self, file: Option<&str>) -> Vec<String> {
+        match file {
+            Some(file) if file != "<stdin>" => self.owners.of(Path::new(file)),
+            _ => vec![UNOWNED.to_string()],
+        }
+    }
+
+    fn write(&self, sections: &BTreeMap<String, Section>) -> Result<(), String> {
+        let failed = |path: &Path, e: std::io::Error| {
+            format!("Error: failed to write {}: {e}", path.display())
+        };
+        fs::create_dir_all(&self.dir).map_err(|e| failed(&self.dir, e))?;
+        for (owner, section) in sections {
+            let stem = file_stem(owner);
+            let path = self.dir.join(format!("{stem}.md"));
+            fs::write(&path, markdown(owner, section)).map_err(|e| failed(&path, e))?;
+            let path = self.dir.join(format!("{stem}.json"));
+            let text = serde_json::to_string_pretty(&to_summary(owner, section)).unwrap();
+            fs::write(&path, text + "\n").map_err(|e| failed(&path, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Reporter for OwnerReporter {
+    fn spec(&self, file: &str, _doc: &JsonValue) {
+        let mut sections = self.sections.lock().unwrap();
+        for owner in self.owners_of(Some(file)) {
+            let files = &mut sections.entry(owner).or_default().files;
+            if !files.iter().any(|known| known == file) {
+                files.push(file.to_string());
+            }
+        }
+    }
+
+    fn report(&self, report: &Report) {
+        let mut sections = self.sections.lock().unwrap();
+        for owner in self.owners_of(report.file.as_deref()) {
+            let section = sections.entry(owner).or_default();
+            if let Some(file) = &report.file {
+                if !section.files.contains(file) {
+                    section.files.push(file.clone());
+                }
+            }
+            section.findings.push(report.clone());
+        }
+    }
+
+    fn finish(&self) {
+        let sections = std::mem::take(&mut *self.sections.lock().unwrap());
+        match self.write(&sections) {
+            Ok(()) => outln!(
+                "📝 Wrote the reports of {} owner(s) to {}.",
+                sections.len(),
+                self.dir.display()
+            ),
+            Err(msg) => failln!("{msg}"),
+        }
+    }
+}
+
+/// A file name for `owner`: `@org/team` becomes `org-team`.
+fn file_stem(owner: &str) -> String {
+    let stem: String = owner
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    stem.trim_matches(|c| c == '-' || c == '.').to_string()
+}
+
+fn count(section: &Section, severity: Severity) -> usize {
+    section
+        .findings
+        .iter()
+        .filter(|report| report.severity == severity)
+        .count()
+}
+
+fn to_summary(owner: &str, section: &Section) -> JsonValue {
+    json!({
+        "owner": owner,
+        "files": section.files,
+        "summary": {
+            "error": count(section, Severity::Error),
+            "warning": count(section, Severity::Warning),
+            "info": count(section, Severity::Info),
+        },
+        "findings": section.findings.iter().map(to_json).collect::<Vec<_>>(),
+    })
+}
+
+fn markdown(owner: &str, section: &Section) -> String {
+    let mut text = format!("# Validation report: {owner}\n\n");
+    text.push_str(&format!(
+        "{} file(s), {} error(s), {} warning(s), {} info\n",
+        section.files.len(),
+        count(section, Severity::Error),
+        count(section, Severity::Warning),
+        count(section, Severity::Info)
+    ));
+    let mut by_file: BTreeMap<Option<&str>, Vec<&Report>> = BTreeMap::new();
+    for report in &section.findings {
+        by_file
+            .entry(report.file.as_deref())
+            .or_default()
+            .push(report);
+    }
+    for file in &section.files {
+        text.push_str(&format!("\n## {file}\n\n"));
+        match by_file.get(&Some(file.as_str())) {
+            Some(findings) => findings
+                .iter()
+                .for_each(|report| text.push_str(&markdown_item(report))),
+            None => text.push_str("✅ No findings.\n"),
+        }
+    }
+    if let Some(findings) = by_file.get(&None) {
+        text.push_str("\n## Across specs\n\n");
+        findings
+            .iter()
+            .for_each(|report| text.push_str(&markdown_item(report)));
+    }
+    text
+}
+
+fn markdown_item(report: &Report) -> String {
+    let at = match (report.location, &report.pointer) {
+        (Some(location), _) => format!(" (line {}, column {})", location.line, location.column),
+        (None, Some(pointer)) => format!(" (`{pointer}`)"),
+        (None, None) => String::new(),
+    };
+    format!(
+        "- {} `{}`{at}: {}\n",
+        report.severity.icon(),
+        report.code,
+        report.message.replace('\n', " ")
+    )
+}
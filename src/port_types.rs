@@ -0,0 +1,211 @@
+//! The data type a phase contract port carries, read from its `type` (a shorthand such as
+//! `array<int>`) or its `schema` (a JSON Schema), so that the `port-types` rule can check that an
+//! input accepts what the output it reads produces. Anything the declaration does not pin down is
+//! taken to accept and produce anything, so only definite mismatches are reported.
+
+use serde_json::Value as JsonValue;
+use std::fmt;
+
+/// What a port carries.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PortType {
+    /// Not declared, or declared in a way these checks cannot follow (`$ref`, unknown names).
+    Any,
+    /// One of these kinds.
+    OneOf(Vec<Kind>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Object,
+    /// An array with items of this type.
+    Array(PortType),
+}
+
+impl PortType {
+    /// The type declared by `port`: its `type` shorthand when it has one, else its `schema`.
+    pub fn of_port(port: &JsonValue) -> Self {
+        match (port.get("type"), port.get("schema")) {
+            (Some(JsonValue::String(text)), _) => Self::parse(text),
+            (_, Some(schema)) => Self::from_schema(schema),
+            _ => PortType::Any,
+        }
+    }
+
+    /// Reads a shorthand such as `string`, `int`, `array<number>`, `list<string>`, `string[]` or
+    /// `int | null`.
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        if text.contains('|') && !text.contains('<') {
+            return Self::union(text.split('|').map(Self::parse));
+        }
+        if let Some(item) = text.strip_suffix("[]") {
+            return PortType::OneOf(vec![Kind::Array(Self::parse(item))]);
+        }
+        if let Some((outer, rest)) = text.split_once('<') {
+            let Some(item) = rest.strip_suffix('>') else {
+                return PortType::Any;
+            };
+            return match outer.trim().to_ascii_lowercase().as_str() {
+                "array" | "list" | "vec" | "seq" => {
+                    PortType::OneOf(vec![Kind::Array(Self::parse(item))])
+                }
+                "map" | "dict" | "record" => PortType::OneOf(vec![Kind::Object]),
+                "option" | "optional" => Self::union([Self::parse(item), Self::kind(Kind::Null)]),
+                _ => PortType::Any,
+            };
+        }
+        let kind = match text.to_ascii_lowercase().as_str() {
+            "null" | "none" => Kind::Null,
+            "bool" | "boolean" => Kind::Boolean,
+            "int" | "integer" | "i32" | "i64" | "u32" | "u64" | "long" => Kind::Integer,
+            "number" | "float" | "double" | "decimal" | "f32" | "f64" => Kind::Number,
+            "string" | "str" | "text" => Kind::String,
+            "object" | "map" | "dict" | "record" => Kind::Object,
+            "array" | "list" => Kind::Array(PortType::Any),
+            _ => return PortType::Any,
+        };
+        Self::kind(kind)
+    }
+
+    /// Reads the `type` (with `items`), `const`, `enum`, `anyOf` and `oneOf` of a JSON Schema.
+    pub fn from_schema(schema: &JsonValue) -> Self {
+        let Some(schema) = schema.as_object() else {
+            return PortType::Any;
+        };
+        if schema.contains_key("$ref") {
+            return PortType::Any;
+        }
+        let items = || schema.get("items").map_or(PortType::Any, Self::from_schema);
+        let named = |name: &str| {
+            Some(match name {
+                "null" => Kind::Null,
+                "boolean" => Kind::Boolean,
+                "integer" => Kind::Integer,
+                "number" => Kind::Number,
+                "string" => Kind::String,
+                "object" => Kind::Object,
+                "array" => Kind::Array(items()),
+                _ => return None,
+            })
+        };
+        match schema.get("type") {
+            Some(JsonValue::String(name)) => return named(name).map_or(PortType::Any, Self::kind),
+            Some(JsonValue::Array(names)) => {
+                return names
+                    .iter()
+                    .map(|name| name.as_str().and_then(named))
+                    .collect::<Option<Vec<_>>>()
+                    .map_or(PortType::Any, PortType::OneOf)
+            }
+            _ => {}
+        }
+        if let Some(value) = schema.get("const") {
+            return Self::kind(Self::kind_of(value));
+        }
+        if let Some(JsonValue::Array(values)) = schema.get("enum") {
+            return Self::union(values.iter().map(|v| Self::kind(Self::kind_of(v))));
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(JsonValue::Array(branches)) = schema.get(key) {
+                return Self::union(branches.iter().map(Self::from_schema));
+            }
+        }
+        PortType::Any
+    }
+
+    /// Whether a port of this type accepts every value a port of type `produced` may carry.
+    /// Integers are numbers; arrays are compared by their items.
+    pub fn accepts(&self, produced: &PortType) -> bool {
+        match (self, produced) {
+            (PortType::Any, _) | (_, PortType::Any) => true,
+            (PortType::OneOf(accepted), PortType::OneOf(produced)) => produced
+                .iter()
+                .all(|kind| accepted.iter().any(|accepted| accepted.accepts(kind))),
+        }
+    }
+
+    fn kind(kind: Kind) -> Self {
+        PortType::OneOf(vec![kind])
+    }
+
+    /// The kinds of any of `types`, or `Any` when one of them is `Any`.
+    fn union(types: impl IntoIterator<Item = PortType>) -> Self {
+        let mut kinds = Vec::new();
+        for port_type in types {
+            match port_type {
+                PortType::Any => return PortType::Any,
+                PortType::OneOf(more) => {
+                    for kind in more {
+                        if !kinds.contains(&kind) {
+                            kinds.push(kind);
+                        }
+                    }
+                }
+            }
+        }
+        if kinds.is_empty() {
+            PortType::Any
+        } else {
+            PortType::OneOf(kinds)
+        }
+    }
+
+    fn kind_of(value: &JsonValue) -> Kind {
+        match value {
+            JsonValue::Null => Kind::Null,
+            JsonValue::Bool(_) => Kind::Boolean,
+            JsonValue::Number(n) if n.is_i64() || n.is_u64() => Kind::Integer,
+            JsonValue::Number(_) => Kind::Number,
+            JsonValue::String(_) => Kind::String,
+            JsonValue::Array(_) => Kind::Array(PortType::Any),
+            JsonValue::Object(_) => Kind::Object,
+        }
+    }
+}
+
+impl Kind {
+    fn accepts(&self, produced: &Kind) -> bool {
+        match (self, produced) {
+            (Kind::Number, Kind::Integer) => true,
+            (Kind::Array(accepted), Kind::Array(produced)) => accepted.accepts(produced),
+            _ => self == produced,
+        }
+    }
+}
+
+impl fmt::Display for PortType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PortType::Any => f.write_str("any"),
+            PortType::OneOf(kinds) => {
+                for (position, kind) in kinds.iter().enumerate() {
+                    if position > 0 {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{kind}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Null => f.write_str("null"),
+            Kind::Boolean => f.write_str("boolean"),
+            Kind::Integer => f.write_str("integer"),
+            Kind::Number => f.write_str("number"),
+            Kind::String => f.write_str("string"),
+            Kind::Object => f.write_str("object"),
+            Kind::Array(items) => write!(f, "array<{items}>"),
+        }
+    }
+}
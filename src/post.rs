@@ -0,0 +1,124 @@
+//! `--post-results URL`: once the run finishes, its summary (the files, the finding counts by
+//! severity and every finding as `--format json` prints it) is POSTed as JSON to a results
+//! endpoint, so that central dashboards collect the results of every repository without glue
+//! scripts. Connection failures, timeouts and server errors are retried with growing delays.
+
+use crate::{
+    diagnostics::Severity,
+    reporter::{to_json, Report, Reporter},
+};
+use reqwest::StatusCode;
+use serde_json::{json, Value as JsonValue};
+use std::{env, sync::Mutex, thread, time::Duration};
+
+/// Environment variable whose value is sent as a bearer token with the results.
+pub const TOKEN_VARIABLE: &str = "PROGRAM_VERIFY_RESULTS_TOKEN";
+
+/// How often the results are sent before giving up.
+const ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; doubled before every further one.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Collects the files and findings of the run and posts them once it finishes.
+pub struct ResultsPoster {
+    url: String,
+    /// Bound on every attempt (`--fetch-timeout`).
+    timeout: Duration,
+    files: Mutex<Vec<String>>,
+    findings: Mutex<Vec<Report>>,
+}
+
+impl ResultsPoster {
+    pub fn new(url: String, timeout: Duration) -> Self {
+        Self {
+            url,
+            timeout,
+            files: Mutex::new(Vec::new()),
+            findings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sends `body`, retrying what may succeed later: failures to connect, timeouts, `429` and
+    /// `5xx` responses.
+    fn post(&self, body: &JsonValue) -> Result<(), String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| format!("failed to prepare HTTP client: {e}"))?;
+        let body = body.to_string();
+        let mut delay = RETRY_DELAY;
+        for attempt in 1..=ATTEMPTS {
+            let mut request = client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            if let Ok(token) = env::var(TOKEN_VARIABLE) {
+                request = request.bearer_auth(token);
+            }
+            let reason = match request.send() {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => {
+                    let status = response.status();
+                    if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                        return Err(format!("the server answered {status}"));
+                    }
+                    format!("the server answered {status}")
+                }
+                Err(e) => e.to_string(),
+            };
+            if attempt == ATTEMPTS {
+                return Err(format!("{reason} (after {ATTEMPTS} attempts)"));
+            }
+            errln!(
+                "⚠️ Failed to post the results to {} ({reason}); retrying in {} s.",
+                self.url,
+                delay.as_secs()
+            );
+            thread::sleep(delay);
+            delay *= 2;
+        }
+        unreachable!("the last attempt returns")
+    }
+}
+
+impl Reporter for ResultsPoster {
+    fn spec(&self, file: &str, _doc: &JsonValue) {
+        let mut files = self.files.lock().unwrap();
+        if !files.iter().any(|known| known == file) {
+            files.push(file.to_string());
+        }
+    }
+
+    fn report(&self, report: &Report) {
+        self.findings.lock().unwrap().push(report.clone());
+    }
+
+    fn finish(&self) {
+        let files = std::mem::take(&mut *self.files.lock().unwrap());
+        let findings = std::mem::take(&mut *self.findings.lock().unwrap());
+        let count = |severity| findings.iter().filter(|r| r.severity == severity).count();
+        let body = json!({
+            "tool": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "files": files,
+            "summary": {
+                "error": count(Severity::Error),
+                "warning": count(Severity::Warning),
+                "info": count(Severity::Info),
+            },
+            "findings": findings.iter().map(to_json).collect::<Vec<_>>(),
+        });
+        match self.post(&body) {
+            Ok(()) => outln!(
+                "📤 Posted the results of {} file(s) to {}.",
+                files.len(),
+                self.url
+            ),
+            Err(reason) => failln!(
+                "Error: failed to post the results to {}: {reason}",
+                self.url
+            ),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Provenance of a validation run: the configuration file, schemas, contract libraries and rules
+//! that produced its findings, with content hashes, so that a machine-readable report can be
+//! audited and the run reproduced later. Inputs are recorded as the run uses them.
+
+use crate::{rules, schemas::ResolvedSchema};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, path::Path, sync::Mutex};
+
+struct Record {
+    /// `{path, sha256}` of the configuration file.
+    config: Option<JsonValue>,
+    /// Schemas by origin.
+    schemas: BTreeMap<String, JsonValue>,
+    /// Contract libraries by name and origin.
+    libraries: BTreeMap<(String, String), JsonValue>,
+}
+
+static RECORD: Mutex<Record> = Mutex::new(Record {
+    config: None,
+    schemas: BTreeMap::new(),
+    libraries: BTreeMap::new(),
+});
+
+fn sha256(bytes: impl AsRef<[u8]>) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Forgets the schemas and libraries of the previous run (`--watch` validates repeatedly).
+pub fn start_run() {
+    let mut record = RECORD.lock().unwrap();
+    record.schemas.clear();
+    record.libraries.clear();
+}
+
+/// Records the configuration file read from `path`.
+pub fn record_config(path: &Path, text: &str) {
+    RECORD.lock().unwrap().config = Some(json!({
+        "path": path.display().to_string(),
+        "sha256": sha256(text),
+    }));
+}
+
+/// Records a schema documents were validated against. The hash is taken over its canonical JSON
+/// serialization, so it does not depend on the formatting of the file it came from.
+pub fn record_schema(schema: &ResolvedSchema) {
+    let mut entry = json!({
+        "origin": schema.origin,
+        "sha256": sha256(schema.schema.to_string()),
+    });
+    if let Some(id) = schema.schema.get("$id").and_then(|id| id.as_str()) {
+        entry["id"] = id.into();
+    }
+    RECORD
+        .lock()
+        .unwrap()
+        .schemas
+        .insert(schema.origin.clone(), entry);
+}
+
+/// Records the contract library bundle `name` loaded from `origin`.
+pub fn record_library(name: &str, origin: &str, bundle: &JsonValue) {
+    let mut entry = json!({
+        "library": name,
+        "origin": origin,
+        "sha256": sha256(bundle.to_string()),
+    });
+    if let Some(version) = bundle.get("version") {
+        entry["version"] = version.clone();
+    }
+    RECORD
+        .lock()
+        .unwrap()
+        .libraries
+        .insert((name.to_string(), origin.to_string()), entry);
+}
+
+/// The provenance of the run so far: tool, configuration, schemas, libraries and rules.
+pub fn to_json() -> JsonValue {
+    let registry = rules::registered();
+    let describe = |name: &str, id: &str, version: &str, builtin: bool| {
+        let settings = registry.settings(name);
+        let mut entry = json!({
+            "name": name,
+            "id": id,
+            "version": version,
+            "builtin": builtin,
+            "enabled": registry.enabled(name),
+        });
+        if let Some(severity) = settings.and_then(|s| s.severity) {
+            entry["severity"] = severity.name().into();
+        }
+        if let Some(escalation) = settings.and_then(|s| s.becomes_error.as_ref()) {
+            entry["severity"] = escalation.severity().name().into();
+            entry["becomes_error"] = escalation.cutoff().into();
+        }
+        entry
+    };
+    let rules: Vec<JsonValue> = registry
+        .rules()
+        .iter()
+        .map(|rule| {
+            let builtin = registry.is_builtin(rule.name());
+            describe(rule.name(), rule.id(), rule.version(), builtin)
+        })
+        .chain(
+            rules::OTHER_RULES
+                .iter()
+                .map(|(name, id)| describe(name, id, env!("CARGO_PKG_VERSION"), true)),
+        )
+        .collect();
+    let record = RECORD.lock().unwrap();
+    json!({
+        "tool": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+        "config": record.config,
+        "schemas": record.schemas.values().collect::<Vec<_>>(),
+        "libraries": record.libraries.values().collect::<Vec<_>>(),
+        "rules": rules,
+    })
+}
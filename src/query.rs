@@ -0,0 +1,50 @@
+//! `query`: prints the values a [JSONPath](crate::jsonpath) selects in a spec, for scripts that need
+//! a field of a spec (the phase list, the spec version, …) without another YAML tool. The spec is
+//! read, parsed and prepared as for validation, so the values are those of the effective spec:
+//! contract libraries merged in and suppression annotations removed.
+
+use crate::{
+    display_input, jsonpath, parse_documents, prepare_document, read_input, Args, InputFormat,
+};
+use serde_json::Value as JsonValue;
+use std::{path::Path, process::ExitCode};
+
+/// Prints every value `path` selects in the documents of `file`, one per line as JSON, or strings
+/// as they are when `raw` is set. Exits with 1 when nothing is selected.
+pub fn query(args: &Args, path: &str, file: &Path, raw: bool) -> ExitCode {
+    match select(args, path, file) {
+        Ok(values) => {
+            for value in &values {
+                match value {
+                    JsonValue::String(text) if raw => outln!("{text}"),
+                    _ => outln!("{value}"),
+                }
+            }
+            ExitCode::from(if values.is_empty() { 1 } else { 0 })
+        }
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn select(args: &Args, path: &str, file: &Path) -> Result<Vec<JsonValue>, String> {
+    let steps = jsonpath::parse(path).map_err(|e| format!("Error: {e}"))?;
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let text = read_input(file)?;
+    let documents = parse_documents(&text, format)
+        .map_err(|e| format!("Error: {}: {e}", display_input(file)))?;
+    let mut values = Vec::new();
+    for doc in &documents {
+        let (doc, ..) = prepare_document(args, file, doc);
+        values.extend(
+            jsonpath::select(&steps, &doc)
+                .into_iter()
+                .map(|(_, value)| value.clone()),
+        );
+    }
+    Ok(values)
+}
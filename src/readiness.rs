@@ -0,0 +1,203 @@
+//! `readiness --target VERSION`: what each spec still needs to pass a later spec version, as a
+//! checklist. Every spec is checked as if its `spec_version` were the target one — against the
+//! target's schema and with the target's rules — and the failures are grouped into the phase
+//! contracts to add, the port fields to fill in (`schema`, `source`), the other required fields
+//! to add and everything else to change, so that migrations can be planned by the amount of work.
+
+use crate::{
+    configured_severity,
+    diagnostics::Severity,
+    display_input, document_rule_findings, expand_inputs, is_program_spec, prepare_document,
+    rules::{parse_semver_major, SpecModel},
+    schemas::SchemaRequest,
+    workspace_documents, Args,
+};
+use jsonschema::error::ValidationErrorKind;
+use serde_json::Value as JsonValue;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// The groups of the checklist, in the order they are printed.
+const SECTIONS: [&str; 4] = [
+    "Phase contracts to add",
+    "Port fields to add",
+    "Required fields to add",
+    "Other changes",
+];
+
+/// The checklist of one spec: items by section.
+#[derive(Default)]
+struct Checklist {
+    sections: [Vec<String>; 4],
+}
+
+impl Checklist {
+    fn len(&self) -> usize {
+        self.sections.iter().map(Vec::len).sum()
+    }
+}
+
+/// Prints the checklist of every spec in `paths` for `target` (`v3` or a full version); fails
+/// when a spec is not ready.
+pub fn readiness(args: &Args, paths: &[PathBuf], target: &str) -> ExitCode {
+    let Some(major) = parse_semver_major(target) else {
+        failln!("Error: --target must be a version like v3, got '{target}'");
+        return ExitCode::from(1);
+    };
+    let target = if target.contains('.') {
+        target.to_string()
+    } else {
+        format!("v{major}.0.0")
+    };
+    let files = match expand_inputs(paths) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let (mut specs, mut ready, mut items) = (0, 0, 0);
+    for (file, doc) in workspace_documents(args, &files) {
+        if !is_program_spec(&doc) {
+            continue;
+        }
+        specs += 1;
+        let label = display_input(&file);
+        let checklist = match checklist(args, &file, &doc, &target) {
+            Ok(checklist) => checklist,
+            Err(msg) => {
+                failln!("{msg}");
+                return ExitCode::from(1);
+            }
+        };
+        if checklist.len() == 0 {
+            ready += 1;
+            outln!("✅ {label} is ready for {target}.");
+            continue;
+        }
+        items += checklist.len();
+        outln!(
+            "── {label}: {} item(s) to reach {target} ──",
+            checklist.len()
+        );
+        for (title, section) in SECTIONS.iter().zip(&checklist.sections) {
+            if section.is_empty() {
+                continue;
+            }
+            outln!("{title}:");
+            for item in section {
+                outln!("  - [ ] {item}");
+            }
+        }
+    }
+    if specs == 0 {
+        failln!("Error: no program specs found in the given paths");
+        return ExitCode::from(1);
+    }
+
+    outln!(
+        "── {specs} spec(s): {ready} ready for {target}, {} with {items} item(s) to do ──",
+        specs - ready
+    );
+    ExitCode::from(if ready == specs { 0 } else { 1 })
+}
+
+/// What `doc` needs to pass `target`.
+fn checklist(args: &Args, file: &Path, doc: &JsonValue, target: &str) -> Result<Checklist, String> {
+    let mut checklist = Checklist::default();
+    let [contracts, ports, required, other] = &mut checklist.sections;
+    let (mut instance, mut suppressions, _) = prepare_document(args, file, doc);
+    match instance.get("spec_version").and_then(|v| v.as_str()) {
+        Some(version) if version == target => {}
+        Some(version) => other.push(format!("Set spec_version from {version} to {target}")),
+        None => other.push(format!("Set spec_version to {target}")),
+    }
+    if let Some(spec) = instance.as_object_mut() {
+        spec.insert("spec_version".into(), target.into());
+    }
+
+    let request = SchemaRequest {
+        input: file,
+        spec_version: Some(target),
+    };
+    let resolved = args.schemas.resolve(None, &request)?;
+    let compiled = args.compiled_schema(&resolved)?;
+    let mut incomplete_ports = BTreeSet::new();
+    if let Err(errors) = compiled.validate(&instance) {
+        for error in errors {
+            let at = error.instance_path.to_string();
+            match &error.kind {
+                ValidationErrorKind::Required { property } => {
+                    let field = property.as_str().unwrap_or_default();
+                    match port(&instance, &at) {
+                        Some(port) => {
+                            ports.push(format!("Add `{field}` to {port} ({at})"));
+                            incomplete_ports.insert(at);
+                        }
+                        None => required.push(format!("Add `{field}` to {}", shown(&at))),
+                    }
+                }
+                _ => other.push(format!("{}: {error}", shown(&at))),
+            }
+        }
+    }
+
+    let spec = SpecModel::new(&instance);
+    if spec.context.needs_contracts() {
+        for phase in &spec.context.phases {
+            if spec.context.contract(phase.name).is_none() {
+                contracts.push(format!(
+                    "Add a phase_contracts entry for phase '{}'",
+                    phase.name
+                ));
+            }
+        }
+    }
+    for finding in document_rule_findings(args, &instance, &mut suppressions) {
+        let diagnostic = &finding.diagnostic;
+        // Missing contracts are listed per phase above, and incomplete ports with their fields.
+        if finding.waiver.is_some()
+            || matches!(diagnostic.code, "PV011" | "PV012")
+            || diagnostic
+                .pointer
+                .as_ref()
+                .is_some_and(|at| incomplete_ports.contains(at))
+            || configured_severity(finding.name, diagnostic.severity) != Severity::Error
+        {
+            continue;
+        }
+        other.push(format!("{} {}", diagnostic.code, diagnostic.message));
+    }
+    Ok(checklist)
+}
+
+/// `input 'NAME' of phase 'PHASE'` when `pointer` is a port of a phase contract.
+fn port(doc: &JsonValue, pointer: &str) -> Option<String> {
+    let segments: Vec<&str> = pointer.split('/').collect();
+    let ["", "implementation", "phase_contracts", phase, direction, _] = segments[..] else {
+        return None;
+    };
+    let noun = match direction {
+        "inputs" => "input",
+        "outputs" => "output",
+        _ => return None,
+    };
+    let phase = phase.replace("~1", "/").replace("~0", "~");
+    match doc.pointer(pointer)?.get("name").and_then(|n| n.as_str()) {
+        Some(name) => Some(format!("{noun} '{name}' of phase '{phase}'")),
+        None => Some(format!("an {noun} of phase '{phase}'")),
+    }
+}
+
+/// A JSON Pointer as shown in the checklist, with `/` for the document itself.
+fn shown(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "/"
+    } else {
+        pointer
+    }
+}
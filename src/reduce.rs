@@ -0,0 +1,198 @@
+//! `reduce`: shrinks a failing spec to a minimal reproducer. Object members and array items are
+//! removed one at a time, keeping each removal after which the target finding is still reported,
+//! until no single removal keeps it.
+
+use crate::{
+    locations::Locations, output, parse_documents, read_input, reporter::Report,
+    serialize_documents, validate_document, Args, InputFormat, Source,
+};
+use serde_json::Value as JsonValue;
+use std::{fs, path::Path, process::ExitCode};
+
+/// The finding a reproducer must keep.
+struct Target {
+    code: String,
+    /// Substring of the message, or the whole message when the target was picked automatically.
+    message: Option<String>,
+    exact: bool,
+}
+
+impl Target {
+    fn matches(&self, report: &Report) -> bool {
+        report.code.eq_ignore_ascii_case(&self.code)
+            && self.message.as_deref().is_none_or(|message| {
+                if self.exact {
+                    report.message == message
+                } else {
+                    report.message.contains(message)
+                }
+            })
+    }
+}
+
+/// Prints the smallest document of `file` that still reports the target finding, or writes it to
+/// `output`. The target is the first finding with `code` (and a message containing `message`), or
+/// the first finding of the spec when no code is given.
+pub fn reduce(
+    args: &Args,
+    file: &Path,
+    code: Option<&str>,
+    message: Option<&str>,
+    output: Option<&Path>,
+) -> ExitCode {
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let text = match read_input(file) {
+        Ok(text) => text,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let documents = match parse_documents(&text, format) {
+        Ok(documents) => documents,
+        Err(msg) => {
+            failln!("Error: {msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    // The first document reporting the target is reduced; the others are dropped.
+    let found = documents.into_iter().find_map(|doc| {
+        let findings = findings(args, file, &doc);
+        let target = match code {
+            Some(code) => Target {
+                code: code.to_string(),
+                message: message.map(str::to_string),
+                exact: false,
+            },
+            None => {
+                let first = findings.first()?;
+                Target {
+                    code: first.code.clone(),
+                    message: Some(first.message.clone()),
+                    exact: true,
+                }
+            }
+        };
+        findings
+            .iter()
+            .any(|report| target.matches(report))
+            .then_some((doc, target))
+    });
+    let Some((mut doc, target)) = found else {
+        match code {
+            Some(code) => failln!(
+                "Error: {} reports no [{code}] finding to reduce",
+                file.display()
+            ),
+            None => failln!("Error: {} reports no findings to reduce", file.display()),
+        }
+        return ExitCode::from(1);
+    };
+
+    let mut still_fails = |candidate: &JsonValue| {
+        findings(args, file, candidate)
+            .iter()
+            .any(|report| target.matches(report))
+    };
+    while shrink(&mut doc, "", &mut still_fails) > 0 {}
+
+    let reduced = match serialize_documents(std::slice::from_ref(&doc), format) {
+        Ok(reduced) => reduced,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    match output {
+        Some(path) => match fs::write(path, &reduced) {
+            Ok(()) => {
+                outln!(
+                    "✂️ Reduced {} from {} to {} line(s); {} still reports [{}].",
+                    file.display(),
+                    text.lines().count(),
+                    reduced.lines().count(),
+                    path.display(),
+                    target.code
+                );
+                ExitCode::from(0)
+            }
+            Err(e) => {
+                failln!("Error: failed to write {}: {e}", path.display());
+                ExitCode::from(1)
+            }
+        },
+        None => {
+            outln!("{}", reduced.trim_end());
+            ExitCode::from(0)
+        }
+    }
+}
+
+/// Everything validating `doc` reports, without printing it.
+fn findings(args: &Args, file: &Path, doc: &JsonValue) -> Vec<Report> {
+    let locations = Locations::default();
+    let source = Source {
+        path: file,
+        text: "",
+        locations: &locations,
+        schema: None,
+        spec_version: None,
+        previous: None,
+    };
+    let (_, captured) = output::capture(|| validate_document(args, &source, doc));
+    captured.findings()
+}
+
+/// Removes every child of the node at `pointer` whose removal keeps `still_fails`, then shrinks the
+/// remaining children. Returns how many nodes were removed.
+fn shrink(
+    doc: &mut JsonValue,
+    pointer: &str,
+    still_fails: &mut impl FnMut(&JsonValue) -> bool,
+) -> usize {
+    // Array items last to first, so that a removal leaves the indices still to try unchanged.
+    let Some(removable) = children(doc, pointer, true) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for child in removable {
+        let mut candidate = doc.clone();
+        match candidate.pointer_mut(pointer) {
+            Some(JsonValue::Object(map)) => {
+                map.remove(&child);
+            }
+            Some(JsonValue::Array(items)) => {
+                items.remove(child.parse::<usize>().unwrap());
+            }
+            _ => unreachable!("children are only listed for objects and arrays"),
+        }
+        if still_fails(&candidate) {
+            *doc = candidate;
+            removed += 1;
+        }
+    }
+    for child in children(doc, pointer, false).unwrap_or_default() {
+        let escaped = child.replace('~', "~0").replace('/', "~1");
+        removed += shrink(doc, &format!("{pointer}/{escaped}"), still_fails);
+    }
+    removed
+}
+
+/// Member names or item indices of the node at `pointer`; `None` for scalars.
+fn children(doc: &JsonValue, pointer: &str, reverse: bool) -> Option<Vec<String>> {
+    match doc.pointer(pointer)? {
+        JsonValue::Object(map) => Some(map.keys().cloned().collect()),
+        JsonValue::Array(items) => {
+            let mut indices: Vec<String> = (0..items.len()).map(|i| i.to_string()).collect();
+            if reverse {
+                indices.reverse();
+            }
+            Some(indices)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,476 @@
+//! `publish` and `verify-published`: a validated spec is pushed to a registry in canonical form,
+//! with a manifest holding its hash and optionally an Ed25519 signature, and a deployed spec is
+//! later checked against what was published.
+//!
+//! A registry is a directory or an HTTP(S) base URL with the same layout:
+//! `<algorithm.name>/<meta.version>/spec.json` and `<algorithm.name>/<meta.version>/manifest.json`.
+//! HTTP registries are read with `GET` and written with `PUT`; the `PROGRAM_VERIFY_REGISTRY_TOKEN`
+//! environment variable, when set, is sent as a bearer token.
+
+use crate::{
+    diff, display_input, output, reporter::Reporter, validate_file, Args, DEFAULT_FETCH_TIMEOUT,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::signature::{self, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// Environment variable holding the bearer token for HTTP registries.
+const TOKEN_VARIABLE: &str = "PROGRAM_VERIFY_REGISTRY_TOKEN";
+
+/// DER prefix of an Ed25519 `SubjectPublicKeyInfo`; the 32-byte key follows it.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Where specs are published.
+enum Registry {
+    Directory(PathBuf),
+    Http(Url),
+}
+
+impl Registry {
+    /// The registry at `location`, a URL or a directory.
+    fn open(location: &Path) -> Result<Self, String> {
+        match location.to_str() {
+            Some(uri) if crate::schemas::is_url(uri) => {
+                let mut url = Url::parse(uri)
+                    .map_err(|e| format!("Error: invalid registry URL {uri}: {e}"))?;
+                // Entries are resolved below the base, not next to its last segment.
+                if !url.path().ends_with('/') {
+                    url.set_path(&format!("{}/", url.path()));
+                }
+                Ok(Registry::Http(url))
+            }
+            _ => Ok(Registry::Directory(location.to_path_buf())),
+        }
+    }
+
+    /// Where `key` is stored, for messages.
+    fn locate(&self, key: &str) -> String {
+        match self {
+            Registry::Directory(dir) => dir.join(key).display().to_string(),
+            Registry::Http(base) => base
+                .join(key)
+                .map_or(key.to_string(), |url| url.to_string()),
+        }
+    }
+
+    fn client(args: &Args) -> Result<reqwest::blocking::Client, String> {
+        if args.offline {
+            return Err("--offline is set and the registry is given by URL".to_string());
+        }
+        reqwest::blocking::Client::builder()
+            .timeout(args.fetch_timeout.unwrap_or(DEFAULT_FETCH_TIMEOUT))
+            .build()
+            .map_err(|e| format!("failed to prepare HTTP client: {e}"))
+    }
+
+    fn url(base: &Url, key: &str) -> Result<Url, String> {
+        base.join(key).map_err(|e| e.to_string())
+    }
+
+    /// The contents of `key`, or `None` when the registry does not have it.
+    fn get(&self, args: &Args, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let fetched = match self {
+            Registry::Directory(dir) => match fs::read(dir.join(key)) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.to_string()),
+            },
+            Registry::Http(base) => Self::client(args).and_then(|client| {
+                let mut request = client.get(Self::url(base, key)?);
+                if let Ok(token) = env::var(TOKEN_VARIABLE) {
+                    request = request.bearer_auth(token);
+                }
+                let response = request.send().map_err(|e| e.to_string())?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                response
+                    .error_for_status()
+                    .and_then(|r| r.bytes())
+                    .map(|bytes| Some(bytes.to_vec()))
+                    .map_err(|e| e.to_string())
+            }),
+        };
+        fetched.map_err(|e| format!("Error: failed to read {}: {e}", self.locate(key)))
+    }
+
+    /// Stores `bytes` as `key`.
+    fn put(&self, args: &Args, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        let stored = match self {
+            Registry::Directory(dir) => {
+                let path = dir.join(key);
+                path.parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| fs::write(&path, bytes))
+                    .map_err(|e| e.to_string())
+            }
+            Registry::Http(base) => Self::client(args).and_then(|client| {
+                let mut request = client
+                    .put(Self::url(base, key)?)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(bytes);
+                if let Ok(token) = env::var(TOKEN_VARIABLE) {
+                    request = request.bearer_auth(token);
+                }
+                request
+                    .send()
+                    .and_then(|r| r.error_for_status())
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }),
+        };
+        stored.map_err(|e| format!("Error: failed to write {}: {e}", self.locate(key)))
+    }
+}
+
+/// A spec in the form it is published in.
+struct Canonical {
+    name: String,
+    version: String,
+    /// Compact JSON with sorted keys, after libraries are merged and suppressions removed.
+    bytes: Vec<u8>,
+    sha256: String,
+    doc: JsonValue,
+}
+
+impl Canonical {
+    fn load(args: &Args, file: &Path) -> Result<Self, String> {
+        let doc = diff::load(args, file)?;
+        let field = |pointer: &str, what: &str| -> Result<String, String> {
+            let value = doc.pointer(pointer).and_then(JsonValue::as_str);
+            match value {
+                Some(value) if is_segment(value) => Ok(value.to_string()),
+                Some(value) => Err(format!(
+                    "Error: {}: {what} '{value}' cannot name a registry entry (it must not be empty, \
+                     contain a slash or a control character, or be '.' or '..')",
+                    display_input(file)
+                )),
+                None => Err(format!(
+                    "Error: {}: a published spec needs a {what}",
+                    display_input(file)
+                )),
+            }
+        };
+        let name = field("/algorithm/name", "algorithm.name")?;
+        let version = field("/meta/version", "meta.version")?;
+        let bytes = serde_json::to_vec(&doc).unwrap();
+        Ok(Self {
+            name,
+            version,
+            sha256: canonical_sha256(&doc),
+            bytes,
+            doc,
+        })
+    }
+
+    fn key(&self, file: &str) -> String {
+        format!("{}/{}/{file}", self.name, self.version)
+    }
+
+    fn title(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// SHA-256 of `doc` in canonical form: compact JSON with sorted keys.
+pub fn canonical_sha256(doc: &JsonValue) -> String {
+    hex(&Sha256::digest(serde_json::to_vec(doc).unwrap()))
+}
+
+/// Whether `value` can be used as one path segment of a registry entry.
+fn is_segment(value: &str) -> bool {
+    !value.is_empty()
+        && value != "."
+        && value != ".."
+        && !value.contains(['/', '\\'])
+        && !value.chars().any(char::is_control)
+}
+
+/// `--registry`, or else the `registry` of the configuration file.
+fn registry(args: &Args, location: Option<&Path>) -> Result<Registry, String> {
+    match location.or(args.settings.registry.as_deref()) {
+        Some(location) => Registry::open(location),
+        None => Err(
+            "Error: no registry configured; pass --registry or set `registry` in the configuration file"
+                .to_string(),
+        ),
+    }
+}
+
+/// Validates `file` and publishes it to the registry, signed with the Ed25519 key in `sign_key`
+/// when given.
+pub fn publish(
+    args: &Args,
+    file: &Path,
+    location: Option<&Path>,
+    sign_key: Option<&Path>,
+) -> ExitCode {
+    match try_publish(args, file, location, sign_key) {
+        Ok(code) => code,
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn try_publish(
+    args: &Args,
+    file: &Path,
+    location: Option<&Path>,
+    sign_key: Option<&Path>,
+) -> Result<ExitCode, String> {
+    let registry = registry(args, location)?;
+    let key_pair = sign_key.map(load_key_pair).transpose()?;
+    let name = display_input(file);
+
+    let ((valid, _), captured) = output::capture(|| validate_file(args, file));
+    if !valid {
+        captured.replay(&args.reporters);
+        args.reporters.finish();
+        errln!("❌ {name} did not pass validation; it was not published.");
+        return Ok(ExitCode::from(1));
+    }
+    let spec = Canonical::load(args, file)?;
+
+    if let Some(existing) = registry.get(args, &spec.key("manifest.json"))? {
+        let existing: JsonValue = serde_json::from_slice(&existing).map_err(|e| {
+            format!(
+                "Error: invalid manifest {}: {e}",
+                registry.locate(&spec.key("manifest.json"))
+            )
+        })?;
+        let published = existing["sha256"].as_str().unwrap_or_default();
+        if published == spec.sha256 {
+            outln!(
+                "ℹ️ {} is already published with the same content (sha256 {published}).",
+                spec.title()
+            );
+            return Ok(ExitCode::from(0));
+        }
+        return Err(format!(
+            "Error: {} is already published with different content (sha256 {published}); \
+             change meta.version to publish this spec",
+            spec.title()
+        ));
+    }
+
+    let published_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut manifest = json!({
+        "name": spec.name,
+        "version": spec.version,
+        "sha256": spec.sha256,
+        "size": spec.bytes.len(),
+        "spec_version": spec.doc.get("spec_version"),
+        "published_at": published_at,
+        "tool": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+    });
+    if let Some(key_pair) = &key_pair {
+        manifest["signature"] = json!({
+            "algorithm": "ed25519",
+            "public_key": hex(key_pair.public_key().as_ref()),
+            "value": hex(key_pair.sign(&spec.bytes).as_ref()),
+        });
+    }
+
+    // The manifest goes last: an entry without one was never completely published.
+    registry.put(args, &spec.key("spec.json"), spec.bytes.clone())?;
+    let manifest_bytes = serde_json::to_string_pretty(&manifest).unwrap() + "\n";
+    registry.put(
+        args,
+        &spec.key("manifest.json"),
+        manifest_bytes.into_bytes(),
+    )?;
+    outln!(
+        "📦 Published {name} as {} to {} (sha256 {}{}).",
+        spec.title(),
+        registry.locate(&format!("{}/{}/", spec.name, spec.version)),
+        spec.sha256,
+        if key_pair.is_some() { ", signed" } else { "" }
+    );
+    Ok(ExitCode::from(0))
+}
+
+/// Checks that the deployed spec in `file` is the one published under its name and version, and
+/// with `verify_key` that the publication was signed by that key.
+pub fn verify(
+    args: &Args,
+    file: &Path,
+    location: Option<&Path>,
+    verify_key: Option<&Path>,
+) -> ExitCode {
+    match try_verify(args, file, location, verify_key) {
+        Ok(code) => code,
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn try_verify(
+    args: &Args,
+    file: &Path,
+    location: Option<&Path>,
+    verify_key: Option<&Path>,
+) -> Result<ExitCode, String> {
+    let registry = registry(args, location)?;
+    let public_key = verify_key.map(load_public_key).transpose()?;
+    let name = display_input(file);
+    let spec = Canonical::load(args, file)?;
+
+    let manifest_key = spec.key("manifest.json");
+    let Some(manifest) = registry.get(args, &manifest_key)? else {
+        errln!(
+            "❌ {} was never published to {}.",
+            spec.title(),
+            registry.locate("")
+        );
+        return Ok(ExitCode::from(1));
+    };
+    let manifest: JsonValue = serde_json::from_slice(&manifest).map_err(|e| {
+        format!(
+            "Error: invalid manifest {}: {e}",
+            registry.locate(&manifest_key)
+        )
+    })?;
+    let published = manifest["sha256"].as_str().unwrap_or_default();
+
+    if published != spec.sha256 {
+        errln!(
+            "❌ {name} does not match {} as published: sha256 {} instead of {published}.",
+            spec.title(),
+            spec.sha256
+        );
+        // Show what changed when the published spec is still there and intact.
+        if let Some(bytes) = registry.get(args, &spec.key("spec.json"))? {
+            if hex(&Sha256::digest(&bytes)) != published {
+                errln!(
+                    "⚠️ {} does not match its manifest either; the registry entry was modified.",
+                    registry.locate(&spec.key("spec.json"))
+                );
+            } else if let Ok(doc) = serde_json::from_slice::<JsonValue>(&bytes) {
+                let title = format!("{} (published)", spec.title());
+                diff::print_differences(&doc, &spec.doc, &title, &name);
+            }
+        }
+        return Ok(ExitCode::from(1));
+    }
+
+    let signature = manifest.get("signature");
+    match (public_key, signature) {
+        (Some(public_key), Some(signature)) => {
+            let value = signature["value"].as_str().and_then(unhex);
+            let verified = value.is_some_and(|value| {
+                UnparsedPublicKey::new(&signature::ED25519, &public_key)
+                    .verify(&spec.bytes, &value)
+                    .is_ok()
+            });
+            if !verified {
+                errln!(
+                    "❌ The signature of {} was not made with the key in {}.",
+                    spec.title(),
+                    verify_key.unwrap_or(Path::new("?")).display()
+                );
+                return Ok(ExitCode::from(1));
+            }
+            outln!(
+                "✅ {name} matches {} as published (sha256 {published}), signed with the given key.",
+                spec.title()
+            );
+        }
+        (Some(_), None) => {
+            errln!(
+                "❌ {name} matches {} as published, but the publication is not signed.",
+                spec.title()
+            );
+            return Ok(ExitCode::from(1));
+        }
+        (None, signature) => {
+            outln!(
+                "✅ {name} matches {} as published (sha256 {published}).",
+                spec.title()
+            );
+            if signature.is_some() {
+                outln!("ℹ️ The publication is signed; pass --verify-key to check the signature.");
+            }
+        }
+    }
+    Ok(ExitCode::from(0))
+}
+
+/// The DER contents of `bytes`, which hold DER or a PEM block.
+fn der(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    let Ok(text) = std::str::from_utf8(&bytes) else {
+        return Ok(bytes);
+    };
+    if !text.trim_start().starts_with("-----BEGIN") {
+        return Ok(bytes);
+    }
+    let body: String = text
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    STANDARD
+        .decode(body)
+        .map_err(|e| format!("invalid PEM: {e}"))
+}
+
+/// The Ed25519 key pair in the PKCS#8 file `path` (PEM or DER), as `openssl genpkey -algorithm
+/// ed25519` writes it.
+fn load_key_pair(path: &Path) -> Result<Ed25519KeyPair, String> {
+    let key = fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(der)
+        .and_then(|der| {
+            Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der)
+                .map_err(|e| format!("not an Ed25519 private key in PKCS#8 format ({e})"))
+        });
+    key.map_err(|e| format!("Error: failed to load signing key {}: {e}", path.display()))
+}
+
+/// The Ed25519 public key in `path`: a `SubjectPublicKeyInfo` (PEM or DER), or the 32 raw bytes.
+fn load_public_key(path: &Path) -> Result<Vec<u8>, String> {
+    let key = fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(der)
+        .and_then(|der| match der.len() {
+            32 => Ok(der),
+            44 if der.starts_with(&ED25519_SPKI_PREFIX) => Ok(der[12..].to_vec()),
+            _ => Err("not an Ed25519 public key".to_string()),
+        });
+    key.map_err(|e| {
+        format!(
+            "Error: failed to load verification key {}: {e}",
+            path.display()
+        )
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
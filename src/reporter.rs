@@ -0,0 +1,267 @@
+//! Reporting of findings. Validation hands every finding to the [`Reporters`] of the run the moment
+//! it is produced; `--format` picks the human, JSON Lines, SARIF or HTML renderer, and other sinks (a
+//! database, a ticketing system) plug in by implementing [`Reporter`].
+
+use crate::{
+    diagnostics::Severity, html::HtmlReporter, locations::Location, output, provenance, rules,
+};
+use clap::ValueEnum;
+use serde_json::{json, Map, Value as JsonValue};
+use std::sync::Mutex;
+
+/// Rule ID under which JSON Schema violations are reported.
+pub const SCHEMA_CODE: &str = "schema";
+
+/// How findings are rendered.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Icons, messages and source excerpts for people.
+    #[default]
+    Human,
+    /// One JSON object per finding and line, printed as soon as the finding is produced, and a
+    /// final `provenance` line.
+    Json,
+    /// A SARIF 2.1.0 log printed at the end of the run, for code scanning tools.
+    Sarif,
+    /// A self-contained HTML page printed at the end of the run, for CI artifacts.
+    Html,
+}
+
+impl ReportFormat {
+    pub fn reporter(self) -> Box<dyn Reporter> {
+        match self {
+            ReportFormat::Human => Box::new(HumanReporter),
+            ReportFormat::Json => Box::new(JsonReporter),
+            ReportFormat::Sarif => Box::new(SarifReporter::default()),
+            ReportFormat::Html => Box::new(HtmlReporter::default()),
+        }
+    }
+}
+
+/// One finding, with everything a renderer may want to show about it.
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// Input the finding is about, as displayed (`<stdin>` for `-`); `None` for the cross-spec
+    /// checks.
+    pub file: Option<String>,
+    /// Rule ID, or [`SCHEMA_CODE`].
+    pub code: String,
+    /// Label of the rule; `None` for schema violations.
+    pub rule: Option<&'static str>,
+    pub severity: Severity,
+    pub message: String,
+    /// JSON Pointer to the offending node.
+    pub pointer: Option<String>,
+    /// Where that node (or its closest ancestor present in the source) starts.
+    pub location: Option<Location>,
+    /// The `--> file:line:column` header and underlined source line.
+    pub excerpt: Option<String>,
+    /// Keyword of the schema that rejected the node (schema violations only).
+    pub schema_path: Option<String>,
+    /// `title: description` the schema gives the offending node (schema violations only).
+    pub annotation: Option<String>,
+}
+
+/// Receives the findings of a run. Validation may run on several threads; findings are delivered
+/// one at a time, in input order, each as soon as its file's turn comes.
+pub trait Reporter: Send + Sync {
+    /// Called with every document of `file` that gets validated, as prepared for the rules, before
+    /// its findings.
+    fn spec(&self, _file: &str, _doc: &JsonValue) {}
+
+    fn report(&self, report: &Report);
+
+    /// Called once the last finding of a run has been reported.
+    fn finish(&self) {}
+}
+
+/// Reporters that all receive every finding.
+pub struct Reporters {
+    reporters: Vec<Box<dyn Reporter>>,
+}
+
+impl Reporters {
+    pub fn new(reporters: Vec<Box<dyn Reporter>>) -> Self {
+        Self { reporters }
+    }
+
+    pub fn push(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters.push(reporter);
+    }
+}
+
+impl Reporter for Reporters {
+    fn spec(&self, file: &str, doc: &JsonValue) {
+        for reporter in &self.reporters {
+            reporter.spec(file, doc);
+        }
+    }
+
+    fn report(&self, report: &Report) {
+        for reporter in &self.reporters {
+            reporter.report(report);
+        }
+    }
+
+    fn finish(&self) {
+        for reporter in &self.reporters {
+            reporter.finish();
+        }
+    }
+}
+
+impl Default for Reporters {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl std::fmt::Debug for Reporters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Reporters({} reporters)", self.reporters.len())
+    }
+}
+
+/// The default terminal output. Informational findings are only shown with `--verbose`.
+pub struct HumanReporter;
+
+impl Reporter for HumanReporter {
+    fn report(&self, report: &Report) {
+        if report.severity == Severity::Info && !output::verbose() {
+            return;
+        }
+        match report.rule {
+            Some(label) => errln!(
+                "{} Rule: {label} [{}]: {}",
+                report.severity.icon(),
+                report.code,
+                report.message
+            ),
+            None => errln!(
+                "  • {} (instance: {}, schema: {})",
+                report.message,
+                report.pointer.as_deref().unwrap_or_default(),
+                report.schema_path.as_deref().unwrap_or_default()
+            ),
+        }
+        if let Some(excerpt) = &report.excerpt {
+            errln!("{excerpt}");
+        }
+        if output::verbose() {
+            if let Some(annotation) = &report.annotation {
+                errln!("    ℹ️ {annotation}");
+            }
+        }
+    }
+}
+
+/// JSON Lines on stdout: one object per finding, fields without a value left out, followed by a
+/// `{"provenance": …}` line once the run finishes.
+pub struct JsonReporter;
+
+impl Reporter for JsonReporter {
+    fn report(&self, report: &Report) {
+        output::emit(&to_json(report).to_string());
+    }
+
+    fn finish(&self) {
+        output::emit(&json!({ "provenance": provenance::to_json() }).to_string());
+    }
+}
+
+/// The JSON object of a finding, as printed by `--format json`.
+pub fn to_json(report: &Report) -> JsonValue {
+    let mut object = Map::new();
+    let mut field = |key: &str, value: Option<JsonValue>| {
+        if let Some(value) = value {
+            object.insert(key.to_string(), value);
+        }
+    };
+    field("file", report.file.clone().map(JsonValue::from));
+    field("line", report.location.map(|l| l.line.into()));
+    field("column", report.location.map(|l| l.column.into()));
+    field("pointer", report.pointer.clone().map(JsonValue::from));
+    field("code", Some(report.code.clone().into()));
+    field("rule", report.rule.map(JsonValue::from));
+    field("severity", Some(report.severity.name().into()));
+    field("message", Some(report.message.clone().into()));
+    field(
+        "schema_path",
+        report.schema_path.clone().map(JsonValue::from),
+    );
+    field("annotation", report.annotation.clone().map(JsonValue::from));
+    JsonValue::Object(object)
+}
+
+/// A SARIF 2.1.0 log on stdout. SARIF is a single document, so results are collected until the
+/// run finishes.
+#[derive(Default)]
+pub struct SarifReporter {
+    results: Mutex<Vec<JsonValue>>,
+}
+
+impl Reporter for SarifReporter {
+    fn report(&self, report: &Report) {
+        let mut result = json!({
+            "ruleId": report.code,
+            "level": sarif_level(report.severity),
+            "message": { "text": report.message },
+        });
+        if let Some(file) = &report.file {
+            let mut location = json!({
+                "physicalLocation": { "artifactLocation": { "uri": file } },
+            });
+            if let Some(at) = report.location {
+                location["physicalLocation"]["region"] =
+                    json!({ "startLine": at.line, "startColumn": at.column });
+            }
+            if let Some(pointer) = &report.pointer {
+                location["logicalLocations"] = json!([{ "fullyQualifiedName": pointer }]);
+            }
+            result["locations"] = json!([location]);
+        }
+        self.results.lock().unwrap().push(result);
+    }
+
+    fn finish(&self) {
+        let results = std::mem::take(&mut *self.results.lock().unwrap());
+        let registry = rules::registered();
+        let rules: Vec<JsonValue> = std::iter::once((SCHEMA_CODE, "JSON Schema violation"))
+            .chain(rules::rule_codes())
+            .map(|(id, summary)| {
+                let mut descriptor = json!({ "id": id, "shortDescription": { "text": summary } });
+                if let Some(rule) = rules::rule_for_code(id).and_then(|name| registry.get(name)) {
+                    descriptor["defaultConfiguration"] =
+                        json!({ "level": sarif_level(rule.default_severity()) });
+                    descriptor["properties"] = json!({ "tags": [rule.category()] });
+                }
+                descriptor
+            })
+            .collect();
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+                "properties": { "provenance": provenance::to_json() },
+            }],
+        });
+        output::emit(&serde_json::to_string_pretty(&log).unwrap());
+    }
+}
+
+/// SARIF `level` of a severity.
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
@@ -0,0 +1,152 @@
+//! `report rules`: which rule IDs fire most across a spec corpus, and where.
+
+use crate::{
+    configured_severity, diagnostics::Severity, display_input, document_rule_findings,
+    expand_inputs, is_program_spec, prepare_document, rule_enabled, rules, workspace_documents,
+    Args, RuleFinding,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+/// File column for findings of the cross-spec checks.
+const CROSS_SPEC: &str = "(across specs)";
+
+/// Occurrences of one rule ID.
+#[derive(Default)]
+struct RuleTally {
+    errors: usize,
+    warnings: usize,
+    infos: usize,
+    per_file: HashMap<String, usize>,
+}
+
+impl RuleTally {
+    fn total(&self) -> usize {
+        self.errors + self.warnings + self.infos
+    }
+}
+
+/// Runs every enabled rule on the specs under `paths` without printing the findings and prints,
+/// per rule ID, how often it fired, in how many files, and the `top` files with most findings.
+/// `--select`/`--ignore` and configured severities apply; baselines and `--fail-on` do not.
+pub fn rules(args: &Args, paths: &[PathBuf], top: usize) -> ExitCode {
+    let files = match expand_inputs(paths) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut tallies: BTreeMap<&'static str, RuleTally> = BTreeMap::new();
+    let mut record = |file: &str, finding: RuleFinding| {
+        let diagnostic = finding.diagnostic;
+        if finding.waiver.is_some()
+            || !rules::code_selected(diagnostic.code, &args.select, &args.ignore)
+        {
+            return;
+        }
+        let tally = tallies.entry(diagnostic.code).or_default();
+        match configured_severity(finding.name, diagnostic.severity) {
+            Severity::Error => tally.errors += 1,
+            Severity::Warning => tally.warnings += 1,
+            Severity::Info => tally.infos += 1,
+        }
+        *tally.per_file.entry(file.to_string()).or_default() += 1;
+    };
+
+    let mut corpus = Vec::new();
+    for (file, doc) in workspace_documents(args, &files) {
+        if !is_program_spec(&doc) {
+            continue;
+        }
+        let label = display_input(&file);
+        let (instance, mut suppressions, findings) = prepare_document(args, &file, &doc);
+        for finding in findings {
+            record(&label, finding);
+        }
+        for finding in document_rule_findings(args, &instance, &mut suppressions) {
+            record(&label, finding);
+        }
+        corpus.push((label, instance));
+    }
+    if corpus.is_empty() {
+        failln!("Error: no program specs found in the given paths");
+        return ExitCode::from(1);
+    }
+
+    for ((name, label), check) in rules::CROSS_SPEC_RULES {
+        if !rule_enabled(name) {
+            continue;
+        }
+        for diagnostic in check(&corpus) {
+            let finding = RuleFinding {
+                name,
+                label,
+                diagnostic,
+                waiver: None,
+            };
+            record(CROSS_SPEC, finding);
+        }
+    }
+
+    let codes = rules::rule_codes();
+    let findings: usize = tallies.values().map(RuleTally::total).sum();
+    outln!(
+        "── {} spec(s), {findings} finding(s) from {} rule ID(s) ──",
+        corpus.len(),
+        tallies.len()
+    );
+    let mut rows: Vec<(&str, &RuleTally)> = tallies.iter().map(|(c, t)| (*c, t)).collect();
+    rows.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(b.0)));
+    if !rows.is_empty() {
+        outln!(
+            "  {:>5}  {:>5}  {:>6}  {:>8}  rule",
+            "count",
+            "files",
+            "errors",
+            "warnings"
+        );
+    }
+    for (code, tally) in rows {
+        let summary = codes
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, summary)| *summary)
+            .unwrap_or_default();
+        outln!(
+            "  {:>5}  {:>5}  {:>6}  {:>8}  {code} {summary}",
+            tally.total(),
+            tally.per_file.len(),
+            tally.errors,
+            tally.warnings
+        );
+        let mut offenders: Vec<(&String, &usize)> = tally.per_file.iter().collect();
+        offenders.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (file, count) in offenders.iter().take(top) {
+            outln!("  {count:>5}         {file}");
+        }
+        if offenders.len() > top {
+            outln!(
+                "                 … and {} more file(s)",
+                offenders.len() - top
+            );
+        }
+    }
+
+    // Only IDs that could have fired in this run: rule enabled and ID selected.
+    let silent: Vec<&str> = codes
+        .iter()
+        .map(|(code, _)| *code)
+        .filter(|code| !tallies.contains_key(code))
+        .filter(|code| rules::code_selected(code, &args.select, &args.ignore))
+        .filter(|code| rules::rule_for_code(code).is_some_and(rule_enabled))
+        .collect();
+    if !silent.is_empty() {
+        outln!("  never fired: {}", silent.join(", "));
+    }
+    ExitCode::from(0)
+}
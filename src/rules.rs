@@ -0,0 +1,3767 @@
+//! Domain rules that go beyond what JSON Schema can express.
+//!
+//! Per-document rules implement [`Rule`] and live in the process-wide [`RuleRegistry`]; the
+//! built-in ones are registered first, and further rules can be added with
+//! [`RuleRegistry::register`] before any spec is validated. [`RuleRegistry::configure`] adjusts any
+//! rule the way the `rules` section of the configuration file does.
+
+use crate::{
+    conditions,
+    config::{IdentifierCase, IdentityMatch, NamingPatterns, RuleConfig},
+    context::{Dependency, DependencyGraph, PhaseSource, Port, SpecContext, Vertex},
+    diagnostics::{pointer, Diagnostic, Severity},
+    port_types::PortType,
+    supervisor::parse_duration,
+};
+use jsonschema::JSONSchema;
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{OnceLock, RwLock, RwLockReadGuard},
+};
+
+/// The spec a rule checks.
+pub struct SpecModel<'a> {
+    /// The parsed document, with contract libraries merged in and suppressions removed.
+    pub doc: &'a JsonValue,
+    /// Phases, contracts, dataflow and graph of `doc`, resolved once for all rules.
+    pub context: SpecContext<'a>,
+}
+
+impl<'a> SpecModel<'a> {
+    pub fn new(doc: &'a JsonValue) -> Self {
+        Self {
+            doc,
+            context: SpecContext::new(doc),
+        }
+    }
+}
+
+/// Findings of one rule on one spec.
+pub struct Diagnostics {
+    severity: Severity,
+    findings: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Collects findings for a rule whose default severity is `severity`.
+    pub fn new(severity: Severity) -> Self {
+        Self {
+            severity,
+            findings: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.findings.push(diagnostic);
+    }
+
+    /// Adds a finding at the rule's default severity about the node at `at`.
+    pub fn report(&mut self, code: &'static str, message: impl Into<String>, at: &str) {
+        self.push(Diagnostic::new(code, self.severity, message).at(at));
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.findings
+    }
+}
+
+/// A rule evaluated on every spec document.
+pub trait Rule: Send + Sync {
+    /// Rule ID prefix shared by its findings, e.g. `PV01` for `PV010`–`PV019`.
+    fn id(&self) -> &'static str;
+
+    /// Stable name used to configure the rule.
+    fn name(&self) -> &'static str;
+
+    /// Human-readable label printed with every finding.
+    fn label(&self) -> &'static str;
+
+    /// Area of the spec the rule is about (`naming`, `contracts`, `data-flow`, `side-effects`,
+    /// `observability`, …), reported as a tag in SARIF output.
+    fn category(&self) -> &'static str;
+
+    /// Severity of findings reported through [`Diagnostics::report`].
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// Identifier and summary of every finding the rule reports; identifiers start with
+    /// [`Rule::id`].
+    fn codes(&self) -> Vec<(&'static str, &'static str)>;
+
+    fn check(&self, spec: &SpecModel, diagnostics: &mut Diagnostics);
+
+    /// Whether the rule runs without an entry in the `rules` section of the configuration file.
+    /// Opt-in rules run once an entry names them, unless it sets `enabled: false`.
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+
+    /// Version of the rule's logic, recorded in the provenance of reports. Built-in rules share
+    /// the validator's version.
+    fn version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// Takes the rule-specific settings of a `rules` entry (such as `match`); called by
+    /// [`RuleRegistry::configure`]. Rules without such settings ignore them.
+    fn configure(&mut self, _settings: &RuleConfig) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Signature shared by the built-in per-document checks.
+type DocumentCheck = fn(&SpecModel, &mut Diagnostics);
+
+/// A built-in rule: one of the `check_*` functions of this module.
+struct BuiltinRule {
+    id: &'static str,
+    name: &'static str,
+    label: &'static str,
+    category: &'static str,
+    severity: Severity,
+    check: DocumentCheck,
+}
+
+impl Rule for BuiltinRule {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn label(&self) -> &'static str {
+        self.label
+    }
+
+    fn category(&self) -> &'static str {
+        self.category
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        !OPT_IN_RULES.contains(&self.name)
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        RULE_CODES
+            .iter()
+            .copied()
+            .filter(|(code, _)| code.starts_with(self.id))
+            .collect()
+    }
+
+    fn check(&self, spec: &SpecModel, diagnostics: &mut Diagnostics) {
+        (self.check)(spec, diagnostics)
+    }
+}
+
+/// `algorithm.name` must match the algorithm identity in `meta.title`, derived by the configured
+/// [`IdentityMatch`].
+#[derive(Default)]
+struct TitleVsAlgorithm {
+    matching: IdentityMatch,
+    /// The compiled pattern of [`IdentityMatch::Regex`].
+    pattern: Option<Regex>,
+}
+
+impl Rule for TitleVsAlgorithm {
+    fn id(&self) -> &'static str {
+        "PV00"
+    }
+
+    fn name(&self) -> &'static str {
+        TITLE_RULE.0
+    }
+
+    fn label(&self) -> &'static str {
+        TITLE_RULE.1
+    }
+
+    fn category(&self) -> &'static str {
+        "naming"
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        RULE_CODES
+            .iter()
+            .copied()
+            .filter(|(code, _)| code.starts_with(self.id()))
+            .collect()
+    }
+
+    fn check(&self, spec: &SpecModel, errors: &mut Diagnostics) {
+        let Some(meta_title) = spec
+            .doc
+            .get("meta")
+            .and_then(|m| m.get("title"))
+            .and_then(|t| t.as_str())
+        else {
+            return errors.report("PV002", "Missing meta.title", "/meta/title");
+        };
+
+        let Some(algorithm_name) = spec
+            .doc
+            .get("algorithm")
+            .and_then(|a| a.get("name"))
+            .and_then(|n| n.as_str())
+        else {
+            return errors.report("PV003", "Missing algorithm.name", "/algorithm/name");
+        };
+
+        let message = match &self.matching {
+            IdentityMatch::Prefix => {
+                let base = base_name_from_title(meta_title);
+                (!same_ident(&base, algorithm_name)).then(|| format!(
+                    "algorithm.name='{algorithm_name}' does not match the base of meta.title='{meta_title}' (detected '{base}')"
+                ))
+            }
+            IdentityMatch::Exact => (!same_ident(meta_title.trim(), algorithm_name)).then(|| {
+                format!(
+                    "algorithm.name='{algorithm_name}' does not match meta.title='{meta_title}'"
+                )
+            }),
+            IdentityMatch::Slug => {
+                let (title_slug, name_slug) = (slugify(meta_title), slugify(algorithm_name));
+                (title_slug != name_slug).then(|| format!(
+                    "algorithm.name='{algorithm_name}' (slug '{name_slug}') does not match meta.title='{meta_title}' (slug '{title_slug}')"
+                ))
+            }
+            IdentityMatch::Regex(source) => {
+                let pattern = self.pattern.as_ref().expect("compiled by configure");
+                let Some(captures) = pattern.captures(meta_title) else {
+                    return errors.report(
+                        "PV005",
+                        format!("meta.title='{meta_title}' does not match the identity pattern '{source}'"),
+                        "/meta/title",
+                    );
+                };
+                let expected = captures
+                    .name("name")
+                    .or_else(|| captures.get(1))
+                    .map_or("", |m| m.as_str().trim());
+                (!same_ident(expected, algorithm_name)).then(|| format!(
+                    "algorithm.name='{algorithm_name}' does not match '{expected}', captured from meta.title='{meta_title}' by the identity pattern"
+                ))
+            }
+            IdentityMatch::MetaSlug => {
+                let Some(slug) = spec.doc.pointer("/meta/slug").and_then(|s| s.as_str()) else {
+                    return errors.report(
+                        "PV004",
+                        "Missing meta.slug, which identifies the algorithm",
+                        "/meta",
+                    );
+                };
+                let name_slug = slugify(algorithm_name);
+                (name_slug != slug).then(|| format!(
+                    "algorithm.name='{algorithm_name}' (slug '{name_slug}') does not match meta.slug='{slug}'"
+                ))
+            }
+        };
+        if let Some(message) = message {
+            errors.report("PV001", message, "/algorithm/name");
+        }
+    }
+
+    fn configure(&mut self, settings: &RuleConfig) -> Result<(), String> {
+        let Some(matching) = &settings.matching else {
+            return Ok(());
+        };
+        self.pattern = match matching {
+            IdentityMatch::Regex(source) => {
+                let pattern = Regex::new(source).map_err(|e| {
+                    format!("Error: invalid match regex of rule '{}': {e}", self.name())
+                })?;
+                if pattern.captures_len() < 2 {
+                    return Err(format!(
+                        "Error: the match regex of rule '{}' needs a capture group for the algorithm name",
+                        self.name()
+                    ));
+                }
+                Some(pattern)
+            }
+            _ => None,
+        };
+        self.matching = matching.clone();
+        Ok(())
+    }
+}
+
+/// Phase names, port names and error codes must match the patterns set in the rule's `patterns`
+/// setting. Kinds of identifiers without a pattern are not checked, so the rule does nothing until
+/// it is configured.
+#[derive(Default)]
+struct Naming {
+    phase: Option<(String, Regex)>,
+    port: Option<(String, Regex)>,
+    error_code: Option<(String, Regex)>,
+}
+
+impl Rule for Naming {
+    fn id(&self) -> &'static str {
+        "PV22"
+    }
+
+    fn name(&self) -> &'static str {
+        NAMING_RULE.0
+    }
+
+    fn label(&self) -> &'static str {
+        NAMING_RULE.1
+    }
+
+    fn category(&self) -> &'static str {
+        "naming"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        RULE_CODES
+            .iter()
+            .copied()
+            .filter(|(code, _)| code.starts_with(self.id()))
+            .collect()
+    }
+
+    fn check(&self, spec: &SpecModel, warnings: &mut Diagnostics) {
+        let context = &spec.context;
+        if let Some((source, pattern)) = &self.phase {
+            for phase in &context.phases {
+                if pattern.is_match(phase.name) {
+                    continue;
+                }
+                let at = match phase.sources.first() {
+                    Some(PhaseSource::Listed(index)) => {
+                        pointer(&["algorithm", "phases", &index.to_string()])
+                    }
+                    _ => "/algorithm/graph/nodes".to_string(),
+                };
+                warnings.report(
+                    "PV220",
+                    format!(
+                        "Phase name '{}' does not match the phase naming pattern '{source}'",
+                        phase.name
+                    ),
+                    &at,
+                );
+            }
+            // Contracts of undeclared phases are PV010; their names are checked all the same.
+            for contract in &context.contracts {
+                if context.phase(contract.phase).is_none() && !pattern.is_match(contract.phase) {
+                    warnings.report(
+                        "PV220",
+                        format!(
+                            "Phase name '{}' does not match the phase naming pattern '{source}'",
+                            contract.phase
+                        ),
+                        &contract_pointer(contract.phase, &[]),
+                    );
+                }
+            }
+        }
+        for contract in &context.contracts {
+            let phase = contract.phase;
+            if let Some((source, pattern)) = &self.port {
+                let ports = [
+                    ("input", "inputs", &contract.inputs),
+                    ("output", "outputs", &contract.outputs),
+                ];
+                for (noun, field, ports) in ports {
+                    for port in ports.iter().filter(|port| !pattern.is_match(port.name)) {
+                        warnings.report(
+                            "PV221",
+                            format!(
+                                "Phase '{phase}' {noun} '{}' does not match the port naming pattern \
+                                 '{source}'",
+                                port.name
+                            ),
+                            &contract_pointer(phase, &[field, &port.index.to_string(), "name"]),
+                        );
+                    }
+                }
+            }
+            if let Some((source, pattern)) = &self.error_code {
+                let errors = contract.value.get("errors").and_then(|e| e.as_array());
+                for (index, error) in errors.into_iter().flatten().enumerate() {
+                    let Some(code) = error.get("code").and_then(|c| c.as_str()) else {
+                        continue;
+                    };
+                    if !pattern.is_match(code) {
+                        warnings.report(
+                            "PV222",
+                            format!(
+                                "Phase '{phase}' error code '{code}' does not match the error code \
+                                 pattern '{source}'"
+                            ),
+                            &contract_pointer(phase, &["errors", &index.to_string(), "code"]),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn configure(&mut self, settings: &RuleConfig) -> Result<(), String> {
+        let Some(patterns) = &settings.patterns else {
+            return Ok(());
+        };
+        let NamingPatterns {
+            phase,
+            port,
+            error_code,
+        } = patterns;
+        let compile = |kind: &str, source: &Option<String>| {
+            source
+                .as_ref()
+                .map(|source| {
+                    Regex::new(source)
+                        .map(|pattern| (source.clone(), pattern))
+                        .map_err(|e| {
+                            format!(
+                                "Error: invalid {kind} pattern of rule '{}': {e}",
+                                self.name()
+                            )
+                        })
+                })
+                .transpose()
+        };
+        let (phase, port, error_code) = (
+            compile("phase", phase)?,
+            compile("port", port)?,
+            compile("error_code", error_code)?,
+        );
+        // A later entry replaces the patterns it sets and keeps the others.
+        self.phase = phase.or(self.phase.take());
+        self.port = port.or(self.port.take());
+        self.error_code = error_code.or(self.error_code.take());
+        Ok(())
+    }
+}
+
+/// Built-in rules that only run once the configuration file enables them: heuristics that would be
+/// too noisy for every spec.
+const OPT_IN_RULES: [&str; 1] = ["contract-drift"];
+
+/// Built-in per-document rules after [`TitleVsAlgorithm`], in reporting order.
+const BUILTIN_RULES: [BuiltinRule; 20] = [
+    BuiltinRule {
+        id: "PV12",
+        name: "structure",
+        label: "structure",
+        category: "structure",
+        severity: Severity::Error,
+        check: check_structure,
+    },
+    BuiltinRule {
+        id: "PV25",
+        name: "graph-edges",
+        label: "graph edges",
+        category: "structure",
+        severity: Severity::Error,
+        check: check_graph_edges,
+    },
+    BuiltinRule {
+        id: "PV26",
+        name: "graph-terminals",
+        label: "graph entry and exit",
+        category: "structure",
+        severity: Severity::Error,
+        check: check_graph_terminals,
+    },
+    BuiltinRule {
+        id: "PV27",
+        name: "graph-conditions",
+        label: "graph conditions",
+        category: "structure",
+        severity: Severity::Error,
+        check: check_graph_conditions,
+    },
+    BuiltinRule {
+        id: "PV01",
+        name: "phase-contracts",
+        label: "phase contracts",
+        category: "contracts",
+        severity: Severity::Error,
+        check: check_phase_contracts,
+    },
+    BuiltinRule {
+        id: "PV13",
+        name: "cycles",
+        label: "dependency cycles",
+        category: "data-flow",
+        severity: Severity::Error,
+        check: check_cycles,
+    },
+    BuiltinRule {
+        id: "PV14",
+        name: "dead-ports",
+        label: "unconsumed outputs",
+        category: "data-flow",
+        severity: Severity::Warning,
+        check: check_dead_ports,
+    },
+    BuiltinRule {
+        id: "PV15",
+        name: "dataflow",
+        label: "dataflow completeness",
+        category: "data-flow",
+        severity: Severity::Error,
+        check: check_dataflow,
+    },
+    BuiltinRule {
+        id: "PV16",
+        name: "port-types",
+        label: "port types",
+        category: "contracts",
+        severity: Severity::Error,
+        check: check_port_types,
+    },
+    BuiltinRule {
+        id: "PV17",
+        name: "port-examples",
+        label: "port examples",
+        category: "contracts",
+        severity: Severity::Warning,
+        check: check_port_examples,
+    },
+    BuiltinRule {
+        id: "PV18",
+        name: "retry-policy",
+        label: "retry policy",
+        category: "side-effects",
+        severity: Severity::Error,
+        check: check_retry_policies,
+    },
+    BuiltinRule {
+        id: "PV19",
+        name: "timeout-budget",
+        label: "timeout budget",
+        category: "contracts",
+        severity: Severity::Error,
+        check: check_timeout_budget,
+    },
+    BuiltinRule {
+        id: "PV20",
+        name: "fallback-chains",
+        label: "fallback chains",
+        category: "contracts",
+        severity: Severity::Error,
+        check: check_fallback_chains,
+    },
+    BuiltinRule {
+        id: "PV21",
+        name: "contract-drift",
+        label: "contract drift",
+        category: "contracts",
+        severity: Severity::Warning,
+        check: check_contract_drift,
+    },
+    BuiltinRule {
+        id: "PV24",
+        name: "algorithm-outputs",
+        label: "algorithm outputs",
+        category: "contracts",
+        severity: Severity::Error,
+        check: check_algorithm_outputs,
+    },
+    BuiltinRule {
+        id: "PV02",
+        name: "data-classification",
+        label: "data classification",
+        category: "data-flow",
+        severity: Severity::Error,
+        check: check_data_classification,
+    },
+    BuiltinRule {
+        id: "PV03",
+        name: "phase-purity",
+        label: "phase purity",
+        category: "side-effects",
+        severity: Severity::Error,
+        check: check_phase_purity,
+    },
+    BuiltinRule {
+        id: "PV04",
+        name: "idempotency-key",
+        label: "idempotency key",
+        category: "side-effects",
+        severity: Severity::Error,
+        check: check_idempotency_keys,
+    },
+    BuiltinRule {
+        id: "PV05",
+        name: "observability",
+        label: "observability",
+        category: "observability",
+        severity: Severity::Error,
+        check: check_observability,
+    },
+    BuiltinRule {
+        id: "PV10",
+        name: OBSERVATIONS_RULE.0,
+        label: OBSERVATIONS_RULE.1,
+        category: "insights",
+        severity: Severity::Info,
+        check: check_observations,
+    },
+];
+
+/// The per-document rules, in reporting order, and the settings of every rule.
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn Rule>>,
+    /// How many of `rules`, from the start, are built in.
+    builtin: usize,
+    settings: BTreeMap<String, RuleConfig>,
+}
+
+impl RuleRegistry {
+    /// The built-in rules.
+    pub fn standard() -> Self {
+        let mut registry = Self::default();
+        registry
+            .register(Box::new(TitleVsAlgorithm::default()))
+            .unwrap();
+        registry.register(Box::new(Naming::default())).unwrap();
+        for rule in BUILTIN_RULES {
+            registry.register(Box::new(rule)).unwrap();
+        }
+        registry.builtin = registry.rules.len();
+        registry
+    }
+
+    /// Adds `rule` after the rules registered so far. Its name and ID prefix must not be taken by
+    /// another rule, and the identifiers of its findings must start with its ID prefix.
+    pub fn register(&mut self, rule: Box<dyn Rule>) -> Result<(), String> {
+        let clash = self
+            .rules
+            .iter()
+            .map(|r| (r.name(), r.id()))
+            .chain(OTHER_RULES)
+            .find(|(name, id)| {
+                *name == rule.name() || id.starts_with(rule.id()) || rule.id().starts_with(id)
+            });
+        if let Some((name, id)) = clash {
+            return Err(format!(
+                "Error: rule '{}' ({}) clashes with registered rule '{name}' ({id})",
+                rule.name(),
+                rule.id()
+            ));
+        }
+        if let Some((code, _)) = rule
+            .codes()
+            .into_iter()
+            .find(|(code, _)| !code.starts_with(rule.id()))
+        {
+            return Err(format!(
+                "Error: rule '{}' reports {code}, which does not start with its ID prefix {}",
+                rule.name(),
+                rule.id()
+            ));
+        }
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    pub fn rules(&self) -> &[Box<dyn Rule>] {
+        &self.rules
+    }
+
+    /// Applies `settings` to rule `name` on top of earlier ones: the rule stays disabled once
+    /// disabled, and a severity or message template replaces an earlier one.
+    pub fn configure(&mut self, name: &str, settings: RuleConfig) -> Result<(), String> {
+        let names: Vec<&str> = self
+            .rules
+            .iter()
+            .map(|rule| (rule.name(), rule.id()))
+            .chain(OTHER_RULES)
+            .map(|(name, _)| name)
+            .collect();
+        if !names.contains(&name) {
+            return Err(format!(
+                "Error: unknown rule '{name}' (known rules: {})",
+                names.join(", ")
+            ));
+        }
+        let mut codes: Vec<&str> = RULE_CODES
+            .iter()
+            .copied()
+            .chain(self.rules.iter().flat_map(|rule| rule.codes()))
+            .map(|(code, _)| code)
+            .filter(|code| self.rule_for_code(code) == Some(name))
+            .collect();
+        codes.sort();
+        codes.dedup();
+        if let Some(code) = settings
+            .messages
+            .keys()
+            .find(|code| !codes.contains(&code.as_str()))
+        {
+            return Err(format!(
+                "Error: rule '{name}' has a message for {code}, which it does not report (it reports: {})",
+                codes.join(", ")
+            ));
+        }
+
+        if settings.severity.is_some() && settings.becomes_error.is_some() {
+            return Err(format!(
+                "Error: rule '{name}' has both a severity and becomes_error; the schedule sets the \
+                 severity"
+            ));
+        }
+        if settings.patterns.is_some() && name != NAMING_RULE.0 {
+            return Err(format!(
+                "Error: rule '{name}' has no patterns setting (only {} does)",
+                NAMING_RULE.0
+            ));
+        }
+        if settings.matching.is_some() && name != TITLE_RULE.0 {
+            return Err(format!(
+                "Error: rule '{name}' has no match setting (only {} does)",
+                TITLE_RULE.0
+            ));
+        }
+        if let Some(rule) = self.rules.iter_mut().find(|rule| rule.name() == name) {
+            rule.configure(&settings)?;
+        }
+
+        let current = self.settings.entry(name.to_string()).or_default();
+        current.enabled &= settings.enabled;
+        // A later severity or schedule replaces an earlier one of either kind.
+        if settings.severity.is_some() || settings.becomes_error.is_some() {
+            current.severity = settings.severity;
+            current.becomes_error = settings.becomes_error;
+        }
+        current.messages.extend(settings.messages);
+        current.matching = settings.matching.or(current.matching.take());
+        if let Some(patterns) = settings.patterns {
+            let current = current.patterns.get_or_insert_with(NamingPatterns::default);
+            current.phase = patterns.phase.or(current.phase.take());
+            current.port = patterns.port.or(current.port.take());
+            current.error_code = patterns.error_code.or(current.error_code.take());
+        }
+        Ok(())
+    }
+
+    pub fn settings(&self, name: &str) -> Option<&RuleConfig> {
+        self.settings.get(name)
+    }
+
+    fn rule_for_code(&self, code: &str) -> Option<&'static str> {
+        self.rules
+            .iter()
+            .map(|rule| (rule.name(), rule.id()))
+            .chain(OTHER_RULES)
+            .find(|(_, id)| code.starts_with(id))
+            .map(|(name, _)| name)
+    }
+
+    /// Whether rule `name` ships with the validator rather than being registered later.
+    pub fn is_builtin(&self, name: &str) -> bool {
+        self.rules[..self.builtin]
+            .iter()
+            .any(|rule| rule.name() == name)
+    }
+
+    /// Whether rule `name` runs: as configured, or else by its default.
+    pub fn enabled(&self, name: &str) -> bool {
+        match self.settings(name) {
+            Some(settings) => settings.enabled,
+            None => self.get(name).is_none_or(|rule| rule.enabled_by_default()),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Rule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.name() == name)
+            .map(|rule| rule.as_ref())
+    }
+}
+
+/// Rule settings and identifier comparison that replace the process-wide ones while
+/// [`with_profile`] runs, so that `serve` can apply a different configuration per tenant.
+pub struct Profile {
+    registry: RwLock<RuleRegistry>,
+    identifiers: RwLock<IdentifierCase>,
+}
+
+impl Profile {
+    /// A profile with the built-in rules and defaults. Profiles live as long as the process.
+    pub fn new() -> &'static Profile {
+        Box::leak(Box::new(Profile {
+            registry: RwLock::new(RuleRegistry::standard()),
+            identifiers: RwLock::new(IdentifierCase::default()),
+        }))
+    }
+}
+
+thread_local! {
+    /// The profile [`with_profile`] activated on this thread.
+    static PROFILE: Cell<Option<&'static Profile>> = const { Cell::new(None) };
+}
+
+/// Runs `work` with `profile` in place of the process-wide registry and identifier comparison,
+/// both for configuring them and for the rules checked on this thread.
+pub fn with_profile<T>(profile: &'static Profile, work: impl FnOnce() -> T) -> T {
+    let outer = PROFILE.with(|active| active.replace(Some(profile)));
+    let result = work();
+    PROFILE.with(|active| active.set(outer));
+    result
+}
+
+/// The profile [`with_profile`] activated on this thread, to carry it over to another one.
+pub(crate) fn active_profile() -> Option<&'static Profile> {
+    PROFILE.with(Cell::get)
+}
+
+/// The rule registry in effect: the active [profile](with_profile)'s, or else the process-wide
+/// one, initialized with the built-in rules.
+pub fn registry() -> &'static RwLock<RuleRegistry> {
+    static REGISTRY: OnceLock<RwLock<RuleRegistry>> = OnceLock::new();
+    match PROFILE.with(Cell::get) {
+        Some(profile) => &profile.registry,
+        None => REGISTRY.get_or_init(|| RwLock::new(RuleRegistry::standard())),
+    }
+}
+
+/// Read access to the registered rules.
+pub fn registered() -> RwLockReadGuard<'static, RuleRegistry> {
+    registry().read().unwrap()
+}
+
+/// Findings of `rule` on `spec`, with the `phase` and `port` placeholders of message templates
+/// filled in from each finding's pointer.
+pub fn check(rule: &dyn Rule, spec: &SpecModel) -> Vec<Diagnostic> {
+    let mut diagnostics = Diagnostics::new(rule.default_severity());
+    rule.check(spec, &mut diagnostics);
+    diagnostics
+        .into_vec()
+        .into_iter()
+        .map(|diagnostic| describe(diagnostic, spec.doc))
+        .collect()
+}
+
+/// Adds the phase a finding is about (`/implementation/phase_contracts/<phase>/…` or
+/// `/algorithm/phases/<index>`) and the input or output port (`…/inputs/<index>/…`).
+fn describe(mut diagnostic: Diagnostic, doc: &JsonValue) -> Diagnostic {
+    let Some(at) = diagnostic.pointer.clone() else {
+        return diagnostic;
+    };
+    let segments: Vec<&str> = at.split('/').skip(1).collect();
+    let unescape = |segment: &str| segment.replace("~1", "/").replace("~0", "~");
+    match segments.as_slice() {
+        ["implementation", "phase_contracts", phase, rest @ ..] => {
+            diagnostic = diagnostic.with("phase", unescape(phase));
+            if let [field @ ("inputs" | "outputs"), index, ..] = rest {
+                let port = pointer(&["implementation", "phase_contracts", &unescape(phase)])
+                    + format!("/{field}/{index}/name").as_str();
+                if let Some(name) = doc.pointer(&port).and_then(|n| n.as_str()) {
+                    diagnostic = diagnostic.with("port", name);
+                }
+            }
+        }
+        ["algorithm", "phases", index, ..] => {
+            let phase = format!("/algorithm/phases/{index}");
+            if let Some(name) = doc.pointer(&phase).and_then(|n| n.as_str()) {
+                diagnostic = diagnostic.with("phase", name);
+            }
+        }
+        _ => {}
+    }
+    diagnostic
+}
+
+/// Names and ID prefixes of the rules that are not per-document rules.
+pub const OTHER_RULES: [(&str, &str); 5] = [
+    (SHARED_PHASES_RULE.0, "PV06"),
+    (LIBRARIES_RULE.0, "PV07"),
+    (SUPPRESSIONS_RULE.0, "PV09"),
+    (DUPLICATE_SPECS_RULE.0, "PV11"),
+    (METADATA_RULE.0, "PV23"),
+];
+
+/// Name and label of the rule comparing `algorithm.name` with `meta.title`.
+pub const TITLE_RULE: (&str, &str) = ("title-vs-algorithm", "meta.title vs algorithm.name");
+
+/// Name and label of the rule checking identifiers against the configured naming patterns.
+pub const NAMING_RULE: (&str, &str) = ("naming", "naming convention");
+
+/// Name and label of the cross-spec rule implemented by [`check_shared_phases`].
+pub const SHARED_PHASES_RULE: (&str, &str) = ("shared-phases", "shared phases");
+
+/// Name and label of the cross-spec rule implemented by [`check_duplicate_specs`].
+pub const DUPLICATE_SPECS_RULE: (&str, &str) = ("duplicate-specs", "duplicate specs");
+
+/// The cross-spec rules with the check implementing each, in reporting order.
+pub const CROSS_SPEC_RULES: [((&str, &str), CrossSpecCheck); 2] = [
+    (SHARED_PHASES_RULE, check_shared_phases),
+    (DUPLICATE_SPECS_RULE, check_duplicate_specs),
+];
+
+/// A check over every spec of a run, each given with the label it is reported under.
+pub type CrossSpecCheck = fn(&[(String, JsonValue)]) -> Vec<Diagnostic>;
+
+/// Name and label under which `implementation.uses` resolution problems are reported.
+pub const LIBRARIES_RULE: (&str, &str) = ("contract-libraries", "contract libraries");
+
+/// Name and label of the rule implemented by [`check_observations`], which also covers the
+/// deprecated fields found by [`crate::keywords::deprecated_fields`].
+pub const OBSERVATIONS_RULE: (&str, &str) = ("observations", "observation");
+
+/// Name and label of the `--changed-since` rule implemented by
+/// [`crate::history::metadata_findings`].
+pub const METADATA_RULE: (&str, &str) = ("metadata-bumps", "metadata bumps");
+
+/// Name and label under which malformed and unused inline suppressions are reported.
+pub const SUPPRESSIONS_RULE: (&str, &str) = ("suppressions", "suppressions");
+
+/// Names of every rule, used to validate configuration keys.
+pub fn rule_names() -> Vec<&'static str> {
+    registered()
+        .rules()
+        .iter()
+        .map(|rule| rule.name())
+        .chain(OTHER_RULES.iter().map(|(name, _)| *name))
+        .collect()
+}
+
+/// Name of the rule that reports findings with `code` (`None` for `versions check` findings).
+pub fn rule_for_code(code: &str) -> Option<&'static str> {
+    registered().rule_for_code(code)
+}
+
+/// Stable identifier and summary of every check, grouped by rule in blocks of ten.
+/// Identifiers are never reused once published; retired checks keep their slot.
+pub const RULE_CODES: &[(&str, &str)] = &[
+    (
+        "PV001",
+        "algorithm.name does not match the algorithm identity in meta.title",
+    ),
+    ("PV002", "meta.title is missing"),
+    ("PV003", "algorithm.name is missing"),
+    ("PV004", "meta.slug is missing (with match: meta-slug)"),
+    (
+        "PV005",
+        "meta.title does not match the identity pattern (with match: regex)",
+    ),
+    (
+        "PV010",
+        "phase_contracts entry for a phase the algorithm does not declare",
+    ),
+    ("PV011", "v3+ spec without implementation.phase_contracts"),
+    ("PV012", "algorithm phase without a phase_contracts entry"),
+    (
+        "PV013",
+        "duplicate input, output or error code in a phase contract",
+    ),
+    ("PV014", "reference to an unknown phase"),
+    (
+        "PV015",
+        "reference to a phase without a phase_contracts entry",
+    ),
+    ("PV016", "reference to an undeclared output port"),
+    ("PV017", "instance/global source without a path"),
+    ("PV018", "retry_policy names an undeclared error code"),
+    (
+        "PV019",
+        "malformed phase contract entry: unnamed port, error without a code, source without a kind, phase or port",
+    ),
+    (
+        "PV020",
+        "data flows to a consumer with a weaker data_classification",
+    ),
+    ("PV021", "malformed data_classification tag"),
+    ("PV030", "deterministic/idempotent is not a boolean"),
+    ("PV031", "malformed side_effects list"),
+    ("PV032", "non-idempotent phase without side_effects"),
+    ("PV033", "retry_policy on a phase marked idempotent: false"),
+    ("PV040", "retried side effects without an idempotency_key"),
+    ("PV041", "malformed idempotency_key"),
+    ("PV042", "idempotency_key is neither an input nor a path"),
+    ("PV050", "telemetry name violates the naming convention"),
+    ("PV051", "metric without a name"),
+    ("PV052", "unknown metric type"),
+    ("PV053", "metric declared more than once"),
+    ("PV054", "log event or span declared more than once"),
+    ("PV055", "alert without a declared metric"),
+    ("PV056", "non-numeric alert threshold"),
+    ("PV060", "shared phase contract differs between specs"),
+    ("PV070", "malformed implementation.uses entry"),
+    ("PV071", "contract library cannot be resolved"),
+    ("PV072", "local phase contract overrides a library contract"),
+    (
+        "PV080",
+        "version map entry points to a missing or invalid schema",
+    ),
+    ("PV081", "version map entry used by no spec"),
+    ("PV082", "spec_version missing from the version map"),
+    ("PV083", "configuration entry without effect"),
+    ("PV090", "malformed inline suppression"),
+    ("PV091", "inline suppression that matched no finding"),
+    (
+        "PV100",
+        "phase output read by no phase, only by the spec's results",
+    ),
+    ("PV101", "deprecated phase still part of the algorithm"),
+    ("PV102", "field the schema marks as deprecated"),
+    ("PV110", "spec identical to another spec of the run"),
+    (
+        "PV111",
+        "meta.title and algorithm.name pair already used by another spec",
+    ),
+    (
+        "PV120",
+        "section of the wrong type, skipped by the other rules",
+    ),
+    ("PV130", "phases that depend on each other in a cycle"),
+    (
+        "PV140",
+        "phase output consumed by no phase, algorithm output or return_contract",
+    ),
+    (
+        "PV150",
+        "required input whose chain of producers is broken further up",
+    ),
+    (
+        "PV160",
+        "phase input whose type does not accept the type of the output it reads",
+    ),
+    (
+        "PV170",
+        "phase input or output example that does not match the port's schema",
+    ),
+    ("PV180", "retry_policy.max_attempts is not an integer of at least 1"),
+    ("PV181", "unknown retry backoff strategy"),
+    (
+        "PV182",
+        "retry backoff delay that is not a non-negative integer, or initial_delay_ms above max_delay_ms",
+    ),
+    ("PV183", "retry backoff without a field its strategy needs"),
+    (
+        "PV190",
+        "phase timeouts along a dependency path add up to more than the spec's time budget",
+    ),
+    (
+        "PV191",
+        "timeout, implementation.total_timeout or meta.sla that is not a positive duration",
+    ),
+    ("PV200", "phase that is its own fallback"),
+    ("PV201", "fallback chain that leads back to where it started"),
+    (
+        "PV210",
+        "phase contract that differs from another one in only one or two fields",
+    ),
+    ("PV220", "phase name that does not match the configured pattern"),
+    (
+        "PV221",
+        "input or output name that does not match the configured pattern",
+    ),
+    ("PV222", "error code that does not match the configured pattern"),
+    (
+        "PV230",
+        "spec that changed without a new meta.version or meta.updated",
+    ),
+    ("PV231", "meta.version bumped without a change to the spec"),
+    (
+        "PV240",
+        "algorithm output neither built by a composition nor produced by the return_contract",
+    ),
+    ("PV241", "algorithm output declared more than once"),
+    (
+        "PV242",
+        "algorithm output both built by a composition and produced by the return_contract",
+    ),
+    ("PV250", "graph edge from or to a node that does not exist"),
+    ("PV251", "graph edge that repeats another one"),
+    ("PV252", "decision branch without an edge"),
+    ("PV253", "decision edge naming a branch the node does not declare"),
+    ("PV260", "graph without an entry node, or with an unknown one"),
+    ("PV261", "graph with several entry nodes and no `entry`"),
+    ("PV262", "graph without a terminal node"),
+    (
+        "PV263",
+        "path through the graph that ends without producing the return_contract",
+    ),
+    ("PV270", "graph condition that is not a valid expression"),
+    (
+        "PV271",
+        "graph condition reading a name that is no phase output, parameter or instance path",
+    ),
+];
+
+/// [`RULE_CODES`] followed by the identifiers of the rules registered on top of the built-in ones.
+pub fn rule_codes() -> Vec<(&'static str, &'static str)> {
+    let mut codes = RULE_CODES.to_vec();
+    for rule in registered().rules() {
+        for code in rule.codes() {
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+    }
+    codes
+}
+
+/// Whether a finding with `code` survives `--select`/`--ignore`. Selectors are identifiers or
+/// identifier prefixes (`PV01` covers `PV010`–`PV019`); an empty selection selects everything.
+pub fn code_selected(code: &str, select: &[String], ignore: &[String]) -> bool {
+    let matches = |selector: &String| code.starts_with(selector.as_str());
+    (select.is_empty() || select.iter().any(matches)) && !ignore.iter().any(matches)
+}
+
+/// Reports the sections that [`SpecContext`] left out because of their type, once each, rather than
+/// every rule that reads them giving up silently.
+pub fn check_structure(spec: &SpecModel, errors: &mut Diagnostics) {
+    for malformed in &spec.context.malformed {
+        errors.report(
+            "PV120",
+            format!(
+                "{} must be an {}, not {} {}; rules that read it skipped it",
+                malformed.section,
+                malformed.expected,
+                article(malformed.found),
+                malformed.found
+            ),
+            &malformed.at,
+        );
+    }
+}
+
+/// Checks the edges of `algorithm.graph`: both ends must be nodes of the graph (`PV250`), no edge
+/// may repeat another one (`PV251`), and every branch of an `if` node needs an edge leaving by it
+/// (`PV252`), as every edge leaving an `if` node by a condition needs a branch of that name
+/// (`PV253`).
+pub fn check_graph_edges(spec: &SpecModel, errors: &mut Diagnostics) {
+    let graph = &spec.context.graph;
+    let node = |id: &str| graph.nodes.iter().find(|node| node.id == id);
+    let edge_pointer = |index: usize, field: &str| {
+        pointer(&["algorithm", "graph", "edges", &index.to_string(), field])
+    };
+
+    let mut seen = HashMap::new();
+    for (index, edge) in graph.edges.iter().enumerate() {
+        for (end, id) in [("from", edge.from), ("to", edge.to)] {
+            if let Some(id) = id.filter(|id| node(id).is_none()) {
+                let suggestion = did_you_mean(id, graph.nodes.iter().map(|node| node.id));
+                errors.report(
+                    "PV250",
+                    format!("Graph edge #{index} {end} '{id}' is not a node of algorithm.graph{suggestion}"),
+                    &edge_pointer(index, end),
+                );
+            }
+        }
+        let key = (edge.from, edge.to, edge.kind, edge.condition);
+        let Some(&first) = seen.get(&key) else {
+            seen.insert(key, index);
+            continue;
+        };
+        errors.report(
+            "PV251",
+            format!(
+                "Graph edge #{index} from '{}' to '{}' repeats edge #{first}",
+                edge.from.unwrap_or_default(),
+                edge.to.unwrap_or_default()
+            ),
+            &pointer(&["algorithm", "graph", "edges", &index.to_string()]),
+        );
+    }
+
+    for decision in graph.nodes.iter().filter(|node| node.kind == Some("if")) {
+        let leaving: Vec<_> = graph
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| edge.from == Some(decision.id))
+            .collect();
+        for branch in &decision.branches {
+            if !leaving
+                .iter()
+                .any(|(_, edge)| edge.condition == Some(branch))
+            {
+                errors.report(
+                    "PV252",
+                    format!(
+                        "Decision node '{}' has no edge for its branch '{branch}'",
+                        decision.id
+                    ),
+                    &pointer(&["algorithm", "graph", "nodes", decision.id, "branches"]),
+                );
+            }
+        }
+        if decision.branches.is_empty() {
+            continue;
+        }
+        for (index, edge) in &leaving {
+            let Some(condition) = edge.condition else {
+                continue;
+            };
+            if !decision.branches.contains(&condition) {
+                let suggestion = did_you_mean(condition, decision.branches.iter().copied());
+                errors.report(
+                    "PV253",
+                    format!(
+                        "Graph edge #{index} leaves decision node '{}' by '{condition}', which is not one of its branches{suggestion}",
+                        decision.id
+                    ),
+                    &edge_pointer(*index, "condition"),
+                );
+            }
+        }
+    }
+}
+
+/// Checks where `algorithm.graph` starts and ends. The graph starts at its `entry`, which must be
+/// one of its nodes (`PV260`); without one, at its only node without incoming edges, and having no
+/// such node is `PV260` as well, having several `PV261`. It ends at its `end` nodes and at the
+/// nodes no edge leaves, and needs at least one (`PV262`). When the phase producing the
+/// `return_contract` runs in the graph, no path from the start may end without running it
+/// (`PV263`).
+pub fn check_graph_terminals(spec: &SpecModel, errors: &mut Diagnostics) {
+    let (doc, graph) = (spec.doc, &spec.context.graph);
+    if graph.nodes.is_empty() {
+        return;
+    }
+    let node_ids = || graph.nodes.iter().map(|node| node.id);
+    let known = |id: Option<&str>| node_ids().find(|&node| Some(node) == id);
+    let node_pointer = |id: &str| pointer(&["algorithm", "graph", "nodes", id]);
+
+    let start = match graph.entry {
+        Some(entry) if known(Some(entry)).is_some() => entry,
+        Some(entry) => {
+            errors.report(
+                "PV260",
+                format!(
+                    "Graph entry '{entry}' is not a node of algorithm.graph{}",
+                    did_you_mean(entry, node_ids())
+                ),
+                "/algorithm/graph/entry",
+            );
+            return;
+        }
+        None => {
+            let roots: Vec<&str> = node_ids()
+                .filter(|&id| !graph.edges.iter().any(|edge| edge.to == Some(id)))
+                .collect();
+            match roots[..] {
+                [root] => root,
+                [] => {
+                    errors.report(
+                        "PV260",
+                        "algorithm.graph has no entry: it sets no `entry` and every node has an incoming edge",
+                        "/algorithm/graph",
+                    );
+                    return;
+                }
+                _ => {
+                    errors.report(
+                        "PV261",
+                        format!(
+                            "algorithm.graph sets no `entry` and starts at {} nodes without incoming edges: {}",
+                            roots.len(),
+                            roots.join(", ")
+                        ),
+                        "/algorithm/graph",
+                    );
+                    return;
+                }
+            }
+        }
+    };
+
+    let terminal = |id: &str| {
+        graph
+            .nodes
+            .iter()
+            .any(|node| node.id == id && node.kind == Some("end"))
+            || !graph
+                .edges
+                .iter()
+                .any(|edge| edge.from == Some(id) && known(edge.to).is_some())
+    };
+    if !node_ids().any(terminal) {
+        errors.report(
+            "PV262",
+            "algorithm.graph has no terminal node: no `end` node, and an edge leaves every node",
+            "/algorithm/graph",
+        );
+        return;
+    }
+
+    let Some(producer) = doc
+        .pointer("/implementation/return_contract/produced_by/phase")
+        .and_then(|p| p.as_str())
+    else {
+        return;
+    };
+    let produces = |id: &str| {
+        graph
+            .nodes
+            .iter()
+            .any(|node| node.id == id && node.phase.is_some_and(|p| same_ident(p, producer)))
+    };
+    if !node_ids().any(produces) {
+        return;
+    }
+    // The nodes reachable from the start without running the producer, in the order found.
+    let mut reached = vec![start];
+    let mut next = 0;
+    while next < reached.len() {
+        let at = reached[next];
+        next += 1;
+        if produces(at) {
+            continue;
+        }
+        for edge in graph.edges.iter().filter(|edge| edge.from == Some(at)) {
+            if let Some(to) = known(edge.to) {
+                if !reached.contains(&to) {
+                    reached.push(to);
+                }
+            }
+        }
+    }
+    for id in reached {
+        if terminal(id) && !produces(id) {
+            errors.report(
+                "PV263",
+                format!(
+                    "Graph node '{id}' ends the algorithm on a path from '{start}' that never runs phase '{producer}', which produces the return_contract"
+                ),
+                &node_pointer(id),
+            );
+        }
+    }
+}
+
+/// Checks the conditions of `algorithm.graph` — the `condition` and `until` of nodes, and the
+/// `condition` of edges that do not leave by a decision branch — as
+/// [expressions](crate::conditions). A condition that does not parse is `PV270`. When every phase
+/// contract lists its outputs, a condition reading a name that is neither a phase output
+/// (`output`, `phase.output`), a parameter in `implementation.parameters` nor an instance path
+/// (`$.field`) is `PV271`. Conditions written in natural language are not checked.
+pub fn check_graph_conditions(spec: &SpecModel, errors: &mut Diagnostics) {
+    let (doc, context) = (spec.doc, &spec.context);
+    let graph = &context.graph;
+    let mut checked = Vec::new();
+    for node in &graph.nodes {
+        // A loop's `until` may read the number of the current iteration.
+        let fields: [(&str, &str, _, &[&str]); 2] = [
+            ("condition", "condition", node.condition, &[]),
+            ("until", "until condition", node.until, &["iteration"]),
+        ];
+        for (field, noun, text, locals) in fields {
+            if let Some(text) = text {
+                checked.push((
+                    format!("The {noun} of graph node '{}'", node.id),
+                    text,
+                    locals,
+                    pointer(&["algorithm", "graph", "nodes", node.id, field]),
+                ));
+            }
+        }
+    }
+    for (index, edge) in graph.edges.iter().enumerate() {
+        let by_branch = graph
+            .nodes
+            .iter()
+            .any(|node| Some(node.id) == edge.from && !node.branches.is_empty());
+        if let (Some(text), false) = (edge.condition, by_branch) {
+            checked.push((
+                format!("The condition of graph edge #{index}"),
+                text,
+                &[],
+                pointer(&[
+                    "algorithm",
+                    "graph",
+                    "edges",
+                    &index.to_string(),
+                    "condition",
+                ]),
+            ));
+        }
+    }
+
+    let parameters: Vec<&str> = doc
+        .pointer("/implementation/parameters")
+        .and_then(|p| p.as_object())
+        .into_iter()
+        .flat_map(|p| p.keys().map(String::as_str))
+        .collect();
+    let resolvable =
+        !context.contracts.is_empty() && context.contracts.iter().all(|c| c.outputs_known);
+    let outputs = || {
+        context
+            .contracts
+            .iter()
+            .flat_map(|contract| contract.outputs.iter().map(|output| output.name))
+    };
+    for (subject, text, locals, at) in checked {
+        if conditions::is_prose(text) {
+            continue;
+        }
+        let names = match conditions::names(text) {
+            Ok(names) => names,
+            Err(e) => {
+                errors.report(
+                    "PV270",
+                    format!("{subject} is not a valid expression: {e} in '{text}'"),
+                    &at,
+                );
+                continue;
+            }
+        };
+        if !resolvable {
+            continue;
+        }
+        for name in names {
+            let first = name[0].as_str();
+            if first == "$"
+                || locals.contains(&first)
+                || parameters.iter().any(|p| same_ident(p, first))
+                || outputs().any(|output| same_ident(output, first))
+            {
+                continue;
+            }
+            let is_phase = context.phase(first).is_some() || context.contract(first).is_some();
+            let message = match (is_phase, name.get(1)) {
+                (true, Some(port)) if context.output(first, port).is_some() => continue,
+                (true, Some(port)) => format!(
+                    "{subject} reads '{port}', which phase '{first}' does not output{}",
+                    context.output_suggestion(first, port)
+                ),
+                (true, None) => {
+                    format!("{subject} reads phase '{first}' instead of one of its outputs")
+                }
+                (false, _) => {
+                    let suggestion = did_you_mean(
+                        first,
+                        outputs()
+                            .chain(parameters.iter().copied())
+                            .chain(context.phases.iter().map(|phase| phase.name)),
+                    );
+                    format!(
+                        "{subject} reads '{first}', which is neither a phase output, a parameter nor an instance path{suggestion}"
+                    )
+                }
+            };
+            errors.report("PV271", message, &at);
+        }
+    }
+}
+
+fn article(word: &str) -> &'static str {
+    if word.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "an"
+    } else {
+        "a"
+    }
+}
+
+pub fn check_phase_contracts(spec: &SpecModel, errors: &mut Diagnostics) {
+    let (doc, context) = (spec.doc, &spec.context);
+    let needs_contracts = context.needs_contracts();
+
+    if context.phases.is_empty() {
+        return;
+    }
+
+    let missing = |errors: &mut Diagnostics, at: &str| {
+        if needs_contracts {
+            errors.push(
+                Diagnostic::error(
+                    "PV011",
+                    "implementation.phase_contracts must be present for v3+ specs",
+                )
+                .at(at),
+            );
+        }
+    };
+    // Sections of the wrong type are reported by `check_structure`.
+    let implementation = match doc.get("implementation") {
+        Some(value) if value.is_object() => value,
+        Some(_) => return,
+        None => return missing(errors, ""),
+    };
+    match implementation.get("phase_contracts") {
+        Some(value) if value.is_object() => {}
+        Some(_) => return,
+        None => return missing(errors, "/implementation"),
+    }
+
+    if needs_contracts {
+        for phase in &context.phases {
+            if context.contract(phase.name).is_none() {
+                errors.push(
+                    Diagnostic::error(
+                        "PV012",
+                        format!(
+                            "Missing phase_contracts entry for algorithm phase '{}'",
+                            phase.name
+                        ),
+                    )
+                    .at("/implementation/phase_contracts"),
+                );
+            }
+        }
+    }
+
+    // Pre-v3 specs treat phase_contracts as optional documentation, so stray entries only warn.
+    let unknown_phase_severity = if needs_contracts {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+    for contract in &context.contracts {
+        let phase_name = contract.phase;
+        if context.phase(phase_name).is_none() {
+            errors.push(
+                Diagnostic::new(
+                    "PV010",
+                    unknown_phase_severity,
+                    format!(
+                        "phase_contracts contains unknown phase '{phase_name}' (not listed in \
+                         algorithm.phases){}",
+                        context.phase_suggestion(phase_name)
+                    ),
+                )
+                .at(contract_pointer(phase_name, &[])),
+            );
+        }
+    }
+
+    let mut phase_error_codes: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for contract in &context.contracts {
+        let phase_name = contract.phase;
+        let Some(contract_obj) = contract.value.as_object() else {
+            continue;
+        };
+        for (field, noun) in [("inputs", "input"), ("outputs", "output")] {
+            let items = contract_obj.get(field).and_then(|v| v.as_array());
+            for (index, item) in items.into_iter().flatten().enumerate() {
+                if item.get("name").and_then(|n| n.as_str()).is_none() {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV019",
+                            format!("Phase '{phase_name}' {noun} #{index} has no name"),
+                        )
+                        .at(contract_pointer(phase_name, &[field, &index.to_string()])),
+                    );
+                }
+            }
+        }
+        let mut seen_outputs = HashSet::new();
+        for output in &contract.outputs {
+            if !seen_outputs.insert(ident(output.name)) {
+                errors.push(
+                    Diagnostic::error(
+                        "PV013",
+                        format!(
+                            "Phase '{phase_name}' defines duplicate output '{}'",
+                            output.name
+                        ),
+                    )
+                    .at(contract_pointer(phase_name, &["outputs"])),
+                );
+            }
+        }
+        if let Some(errors_array) = contract_obj.get("errors").and_then(|v| v.as_array()) {
+            let mut seen_codes = HashSet::new();
+            for (index, error_value) in errors_array.iter().enumerate() {
+                let Some(code) = error_value.get("code").and_then(|c| c.as_str()) else {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV019",
+                            format!("Phase '{phase_name}' error #{index} has no code"),
+                        )
+                        .at(contract_pointer(
+                            phase_name,
+                            &["errors", &index.to_string()],
+                        )),
+                    );
+                    continue;
+                };
+                if !seen_codes.insert(code.to_string()) {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV013",
+                            format!("Phase '{phase_name}' declares duplicate error code '{code}'",),
+                        )
+                        .at(contract_pointer(phase_name, &["errors"])),
+                    );
+                }
+            }
+            if !seen_codes.is_empty() {
+                phase_error_codes.insert(ident(phase_name).into_owned(), seen_codes);
+            }
+        }
+    }
+
+    for contract in &context.contracts {
+        let phase_name = contract.phase;
+        let Some(contract_obj) = contract.value.as_object() else {
+            continue;
+        };
+
+        let mut seen_inputs = HashSet::new();
+        for input in &contract.inputs {
+            let input_name = input.name;
+            if !seen_inputs.insert(ident(input_name)) {
+                errors.push(
+                    Diagnostic::error(
+                        "PV013",
+                        format!("Phase '{phase_name}' declares duplicate input '{input_name}'",),
+                    )
+                    .at(contract_pointer(phase_name, &["inputs"])),
+                );
+            }
+
+            if needs_contracts && input.value.get("source").is_none() {
+                errors.push(
+                    Diagnostic::error(
+                        "PV019",
+                        format!("Phase '{phase_name}' input '{input_name}' has no source"),
+                    )
+                    .at(contract_pointer(
+                        phase_name,
+                        &["inputs", &input.index.to_string()],
+                    )),
+                );
+            }
+            if let Some(source_value) = input.value.get("source") {
+                let location =
+                    contract_pointer(phase_name, &["inputs", &input.index.to_string(), "source"]);
+                validate_io_source(
+                    source_value,
+                    Some((phase_name, input_name)),
+                    None,
+                    context,
+                    |code, msg| errors.push(Diagnostic::error(code, msg).at(location.clone())),
+                );
+            }
+        }
+
+        let malformed = |errors: &mut Diagnostics, what: &str, at: &[&str]| {
+            errors.push(
+                Diagnostic::error("PV019", format!("Phase '{phase_name}' {what}"))
+                    .at(contract_pointer(phase_name, at)),
+            )
+        };
+        match contract_obj.get("retry_policy") {
+            Some(value) if !value.is_object() => {
+                malformed(errors, "retry_policy must be an object", &["retry_policy"])
+            }
+            Some(JsonValue::Object(retry_policy)) => {
+                match retry_policy.get("retryable_errors") {
+                    Some(value) if !value.is_array() => malformed(
+                        errors,
+                        "retry_policy.retryable_errors must be a list of error codes",
+                        &["retry_policy", "retryable_errors"],
+                    ),
+                    Some(JsonValue::Array(retryable_errors)) => {
+                        let declared_codes = phase_error_codes.get(ident(phase_name).as_ref());
+                        for (index, code_value) in retryable_errors.iter().enumerate() {
+                            let Some(code) = code_value.as_str() else {
+                                malformed(
+                                errors,
+                                &format!("retry_policy.retryable_errors #{index} is not an error code"),
+                                &["retry_policy", "retryable_errors", &index.to_string()],
+                            );
+                                continue;
+                            };
+                            if let Some(codes) = declared_codes {
+                                if !codes.contains(code) {
+                                    errors.push(Diagnostic::error("PV018", format!(
+                                    "Phase '{phase_name}' retry_policy references unknown error code '{code}'{}",
+                                    did_you_mean(code, codes.iter().map(String::as_str))
+                                )).at(contract_pointer(phase_name, &["retry_policy", "retryable_errors"])));
+                                }
+                            } else {
+                                errors.push(Diagnostic::error("PV018", format!(
+                                "Phase '{phase_name}' retry_policy declares retryable error '{code}' but no errors block is defined",
+                            )).at(contract_pointer(phase_name, &["retry_policy", "retryable_errors"])));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+
+        if contract_obj.get("fallback").is_some_and(|v| !v.is_object()) {
+            malformed(errors, "fallback must be an object", &["fallback"]);
+        }
+        if let Some(fallback) = contract_obj.get("fallback").and_then(|v| v.as_object()) {
+            if let Some(fallback_phase) = fallback.get("phase").and_then(|p| p.as_str()) {
+                if context.phase(fallback_phase).is_none() {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV014",
+                            format!(
+                        "Phase '{phase_name}' fallback references unknown phase '{fallback_phase}'{}",
+                        context.phase_suggestion(fallback_phase)
+                    ),
+                        )
+                        .at(contract_pointer(phase_name, &["fallback", "phase"])),
+                    );
+                } else if context.contract(fallback_phase).is_none() {
+                    errors.push(Diagnostic::error("PV015", format!(
+                        "Phase '{phase_name}' fallback references phase '{fallback_phase}' but it has no phase_contracts entry",
+                    )).at(contract_pointer(phase_name, &["fallback", "phase"])));
+                }
+            }
+        }
+    }
+
+    if let Some(outputs) = doc.pointer("/algorithm/outputs").and_then(|v| v.as_array()) {
+        for (index, output) in outputs.iter().enumerate() {
+            if let Some(build) = output.get("build") {
+                let location = pointer(&["algorithm", "outputs", &index.to_string(), "build"]);
+                let mut sources = Vec::new();
+                collect_io_sources(build, &mut sources);
+                let output_name = output
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("<composition>");
+                for source in sources {
+                    validate_io_source(source, None, Some(output_name), context, |code, msg| {
+                        errors.push(Diagnostic::error(code, msg).at(location.clone()))
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(return_contract) = implementation
+        .get("return_contract")
+        .and_then(|v| v.as_object())
+    {
+        if let Some(produced_by) = return_contract
+            .get("produced_by")
+            .and_then(|v| v.as_object())
+        {
+            let phase = produced_by
+                .get("phase")
+                .and_then(|p| p.as_str())
+                .unwrap_or_default();
+
+            if !phase.is_empty() {
+                if context.phase(phase).is_none() {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV014",
+                            format!(
+                                "return_contract.produced_by references unknown phase '{phase}'{}",
+                                context.phase_suggestion(phase)
+                            ),
+                        )
+                        .at("/implementation/return_contract/produced_by/phase"),
+                    );
+                } else if context.contract(phase).is_none() {
+                    errors.push(Diagnostic::error("PV015", format!(
+                        "return_contract.produced_by references phase '{phase}' but it has no phase_contracts entry",
+                    )).at("/implementation/return_contract/produced_by/phase"));
+                } else if let Some(port) = produced_by.get("port").and_then(|p| p.as_str()) {
+                    if context.undeclared_output(phase, port) {
+                        errors.push(Diagnostic::error("PV016", format!(
+                            "return_contract.produced_by references output '{port}' from phase '{phase}' which is not declared{}",
+                            context.output_suggestion(phase, port)
+                        )).at("/implementation/return_contract/produced_by/port"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rejects phases that depend on each other in a cycle through `algorithm.graph` edges and
+/// `phase_output` reads, which never completes at runtime. Reports the shortest cycle through the
+/// first phase of each group of mutually dependent phases.
+pub fn check_cycles(spec: &SpecModel, errors: &mut Diagnostics) {
+    let context = &spec.context;
+    let graph = context.dependencies();
+    for component in graph.components() {
+        let Some(cycle) = graph.shortest_cycle(&component) else {
+            continue;
+        };
+        let at = match cycle[0].2 {
+            Dependency::Edge(index) => {
+                pointer(&["algorithm", "graph", "edges", &index.to_string()])
+            }
+            Dependency::Dataflow(index) => {
+                let flow = &context.dataflow[index];
+                let input = context
+                    .contract(flow.consumer)
+                    .and_then(|contract| contract.input(flow.input))
+                    .map_or(0, |input| input.index);
+                contract_pointer(flow.consumer, &["inputs", &input.to_string(), "source"])
+            }
+        };
+        errors.report("PV130", describe_cycle(context, &graph, &cycle), &at);
+    }
+}
+
+/// The message reporting `cycle` of the [dependency graph](SpecContext::dependencies): the path
+/// and the reason for every step.
+pub fn describe_cycle(
+    context: &SpecContext,
+    graph: &DependencyGraph,
+    cycle: &[(usize, usize, Dependency)],
+) -> String {
+    let name = |vertex: usize| match graph.vertices[vertex] {
+        Vertex::Phase(phase) => phase.to_string(),
+        Vertex::Node(id, kind) => format!("{id} ({})", kind.unwrap_or("node")),
+    };
+    let mut path = vec![name(cycle[0].0)];
+    let mut reasons = Vec::new();
+    for &(from, to, dependency) in cycle {
+        path.push(name(to));
+        let reason = match dependency {
+            Dependency::Edge(index) => format!("algorithm.graph edge #{index}"),
+            Dependency::Dataflow(index) => {
+                let flow = &context.dataflow[index];
+                match flow.port {
+                    Some(port) => format!("input '{}' reads output '{port}'", flow.input),
+                    None => format!("input '{}' reads its output", flow.input),
+                }
+            }
+        };
+        reasons.push(format!("{} → {}: {reason}", name(from), name(to)));
+    }
+    format!(
+        "Phases depend on each other in a cycle: {} ({})",
+        path.join(" → "),
+        reasons.join("; ")
+    )
+}
+
+/// Ordered data handling tiers accepted in `data_classification` tags (least to most sensitive).
+const DATA_CLASSIFICATIONS: [&str; 3] = ["public", "internal", "pii"];
+
+/// Clearance assumed for consumers without a tag: anything up to `internal`, never pii.
+const UNTAGGED_CLEARANCE: usize = 1;
+
+/// Describes a data downgrade when `rank`-tagged data reaches a consumer with `clearance`.
+fn classification_downgrade(rank: usize, clearance: Option<usize>) -> Option<String> {
+    if rank <= clearance.unwrap_or(UNTAGGED_CLEARANCE) {
+        return None;
+    }
+    Some(format!(
+        "{} data but is only cleared for {}",
+        DATA_CLASSIFICATIONS[rank],
+        match clearance {
+            Some(c) => DATA_CLASSIFICATIONS[c],
+            None => "untagged (at most internal) data",
+        }
+    ))
+}
+
+/// Reads the optional `data_classification` tag of `value` (found at `at`), reporting malformed
+/// labels.
+fn read_classification(
+    value: &JsonValue,
+    location: String,
+    at: String,
+    errors: &mut Diagnostics,
+) -> Option<usize> {
+    let label = value.get("data_classification")?;
+    let Some(label) = label.as_str() else {
+        errors.push(
+            Diagnostic::error(
+                "PV021",
+                format!("{location} declares a non-string data_classification"),
+            )
+            .at(format!("{at}/data_classification")),
+        );
+        return None;
+    };
+    let rank = DATA_CLASSIFICATIONS.iter().position(|c| *c == label);
+    if rank.is_none() {
+        errors.push(
+            Diagnostic::error(
+                "PV021",
+                format!(
+                "{location} declares unknown data_classification '{label}' (expected one of: {})",
+                DATA_CLASSIFICATIONS.join(", ")
+            ),
+            )
+            .at(format!("{at}/data_classification")),
+        );
+    }
+    rank
+}
+
+/// Follows `phase_output` sources and reports places where data tagged with a stricter
+/// `data_classification` flows into a consumer that is only cleared for a weaker one.
+pub fn check_data_classification(spec: &SpecModel, errors: &mut Diagnostics) {
+    let (doc, context) = (spec.doc, &spec.context);
+
+    if context.contracts.is_empty() {
+        return;
+    }
+
+    // Classification of every declared output port, plus the phase-level clearance.
+    let mut output_tags: HashMap<(String, String), usize> = HashMap::new();
+    let mut phase_clearance: HashMap<String, usize> = HashMap::new();
+    for contract in &context.contracts {
+        let phase_name = contract.phase;
+        if let Some(rank) = read_classification(
+            contract.value,
+            format!("Phase '{phase_name}'"),
+            contract_pointer(phase_name, &[]),
+            errors,
+        ) {
+            phase_clearance.insert(ident(phase_name).into_owned(), rank);
+        }
+        for output in &contract.outputs {
+            let port = output.name;
+            if let Some(rank) = read_classification(
+                output.value,
+                format!("Phase '{phase_name}' output '{port}'"),
+                contract_pointer(phase_name, &["outputs", &output.index.to_string()]),
+                errors,
+            ) {
+                output_tags.insert(
+                    (ident(phase_name).into_owned(), ident(port).into_owned()),
+                    rank,
+                );
+            }
+        }
+    }
+
+    // `source` is a phase_output reference or a return_contract.produced_by block.
+    let produced_tag = |source: &JsonValue| -> Option<(String, String, usize)> {
+        let phase = source.get("phase").and_then(|p| p.as_str())?;
+        let port = source.get("port").and_then(|p| p.as_str())?;
+        let rank = *output_tags.get(&(ident(phase).into_owned(), ident(port).into_owned()))?;
+        Some((phase.to_string(), port.to_string(), rank))
+    };
+    let is_phase_output =
+        |source: &&JsonValue| source.get("kind").and_then(|k| k.as_str()) == Some("phase_output");
+
+    for contract in &context.contracts {
+        let phase_name = contract.phase;
+        for input in &contract.inputs {
+            let input_name = input.name;
+            let location = contract_pointer(phase_name, &["inputs", &input.index.to_string()]);
+            let declared = read_classification(
+                input.value,
+                format!("Phase '{phase_name}' input '{input_name}'"),
+                location.clone(),
+                errors,
+            );
+            let Some((producer, port, rank)) = input
+                .value
+                .get("source")
+                .filter(is_phase_output)
+                .and_then(produced_tag)
+            else {
+                continue;
+            };
+            let clearance = declared.max(phase_clearance.get(ident(phase_name).as_ref()).copied());
+            if let Some(detail) = classification_downgrade(rank, clearance) {
+                errors.push(Diagnostic::error("PV020", format!(
+                    "Phase '{phase_name}' input '{input_name}' receives '{producer}.{port}' {detail}",
+                )).at(location));
+            }
+        }
+    }
+
+    if let Some(outputs) = doc
+        .get("algorithm")
+        .and_then(|a| a.get("outputs"))
+        .and_then(|v| v.as_array())
+    {
+        for (index, output) in outputs.iter().enumerate() {
+            let output_name = output
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("<composition>");
+            let location = pointer(&["algorithm", "outputs", &index.to_string()]);
+            let declared = read_classification(
+                output,
+                format!("Composition '{output_name}'"),
+                location.clone(),
+                errors,
+            );
+            let Some(build) = output.get("build") else {
+                continue;
+            };
+            let mut sources = Vec::new();
+            collect_io_sources(build, &mut sources);
+            for (producer, port, rank) in sources
+                .into_iter()
+                .filter(is_phase_output)
+                .filter_map(produced_tag)
+            {
+                if let Some(detail) = classification_downgrade(rank, declared) {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV020",
+                            format!(
+                                "Composition '{output_name}' exposes '{producer}.{port}' {detail}",
+                            ),
+                        )
+                        .at(location.clone()),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(return_contract) = doc
+        .get("implementation")
+        .and_then(|i| i.get("return_contract"))
+    {
+        let declared = read_classification(
+            return_contract,
+            "return_contract".to_string(),
+            "/implementation/return_contract".to_string(),
+            errors,
+        );
+        if let Some((producer, port, rank)) =
+            return_contract.get("produced_by").and_then(produced_tag)
+        {
+            if let Some(detail) = classification_downgrade(rank, declared) {
+                errors.push(
+                    Diagnostic::error(
+                        "PV020",
+                        format!("return_contract returns '{producer}.{port}' {detail}",),
+                    )
+                    .at("/implementation/return_contract"),
+                );
+            }
+        }
+    }
+}
+
+/// Validates the `deterministic`, `side_effects` and `idempotent` annotations of phase contracts
+/// and rejects retry policies on phases explicitly declared non-idempotent.
+pub fn check_phase_purity(spec: &SpecModel, errors: &mut Diagnostics) {
+    for contract in &spec.context.contracts {
+        let phase_name = contract.phase;
+        let Some(contract_obj) = contract.value.as_object() else {
+            continue;
+        };
+
+        for flag in ["deterministic", "idempotent"] {
+            if let Some(value) = contract_obj.get(flag) {
+                if !value.is_boolean() {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV030",
+                            format!("Phase '{phase_name}' {flag} must be a boolean"),
+                        )
+                        .at(contract_pointer(phase_name, &[flag])),
+                    );
+                }
+            }
+        }
+
+        let mut side_effects = Vec::new();
+        match contract_obj.get("side_effects") {
+            None => {}
+            Some(JsonValue::Array(items)) => {
+                let mut seen = HashSet::new();
+                for item in items {
+                    match item.as_str() {
+                        Some(effect) if !effect.trim().is_empty() => {
+                            if !seen.insert(effect) {
+                                errors.push(Diagnostic::error("PV031", format!(
+                                    "Phase '{phase_name}' lists side effect '{effect}' more than once",
+                                )).at(contract_pointer(phase_name, &["side_effects"])));
+                            }
+                            side_effects.push(effect);
+                        }
+                        _ => errors.push(
+                            Diagnostic::error(
+                                "PV031",
+                                format!(
+                            "Phase '{phase_name}' side_effects entries must be non-empty strings",
+                        ),
+                            )
+                            .at(contract_pointer(phase_name, &["side_effects"])),
+                        ),
+                    }
+                }
+            }
+            Some(_) => errors.push(
+                Diagnostic::error(
+                    "PV031",
+                    format!("Phase '{phase_name}' side_effects must be a list of strings"),
+                )
+                .at(contract_pointer(phase_name, &["side_effects"])),
+            ),
+        }
+
+        let idempotent = contract_obj.get("idempotent").and_then(|v| v.as_bool());
+
+        if idempotent == Some(false) && side_effects.is_empty() {
+            errors.push(
+                Diagnostic::warning(
+                    "PV032",
+                    format!(
+                        "Phase '{phase_name}' is declared non-idempotent but lists no side_effects",
+                    ),
+                )
+                .at(contract_pointer(phase_name, &["idempotent"])),
+            );
+        }
+
+        // Side-effecting phases with a retry policy are covered by check_idempotency_keys.
+        if idempotent == Some(false) && contract_obj.contains_key("retry_policy") {
+            errors.push(Diagnostic::error("PV033", format!(
+                "Phase '{phase_name}' declares a retry_policy but is explicitly marked idempotent: false",
+            )).at(contract_pointer(phase_name, &["retry_policy"])));
+        }
+    }
+}
+
+/// Backoff strategies of `retry_policy.backoff` and the fields each one needs: the delay of the first
+/// retry, and for exponential growth a cap.
+pub const BACKOFF_STRATEGIES: [(&str, &[&str]); 4] = [
+    ("fixed", &["initial_delay_ms"]),
+    ("linear", &["initial_delay_ms"]),
+    ("exponential", &["initial_delay_ms", "max_delay_ms"]),
+    ("jittered", &["initial_delay_ms"]),
+];
+
+/// Backoff fields in seconds that early specs used, and the fields in milliseconds replacing them.
+const RENAMED_DELAYS: [(&str, &str); 2] = [
+    ("min_seconds", "initial_delay_ms"),
+    ("max_seconds", "max_delay_ms"),
+];
+
+/// Checks what the schema versions before the retry fields cannot: `max_attempts` of at least 1, a
+/// known backoff `strategy` with the delays it needs, and delays that are non-negative integers with
+/// `initial_delay_ms` no greater than `max_delay_ms`. Retry policies that are not objects are
+/// `PV019`.
+pub fn check_retry_policies(spec: &SpecModel, errors: &mut Diagnostics) {
+    for contract in &spec.context.contracts {
+        let phase = contract.phase;
+        let Some(policy) = contract
+            .value
+            .get("retry_policy")
+            .and_then(|p| p.as_object())
+        else {
+            continue;
+        };
+        if let Some(attempts) = policy.get("max_attempts") {
+            if attempts.as_u64().is_none_or(|n| n < 1) {
+                errors.report(
+                    "PV180",
+                    format!(
+                        "Phase '{phase}' retry_policy.max_attempts is {attempts}; expected an \
+                         integer of at least 1"
+                    ),
+                    &contract_pointer(phase, &["retry_policy", "max_attempts"]),
+                );
+            }
+        }
+        let Some(backoff) = policy.get("backoff").and_then(|b| b.as_object()) else {
+            continue;
+        };
+        let at = |field: &str| contract_pointer(phase, &["retry_policy", "backoff", field]);
+        for field in ["initial_delay_ms", "max_delay_ms"] {
+            let Some(value) = backoff.get(field) else {
+                continue;
+            };
+            if value.as_u64().is_none() {
+                errors.report(
+                    "PV182",
+                    format!(
+                        "Phase '{phase}' retry backoff {field} is {value}; expected a non-negative \
+                         integer"
+                    ),
+                    &at(field),
+                );
+            }
+        }
+        if let (Some(initial), Some(max)) = (
+            backoff.get("initial_delay_ms").and_then(|d| d.as_u64()),
+            backoff.get("max_delay_ms").and_then(|d| d.as_u64()),
+        ) {
+            if initial > max {
+                errors.report(
+                    "PV182",
+                    format!(
+                        "Phase '{phase}' retry backoff starts at initial_delay_ms {initial}, above \
+                         its max_delay_ms {max}"
+                    ),
+                    &at("initial_delay_ms"),
+                );
+            }
+        }
+        let Some(strategy) = backoff.get("strategy") else {
+            errors.report(
+                "PV183",
+                format!("Phase '{phase}' retry backoff has no strategy"),
+                &contract_pointer(phase, &["retry_policy", "backoff"]),
+            );
+            continue;
+        };
+        let known = BACKOFF_STRATEGIES
+            .iter()
+            .find(|(name, _)| Some(*name) == strategy.as_str());
+        let Some((name, needed)) = known else {
+            let names: Vec<&str> = BACKOFF_STRATEGIES.iter().map(|(name, _)| *name).collect();
+            errors.report(
+                "PV181",
+                format!(
+                    "Phase '{phase}' retry backoff strategy {strategy} is unknown (expected one of: \
+                     {})",
+                    names.join(", ")
+                ),
+                &at("strategy"),
+            );
+            continue;
+        };
+        let missing: Vec<&str> = needed
+            .iter()
+            .copied()
+            .filter(|field| !backoff.contains_key(*field))
+            .collect();
+        if !missing.is_empty() {
+            let renamed: Vec<String> = RENAMED_DELAYS
+                .iter()
+                .filter(|(old, new)| backoff.contains_key(*old) && missing.contains(new))
+                .map(|(old, new)| format!("{old} is now {new}, in milliseconds"))
+                .collect();
+            let hint = if renamed.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", renamed.join("; "))
+            };
+            errors.report(
+                "PV183",
+                format!(
+                    "Phase '{phase}' retry backoff strategy '{name}' needs {}{hint}",
+                    missing.join(" and ")
+                ),
+                &contract_pointer(phase, &["retry_policy", "backoff"]),
+            );
+        }
+    }
+}
+
+/// Follows the `fallback.phase` chains of the phase contracts: a phase must not fall back to itself,
+/// directly (`PV200`) or through other phases (`PV201`). Every cycle is reported once, at the phase
+/// of the cycle whose contract comes first. Unknown fallback phases are `PV014`.
+pub fn check_fallback_chains(spec: &SpecModel, errors: &mut Diagnostics) {
+    let context = &spec.context;
+    let fallback = |phase: &str| {
+        let target = context
+            .contract(phase)?
+            .value
+            .pointer("/fallback/phase")?
+            .as_str()?;
+        Some(context.contract(target)?.phase)
+    };
+    let position = |phase: &str| context.contracts.iter().position(|c| c.phase == phase);
+    for contract in &context.contracts {
+        let start = contract.phase;
+        let mut chain = vec![start];
+        let mut at = start;
+        while let Some(next) = fallback(at) {
+            if next == start || chain.contains(&next) {
+                chain.push(next);
+                break;
+            }
+            chain.push(next);
+            at = next;
+        }
+        if chain.len() < 2 || chain.last() != Some(&start) {
+            continue;
+        }
+        let first = chain.iter().filter_map(|phase| position(phase)).min();
+        if first != position(start) {
+            continue;
+        }
+        let at = contract_pointer(start, &["fallback", "phase"]);
+        if chain.len() == 2 {
+            errors.report(
+                "PV200",
+                format!("Phase '{start}' falls back to itself"),
+                &at,
+            );
+        } else {
+            let path: Vec<String> = chain.iter().map(|phase| format!("'{phase}'")).collect();
+            errors.report(
+                "PV201",
+                format!(
+                    "Phase '{start}' falls back to itself through {}",
+                    path.join(" → ")
+                ),
+                &at,
+            );
+        }
+    }
+}
+
+/// The parts of phase contracts `contract-drift` compares.
+const DRIFT_FIELDS: [&str; 3] = ["inputs", "outputs", "errors"];
+
+/// Flags pairs of phase contracts whose inputs, outputs and errors are the same except for one or
+/// two values, a telltale of a contract copied from another and edited incompletely. Pairs must
+/// share at least four values, so that small contracts do not all look alike. Opt-in.
+pub fn check_contract_drift(spec: &SpecModel, notes: &mut Diagnostics) {
+    let contracts: Vec<(&str, BTreeMap<String, &JsonValue>)> = spec
+        .context
+        .contracts
+        .iter()
+        .map(|contract| {
+            let mut leaves = BTreeMap::new();
+            for field in DRIFT_FIELDS {
+                if let Some(value) = contract.value.get(field) {
+                    collect_leaves(value, pointer(&[field]), &mut leaves);
+                }
+            }
+            (contract.phase, leaves)
+        })
+        .collect();
+    for (index, (phase, leaves)) in contracts.iter().enumerate() {
+        for (earlier, earlier_leaves) in &contracts[..index] {
+            let differing: BTreeSet<&String> = leaves
+                .keys()
+                .chain(earlier_leaves.keys())
+                .filter(|at| leaves.get(*at) != earlier_leaves.get(*at))
+                .collect();
+            let shared = leaves
+                .iter()
+                .filter(|(at, value)| earlier_leaves.get(*at) == Some(value))
+                .count();
+            if differing.is_empty() || differing.len() > 2 || shared < 4 {
+                continue;
+            }
+            let shown =
+                |value: Option<&&JsonValue>| value.map_or("nothing".to_string(), |v| v.to_string());
+            let differences: Vec<String> = differing
+                .iter()
+                .map(|at| {
+                    format!(
+                        "{} ({} here, {} in '{earlier}')",
+                        at.trim_start_matches('/'),
+                        shown(leaves.get(*at)),
+                        shown(earlier_leaves.get(*at))
+                    )
+                })
+                .collect();
+            let first = differing.first().map_or("", |at| at.as_str());
+            let at = if leaves.contains_key(first) {
+                format!("{}{first}", contract_pointer(phase, &[]))
+            } else {
+                contract_pointer(phase, &[])
+            };
+            notes.report(
+                "PV210",
+                format!(
+                    "Phase contract '{phase}' is a near-copy of '{earlier}', differing only in {}",
+                    differences.join(" and ")
+                ),
+                &at,
+            );
+        }
+    }
+}
+
+/// The scalar values under `value` by their JSON Pointer, prefixed with `at`. Empty objects and
+/// lists count as values.
+fn collect_leaves<'v>(
+    value: &'v JsonValue,
+    at: String,
+    leaves: &mut BTreeMap<String, &'v JsonValue>,
+) {
+    match value {
+        JsonValue::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                collect_leaves(
+                    item,
+                    format!("{at}/{}", key.replace('~', "~0").replace('/', "~1")),
+                    leaves,
+                );
+            }
+        }
+        JsonValue::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                collect_leaves(item, format!("{at}/{index}"), leaves);
+            }
+        }
+        _ => {
+            leaves.insert(at, value);
+        }
+    }
+}
+
+/// Checks `algorithm.outputs` against `implementation.return_contract` when a spec has both. Every
+/// output must be built by a composition or produced by the return contract (`PV240`), no two
+/// outputs may share a name (`PV241`), and no output may be built by a composition and produced by
+/// the return contract too (`PV242`). The return contract produces the properties of its `schema`
+/// and the port of its `produced_by`; a property that `$ref`s the schema of an output only
+/// describes that output.
+pub fn check_algorithm_outputs(spec: &SpecModel, errors: &mut Diagnostics) {
+    let doc = spec.doc;
+    let (Some(outputs), Some(return_contract)) = (
+        doc.pointer("/algorithm/outputs").and_then(|v| v.as_array()),
+        doc.pointer("/implementation/return_contract"),
+    ) else {
+        return;
+    };
+
+    // What the return contract produces: name, where it is declared and the `$ref` it describes.
+    let mut returned: Vec<(&str, String, Option<&str>)> = Vec::new();
+    if let Some(properties) = return_contract
+        .pointer("/schema/properties")
+        .and_then(|p| p.as_object())
+    {
+        for (name, schema) in properties {
+            let at = [
+                "implementation",
+                "return_contract",
+                "schema",
+                "properties",
+                name,
+            ];
+            let reference = schema.get("$ref").and_then(|r| r.as_str());
+            returned.push((name, pointer(&at), reference));
+        }
+    }
+    if let Some(port) = return_contract
+        .pointer("/produced_by/port")
+        .and_then(|p| p.as_str())
+    {
+        let at = "/implementation/return_contract/produced_by/port".to_string();
+        returned.push((port, at, None));
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (index, output) in outputs.iter().enumerate() {
+        let Some(name) = output.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let location = pointer(&["algorithm", "outputs", &index.to_string()]);
+        if let Some(first) = seen.get(ident(name).as_ref()) {
+            errors.push(
+                Diagnostic::error(
+                    "PV241",
+                    format!("Algorithm output '{name}' is declared more than once (first as algorithm.outputs #{first})"),
+                )
+                .at(format!("{location}/name")),
+            );
+            continue;
+        }
+        seen.insert(ident(name).into_owned(), index);
+
+        let describes = |reference: &str| {
+            let target = format!("#{location}");
+            reference == target || reference.starts_with(&format!("{target}/"))
+        };
+        let produced = returned
+            .iter()
+            .find(|(returned, ..)| same_ident(returned, name));
+        match (output.get("build").is_some(), produced) {
+            (false, None) => errors.push(
+                Diagnostic::error(
+                    "PV240",
+                    format!("Algorithm output '{name}' is neither built by a composition nor produced by the return_contract"),
+                )
+                .at(location),
+            ),
+            (true, Some((_, at, reference))) if !reference.is_some_and(describes) => errors.push(
+                Diagnostic::error(
+                    "PV242",
+                    format!("Algorithm output '{name}' is built by a composition and also produced by the return_contract ({at})"),
+                )
+                .at(format!("{location}/build")),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Where a spec may declare the time budget of a whole run.
+const TIME_BUDGETS: [&[&str]; 2] = [&["implementation", "total_timeout"], &["meta", "sla"]];
+
+/// Compares the phase `timeout`s with the time budget of the spec (`implementation.total_timeout`,
+/// `meta.sla`): the timeouts along the longest dependency path must fit into it. Phases without a
+/// timeout count as taking no time, and specs whose phases depend on each other in a cycle are left
+/// to `PV130`.
+pub fn check_timeout_budget(spec: &SpecModel, errors: &mut Diagnostics) {
+    let (doc, context) = (spec.doc, &spec.context);
+    let mut declared = false;
+    for contract in &context.contracts {
+        let Some(value) = contract.value.get("timeout") else {
+            continue;
+        };
+        declared = true;
+        if timeout_seconds(value).is_none() {
+            errors.report(
+                "PV191",
+                format!(
+                    "Phase '{}' timeout is {value}; expected a positive number of seconds or a \
+                     duration such as 30s, 500ms, 2m or 1h",
+                    contract.phase
+                ),
+                &contract_pointer(contract.phase, &["timeout"]),
+            );
+        }
+    }
+    let mut budgets = Vec::new();
+    for path in TIME_BUDGETS {
+        let (name, at) = (path.join("."), pointer(path));
+        let Some(value) = doc.pointer(&at) else {
+            continue;
+        };
+        match timeout_seconds(value) {
+            Some(seconds) => budgets.push((name, at, value, seconds)),
+            None => errors.report(
+                "PV191",
+                format!(
+                    "{name} is {value}; expected a positive number of seconds or a duration such \
+                     as 30s, 500ms, 2m or 1h"
+                ),
+                &at,
+            ),
+        }
+    }
+    if !declared || budgets.is_empty() {
+        return;
+    }
+
+    let timeout = |phase: &str| {
+        context
+            .contract(phase)
+            .and_then(|contract| contract.value.get("timeout"))
+            .and_then(timeout_seconds)
+    };
+    let Some((path, total)) = critical_path(context, |phase| timeout(phase).unwrap_or(0.0)) else {
+        return;
+    };
+    let steps: Vec<String> = path
+        .iter()
+        .map(|phase| match timeout(phase) {
+            Some(seconds) => format!("'{phase}' ({})", seconds_text(seconds)),
+            None => format!("'{phase}'"),
+        })
+        .collect();
+    for (name, at, value, budget) in budgets {
+        if total > budget {
+            let value = value.as_str().map_or(value.to_string(), str::to_string);
+            errors.report(
+                "PV190",
+                format!(
+                    "Phase timeouts along {} add up to {}, more than {name} ({value})",
+                    steps.join(" → "),
+                    seconds_text(total)
+                ),
+                &at,
+            );
+        }
+    }
+}
+
+/// Seconds of a `timeout`: a positive number of seconds or a duration such as `30s` or `2m`.
+fn timeout_seconds(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Number(seconds) => seconds.as_f64().filter(|seconds| *seconds > 0.0),
+        JsonValue::String(text) => parse_duration(text).ok().map(|d| d.as_secs_f64()),
+        _ => None,
+    }
+}
+
+fn seconds_text(seconds: f64) -> String {
+    format!("{}s", (seconds * 1000.0).round() / 1000.0)
+}
+
+/// The phases of the dependency path whose `cost`s add up to the most, and that sum; `None` when
+/// the phases depend on each other in a cycle.
+fn critical_path<'a>(
+    context: &SpecContext<'a>,
+    cost: impl Fn(&str) -> f64,
+) -> Option<(Vec<&'a str>, f64)> {
+    let mut graph = context.dependencies();
+    for phase in &context.phases {
+        graph.vertex(Vertex::Phase(phase.name));
+    }
+    let order = graph.topological_order(|vertex| vertex)?;
+    let count = graph.vertices.len();
+    let (mut start, mut end) = (vec![0.0; count], vec![0.0; count]);
+    let mut previous = vec![None; count];
+    for &vertex in &order {
+        end[vertex] = start[vertex]
+            + match graph.vertices[vertex] {
+                Vertex::Phase(phase) => cost(phase),
+                Vertex::Node(..) => 0.0,
+            };
+        for &(next, _) in &graph.successors[vertex] {
+            if end[vertex] > start[next] {
+                start[next] = end[vertex];
+                previous[next] = Some(vertex);
+            }
+        }
+    }
+    let mut at = (0..count).max_by(|&a, &b| end[a].total_cmp(&end[b]))?;
+    let total = end[at];
+    let mut path = Vec::new();
+    loop {
+        if let Vertex::Phase(phase) = graph.vertices[at] {
+            path.push(phase);
+        }
+        match previous[at] {
+            Some(before) => at = before,
+            None => break,
+        }
+    }
+    path.reverse();
+    Some((path, total))
+}
+
+/// Requires every phase that both lists `side_effects` and declares a `retry_policy` to name the
+/// mechanism that deduplicates retries in `idempotency_key`: either the name of one of its inputs
+/// or a path (`$.request.id`) into the payload.
+pub fn check_idempotency_keys(spec: &SpecModel, errors: &mut Diagnostics) {
+    for contract in &spec.context.contracts {
+        let phase_name = contract.phase;
+        let Some(contract_obj) = contract.value.as_object() else {
+            continue;
+        };
+
+        let side_effects: Vec<&str> = contract_obj
+            .get("side_effects")
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|i| i.as_str()).collect())
+            .unwrap_or_default();
+
+        let key = match contract_obj.get("idempotency_key") {
+            None => None,
+            Some(JsonValue::String(key)) if !key.trim().is_empty() => Some(key.as_str()),
+            Some(_) => {
+                errors.push(
+                    Diagnostic::error(
+                        "PV041",
+                        format!("Phase '{phase_name}' idempotency_key must be a non-empty string",),
+                    )
+                    .at(contract_pointer(phase_name, &["idempotency_key"])),
+                );
+                continue;
+            }
+        };
+
+        if let Some(key) = key {
+            let is_path = key.starts_with('$') || key.contains('.') || key.contains('/');
+            if !is_path && contract.input(key).is_none() {
+                errors.push(Diagnostic::error("PV042", format!(
+                    "Phase '{phase_name}' idempotency_key '{key}' is neither a declared input nor a path",
+                )).at(contract_pointer(phase_name, &["idempotency_key"])));
+            }
+            continue;
+        }
+
+        if side_effects.is_empty() || !contract_obj.contains_key("retry_policy") {
+            continue;
+        }
+
+        let mut message = format!(
+            "Phase '{phase_name}' retries side effects ({}) but declares no idempotency_key",
+            side_effects.join(", ")
+        );
+        if let Some(fallback) = contract_obj
+            .get("fallback")
+            .and_then(|f| f.get("phase"))
+            .and_then(|p| p.as_str())
+        {
+            message.push_str(&format!(
+                " (retries exhausted fall back to phase '{fallback}')"
+            ));
+        }
+        errors.push(Diagnostic::error("PV040", message).at(contract_pointer(phase_name, &[])));
+    }
+}
+
+/// Metric kinds accepted in `observability.metrics[].type`.
+const METRIC_TYPES: [&str; 4] = ["counter", "gauge", "histogram", "summary"];
+
+/// Naming convention shared by metrics, log events and trace spans: dot-separated lowercase
+/// snake_case segments, e.g. `support.collect_issue.latency_ms`.
+fn telemetry_name_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^[a-z][a-z0-9_]*(\.[a-z][a-z0-9_]*)*$").unwrap())
+}
+
+/// Validates per-phase `observability` blocks (`metrics`, `log_events`, `spans`, `alerts`):
+/// naming conventions, metric names unique across the whole spec, and alerts that only
+/// reference declared metrics. The list-of-hooks form used by later schema versions is left
+/// to JSON Schema.
+pub fn check_observability(spec: &SpecModel, errors: &mut Diagnostics) {
+    let name_re = telemetry_name_regex();
+    let mut metric_owner: HashMap<&str, &str> = HashMap::new();
+    let mut alerts: Vec<(&str, String, &JsonValue)> = Vec::new();
+
+    for contract in &spec.context.contracts {
+        let phase_name = contract.phase;
+        let Some(observability) = contract
+            .value
+            .get("observability")
+            .and_then(|v| v.as_object())
+        else {
+            continue;
+        };
+
+        if let Some(metrics) = observability.get("metrics").and_then(|v| v.as_array()) {
+            for (index, metric) in metrics.iter().enumerate() {
+                let location = contract_pointer(
+                    phase_name,
+                    &["observability", "metrics", &index.to_string()],
+                );
+                let Some(name) = metric.get("name").and_then(|n| n.as_str()) else {
+                    errors.push(
+                        Diagnostic::error(
+                            "PV051",
+                            format!("Phase '{phase_name}' declares a metric without a name"),
+                        )
+                        .at(location),
+                    );
+                    continue;
+                };
+                if !name_re.is_match(name) {
+                    errors.push(Diagnostic::warning("PV050", format!(
+                        "Phase '{phase_name}' metric '{name}' does not match the naming convention {}",
+                        name_re.as_str()
+                    )).at(location.clone()));
+                }
+                if let Some(kind) = metric.get("type").and_then(|t| t.as_str()) {
+                    if !METRIC_TYPES.contains(&kind) {
+                        errors.push(Diagnostic::error("PV052", format!(
+                            "Phase '{phase_name}' metric '{name}' has unknown type '{kind}' (expected one of: {})",
+                            METRIC_TYPES.join(", ")
+                        )).at(location.clone()));
+                    }
+                }
+                match metric_owner.get(name) {
+                    Some(&owner) if owner == phase_name => errors.push(Diagnostic::error("PV053", format!(
+                        "Phase '{phase_name}' declares metric '{name}' more than once",
+                    )).at(location.clone())),
+                    Some(owner) => errors.push(Diagnostic::error("PV053", format!(
+                        "Metric '{name}' is declared by both phase '{owner}' and phase '{phase_name}'",
+                    )).at(location.clone())),
+                    None => {
+                        metric_owner.insert(name, phase_name);
+                    }
+                }
+            }
+        }
+
+        for (field, label) in [("log_events", "log event"), ("spans", "trace span")] {
+            let Some(items) = observability.get(field).and_then(|v| v.as_array()) else {
+                continue;
+            };
+            let mut seen = HashSet::new();
+            for name in items.iter().filter_map(|i| i.as_str()) {
+                if !name_re.is_match(name) {
+                    errors.push(Diagnostic::warning("PV050", format!(
+                        "Phase '{phase_name}' {label} '{name}' does not match the naming convention {}",
+                        name_re.as_str()
+                    )).at(contract_pointer(phase_name, &["observability", field])));
+                }
+                if !seen.insert(name) {
+                    errors.push(
+                        Diagnostic::warning(
+                            "PV054",
+                            format!(
+                                "Phase '{phase_name}' declares {label} '{name}' more than once",
+                            ),
+                        )
+                        .at(contract_pointer(phase_name, &["observability", field])),
+                    );
+                }
+            }
+        }
+
+        if let Some(items) = observability.get("alerts").and_then(|v| v.as_array()) {
+            alerts.extend(items.iter().enumerate().map(|(index, alert)| {
+                let location =
+                    contract_pointer(phase_name, &["observability", "alerts", &index.to_string()]);
+                (phase_name, location, alert)
+            }));
+        }
+    }
+
+    // Alerts may watch metrics emitted by any phase, so they are checked once all are known.
+    for (phase_name, location, alert) in alerts {
+        match alert.get("metric").and_then(|m| m.as_str()) {
+            Some(metric) if metric_owner.contains_key(metric) => {}
+            Some(metric) => errors.push(
+                Diagnostic::error(
+                    "PV055",
+                    format!(
+                        "Phase '{phase_name}' alert references undeclared metric '{metric}'{}",
+                        did_you_mean(metric, metric_owner.keys().copied())
+                    ),
+                )
+                .at(format!("{location}/metric")),
+            ),
+            None => errors.push(
+                Diagnostic::error(
+                    "PV055",
+                    format!("Phase '{phase_name}' declares an alert without a metric"),
+                )
+                .at(location.clone()),
+            ),
+        }
+        if let Some(threshold) = alert.get("threshold") {
+            if !threshold.is_number() {
+                errors.push(
+                    Diagnostic::error(
+                        "PV056",
+                        format!("Phase '{phase_name}' alert threshold must be a number"),
+                    )
+                    .at(format!("{location}/threshold")),
+                );
+            }
+        }
+    }
+}
+
+/// Reports phase outputs that nothing consumes: no phase input reads them and neither an
+/// `algorithm.outputs` composition nor `return_contract.produced_by` delivers them. Such a port is
+/// usually left over from a refactoring; outputs kept only for their side effects can be waived
+/// with a lower `severity` for the rule or an inline suppression.
+pub fn check_dead_ports(spec: &SpecModel, warnings: &mut Diagnostics) {
+    let context = &spec.context;
+    let read = read_outputs(context);
+    let delivered = delivered_outputs(spec.doc);
+    for contract in &context.contracts {
+        for output in &contract.outputs {
+            let key = port_key((contract.phase, output.name));
+            if !read.contains(&key) && !delivered.contains_key(&key) {
+                warnings.report(
+                    "PV140",
+                    format!(
+                        "Phase '{}' output '{}' is consumed by no phase input, algorithm output or \
+                         return_contract",
+                        contract.phase, output.name
+                    ),
+                    &contract_pointer(contract.phase, &["outputs", &output.index.to_string()]),
+                );
+            }
+        }
+    }
+}
+
+/// Outputs read by some phase input.
+fn read_outputs(context: &SpecContext) -> HashSet<(String, String)> {
+    context
+        .dataflow
+        .iter()
+        .filter_map(|flow| Some(port_key((flow.producer, flow.port?))))
+        .collect()
+}
+
+/// Where each output that leaves the algorithm ends up: the return_contract or the first
+/// `algorithm.outputs` composition that uses it.
+fn delivered_outputs(doc: &JsonValue) -> HashMap<(String, String), String> {
+    let mut delivered: HashMap<(String, String), String> = HashMap::new();
+    if let Some(produced_by) = doc.pointer("/implementation/return_contract/produced_by") {
+        if let (Some(phase), Some(port)) = (
+            produced_by.get("phase").and_then(|p| p.as_str()),
+            produced_by.get("port").and_then(|p| p.as_str()),
+        ) {
+            delivered.insert(port_key((phase, port)), "the return_contract".to_string());
+        }
+    }
+    for output in doc
+        .pointer("/algorithm/outputs")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let mut sources = Vec::new();
+        if let Some(build) = output.get("build") {
+            collect_io_sources(build, &mut sources);
+        }
+        let name = output
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or("<composition>");
+        for key in sources.into_iter().filter_map(phase_output).map(port_key) {
+            delivered
+                .entry(key)
+                .or_insert_with(|| format!("algorithm output '{name}'"));
+        }
+    }
+    delivered
+}
+
+/// Reports required inputs that can never be satisfied because the chain of producers they depend
+/// on is broken further up: the input reads an output that exists, but the producing phase has a
+/// required input that cannot be satisfied, and so on. The message follows the chain to the first
+/// missing link. Broken direct references are `PV014`–`PV016`; inputs marked `optional: true`
+/// are not followed.
+pub fn check_dataflow(spec: &SpecModel, errors: &mut Diagnostics) {
+    let context = &spec.context;
+    let mut blocked = HashMap::new();
+    for contract in &context.contracts {
+        for input in contract.inputs.iter().filter(|input| required(input)) {
+            let Binding::Output(producer, port) = binding(context, contract.phase, input) else {
+                continue;
+            };
+            let Some(chain) = blocked_by(context, producer, &mut blocked) else {
+                continue;
+            };
+            errors.report(
+                "PV150",
+                format!(
+                    "Required input '{}' of phase '{}' can never be satisfied: it reads output \
+                     '{port}' of phase '{producer}', {}",
+                    input.name,
+                    contract.phase,
+                    chain.join(", ")
+                ),
+                &contract_pointer(contract.phase, &["inputs", &input.index.to_string()]),
+            );
+        }
+    }
+}
+
+/// Reports phase inputs whose declared type does not accept the declared type of the output they
+/// read, e.g. an input of `type: array<int>` reading an output whose schema is `type: string`.
+/// Types come from a port's `type` shorthand or else its `schema`; see [`PortType`].
+pub fn check_port_types(spec: &SpecModel, errors: &mut Diagnostics) {
+    let context = &spec.context;
+    for contract in &context.contracts {
+        for input in &contract.inputs {
+            let Binding::Output(producer, port) = binding(context, contract.phase, input) else {
+                continue;
+            };
+            let Some(output) = context.output(producer, port) else {
+                continue;
+            };
+            let (expected, produced) = (
+                PortType::of_port(input.value),
+                PortType::of_port(output.value),
+            );
+            if expected.accepts(&produced) {
+                continue;
+            }
+            let field = if input.value.get("type").is_some() {
+                "type"
+            } else {
+                "schema"
+            };
+            errors.report(
+                "PV160",
+                format!(
+                    "Input '{}' of phase '{}' expects {expected} but reads output '{}' of phase \
+                     '{producer}', which is {produced}",
+                    input.name, contract.phase, output.name
+                ),
+                &contract_pointer(contract.phase, &["inputs", &input.index.to_string(), field]),
+            );
+        }
+    }
+}
+
+/// Validates the `example` (or every entry of `examples`) of a phase input or output against the
+/// port's `schema`. Schemas given by name and fragments with `$ref`s that leave the fragment are
+/// not followed, and neither are fragments that do not compile.
+pub fn check_port_examples(spec: &SpecModel, warnings: &mut Diagnostics) {
+    for contract in &spec.context.contracts {
+        let sections = [
+            ("input", "inputs", &contract.inputs),
+            ("output", "outputs", &contract.outputs),
+        ];
+        for (direction, section, ports) in sections {
+            for port in ports {
+                let Some(schema) = port.value.get("schema").filter(|s| s.is_object()) else {
+                    continue;
+                };
+                let mut examples = Vec::new();
+                if let Some(example) = port.value.get("example") {
+                    examples.push(("example".to_string(), example));
+                }
+                if let Some(list) = port.value.get("examples").and_then(|e| e.as_array()) {
+                    examples.extend(
+                        list.iter()
+                            .enumerate()
+                            .map(|(index, example)| (format!("examples/{index}"), example)),
+                    );
+                }
+                if examples.is_empty() || leaves_fragment(schema) {
+                    continue;
+                }
+                let Ok(compiled) = JSONSchema::compile(schema) else {
+                    continue;
+                };
+                for (key, example) in examples {
+                    let Err(errors) = compiled.validate(example) else {
+                        continue;
+                    };
+                    let errors: Vec<String> = errors
+                        .map(|error| match error.instance_path.to_string() {
+                            path if path.is_empty() => error.to_string(),
+                            path => format!("{error} (at {path})"),
+                        })
+                        .collect();
+                    let more = match errors.len() {
+                        1 => String::new(),
+                        count => format!(" and {} more problem(s)", count - 1),
+                    };
+                    let index = port.index.to_string();
+                    let mut at = vec![section, &index];
+                    at.extend(key.split('/'));
+                    warnings.report(
+                        "PV170",
+                        format!(
+                            "Example of {direction} '{}' of phase '{}' does not match its schema: \
+                             {}{more}",
+                            port.name, contract.phase, errors[0]
+                        ),
+                        &contract_pointer(contract.phase, &at),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Whether `schema` has a `$ref` to something other than a part of itself.
+pub fn leaves_fragment(schema: &JsonValue) -> bool {
+    match schema {
+        JsonValue::Object(map) => map.iter().any(|(key, value)| match (key.as_str(), value) {
+            ("$ref", JsonValue::String(target)) => !target.starts_with('#'),
+            _ => leaves_fragment(value),
+        }),
+        JsonValue::Array(items) => items.iter().any(leaves_fragment),
+        _ => false,
+    }
+}
+
+/// What a phase input reads.
+enum Binding<'a> {
+    /// Data from the instance or the globals, or a source that cannot be followed.
+    External,
+    /// Output `port` of a phase.
+    Output(&'a str, &'a str),
+    /// A producer or port that does not exist, described from the input's point of view.
+    Missing(String),
+}
+
+/// Inputs are required unless marked `optional: true`.
+fn required(input: &Port) -> bool {
+    input.value.get("optional").and_then(|o| o.as_bool()) != Some(true)
+}
+
+/// What `input` of `phase` reads. An input without a `source` reads the output of the same name
+/// of an earlier phase of `algorithm.phases`, if there is one, and the instance otherwise.
+fn binding<'a>(context: &SpecContext<'a>, phase: &str, input: &Port<'a>) -> Binding<'a> {
+    let Some(source) = input.value.get("source") else {
+        let earlier = context
+            .phases
+            .iter()
+            .filter(|p| p.listed())
+            .take_while(|p| !same_ident(p.name, phase));
+        for producer in earlier {
+            if let Some(output) = context.output(producer.name, input.name) {
+                return Binding::Output(producer.name, output.name);
+            }
+        }
+        return Binding::External;
+    };
+    let Some((producer, port)) = phase_output(source) else {
+        return Binding::External;
+    };
+    if context.phase(producer).is_none() {
+        return Binding::Missing(format!("reads phase '{producer}', which does not exist"));
+    }
+    if context.contract(producer).is_none() {
+        return Binding::Missing(format!(
+            "reads phase '{producer}', which has no phase_contracts entry"
+        ));
+    }
+    if context.undeclared_output(producer, port) {
+        return Binding::Missing(format!(
+            "reads output '{port}' of phase '{producer}', which does not declare it"
+        ));
+    }
+    Binding::Output(producer, port)
+}
+
+/// Why `phase` can never run, as the chain from its first unsatisfiable required input to the
+/// first missing link, or `None` if it can (as far as its contract tells). Results are kept in
+/// `blocked` by phase; a phase is taken to be runnable while its own inputs are being followed,
+/// so that cycles (`PV130`) end the search.
+fn blocked_by(
+    context: &SpecContext,
+    phase: &str,
+    blocked: &mut HashMap<String, Option<Vec<String>>>,
+) -> Option<Vec<String>> {
+    let key = ident(phase).into_owned();
+    if let Some(chain) = blocked.get(&key) {
+        return chain.clone();
+    }
+    blocked.insert(key.clone(), None);
+    let mut chain = None;
+    for input in context.contract(phase).into_iter().flat_map(|c| &c.inputs) {
+        if !required(input) {
+            continue;
+        }
+        let clause = format!("whose required input '{}'", input.name);
+        match binding(context, phase, input) {
+            Binding::External => {}
+            Binding::Missing(reason) => chain = Some(vec![format!("{clause} {reason}")]),
+            Binding::Output(producer, port) => {
+                chain = blocked_by(context, producer, blocked).map(|rest| {
+                    let read = format!("{clause} reads output '{port}' of phase '{producer}'");
+                    [vec![read], rest].concat()
+                });
+            }
+        }
+        if chain.is_some() {
+            break;
+        }
+    }
+    blocked.insert(key, chain.clone());
+    chain
+}
+
+/// Observations that need no action: phase outputs that no other phase reads but that feed the
+/// return_contract or an algorithm output, and phases whose `deprecation_plan` says they are on
+/// their way out. Reported at the rule's default severity, `info`. `PV102` (fields the schema
+/// marks `deprecated`) needs the schema and is reported by [`crate::keywords::deprecated_fields`].
+pub fn check_observations(spec: &SpecModel, notes: &mut Diagnostics) {
+    let (doc, context) = (spec.doc, &spec.context);
+    if context.contracts.is_empty() {
+        return;
+    }
+    let read = read_outputs(context);
+    let delivered = delivered_outputs(doc);
+
+    for contract in &context.contracts {
+        let phase_name = contract.phase;
+        for output in &contract.outputs {
+            let port = output.name;
+            let key = port_key((phase_name, port));
+            if let Some(target) = delivered.get(&key).filter(|_| !read.contains(&key)) {
+                notes.report(
+                    "PV100",
+                    format!(
+                        "Phase '{phase_name}' output '{port}' is read by no other phase; it feeds {target}",
+                    ),
+                    &contract_pointer(phase_name, &["outputs", &output.index.to_string()]),
+                );
+            }
+        }
+
+        let Some(plan) = contract.value.get("deprecation_plan") else {
+            continue;
+        };
+        let status = plan
+            .get("status")
+            .and_then(|s| s.as_str())
+            .unwrap_or_default();
+        let listed = context
+            .phase(phase_name)
+            .is_some_and(|phase| phase.listed());
+        if matches!(status, "deprecated" | "retired") && listed {
+            let replacement = match plan.get("replacement_phase").and_then(|r| r.as_str()) {
+                Some(replacement) => format!("; its replacement is '{replacement}'"),
+                None => String::new(),
+            };
+            notes.report(
+                "PV101",
+                format!(
+                    "Phase '{phase_name}' is {status} but still part of the algorithm{replacement}",
+                ),
+                &contract_pointer(phase_name, &["deprecation_plan", "status"]),
+            );
+        }
+    }
+}
+
+/// A phase and port pair as compared under the configured [`IdentifierCase`].
+fn port_key((phase, port): (&str, &str)) -> (String, String) {
+    (ident(phase).into_owned(), ident(port).into_owned())
+}
+
+/// Phase and port read by a `phase_output` source.
+fn phase_output(source: &JsonValue) -> Option<(&str, &str)> {
+    if source.get("kind").and_then(|k| k.as_str()) != Some("phase_output") {
+        return None;
+    }
+    Some((
+        source.get("phase")?.as_str()?,
+        source.get("port")?.as_str()?,
+    ))
+}
+
+/// Pointer to `implementation.phase_contracts.<phase>`, extended by `rest`.
+fn contract_pointer(phase: &str, rest: &[&str]) -> String {
+    pointer(&[&["implementation", "phase_contracts", phase], rest].concat())
+}
+
+/// Phase name plus optional `contract_version` identifying a phase shared between specs.
+type SharedPhaseKey = (String, Option<String>);
+
+/// Compares phase contracts that appear in several specs of one run. Contracts sharing a phase
+/// name and `contract_version` must be identical unless one of them sets `distinct: true`.
+pub fn check_shared_phases(corpus: &[(String, JsonValue)]) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+
+    // (compared phase name, contract_version) → [(spec label, phase name, contract)], ordered
+    // for stable output
+    let mut shared: BTreeMap<SharedPhaseKey, Vec<(&str, &str, &JsonValue)>> = BTreeMap::new();
+    for (label, doc) in corpus {
+        for contract in SpecContext::new(doc).contracts {
+            let (phase_name, contract) = (contract.phase, contract.value);
+            if contract.get("distinct").and_then(|d| d.as_bool()) == Some(true) {
+                continue;
+            }
+            let version = contract
+                .get("contract_version")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            shared
+                .entry((ident(phase_name).into_owned(), version))
+                .or_default()
+                .push((label.as_str(), phase_name, contract));
+        }
+    }
+
+    for (key, occurrences) in &shared {
+        let (reference_label, _, reference) = occurrences[0];
+        for (label, phase_name, contract) in &occurrences[1..] {
+            if *contract == reference {
+                continue;
+            }
+            let mut fields: Vec<&str> = Vec::new();
+            for (field, value) in reference.as_object().into_iter().flatten() {
+                if contract.get(field) != Some(value) {
+                    fields.push(field);
+                }
+            }
+            for field in contract.as_object().into_iter().flatten().map(|(f, _)| f) {
+                if reference.get(field).is_none() {
+                    fields.push(field);
+                }
+            }
+            let (_, version) = key;
+            let version = version
+                .as_deref()
+                .map(|v| format!(" (contract_version {v})"))
+                .unwrap_or_default();
+            errors.push(Diagnostic::error("PV060", format!(
+                "Phase '{phase_name}'{version} differs between {reference_label} and {label} in: {}; align the contracts or mark one with distinct: true",
+                if fields.is_empty() {
+                    "contract shape".to_string()
+                } else {
+                    fields.join(", ")
+                }
+            )).at(contract_pointer(phase_name, &[])));
+        }
+    }
+
+    errors
+}
+
+/// Reports specs of one run that are the same spec: PV110 for identical content (after inline
+/// suppressions are removed, whatever the file format or key order) and PV111 for specs sharing
+/// the `(meta.title, algorithm.name)` identity a registry keys specs by.
+pub fn check_duplicate_specs(corpus: &[(String, JsonValue)]) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+
+    // serde_json keeps object keys sorted, so its serialization is canonical.
+    let mut by_hash: HashMap<String, &str> = HashMap::new();
+    let mut copies: HashSet<&str> = HashSet::new();
+    for (label, doc) in corpus {
+        let hash = format!("{:x}", Sha256::digest(doc.to_string()));
+        match by_hash.get(hash.as_str()) {
+            Some(original) => {
+                errors.push(Diagnostic::error(
+                    "PV110",
+                    format!(
+                        "{label} is identical to {original} (sha256 {}); remove one of the copies",
+                        &hash[..12]
+                    ),
+                ));
+                copies.insert(label);
+            }
+            None => {
+                by_hash.insert(hash, label);
+            }
+        }
+    }
+
+    let mut by_identity: HashMap<(String, String), &str> = HashMap::new();
+    for (label, doc) in corpus {
+        let title = doc.pointer("/meta/title").and_then(|v| v.as_str());
+        let name = doc.pointer("/algorithm/name").and_then(|v| v.as_str());
+        let (Some(title), Some(name)) = (title, name) else {
+            continue;
+        };
+        let identity = (ident(title).into_owned(), ident(name).into_owned());
+        match by_identity.get(&identity) {
+            // Identical copies are already reported as PV110.
+            Some(_) if copies.contains(label.as_str()) => {}
+            Some(original) => errors.push(
+                Diagnostic::error(
+                    "PV111",
+                    format!(
+                        "{label} reuses meta.title '{title}' and algorithm.name '{name}' of {original}; rename one of the algorithms"
+                    ),
+                )
+                .at(pointer(&["algorithm", "name"])),
+            ),
+            None => {
+                by_identity.insert(identity, label);
+            }
+        }
+    }
+
+    errors
+}
+
+fn validate_io_source<F>(
+    source: &JsonValue,
+    phase_context: Option<(&str, &str)>,
+    composition_name: Option<&str>,
+    context: &SpecContext,
+    mut push_error: F,
+) where
+    F: FnMut(&'static str, String),
+{
+    let composition_label = composition_name.unwrap_or("<composition>");
+    let subject = match phase_context {
+        Some((phase_name, input_name)) => {
+            format!("Phase '{phase_name}' input '{input_name}' source")
+        }
+        None => format!("Composition '{composition_label}' source"),
+    };
+    let field = |name: &str| source.get(name).and_then(|v| v.as_str());
+
+    if !source.is_object() {
+        return push_error("PV019", format!("{subject} must be an object"));
+    }
+    let Some(kind) = field("kind") else {
+        return push_error("PV019", format!("{subject} has no kind"));
+    };
+
+    match kind {
+        "phase_output" => {
+            let Some(target_phase) = field("phase") else {
+                return push_error(
+                    "PV019",
+                    format!("{subject} of kind 'phase_output' names no phase"),
+                );
+            };
+
+            if context.phase(target_phase).is_none() {
+                push_error("PV014", match phase_context {
+                    Some((phase_name, input_name)) => format!(
+                        "Phase '{phase_name}' references unknown producing phase '{target_phase}' in input '{input_name}'{}",
+                        context.phase_suggestion(target_phase)
+                    ),
+                    None => format!(
+                        "Composition '{composition_label}' references unknown producing phase '{target_phase}'{}",
+                        context.phase_suggestion(target_phase)
+                    ),
+                });
+                return;
+            }
+
+            if context.contract(target_phase).is_none() {
+                push_error("PV015", match phase_context {
+                    Some((phase_name, input_name)) => format!(
+                        "Phase '{phase_name}' references phase '{target_phase}' in input '{input_name}' but that phase lacks a phase_contracts entry",
+                    ),
+                    None => format!(
+                        "Composition '{composition_label}' references phase '{target_phase}' but it has no phase_contracts entry",
+                    ),
+                });
+                return;
+            }
+
+            let Some(port) = field("port") else {
+                return push_error(
+                    "PV019",
+                    format!("{subject} of kind 'phase_output' names no port"),
+                );
+            };
+
+            if context.undeclared_output(target_phase, port) {
+                push_error("PV016", match phase_context {
+                    Some((phase_name, input_name)) => format!(
+                        "Phase '{phase_name}' expects output '{port}' from phase '{target_phase}' in input '{input_name}', but it is not declared{}",
+                        context.output_suggestion(target_phase, port)
+                    ),
+                    None => format!(
+                        "Composition '{composition_label}' expects output '{port}' from phase '{target_phase}' but it is not declared{}",
+                        context.output_suggestion(target_phase, port)
+                    ),
+                });
+            }
+        }
+        "instance" | "global" => {
+            match field("path") {
+                Some(path) if !path.trim().is_empty() => {}
+                _ => push_error("PV017", match phase_context {
+                    Some((phase_name, input_name)) => format!(
+                        "Phase '{phase_name}' input '{input_name}' must declare a non-empty source.path for kind '{kind}'",
+                    ),
+                    None => format!(
+                        "Composition '{composition_label}' source must declare a non-empty path for kind '{kind}'",
+                    ),
+                }),
+            }
+        }
+        // Other kinds are for the schema to accept or reject.
+        _ => {}
+    }
+}
+
+fn collect_io_sources<'a>(value: &'a JsonValue, acc: &mut Vec<&'a JsonValue>) {
+    match value {
+        JsonValue::Object(map) => {
+            if map.contains_key("kind") {
+                acc.push(value);
+            } else {
+                for (key, inner) in map {
+                    // Taken even without a kind, so that such a source is reported as malformed.
+                    if key == "source" {
+                        acc.push(inner);
+                    } else {
+                        collect_io_sources(inner, acc);
+                    }
+                }
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_io_sources(item, acc);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn parse_semver_major(ver: &str) -> Option<u64> {
+    let trimmed = ver.strip_prefix('v')?;
+    let major_part = trimmed.split(['.', '-', '+']).next()?;
+    major_part.parse().ok()
+}
+
+/// How identifiers are compared, set from the `identifiers` configuration setting.
+static IDENTIFIER_CASE: RwLock<IdentifierCase> = RwLock::new(IdentifierCase::Sensitive);
+
+/// The identifier comparison in effect: the active [profile](with_profile)'s, or else the
+/// process-wide one.
+fn identifier_case() -> &'static RwLock<IdentifierCase> {
+    match PROFILE.with(Cell::get) {
+        Some(profile) => &profile.identifiers,
+        None => &IDENTIFIER_CASE,
+    }
+}
+
+/// Makes every rule compare phase, port and algorithm names according to `case`.
+pub fn set_identifier_case(case: IdentifierCase) {
+    *identifier_case().write().unwrap() = case;
+}
+
+/// `name` in the form identifiers are compared in under the configured [`IdentifierCase`].
+pub fn ident(name: &str) -> Cow<'_, str> {
+    match *identifier_case().read().unwrap() {
+        IdentifierCase::Sensitive => Cow::Borrowed(name),
+        IdentifierCase::Insensitive => Cow::Owned(name.to_ascii_lowercase()),
+        IdentifierCase::Casefold => Cow::Owned(casefold(name)),
+    }
+}
+
+fn same_ident(a: &str, b: &str) -> bool {
+    a == b || ident(a) == ident(b)
+}
+
+/// `; did you mean 'x'?` naming up to three `candidates` nearest to `name`. Empty when none is
+/// within one edit per three characters of it (one edit for short names).
+pub fn did_you_mean<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+    let wanted: Vec<char> = ident(name).chars().collect();
+    let mut nearest: Vec<&str> = Vec::new();
+    let mut best = (wanted.len() / 3).max(1);
+    for candidate in candidates {
+        let distance = edit_distance(&wanted, &ident(candidate).chars().collect::<Vec<_>>());
+        if distance == 0 || distance > best || nearest.contains(&candidate) {
+            continue;
+        }
+        if distance < best {
+            best = distance;
+            nearest.clear();
+        }
+        nearest.push(candidate);
+    }
+    nearest.sort_unstable();
+    let quoted: Vec<String> = nearest.iter().take(3).map(|c| format!("'{c}'")).collect();
+    match quoted.as_slice() {
+        [] => String::new(),
+        [only] => format!("; did you mean {only}?"),
+        [rest @ .., last] => format!("; did you mean {} or {last}?", rest.join(", ")),
+    }
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Unicode full case folding of `text`: lowercasing plus the folds lowercasing misses.
+fn casefold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'ς' => folded.push('σ'),
+            'ſ' => folded.push('s'),
+            'ﬀ' => folded.push_str("ff"),
+            'ﬁ' => folded.push_str("fi"),
+            'ﬂ' => folded.push_str("fl"),
+            'ﬅ' | 'ﬆ' => folded.push_str("st"),
+            _ => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Lowercase letters and digits, with every other run of characters turned into a single `-`.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Extracts the base name from the title: everything before the first opening parenthesis.
+fn base_name_from_title(title: &str) -> String {
+    if let Some((left, _)) = title.split_once('(') {
+        left.trim().to_string()
+    } else {
+        title.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The codes and pointers of what `check` finds in `doc`.
+    fn findings(
+        check: fn(&SpecModel, &mut Diagnostics),
+        doc: &JsonValue,
+    ) -> Vec<(&'static str, String)> {
+        let mut errors = Diagnostics::new(Severity::Error);
+        check(&SpecModel::new(doc), &mut errors);
+        errors
+            .into_vec()
+            .into_iter()
+            .map(|d| (d.code, d.pointer.unwrap_or_default()))
+            .collect()
+    }
+
+    fn with_retry_policy(policy: JsonValue) -> JsonValue {
+        json!({
+            "algorithm": { "phases": ["fetch"] },
+            "implementation": {
+                "phase_contracts": { "fetch": { "retry_policy": policy } }
+            }
+        })
+    }
+
+    fn retry_findings(policy: JsonValue) -> Vec<(&'static str, String)> {
+        findings(check_retry_policies, &with_retry_policy(policy))
+    }
+
+    const POLICY: &str = "/implementation/phase_contracts/fetch/retry_policy";
+
+    #[test]
+    fn accepts_well_formed_retry_policies() {
+        for policy in [
+            json!({ "max_attempts": 1 }),
+            json!({ "max_attempts": 3, "backoff": { "strategy": "fixed", "initial_delay_ms": 0 } }),
+            json!({
+                "backoff": {
+                    "strategy": "exponential",
+                    "initial_delay_ms": 100,
+                    "max_delay_ms": 100
+                }
+            }),
+        ] {
+            assert_eq!(retry_findings(policy.clone()), [], "{policy}");
+        }
+    }
+
+    #[test]
+    fn rejects_max_attempts_below_one() {
+        for attempts in [json!(0), json!(-1), json!(2.5), json!("3")] {
+            assert_eq!(
+                retry_findings(json!({ "max_attempts": attempts })),
+                [("PV180", format!("{POLICY}/max_attempts"))],
+                "{attempts}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_backoff_strategies() {
+        let policy = json!({ "backoff": { "strategy": "random", "initial_delay_ms": 10 } });
+        assert_eq!(
+            retry_findings(policy),
+            [("PV181", format!("{POLICY}/backoff/strategy"))]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_backoff_delays() {
+        let policy = json!({ "backoff": { "strategy": "linear", "initial_delay_ms": -5 } });
+        assert_eq!(
+            retry_findings(policy),
+            [("PV182", format!("{POLICY}/backoff/initial_delay_ms"))]
+        );
+        let policy = json!({
+            "backoff": { "strategy": "exponential", "initial_delay_ms": 5000, "max_delay_ms": 100 }
+        });
+        assert_eq!(
+            retry_findings(policy),
+            [("PV182", format!("{POLICY}/backoff/initial_delay_ms"))]
+        );
+    }
+
+    #[test]
+    fn requires_a_strategy_and_its_fields() {
+        let policy = json!({ "backoff": { "initial_delay_ms": 100 } });
+        assert_eq!(
+            retry_findings(policy),
+            [("PV183", format!("{POLICY}/backoff"))]
+        );
+        let policy = json!({ "backoff": { "strategy": "exponential", "initial_delay_ms": 100 } });
+        assert_eq!(
+            retry_findings(policy),
+            [("PV183", format!("{POLICY}/backoff"))]
+        );
+    }
+
+    /// A spec whose `decide` node has `condition` and whose `review` loop runs until `until`.
+    fn with_conditions(condition: &str, until: &str) -> JsonValue {
+        json!({
+            "algorithm": {
+                "phases": ["analyze", "escalate"],
+                "graph": {
+                    "nodes": {
+                        "analyze": { "type": "phase" },
+                        "decide": { "type": "if", "condition": condition },
+                        "review": { "type": "loop", "until": until },
+                        "escalate": { "type": "phase" }
+                    },
+                    "edges": [{ "from": "analyze", "to": "escalate", "condition": condition }]
+                }
+            },
+            "implementation": {
+                "parameters": { "threshold": { "type": "number" } },
+                "phase_contracts": {
+                    "analyze": { "outputs": [{ "name": "severity" }, { "name": "labels" }] },
+                    "escalate": { "outputs": [{ "name": "ticket" }] }
+                }
+            }
+        })
+    }
+
+    fn condition_findings(condition: &str) -> Vec<(&'static str, String)> {
+        findings(check_graph_conditions, &with_conditions(condition, "done"))
+            .into_iter()
+            .filter(|(_, at)| !at.ends_with("/until"))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_conditions_over_known_names() {
+        for condition in [
+            "severity >= 0.8",
+            "analyze.severity > threshold",
+            "$.customer.tier == 'vip' and not labels",
+            "escalate.ticket != null",
+            "the customer is angry",
+        ] {
+            assert_eq!(condition_findings(condition), [], "{condition}");
+        }
+        let doc = with_conditions("true", "iteration >= 3 || ticket");
+        assert_eq!(findings(check_graph_conditions, &doc), []);
+    }
+
+    #[test]
+    fn rejects_conditions_that_do_not_parse() {
+        assert_eq!(
+            condition_findings("severity >="),
+            [
+                (
+                    "PV270",
+                    "/algorithm/graph/nodes/decide/condition".to_string()
+                ),
+                ("PV270", "/algorithm/graph/edges/0/condition".to_string()),
+            ]
+        );
+        let doc = with_conditions("true", "(iteration > 3");
+        assert_eq!(
+            findings(check_graph_conditions, &doc),
+            [("PV270", "/algorithm/graph/nodes/review/until".to_string())]
+        );
+    }
+
+    #[test]
+    fn rejects_conditions_over_unknown_names() {
+        for condition in [
+            "severty > 1",
+            "analyze.sevrity > 1",
+            "analyze > 1",
+            "iteration > 3",
+        ] {
+            let codes: Vec<_> = condition_findings(condition)
+                .into_iter()
+                .map(|(code, _)| code)
+                .collect();
+            assert_eq!(codes, ["PV271", "PV271"], "{condition}");
+        }
+    }
+
+    #[test]
+    fn skips_unknown_names_when_outputs_are_unknown() {
+        let mut doc = with_conditions("severty > 1", "done");
+        doc["implementation"]["phase_contracts"]["escalate"]["outputs"] = json!("ticket");
+        assert_eq!(findings(check_graph_conditions, &doc), []);
+        doc["implementation"]["phase_contracts"] = json!({});
+        assert_eq!(findings(check_graph_conditions, &doc), []);
+    }
+}
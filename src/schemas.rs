@@ -0,0 +1,472 @@
+//! Schema resolution. A [`SchemaResolvers`] chain asks each [`SchemaResolver`] in turn for the
+//! schema of a `spec_version` or of an explicit URI (`--schema`); the first one that knows the
+//! answer wins. The standard chain covers local files, HTTP(S) URLs, the version map and the
+//! embedded schema; other sources (a database, an artifact store) plug in by implementing the trait.
+//!
+//! Schemas may be split into several documents: a `$ref` to another file (JSON or YAML) or URL is
+//! resolved relative to the location of the schema that contains it.
+//!
+//! The JSON Schema draft is inferred from `$schema` unless `--draft` enforces one.
+
+use crate::{
+    cache::{Memo, SchemaCache, FETCHED_SCHEMAS},
+    encoding,
+    fetch::Fetcher,
+    formats::CustomFormat,
+    resolve_versions_map_path, EMBEDDED_SCHEMA,
+};
+use clap::ValueEnum;
+use jsonschema::{Draft, JSONSchema, SchemaResolverError};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value as JsonValue;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use url::Url;
+
+/// A schema together with where it came from.
+pub struct ResolvedSchema {
+    /// Human-readable origin: a path, a URL or `embedded schema`.
+    pub origin: String,
+    pub schema: JsonValue,
+    /// Where the schema was read from; relative `$ref`s are resolved against it. `None` for the
+    /// embedded schema.
+    pub base: Option<Url>,
+}
+
+/// The document a schema is needed for.
+pub struct SchemaRequest<'a> {
+    /// Spec file (or `-`); relative lookups such as the version map start from its directory.
+    pub input: &'a Path,
+    pub spec_version: Option<&'a str>,
+}
+
+/// One source of schemas. Both lookups return `Ok(None)` when the resolver does not handle the
+/// request, so that the next resolver in the chain is asked.
+pub trait SchemaResolver: Send + Sync {
+    /// Schema for the `spec_version` of a document (`None` for documents without one).
+    fn by_version(&self, _request: &SchemaRequest) -> Result<Option<ResolvedSchema>, String> {
+        Ok(None)
+    }
+
+    /// Schema at `uri`, a path or URL.
+    fn by_uri(&self, _uri: &str) -> Result<Option<ResolvedSchema>, String> {
+        Ok(None)
+    }
+}
+
+/// Resolvers asked in order.
+pub struct SchemaResolvers {
+    resolvers: Vec<Box<dyn SchemaResolver>>,
+}
+
+impl SchemaResolvers {
+    pub fn new(resolvers: Vec<Box<dyn SchemaResolver>>) -> Self {
+        Self { resolvers }
+    }
+
+    /// Asks `resolver` before the resolvers already in the chain.
+    pub fn prepend(&mut self, resolver: Box<dyn SchemaResolver>) {
+        self.resolvers.insert(0, resolver);
+    }
+
+    /// Files and HTTP(S) URLs for explicit schemas, then the version map, then the embedded
+    /// schema for documents without a `spec_version`. Parsed schemas are kept in `cache`.
+    pub fn standard(versions_map: &Path, cache: SchemaCache, fetcher: Fetcher) -> Self {
+        Self::new(vec![
+            Box::new(HttpResolver::new(cache.clone(), fetcher.clone())),
+            Box::new(FileResolver::new(cache.clone())),
+            Box::new(VersionMapResolver {
+                map: versions_map.to_path_buf(),
+                http: HttpResolver::new(cache.clone(), fetcher),
+                files: FileResolver::new(cache),
+            }),
+            Box::new(EmbeddedResolver),
+        ])
+    }
+
+    /// Schema for a document: the explicit `schema` URI if given, otherwise by spec version.
+    pub fn resolve(
+        &self,
+        schema: Option<&str>,
+        request: &SchemaRequest,
+    ) -> Result<ResolvedSchema, String> {
+        if let Some(uri) = schema {
+            return self.by_uri(uri);
+        }
+        for resolver in &self.resolvers {
+            if let Some(resolved) = resolver.by_version(request)? {
+                return Ok(resolved);
+            }
+        }
+        Err(match request.spec_version {
+            Some(version) => format!("Error: no schema found for spec_version '{version}'"),
+            None => "Error: no schema found for a document without spec_version".to_string(),
+        })
+    }
+
+    /// Schema at a path or URL.
+    pub fn by_uri(&self, uri: &str) -> Result<ResolvedSchema, String> {
+        for resolver in &self.resolvers {
+            if let Some(resolved) = resolver.by_uri(uri)? {
+                return Ok(resolved);
+            }
+        }
+        Err(format!("Error: no schema resolver handles '{uri}'"))
+    }
+}
+
+impl Default for SchemaResolvers {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl std::fmt::Debug for SchemaResolvers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SchemaResolvers({} resolvers)", self.resolvers.len())
+    }
+}
+
+/// Whether a schema URI names an HTTP(S) resource rather than a file.
+pub fn is_url(uri: &str) -> bool {
+    uri.starts_with("http://") || uri.starts_with("https://")
+}
+
+/// The schema bundled into the binary, used for documents without a `spec_version`.
+pub struct EmbeddedResolver;
+
+impl SchemaResolver for EmbeddedResolver {
+    fn by_version(&self, request: &SchemaRequest) -> Result<Option<ResolvedSchema>, String> {
+        if request.spec_version.is_some() {
+            return Ok(None);
+        }
+        let schema = serde_json::from_str(EMBEDDED_SCHEMA)
+            .map_err(|e| format!("Embedded schema is invalid: {e}"))?;
+        Ok(Some(ResolvedSchema {
+            origin: "embedded schema".to_string(),
+            schema,
+            base: None,
+        }))
+    }
+}
+
+/// Schema files on disk (JSON or YAML); also accepts `file://` URIs.
+#[derive(Default)]
+pub struct FileResolver {
+    cache: SchemaCache,
+}
+
+impl FileResolver {
+    pub fn new(cache: SchemaCache) -> Self {
+        Self { cache }
+    }
+}
+
+impl SchemaResolver for FileResolver {
+    fn by_uri(&self, uri: &str) -> Result<Option<ResolvedSchema>, String> {
+        if is_url(uri) {
+            return Ok(None);
+        }
+        let path = Path::new(uri.strip_prefix("file://").unwrap_or(uri));
+        let origin = path.display().to_string();
+        let decoded = encoding::read(path)
+            .map_err(|e| format!("Error: failed to read schema {origin}: {e}"))?;
+        if let Some(encoding) = decoded.encoding {
+            encoding::warn_converted(&origin, encoding);
+        }
+        let text = decoded.text;
+        let schema = self.cache.parsed(&text, || parse_schema(&text, &origin))?;
+        let base = fs::canonicalize(path)
+            .ok()
+            .and_then(|path| Url::from_file_path(path).ok());
+        Ok(Some(ResolvedSchema {
+            origin,
+            schema,
+            base,
+        }))
+    }
+}
+
+/// Schemas served over HTTP(S); each URL is fetched once per run, however many threads ask for
+/// it, and a failed fetch is not retried within the run.
+pub struct HttpResolver {
+    fetched: Memo<Result<JsonValue, String>>,
+    cache: SchemaCache,
+    fetcher: Fetcher,
+}
+
+impl HttpResolver {
+    pub fn new(cache: SchemaCache, fetcher: Fetcher) -> Self {
+        Self {
+            fetched: Memo::new(&FETCHED_SCHEMAS),
+            cache,
+            fetcher,
+        }
+    }
+}
+
+impl SchemaResolver for HttpResolver {
+    fn by_uri(&self, uri: &str) -> Result<Option<ResolvedSchema>, String> {
+        if !is_url(uri) {
+            return Ok(None);
+        }
+        let schema = self.fetched.get_or_compute(uri, || {
+            let text = self
+                .fetcher
+                .get(uri)
+                .map_err(|e| format!("Error: failed to fetch schema {uri}: {e}"))?;
+            self.cache.parsed(&text, || parse_schema(&text, uri))
+        })?;
+        Ok(Some(ResolvedSchema {
+            origin: uri.to_string(),
+            schema,
+            base: Url::parse(uri).ok(),
+        }))
+    }
+}
+
+/// `spec_version` → schema through the version map. Map entries may be paths (relative to the
+/// map) or HTTP(S) URLs.
+pub struct VersionMapResolver {
+    /// The `--versions-map` value; looked up like [`resolve_versions_map_path`] does.
+    pub map: PathBuf,
+    /// Fetches the URL entries.
+    pub http: HttpResolver,
+    /// Reads the path entries.
+    pub files: FileResolver,
+}
+
+impl SchemaResolver for VersionMapResolver {
+    fn by_version(&self, request: &SchemaRequest) -> Result<Option<ResolvedSchema>, String> {
+        let Some(version) = request.spec_version else {
+            return Ok(None);
+        };
+        let map_path = resolve_versions_map_path(&self.map, request.input)?;
+        let target = schema_uri_from_version_map(&map_path, version)?;
+        if is_url(&target) {
+            return self.http.by_uri(&target);
+        }
+        self.files.by_uri(&target)
+    }
+}
+
+/// Compiles a resolved schema. Unless the schema declares its own `$id`, it is given its location
+/// as `$id`, so relative `$ref`s to other files or URLs resolve next to it.
+/// JSON Schema drafts that `--draft` can enforce.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaDraft {
+    #[value(name = "7")]
+    Draft7,
+    #[value(name = "2019-09")]
+    Draft201909,
+    #[value(name = "2020-12")]
+    Draft202012,
+}
+
+impl SchemaDraft {
+    /// The name used by `--draft`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaDraft::Draft7 => "7",
+            SchemaDraft::Draft201909 => "2019-09",
+            SchemaDraft::Draft202012 => "2020-12",
+        }
+    }
+
+    fn draft(self) -> Draft {
+        match self {
+            SchemaDraft::Draft7 => Draft::Draft7,
+            SchemaDraft::Draft201909 => Draft::Draft201909,
+            SchemaDraft::Draft202012 => Draft::Draft202012,
+        }
+    }
+}
+
+/// Accepts `draft: 7` as well as `draft: "2020-12"` in the configuration file.
+impl<'de> Deserialize<'de> for SchemaDraft {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = match serde_yaml::Value::deserialize(deserializer)? {
+            serde_yaml::Value::Number(n) => n.to_string(),
+            serde_yaml::Value::String(s) => s,
+            other => format!("{other:?}"),
+        };
+        SchemaDraft::from_str(&name, false).map_err(|_| {
+            serde::de::Error::custom(format!(
+                "unknown draft '{name}', expected one of: 7, 2019-09, 2020-12"
+            ))
+        })
+    }
+}
+
+/// The draft a schema's `$schema` names: `4`, `6`, `7`, `2019-09`, `2020-12`, or the URI itself
+/// for any other meta-schema. `None` when the schema has no `$schema`.
+fn declared_draft(schema: &JsonValue) -> Option<String> {
+    let uri = schema.get("$schema")?.as_str()?;
+    let known = [
+        ("json-schema.org/draft-04/schema", "4"),
+        ("json-schema.org/draft-06/schema", "6"),
+        ("json-schema.org/draft-07/schema", "7"),
+        ("json-schema.org/draft/2019-09/schema", "2019-09"),
+        ("json-schema.org/draft/2020-12/schema", "2020-12"),
+    ];
+    let normalized = uri
+        .trim_end_matches('#')
+        .trim_start_matches("http://")
+        .trim_start_matches("https://");
+    Some(
+        known
+            .iter()
+            .find(|(known, _)| normalized == *known)
+            .map_or(uri, |(_, name)| name)
+            .to_string(),
+    )
+}
+
+/// Compiles a resolved schema. With `draft`, the schema is compiled as that draft and must not
+/// declare another one in `$schema`. `formats` are checked in addition to the standard formats,
+/// with format validation switched on for every draft.
+pub fn compile(
+    resolved: &ResolvedSchema,
+    cache: &SchemaCache,
+    fetcher: &Fetcher,
+    draft: Option<SchemaDraft>,
+    formats: &[CustomFormat],
+) -> Result<JSONSchema, String> {
+    let mut options = JSONSchema::options();
+    if let Some(draft) = draft {
+        if let Some(declared) = declared_draft(&resolved.schema) {
+            if declared != draft.name() {
+                return Err(format!(
+                    "Error: {} declares JSON Schema draft {declared} in $schema, but draft {} is enforced",
+                    resolved.origin,
+                    draft.name()
+                ));
+            }
+        }
+        options.with_draft(draft.draft());
+    }
+    for (name, check) in formats {
+        options.with_format(name, *check);
+    }
+    if !formats.is_empty() {
+        options.should_validate_formats(true);
+    }
+    let mut schema = resolved.schema.clone();
+    if let (Some(base), Some(root)) = (&resolved.base, schema.as_object_mut()) {
+        if !root.contains_key("$id") && !root.contains_key("id") {
+            root.insert("$id".to_string(), base.as_str().into());
+        }
+    }
+    options
+        .with_resolver(ExternalRefs {
+            files: FileResolver::new(cache.clone()),
+            http: HttpResolver::new(cache.clone(), fetcher.clone()),
+        })
+        .compile(&schema)
+        .map_err(|e| format!("Error: schema document is invalid: {e}"))
+}
+
+/// Loads the documents that `$ref`s outside of a schema point to.
+struct ExternalRefs {
+    files: FileResolver,
+    http: HttpResolver,
+}
+
+impl jsonschema::SchemaResolver for ExternalRefs {
+    fn resolve(
+        &self,
+        _root_schema: &JsonValue,
+        url: &Url,
+        original_reference: &str,
+    ) -> Result<Arc<JsonValue>, SchemaResolverError> {
+        let mut document = url.clone();
+        document.set_fragment(None);
+        let resolved = match url.scheme() {
+            "file" => {
+                let path = url.to_file_path().map_err(|()| {
+                    SchemaResolverError::msg(format!("invalid file URL in $ref '{url}'"))
+                })?;
+                self.files.by_uri(&path.display().to_string())
+            }
+            "http" | "https" => self.http.by_uri(document.as_str()),
+            // `json-schema:///…` is what a relative reference becomes in a schema without a location.
+            _ => Err(format!(
+                "Error: cannot resolve $ref '{original_reference}': the schema has no location to resolve it against"
+            )),
+        };
+        match resolved {
+            Ok(Some(resolved)) => Ok(Arc::new(resolved.schema)),
+            Ok(None) => Err(SchemaResolverError::msg(format!("no schema at '{url}'"))),
+            Err(msg) => Err(SchemaResolverError::msg(
+                msg.trim_start_matches("Error: ").to_string(),
+            )),
+        }
+    }
+}
+
+/// Loads `version_map.yaml` and returns the schema for the provided version: a URL as written,
+/// or a path resolved relative to the directory containing the map file.
+pub fn schema_uri_from_version_map(map_path: &Path, version: &str) -> Result<String, String> {
+    let map_text = fs::read_to_string(map_path).map_err(|e| {
+        format!(
+            "Error: failed to read version map {}: {e}",
+            map_path.display()
+        )
+    })?;
+
+    let map: HashMap<String, String> = serde_yaml::from_str(&map_text).map_err(|e| {
+        format!(
+            "Error: {} is not valid YAML mapping 'version: path': {e}",
+            map_path.display()
+        )
+    })?;
+
+    let Some(target) = map.get(version) else {
+        let mut keys: Vec<&str> = map.keys().map(|s| s.as_str()).collect();
+        keys.sort_unstable();
+        return Err(format!(
+            "Error: version '{}' was not found in {}.\nAvailable versions: {}",
+            version,
+            map_path.display(),
+            if keys.is_empty() {
+                "(no entries)".into()
+            } else {
+                keys.join(", ")
+            }
+        ));
+    };
+
+    Ok(map_target(map_path, target))
+}
+
+/// A version map entry as a URI: URLs and absolute paths as written, other paths relative to
+/// the map's directory.
+pub fn map_target(map_path: &Path, target: &str) -> String {
+    if is_url(target) || Path::new(target).is_absolute() {
+        target.to_string()
+    } else {
+        map_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(target)
+            .display()
+            .to_string()
+    }
+}
+
+/// Parses a schema. Tries JSON first; if that fails, attempts YAML and converts it to JSON.
+fn parse_schema(text: &str, origin: &str) -> Result<JsonValue, String> {
+    // Try JSON first…
+    if let Ok(v) = serde_json::from_str::<JsonValue>(text) {
+        return Ok(v);
+    }
+    // …and fall back to YAML -> JSON
+    let y: serde_yaml::Value = serde_yaml::from_str(text)
+        .map_err(|e| format!("Error: schema file {origin} is neither valid JSON nor YAML: {e}"))?;
+    serde_json::to_value(y)
+        .map_err(|e| format!("Error: converting schema {origin} from YAML to JSON failed: {e}"))
+}
@@ -0,0 +1,230 @@
+//! Custom rules written as Rhai scripts, for organization-specific checks too small to be worth a
+//! Rust rule. Every `*.rhai` file of the `rule_scripts` directory is one rule named after the file;
+//! it declares its ID prefix and the IDs it reports as constants and defines `check(doc)`, which
+//! returns the findings on the spec as an array of `#{code, message, at}` maps:
+//!
+//! ```rhai
+//! const ID = "ACME01";
+//! const CODES = #{ ACME010: "phase without an owner" };
+//!
+//! fn check(doc) {
+//!     let findings = [];
+//!     for name in doc.implementation.phase_contracts.keys() {
+//!         if !("owner" in doc.implementation.phase_contracts[name]) {
+//!             findings.push(#{ code: "ACME010", message: `phase ${name} has no owner`,
+//!                              at: `/implementation/phase_contracts/${name}` });
+//!         }
+//!     }
+//!     findings
+//! }
+//! ```
+//!
+//! The rules are registered after the built-in ones and are configured, selected and suppressed
+//! like them.
+
+use crate::{
+    diagnostics::{Diagnostic, Severity},
+    rules::{Diagnostics, Rule, RuleRegistry, SpecModel},
+};
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Operations a script may run per spec before it is stopped, so that a runaway loop fails the
+/// rule instead of hanging the run.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// The constants a script declares about itself.
+#[derive(Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct Header {
+    id: String,
+    codes: BTreeMap<String, String>,
+    label: Option<String>,
+    category: Option<String>,
+    severity: Option<Severity>,
+}
+
+/// A finding returned by `check`.
+#[derive(Deserialize)]
+struct ScriptFinding {
+    code: String,
+    message: String,
+    /// JSON Pointer of the node the finding is about.
+    #[serde(default)]
+    at: Option<String>,
+}
+
+/// A rule implemented by a Rhai script. Its strings live as long as the process, like the rule
+/// registry that holds it.
+struct ScriptRule {
+    name: &'static str,
+    id: &'static str,
+    label: &'static str,
+    category: &'static str,
+    severity: Severity,
+    /// The declared `CODES`, then the rule ID itself, under which failures of the script are
+    /// reported.
+    codes: Vec<(&'static str, &'static str)>,
+    /// `sha256:` and the start of the script's hash.
+    version: &'static str,
+    engine: Engine,
+    ast: AST,
+}
+
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+impl ScriptRule {
+    fn load(path: &Path) -> Result<Self, String> {
+        let failed = |e: String| format!("Error: invalid rule script {}: {e}", path.display());
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| failed("the file name is not valid UTF-8".to_string()))?;
+        let text = crate::encoding::read(path).map_err(failed)?.text;
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        // The default nesting limits of debug builds are too low for ordinary check functions.
+        engine.set_max_expr_depths(64, 64);
+        let ast = engine.compile(&text).map_err(|e| failed(e.to_string()))?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "check" && f.params.len() == 1)
+        {
+            return Err(failed("it does not define check(doc)".to_string()));
+        }
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| failed(e.to_string()))?;
+        let constants: Map = scope
+            .iter()
+            .map(|(name, _, value)| (name.into(), value))
+            .collect();
+        let header: Header = rhai::serde::from_dynamic(&Dynamic::from_map(constants))
+            .map_err(|e| failed(format!("{e} (it must declare const ID and const CODES)")))?;
+        if let Some(code) = header
+            .codes
+            .keys()
+            .find(|code| !code.starts_with(&header.id))
+        {
+            return Err(failed(format!(
+                "{code} does not start with the rule ID {}",
+                header.id
+            )));
+        }
+
+        let id = leak(header.id);
+        let mut codes: Vec<(&'static str, &'static str)> = header
+            .codes
+            .into_iter()
+            .map(|(code, summary)| (leak(code), leak(summary)))
+            .collect();
+        codes.push((id, "the rule script failed"));
+        let hash = format!("{:x}", Sha256::digest(&text));
+        Ok(Self {
+            name: leak(name.to_string()),
+            id,
+            label: leak(
+                header
+                    .label
+                    .unwrap_or_else(|| name.replace(['-', '_'], " ")),
+            ),
+            category: leak(header.category.unwrap_or_else(|| "custom".to_string())),
+            severity: header.severity.unwrap_or(Severity::Error),
+            codes,
+            version: leak(format!("sha256:{}", &hash[..12])),
+            engine,
+            ast,
+        })
+    }
+
+    fn run(&self, spec: &SpecModel) -> Result<Vec<ScriptFinding>, String> {
+        let doc = rhai::serde::to_dynamic(spec.doc).map_err(|e| e.to_string())?;
+        let result: Dynamic = self
+            .engine
+            .call_fn(&mut Scope::new(), &self.ast, "check", (doc,))
+            .map_err(|e| e.to_string())?;
+        rhai::serde::from_dynamic(&result)
+            .map_err(|e| format!("check(doc) must return an array of #{{code, message, at}}: {e}"))
+    }
+}
+
+impl Rule for ScriptRule {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn label(&self) -> &'static str {
+        self.label
+    }
+
+    fn category(&self) -> &'static str {
+        self.category
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        self.codes.clone()
+    }
+
+    fn version(&self) -> &'static str {
+        self.version
+    }
+
+    fn check(&self, spec: &SpecModel, diagnostics: &mut Diagnostics) {
+        let findings = match self.run(spec) {
+            Ok(findings) => findings,
+            Err(e) => {
+                let message = format!("rule script {} failed: {e}", self.name);
+                return diagnostics.push(Diagnostic::error(self.id, message));
+            }
+        };
+        for finding in findings {
+            let diagnostic = match self.codes.iter().find(|(code, _)| *code == finding.code) {
+                Some((code, _)) => Diagnostic::new(code, self.severity, finding.message),
+                None => Diagnostic::error(
+                    self.id,
+                    format!(
+                        "rule script {} reported {}, which its CODES do not declare",
+                        self.name, finding.code
+                    ),
+                ),
+            };
+            diagnostics.push(match finding.at {
+                Some(at) => diagnostic.at(at),
+                None => diagnostic,
+            });
+        }
+    }
+}
+
+/// Registers the rule of every `*.rhai` file in `dir`, in file name order.
+pub fn register(registry: &mut RuleRegistry, dir: &Path) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| {
+        format!(
+            "Error: failed to read rule scripts from {}: {e}",
+            dir.display()
+        )
+    })?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        registry.register(Box::new(ScriptRule::load(&path)?))?;
+    }
+    Ok(())
+}
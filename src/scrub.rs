@@ -0,0 +1,260 @@
+//! `scrub`: a copy of a spec with the values at sensitive paths replaced by placeholders, so that a
+//! failing spec can be shared in a bug report without leaking internal data.
+//!
+//! Sensitive paths are JSON Pointer globs (`/implementation/phase_contracts/*/inputs/*/name`; `*`
+//! matches one segment, `**` any number). Every word of a string under a matched path is replaced by
+//! a placeholder (`redacted_1`, digits by as many digits), the same word by the same placeholder
+//! everywhere, so that references between fields keep pointing at each other. Numbers become the
+//! schema's `minimum` (or 0), and values the schema restricts with `enum`/`const` are kept. Member
+//! names matched by a pattern ending in `*` are replaced too, unless the schema declares them.
+
+use crate::{
+    config::glob_match,
+    extract_spec_version, parse_documents, read_input,
+    schemas::SchemaRequest,
+    serialize_documents,
+    usage::{item_schemas, SchemaWalker},
+    Args, InputFormat,
+};
+use regex::{Captures, Regex};
+use serde_json::{Map, Value as JsonValue};
+use std::{collections::HashMap, fs, path::Path, process::ExitCode};
+
+/// Prints the scrubbed `file`, or writes it to `output`. `patterns` come from `--path` and the
+/// `scrub_paths` configuration setting.
+pub fn scrub(args: &Args, file: &Path, patterns: &[String], output: Option<&Path>) -> ExitCode {
+    let patterns: Vec<&str> = patterns
+        .iter()
+        .chain(&args.settings.scrub_paths)
+        .map(String::as_str)
+        .collect();
+    if patterns.is_empty() {
+        failln!("Error: no sensitive paths to scrub; pass --path or set scrub_paths in the configuration file");
+        return ExitCode::from(1);
+    }
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let documents = match read_input(file)
+        .and_then(|text| parse_documents(&text, format).map_err(|e| format!("Error: {e}")))
+    {
+        Ok(documents) => documents,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut scrubber = Scrubber {
+        patterns,
+        placeholders: HashMap::new(),
+        word: Regex::new(r"[A-Za-z0-9_]+").unwrap(),
+    };
+    let mut scrubbed = Vec::new();
+    for (index, doc) in documents.into_iter().enumerate() {
+        let schema = document_schema(args, file, &doc);
+        let schema = match &schema {
+            Ok(schema) => schema,
+            Err(msg) => {
+                failln!("{msg}");
+                return ExitCode::from(1);
+            }
+        };
+        let mut walker = SchemaWalker::new(&schema.schema);
+        let nodes = walker.applicable(&schema.schema);
+        let doc = scrubber.scrub(&mut walker, &nodes, &doc, "", false);
+        if let Ok(compiled) = args.compiled_schema(schema) {
+            if let Err(errors) = compiled.validate(&doc) {
+                errln!(
+                    "⚠️ Document {} no longer passes schema validation after scrubbing:",
+                    index + 1
+                );
+                for error in errors {
+                    errln!("  • {error} (instance: {})", error.instance_path);
+                }
+            }
+        }
+        scrubbed.push(doc);
+    }
+
+    let text = match serialize_documents(&scrubbed, format) {
+        Ok(text) => text,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    match output {
+        Some(path) => match fs::write(path, text) {
+            Ok(()) => {
+                outln!(
+                    "🧽 Wrote the scrubbed spec to {} ({} distinct word(s) replaced).",
+                    path.display(),
+                    scrubber.placeholders.len()
+                );
+                ExitCode::from(0)
+            }
+            Err(e) => {
+                failln!("Error: failed to write {}: {e}", path.display());
+                ExitCode::from(1)
+            }
+        },
+        None => {
+            outln!("{}", text.trim_end());
+            ExitCode::from(0)
+        }
+    }
+}
+
+struct Scrubber<'a> {
+    patterns: Vec<&'a str>,
+    /// Placeholder of every word replaced so far.
+    placeholders: HashMap<String, String>,
+    word: Regex,
+}
+
+impl Scrubber<'_> {
+    /// `value` (at `pointer`, described by the schema `nodes`) with sensitive values replaced.
+    /// `sensitive` is set below a matched path.
+    fn scrub<'s>(
+        &mut self,
+        walker: &mut SchemaWalker<'s>,
+        nodes: &[&'s JsonValue],
+        value: &JsonValue,
+        pointer: &str,
+        sensitive: bool,
+    ) -> JsonValue {
+        let sensitive = sensitive || self.matches(pointer);
+        match value {
+            JsonValue::Object(map) => {
+                let mut scrubbed = Map::new();
+                for (key, item) in map {
+                    let declared: Vec<&JsonValue> = nodes
+                        .iter()
+                        .filter_map(|node| node.get("properties")?.get(key))
+                        .collect();
+                    let is_declared = !declared.is_empty();
+                    let subs = if is_declared {
+                        declared
+                    } else {
+                        nodes
+                            .iter()
+                            .flat_map(|node| walker.map_value_schemas_for(node, key))
+                            .collect()
+                    };
+                    let item_nodes: Vec<&JsonValue> = subs
+                        .into_iter()
+                        .flat_map(|sub| walker.applicable(sub))
+                        .collect();
+                    let escaped = key.replace('~', "~0").replace('/', "~1");
+                    let item_pointer = format!("{pointer}/{escaped}");
+                    let item = self.scrub(walker, &item_nodes, item, &item_pointer, sensitive);
+                    // A pattern ending in `*` also covers the member names it matches, except for
+                    // the properties the schema declares.
+                    let key = if !is_declared && self.matches_key(&item_pointer) {
+                        self.replace_words(key)
+                    } else {
+                        key.clone()
+                    };
+                    scrubbed.insert(key, item);
+                }
+                JsonValue::Object(scrubbed)
+            }
+            JsonValue::Array(items) => {
+                let item_nodes: Vec<&JsonValue> = nodes
+                    .iter()
+                    .flat_map(|node| item_schemas(node))
+                    .flat_map(|sub| walker.applicable(sub))
+                    .collect();
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let item_pointer = format!("{pointer}/{i}");
+                        self.scrub(walker, &item_nodes, item, &item_pointer, sensitive)
+                    })
+                    .collect()
+            }
+            JsonValue::String(_) | JsonValue::Number(_) if sensitive => {
+                if nodes
+                    .iter()
+                    .any(|node| node.get("enum").is_some() || node.get("const").is_some())
+                {
+                    return value.clone();
+                }
+                match value {
+                    JsonValue::String(text) => JsonValue::String(self.replace_words(text)),
+                    _ => placeholder_number(value, nodes),
+                }
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn matches(&self, pointer: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, pointer))
+    }
+
+    fn matches_key(&self, pointer: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.ends_with('*') && glob_match(pattern, pointer))
+    }
+
+    /// `text` with every word replaced by its placeholder.
+    fn replace_words(&mut self, text: &str) -> String {
+        let word = self.word.clone();
+        word.replace_all(text, |captures: &Captures| {
+            let original = &captures[0];
+            let next = self.placeholders.len() + 1;
+            self.placeholders
+                .entry(original.to_string())
+                .or_insert_with(|| {
+                    if original.bytes().all(|b| b.is_ascii_digit()) {
+                        // As many digits as the original, so that lengths and formats hold.
+                        let width = original.len().min(18);
+                        format!("{:0width$}", next as u64 % 10u64.pow(width as u32))
+                    } else {
+                        format!("redacted_{next}")
+                    }
+                })
+                .clone()
+        })
+        .into_owned()
+    }
+}
+
+/// The schema's `minimum` for a number, or 0, keeping integers integers.
+fn placeholder_number(value: &JsonValue, nodes: &[&JsonValue]) -> JsonValue {
+    let minimum = nodes
+        .iter()
+        .find_map(|node| node.get("minimum").filter(|m| m.is_number()));
+    match minimum {
+        Some(minimum) if value.is_i64() || value.is_u64() => minimum
+            .as_f64()
+            .map_or(JsonValue::from(0), |m| JsonValue::from(m.ceil() as i64)),
+        Some(minimum) => minimum.clone(),
+        None if value.is_i64() || value.is_u64() => JsonValue::from(0),
+        None => JsonValue::from(0.0),
+    }
+}
+
+/// The schema `doc` is validated against.
+fn document_schema(
+    args: &Args,
+    file: &Path,
+    doc: &JsonValue,
+) -> Result<crate::schemas::ResolvedSchema, String> {
+    let spec_version = match &args.spec_version {
+        Some(version) => Some(version.clone()),
+        None => extract_spec_version(doc).map_err(|e| format!("Error: {e}"))?,
+    };
+    let request = SchemaRequest {
+        input: file,
+        spec_version: spec_version.as_deref(),
+    };
+    let explicit = args.schema.as_ref().map(|p| p.to_string_lossy());
+    args.schemas.resolve(explicit.as_deref(), &request)
+}
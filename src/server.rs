@@ -0,0 +1,917 @@
+//! `serve`: a long-running HTTP server that validates specs submitted over the network, for
+//! platforms that validate specs entered in a web UI. Schemas are resolved and compiled once and
+//! reused by later requests.
+//!
+//! - `POST /validate`: the body is a spec (YAML by default, JSON or TOML by `Content-Type` or the
+//!   `format` parameter); the `spec_version` and `name` query parameters stand for
+//!   `--spec-version` and the file name findings are reported under. Responds with the findings as
+//!   `--format json` renders them.
+//! - `POST /tenants/<name>/validate`: the same, with the configuration of tenant `<name>`.
+//! - `GET /health`: responds `{"status": "ok"}` and the names of the tenants.
+//! - `GET /metrics`: hit and miss counts of the caches, in the Prometheus text format.
+//!
+//! Tenants are named configuration files (`--tenant NAME=CONFIG`), each with its own schemas,
+//! version map and rule settings; a request selects one by its path or the `X-Tenant` header, and
+//! is validated with the configuration the server was started with otherwise. With `--audit-log`,
+//! every validation request is recorded in the [audit log](crate::audit_log).
+//!
+//! Every connection serves one request. Connections are read and answered asynchronously on a
+//! single executor thread, so a slow client only holds a task, and at most [`MAX_CONNECTIONS`] are
+//! open at once; a request must arrive within [`READ_TIMEOUT`]. Validation blocks (the rules,
+//! schema downloads, the audit log), so it never runs on the executor: requests are handed to a
+//! fixed pool of `--jobs` validator threads, up to [`MAX_PENDING`] wait for one and further ones
+//! are answered with 503.
+//!
+//! Requests come from the network, so they cannot make the server read files or fetch URLs of
+//! their choosing: schemas are only selected by `spec_version` from the version map (there is no
+//! `schema` parameter), `name` must be a relative path that stays below the working directory, and
+//! libraries are only looked up in the library directories, never by a `path` or `url` of the spec.
+//! Errors name files [relative](relative_paths) to the tenant's directory, and the request head is
+//! limited to [`MAX_HEADERS`] lines of at most [`MAX_LINE`] bytes.
+//!
+//! Results are cached by the hash of the spec, the hashes of the schemas it resolves to and the
+//! tenant, so a spec uploaded again is answered without running the rules; entries expire after
+//! `--result-cache-ttl` and the oldest is dropped when `--result-cache-size` entries are held.
+
+use crate::{
+    audit_log::AuditLog, cache, encoding, locations, output, parse_documents, reporter,
+    resolve_schema, rules, validate_document, Args, InputFormat, Source,
+};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    env,
+    panic::{self, AssertUnwindSafe},
+    path::{Component, Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        mpsc::{self, SyncSender, TrySendError},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    runtime,
+    sync::{oneshot, Semaphore},
+    time,
+};
+
+/// Largest spec accepted.
+const MAX_BODY: usize = 10 * 1024 * 1024;
+
+/// Longest line of the request head (the request line or a header) accepted.
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most headers accepted in one request.
+const MAX_HEADERS: usize = 32;
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long sending a response may block on a client that does not read it.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Requests waiting for a validator thread; further ones are answered with 503.
+const MAX_PENDING: usize = 64;
+
+/// Connections open at once (each may hold a body of up to [`MAX_BODY`] bytes); further ones are
+/// answered with 503.
+const MAX_CONNECTIONS: usize = 256;
+
+/// Header naming the tenant whose configuration validates a request.
+const TENANT_HEADER: &str = "x-tenant";
+
+/// Header naming who submitted a request, recorded in the audit log.
+const USER_HEADER: &str = "x-user";
+
+/// A parsed HTTP request.
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    content_type: Option<String>,
+    /// The value of the `X-Tenant` header.
+    tenant: Option<String>,
+    /// The value of the `X-User` header.
+    user: Option<String>,
+    /// Address of the client.
+    client: String,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response: status, content type and body.
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: &JsonValue) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+/// What the server was started with.
+struct Server<'a> {
+    args: &'a Args,
+    tenants: Vec<Tenant>,
+    audit: Option<AuditLog>,
+    results: ResultCache,
+}
+
+/// The result of validating the spec of a request.
+#[derive(Clone)]
+struct Outcome {
+    valid: bool,
+    documents: usize,
+    findings: Vec<JsonValue>,
+    errors: Vec<String>,
+    /// `{spec_version, schema, schema_sha256}` of every document whose schema was found.
+    schemas: Vec<JsonValue>,
+    /// Whether the outcome was answered from the result cache.
+    cached: bool,
+}
+
+/// Outcomes of earlier requests by their key, with the time they were computed.
+pub struct ResultCache {
+    ttl: Duration,
+    /// Entries held at most; 0 disables the cache.
+    capacity: usize,
+    entries: Mutex<HashMap<String, (Instant, Outcome)>>,
+}
+
+impl ResultCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// The outcome stored under `key` if it has not expired.
+    fn get(&self, key: &str) -> Option<Outcome> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let found = match entries.get(key) {
+            Some((stored, outcome)) if stored.elapsed() < self.ttl => Some(outcome.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        cache::VALIDATION_RESULTS.count(found.is_some());
+        found
+    }
+
+    /// Stores `outcome` under `key`, dropping expired entries, then the oldest, to make room.
+    fn insert(&self, key: String, outcome: &Outcome) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        }
+        while entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => entries.remove(&oldest),
+                None => break,
+            };
+        }
+        entries.insert(key, (Instant::now(), outcome.clone()));
+    }
+}
+
+/// A named configuration requests can select.
+struct Tenant {
+    name: String,
+    args: Args,
+    /// The directory of the configuration file, which paths in responses are relative to.
+    root: PathBuf,
+    /// Rule settings of the configuration, active while its requests are validated.
+    profile: &'static rules::Profile,
+}
+
+impl Tenant {
+    /// The command-line options of the server, `args`, with `config` in place of its
+    /// configuration file.
+    fn load(args: &Args, name: &str, config: &Path) -> Result<Self, String> {
+        let profile = rules::Profile::new();
+        let mut args = args.command_line();
+        args.config = Some(config.to_path_buf());
+        rules::with_profile(profile, || args.apply_config())
+            .map_err(|e| format!("{e} (tenant '{name}')"))?;
+        let root = std::path::absolute(config)
+            .ok()
+            .and_then(|config| config.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        Ok(Self {
+            name: name.to_string(),
+            args,
+            root,
+            profile,
+        })
+    }
+}
+
+/// Parses a `--tenant` value, `NAME=CONFIG`.
+pub fn parse_tenant(value: &str) -> Result<(String, PathBuf), String> {
+    let Some((name, config)) = value.split_once('=') else {
+        return Err("expected NAME=CONFIG".to_string());
+    };
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid tenant name '{name}' (use letters, digits, '-' and '_')"
+        ));
+    }
+    Ok((name.to_string(), PathBuf::from(config)))
+}
+
+/// Serves requests on `host:port` until the process is stopped.
+pub fn serve(
+    args: &Args,
+    host: &str,
+    port: u16,
+    tenants: &[(String, PathBuf)],
+    audit: Option<AuditLog>,
+    results: ResultCache,
+) -> ExitCode {
+    let mut loaded: Vec<Tenant> = Vec::new();
+    for (name, config) in tenants {
+        if loaded.iter().any(|tenant| &tenant.name == name) {
+            failln!("Error: tenant '{name}' is given more than once");
+            return ExitCode::from(1);
+        }
+        match Tenant::load(args, name, config) {
+            Ok(tenant) => loaded.push(tenant),
+            Err(msg) => {
+                failln!("{msg}");
+                return ExitCode::from(1);
+            }
+        }
+    }
+    let server = &Server {
+        args,
+        tenants: loaded,
+        audit,
+        results,
+    };
+    let listener = match std::net::TcpListener::bind((host, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            failln!("Error: failed to listen on {host}:{port}: {e}");
+            return ExitCode::from(1);
+        }
+    };
+    let address = listener
+        .local_addr()
+        .map_or_else(|_| format!("{host}:{port}"), |a| a.to_string());
+    outln!("🌐 Listening on http://{address} (POST /validate, GET /health, GET /metrics).");
+    if !server.tenants.is_empty() {
+        let names: Vec<&str> = server.tenants.iter().map(|t| t.name.as_str()).collect();
+        outln!(
+            "ℹ️ Tenants (POST /tenants/<name>/validate): {}.",
+            names.join(", ")
+        );
+    }
+    let workers = match args.jobs {
+        0 => thread::available_parallelism().map_or(4, |n| n.get()),
+        jobs => jobs,
+    };
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build();
+    let runtime = match runtime {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            failln!("Error: failed to start the server: {e}");
+            return ExitCode::from(1);
+        }
+    };
+    let (sender, receiver) = mpsc::sync_channel::<Job>(MAX_PENDING);
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| answer_jobs(server, &receiver));
+        }
+        runtime.block_on(accept(listener, sender));
+    });
+    ExitCode::from(0)
+}
+
+/// A request waiting for a validator thread, with where its response goes.
+struct Job {
+    request: Request,
+    reply: oneshot::Sender<Response>,
+}
+
+/// Answers the requests of `jobs` until the server stops.
+fn answer_jobs(server: &Server, jobs: &Mutex<mpsc::Receiver<Job>>) {
+    loop {
+        let next = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+        let Ok(Job { request, reply }) = next else {
+            break;
+        };
+        // A request that panics is answered with 500; the thread goes on with the next one.
+        let response = panic::catch_unwind(AssertUnwindSafe(|| route(server, &request)))
+            .unwrap_or_else(|_| Response::error(500, "failed to validate the spec"));
+        let _ = reply.send(response);
+    }
+}
+
+/// Accepts connections until the process is stopped, each handled by a task of its own.
+async fn accept(listener: std::net::TcpListener, jobs: SyncSender<Job>) {
+    let listener = listener
+        .set_nonblocking(true)
+        .and_then(|()| TcpListener::from_std(listener));
+    let listener = match listener {
+        Ok(listener) => listener,
+        Err(e) => {
+            failln!("Error: failed to listen: {e}");
+            return;
+        }
+    };
+    let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    loop {
+        let (stream, client) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                errln!("⚠️ Failed to accept a connection: {e}");
+                // Running out of file descriptors fails every accept until a connection closes.
+                time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let permit = Arc::clone(&connections).try_acquire_owned();
+        let jobs = jobs.clone();
+        tokio::spawn(async move {
+            match permit {
+                Ok(_permit) => handle(stream, client.to_string(), &jobs).await,
+                Err(_) => {
+                    let busy = Response::error(503, "the server is busy; retry later");
+                    send(stream, &busy, "-").await;
+                }
+            }
+        });
+    }
+}
+
+/// Reads one request from `stream`, has it answered and logs it.
+async fn handle(mut stream: TcpStream, client: String, jobs: &SyncSender<Job>) {
+    let started = Instant::now();
+    let read = time::timeout(
+        READ_TIMEOUT,
+        read_request(&mut BufReader::new(&mut stream), client),
+    )
+    .await;
+    let (line, response) = match read {
+        Ok(Ok(request)) => (
+            format!("{} {}", request.method, request.path),
+            answer(request, jobs).await,
+        ),
+        Ok(Err(response)) => ("-".to_string(), response),
+        Err(_) => (
+            "-".to_string(),
+            Response::error(
+                408,
+                &format!(
+                    "the request did not arrive within {} s",
+                    READ_TIMEOUT.as_secs()
+                ),
+            ),
+        ),
+    };
+    send(stream, &response, &line).await;
+    if output::verbose() {
+        outln!(
+            "ℹ️ {line} → {} in {} ms",
+            response.status,
+            started.elapsed().as_millis()
+        );
+    }
+}
+
+/// Has a validator thread answer `request`; 503 when [`MAX_PENDING`] requests already wait for
+/// one.
+async fn answer(request: Request, jobs: &SyncSender<Job>) -> Response {
+    let (reply, response) = oneshot::channel();
+    match jobs.try_send(Job { request, reply }) {
+        Ok(()) => response
+            .await
+            .unwrap_or_else(|_| Response::error(500, "failed to validate the spec")),
+        Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => {
+            Response::error(503, "the server is busy; retry later")
+        }
+    }
+}
+
+/// Writes `response` to the request `line` and closes the connection.
+async fn send(mut stream: TcpStream, response: &Response, line: &str) {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    let sent = time::timeout(WRITE_TIMEOUT, async {
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(response.body.as_bytes()).await?;
+        stream.shutdown().await
+    })
+    .await;
+    match sent {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => errln!("⚠️ Failed to send the response to {line}: {e}"),
+        Err(_) => errln!("⚠️ Failed to send the response to {line}: the client does not read it"),
+    }
+}
+
+/// One line of the request head, without its line ending; a line longer than [`MAX_LINE`] bytes
+/// is answered with `status`, naming it `what`.
+async fn read_line(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    status: u16,
+    what: &str,
+) -> Result<String, Response> {
+    let mut line = String::new();
+    let read = (&mut *reader)
+        .take(MAX_LINE as u64 + 1)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| Response::error(400, &format!("failed to read the request: {e}")))?;
+    if read == 0 {
+        return Err(Response::error(
+            400,
+            "the connection closed before the request was complete",
+        ));
+    }
+    if read > MAX_LINE && !line.ends_with('\n') {
+        return Err(Response::error(
+            status,
+            &format!("{what} is longer than {MAX_LINE} bytes"),
+        ));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+/// Reads a request from `reader`: the request line, the headers and, for `POST`, a body of
+/// `Content-Length` bytes.
+async fn read_request(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    client: String,
+) -> Result<Request, Response> {
+    let bad = |message: &str| Response::error(400, message);
+    let request_line = read_line(reader, 400, "the request line").await?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect(),
+        content_type: None,
+        tenant: None,
+        user: None,
+        client,
+        body: Vec::new(),
+    };
+
+    let mut length = None;
+    for count in 0.. {
+        let header = read_line(reader, 431, "a header line").await?;
+        if header.is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(Response::error(
+                431,
+                &format!("the request has more than {MAX_HEADERS} headers"),
+            ));
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad("malformed header"));
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                length = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| bad("invalid Content-Length"))?,
+                )
+            }
+            "content-type" => request.content_type = Some(value.to_ascii_lowercase()),
+            TENANT_HEADER => request.tenant = Some(value.to_string()),
+            USER_HEADER => request.user = Some(value.to_string()),
+            "transfer-encoding" => {
+                return Err(Response::error(
+                    411,
+                    "chunked requests are not supported; send a Content-Length",
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    if request.method == "POST" {
+        let Some(length) = length else {
+            return Err(Response::error(411, "a Content-Length is required"));
+        };
+        if length > MAX_BODY {
+            return Err(Response::error(
+                413,
+                &format!("the spec is larger than {MAX_BODY} bytes"),
+            ));
+        }
+        request.body = vec![0; length];
+        reader
+            .read_exact(&mut request.body)
+            .await
+            .map_err(|e| bad(&format!("failed to read the request body: {e}")))?;
+    }
+    Ok(request)
+}
+
+fn route(server: &Server, request: &Request) -> Response {
+    let scoped = request
+        .path
+        .strip_prefix("/tenants/")
+        .and_then(|rest| rest.strip_suffix("/validate"));
+    if let Some(name) = scoped {
+        if request.method != "POST" {
+            return Response::error(405, "method not allowed");
+        }
+        if request
+            .tenant
+            .as_deref()
+            .is_some_and(|header| header != name)
+        {
+            return Response::error(
+                400,
+                "the X-Tenant header names another tenant than the path",
+            );
+        }
+        return validate_for(server, Some(name), request);
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/validate") => validate_for(server, request.tenant.as_deref(), request),
+        ("GET", "/health") => {
+            let names: Vec<&str> = server.tenants.iter().map(|t| t.name.as_str()).collect();
+            Response::json(200, &json!({ "status": "ok", "tenants": names }))
+        }
+        ("GET", "/metrics") => Response {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: metrics(),
+        },
+        (_, "/validate" | "/health" | "/metrics") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+/// Validates the spec in the body of `request` with the configuration of `tenant`, or of the server
+/// when no tenant is named, and records the decision in the audit log.
+fn validate_for(server: &Server, tenant: Option<&str>, request: &Request) -> Response {
+    let results = &server.results;
+    let outcome = match tenant {
+        None => {
+            let root = env::current_dir().unwrap_or_default();
+            validate(server.args, None, &root, results, request)
+        }
+        Some(name) => match server.tenants.iter().find(|t| t.name == name) {
+            Some(tenant) => rules::with_profile(tenant.profile, || {
+                validate(&tenant.args, Some(name), &tenant.root, results, request)
+            }),
+            None => Err(Response::error(404, &format!("unknown tenant '{name}'"))),
+        },
+    };
+    if let Some(audit) = &server.audit {
+        if let Err(msg) = audit.record(&audit_entry(request, tenant, &outcome)) {
+            failln!("{msg}");
+            // A decision that cannot be recorded is not handed out.
+            return Response::error(500, "failed to write the audit log");
+        }
+    }
+    match outcome {
+        Ok(outcome) => Response::json(
+            200,
+            &json!({
+                "valid": outcome.valid,
+                "documents": outcome.documents,
+                "findings": outcome.findings,
+                "errors": outcome.errors,
+                "cached": outcome.cached,
+            }),
+        ),
+        Err(response) => response,
+    }
+}
+
+/// The audit log entry of a validation request: who sent which spec, the schemas it was checked
+/// against and the outcome, with counts of the findings by severity.
+fn audit_entry(
+    request: &Request,
+    tenant: Option<&str>,
+    outcome: &Result<Outcome, Response>,
+) -> JsonValue {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut entry = json!({
+        "time": time,
+        "client": request.client,
+        "user": request.user,
+        "tenant": tenant,
+        "name": request.param("name").unwrap_or("<request>"),
+        "sha256": format!("{:x}", Sha256::digest(&request.body)),
+        "size": request.body.len(),
+    });
+    match outcome {
+        Ok(outcome) => {
+            let count = |severity: &str| {
+                outcome
+                    .findings
+                    .iter()
+                    .filter(|f| f["severity"] == severity)
+                    .count()
+            };
+            entry["outcome"] = if outcome.valid { "valid" } else { "invalid" }.into();
+            entry["status"] = 200.into();
+            entry["cached"] = outcome.cached.into();
+            entry["documents"] = outcome.schemas.clone().into();
+            entry["findings"] = json!({
+                "error": count("error"),
+                "warning": count("warning"),
+                "info": count("info"),
+            });
+            entry["errors"] = outcome.errors.clone().into();
+        }
+        Err(response) => {
+            entry["outcome"] = "rejected".into();
+            entry["status"] = response.status.into();
+            // Rejections are answered with `{"error": message}`.
+            let body: JsonValue = serde_json::from_str(&response.body).unwrap_or_default();
+            entry["errors"] = json!([body["error"]]);
+        }
+    }
+    entry
+}
+
+/// Validates the spec in the body of `request` with the configuration of `tenant`, or answers it
+/// from `results`. Specs that cannot be read or parsed are reported as invalid, with the reason
+/// under `errors`; the paths in the reasons are relative to the tenant's `root`.
+fn validate(
+    args: &Args,
+    tenant: Option<&str>,
+    root: &Path,
+    results: &ResultCache,
+    request: &Request,
+) -> Result<Outcome, Response> {
+    let format = match request.param("format") {
+        Some("yaml") | None => match request.content_type.as_deref() {
+            Some(t) if t.contains("json") => InputFormat::Json,
+            Some(t) if t.contains("toml") => InputFormat::Toml,
+            _ => InputFormat::Yaml,
+        },
+        Some("json") => InputFormat::Json,
+        Some("toml") => InputFormat::Toml,
+        Some(other) => {
+            return Err(Response::error(
+                400,
+                &format!("unknown format '{other}' (expected yaml, json or toml)"),
+            ))
+        }
+    };
+    if request.param("schema").is_some() {
+        return Err(Response::error(
+            400,
+            "the schema parameter is not supported; select a schema of the version map with \
+             spec_version",
+        ));
+    }
+    let name = request.param("name").unwrap_or("<request>");
+    // The name also locates the version map next to the spec; keep it below the working directory.
+    if !Path::new(name)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(Response::error(
+            400,
+            &format!("invalid name '{name}' (use a relative path without '..')"),
+        ));
+    }
+    let invalid = |error: String| {
+        Ok(Outcome {
+            valid: false,
+            documents: 0,
+            findings: Vec::new(),
+            errors: vec![relative_paths(&error, root)],
+            schemas: Vec::new(),
+            cached: false,
+        })
+    };
+
+    let text = match encoding::decode(request.body.clone()) {
+        Ok(decoded) => decoded.text,
+        Err(e) => {
+            return invalid(format!("Error: failed to read the spec: {e}"));
+        }
+    };
+    let documents = match parse_documents(&text, format) {
+        Ok(documents) => documents,
+        Err(e) => return invalid(format!("Error: {e}")),
+    };
+    let all_locations = match format {
+        InputFormat::Yaml | InputFormat::Json => locations::yaml_documents(&text),
+        InputFormat::Toml => Vec::new(),
+    };
+    let no_locations = locations::Locations::default();
+
+    let source = |index: usize| Source {
+        path: Path::new(name),
+        text: &text,
+        locations: all_locations.get(index).unwrap_or(&no_locations),
+        schema: None,
+        spec_version: request.param("spec_version"),
+        previous: None,
+    };
+    // Resolved ahead of validation (from the caches) for the cache key and the audit log; failures
+    // are reported by validation.
+    let mut schemas = Vec::new();
+    for (index, doc) in documents.iter().enumerate() {
+        if let Ok((spec_version, schema)) = resolve_schema(args, &source(index), doc) {
+            schemas.push(json!({
+                "spec_version": spec_version,
+                "schema": schema.origin,
+                "schema_sha256": format!("{:x}", Sha256::digest(schema.schema.to_string())),
+            }));
+        }
+    }
+    // Findings carry the name and the lines of the spec, so the key covers the exact text.
+    let key = json!({
+        "tenant": tenant,
+        "format": format!("{format:?}"),
+        "name": name,
+        "spec_version": request.param("spec_version"),
+        "sha256": format!("{:x}", Sha256::digest(&text)),
+        "schemas": schemas,
+    })
+    .to_string();
+    if let Some(outcome) = results.get(&key) {
+        return Ok(Outcome {
+            cached: true,
+            ..outcome
+        });
+    }
+
+    let (mut valid, mut findings, mut errors) = (true, Vec::new(), Vec::new());
+    for (index, doc) in documents.iter().enumerate() {
+        let (code, captured) = output::capture(|| validate_document(args, &source(index), doc));
+        valid &= code == ExitCode::SUCCESS;
+        errors.extend(captured.errors().iter().map(|e| relative_paths(e, root)));
+        findings.extend(captured.findings().iter().map(reporter::to_json));
+    }
+    let outcome = Outcome {
+        valid,
+        documents: documents.len(),
+        findings,
+        errors,
+        schemas,
+        cached: false,
+    };
+    results.insert(key, &outcome);
+    Ok(outcome)
+}
+
+/// `text` with the absolute paths in it made relative to `root`, so that responses do not reveal
+/// where the server keeps its files. A path is a word starting with `/` that names an existing
+/// file or shares its top directory with `root`, which leaves JSON pointers alone.
+fn relative_paths(text: &str, root: &Path) -> String {
+    let roots: Vec<PathBuf> = [Some(root.to_path_buf()), root.canonicalize().ok()]
+        .into_iter()
+        .flatten()
+        .collect();
+    fn top(path: &Path) -> Option<Component<'_>> {
+        path.components().nth(1)
+    }
+    let is_delimiter = |c: char| c.is_whitespace() || "'\"`(),:".contains(c);
+    text.split_inclusive(is_delimiter)
+        .map(|piece| {
+            let word = piece.trim_end_matches(is_delimiter);
+            let path = Path::new(word);
+            let known = Path::new(word.trim_end_matches('.')).exists()
+                || roots.iter().any(|root| top(root) == top(path));
+            if !word.starts_with('/') || !known {
+                return piece.to_string();
+            }
+            let relative = roots
+                .iter()
+                .map(|root| relative_to(path, root))
+                .min_by_key(|relative| relative.as_os_str().len())
+                .unwrap_or_else(|| path.to_path_buf());
+            format!("{}{}", relative.display(), &piece[word.len()..])
+        })
+        .collect()
+}
+
+/// The absolute `path` relative to the absolute directory `root`.
+fn relative_to(path: &Path, root: &Path) -> PathBuf {
+    let mut path = path.components().peekable();
+    let mut root = root.components().peekable();
+    while path.peek().is_some() && path.peek() == root.peek() {
+        path.next();
+        root.next();
+    }
+    let relative: PathBuf = root.map(|_| Component::ParentDir).chain(path).collect();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+/// The cache statistics in the Prometheus text exposition format.
+fn metrics() -> String {
+    let mut text = String::new();
+    for (metric, help, pick) in [
+        (
+            "program_verify_cache_hits_total",
+            "Lookups answered from the cache.",
+            (|(hits, _)| hits) as fn((u64, u64)) -> u64,
+        ),
+        (
+            "program_verify_cache_misses_total",
+            "Lookups that had to compute the value.",
+            |(_, misses)| misses,
+        ),
+    ] {
+        text.push_str(&format!(
+            "# HELP {metric} {help}\n# TYPE {metric} counter\n"
+        ));
+        for stats in cache::STATISTICS
+            .iter()
+            .copied()
+            .chain([&cache::VALIDATION_RESULTS])
+        {
+            text.push_str(&format!(
+                "{metric}{{cache=\"{}\"}} {}\n",
+                stats.name,
+                pick(stats.counts())
+            ));
+        }
+    }
+    text
+}
@@ -0,0 +1,81 @@
+//! `schema show`: prints the schema that validating a spec would use, selected the same way as
+//! during validation (`--schema`, then `--spec-version` or the spec's `spec_version` looked up in
+//! the version map, then the embedded schema).
+
+use crate::{
+    bundle::bundled, extract_spec_version, parse_documents, read_input, schemas::SchemaRequest,
+    serialize_documents, Args, InputFormat,
+};
+use std::{path::Path, process::ExitCode};
+
+/// Prints the effective schema for `file` (or for no spec in particular), inlining every `$ref`
+/// when `bundle` is set, as YAML when `yaml` is set and as JSON otherwise. Which schema was picked
+/// and why goes to stderr.
+pub fn show(args: &Args, file: Option<&Path>, bundle: bool, yaml: bool) -> ExitCode {
+    match effective_schema(args, file, bundle, yaml) {
+        Ok(text) => {
+            outln!("{}", text.trim_end());
+            ExitCode::from(0)
+        }
+        Err(msg) => {
+            failln!("{msg}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn effective_schema(
+    args: &Args,
+    file: Option<&Path>,
+    bundle: bool,
+    yaml: bool,
+) -> Result<String, String> {
+    let declared = match file {
+        Some(file) => declared_spec_version(args, file)?,
+        None => None,
+    };
+    let spec_version = args.spec_version.clone().or(declared.clone());
+    let request = SchemaRequest {
+        input: file.unwrap_or(Path::new(".")),
+        spec_version: spec_version.as_deref(),
+    };
+    let explicit = args.schema.as_ref().map(|p| p.to_string_lossy());
+    let resolved = args.schemas.resolve(explicit.as_deref(), &request)?;
+
+    let reason = match (&args.schema, &args.spec_version, declared, file) {
+        (Some(_), ..) => "--schema".to_string(),
+        (None, Some(version), ..) => format!("--spec-version {version}"),
+        (None, None, Some(version), Some(file)) => {
+            format!("spec_version {version} of {}", file.display())
+        }
+        _ => "no spec_version".to_string(),
+    };
+    errln!("ℹ️ {} (selected by {reason})", resolved.origin);
+
+    let schema = if bundle {
+        bundled(args, resolved)?
+    } else {
+        resolved.schema
+    };
+    let format = if yaml {
+        InputFormat::Yaml
+    } else {
+        InputFormat::Json
+    };
+    serialize_documents(&[schema], format)
+}
+
+/// `spec_version` of the first document of `file` that declares one.
+fn declared_spec_version(args: &Args, file: &Path) -> Result<Option<String>, String> {
+    let format = args
+        .input_format
+        .unwrap_or_else(|| InputFormat::from_path(file));
+    let text = read_input(file)?;
+    let documents = parse_documents(&text, format).map_err(|msg| format!("Error: {msg}"))?;
+    for doc in &documents {
+        if let Some(version) = extract_spec_version(doc).map_err(|msg| format!("Error: {msg}"))? {
+            return Ok(Some(version));
+        }
+    }
+    Ok(None)
+}
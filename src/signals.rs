@@ -0,0 +1,49 @@
+//! SIGINT/SIGTERM handling. The handlers only record the signal; the supervisor thread notices it,
+//! prints what the run achieved so far and exits.
+
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Number of the first termination signal received, or 0.
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+/// A termination signal and the exit code a shell reports for it (128 + signal number).
+#[derive(Clone, Copy, Debug)]
+pub struct Signal {
+    pub name: &'static str,
+    pub exit_code: u8,
+}
+
+#[cfg(unix)]
+extern "C" fn record(signal: libc::c_int) {
+    let _ = RECEIVED.compare_exchange(0, signal, Ordering::SeqCst, Ordering::SeqCst);
+}
+
+/// Replaces the default (immediately fatal) SIGINT and SIGTERM handlers. No-op on other platforms.
+pub fn install() {
+    #[cfg(unix)]
+    {
+        let handler: extern "C" fn(libc::c_int) = record;
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            // SAFETY: the handler only performs an atomic store, which is async-signal-safe.
+            unsafe {
+                libc::signal(signal, handler as libc::sighandler_t);
+            }
+        }
+    }
+}
+
+/// The termination signal received so far, if any.
+pub fn received() -> Option<Signal> {
+    match RECEIVED.load(Ordering::SeqCst) {
+        0 => None,
+        #[cfg(unix)]
+        libc::SIGTERM => Some(Signal {
+            name: "SIGTERM",
+            exit_code: 143,
+        }),
+        _ => Some(Signal {
+            name: "SIGINT",
+            exit_code: 130,
+        }),
+    }
+}
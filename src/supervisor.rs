@@ -0,0 +1,172 @@
+//! Runs the work on a worker thread and keeps an eye on it from the main thread: enforces
+//! `--timeout`/`--file-timeout` and reacts to SIGINT/SIGTERM. In both cases the outstanding work is
+//! abandoned (the process exits without waiting for it) after printing what the run got through.
+
+use crate::{display_input, signals, Args};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Exit code of a run cancelled by a timeout (the same as coreutils `timeout`).
+pub const TIMEOUT_EXIT_CODE: u8 = 124;
+
+/// How often the supervisor checks for a termination signal.
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress of a validation run, sent by the worker to the supervising thread.
+#[derive(Debug)]
+pub enum Progress {
+    /// Every file the run is going to validate, in order. Starts a new run in `--watch` mode.
+    Planned(Vec<PathBuf>),
+    Started(PathBuf),
+    /// A file was validated; `true` when it passed.
+    Finished(PathBuf, bool),
+    Done(ExitCode),
+}
+
+/// Parses durations such as `30s`, `500ms`, `2m` or `1h`; a bare number means seconds.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{text}' (expected e.g. 30s, 500ms, 2m)"))?;
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        other => {
+            return Err(format!(
+                "unknown duration unit '{other}' in '{text}' (expected ms, s, m or h)"
+            ))
+        }
+    };
+    if seconds <= 0.0 || !seconds.is_finite() {
+        return Err(format!("duration '{text}' must be positive"));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// What the supervisor knows about the current run.
+#[derive(Default)]
+struct RunState {
+    planned: Vec<PathBuf>,
+    /// Validated files and whether they passed.
+    finished: HashMap<PathBuf, bool>,
+    /// Files being validated (several with `--jobs`) and when each started.
+    running: HashMap<PathBuf, Instant>,
+}
+
+impl RunState {
+    /// The file that has been running longest.
+    fn oldest(&self) -> Option<(&PathBuf, Instant)> {
+        self.running
+            .iter()
+            .map(|(file, since)| (file, *since))
+            .min_by_key(|(_, since)| *since)
+    }
+}
+
+/// Runs `run` on a worker thread, enforcing `--timeout` for the whole run and `--file-timeout`
+/// for every single file, and stopping with a partial summary on SIGINT/SIGTERM.
+pub fn supervise(mut args: Args, run: fn(&Args) -> ExitCode) -> ExitCode {
+    signals::install();
+    let (sender, receiver) = mpsc::channel();
+    args.progress = Some(sender.clone());
+    let (total, per_file) = (args.timeout, args.file_timeout);
+    thread::spawn(move || {
+        let code = run(&args);
+        let _ = sender.send(Progress::Done(code));
+    });
+
+    let start = Instant::now();
+    let mut state = RunState::default();
+    loop {
+        let run_deadline = total.map(|limit| (start + limit, limit, "--timeout"));
+        let file_deadline = per_file
+            .zip(state.oldest())
+            .map(|(limit, (_, since))| (since + limit, limit, "--file-timeout"));
+        let deadline = match (run_deadline, file_deadline) {
+            (Some(a), Some(b)) => Some(if b.0 < a.0 { b } else { a }),
+            (a, b) => a.or(b),
+        };
+        let wait = deadline
+            .map(|(at, _, _)| at.saturating_duration_since(Instant::now()))
+            .map_or(SIGNAL_POLL_INTERVAL, |left| left.min(SIGNAL_POLL_INTERVAL));
+
+        match receiver.recv_timeout(wait) {
+            Ok(Progress::Planned(files)) => {
+                state = RunState {
+                    planned: files,
+                    ..RunState::default()
+                };
+            }
+            Ok(Progress::Started(file)) => {
+                state.running.insert(file, Instant::now());
+            }
+            Ok(Progress::Finished(file, passed)) => {
+                state.running.remove(&file);
+                state.finished.insert(file, passed);
+            }
+            Ok(Progress::Done(code)) => return code,
+            // The worker panicked; the panic message has already been printed.
+            Err(RecvTimeoutError::Disconnected) => return ExitCode::from(1),
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(signal) = signals::received() {
+                    report_cancellation(&format!("Interrupted by {}", signal.name), &state);
+                    return ExitCode::from(signal.exit_code);
+                }
+                if let Some((at, limit, flag)) = deadline {
+                    if Instant::now() >= at {
+                        let reason = match state.oldest() {
+                            Some((file, _)) if flag == "--file-timeout" => format!(
+                                "Cancelled: {} exceeded {flag} {limit:?}",
+                                display_input(file)
+                            ),
+                            _ => format!("Cancelled: the run exceeded {flag} {limit:?}"),
+                        };
+                        report_cancellation(&reason, &state);
+                        return ExitCode::from(TIMEOUT_EXIT_CODE);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Prints why the run stopped, how far it got, and every file without a result.
+fn report_cancellation(reason: &str, state: &RunState) {
+    // Whatever the worker printed so far stays ahead of the summary.
+    let _ = io::stdout().flush();
+    if state.planned.is_empty() {
+        errln!("⏹️ {reason}.");
+        return;
+    }
+    let failed = state.finished.values().filter(|passed| !**passed).count();
+    errln!(
+        "⏹️ {reason} after {} of {} file(s) ({failed} failed).",
+        state.finished.len(),
+        state.planned.len()
+    );
+    for file in &state.planned {
+        if state.finished.contains_key(file) {
+            continue;
+        }
+        let status = if state.running.contains_key(file) {
+            "interrupted"
+        } else {
+            "not validated"
+        };
+        errln!("  • {} ({status})", display_input(file));
+    }
+}
@@ -0,0 +1,175 @@
+//! Inline suppressions: `x-verify-ignore` on spec nodes and the document-wide `meta.verify_ignore`.
+
+use crate::{
+    diagnostics::{pointer, Diagnostic},
+    rules,
+};
+use serde_json::Value as JsonValue;
+
+/// Key that silences findings about the node carrying it and everything below it.
+pub const NODE_KEY: &str = "x-verify-ignore";
+
+/// A single rule ID (or ID prefix) silenced at a node.
+struct Suppression {
+    /// Pointer of the annotated node; empty for `meta.verify_ignore`.
+    pointer: String,
+    code: String,
+    used: bool,
+}
+
+/// Suppressions collected from one spec document.
+#[derive(Default)]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Removes every suppression annotation from `doc`, so JSON Schema and the rules never see
+    /// them, and returns what was collected plus findings about malformed annotations.
+    pub fn extract(doc: &mut JsonValue) -> (Self, Vec<Diagnostic>) {
+        let mut suppressions = Suppressions::default();
+        let mut diagnostics = Vec::new();
+
+        if let Some(value) = doc
+            .get_mut("meta")
+            .and_then(|m| m.as_object_mut())
+            .and_then(|m| m.remove("verify_ignore"))
+        {
+            suppressions.add(
+                String::new(),
+                &value,
+                "/meta/verify_ignore",
+                &mut diagnostics,
+            );
+        }
+
+        let mut path = Vec::new();
+        suppressions.walk(doc, &mut path, &mut diagnostics);
+        (suppressions, diagnostics)
+    }
+
+    fn walk(&mut self, value: &mut JsonValue, path: &mut Vec<String>, out: &mut Vec<Diagnostic>) {
+        match value {
+            JsonValue::Object(map) => {
+                if let Some(annotation) = map.remove(NODE_KEY) {
+                    let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+                    let node = pointer(&segments);
+                    let at = format!("{node}/{NODE_KEY}");
+                    self.add(node, &annotation, &at, out);
+                }
+                for (key, child) in map.iter_mut() {
+                    path.push(key.clone());
+                    self.walk(child, path, out);
+                    path.pop();
+                }
+            }
+            JsonValue::Array(items) => {
+                for (index, child) in items.iter_mut().enumerate() {
+                    path.push(index.to_string());
+                    self.walk(child, path, out);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the IDs listed in `annotation` (a list of IDs or a single ID) for `node`.
+    fn add(&mut self, node: String, annotation: &JsonValue, at: &str, out: &mut Vec<Diagnostic>) {
+        let codes: Vec<&JsonValue> = match annotation {
+            JsonValue::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for code in codes {
+            let Some(code) = code.as_str().map(|c| c.trim().to_ascii_uppercase()) else {
+                out.push(
+                    Diagnostic::error("PV090", "suppressions must list rule IDs as strings").at(at),
+                );
+                continue;
+            };
+            if code.is_empty()
+                || !rules::rule_codes()
+                    .iter()
+                    .any(|(c, _)| c.starts_with(&code))
+            {
+                out.push(
+                    Diagnostic::error(
+                        "PV090",
+                        format!("suppression '{code}' does not match any rule ID"),
+                    )
+                    .at(at),
+                );
+                continue;
+            }
+            self.entries.push(Suppression {
+                pointer: node.clone(),
+                code,
+                used: false,
+            });
+        }
+    }
+
+    /// Pointer of the annotation that silences `diagnostic` (the innermost one when several do),
+    /// if any; marks every matching annotation as used. Findings without a location can only be
+    /// silenced document-wide.
+    pub fn suppressed_by(&mut self, diagnostic: &Diagnostic) -> Option<String> {
+        let location = diagnostic.pointer.as_deref().unwrap_or_default();
+        let mut matched: Option<&str> = None;
+        for entry in &mut self.entries {
+            let covers = entry.pointer.is_empty()
+                || location == entry.pointer
+                || location
+                    .strip_prefix(entry.pointer.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'));
+            if covers && diagnostic.code.starts_with(entry.code.as_str()) {
+                entry.used = true;
+                if matched.is_none_or(|m| m.len() < entry.pointer.len()) {
+                    matched = Some(&entry.pointer);
+                }
+            }
+        }
+        matched.map(|node| {
+            if node.is_empty() {
+                "/meta/verify_ignore".to_string()
+            } else {
+                format!("{node}/{NODE_KEY}")
+            }
+        })
+    }
+
+    /// Warnings for annotations that silenced nothing. `checked` tells whether findings with a
+    /// given ID could have been produced in this run (rule enabled and selected).
+    pub fn unused(&self, checked: impl Fn(&str) -> bool) -> Vec<Diagnostic> {
+        let codes = rules::rule_codes();
+        self.entries
+            .iter()
+            .filter(|entry| !entry.used)
+            .filter(|entry| {
+                codes
+                    .iter()
+                    .any(|(code, _)| code.starts_with(entry.code.as_str()) && checked(code))
+            })
+            .map(|entry| {
+                if entry.pointer.is_empty() {
+                    Diagnostic::warning(
+                        "PV091",
+                        format!(
+                            "meta.verify_ignore entry '{}' never matched a finding",
+                            entry.code
+                        ),
+                    )
+                    .at("/meta/verify_ignore")
+                } else {
+                    Diagnostic::warning(
+                        "PV091",
+                        format!(
+                            "{NODE_KEY} entry '{}' at '{}' never matched a finding",
+                            entry.code, entry.pointer
+                        ),
+                    )
+                    .at(format!("{}/{NODE_KEY}", entry.pointer))
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,215 @@
+//! Follows a spec through its schema: visits every schema node that applies to each value of the
+//! spec, following local `$ref`s and `allOf`, the `oneOf`/`anyOf` arms the value validates against
+//! and the `then`/`else` branch its `if` selects. Used by `schema coverage` and the custom keywords.
+
+use crate::{
+    schemas::{self, ResolvedSchema},
+    usage::SchemaWalker,
+    Args,
+};
+use jsonschema::JSONSchema;
+use regex::Regex;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+
+/// A schema node applying to a value of the spec.
+pub struct Visit<'v> {
+    pub node: &'v Map<String, JsonValue>,
+    /// JSON Pointer of the node in the schema document.
+    pub pointer: &'v str,
+    pub instance: &'v JsonValue,
+    /// JSON Pointer of the value in the spec.
+    pub at: &'v str,
+}
+
+/// The keyword [`Tracer::is_valid`] keeps the schema under while checking one of its subschemas.
+const WRAPPED: &str = "x-program-verify-schema";
+
+pub struct Tracer<'a> {
+    args: &'a Args,
+    resolved: &'a ResolvedSchema,
+    walker: SchemaWalker<'a>,
+    /// Compiled `oneOf`/`anyOf` arms and `if` conditions by pointer (`None` if they do not compile).
+    conditions: HashMap<String, Option<JSONSchema>>,
+    patterns: HashMap<String, Option<Regex>>,
+}
+
+impl<'a> Tracer<'a> {
+    pub fn new(args: &'a Args, resolved: &'a ResolvedSchema) -> Self {
+        Self {
+            args,
+            resolved,
+            walker: SchemaWalker::new(&resolved.schema),
+            conditions: HashMap::new(),
+            patterns: HashMap::new(),
+        }
+    }
+
+    /// Calls `visit` for every schema node applying to `instance` or one of its values.
+    pub fn trace(&mut self, instance: &JsonValue, visit: &mut dyn FnMut(&Visit)) {
+        let root = &self.resolved.schema;
+        self.walk(root, "", instance, "", visit);
+    }
+
+    fn walk(
+        &mut self,
+        node: &'a JsonValue,
+        pointer: &str,
+        instance: &JsonValue,
+        at: &str,
+        visit: &mut dyn FnMut(&Visit),
+    ) {
+        let Some(map) = node.as_object() else {
+            return;
+        };
+        visit(&Visit {
+            node: map,
+            pointer,
+            instance,
+            at,
+        });
+        if let Some((reference, target)) = self.walker.reference(node) {
+            self.walker.refs.push(reference);
+            self.walk(target, &reference[1..], instance, at, visit);
+            self.walker.refs.pop();
+        }
+        for (i, arm) in map
+            .get("allOf")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            self.walk(arm, &format!("{pointer}/allOf/{i}"), instance, at, visit);
+        }
+        for key in ["oneOf", "anyOf"] {
+            for (i, arm) in map
+                .get(key)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                let arm_pointer = format!("{pointer}/{key}/{i}");
+                if self.is_valid(&arm_pointer, instance) {
+                    self.walk(arm, &arm_pointer, instance, at, visit);
+                }
+            }
+        }
+        if map.contains_key("if") {
+            let branch = if self.is_valid(&format!("{pointer}/if"), instance) {
+                "then"
+            } else {
+                "else"
+            };
+            if let Some(sub) = map.get(branch) {
+                self.walk(sub, &format!("{pointer}/{branch}"), instance, at, visit);
+            }
+        }
+
+        // Recursion now follows the instance, which is finite, so the `$ref` guard starts over.
+        let expanding = std::mem::take(&mut self.walker.refs);
+        match instance {
+            JsonValue::Object(members) => self.walk_members(map, pointer, members, at, visit),
+            JsonValue::Array(items) => match map.get("items") {
+                Some(JsonValue::Array(tuple)) => {
+                    for (i, (sub, item)) in tuple.iter().zip(items).enumerate() {
+                        let (pointer, at) = (format!("{pointer}/items/{i}"), format!("{at}/{i}"));
+                        self.walk(sub, &pointer, item, &at, visit);
+                    }
+                }
+                Some(sub) => {
+                    for (i, item) in items.iter().enumerate() {
+                        self.walk(
+                            sub,
+                            &format!("{pointer}/items"),
+                            item,
+                            &format!("{at}/{i}"),
+                            visit,
+                        );
+                    }
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        self.walker.refs = expanding;
+    }
+
+    fn walk_members(
+        &mut self,
+        node: &'a Map<String, JsonValue>,
+        pointer: &str,
+        members: &Map<String, JsonValue>,
+        at: &str,
+        visit: &mut dyn FnMut(&Visit),
+    ) {
+        let properties = node.get("properties").and_then(|v| v.as_object());
+        let patterns = node.get("patternProperties").and_then(|v| v.as_object());
+        for (key, value) in members {
+            let member_at = format!("{at}/{}", escape(key));
+            if let Some(sub) = properties.and_then(|p| p.get(key)) {
+                let property = format!("{pointer}/properties/{}", escape(key));
+                self.walk(sub, &property, value, &member_at, visit);
+                continue;
+            }
+            let mut matched = false;
+            for (pattern, sub) in patterns.into_iter().flatten() {
+                let regex = self
+                    .patterns
+                    .entry(pattern.clone())
+                    .or_insert_with(|| Regex::new(pattern).ok());
+                if regex.as_ref().is_some_and(|re| re.is_match(key)) {
+                    matched = true;
+                    let sub_pointer = format!("{pointer}/patternProperties/{}", escape(pattern));
+                    self.walk(sub, &sub_pointer, value, &member_at, visit);
+                }
+            }
+            if let (false, Some(sub)) = (matched, node.get("additionalProperties")) {
+                let sub_pointer = format!("{pointer}/additionalProperties");
+                self.walk(sub, &sub_pointer, value, &member_at, visit);
+            }
+        }
+    }
+
+    /// Whether `instance` validates against the subschema at `pointer`. The subschema is compiled
+    /// as a reference into a copy of the whole schema, kept under a keyword that validates nothing,
+    /// next to its `definitions`/`$defs`, so that `$ref`s to them still resolve.
+    fn is_valid(&mut self, pointer: &str, instance: &JsonValue) -> bool {
+        let (args, resolved) = (self.args, self.resolved);
+        let compiled = self
+            .conditions
+            .entry(pointer.to_string())
+            .or_insert_with(|| {
+                let mut wrapper = Map::new();
+                if let Some(root) = resolved.schema.as_object() {
+                    for key in ["$schema", "$id", "id", "definitions", "$defs"] {
+                        if let Some(value) = root.get(key) {
+                            wrapper.insert(key.to_string(), value.clone());
+                        }
+                    }
+                }
+                wrapper.insert(WRAPPED.to_string(), resolved.schema.clone());
+                wrapper.insert("$ref".to_string(), format!("#/{WRAPPED}{pointer}").into());
+                let wrapper = ResolvedSchema {
+                    origin: format!("{}#{pointer}", resolved.origin),
+                    schema: JsonValue::Object(wrapper),
+                    base: resolved.base.clone(),
+                };
+                schemas::compile(
+                    &wrapper,
+                    &args.cache,
+                    &args.fetcher,
+                    args.draft,
+                    &args.custom_formats,
+                )
+                .ok()
+            });
+        compiled.as_ref().is_some_and(|c| c.is_valid(instance))
+    }
+}
+
+/// Escapes a name for use as a JSON Pointer segment.
+pub fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
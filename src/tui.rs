@@ -0,0 +1,410 @@
+//! `--tui`: once the run finishes, its files and findings are browsed in a terminal UI instead of
+//! being printed. The files are listed with their status; the findings of the selected file can be
+//! expanded, filtered by severity and by rule, and are shown in the source around their location.
+
+use crate::{
+    diagnostics::Severity,
+    reporter::{Report, Reporter},
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::{collections::HashMap, fs, io, sync::Mutex};
+
+/// Name under which the findings of the cross-spec checks are listed.
+const ACROSS_SPECS: &str = "(across specs)";
+
+/// The findings of one input.
+struct FileResult {
+    name: String,
+    findings: Vec<Report>,
+}
+
+/// Collects the files and findings of the run and opens the browser once it finishes.
+#[derive(Default)]
+pub struct TuiReporter {
+    files: Mutex<Vec<FileResult>>,
+}
+
+impl TuiReporter {
+    fn file<'f>(files: &'f mut Vec<FileResult>, name: &str) -> &'f mut FileResult {
+        let index = match files.iter().position(|file| file.name == name) {
+            Some(index) => index,
+            None => {
+                files.push(FileResult {
+                    name: name.to_string(),
+                    findings: Vec::new(),
+                });
+                files.len() - 1
+            }
+        };
+        &mut files[index]
+    }
+}
+
+impl Reporter for TuiReporter {
+    fn spec(&self, file: &str, _doc: &serde_json::Value) {
+        Self::file(&mut self.files.lock().unwrap(), file);
+    }
+
+    fn report(&self, report: &Report) {
+        let mut files = self.files.lock().unwrap();
+        let name = report.file.as_deref().unwrap_or(ACROSS_SPECS);
+        Self::file(&mut files, name).findings.push(report.clone());
+    }
+
+    fn finish(&self) {
+        let files = std::mem::take(&mut *self.files.lock().unwrap());
+        let mut terminal = ratatui::init();
+        let result = Browser::new(files).run(&mut terminal);
+        ratatui::restore();
+        if let Err(e) = result {
+            failln!("Error: the results browser failed: {e}");
+        }
+    }
+}
+
+/// Which list the arrow keys move in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Files,
+    Findings,
+}
+
+struct Browser {
+    files: Vec<FileResult>,
+    focus: Focus,
+    file: ListState,
+    finding: ListState,
+    /// Expanded findings, by file and position in the file.
+    expanded: Vec<(usize, usize)>,
+    /// Findings below this severity are hidden.
+    min_severity: Severity,
+    /// Only findings whose ID or rule contains this (case-insensitively) are shown.
+    filter: String,
+    /// Keys go to `filter` rather than moving around.
+    editing: bool,
+    /// Text of the files shown in the preview, read on first use.
+    sources: HashMap<String, Option<String>>,
+}
+
+impl Browser {
+    fn new(files: Vec<FileResult>) -> Self {
+        let mut file = ListState::default();
+        file.select((!files.is_empty()).then_some(0));
+        Self {
+            files,
+            focus: Focus::Files,
+            file,
+            finding: ListState::default().with_selected(Some(0)),
+            expanded: Vec::new(),
+            min_severity: Severity::Info,
+            filter: String::new(),
+            editing: false,
+            sources: HashMap::new(),
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if self.editing {
+                match key.code {
+                    KeyCode::Enter => self.editing = false,
+                    KeyCode::Esc => {
+                        self.editing = false;
+                        self.filter.clear();
+                    }
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                    }
+                    KeyCode::Char(c) => self.filter.push(c),
+                    _ => {}
+                }
+                self.finding.select(Some(0));
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab | KeyCode::BackTab => self.switch_focus(),
+                KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::Files,
+                KeyCode::Right | KeyCode::Char('l') => self.focus = Focus::Findings,
+                KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.step(1),
+                KeyCode::PageUp => self.step(-10),
+                KeyCode::PageDown => self.step(10),
+                KeyCode::Enter | KeyCode::Char(' ') => match self.focus {
+                    Focus::Files => self.focus = Focus::Findings,
+                    Focus::Findings => self.toggle_expanded(),
+                },
+                KeyCode::Char('s') => {
+                    self.min_severity = match self.min_severity {
+                        Severity::Info => Severity::Warning,
+                        Severity::Warning => Severity::Error,
+                        Severity::Error => Severity::Info,
+                    };
+                    self.finding.select(Some(0));
+                }
+                KeyCode::Char('/') => self.editing = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn switch_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Files => Focus::Findings,
+            Focus::Findings => Focus::Files,
+        };
+    }
+
+    /// Moves the selection of the focused list by `delta`, staying within the list.
+    fn step(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Focus::Files => (&mut self.file, self.files.len()),
+            Focus::Findings => {
+                let len = self.visible().len();
+                (&mut self.finding, len)
+            }
+        };
+        if len == 0 {
+            return;
+        }
+        let at = state.selected().unwrap_or(0) as isize + delta;
+        state.select(Some(at.clamp(0, len as isize - 1) as usize));
+        if self.focus == Focus::Files {
+            self.finding.select(Some(0));
+        }
+    }
+
+    fn toggle_expanded(&mut self) {
+        let (Some(file), Some(&(position, _))) = (
+            self.file.selected(),
+            self.visible().get(self.finding.selected().unwrap_or(0)),
+        ) else {
+            return;
+        };
+        match self.expanded.iter().position(|&e| e == (file, position)) {
+            Some(index) => {
+                self.expanded.remove(index);
+            }
+            None => self.expanded.push((file, position)),
+        }
+    }
+
+    /// Whether `report` passes the severity and rule filters.
+    fn shown(&self, report: &Report) -> bool {
+        let filter = self.filter.to_lowercase();
+        report.severity >= self.min_severity
+            && (filter.is_empty()
+                || report.code.to_lowercase().contains(&filter)
+                || report
+                    .rule
+                    .is_some_and(|rule| rule.to_lowercase().contains(&filter)))
+    }
+
+    /// The findings of the selected file that pass the filters, with their position in the file.
+    fn visible(&self) -> Vec<(usize, &Report)> {
+        let Some(file) = self.file.selected().and_then(|index| self.files.get(index)) else {
+            return Vec::new();
+        };
+        file.findings
+            .iter()
+            .enumerate()
+            .filter(|(_, report)| self.shown(report))
+            .collect()
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [files, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+        let [findings, preview] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(right);
+        self.draw_files(frame, files);
+        self.draw_findings(frame, findings);
+        self.draw_preview(frame, preview);
+        self.draw_status(frame, status);
+    }
+
+    fn block(&self, title: String, focus: Focus) -> Block<'static> {
+        let style = if self.focus == focus {
+            Style::new().fg(Color::Cyan)
+        } else {
+            Style::new()
+        };
+        Block::bordered().title(title).border_style(style)
+    }
+
+    fn draw_files(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .files
+            .iter()
+            .map(|file| {
+                let shown = file.findings.iter().filter(|r| self.shown(r)).count();
+                let worst = file.findings.iter().map(|r| r.severity).max();
+                let status = match worst {
+                    Some(severity) => marker(severity),
+                    None => Span::styled("✓", Style::new().fg(Color::Green)),
+                };
+                ListItem::new(Line::from(vec![
+                    status,
+                    Span::raw(format!(" {} ", file.name)),
+                    Span::styled(format!("({shown})"), Style::new().fg(Color::DarkGray)),
+                ]))
+            })
+            .collect();
+        let title = format!(" Files ({}) ", self.files.len());
+        let list = List::new(items)
+            .block(self.block(title, Focus::Files))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.file);
+    }
+
+    fn draw_findings(&mut self, frame: &mut Frame, area: Rect) {
+        let file = self.file.selected().unwrap_or(0);
+        let items: Vec<ListItem> = self
+            .visible()
+            .into_iter()
+            .map(|(position, report)| {
+                let mut lines = vec![Line::from(vec![
+                    marker(report.severity),
+                    Span::styled(
+                        format!(" [{}] ", report.code),
+                        Style::new().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(
+                        report
+                            .message
+                            .lines()
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                    ),
+                ])];
+                if self.expanded.contains(&(file, position)) {
+                    lines.extend(details(report).into_iter().map(|detail| {
+                        Line::styled(format!("    {detail}"), Style::new().fg(Color::Gray))
+                    }));
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+        let title = format!(" Findings ({}) ", items.len());
+        let list = List::new(items)
+            .block(self.block(title, Focus::Findings))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.finding);
+    }
+
+    fn draw_preview(&mut self, frame: &mut Frame, area: Rect) {
+        let selected = self
+            .visible()
+            .get(self.finding.selected().unwrap_or(0))
+            .map(|(_, report)| (report.file.clone(), report.location, report.excerpt.clone()));
+        let block = Block::bordered().title(" Source ");
+        let Some((Some(file), Some(location), excerpt)) = selected.clone() else {
+            let text = selected
+                .and_then(|(_, _, excerpt)| excerpt)
+                .unwrap_or_else(|| "No source location.".to_string());
+            frame.render_widget(Paragraph::new(text).block(block), area);
+            return;
+        };
+        let source = self
+            .sources
+            .entry(file.clone())
+            .or_insert_with(|| fs::read_to_string(&file).ok());
+        let Some(source) = source else {
+            let text = excerpt.unwrap_or_default();
+            frame.render_widget(Paragraph::new(text).block(block), area);
+            return;
+        };
+        // The located line, with as much context above and below as fits.
+        let height = area.height.saturating_sub(2) as usize;
+        let first = location.line.saturating_sub(height / 2).max(1);
+        let lines: Vec<Line> = source
+            .lines()
+            .enumerate()
+            .skip(first - 1)
+            .take(height)
+            .map(|(index, text)| {
+                let number = index + 1;
+                let style = if number == location.line {
+                    Style::new().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+                Line::from(vec![
+                    Span::styled(format!("{number:>5} │ "), Style::new().fg(Color::DarkGray)),
+                    Span::styled(text.to_string(), style),
+                ])
+            })
+            .collect();
+        let block = block.title(format!(" {file}:{}:{} ", location.line, location.column));
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let filter = if self.editing {
+            format!("rule filter: {}▏", self.filter)
+        } else if self.filter.is_empty() {
+            "/ filter by rule".to_string()
+        } else {
+            format!("rule filter: {} (/ to edit)", self.filter)
+        };
+        let text = format!(
+            " q quit · Tab switch · ↑↓ move · Enter expand · s severity ≥ {} · {filter}",
+            self.min_severity.name()
+        );
+        frame.render_widget(
+            Paragraph::new(text).style(Style::new().add_modifier(Modifier::REVERSED)),
+            area,
+        );
+    }
+}
+
+/// A colored marker for a finding of `severity`.
+fn marker(severity: Severity) -> Span<'static> {
+    match severity {
+        Severity::Error => Span::styled("✗", Style::new().fg(Color::Red)),
+        Severity::Warning => Span::styled("!", Style::new().fg(Color::Yellow)),
+        Severity::Info => Span::styled("i", Style::new().fg(Color::Blue)),
+    }
+}
+
+/// What an expanded finding shows below its first line.
+fn details(report: &Report) -> Vec<String> {
+    let mut details: Vec<String> = report.message.lines().skip(1).map(str::to_string).collect();
+    details.push(format!(
+        "{} · {}",
+        report.rule.unwrap_or("JSON Schema"),
+        report.severity.name()
+    ));
+    if let Some(pointer) = &report.pointer {
+        let at = match report.location {
+            Some(location) => format!(" (line {}, column {})", location.line, location.column),
+            None => String::new(),
+        };
+        details.push(format!("at {pointer}{at}"));
+    }
+    if let Some(schema_path) = &report.schema_path {
+        details.push(format!("schema: {schema_path}"));
+    }
+    if let Some(annotation) = &report.annotation {
+        details.push(annotation.clone());
+    }
+    details
+}
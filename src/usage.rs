@@ -0,0 +1,334 @@
+//! `report schema-usage`: which optional schema fields a spec corpus actually uses.
+//!
+//! Fields are named by their schema path: dot-separated property names, `*` for values of
+//! free-form maps (`patternProperties`/`additionalProperties`) and `[]` for array items, e.g.
+//! `implementation.phase_contracts.*.retry_policy`.
+
+use crate::{
+    expand_inputs, extract_spec_version, is_program_spec,
+    schemas::{ResolvedSchema, SchemaRequest},
+    workspace_documents, Args,
+};
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+/// Group label for specs without a `spec_version`.
+pub const UNVERSIONED: &str = "(no spec_version)";
+
+/// The program specs found in `paths`, grouped by spec version ([`UNVERSIONED`] for specs without
+/// one).
+pub fn specs_by_version(
+    args: &Args,
+    paths: &[PathBuf],
+) -> Result<BTreeMap<String, Vec<(PathBuf, JsonValue)>>, String> {
+    let files = expand_inputs(paths)?;
+    let mut groups: BTreeMap<String, Vec<(PathBuf, JsonValue)>> = BTreeMap::new();
+    for (file, doc) in workspace_documents(args, &files) {
+        if !is_program_spec(&doc) {
+            continue;
+        }
+        let version = args
+            .spec_version
+            .clone()
+            .or_else(|| extract_spec_version(&doc).ok().flatten())
+            .unwrap_or_else(|| UNVERSIONED.to_string());
+        groups.entry(version).or_default().push((file, doc));
+    }
+    if groups.is_empty() {
+        return Err("Error: no program specs found in the given paths".to_string());
+    }
+    Ok(groups)
+}
+
+/// The schema for a group of [`specs_by_version`].
+pub fn group_schema(
+    args: &Args,
+    version: &str,
+    specs: &[(PathBuf, JsonValue)],
+) -> Result<ResolvedSchema, String> {
+    let request = SchemaRequest {
+        input: &specs[0].0,
+        spec_version: (version != UNVERSIONED).then_some(version),
+    };
+    let explicit = args.schema.as_ref().map(|p| p.to_string_lossy());
+    args.schemas.resolve(explicit.as_deref(), &request)
+}
+
+/// Prints, per spec version, how many specs use each optional field of the schema and how many
+/// times it occurs in total.
+pub fn schema_usage(args: &Args, paths: &[PathBuf]) -> ExitCode {
+    let groups = match specs_by_version(args, paths) {
+        Ok(groups) => groups,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut failed = false;
+    for (version, specs) in &groups {
+        let (origin, schema) = match group_schema(args, version, specs) {
+            Ok(resolved) => (resolved.origin, resolved.schema),
+            Err(msg) => {
+                errln!("── {version} — {} spec(s) ──", specs.len());
+                failln!("{msg}");
+                failed = true;
+                continue;
+            }
+        };
+
+        let mut walker = SchemaWalker::new(&schema);
+        let mut optional = BTreeSet::new();
+        walker.optional(&schema, "", &mut optional);
+
+        // field → (specs using it, total occurrences)
+        let mut usage: HashMap<&str, (usize, usize)> = optional
+            .iter()
+            .map(|field| (field.as_str(), (0, 0)))
+            .collect();
+        for (_, doc) in specs {
+            let mut used = BTreeSet::new();
+            walker.used(&schema, doc, "", "", &mut used);
+            let mut per_field: HashMap<&str, usize> = HashMap::new();
+            for (field, _) in &used {
+                *per_field.entry(field.as_str()).or_default() += 1;
+            }
+            for (field, count) in per_field {
+                if let Some(entry) = usage.get_mut(field) {
+                    entry.0 += 1;
+                    entry.1 += count;
+                }
+            }
+        }
+
+        let mut rows: Vec<(&str, usize, usize)> = usage
+            .into_iter()
+            .map(|(field, (specs, uses))| (field, specs, uses))
+            .collect();
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(b.0)));
+        let unused = rows.iter().filter(|row| row.1 == 0).count();
+
+        outln!("── {version} — {} spec(s), {origin} ──", specs.len());
+        outln!("  {:>5}  {:>5}  field", "specs", "uses");
+        for (field, specs, uses) in &rows {
+            outln!("  {specs:>5}  {uses:>5}  {field}");
+        }
+        outln!("  {unused} of {} optional field(s) unused", rows.len());
+    }
+
+    if failed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::from(0)
+    }
+}
+
+/// Walks a schema together with `$ref`s (local `#/...` only) and the `allOf`/`anyOf`/`oneOf`/
+/// `then`/`else` combinators, treating every branch as applicable.
+pub struct SchemaWalker<'a> {
+    root: &'a JsonValue,
+    /// References currently being expanded, to stop on recursive definitions.
+    pub refs: Vec<&'a str>,
+    patterns: HashMap<&'a str, Option<Regex>>,
+}
+
+impl<'a> SchemaWalker<'a> {
+    pub fn new(root: &'a JsonValue) -> Self {
+        Self {
+            root,
+            refs: Vec::new(),
+            patterns: HashMap::new(),
+        }
+    }
+
+    /// Subschemas that apply to the same instance as `node`.
+    fn branches(&self, node: &'a JsonValue) -> Vec<&'a JsonValue> {
+        let mut branches = Vec::new();
+        for key in ["allOf", "anyOf", "oneOf"] {
+            if let Some(items) = node.get(key).and_then(|v| v.as_array()) {
+                branches.extend(items);
+            }
+        }
+        for key in ["then", "else"] {
+            if let Some(sub) = node.get(key).filter(|v| v.is_object()) {
+                branches.push(sub);
+            }
+        }
+        branches
+    }
+
+    /// `node` and every subschema applying to the same instance through `$ref`s and combinators.
+    pub fn applicable(&mut self, node: &'a JsonValue) -> Vec<&'a JsonValue> {
+        let mut nodes = vec![node];
+        if let Some((reference, target)) = self.reference(node) {
+            self.refs.push(reference);
+            nodes.extend(self.applicable(target));
+            self.refs.pop();
+        }
+        for branch in self.branches(node) {
+            nodes.extend(self.applicable(branch));
+        }
+        nodes
+    }
+
+    /// The target of `node`'s `$ref`, unless it is already being expanded.
+    pub fn reference(&self, node: &'a JsonValue) -> Option<(&'a str, &'a JsonValue)> {
+        let reference = node.get("$ref")?.as_str()?;
+        if self.refs.contains(&reference) {
+            return None;
+        }
+        let target = self.root.pointer(reference.strip_prefix('#')?)?;
+        Some((reference, target))
+    }
+
+    /// Collects the paths of all properties not listed in their parent's `required`.
+    fn optional(&mut self, node: &'a JsonValue, path: &str, out: &mut BTreeSet<String>) {
+        if let Some((reference, target)) = self.reference(node) {
+            self.refs.push(reference);
+            self.optional(target, path, out);
+            self.refs.pop();
+        }
+        for branch in self.branches(node) {
+            self.optional(branch, path, out);
+        }
+
+        let required = required(node);
+        if let Some(properties) = node.get("properties").and_then(|v| v.as_object()) {
+            for (name, sub) in properties {
+                let field = join(path, name);
+                if !required.contains(&name.as_str()) {
+                    out.insert(field.clone());
+                }
+                self.optional(sub, &field, out);
+            }
+        }
+        for sub in map_value_schemas(node) {
+            self.optional(sub, &join(path, "*"), out);
+        }
+        for sub in item_schemas(node) {
+            self.optional(sub, &format!("{path}[]"), out);
+        }
+    }
+
+    /// Collects `(field path, instance pointer)` for every declared property present in
+    /// `instance`.
+    fn used(
+        &mut self,
+        node: &'a JsonValue,
+        instance: &JsonValue,
+        path: &str,
+        at: &str,
+        out: &mut BTreeSet<(String, String)>,
+    ) {
+        if let Some((reference, target)) = self.reference(node) {
+            self.refs.push(reference);
+            self.used(target, instance, path, at, out);
+            self.refs.pop();
+        }
+        for branch in self.branches(node) {
+            self.used(branch, instance, path, at, out);
+        }
+
+        // Recursion now follows the instance, which is finite, so the `$ref` guard starts over.
+        let expanding = std::mem::take(&mut self.refs);
+
+        match instance {
+            JsonValue::Object(map) => {
+                let properties = node.get("properties").and_then(|v| v.as_object());
+                for (key, value) in map {
+                    let location = format!("{at}/{key}");
+                    if let Some(sub) = properties.and_then(|p| p.get(key)) {
+                        let field = join(path, key);
+                        out.insert((field.clone(), location.clone()));
+                        self.used(sub, value, &field, &location, out);
+                        continue;
+                    }
+                    let field = join(path, "*");
+                    for sub in self.map_value_schemas_for(node, key) {
+                        self.used(sub, value, &field, &location, out);
+                    }
+                }
+            }
+            JsonValue::Array(items) => {
+                for sub in item_schemas(node) {
+                    for (index, item) in items.iter().enumerate() {
+                        self.used(
+                            sub,
+                            item,
+                            &format!("{path}[]"),
+                            &format!("{at}/{index}"),
+                            out,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.refs = expanding;
+    }
+
+    /// Schemas for the value of undeclared property `key`: matching `patternProperties`, or
+    /// `additionalProperties` when no pattern matches.
+    pub fn map_value_schemas_for(&mut self, node: &'a JsonValue, key: &str) -> Vec<&'a JsonValue> {
+        let mut matched = Vec::new();
+        if let Some(patterns) = node.get("patternProperties").and_then(|v| v.as_object()) {
+            for (pattern, sub) in patterns {
+                let regex = self
+                    .patterns
+                    .entry(pattern.as_str())
+                    .or_insert_with(|| Regex::new(pattern).ok());
+                if regex.as_ref().is_some_and(|re| re.is_match(key)) {
+                    matched.push(sub);
+                }
+            }
+        }
+        if matched.is_empty() {
+            if let Some(sub) = node.get("additionalProperties").filter(|v| v.is_object()) {
+                matched.push(sub);
+            }
+        }
+        matched
+    }
+}
+
+pub fn required(node: &JsonValue) -> Vec<&str> {
+    node.get("required")
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|i| i.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// `patternProperties` values and an object-valued `additionalProperties`.
+pub fn map_value_schemas(node: &JsonValue) -> Vec<&JsonValue> {
+    let mut schemas: Vec<&JsonValue> = node
+        .get("patternProperties")
+        .and_then(|v| v.as_object())
+        .map(|patterns| patterns.values().collect())
+        .unwrap_or_default();
+    if let Some(sub) = node.get("additionalProperties").filter(|v| v.is_object()) {
+        schemas.push(sub);
+    }
+    schemas
+}
+
+/// `items` as a single schema or as a tuple of schemas.
+pub fn item_schemas(node: &JsonValue) -> Vec<&JsonValue> {
+    match node.get("items") {
+        Some(JsonValue::Array(items)) => items.iter().collect(),
+        Some(sub @ JsonValue::Object(_)) => vec![sub],
+        _ => Vec::new(),
+    }
+}
+
+pub fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
@@ -0,0 +1,208 @@
+//! The library entry point: a [`Validator`] checks specs in-process the way the command line does
+//! and hands back the findings instead of printing them.
+//!
+//! The async entry points read files with tokio and run the validation itself on a thread of its
+//! own, so that async hosts do not block an executor thread on a large document. Rules, schema
+//! downloads and the audit log stay blocking there; a validation is stopped early with a
+//! [cancellation token](Validator::cancellation), not by dropping its future.
+
+use crate::{
+    cancellation::CancellationToken,
+    encoding,
+    keywords::Keyword,
+    locations::{self, Locations},
+    output, parse_documents,
+    reporter::{Report, Reporter, Reporters},
+    rules::{self, with_profile},
+    schemas::SchemaResolver,
+    validate_document, Args, InputFormat, Source,
+};
+use clap::Parser;
+use std::{path::Path, process::ExitCode, sync::Arc, thread};
+use tokio::{fs, sync::oneshot};
+
+/// Validates specs with the schemas, rules and configuration of a command line.
+pub struct Validator {
+    /// Shared with the threads of [async validations](Validator::validate_async).
+    setup: Arc<Setup>,
+}
+
+struct Setup {
+    args: Args,
+    /// Reporters of the embedder, which get the findings of every spec as well.
+    reporters: Reporters,
+}
+
+/// What validating one spec found.
+#[derive(Debug)]
+pub struct Validation {
+    /// Whether the spec passes: no document failed, and every document could be validated.
+    pub valid: bool,
+    /// The findings of every document, in the order they were produced.
+    pub findings: Vec<Report>,
+    /// Why documents could not be validated (an unknown spec version, an unreadable schema), as
+    /// the command line prints it.
+    pub errors: Vec<String>,
+    /// The validation was [cancelled](Validator::cancellation) before it was done; the findings are
+    /// incomplete and the spec is not valid.
+    pub cancelled: bool,
+}
+
+impl Validator {
+    /// A validator with the options `options` give the command line (`["--schema",
+    /// "schemas/v4.json"]`), including the configuration file they name or the nearest one found
+    /// from the working directory.
+    pub fn new(options: &[&str]) -> Result<Self, String> {
+        let command_line = ["program-verify"].iter().chain(options).chain(&["-"]);
+        let mut args = Args::try_parse_from(command_line).map_err(|e| e.to_string())?;
+        args.apply_config()?;
+        let reporters = Reporters::default();
+        Ok(Self {
+            setup: Arc::new(Setup { args, reporters }),
+        })
+    }
+
+    fn setup(&mut self) -> &mut Setup {
+        Arc::get_mut(&mut self.setup).expect("a validator is set up before it validates")
+    }
+
+    /// Asks `resolver` for schemas before the resolvers of the command line (files and URLs, the
+    /// version map, the embedded schema), e.g. to serve them from a database.
+    pub fn resolver(mut self, resolver: Box<dyn SchemaResolver>) -> Self {
+        self.setup().args.schemas.prepend(resolver);
+        self
+    }
+
+    /// Hands the documents and findings of every spec to `reporter` too, once per document as
+    /// soon as it is validated, and calls [`Reporter::finish`] when the spec is done.
+    pub fn reporter(mut self, reporter: Box<dyn Reporter>) -> Self {
+        self.setup().reporters.push(reporter);
+        self
+    }
+
+    /// Checks the schema keyword `keyword` as well as the built-in ones wherever a schema uses it.
+    pub fn keyword(mut self, keyword: Box<dyn Keyword>) -> Self {
+        self.setup().args.keywords.register(keyword);
+        self
+    }
+
+    /// Stops validations once `token` is cancelled, at the next document, schema check or rule, so
+    /// that an editor can drop a validation the user has typed past.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.setup().args.cancellation = token;
+        self
+    }
+
+    /// Validates the spec `text`. `name` is the file name findings are reported under; its
+    /// extension selects the format (YAML unless `.json` or `.toml`) and the version map is looked
+    /// up next to it.
+    pub fn validate(&self, name: &str, text: &str) -> Validation {
+        self.setup.validate(name, text)
+    }
+
+    /// [Validates](Validator::validate) the spec `text` on a thread of its own, with the rule
+    /// profile active here.
+    pub async fn validate_async(&self, name: &str, text: &str) -> Validation {
+        let setup = Arc::clone(&self.setup);
+        let profile = rules::active_profile();
+        let (name, text) = (name.to_string(), text.to_string());
+        let (reply, validation) = oneshot::channel();
+        thread::spawn(move || {
+            let validation = match profile {
+                Some(profile) => with_profile(profile, || setup.validate(&name, &text)),
+                None => setup.validate(&name, &text),
+            };
+            // Let go of the setup first, so that the validator can be set up again once the
+            // validation is awaited.
+            drop(setup);
+            let _ = reply.send(validation);
+        });
+        validation.await.expect("the validation thread panicked")
+    }
+
+    /// Reads the spec file `path` without blocking and [validates](Validator::validate_async) it.
+    pub async fn validate_file(&self, path: impl AsRef<Path>) -> Validation {
+        let path = path.as_ref();
+        let decoded = match fs::read(path).await {
+            Ok(bytes) => encoding::decode(bytes),
+            Err(e) => Err(e.to_string()),
+        };
+        match decoded {
+            Ok(decoded) => {
+                self.validate_async(&path.to_string_lossy(), &decoded.text)
+                    .await
+            }
+            Err(e) => {
+                self.setup.reporters.finish();
+                Validation::failed(format!(
+                    "Error: failed to read file {}: {e}",
+                    path.display()
+                ))
+            }
+        }
+    }
+}
+
+impl Validation {
+    /// A validation that failed before any document was validated.
+    fn failed(error: String) -> Self {
+        Validation {
+            valid: false,
+            findings: Vec::new(),
+            errors: vec![error],
+            cancelled: false,
+        }
+    }
+}
+
+impl Setup {
+    fn validate(&self, name: &str, text: &str) -> Validation {
+        let path = Path::new(name);
+        let format = self
+            .args
+            .input_format
+            .unwrap_or_else(|| InputFormat::from_path(path));
+        let documents = match parse_documents(text, format) {
+            Ok(documents) => documents,
+            Err(msg) => {
+                self.reporters.finish();
+                return Validation::failed(format!("Error: {msg}"));
+            }
+        };
+        let all_locations = match format {
+            InputFormat::Yaml | InputFormat::Json => locations::yaml_documents(text),
+            InputFormat::Toml => Vec::new(),
+        };
+        let no_locations = Locations::default();
+
+        let mut validation = Validation {
+            valid: true,
+            findings: Vec::new(),
+            errors: Vec::new(),
+            cancelled: false,
+        };
+        for (index, doc) in documents.iter().enumerate() {
+            if self.args.cancellation.is_cancelled() {
+                break;
+            }
+            let source = Source {
+                path,
+                text,
+                locations: all_locations.get(index).unwrap_or(&no_locations),
+                schema: None,
+                spec_version: None,
+                previous: None,
+            };
+            let (code, captured) = output::capture(|| validate_document(&self.args, &source, doc));
+            validation.valid &= code == ExitCode::SUCCESS;
+            validation.errors.extend(captured.errors());
+            validation
+                .findings
+                .extend(captured.deliver(&self.reporters));
+        }
+        self.reporters.finish();
+        validation.cancelled = self.args.cancellation.is_cancelled();
+        validation.valid &= !validation.cancelled;
+        validation
+    }
+}
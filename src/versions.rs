@@ -0,0 +1,204 @@
+//! `versions check`: keeps the version map and the configuration file from rotting.
+
+use crate::{
+    diagnostics::Diagnostic, expand_inputs, extract_spec_version, resolve_versions_map_path, rules,
+    schemas::map_target, workspace_documents, Args,
+};
+use jsonschema::JSONSchema;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Checks that every version map entry points to a usable schema and is referenced by at least
+/// one spec under `paths`, and that the configuration file holds no entries without effect.
+pub fn check(args: &Args, paths: &[PathBuf]) -> ExitCode {
+    let files = match expand_inputs(paths) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    let used = referenced_versions(args, &files);
+    check_version_map(args, &paths[0], &used, &mut diagnostics);
+    check_config(args, &files, &mut diagnostics);
+
+    let mut failed = false;
+    diagnostics.retain(|d| rules::code_selected(d.code, &args.select, &args.ignore));
+    for diagnostic in &diagnostics {
+        errln!(
+            "{} versions check [{}]: {}",
+            diagnostic.severity.icon(),
+            diagnostic.code,
+            diagnostic.message
+        );
+        failed |= diagnostic.severity >= args.fail_on();
+    }
+
+    if diagnostics.is_empty() {
+        outln!(
+            "✅ OK — no broken or unused configuration ({} spec file(s) scanned).",
+            files.len()
+        );
+    }
+    if failed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::from(0)
+    }
+}
+
+/// Spec versions used by the (non-excluded) spec files, with the files that use each of them.
+fn referenced_versions(args: &Args, files: &[PathBuf]) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut used: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for (file, doc) in workspace_documents(args, files) {
+        if let Ok(Some(version)) = extract_spec_version(&doc) {
+            let users = used.entry(version).or_default();
+            if !users.contains(&file) {
+                users.push(file);
+            }
+        }
+    }
+    used
+}
+
+/// Broken targets and unused entries in the version map; versions used by specs but missing
+/// from the map.
+fn check_version_map(
+    args: &Args,
+    start: &Path,
+    used: &BTreeMap<String, Vec<PathBuf>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let map_path = match resolve_versions_map_path(args.versions_map(), start) {
+        Ok(path) => path,
+        Err(_) if used.is_empty() && args.versions_map.is_none() => return,
+        Err(msg) => {
+            diagnostics.push(Diagnostic::error(
+                "PV080",
+                msg.trim_start_matches("Error: "),
+            ));
+            return;
+        }
+    };
+    let map: HashMap<String, String> = match fs::read_to_string(&map_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_yaml::from_str(&text).map_err(|e| e.to_string()))
+    {
+        Ok(map) => map,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error(
+                "PV080",
+                format!(
+                    "version map {} is not a valid 'version: path' mapping: {e}",
+                    map_path.display()
+                ),
+            ));
+            return;
+        }
+    };
+    let entries: BTreeMap<&String, &String> = map.iter().collect();
+
+    for (version, target) in &entries {
+        let resolved = map_target(&map_path, target);
+        match args.schemas.by_uri(&resolved) {
+            Ok(resolved) => {
+                if let Err(e) = JSONSchema::compile(&resolved.schema) {
+                    diagnostics.push(Diagnostic::error(
+                        "PV080",
+                        format!(
+                            "version '{version}' points to an invalid schema {}: {e}",
+                            resolved.origin
+                        ),
+                    ));
+                }
+            }
+            Err(msg) => diagnostics.push(Diagnostic::error(
+                "PV080",
+                format!(
+                    "version '{version}' points to an unusable schema: {}",
+                    msg.trim_start_matches("Error: ")
+                ),
+            )),
+        }
+        if !used.contains_key(*version) {
+            diagnostics.push(Diagnostic::warning(
+                "PV081",
+                format!(
+                    "version '{version}' in {} is not used by any spec",
+                    map_path.display()
+                ),
+            ));
+        }
+    }
+
+    for (version, files) in used {
+        if !map.contains_key(version) {
+            let names: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+            diagnostics.push(Diagnostic::error(
+                "PV082",
+                format!(
+                    "spec_version '{version}' (used by {}) is missing from {}",
+                    names.join(", "),
+                    map_path.display()
+                ),
+            ));
+        }
+    }
+}
+
+/// Config entries that no longer do anything: exclude patterns matching no file, rule entries
+/// that only restate the defaults and library paths that do not exist.
+fn check_config(args: &Args, files: &[PathBuf], diagnostics: &mut Vec<Diagnostic>) {
+    let config = &args.settings;
+    let Some(config_path) = &config.path else {
+        return;
+    };
+    let location = config_path.display();
+
+    for pattern in &config.exclude {
+        if !files.iter().any(|file| config.excluded_by(pattern, file)) {
+            diagnostics.push(Diagnostic::warning(
+                "PV083",
+                format!("exclude pattern '{pattern}' in {location} matches no spec file"),
+            ));
+        }
+    }
+
+    for (name, rule) in &config.rules {
+        // An entry for an opt-in rule is what enables it.
+        let opt_in = rules::registered()
+            .get(name)
+            .is_some_and(|rule| !rule.enabled_by_default());
+        if rule.enabled
+            && !opt_in
+            && rule.severity.is_none()
+            && rule.becomes_error.is_none()
+            && rule.messages.is_empty()
+            && rule.matching.is_none()
+            && rule.patterns.is_none()
+        {
+            diagnostics.push(Diagnostic::warning(
+                "PV083",
+                format!("rules.{name} in {location} only restates the defaults"),
+            ));
+        }
+    }
+
+    for dir in &config.library_paths {
+        if !dir.is_dir() {
+            diagnostics.push(Diagnostic::warning(
+                "PV083",
+                format!(
+                    "library path {} in {location} does not exist",
+                    dir.display()
+                ),
+            ));
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! `workspace graph`: which specs depend on which shared files, so that the blast radius of a
+//! change to a contract library or a shared schema fragment is known before making it.
+//!
+//! A spec depends on the library bundles its `implementation.uses` entries resolve to (found the
+//! way validation finds them) and on the files its `$ref`s point to; bundles and fragments depend
+//! on the files their own `$ref`s point to, followed transitively. Remote references are shown but
+//! not followed. Files that import each other in a cycle are reported.
+
+use crate::{
+    display_input, encoding, expand_inputs,
+    graph::{mermaid_text, quote},
+    is_program_spec, libraries, parse_documents, workspace_documents, Args, InputFormat,
+};
+use clap::ValueEnum;
+use serde_json::{json, Value as JsonValue};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Component, Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Output format of `workspace graph`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WorkspaceFormat {
+    /// Graphviz DOT, for `dot -Tsvg`.
+    #[default]
+    Dot,
+    /// A fenced Mermaid `flowchart` block, for Markdown docs rendered by GitHub or GitLab.
+    Mermaid,
+    /// One JSON object with the nodes, edges and cycles, for tools.
+    Json,
+}
+
+/// What a file of the workspace graph is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Spec,
+    /// A contract library bundle.
+    Library,
+    /// A file a `$ref` points to.
+    Fragment,
+    /// A `$ref` to a URL, which is not followed.
+    Remote,
+    /// An import that does not resolve: a missing file or library.
+    Missing,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Spec => "spec",
+            Kind::Library => "library",
+            Kind::Fragment => "fragment",
+            Kind::Remote => "remote",
+            Kind::Missing => "missing",
+        }
+    }
+}
+
+struct Node {
+    /// The path or URL of the file, or `library NAME` for a library that does not resolve.
+    id: String,
+    kind: Kind,
+    /// `NAME VERSION` of a library bundle.
+    library: Option<String>,
+    /// Why a missing import cannot be found.
+    problem: Option<String>,
+}
+
+/// An import: `uses` with its version constraint, or `$ref`.
+struct Edge {
+    from: usize,
+    to: usize,
+    via: String,
+}
+
+#[derive(Default)]
+struct Workspace {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    /// Node by the canonical path of its file (or its id when it has no file).
+    index: HashMap<String, usize>,
+    /// Files whose references still have to be followed.
+    pending: Vec<(usize, PathBuf)>,
+}
+
+impl Workspace {
+    /// The node of `key`, added as `id` of `kind` when it is new.
+    fn node(&mut self, key: String, id: String, kind: Kind) -> (usize, bool) {
+        if let Some(&node) = self.index.get(&key) {
+            return (node, false);
+        }
+        self.nodes.push(Node {
+            id,
+            kind,
+            library: None,
+            problem: None,
+        });
+        self.index.insert(key, self.nodes.len() - 1);
+        (self.nodes.len() - 1, true)
+    }
+
+    /// The node of the local file `path`, queued to have its references followed when it is new.
+    fn file(&mut self, path: &Path, kind: Kind) -> usize {
+        let path = normalize(path);
+        let key = fs::canonicalize(&path)
+            .unwrap_or_else(|_| path.clone())
+            .display()
+            .to_string();
+        let (node, new) = self.node(key, path.display().to_string(), kind);
+        if new {
+            self.pending.push((node, path));
+        } else if kind == Kind::Spec {
+            self.nodes[node].kind = Kind::Spec;
+        }
+        node
+    }
+
+    fn edge(&mut self, from: usize, to: usize, via: String) {
+        if !self
+            .edges
+            .iter()
+            .any(|edge| edge.from == from && edge.to == to && edge.via == via)
+        {
+            self.edges.push(Edge { from, to, via });
+        }
+    }
+
+    /// Adds the targets of the `$ref`s in `doc`, a document of the file `node` in `dir`.
+    fn references(&mut self, node: usize, doc: &JsonValue, dir: &Path) {
+        let mut targets = BTreeSet::new();
+        collect_references(doc, &mut targets);
+        for target in targets {
+            let to = if target.starts_with("http://") || target.starts_with("https://") {
+                self.node(target.clone(), target, Kind::Remote).0
+            } else {
+                self.file(&dir.join(target), Kind::Fragment)
+            };
+            self.edge(node, to, "$ref".to_string());
+        }
+    }
+
+    /// Follows the references of every queued file, marking the ones that cannot be read.
+    fn follow(&mut self, args: &Args) {
+        while let Some((node, path)) = self.pending.pop() {
+            let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            let text = match encoding::read(&path) {
+                Ok(decoded) => decoded.text,
+                Err(e) => {
+                    self.nodes[node].kind = Kind::Missing;
+                    self.nodes[node].problem =
+                        Some(format!("{} cannot be read: {e}", path.display()));
+                    continue;
+                }
+            };
+            let format = args
+                .input_format
+                .unwrap_or_else(|| InputFormat::from_path(&path));
+            let Ok(docs) = parse_documents(&text, format) else {
+                continue;
+            };
+            for doc in &docs {
+                self.references(node, doc, &dir);
+                if self.nodes[node].kind == Kind::Spec {
+                    self.imports(args, node, doc, &dir);
+                }
+            }
+        }
+    }
+
+    /// Adds the library bundles the `implementation.uses` entries of the spec `doc` resolve to.
+    fn imports(&mut self, args: &Args, node: usize, doc: &JsonValue, dir: &Path) {
+        let found = libraries::locate(doc, dir, &args.library_paths, &args.fetcher, true);
+        for (name, import) in found {
+            let (to, via) = match import {
+                Ok(import) => {
+                    let to = if import.origin.contains("://") {
+                        let origin = import.origin.clone();
+                        self.node(origin.clone(), origin, Kind::Library).0
+                    } else {
+                        self.file(Path::new(&import.origin), Kind::Library)
+                    };
+                    self.nodes[to].kind = Kind::Library;
+                    self.nodes[to].library = Some(format!("{} {}", import.library, import.version));
+                    (to, format!("uses {}", import.constraint))
+                }
+                Err(msg) => {
+                    let id = format!("library {}", name.as_deref().unwrap_or("?"));
+                    let (to, _) = self.node(id.clone(), id, Kind::Missing);
+                    self.nodes[to].problem = Some(msg);
+                    (to, "uses".to_string())
+                }
+            };
+            self.edge(node, to, via);
+        }
+    }
+
+    /// The specs that depend on `node`, directly or through other files.
+    fn dependents(&self, node: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = vec![node];
+        while let Some(current) = stack.pop() {
+            for edge in self.edges.iter().filter(|edge| edge.to == current) {
+                if !seen[edge.from] {
+                    seen[edge.from] = true;
+                    stack.push(edge.from);
+                }
+            }
+        }
+        (0..self.nodes.len())
+            .filter(|&other| other != node && seen[other] && self.nodes[other].kind == Kind::Spec)
+            .collect()
+    }
+
+    /// One cycle per set of files that import each other, as the files along it.
+    fn cycles(&self) -> Vec<Vec<usize>> {
+        // 0: not visited, 1: on the current path, 2: done.
+        let mut state = vec![0u8; self.nodes.len()];
+        let mut cycles: Vec<Vec<usize>> = Vec::new();
+        for start in 0..self.nodes.len() {
+            if state[start] != 0 {
+                continue;
+            }
+            let mut path = vec![start];
+            let mut next = vec![0];
+            state[start] = 1;
+            while let (Some(&node), Some(edge)) = (path.last(), next.last_mut()) {
+                let Some(found) = self.edges.iter().filter(|e| e.from == node).nth(*edge) else {
+                    state[node] = 2;
+                    path.pop();
+                    next.pop();
+                    continue;
+                };
+                *edge += 1;
+                match state[found.to] {
+                    0 => {
+                        state[found.to] = 1;
+                        path.push(found.to);
+                        next.push(0);
+                    }
+                    1 => {
+                        let at = path.iter().position(|&n| n == found.to).unwrap_or(0);
+                        let cycle = path[at..].to_vec();
+                        let members: BTreeSet<_> = cycle.iter().collect();
+                        if !cycles
+                            .iter()
+                            .any(|known| known.iter().collect::<BTreeSet<_>>() == members)
+                        {
+                            cycles.push(cycle);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        cycles
+    }
+
+    fn in_cycle(cycles: &[Vec<usize>], edge: &Edge) -> bool {
+        cycles.iter().any(|cycle| {
+            let at = cycle.iter().position(|&n| n == edge.from);
+            at.is_some_and(|at| cycle[(at + 1) % cycle.len()] == edge.to)
+        })
+    }
+
+    fn label(&self, node: usize) -> String {
+        let node = &self.nodes[node];
+        match &node.library {
+            Some(library) => format!("{library}\n{}", node.id),
+            None => node.id.clone(),
+        }
+    }
+
+    fn to_dot(&self, cycles: &[Vec<usize>]) -> String {
+        let mut dot = "digraph workspace {\n  rankdir=LR;\n".to_string();
+        dot.push_str("  node [shape=box, fontname=\"Helvetica\"];\n");
+        dot.push_str("  edge [fontname=\"Helvetica\", fontsize=10];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let style = match node.kind {
+                Kind::Spec => "style=bold",
+                Kind::Library => "shape=component",
+                Kind::Fragment => "shape=note",
+                Kind::Remote => "shape=note, style=dashed",
+                Kind::Missing => "style=dashed, color=red, fontcolor=red",
+            };
+            dot.push_str(&format!(
+                "  n{i} [label={}, {style}];\n",
+                quote(&self.label(i))
+            ));
+        }
+        for edge in &self.edges {
+            let mut attributes = format!("label={}", quote(&edge.via));
+            if edge.via == "$ref" {
+                attributes.push_str(", style=dotted");
+            }
+            if Self::in_cycle(cycles, edge) {
+                attributes.push_str(", color=red, fontcolor=red, penwidth=2");
+            }
+            dot.push_str(&format!(
+                "  n{} -> n{} [{attributes}];\n",
+                edge.from, edge.to
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_mermaid(&self, cycles: &[Vec<usize>]) -> String {
+        let mut mermaid = "```mermaid\nflowchart LR\n".to_string();
+        for (i, node) in self.nodes.iter().enumerate() {
+            let lines: Vec<String> = self.label(i).lines().map(mermaid_text).collect();
+            let label = lines.join("<br/>");
+            let (open, close) = match node.kind {
+                Kind::Spec => ("[", "]"),
+                Kind::Library => ("[[", "]]"),
+                Kind::Fragment | Kind::Remote => ("[/", "/]"),
+                Kind::Missing => ("([", "])"),
+            };
+            mermaid.push_str(&format!("  n{i}{open}\"{label}\"{close}\n"));
+        }
+        let mut red = Vec::new();
+        for (i, edge) in self.edges.iter().enumerate() {
+            let arrow = if edge.via == "$ref" { "-.->" } else { "-->" };
+            mermaid.push_str(&format!(
+                "  n{} {arrow}|\"{}\"| n{}\n",
+                edge.from,
+                mermaid_text(&edge.via),
+                edge.to
+            ));
+            if Self::in_cycle(cycles, edge) {
+                red.push(i.to_string());
+            }
+        }
+        if !red.is_empty() {
+            mermaid.push_str(&format!(
+                "  linkStyle {} stroke:red,color:red,stroke-width:2px\n",
+                red.join(",")
+            ));
+        }
+        let missing: Vec<String> = (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].kind == Kind::Missing)
+            .map(|i| format!("n{i}"))
+            .collect();
+        if !missing.is_empty() {
+            mermaid.push_str("  classDef missing stroke:red,color:red,stroke-dasharray:4\n");
+            mermaid.push_str(&format!("  class {} missing\n", missing.join(",")));
+        }
+        mermaid.push_str("```\n");
+        mermaid
+    }
+
+    fn to_json(&self, cycles: &[Vec<usize>]) -> String {
+        let id = |node: usize| self.nodes[node].id.clone();
+        let nodes: Vec<JsonValue> = (0..self.nodes.len())
+            .map(|i| {
+                let node = &self.nodes[i];
+                let mut value = json!({ "id": node.id, "kind": node.kind.name() });
+                if let Some(library) = &node.library {
+                    value["library"] = library.clone().into();
+                }
+                if node.kind != Kind::Spec {
+                    let dependents: Vec<String> = self.dependents(i).into_iter().map(id).collect();
+                    value["dependent_specs"] = dependents.into();
+                }
+                value
+            })
+            .collect();
+        let edges: Vec<JsonValue> = self
+            .edges
+            .iter()
+            .map(|edge| json!({ "from": id(edge.from), "to": id(edge.to), "via": edge.via }))
+            .collect();
+        let cycles: Vec<Vec<String>> = cycles
+            .iter()
+            .map(|cycle| cycle.iter().map(|&node| id(node)).collect())
+            .collect();
+        let graph = json!({ "nodes": nodes, "edges": edges, "cycles": cycles });
+        serde_json::to_string_pretty(&graph).unwrap_or_default()
+    }
+}
+
+/// The `$ref`s of `value` to other files (or URLs), without their fragments.
+fn collect_references(value: &JsonValue, targets: &mut BTreeSet<String>) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", JsonValue::String(target)) => {
+                        let file = target.split('#').next().unwrap_or_default();
+                        if !file.is_empty() {
+                            targets.insert(file.to_string());
+                        }
+                    }
+                    _ => collect_references(value, targets),
+                }
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_references(item, targets);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `path` without `.` and with `..` applied where possible, so that one file has one name.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(normal.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                normal.pop();
+            }
+            other => normal.push(other),
+        }
+    }
+    normal
+}
+
+/// Prints the dependency graph of the specs in `paths` in `format`, or writes it to `output`.
+/// Fails when files import each other in a cycle.
+pub fn graph(
+    args: &Args,
+    paths: &[PathBuf],
+    format: WorkspaceFormat,
+    output: Option<&Path>,
+) -> ExitCode {
+    let files = match expand_inputs(paths) {
+        Ok(files) => files,
+        Err(msg) => {
+            failln!("{msg}");
+            return ExitCode::from(1);
+        }
+    };
+    let mut workspace = Workspace::default();
+    for (file, doc) in workspace_documents(args, &files) {
+        if is_program_spec(&doc) {
+            workspace.file(&file, Kind::Spec);
+        }
+    }
+    if workspace.nodes.is_empty() {
+        failln!("Error: no program specs found in the given paths");
+        return ExitCode::from(1);
+    }
+    workspace.follow(args);
+
+    let cycles = workspace.cycles();
+    let text = match format {
+        WorkspaceFormat::Dot => workspace.to_dot(&cycles),
+        WorkspaceFormat::Mermaid => workspace.to_mermaid(&cycles),
+        WorkspaceFormat::Json => workspace.to_json(&cycles),
+    };
+    let mut failed = false;
+    match output {
+        Some(path) => match fs::write(path, text) {
+            Ok(()) => outln!(
+                "🗺️ Wrote the dependencies of {} spec(s) on {} shared file(s) to {}.",
+                workspace
+                    .nodes
+                    .iter()
+                    .filter(|n| n.kind == Kind::Spec)
+                    .count(),
+                workspace
+                    .nodes
+                    .iter()
+                    .filter(|n| n.kind != Kind::Spec)
+                    .count(),
+                path.display()
+            ),
+            Err(e) => {
+                failln!("Error: failed to write {}: {e}", path.display());
+                failed = true;
+            }
+        },
+        None => outln!("{}", text.trim_end()),
+    }
+    for node in workspace.nodes.iter().filter(|n| n.kind == Kind::Missing) {
+        let problem = node.problem.as_deref().unwrap_or("it cannot be found");
+        errln!("⚠️ {} is imported, but {problem}", node.id);
+    }
+    for cycle in &cycles {
+        let mut files: Vec<String> = cycle
+            .iter()
+            .map(|&node| display_input(Path::new(&workspace.nodes[node].id)))
+            .collect();
+        files.push(files[0].clone());
+        errln!(
+            "❌ Files import each other in a cycle: {}",
+            files.join(" → ")
+        );
+        failed = true;
+    }
+    ExitCode::from(u8::from(failed))
+}
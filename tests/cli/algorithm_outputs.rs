@@ -0,0 +1,58 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// `draft` produces `notes` and `text`; `release` is built from `notes` and `summary` is the
+/// `text` the return_contract produces.
+fn spec(outputs: JsonValue, properties: JsonValue) -> JsonValue {
+    let notes = json!({"kind": "phase_output", "phase": "draft", "port": "notes"});
+    let mut outputs = outputs;
+    outputs[0]["build"] = json!({"object": [{"name": "notes", "source": notes}]});
+    json!({
+        "meta": {"title": "A", "version": "v1"},
+        "algorithm": {"name": "A", "phases": ["draft"], "outputs": outputs},
+        "implementation": {
+            "phase_contracts": {"draft": {"outputs": [{"name": "notes"}, {"name": "text"}]}},
+            "return_contract": {
+                "produced_by": {"phase": "draft", "port": "text"},
+                "schema": {"properties": properties}
+            }
+        }
+    })
+}
+
+#[test]
+fn passes_outputs_defined_once() {
+    let run = Scratch::new().check(&spec(
+        json!([{"name": "release"}, {"name": "text"}, {"name": "summary"}]),
+        json!({
+            "summary": {"type": "string"},
+            "release": {"$ref": "#/algorithm/outputs/0/schema"}
+        }),
+    ));
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_missing_repeated_and_double_outputs() {
+    let run = Scratch::new().check(&spec(
+        json!([
+            {"name": "release"},
+            {"name": "text"},
+            {"name": "text"},
+            {"name": "changelog"}
+        ]),
+        json!({"release": {"type": "object"}}),
+    ));
+    assert_eq!(run.code, Some(1));
+    for finding in [
+        "[PV242]: Algorithm output 'release' is built by a composition and also produced by the \
+         return_contract (/implementation/return_contract/schema/properties/release)",
+        "[PV241]: Algorithm output 'text' is declared more than once (first as algorithm.outputs \
+         #1)",
+        "[PV240]: Algorithm output 'changelog' is neither built by a composition nor produced by \
+         the return_contract",
+    ] {
+        let finding = format!("❌ Rule: algorithm outputs {finding}");
+        assert!(run.reports(&finding), "{finding}\n{}", run.stderr);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::support::Scratch;
+
+const CONFIG: &str = r#"schema: open-schema.json
+assertions:
+  - path: $.algorithm.phases
+    assert: length <= 2
+    message: "the algorithm has {length} phases; split it (at most 2)"
+    severity: warning
+  - code: CFG100
+    path: $.implementation.phase_contracts.*
+    assert: contains(value, 'description')
+  - path: $..telemetry.name
+    assert: matches(value, '^[a-z][a-z0-9_.]*$')
+"#;
+
+fn workspace(config: &str) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(".program-verify.yaml", config);
+    scratch.write("open-schema.json", "{}");
+    scratch
+}
+
+fn spec(phases: &str, contract: &str) -> String {
+    format!(
+        "meta: {{title: Support, version: v1}}\nalgorithm: {{name: Support, phases: {phases}}}\n\
+         implementation:\n  phase_contracts:\n    triage: {contract}\n"
+    )
+}
+
+#[test]
+fn passes_specs_that_satisfy_the_assertions() {
+    let scratch = workspace(CONFIG);
+    scratch.write(
+        "spec.yml",
+        &spec(
+            "[triage]",
+            "{description: x, telemetry: {name: support.triage}}",
+        ),
+    );
+    let run = scratch.run(&["--fail-on", "warning", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_nodes_that_fail_an_assertion() {
+    let scratch = workspace(CONFIG);
+    scratch.write(
+        "spec.yml",
+        &spec("[a, b, triage]", "{telemetry: {name: Support.Triage}}"),
+    );
+    let run = scratch.run(&["--format", "json", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    for (code, severity, pointer, message) in [
+        (
+            "CFG001",
+            "warning",
+            "/algorithm/phases",
+            "the algorithm has 3 phases; split it (at most 2)",
+        ),
+        (
+            "CFG100",
+            "error",
+            "/implementation/phase_contracts/triage",
+            "",
+        ),
+        (
+            "CFG003",
+            "error",
+            "/implementation/phase_contracts/triage/telemetry/name",
+            "",
+        ),
+    ] {
+        let line = run
+            .stdout
+            .lines()
+            .find(|line| line.contains(&format!(r#""code":"{code}""#)))
+            .unwrap_or_else(|| panic!("{code}: {}", run.stdout));
+        assert!(
+            line.contains(&format!(r#""severity":"{severity}""#)),
+            "{line}"
+        );
+        assert!(
+            line.contains(&format!(r#""pointer":"{pointer}""#)),
+            "{line}"
+        );
+        assert!(line.contains(message), "{line}");
+    }
+
+    scratch.write(
+        ".program-verify.yaml",
+        &format!("{CONFIG}rules:\n  assertions:\n    enabled: false\n"),
+    );
+    let run = scratch.run(&["spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_expressions_that_do_not_evaluate_to_a_boolean() {
+    let scratch = workspace(
+        "schema: open-schema.json\nassertions:\n  - path: $.meta.title\n    assert: value < 3\n",
+    );
+    scratch.write("spec.yml", &spec("[triage]", "{}"));
+    let run = scratch.run(&["spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("[CFG001]"), "{}", run.stderr);
+    assert!(
+        run.reports("cannot compare a string with a number"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn rejects_invalid_assertions() {
+    for (assertion, error) in [
+        ("path: algorithm\n    assert: length > 0", "algorithm"),
+        (
+            "path: $.algorithm\n    assert: length >",
+            "unexpected end of the expression",
+        ),
+        (
+            "path: $.algorithm\n    assert: size(value)",
+            "unknown function 'size'",
+        ),
+    ] {
+        let scratch = workspace(&format!(
+            "schema: open-schema.json\nassertions:\n  - {assertion}\n"
+        ));
+        scratch.write("spec.yml", &spec("[triage]", "{}"));
+        let run = scratch.run(&["spec.yml"]);
+        assert!(!run.success(), "{assertion}");
+        assert!(run.reports(error), "{assertion}: {}", run.stderr);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+fn spec(name: &str, classification: &str) -> JsonValue {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": name, "phases": ["collect"] },
+        "implementation": {
+            "phase_contracts": { "collect": { "data_classification": classification } }
+        }
+    })
+}
+
+#[test]
+fn hides_recorded_findings_and_reports_new_ones() {
+    let scratch = Scratch::new();
+    let run = scratch.check(&spec("Billing", "pii"));
+    assert!(!run.success());
+
+    let args = ["--schema", "open-schema.json", "spec.yml"];
+    let run = scratch.run(&[&args[..], &["--write-baseline", "baseline.json"]].concat());
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .contains("📝 Recorded 1 finding(s) in baseline.json."));
+
+    let with_baseline = [&args[..], &["--baseline", "baseline.json"]].concat();
+    let run = scratch.run(&with_baseline);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("[PV001]"));
+    assert!(run
+        .stdout
+        .contains("ℹ️ 1 known finding(s) hidden by the baseline baseline.json."));
+
+    scratch.check(&spec("Billing", "secret"));
+    let run = scratch.run(&with_baseline);
+    assert!(!run.success());
+    assert!(run.reports("[PV021]") && !run.reports("[PV001]"));
+}
+
+#[test]
+fn counts_stale_entries() {
+    let scratch = Scratch::new();
+    scratch.check(&spec("Billing", "pii"));
+    scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "spec.yml",
+        "--write-baseline",
+        "baseline.json",
+    ]);
+
+    scratch.check(&spec("Support", "pii"));
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "spec.yml",
+        "--baseline",
+        "baseline.json",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.contains("ℹ️ 1 baseline entries no longer occur"));
+}
@@ -0,0 +1,68 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A spec with a PV001 finding, validated with `becomes_error` on its rule.
+fn check(cutoff: &str, args: &[&str]) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        ".program-verify.yaml",
+        &format!("rules:\n  title-vs-algorithm:\n    becomes_error: {cutoff}\n"),
+    );
+    scratch.write(
+        "spec.yml",
+        "meta: {title: Support, version: v1}\nalgorithm: {name: Billing, phases: [x]}\n",
+    );
+    scratch.run(&[&["--schema", "open-schema.json", "spec.yml"], args].concat())
+}
+
+#[test]
+fn reports_warnings_until_the_cutoff() {
+    for cutoff in ["2999-01-01", "99.0.0", "v99"] {
+        let run = check(cutoff, &[]);
+        assert!(run.success(), "{cutoff}: {}", run.stderr);
+        assert!(
+            run.reports("⚠️ Rule: meta.title vs algorithm.name [PV001]"),
+            "{cutoff}"
+        );
+    }
+    for cutoff in ["2000-01-01", "0.0.1", "v0.1"] {
+        let run = check(cutoff, &[]);
+        assert_eq!(run.code, Some(1), "{cutoff}");
+        assert!(
+            run.reports("❌ Rule: meta.title vs algorithm.name [PV001]"),
+            "{cutoff}"
+        );
+    }
+}
+
+#[test]
+fn records_the_cutoff_in_the_provenance() {
+    let run = check("2999-01-01", &["--format", "json"]);
+    let provenance = run
+        .stdout
+        .lines()
+        .map(|line| serde_json::from_str::<JsonValue>(line).unwrap())
+        .find_map(|line| line.get("provenance").cloned())
+        .unwrap();
+    let rule = &provenance["rules"][0];
+    assert_eq!(rule["name"], "title-vs-algorithm");
+    assert_eq!(rule["severity"], "warning");
+    assert_eq!(rule["becomes_error"], json!("2999-01-01"));
+}
+
+#[test]
+fn rejects_invalid_cutoffs() {
+    let run = check("soon", &[]);
+    assert!(!run.success());
+    assert!(run.reports(
+        "invalid becomes_error 'soon', expected a YYYY-MM-DD date or a version such as 0.2.0"
+    ));
+
+    let run = check("2999-01-01\n    severity: info", &[]);
+    assert!(!run.success());
+    assert!(run.reports(
+        "Error: rule 'title-vs-algorithm' has both a severity and becomes_error; the schedule sets \
+         the severity"
+    ));
+}
@@ -0,0 +1,96 @@
+use crate::support::Scratch;
+use std::process::Command;
+
+/// The spec named `name`.
+fn spec(name: &str) -> String {
+    format!(
+        "meta: {{title: {name}, version: v1.0.0}}\nalgorithm: {{name: {name}, phases: [fetch]}}\n"
+    )
+}
+
+fn git(scratch: &Scratch, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(scratch.path(""))
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {args:?}");
+}
+
+/// A git repository whose first commit holds the specs `a.yml` and `b.yml`.
+fn repository() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("specs/a.yml", &spec("A"));
+    scratch.write("specs/b.yml", &spec("B"));
+    git(&scratch, &["init", "-q"]);
+    git(&scratch, &["add", "specs"]);
+    git(&scratch, &["commit", "-q", "-m", "specs"]);
+    scratch
+}
+
+fn validate(scratch: &Scratch) -> crate::support::Run {
+    scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "specs",
+        "--changed-since",
+        "HEAD",
+    ])
+}
+
+#[test]
+fn validates_only_changed_specs() {
+    let scratch = repository();
+    let run = validate(&scratch);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(run.stdout, "✅ No spec file changed since HEAD.\n");
+
+    scratch.write(
+        "specs/b.yml",
+        &spec("B")
+            .replace("[fetch]", "[fetch, store]")
+            .replace("v1.0.0", "v1.1.0"),
+    );
+    scratch.write("specs/c.yml", &spec("C"));
+    let run = validate(&scratch);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("b.yml") && run.reports("c.yml"));
+    assert!(!run.reports("a.yml"));
+}
+
+#[test]
+fn reports_stale_and_empty_bumps() {
+    let scratch = repository();
+    // Formatting and comments are no change.
+    scratch.write("specs/a.yml", &format!("# Reformatted\n{}\n", spec("A")));
+    scratch.write(
+        "specs/b.yml",
+        &spec("B").replace("[fetch]", "[fetch, store]"),
+    );
+    let run = validate(&scratch);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.reports(
+            "⚠️ Rule: metadata bumps [PV230]: The spec changed since HEAD, but meta.version \
+             (v1.0.0) did not"
+        ),
+        "{}",
+        run.stderr
+    );
+    assert_eq!(run.stderr.matches("[PV230]").count(), 1);
+
+    scratch.write("specs/a.yml", &spec("A").replace("v1.0.0", "v1.0.1"));
+    let run = validate(&scratch);
+    assert!(run.reports("[PV231]"), "{}", run.stderr);
+}
+
+#[test]
+fn rejects_unknown_revisions() {
+    let scratch = repository();
+    let run = scratch.run(&["specs", "--changed-since", "no-such-branch"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("--changed-since no-such-branch is not a commit"));
+}
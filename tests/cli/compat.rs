@@ -0,0 +1,79 @@
+use crate::support::Scratch;
+
+/// A spec with the phases `collect` and, unless `phases` says otherwise, `reply`, and the given
+/// `collect` ports.
+fn spec(phases: &str, inputs: &str, outputs: &str) -> String {
+    format!(
+        "\
+meta: {{title: Support, version: v1}}
+algorithm: {{name: Support, phases: {phases}}}
+implementation:
+  phase_contracts:
+    collect:
+      inputs: {inputs}
+      outputs: {outputs}
+    reply:
+      inputs: []
+"
+    )
+}
+
+const TICKET: &str = "{name: ticket, schema: {type: object}}";
+const ISSUE: &str = "{name: issue, schema: {type: number}}";
+
+fn compat(new: &str, flags: &[&str]) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write(
+        "old.yml",
+        &spec("[collect, reply]", &format!("[{TICKET}]"), &format!("[{ISSUE}]")),
+    );
+    scratch.write("new.yml", new);
+    scratch.run(&[&["compat", "old.yml", "new.yml"], flags].concat())
+}
+
+#[test]
+fn additions_are_not_breaking() {
+    let run = compat(
+        &spec(
+            "[collect, reply]",
+            &format!("[{TICKET}, {{name: hint, optional: true}}]"),
+            &format!("[{ISSUE}, {{name: urgency}}]"),
+        ),
+        &[],
+    );
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+    assert_eq!(
+        run.stdout,
+        "\
+── old.yml → new.yml: 0 breaking, 2 non-breaking change(s) ──
+✅ Non-breaking: Phase 'collect' output 'urgency' added
+✅ Non-breaking: Phase 'collect' optional input 'hint' added
+✅ No breaking changes.
+"
+    );
+}
+
+#[test]
+fn reports_breaking_changes() {
+    let new = spec(
+        "[collect]",
+        "[{name: ticket, schema: {type: object, required: [id]}}, {name: tenant}]",
+        "[{name: issue, schema: {type: [number, string]}}]",
+    )
+    .replace("    reply:\n      inputs: []\n", "");
+    let breaking = "\
+── old.yml → new.yml: 4 breaking, 0 non-breaking change(s) ──
+❌ Breaking: Phase 'collect' output 'issue' widened: type number → number, string
+❌ Breaking: Phase 'collect' input 'ticket' narrowed: property 'id' now required
+❌ Breaking: Phase 'collect' required input 'tenant' added
+❌ Breaking: Phase 'reply' removed
+";
+    let run = compat(&new, &[]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(breaking), "{}", run.stdout);
+    assert!(run.reports("❌ 4 breaking change(s); pass --allow-breaking to accept them."));
+
+    let run = compat(&new, &["--allow-breaking"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ 4 breaking change(s) allowed by --allow-breaking."));
+}
@@ -0,0 +1,114 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+/// A spec whose title does not match its algorithm name.
+fn mismatched() -> String {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Billing", "phases": [] }
+    })
+    .to_string()
+}
+
+#[test]
+fn applies_the_nearest_config() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("specs/spec.yml", &mismatched());
+    let run = scratch.run(&["--schema", "open-schema.json", "specs/spec.yml"]);
+    assert!(!run.success());
+
+    scratch.write(
+        ".program-verify.yaml",
+        "schema: open-schema.json\nrules:\n  title-vs-algorithm:\n    severity: warning\n",
+    );
+    let run = scratch.run(&["specs/spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ Rule: meta.title vs algorithm.name"));
+
+    // Flags override the config.
+    let run = scratch.run(&["--fail-on", "warning", "specs/spec.yml"]);
+    assert!(!run.success());
+}
+
+#[test]
+fn disables_rules_and_excludes_files() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("specs/spec.yml", &mismatched());
+    scratch.write("specs/generated/spec.yml", "not: [valid");
+    scratch.write(
+        "checks.yaml",
+        "schema: open-schema.json\nexclude:\n  - specs/generated/**\nrules:\n  title-vs-algorithm:\n    enabled: false\n",
+    );
+    let run = scratch.run(&["--config", "checks.yaml", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("meta.title vs algorithm.name"));
+
+    let run = scratch.run(&["--config", "checks.yaml", "specs/generated/spec.yml"]);
+    assert!(!run.success());
+    assert!(run.reports("every input is excluded by the configuration file"));
+}
+
+#[test]
+fn rejects_invalid_configs() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", &mismatched());
+    scratch.write(".program-verify.yaml", "fail_on: [");
+    let run = scratch.run(&["spec.yml"]);
+    assert!(!run.success());
+    assert!(run.reports("Error: invalid config"));
+}
+
+#[test]
+fn message_templates_replace_messages() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &json!({
+            "meta": { "title": "Support", "version": "v1" },
+            "algorithm": { "name": "Billing", "phases": ["collect", "reply"] },
+            "implementation": { "phase_contracts": {
+                "collect": { "outputs": [{ "name": "answer" }] },
+                "reply": { "inputs": [{
+                    "name": "answer",
+                    "source": { "kind": "phase_output", "phase": "collect", "port": "question" }
+                }] }
+            } }
+        })
+        .to_string(),
+    );
+    scratch.write(
+        ".program-verify.yaml",
+        "\
+schema: open-schema.json
+rules:
+  title-vs-algorithm:
+    messages:
+      PV001: '{code} at {pointer}: {message} ({ticket}), see NAMING.md'
+  phase-contracts:
+    messages:
+      PV016: '{phase}.{port} reads nothing'
+",
+    );
+    let run = scratch.run(&["--format", "json", "spec.yml"]);
+    let messages: Vec<String> = run
+        .stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter_map(|finding| Some(finding["message"].as_str()?.to_string()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "PV001 at /algorithm/name: algorithm.name='Billing' does not match the base of \
+             meta.title='Support' (detected 'Support') ({ticket}), see NAMING.md",
+            "reply.answer reads nothing",
+            "Phase 'collect' output 'answer' is consumed by no phase input, algorithm output or \
+             return_contract",
+        ],
+        "{}",
+        run.stderr
+    );
+}
@@ -0,0 +1,59 @@
+use crate::support::Scratch;
+
+/// `fetch_orders` is a copy of `fetch_invoices` with one output renamed; `notify` shares nothing
+/// with either.
+const SPEC: &str = "\
+meta: {title: A, version: v1}
+algorithm: {name: A, phases: [fetch_invoices, fetch_orders, notify]}
+implementation:
+  phase_contracts:
+    fetch_invoices:
+      inputs: [{name: customer, schema: {type: string}}]
+      outputs: [{name: invoices, schema: {type: array}}]
+      errors: [{code: UPSTREAM_DOWN}]
+    fetch_orders:
+      inputs: [{name: customer, schema: {type: string}}]
+      outputs: [{name: orders, schema: {type: array}}]
+      errors: [{code: UPSTREAM_DOWN}]
+    notify:
+      inputs: [{name: address, schema: {type: string, format: email}}]
+";
+
+fn with_config(config: Option<&str>) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", SPEC);
+    if let Some(config) = config {
+        scratch.write(".program-verify.yaml", config);
+    }
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn is_off_unless_configured() {
+    let run = with_config(None);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("PV210"));
+
+    let run = with_config(Some("rules:\n  contract-drift:\n    enabled: false\n"));
+    assert!(!run.reports("PV210"));
+}
+
+#[test]
+fn flags_near_copies_once_enabled() {
+    let run = with_config(Some("rules:\n  contract-drift: {}\n"));
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.reports(
+            "⚠️ Rule: contract drift [PV210]: Phase contract 'fetch_orders' is a near-copy of \
+             'fetch_invoices', differing only in outputs/0/name (\"orders\" here, \"invoices\" in \
+             'fetch_invoices')\n  --> spec.yml:11:18"
+        ),
+        "{}",
+        run.stderr
+    );
+    assert_eq!(run.stderr.matches("[PV210]").count(), 1, "{}", run.stderr);
+
+    let run = with_config(Some("rules:\n  contract-drift: {severity: error}\n"));
+    assert_eq!(run.code, Some(1));
+}
@@ -0,0 +1,68 @@
+use crate::support::Scratch;
+
+/// A spec running `collect` then `analyze` and ending, with `edge` added to the graph and `input`
+/// to the inputs of `collect`.
+fn check(edge: &str, input: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}
+algorithm:
+  name: A
+  phases: [collect, analyze]
+  graph:
+    entry: c
+    nodes:
+      c: {{type: phase, phase: collect}}
+      a: {{type: phase, phase: analyze}}
+      e: {{type: end}}
+    edges:
+      - {{from: c, to: a}}
+{edge}      - {{from: a, to: e}}
+implementation:
+  phase_contracts:
+    collect:
+      inputs: [{input}]
+      outputs: [{{name: issue}}]
+    analyze:
+      outputs: [{{name: labels}}]
+"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+const READS_LABELS: &str =
+    "{name: labels, source: {kind: phase_output, phase: analyze, port: labels}}";
+
+#[test]
+fn reports_dependency_cycles() {
+    let run = check("", READS_LABELS);
+    assert_eq!(run.code, Some(1));
+    assert!(
+        run.reports(
+            "❌ Rule: dependency cycles [PV130]: Phases depend on each other in a cycle: collect → \
+             analyze → collect (collect → analyze: algorithm.graph edge #0; analyze → collect: \
+             input 'labels' reads output 'labels')\n  --> spec.yml:12:9\n"
+        ),
+        "{}",
+        run.stderr
+    );
+
+    let run = check("      - {from: a, to: c}\n", "");
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stderr.matches("[PV130]").count(), 1, "{}", run.stderr);
+    assert!(run.reports(
+        "collect → analyze → collect (collect → analyze: algorithm.graph edge #0; analyze → \
+         collect: algorithm.graph edge #1)"
+    ));
+}
+
+#[test]
+fn leaves_loops_out() {
+    let run = check("      - {from: a, to: c, kind: loop}\n", "");
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("[PV130]"));
+}
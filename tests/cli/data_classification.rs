@@ -0,0 +1,56 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A spec whose `reply` phase reads the `email` output of `collect`, with the given tags.
+fn spec(output: &str, input: Option<&str>) -> JsonValue {
+    let mut input_port = json!({
+        "name": "email",
+        "source": { "kind": "phase_output", "phase": "collect", "port": "email" }
+    });
+    if let Some(tag) = input {
+        input_port["data_classification"] = tag.into();
+    }
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Support", "phases": ["collect", "reply"] },
+        "implementation": {
+            "phase_contracts": {
+                "collect": { "outputs": [{ "name": "email", "data_classification": output }] },
+                "reply": { "inputs": [input_port] }
+            }
+        }
+    })
+}
+
+#[test]
+fn accepts_data_flowing_to_a_cleared_consumer() {
+    let scratch = Scratch::new();
+    for (output, input) in [
+        ("pii", Some("pii")),
+        ("internal", None),
+        ("public", Some("internal")),
+    ] {
+        let run = scratch.check(&spec(output, input));
+        assert!(run.success(), "{output} -> {input:?}: {}", run.stderr);
+    }
+}
+
+#[test]
+fn reports_pii_reaching_an_uncleared_consumer() {
+    let scratch = Scratch::new();
+    let run = scratch.check(&spec("pii", None));
+    assert!(!run.success());
+    assert!(run.reports(
+        "Phase 'reply' input 'email' receives 'collect.email' pii data but is only cleared for \
+         untagged (at most internal) data"
+    ));
+    let run = scratch.check(&spec("internal", Some("public")));
+    assert!(run.reports("internal data but is only cleared for public"));
+}
+
+#[test]
+fn reports_unknown_tiers() {
+    let run = Scratch::new().check(&spec("secret", Some("pii")));
+    assert!(!run.success());
+    assert!(run.reports("declares unknown data_classification 'secret'"));
+}
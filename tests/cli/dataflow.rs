@@ -0,0 +1,57 @@
+use crate::support::Scratch;
+
+/// `reply` reads `labels` of `analyze`, which reads `issue` and the input `profile` of `collect`.
+fn check(profile: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}
+algorithm: {{name: A, phases: [collect, analyze, reply]}}
+implementation:
+  phase_contracts:
+    collect:
+      outputs: [{{name: issue}}, {{name: context}}]
+    analyze:
+      inputs:
+        - {{name: issue, source: {{kind: phase_output, phase: collect, port: issue}}}}
+        - {profile}
+      outputs: [{{name: labels}}]
+    reply:
+      inputs:
+        - {{name: labels, source: {{kind: phase_output, phase: analyze, port: labels}}}}
+"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn follows_required_inputs_to_the_first_missing_link() {
+    let run = check("{name: profile, source: {kind: phase_output, phase: collect, port: profile}}");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("[PV016]"), "{}", run.stderr);
+    assert!(
+        run.reports(
+            "❌ Rule: dataflow completeness [PV150]: Required input 'labels' of phase 'reply' can \
+             never be satisfied: it reads output 'labels' of phase 'analyze', whose required input \
+             'profile' reads output 'profile' of phase 'collect', which does not declare it\n  \
+             --> spec.yml:14:11\n"
+        ),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn passes_satisfiable_and_optional_inputs() {
+    for profile in [
+        "{name: profile, source: {kind: phase_output, phase: collect, port: context}}",
+        "{name: profile, optional: true, source: {kind: phase_output, phase: collect, port: x}}",
+        "{name: profile, source: {kind: instance, path: profile}}",
+    ] {
+        let run = check(profile);
+        assert!(!run.reports("[PV150]"), "{profile}: {}", run.stderr);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::support::Scratch;
+
+/// `collect` declares `outputs`; `trace` is built into an algorithm output, `issue` is read by
+/// `reply` and `text` is the return_contract's.
+fn workspace(outputs: &str) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}
+algorithm:
+  name: A
+  phases: [collect, reply]
+  outputs:
+    - name: trace
+      build: {{source: {{kind: phase_output, phase: collect, port: trace}}}}
+implementation:
+  return_contract: {{produced_by: {{phase: reply, port: text}}}}
+  phase_contracts:
+    collect:
+      outputs: {outputs}
+    reply:
+      inputs:
+        - {{name: issue, source: {{kind: phase_output, phase: collect, port: issue}}}}
+      outputs: [{{name: text}}]
+"
+        ),
+    );
+    scratch
+}
+
+#[test]
+fn reports_outputs_nothing_consumes() {
+    let scratch = workspace(
+        "[{name: issue}, {name: trace}, {name: unused}, {name: debug, x-verify-ignore: [PV140]}]",
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stderr
+            .matches("Rule: unconsumed outputs [PV140]")
+            .count(),
+        1,
+        "{}",
+        run.stderr
+    );
+    assert!(run.reports(
+        "⚠️ Rule: unconsumed outputs [PV140]: Phase 'collect' output 'unused' is consumed by no \
+         phase input, algorithm output or return_contract\n  --> spec.yml:12:47\n"
+    ));
+
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "--fail-on",
+        "warning",
+        "spec.yml",
+    ]);
+    assert_eq!(run.code, Some(1));
+
+    scratch.write(
+        ".program-verify.yaml",
+        "rules:\n  dead-ports:\n    enabled: false\n",
+    );
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "--fail-on",
+        "warning",
+        "spec.yml",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn passes_outputs_that_are_consumed() {
+    let run = workspace("[{name: issue}, {name: trace}]").run(&[
+        "--schema",
+        "open-schema.json",
+        "--fail-on",
+        "warning",
+        "spec.yml",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("[PV140]"));
+}
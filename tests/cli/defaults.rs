@@ -0,0 +1,64 @@
+use crate::support::Scratch;
+
+/// A schema giving `algorithm.name` a default directly and `algorithm.owner` one through a `$ref`.
+const SCHEMA: &str = r##"{
+  "properties": {
+    "algorithm": {
+      "properties": {
+        "name": {"default": "Helpdesk"},
+        "owner": {"$ref": "#/$defs/owner"}
+      }
+    }
+  },
+  "$defs": {"owner": {"default": "ops"}}
+}"##;
+
+fn workspace(title: &str) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    scratch.write(
+        "spec.yml",
+        &format!("meta: {{title: {title}, version: v1}}\nalgorithm: {{phases: [x]}}\n"),
+    );
+    scratch
+}
+
+#[test]
+fn runs_the_rules_on_the_defaulted_spec() {
+    let scratch = workspace("Support");
+    let run = scratch.run(&["--schema", "schema.json", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(
+        run.reports("[PV003]: Missing algorithm.name"),
+        "{}",
+        run.stderr
+    );
+
+    let run = scratch.run(&[
+        "--schema",
+        "schema.json",
+        "--apply-defaults",
+        "--verbose",
+        "spec.yml",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("ℹ️ Applied 2 schema default(s): /algorithm/name, /algorithm/owner."));
+    assert!(run.reports(
+        "[PV001]: algorithm.name='Helpdesk' does not match the base of meta.title='Support'"
+    ));
+
+    let run =
+        workspace("Helpdesk").run(&["--schema", "schema.json", "--apply-defaults", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn shows_the_defaulted_spec() {
+    let run =
+        workspace("Helpdesk").run(&["--schema", "schema.json", "--show-defaults", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.starts_with(
+        "algorithm:\n  name: Helpdesk\n  owner: ops\n  phases:\n  - x\n\
+         meta:\n  title: Helpdesk\n  version: v1\n"
+    ));
+}
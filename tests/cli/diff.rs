@@ -0,0 +1,90 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+const OLD: &str = "\
+meta: {title: Support, version: v1}
+algorithm: {name: Support, phases: [collect, reply]}
+implementation:
+  phase_contracts:
+    collect:
+      inputs: [{name: ticket, schema: {type: string}}]
+    reply: {}
+";
+
+const NEW: &str = "\
+meta: {title: Support, version: v2}
+algorithm: {name: Support, phases: [reply, collect, audit]}
+implementation:
+  phase_contracts:
+    collect:
+      inputs: [{name: ticket, schema: {type: object}}]
+      retry_policy: {max_attempts: 2}
+";
+
+#[test]
+fn ignores_formatting_and_key_order() {
+    let scratch = Scratch::new();
+    scratch.write("old.yml", OLD);
+    scratch.write(
+        "old.json",
+        r#"{"implementation": {"phase_contracts": {"reply": {}, "collect": {"inputs": [{"schema": {"type": "string"}, "name": "ticket"}]}}},
+            "algorithm": {"phases": ["collect", "reply"], "name": "Support"},
+            "meta": {"version": "v1", "title": "Support"}}"#,
+    );
+    let run = scratch.run(&["diff", "old.yml", "old.json"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "✅ old.yml and old.json are structurally identical.\n"
+    );
+}
+
+#[test]
+fn lists_changes_by_section() {
+    let scratch = Scratch::new();
+    scratch.write("old.yml", OLD);
+    scratch.write("new.yml", NEW);
+    let run = scratch.run(&["diff", "old.yml", "new.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "\
+── old.yml → new.yml: 5 change(s) ──
+meta:
+  ~ meta.version: v1 → v2
+phases:
+  + audit
+  ~ order: collect, reply → reply, collect
+contracts:
+  ~ collect: inputs.ticket changed (schema); retry_policy added
+  - reply
+"
+    );
+
+    let run = scratch.run(&["diff", "old.yml", "new.yml", "--format", "json"]);
+    let changes: Vec<JsonValue> = run
+        .stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(changes.len(), 5);
+    assert_eq!(
+        changes[4],
+        json!({
+            "change": "removed",
+            "details": [],
+            "pointer": "/implementation/phase_contracts/reply",
+            "section": "contracts",
+            "subject": "reply"
+        })
+    );
+}
+
+#[test]
+fn fails_only_on_unreadable_specs() {
+    let scratch = Scratch::new();
+    scratch.write("old.yml", OLD);
+    let run = scratch.run(&["diff", "old.yml", "missing.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("failed to read file missing.yml"));
+}
@@ -0,0 +1,136 @@
+use crate::support::Scratch;
+use std::fs;
+
+const SPEC: &str = "\
+meta: {title: Support, version: v1, purpose: Answers tickets}
+algorithm: {name: Support, phases: [collect, reply]}
+implementation:
+  return_contract:
+    produced_by: {phase: reply, port: answer}
+    schema: {type: string}
+  phase_contracts:
+    collect:
+      description: Reads the ticket.
+      inputs: [{name: ticket, schema: {type: object}, source: {kind: instance, path: $.ticket}}]
+      outputs: [{name: issue}]
+      errors: [{code: NO_TICKET, severity: error}]
+      retry_policy: {max_attempts: 3}
+    reply:
+      inputs: [{name: issue, source: {kind: phase_output, phase: collect, port: issue}}]
+      outputs: [{name: answer}]
+";
+
+#[test]
+fn renders_a_page_per_spec() {
+    let scratch = Scratch::new();
+    scratch.write("support.yml", SPEC);
+    let run = scratch.run(&["docs", "generate", "support.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    for part in [
+        "# Support\n\nAnswers tickets\n",
+        "| [`collect`](#collect) | `ticket` | `issue` | `NO_TICKET` |\n\
+         | [`reply`](#reply) | `issue` | `answer` | — |\n",
+        "### `collect`\n\nReads the ticket.\n",
+        "| `ticket` | object | instance `$.ticket` |  |\n",
+        "| `NO_TICKET` | error |  |\n",
+        "**Retries:** up to 3 attempts.\n",
+        "| `issue` | — | `issue` of [`collect`](#collect) |  |\n",
+        "## Return contract\n\nProduced by output `answer` of phase [`reply`](#reply).\n\n\
+         ```yaml\ntype: string\n```\n",
+    ] {
+        assert!(run.stdout.contains(part), "{part}\n{}", run.stdout);
+    }
+}
+
+#[test]
+fn check_reports_drifted_pages() {
+    let scratch = Scratch::new();
+    scratch.write("support.yml", SPEC);
+    let run = scratch.run(&["docs", "generate", "support.yml", "-o", "docs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .contains("📝 Wrote the documentation of support.yml to docs/support.md."));
+
+    let check = ["docs", "generate", "support.yml", "-o", "docs", "--check"];
+    let run = scratch.run(&check);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("✅ docs/support.md is up to date."));
+
+    scratch.write(
+        "support.yml",
+        &SPEC.replace("Reads the ticket.", "Reads it."),
+    );
+    let run = scratch.run(&check);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "❌ docs/support.md is out of date with support.yml; run docs generate to update it."
+    ));
+
+    fs::remove_file(scratch.path("docs/support.md")).unwrap();
+    let run = scratch.run(&check);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("❌ docs/support.md cannot be read"));
+}
+
+#[test]
+fn per_phase_writes_linked_phase_pages() {
+    let scratch = Scratch::new();
+    scratch.write("support.yml", SPEC);
+    let generate = [
+        "docs",
+        "generate",
+        "support.yml",
+        "-o",
+        "docs",
+        "--per-phase",
+    ];
+    let run = scratch.run(&generate);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports(
+        "📝 Wrote the documentation of support.yml to docs/support.md and 2 phase page(s) to \
+         docs/support."
+    ));
+    let page = |name: &str| fs::read_to_string(scratch.path(name)).unwrap();
+    let spec = page("docs/support.md");
+    assert!(
+        spec.contains("| [`collect`](support/collect.md) | `ticket` | `issue` | `NO_TICKET` |\n")
+    );
+    assert!(spec.contains(
+        "Produced by output `answer` of phase [`reply`](support/reply.md#output-answer)."
+    ));
+    assert!(!spec.contains("### `collect`"));
+
+    let collect = page("docs/support/collect.md");
+    assert!(collect.starts_with("# `collect`\n\nPhase of [Support](../support.md).\n"));
+    for part in [
+        r#"| <a id="output-issue"></a>`issue` | — | `issue` of [`reply`](reply.md#input-issue) |"#,
+        r#"| <a id="error-no_ticket"></a>`NO_TICKET` | error |"#,
+    ] {
+        assert!(collect.contains(part), "{part}\n{collect}");
+    }
+    assert!(page("docs/support/reply.md").contains(
+        r#"| <a id="input-issue"></a>`issue` | — | `issue` of [`collect`](collect.md#output-issue)"#
+    ));
+
+    // Pages of phases that no longer exist are reported by --check and removed by a new run.
+    scratch.write("docs/support/old.md", "# `old`\n");
+    let run = scratch.run(&[&generate[..], &["--check"]].concat());
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "❌ docs/support/old.md documents no phase of support.yml; run docs generate to remove it."
+    ));
+    assert!(scratch.run(&generate).success());
+    assert!(!scratch.path("docs/support/old.md").exists());
+    let run = scratch.run(&[&generate[..], &["--check"]].concat());
+    assert!(
+        run.reports("✅ docs/support.md and its 2 phase page(s) in docs/support are up to date.")
+    );
+}
+
+#[test]
+fn per_phase_needs_an_output_directory() {
+    let run = Scratch::new().run(&["docs", "generate", "support.yml", "--per-phase"]);
+    assert_eq!(run.code, Some(2));
+    assert!(run.reports("--output <DIR>"));
+}
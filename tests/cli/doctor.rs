@@ -0,0 +1,82 @@
+use crate::support::{Scratch, Server};
+
+fn workspace(versions: &str) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("schemas/v1.json", "{}");
+    scratch.write("schemas/broken.json", r#"{"type": 5}"#);
+    scratch.write("version_map.yaml", versions);
+    scratch
+}
+
+#[test]
+fn passes_a_healthy_setup() {
+    let scratch = workspace("v1: schemas/v1.json\n");
+    scratch.write(".program-verify.yaml", "schema: schemas/v1.json\n");
+    let run = scratch.run(&["doctor"]);
+    assert!(run.success(), "{}", run.stdout);
+    let dir = scratch.path("");
+    let dir = dir.display();
+    assert_eq!(
+        run.stdout,
+        format!(
+            "✅ Configuration: {dir}.program-verify.yaml is valid\n\
+             ✅ Version map: {dir}version_map.yaml maps 1 version(s)\n\
+             ✅ Schemas: all 3 schema(s) compile\n\
+             ✅ Cache: {dir}.cache/program-verify is writable\n\
+             ✅ Network: no remote schemas to reach\n"
+        )
+    );
+}
+
+#[test]
+fn reports_problems_with_a_fix() {
+    let scratch = workspace("v1: schemas/v1.json\nv2: schemas/broken.json\n");
+    scratch.write(".program-verify.yaml", "bogus: [\n");
+    let run = scratch.run(&["doctor"]);
+    assert_eq!(run.code, Some(1));
+    for line in [
+        "❌ Configuration: invalid config ",
+        "   → fix the entry named above, or point --config at another file; the other checks ran \
+         with the default settings\n",
+        "❌ Schemas: version v2 (schemas/broken.json): schema document is invalid: ",
+        "   → fix the schema file or its version map entry; specs of this version cannot be \
+         validated until then\n",
+    ] {
+        assert!(run.stdout.contains(line), "{line}\n{}", run.stdout);
+    }
+
+    let scratch = workspace("[v1]\n");
+    scratch.write(".cache", "a file, not a directory");
+    let run = scratch.run(&["doctor"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.stdout.contains("is not a 'version: path' mapping: "));
+    assert!(run
+        .stdout
+        .contains("   → write one entry per line, e.g. `v4.0.0: schemas/v4.json`\n"));
+    assert!(run.stdout.contains("⚠️ Cache: "), "{}", run.stdout);
+    assert!(run
+        .stdout
+        .contains(".cache/program-verify is not writable: "));
+}
+
+#[test]
+fn checks_remote_schemas_without_the_cache() {
+    let server = Server::new("{}");
+    let url = format!("{}/v1.json", server.url);
+    let scratch = workspace(&format!("v1: {url}\n"));
+    let run = scratch.run(&["doctor"]);
+    assert!(run.success(), "{}", run.stdout);
+    assert!(
+        run.stdout.contains(&format!("{url} is reachable")),
+        "{}",
+        run.stdout
+    );
+
+    server.go_down();
+    let run = scratch.run(&["doctor"]);
+    assert!(
+        run.stdout.contains(&format!("cannot download {url}: ")),
+        "{}",
+        run.stdout
+    );
+}
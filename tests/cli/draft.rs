@@ -0,0 +1,54 @@
+use crate::support::Scratch;
+
+/// `minContains` only exists since draft 2019-09; draft 7 requires an integer tag.
+const SCHEMA: &str =
+    r#"{"properties": {"tags": {"contains": {"type": "integer"}, "minContains": 0}}}"#;
+
+#[test]
+fn compiles_schemas_as_the_given_draft() {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    scratch.write(
+        "spec.yml",
+        "meta:\n  title: A\nalgorithm:\n  name: A\ntags: [x]\n",
+    );
+
+    let run = scratch.run(&["--draft", "2020-12", "--schema", "schema.json", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    let run = scratch.run(&["--draft", "7", "--schema", "schema.json", "spec.yml"]);
+    assert!(
+        run.reports("schema: /properties/tags/contains"),
+        "{}",
+        run.stderr
+    );
+
+    scratch.write(".program-verify.yaml", "draft: 7\n");
+    let run = scratch.run(&["--schema", "schema.json", "spec.yml"]);
+    assert!(
+        run.reports("schema: /properties/tags/contains"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn rejects_schemas_declaring_another_draft() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "schema.json",
+        r#"{"$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}"#,
+    );
+    scratch.write("spec.yml", "meta:\n  title: A\nalgorithm:\n  name: A\n");
+
+    let run = scratch.run(&["--draft", "7", "--schema", "schema.json", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    let run = scratch.run(&["--draft", "2020-12", "--schema", "schema.json", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(
+        run.reports(
+            "schema.json declares JSON Schema draft 7 in $schema, but draft 2020-12 is enforced"
+        ),
+        "{}",
+        run.stderr
+    );
+}
@@ -0,0 +1,49 @@
+use crate::support::Scratch;
+
+fn spec(name: &str, version: &str) -> String {
+    format!("meta: {{title: {name}, version: {version}}}\nalgorithm: {{name: {name}, phases: [a]}}\n")
+}
+
+fn validate(scratch: &Scratch, files: &[&str]) -> crate::support::Run {
+    scratch.write("open-schema.json", "{}");
+    scratch.run(&[&["--schema", "open-schema.json"], files].concat())
+}
+
+#[test]
+fn distinct_specs_pass() {
+    let scratch = Scratch::new();
+    scratch.write("support.yml", &spec("Support", "v1"));
+    scratch.write("billing.yml", &spec("Billing", "v1"));
+    let run = validate(&scratch, &["support.yml", "billing.yml"]);
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+    assert!(!run.reports("duplicate specs"));
+}
+
+#[test]
+fn reports_copies_and_reused_identities() {
+    let scratch = Scratch::new();
+    scratch.write("a.yml", &spec("Support", "v1"));
+    // The same content as JSON, with the keys in another order.
+    scratch.write(
+        "b.json",
+        r#"{"algorithm": {"phases": ["a"], "name": "Support"}, "meta": {"version": "v1", "title": "Support"}}"#,
+    );
+    scratch.write("c.yml", &spec("Support", "v2"));
+    let run = validate(&scratch, &["a.yml", "b.json", "c.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "❌ Rule: duplicate specs [PV110]: b.json is identical to a.yml (sha256 23f06c6c4d6c); \
+         remove one of the copies"
+    ));
+    assert!(run.reports(
+        "❌ Rule: duplicate specs [PV111]: c.yml reuses meta.title 'Support' and algorithm.name \
+         'Support' of a.yml; rename one of the algorithms"
+    ));
+
+    scratch.write(
+        ".program-verify.yaml",
+        "rules:\n  duplicate-specs:\n    enabled: false\n",
+    );
+    let run = validate(&scratch, &["a.yml", "b.json", "c.yml"]);
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+}
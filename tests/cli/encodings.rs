@@ -0,0 +1,75 @@
+use crate::support::Scratch;
+use std::fs;
+
+const SPEC: &str = "meta: {title: B, version: v1}\nalgorithm: {name: B, phases: [x]}\n";
+
+fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+    text.encode_utf16()
+        .flat_map(|unit| match little_endian {
+            true => unit.to_le_bytes(),
+            false => unit.to_be_bytes(),
+        })
+        .collect()
+}
+
+#[test]
+fn converts_other_unicode_encodings() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    let utf32: Vec<u8> = SPEC.chars().flat_map(|c| (c as u32).to_be_bytes()).collect();
+    for (name, bytes, encoding) in [
+        ("bom.yml", [&[0xEF, 0xBB, 0xBF][..], SPEC.as_bytes()].concat(), "UTF-8 with a byte order mark"),
+        ("le.yml", [&[0xFF, 0xFE][..], &utf16(SPEC, true)].concat(), "UTF-16LE"),
+        ("be.yml", utf16(SPEC, false), "UTF-16BE"),
+        ("wide.yml", [&[0, 0, 0xFE, 0xFF][..], &utf32].concat(), "UTF-32BE"),
+    ] {
+        fs::write(scratch.path(name), bytes).unwrap();
+        let run = scratch.run(&["--schema", "open-schema.json", name]);
+        assert!(run.success(), "{name}: {}", run.stderr);
+        assert!(
+            run.reports(&format!(
+                "⚠️ {name} is encoded in {encoding}; it was converted to UTF-8 for reading. Save \
+                 it as UTF-8 without a byte order mark."
+            )),
+            "{name}: {}{}",
+            run.stdout,
+            run.stderr
+        );
+    }
+
+    // Schemas and libraries too.
+    fs::write(scratch.path("schema.json"), [&[0xFF, 0xFE][..], &utf16("{}", true)].concat()).unwrap();
+    scratch.write("plain.yml", SPEC);
+    let run = scratch.run(&["--schema", "schema.json", "plain.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ schema.json is encoded in UTF-16LE; it was converted to UTF-8"));
+
+    let library = "library: common\nversion: 1.0.0\nphase_contracts: {}\n";
+    fs::create_dir(scratch.path("libs")).unwrap();
+    fs::write(scratch.path("libs/common.yml"), utf16(library, false)).unwrap();
+    scratch.write(
+        "uses.yml",
+        &format!("{SPEC}implementation:\n  uses: [{{library: common, version: \"^1\"}}]\n"),
+    );
+    let args = ["--schema", "open-schema.json", "--library-path", "libs", "uses.yml"];
+    let run = scratch.run(&args);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ libs/common.yml is encoded in UTF-16BE; it was converted to UTF-8"));
+}
+
+#[test]
+fn locates_invalid_utf8() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    fs::write(
+        scratch.path("legacy.yml"),
+        b"meta: {title: B, version: v1}\nalgorithm: {name: Caf\xe9}\n",
+    )
+    .unwrap();
+    let run = scratch.run(&["--schema", "open-schema.json", "legacy.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "Error: failed to read file legacy.yml: invalid UTF-8 at byte offset 51 (line 2, column \
+         22): 0xE9; the file may be saved in a legacy encoding such as Windows-1252, save it as UTF-8"
+    ));
+}
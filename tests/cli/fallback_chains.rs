@@ -0,0 +1,64 @@
+use crate::support::Scratch;
+
+/// A spec whose phases `fetch`, `parse` and `store` fall back to the phases in `fallbacks`.
+fn check(fallbacks: [&str; 3]) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    let mut spec = String::from(
+        "meta: {title: A, version: v1}\nalgorithm: {name: A, phases: [fetch, parse, store]}\n\
+         implementation:\n  phase_contracts:\n",
+    );
+    for (phase, fallback) in ["fetch", "parse", "store"].into_iter().zip(fallbacks) {
+        spec += &format!("    {phase}:\n      fallback: {{phase: {fallback}}}\n");
+    }
+    scratch.write("spec.yml", &spec);
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_phases_falling_back_to_themselves() {
+    let run = check(["fetch", "store", "store"]);
+    assert_eq!(run.code, Some(1));
+    assert!(
+        run.reports(
+            "❌ Rule: fallback chains [PV200]: Phase 'fetch' falls back to itself\n \
+             --> spec.yml:6:18"
+        ),
+        "{}",
+        run.stderr
+    );
+    assert!(run.reports("[PV200]: Phase 'store' falls back to itself"));
+}
+
+#[test]
+fn reports_each_cycle_once() {
+    let run = check(["store", "fetch", "parse"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "❌ Rule: fallback chains [PV201]: Phase 'fetch' falls back to itself through 'fetch' → \
+         'store' → 'parse' → 'fetch'"
+    ));
+    assert_eq!(run.stderr.matches("[PV201]").count(), 1, "{}", run.stderr);
+}
+
+#[test]
+fn reports_cycles_at_their_members() {
+    let run = check(["parse", "store", "parse"]);
+    // `parse` and `store` fall back to each other; `fetch` only leads into that cycle.
+    assert!(run.reports("through 'parse' → 'store' → 'parse'"));
+    assert!(!run.reports("Phase 'fetch' falls back"));
+}
+
+#[test]
+fn passes_chains_that_end() {
+    let scratch = Scratch::new();
+    let run = scratch.check(&serde_json::json!({
+        "meta": {"title": "A", "version": "v1"},
+        "algorithm": {"name": "A", "phases": ["fetch", "parse"]},
+        "implementation": {"phase_contracts": {
+            "fetch": {"fallback": {"phase": "parse"}},
+            "parse": {}
+        }}
+    }));
+    assert!(run.success(), "{}", run.stderr);
+}
@@ -0,0 +1,49 @@
+use crate::support::Scratch;
+use std::fs;
+
+/// CRLF line endings, trailing whitespace (part of a block scalar on line 4) and no final newline.
+const UNTIDY: &str =
+    "meta:  \r\n  title: A\t\r\n  notes: |\n    keep  \n    this\nalgorithm: {name: A}";
+
+const TIDY: &str = "meta:\n  title: A\n  notes: |\n    keep  \n    this\nalgorithm: {name: A}\n";
+
+#[test]
+fn check_lists_what_would_change() {
+    let scratch = Scratch::new();
+    scratch.write("specs/a.yml", UNTIDY);
+    scratch.write("specs/b.yml", "meta: {title: B}\n");
+    let run = scratch.run(&["fmt", "specs", "--check"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "⚠️ specs/a.yml: trailing whitespace on 1 line(s) (4) is part of a value and is kept."
+    ));
+    assert!(run.reports(
+        "❌ specs/a.yml: 2 line(s) not ending in LF; trailing whitespace on 2 line(s) (1, 2); no \
+         newline at end of file."
+    ));
+    assert!(run.reports("❌ 1 of 2 file(s) need formatting; run fmt to fix them."));
+    assert_eq!(fs::read_to_string(scratch.path("specs/a.yml")).unwrap(), UNTIDY);
+}
+
+#[test]
+fn rewrites_files_in_place() {
+    let scratch = Scratch::new();
+    scratch.write("specs/a.yml", UNTIDY);
+    scratch.write("specs/b.yml", "meta: {title: B}\n");
+    let run = scratch.run(&["fmt", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("✅ Formatted 1 of 2 file(s)."));
+    assert_eq!(fs::read_to_string(scratch.path("specs/a.yml")).unwrap(), TIDY);
+
+    let run = scratch.run(&["fmt", "specs", "--check"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("✅ All 2 file(s) are formatted."));
+
+    scratch.write(".program-verify.yaml", "line_endings: crlf\n");
+    let run = scratch.run(&["fmt", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        fs::read_to_string(scratch.path("specs/b.yml")).unwrap(),
+        "meta: {title: B}\r\n"
+    );
+}
@@ -0,0 +1,69 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+/// Validates `value` against `{"format": format}` and returns whether the spec passed.
+fn accepts(scratch: &Scratch, format: &str, value: &str) -> bool {
+    let schema = json!({ "properties": { "value": { "type": "string", "format": format } } });
+    scratch.write("schema.json", &schema.to_string());
+    let spec = json!({ "meta": { "title": "A" }, "algorithm": { "name": "A" }, "value": value });
+    scratch.write("spec.yml", &spec.to_string());
+    scratch
+        .run(&["--schema", "schema.json", "spec.yml"])
+        .success()
+}
+
+#[test]
+fn checks_domain_formats() {
+    let scratch = Scratch::new();
+    for (format, good, bad) in [
+        (
+            "duration",
+            ["P3D", "PT1H30M", "PT0.5S", "P2W"],
+            ["3 days", "P", "PT", "P1H"],
+        ),
+        (
+            "identifier",
+            ["collect_issue", "a", "v2_phase", "x1"],
+            ["CollectIssue", "1st", "a-b", ""],
+        ),
+        (
+            "semver",
+            ["1.2.3", "1.2.3-rc.1", "v4.0.0", "0.1.0+build.5"],
+            ["1.2", "01.2.3", "v", "1.2.3-"],
+        ),
+        (
+            "cron",
+            [
+                "*/5 9-17 * JAN-MAR mon-fri",
+                "0 0 * * *",
+                "@daily",
+                "15 14 1 * 0",
+            ],
+            ["* * * *", "61 * * * *", "@sometimes", "* * * * * *"],
+        ),
+    ] {
+        for value in good {
+            assert!(
+                accepts(&scratch, format, value),
+                "{format} rejects {value:?}"
+            );
+        }
+        for value in bad {
+            assert!(
+                !accepts(&scratch, format, value),
+                "{format} accepts {value:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn custom_formats_can_be_turned_off() {
+    let scratch = Scratch::new();
+    scratch.write(".program-verify.yaml", "custom_formats: [cron]\n");
+    assert!(accepts(&scratch, "duration", "3 days"));
+    assert!(!accepts(&scratch, "cron", "sometimes"));
+
+    scratch.write(".program-verify.yaml", "custom_formats: []\n");
+    assert!(accepts(&scratch, "cron", "sometimes"));
+}
@@ -0,0 +1,74 @@
+use crate::support::Scratch;
+
+const SPEC: &str = r#"meta: {title: A, version: v1}
+algorithm:
+  name: A
+  phases: [score, publish]
+  graph:
+    entry: start
+    nodes:
+      start: {type: phase, phase: score}
+      loop: {type: loop, until: "score.quality >= threshold || iteration > 3"}
+      publish: {type: phase, phase: publish, condition: "quality > 0.5 && $.mode != 'dry'"}
+      done: {type: end}
+    edges:
+      - {from: start, to: loop}
+      - {from: loop, to: publish, condition: "ready == true"}
+      - {from: loop, to: done, condition: "the reviewers are satisfied"}
+      - {from: publish, to: done}
+implementation:
+  parameters: {threshold: 0.8}
+  phase_contracts:
+    score:
+      outputs: [{name: quality}, {name: ready}]
+    publish:
+      inputs: [{name: quality, source: {kind: phase_output, phase: score, port: quality}}]
+"#;
+
+fn check(spec: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", spec);
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn passes_conditions_reading_declared_names() {
+    let run = check(SPEC);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("graph conditions"));
+}
+
+#[test]
+fn reports_malformed_conditions_and_unknown_names() {
+    let run = check(
+        &SPEC
+            .replace("score.quality >= threshold", "score.qualty >= treshold")
+            .replace("ready == true", "ready ==")
+            .replace("quality > 0.5", "publish > 0.5"),
+    );
+    assert_eq!(run.code, Some(1));
+    for finding in [
+        "[PV271]: The until condition of graph node 'loop' reads 'qualty', which phase 'score' \
+         does not output; did you mean 'quality'?\n --> spec.yml:9:26",
+        "[PV271]: The until condition of graph node 'loop' reads 'treshold', which is neither a \
+         phase output, a parameter nor an instance path; did you mean 'threshold'?",
+        "[PV271]: The condition of graph node 'publish' reads phase 'publish' instead of one of \
+         its outputs",
+        "[PV270]: The condition of graph edge #1 is not a valid expression: unexpected end of the \
+         condition in 'ready =='\n  --> spec.yml:14:35",
+    ] {
+        let finding = format!("❌ Rule: graph conditions {finding}");
+        assert!(run.reports(&finding), "{finding}\n{}", run.stderr);
+    }
+}
+
+#[test]
+fn parses_conditions_with_dotted_names_or_numbers() {
+    let run = check(&SPEC.replace("ready == true", "score.quality 0.8"));
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "[PV270]: The condition of graph edge #1 is not a valid expression: unexpected number 0.8 \
+         in 'score.quality 0.8'"
+    ));
+}
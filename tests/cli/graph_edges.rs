@@ -0,0 +1,55 @@
+use crate::support::Scratch;
+
+const SPEC: &str = "\
+meta: {title: A, version: v1}
+algorithm:
+  name: A
+  phases: [review, publish, reject]
+  graph:
+    entry: start
+    nodes:
+      start: {type: phase, phase: review}
+      gate: {type: if, branches: [{name: accept}, {name: decline}]}
+      publish: {type: phase, phase: publish}
+      reject: {type: phase, phase: reject}
+      done: {type: end}
+    edges:
+      - {from: start, to: gate}
+      - {from: gate, to: publish, condition: accept}
+      - {from: gate, to: reject, condition: decline}
+      - {from: publish, to: done}
+      - {from: reject, to: done}
+";
+
+fn check(spec: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", spec);
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn passes_well_formed_graphs() {
+    let run = check(SPEC);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_broken_edges() {
+    let run = check(&SPEC.replace(
+        "      - {from: gate, to: reject, condition: decline}\n",
+        "      - {from: gate, to: reject, condition: acept}\n      - {from: reject, to: done}\n      \
+         - {from: reject, to: finsh}\n",
+    ));
+    assert_eq!(run.code, Some(1));
+    for finding in [
+        "[PV250]: Graph edge #4 to 'finsh' is not a node of algorithm.graph\n  --> spec.yml:18:24",
+        "[PV251]: Graph edge #6 from 'reject' to 'done' repeats edge #3\n  --> spec.yml:20:9",
+        "[PV252]: Decision node 'gate' has no edge for its branch 'decline'\n --> spec.yml:9:24",
+        "[PV253]: Graph edge #2 leaves decision node 'gate' by 'acept', which is not one of its \
+         branches; did you mean 'accept'?\n  --> spec.yml:16:34",
+    ] {
+        let finding = format!("❌ Rule: graph edges {finding}");
+        assert!(run.reports(&finding), "{finding}\n{}", run.stderr);
+    }
+}
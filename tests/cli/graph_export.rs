@@ -0,0 +1,156 @@
+use crate::support::Scratch;
+use std::fs;
+
+const SPEC: &str = "\
+meta: {title: Support, version: v1}
+algorithm:
+  name: Support
+  phases: [collect, reply]
+  graph:
+    entry: start
+    nodes:
+      start: {type: phase, phase: collect}
+      check: {type: if}
+      done: {type: end}
+    edges:
+      - {from: start, to: check}
+      - {from: check, to: reply, kind: conditional}
+      - {from: reply, to: done}
+implementation:
+  phase_contracts:
+    collect:
+      outputs: [{name: issue}]
+    reply:
+      inputs:
+        - {name: issue, source: {kind: phase_output, phase: collect, port: issue}}
+";
+
+#[test]
+fn renders_control_and_dataflow() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", SPEC);
+    let run = scratch.run(&["graph", "export", "spec.yml", "-o", "support.dot"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "🗺️ Wrote the graph of 4 node(s) and 4 edge(s) to support.dot.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(scratch.path("support.dot")).unwrap(),
+        r#"digraph "Support" {
+  rankdir=LR;
+  node [shape=box, style=rounded, fontname="Helvetica"];
+  edge [fontname="Helvetica", fontsize=10];
+  "check" [label="check\n(if)", shape=diamond];
+  "done" [label="done\n(end)", shape=doublecircle];
+  "start" [label="start\nphase: collect", penwidth=2];
+  "reply" [label="reply"];
+  "start" -> "check";
+  "check" -> "reply" [label="conditional"];
+  "reply" -> "done";
+  "start" -> "reply" [label="issue", style=dotted, color=blue, fontcolor=blue];
+}
+"#
+    );
+}
+
+#[test]
+fn draws_unresolved_references_in_red() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "spec.yml",
+        &SPEC
+            .replace(
+                "      - {from: reply, to: done}\n",
+                "      - {from: reply, to: done}\n      - {from: check, to: ghost, kind: fallback}\n",
+            )
+            .replace(
+                "port: issue}}\n",
+                "port: issue}}\n        - {name: notes, source: {kind: phase_output, phase: collect, port: notes}}\n        - {name: who, source: {kind: phase_output, phase: nobody, port: x}}\n",
+            ),
+    );
+    let run = scratch.run(&["graph", "export", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    for line in [
+        r#"  "ghost" [label="ghost\n(unresolved)", style="rounded,dashed", color=red, fontcolor=red];"#,
+        r#"  "nobody" [label="nobody\n(unresolved)", style="rounded,dashed", color=red, fontcolor=red];"#,
+        r#"  "check" -> "ghost" [label="fallback", style=dashed, color=red, fontcolor=red, penwidth=2];"#,
+        r#"  "start" -> "reply" [label="notes (undeclared)", style=dotted, color=red, fontcolor=red, penwidth=2];"#,
+        r#"  "nobody" -> "reply" [label="x → who", style=dotted, color=red, fontcolor=red, penwidth=2];"#,
+    ] {
+        assert!(run.stdout.contains(line), "{line}\n{}", run.stdout);
+    }
+}
+
+#[test]
+fn renders_mermaid() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", SPEC);
+    let run = scratch.run(&["graph", "export", "spec.yml", "--format", "mermaid"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        r#"```mermaid
+---
+title: Support
+---
+flowchart LR
+  n0{"check<br/>(if)"}
+  n1((("done<br/>(end)")))
+  n2("start<br/>phase: collect")
+  n3("reply")
+  n2 --> n0
+  n0 -->|"conditional"| n3
+  n3 --> n1
+  n2 -.->|"issue"| n3
+  linkStyle 3 stroke:blue,color:blue
+  classDef entry stroke-width:3px
+  class n2 entry
+```
+"#
+    );
+}
+
+#[test]
+fn collapse_keeps_only_phases() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", SPEC);
+    let run = scratch.run(&[
+        "graph",
+        "export",
+        "spec.yml",
+        "--format",
+        "mermaid",
+        "--collapse",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.contains(
+        "  n0(\"start<br/>phase: collect\")\n  n1(\"reply\")\n  n0 -.->|\"issue\"| n1\n  n0 -->|\"conditional\"| n1\n"
+    ));
+    assert!(!run.stdout.contains("check") && !run.stdout.contains("done"));
+}
+
+#[test]
+fn renders_svg_without_other_tools() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", SPEC);
+    let run = scratch.run(&["graph", "export", "spec.yml", "--format", "svg"]);
+    assert!(run.success(), "{}", run.stderr);
+    let svg = &run.stdout;
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains("  <title>Support</title>\n"));
+    // `check` is a diamond and `done` an ellipse; the entry node has a bold outline.
+    assert!(svg.contains("<polygon points=\"283,30 344,52 283,74 222,52\""));
+    assert!(svg.contains("<text x=\"667\" y=\"50\" text-anchor=\"middle\" font-size=\"12\" fill=\"black\">done</text>"));
+    assert!(svg.contains("stroke-width=\"3\""));
+    // Dataflow is a dotted blue arc.
+    assert!(svg.contains("fill=\"blue\">issue</text>"));
+}
+
+#[test]
+fn rejects_unreadable_specs() {
+    let run = Scratch::new().run(&["graph", "export", "missing.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("failed to read file missing.yml"));
+}
@@ -0,0 +1,70 @@
+use crate::support::Scratch;
+
+/// A spec declaring `phases`, in which `reply` reads an output of `collect`, and with `edges`.
+fn order(phases: &str, edges: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}
+algorithm:
+  name: A
+  phases: {phases}
+  graph:
+    entry: r
+    nodes:
+      c: {{type: phase, phase: collect}}
+      r: {{type: phase, phase: reply}}
+      n: {{type: phase, phase: notify}}
+    edges: {edges}
+implementation:
+  phase_contracts:
+    collect:
+      outputs: [{{name: issue}}]
+    reply:
+      inputs:
+        - {{name: issue, source: {{kind: phase_output, phase: collect, port: issue}}}}
+    notify: {{}}
+"
+        ),
+    );
+    scratch.run(&["graph", "order", "spec.yml"])
+}
+
+#[test]
+fn orders_phases_after_their_dependencies() {
+    for (phases, edges, expected) in [
+        ("[reply, notify, collect]", "[]", "notify\ncollect\nreply\n"),
+        ("[notify, reply, collect]", "[]", "notify\ncollect\nreply\n"),
+        (
+            "[reply, notify, collect]",
+            "[{from: r, to: n}]",
+            "collect\nreply\nnotify\n",
+        ),
+        (
+            "[reply, notify, collect]",
+            "[{from: r, to: n}, {from: n, to: r, kind: loop}]",
+            "collect\nreply\nnotify\n",
+        ),
+    ] {
+        let run = order(phases, edges);
+        assert!(run.success(), "{phases} {edges}: {}", run.stderr);
+        assert_eq!(run.stdout, expected, "{phases} {edges}");
+    }
+}
+
+#[test]
+fn reports_the_cycles_that_prevent_an_order() {
+    let run = order(
+        "[collect, reply, notify]",
+        "[{from: r, to: n}, {from: n, to: c}]",
+    );
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stdout, "");
+    assert_eq!(
+        run.stderr,
+        "Error: spec.yml has no execution order:\n  Phases depend on each other in a cycle: \
+         reply → notify → collect → reply (reply → notify: algorithm.graph edge #0; notify → \
+         collect: algorithm.graph edge #1; collect → reply: input 'issue' reads output 'issue')\n"
+    );
+}
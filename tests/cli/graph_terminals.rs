@@ -0,0 +1,111 @@
+use crate::support::Scratch;
+
+/// `review` decides between `publish` and `reject`; both lead to `done`.
+const SPEC: &str = "\
+meta: {title: A, version: v1}
+algorithm:
+  name: A
+  phases: [review, publish, reject]
+  graph:
+    entry: start
+    nodes:
+      start: {type: phase, phase: review}
+      gate: {type: if, branches: [{name: accept}, {name: decline}]}
+      publish: {type: phase, phase: publish}
+      reject: {type: phase, phase: reject}
+      done: {type: end}
+    edges:
+      - {from: start, to: gate}
+      - {from: gate, to: publish, condition: accept}
+      - {from: gate, to: reject, condition: decline}
+      - {from: publish, to: done}
+      - {from: reject, to: done}
+implementation:
+  phase_contracts:
+    review:
+      outputs: [{name: verdict}]
+  return_contract:
+    produced_by: {phase: review, port: verdict}
+";
+
+fn check(spec: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", spec);
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+fn assert_reports(run: &crate::support::Run, finding: &str) {
+    let finding = format!("❌ Rule: graph entry and exit {finding}");
+    assert!(run.reports(&finding), "{finding}\n{}", run.stderr);
+}
+
+#[test]
+fn passes_graphs_that_start_and_end() {
+    let run = check(SPEC);
+    assert!(run.success(), "{}", run.stderr);
+    // Without an `entry`, the graph starts at its only node without incoming edges.
+    let run = check(&SPEC.replace("    entry: start\n", ""));
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_missing_and_ambiguous_entries() {
+    let run = check(&SPEC.replace("entry: start", "entry: stat"));
+    assert_reports(
+        &run,
+        "[PV260]: Graph entry 'stat' is not a node of algorithm.graph; did you mean 'start'?\n \
+         --> spec.yml:6:5",
+    );
+
+    let run = check(&SPEC.replace("    entry: start\n", "").replace(
+        "      done: {type: end}\n",
+        "      done: {type: end}\n      audit: {}\n",
+    ));
+    assert_reports(
+        &run,
+        "[PV261]: algorithm.graph sets no `entry` and starts at 2 nodes without incoming edges: \
+         audit, start",
+    );
+
+    let run = check(&SPEC.replace("    entry: start\n", "").replace(
+        "      - {from: reject, to: done}\n",
+        "      - {from: reject, to: done}\n      - {from: done, to: start}\n",
+    ));
+    assert_reports(
+        &run,
+        "[PV260]: algorithm.graph has no entry: it sets no `entry` and every node has an incoming \
+         edge",
+    );
+}
+
+#[test]
+fn reports_graphs_without_an_end() {
+    let run = check(
+        &SPEC
+            .replace("      done: {type: end}\n", "")
+            .replace("to: done}", "to: start}"),
+    );
+    assert_reports(
+        &run,
+        "[PV262]: algorithm.graph has no terminal node: no `end` node, and an edge leaves every \
+         node",
+    );
+}
+
+#[test]
+fn reports_paths_that_skip_the_return_contract() {
+    let run = check(
+        &SPEC
+            .replace("phase: review, port: verdict", "phase: publish, port: release")
+            .replace(
+                "      outputs: [{name: verdict}]\n",
+                "      outputs: [{name: verdict}]\n    publish:\n      outputs: [{name: release}]\n",
+            ),
+    );
+    assert_reports(
+        &run,
+        "[PV263]: Graph node 'done' ends the algorithm on a path from 'start' that never runs \
+         phase 'publish', which produces the return_contract\n  --> spec.yml:12:7",
+    );
+}
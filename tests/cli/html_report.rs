@@ -0,0 +1,48 @@
+use crate::support::Scratch;
+
+#[test]
+fn prints_one_self_contained_page() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("a.yml", "meta: {title: 'A<b>'}\nalgorithm: {name: B}\n");
+    scratch.write(
+        "b.yml",
+        "meta: {title: B, version: v1}\nalgorithm: {name: B, phases: [x]}\n",
+    );
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "--format",
+        "html",
+        "a.yml",
+        "b.yml",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stderr, "");
+    let page = &run.stdout;
+    assert!(page.starts_with("<!DOCTYPE html>\n"));
+    assert!(page.trim_end().ends_with("</html>"));
+    assert!(page.contains(&format!(
+        "<p>2 file(s): <span class=\"error\">1 error(s)</span>, <span class=\"warning\">0 \
+         warning(s)</span>, <span class=\"info\">0 info</span>. Generated by program-verify {}.</p>",
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(page.contains(
+        "<tr><td class=\"error\">error</td><td><a href=\"#input-0\">a.yml:2:13</a></td>\
+         <td>PV001</td><td>algorithm.name='B' does not match the base of meta.title='A&lt;b&gt;' \
+         (detected 'A&lt;b&gt;')</td></tr>"
+    ));
+    assert!(page.contains("<h2 id=\"input-1\">b.yml</h2>"));
+    assert!(page.contains("<summary>Dataflow graph</summary>"));
+    assert!(page.contains("<h2>Provenance</h2>"));
+    assert!(!page.contains("<script") && !page.contains("<link"));
+}
+
+#[test]
+fn does_not_support_the_audit() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", "meta: {}\n");
+    let run = scratch.run(&["--format", "html", "--report-suppressed", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("--report-suppressed needs --format human or --format json"));
+}
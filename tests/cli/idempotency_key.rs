@@ -0,0 +1,49 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+fn spec(contract: JsonValue) -> JsonValue {
+    json!({
+        "meta": { "title": "Payments", "version": "v1" },
+        "algorithm": { "name": "Payments", "phases": ["charge"] },
+        "implementation": { "phase_contracts": { "charge": contract } }
+    })
+}
+
+#[test]
+fn accepts_retried_side_effects_with_a_key() {
+    let scratch = Scratch::new();
+    for key in ["$.charge.id", "order_id"] {
+        let run = scratch.check(&spec(json!({
+            "inputs": [{ "name": "order_id" }],
+            "side_effects": ["charge_card"],
+            "idempotency_key": key,
+            "retry_policy": { "max_attempts": 3 }
+        })));
+        assert!(run.success(), "{key}: {}", run.stderr);
+    }
+}
+
+#[test]
+fn reports_retried_side_effects_without_a_key() {
+    let run = Scratch::new().check(&spec(json!({
+        "side_effects": ["charge_card", "send_email"],
+        "retry_policy": { "max_attempts": 3 },
+        "fallback": { "phase": "refund" }
+    })));
+    assert!(!run.success());
+    assert!(run.reports(
+        "Phase 'charge' retries side effects (charge_card, send_email) but declares no \
+         idempotency_key (retries exhausted fall back to phase 'refund')"
+    ));
+}
+
+#[test]
+fn reports_keys_naming_nothing() {
+    let scratch = Scratch::new();
+    let run = scratch.check(&spec(json!({ "idempotency_key": "order_id" })));
+    assert!(!run.success());
+    assert!(run.reports("idempotency_key 'order_id' is neither a declared input nor a path"));
+
+    let run = scratch.check(&spec(json!({ "idempotency_key": "" })));
+    assert!(run.reports("Phase 'charge' idempotency_key must be a non-empty string"));
+}
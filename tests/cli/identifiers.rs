@@ -0,0 +1,65 @@
+use crate::support::Scratch;
+
+/// Names that differ from each other only in case: `Collect`/`collect`/`COLLECT`, the ports
+/// `issue`/`ISSUE`, and `Straße`/`STRASSE`, which match after Unicode case folding only.
+fn validate(identifiers: &str, outputs: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        ".program-verify.yaml",
+        &format!("schema: open-schema.json\nidentifiers: {identifiers}\n"),
+    );
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "\
+meta: {{title: Straße, version: v1}}
+algorithm: {{name: STRASSE, phases: [Collect, reply]}}
+implementation:
+  phase_contracts:
+    collect:
+      inputs: []
+      outputs: {outputs}
+    reply:
+      inputs: [{{name: x, source: {{kind: phase_output, phase: COLLECT, port: ISSUE}}}}]
+"
+        ),
+    );
+    scratch.run(&["spec.yml"])
+}
+
+#[test]
+fn compares_names_as_configured() {
+    let run = validate("sensitive", "[{name: issue}]");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("[PV001]: algorithm.name='STRASSE' does not match"));
+    assert!(run.reports("[PV010]: phase_contracts contains unknown phase 'collect'"));
+    assert!(run.reports("[PV014]: Phase 'reply' references unknown producing phase 'COLLECT'"));
+
+    let run = validate("insensitive", "[{name: issue}]");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("[PV001]"));
+    assert!(!run.reports("[PV010]") && !run.reports("[PV014]"), "{}", run.stdout);
+
+    let run = validate("casefold", "[{name: issue}]");
+    assert!(run.success(), "{}", run.stdout);
+}
+
+#[test]
+fn finds_duplicates_that_differ_in_case() {
+    let run = validate("sensitive", "[{name: Issue}, {name: issue}]");
+    assert!(!run.reports("[PV013]"));
+
+    let run = validate("casefold", "[{name: Issue}, {name: issue}]");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("❌ Rule: phase contracts [PV013]: Phase 'collect' defines duplicate output 'issue'"));
+}
+
+#[test]
+fn rejects_unknown_modes() {
+    let run = validate("loose", "[]");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "identifiers: unknown variant `loose`, expected one of `sensitive`, `insensitive`, `casefold`"
+    ));
+}
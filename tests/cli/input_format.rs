@@ -0,0 +1,47 @@
+use crate::support::Scratch;
+
+const TOML_SPEC: &str = r#"
+[meta]
+title = "Support"
+version = "v1"
+
+[algorithm]
+name = "Billing"
+phases = []
+"#;
+
+#[test]
+fn detects_json_and_toml_by_extension() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.json",
+        r#"{"meta": {"title": "Support", "version": "v1"}, "algorithm": {"name": "Support", "phases": []}}"#,
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.json"]);
+    assert!(run.success(), "{}", run.stderr);
+
+    scratch.write("spec.toml", TOML_SPEC);
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.toml"]);
+    assert!(!run.success());
+    assert!(run.reports("meta.title vs algorithm.name"));
+}
+
+#[test]
+fn input_format_overrides_the_extension() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.txt", TOML_SPEC);
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "--input-format",
+        "toml",
+        "spec.txt",
+    ]);
+    assert!(run.reports("meta.title vs algorithm.name"));
+
+    let run = scratch.run(&["--input-format", "json", "spec.txt"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("invalid JSON"));
+}
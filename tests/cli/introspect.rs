@@ -0,0 +1,84 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+#[test]
+fn describes_the_capabilities_as_json() {
+    let scratch = Scratch::new();
+    scratch.write(
+        ".program-verify.yaml",
+        "assertions:\n  - path: $.algorithm.phases\n    assert: length <= 8\n",
+    );
+    let run = scratch.run(&["introspect", "--format", "json"]);
+    assert!(run.success(), "{}", run.stderr);
+    let capabilities: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(capabilities["name"], "program-verify");
+    assert_eq!(capabilities["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        capabilities["input_formats"],
+        json!(["yaml", "json", "toml"])
+    );
+    assert_eq!(
+        capabilities["schema_drafts"],
+        json!(["7", "2019-09", "2020-12"])
+    );
+    assert_eq!(
+        capabilities["output_formats"]["report"],
+        json!(["human", "json", "sarif", "html"])
+    );
+
+    let rules = capabilities["rules"].as_array().unwrap();
+    assert_eq!(
+        rules[0],
+        json!({ "id": "schema", "summary": "JSON Schema violation" })
+    );
+    assert!(rules.contains(&json!({
+        "builtin": true,
+        "category": "naming",
+        "default_severity": "error",
+        "id": "PV001",
+        "rule": "title-vs-algorithm",
+        "summary": "algorithm.name does not match the algorithm identity in meta.title"
+    })));
+    assert!(rules.contains(&json!({
+        "builtin": false,
+        "category": "policy",
+        "default_severity": "error",
+        "id": "CFG001",
+        "rule": "assertions",
+        "summary": "$.algorithm.phases must satisfy length <= 8"
+    })));
+
+    let subcommands: Vec<&str> = capabilities["subcommands"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|command| command["name"].as_str().unwrap())
+        .collect();
+    for name in [
+        "report rules",
+        "schema show",
+        "introspect",
+        "graph export",
+        "verify-artifact",
+    ] {
+        assert!(subcommands.contains(&name), "{name}: {subcommands:?}");
+    }
+    assert!(!subcommands.contains(&"report"));
+}
+
+#[test]
+fn summarizes_the_capabilities() {
+    let run = Scratch::new().run(&["introspect"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.starts_with(&format!(
+        "program-verify {}\nInput formats: yaml, json, toml\n",
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(run
+        .stdout
+        .contains("\nOutput formats (graph): dot, mermaid, svg\n"));
+    assert!(run
+        .stdout
+        .contains("\nSchema drafts: 7, 2019-09, 2020-12\n"));
+    assert!(run.stdout.contains("\nRule IDs: schema, PV001, PV002, "));
+}
@@ -0,0 +1,101 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A scratch directory with two versions of the `common` library; only 3.0.0 has a broken
+/// `collect` contract.
+fn with_library() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    for (version, key) in [("2.3.1", "$.request.id"), ("3.0.0", "request")] {
+        let bundle = json!({
+            "library": "common",
+            "version": version,
+            "phase_contracts": { "collect": { "idempotency_key": key } }
+        });
+        scratch.write(&format!("libs/common/{version}.yml"), &bundle.to_string());
+    }
+    scratch
+}
+
+fn spec(uses: JsonValue, contracts: JsonValue) -> JsonValue {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Support", "phases": ["collect"] },
+        "implementation": { "uses": uses, "phase_contracts": contracts }
+    })
+}
+
+fn validate(scratch: &Scratch, spec: &JsonValue) -> crate::support::Run {
+    scratch.write("spec.yml", &spec.to_string());
+    scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "--library-path",
+        "libs",
+        "spec.yml",
+    ])
+}
+
+#[test]
+fn merges_the_highest_matching_version() {
+    let scratch = with_library();
+    let run = validate(
+        &scratch,
+        &spec(json!([{ "library": "common", "version": "^2" }]), json!({})),
+    );
+    assert!(run.success(), "{}", run.stderr);
+
+    let run = validate(
+        &scratch,
+        &spec(
+            json!([{ "library": "common", "version": ">=2" }]),
+            json!({}),
+        ),
+    );
+    assert!(!run.success());
+    assert!(run.reports("idempotency_key 'request' is neither a declared input nor a path"));
+}
+
+#[test]
+fn local_contracts_win_with_a_warning() {
+    let scratch = with_library();
+    let run = validate(
+        &scratch,
+        &spec(
+            json!([{ "library": "common", "version": "3.0.0" }]),
+            json!({ "collect": {} }),
+        ),
+    );
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports(
+        "[PV072]: Phase 'collect' is defined locally and overrides the contract from library 'common'"
+    ));
+}
+
+#[test]
+fn reports_unresolvable_imports() {
+    let scratch = with_library();
+    let run = validate(
+        &scratch,
+        &spec(json!([{ "library": "common", "version": "^4" }]), json!({})),
+    );
+    assert!(!run.success());
+    assert!(run.reports(
+        "[PV071]: No version of library 'common' satisfies '^4' (available: 2.3.1, 3.0.0)"
+    ));
+
+    let run = validate(
+        &scratch,
+        &spec(json!([{ "library": "billing" }]), json!({})),
+    );
+    assert!(run.reports("Library 'billing' was not found (searched: libs)"));
+
+    let run = validate(
+        &scratch,
+        &spec(
+            json!([{ "library": "common", "version": "two" }]),
+            json!({}),
+        ),
+    );
+    assert!(run.reports("[PV070]: Library 'common' has an invalid version constraint"));
+}
@@ -0,0 +1,413 @@
+use crate::support::Scratch;
+use program_verify::{
+    registry, with_profile, CancellationToken, Diagnostics, Keyword, Profile, Report, Reporter,
+    ResolvedSchema, Rule, RuleConfig, SchemaRequest, SchemaResolver, Severity, SpecModel,
+    Validator,
+};
+use serde_json::{json, Value as JsonValue};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
+};
+use tokio::runtime;
+
+/// A rule of an embedding crate: phases must not be named `todo`.
+struct NoPlaceholderPhases;
+
+impl Rule for NoPlaceholderPhases {
+    fn id(&self) -> &'static str {
+        "ACME1"
+    }
+
+    fn name(&self) -> &'static str {
+        "no-placeholder-phases"
+    }
+
+    fn label(&self) -> &'static str {
+        "placeholder phases"
+    }
+
+    fn category(&self) -> &'static str {
+        "naming"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("ACME10", "phase named todo")]
+    }
+
+    fn check(&self, spec: &SpecModel, diagnostics: &mut Diagnostics) {
+        let phases = spec.doc["algorithm"]["phases"].as_array();
+        for (index, phase) in phases.into_iter().flatten().enumerate() {
+            if phase == "todo" {
+                let at = format!("/algorithm/phases/{index}");
+                diagnostics.report("ACME10", "phase 'todo' is a placeholder", &at);
+            }
+        }
+    }
+}
+
+const SPEC: &str = "meta: {title: A, version: v1}\nalgorithm: {name: A, phases: [collect, todo]}\n";
+
+/// A validator against a schema that accepts anything, so that only the domain rules report.
+fn validator(scratch: &Scratch) -> Validator {
+    let schema = scratch.write("open-schema.json", "{}");
+    Validator::new(&["--schema", schema.to_str().unwrap()]).unwrap()
+}
+
+#[test]
+fn runs_rules_registered_by_the_embedder() {
+    let scratch = Scratch::new();
+    let validation = with_profile(Profile::new(), || {
+        registry()
+            .write()
+            .unwrap()
+            .register(Box::new(NoPlaceholderPhases))
+            .unwrap();
+        validator(&scratch).validate("spec.yml", SPEC)
+    });
+    assert!(validation.valid, "{validation:?}");
+    assert_eq!(validation.errors, Vec::<String>::new());
+    let [finding] = &validation.findings[..] else {
+        panic!("{validation:?}");
+    };
+    assert_eq!(finding.code, "ACME10");
+    assert_eq!(finding.rule, Some("placeholder phases"));
+    assert_eq!(finding.severity, Severity::Warning);
+    assert_eq!(finding.file.as_deref(), Some("spec.yml"));
+    assert_eq!(finding.pointer.as_deref(), Some("/algorithm/phases/1"));
+
+    // Another profile does not have the rule.
+    let validation = with_profile(Profile::new(), || {
+        validator(&scratch).validate("spec.yml", SPEC)
+    });
+    assert!(validation.findings.is_empty(), "{validation:?}");
+}
+
+#[test]
+fn rejects_rules_that_clash_with_registered_ones() {
+    with_profile(Profile::new(), || {
+        let mut registry = registry().write().unwrap();
+        registry.register(Box::new(NoPlaceholderPhases)).unwrap();
+        let error = registry
+            .register(Box::new(NoPlaceholderPhases))
+            .unwrap_err();
+        assert!(error.contains("clashes with registered rule"), "{error}");
+    });
+}
+
+#[test]
+fn reports_specs_it_cannot_validate() {
+    let scratch = Scratch::new();
+    let validation = validator(&scratch).validate("spec.json", "{");
+    assert!(!validation.valid);
+    assert!(validation.findings.is_empty());
+    assert!(
+        validation.errors[0].starts_with("Error: invalid JSON: "),
+        "{validation:?}"
+    );
+    assert!(Validator::new(&["--fail-on", "never"]).is_err());
+}
+
+/// Schemas of an in-memory store, by spec version.
+struct StoredSchemas(Vec<(&'static str, JsonValue)>);
+
+impl SchemaResolver for StoredSchemas {
+    fn by_version(&self, request: &SchemaRequest) -> Result<Option<ResolvedSchema>, String> {
+        let stored = self
+            .0
+            .iter()
+            .find(|(v, _)| Some(*v) == request.spec_version);
+        Ok(stored.map(|(version, schema)| ResolvedSchema {
+            origin: format!("store:{version}"),
+            schema: schema.clone(),
+            base: None,
+        }))
+    }
+}
+
+#[test]
+fn asks_resolvers_of_the_embedder_first() {
+    let store = StoredSchemas(vec![("v2.7.0", json!({"required": ["owner"]}))]);
+    let validator = Validator::new(&[]).unwrap().resolver(Box::new(store));
+    let validation = validator.validate("spec.yml", &format!("spec_version: v2.7.0\n{SPEC}"));
+    assert!(!validation.valid);
+    let [finding] = &validation.findings[..] else {
+        panic!("{validation:?}");
+    };
+    assert_eq!(finding.code, "schema");
+    assert_eq!(finding.message, "\"owner\" is a required property");
+
+    // Versions the store does not have are looked up in the version map.
+    let validation = validator.validate("spec.yml", &format!("spec_version: v2.9.0\n{SPEC}"));
+    assert!(
+        validation.errors[0].starts_with("Error: version 'v2.9.0' was not found in "),
+        "{validation:?}"
+    );
+}
+
+/// Records what it is handed, as a ticketing system would receive it.
+#[derive(Clone, Default)]
+struct Events(Arc<Mutex<Vec<String>>>);
+
+impl Reporter for Events {
+    fn spec(&self, file: &str, doc: &JsonValue) {
+        let title = &doc["meta"]["title"];
+        self.0.lock().unwrap().push(format!("spec {file} {title}"));
+    }
+
+    fn report(&self, report: &Report) {
+        let pointer = report.pointer.as_deref().unwrap_or_default();
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {pointer}", report.code));
+    }
+
+    fn finish(&self) {
+        self.0.lock().unwrap().push("finish".to_string());
+    }
+}
+
+#[test]
+fn hands_findings_to_reporters_of_the_embedder() {
+    let scratch = Scratch::new();
+    let events = Events::default();
+    let validator = validator(&scratch).reporter(Box::new(events.clone()));
+    let spec = "meta: {title: A, version: v1}\nalgorithm: {name: B}\n---\nmeta: {title: C}\n";
+    let validation = validator.validate("spec.yml", spec);
+    assert_eq!(validation.findings.len(), 2, "{validation:?}");
+    assert_eq!(
+        *events.0.lock().unwrap(),
+        [
+            "spec spec.yml \"A\"",
+            "PV001 /algorithm/name",
+            "spec spec.yml \"C\"",
+            "PV003 /algorithm/name",
+            "finish",
+        ]
+    );
+}
+
+/// Cancels the validation it runs in, as an editor does when the user keeps typing.
+struct Interrupt(CancellationToken);
+
+impl Rule for Interrupt {
+    fn id(&self) -> &'static str {
+        "ACME2"
+    }
+
+    fn name(&self) -> &'static str {
+        "interrupt"
+    }
+
+    fn label(&self) -> &'static str {
+        "interrupt"
+    }
+
+    fn category(&self) -> &'static str {
+        "insights"
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("ACME20", "validation interrupted")]
+    }
+
+    fn check(&self, _spec: &SpecModel, diagnostics: &mut Diagnostics) {
+        self.0.cancel();
+        diagnostics.report("ACME20", "interrupted", "");
+    }
+}
+
+#[test]
+fn stops_when_the_embedder_cancels() {
+    let scratch = Scratch::new();
+    let token = CancellationToken::default();
+    let spec = "meta: {title: A}\n---\nmeta: {title: B}\n";
+    let validation = with_profile(Profile::new(), || {
+        registry()
+            .write()
+            .unwrap()
+            .register(Box::new(Interrupt(token.clone())))
+            .unwrap();
+        validator(&scratch)
+            .cancellation(token.clone())
+            .validate("spec.yml", spec)
+    });
+    assert!(validation.cancelled && !validation.valid, "{validation:?}");
+    // The second document is never validated.
+    let interrupted = validation.findings.iter().filter(|f| f.code == "ACME20");
+    assert_eq!(interrupted.count(), 1, "{validation:?}");
+
+    let validation = validator(&scratch)
+        .cancellation(token)
+        .validate("spec.yml", spec);
+    assert!(validation.cancelled && !validation.valid);
+    assert!(validation.findings.is_empty(), "{validation:?}");
+}
+
+/// `x-acme-single-word: true` rejects strings with whitespace.
+struct SingleWord;
+
+impl Keyword for SingleWord {
+    fn name(&self) -> &'static str {
+        "x-acme-single-word"
+    }
+
+    fn check(&self, value: &JsonValue, instance: &JsonValue) -> Vec<String> {
+        match instance.as_str() {
+            Some(text) if value == true && text.contains(char::is_whitespace) => {
+                vec![format!("{instance} is more than one word")]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn checks_keywords_of_the_embedder() {
+    let scratch = Scratch::new();
+    let schema = scratch.write(
+        "schema.json",
+        r#"{"properties": {"meta": {"properties": {"title": {"x-acme-single-word": true}}}}}"#,
+    );
+    let validator = Validator::new(&["--schema", schema.to_str().unwrap()])
+        .unwrap()
+        .keyword(Box::new(SingleWord));
+    let validation = validator.validate(
+        "spec.yml",
+        "meta: {title: Ticket triage}\nalgorithm: {name: Ticket triage}\n",
+    );
+    assert!(!validation.valid);
+    let [finding] = &validation.findings[..] else {
+        panic!("{validation:?}");
+    };
+    assert_eq!(finding.code, "schema");
+    assert_eq!(finding.message, "\"Ticket triage\" is more than one word");
+    assert_eq!(
+        finding.schema_path.as_deref(),
+        Some("/properties/meta/properties/title/x-acme-single-word")
+    );
+    assert!(
+        validator
+            .validate(
+                "spec.yml",
+                "meta: {title: Triage}\nalgorithm: {name: Triage}\n"
+            )
+            .valid
+    );
+}
+
+#[test]
+fn takes_severities_and_messages_of_the_embedder() {
+    let scratch = Scratch::new();
+    let spec = "meta: {title: Support, version: v1}\nalgorithm: {name: B}\n";
+    let validation = with_profile(Profile::new(), || {
+        let settings = RuleConfig {
+            severity: Some(Severity::Warning),
+            messages: [(
+                "PV001".to_string(),
+                "Rename to match the title: {message}".to_string(),
+            )]
+            .into(),
+            ..RuleConfig::default()
+        };
+        registry()
+            .write()
+            .unwrap()
+            .configure("title-vs-algorithm", settings)
+            .unwrap();
+        validator(&scratch).validate("spec.yml", spec)
+    });
+    assert!(validation.valid, "{validation:?}");
+    let [finding] = &validation.findings[..] else {
+        panic!("{validation:?}");
+    };
+    assert_eq!(finding.code, "PV001");
+    assert_eq!(finding.severity, Severity::Warning);
+    assert_eq!(
+        finding.message,
+        "Rename to match the title: algorithm.name='B' does not match the base of meta.title='Support' (detected 'Support')"
+    );
+
+    let validation = with_profile(Profile::new(), || {
+        validator(&scratch).validate("spec.yml", spec)
+    });
+    assert!(!validation.valid);
+    assert_eq!(validation.findings[0].severity, Severity::Error);
+}
+
+/// Holds the validation it runs in until the test lets it go.
+struct Gate(Mutex<mpsc::Receiver<()>>);
+
+impl Rule for Gate {
+    fn id(&self) -> &'static str {
+        "ACME3"
+    }
+
+    fn name(&self) -> &'static str {
+        "gate"
+    }
+
+    fn label(&self) -> &'static str {
+        "gate"
+    }
+
+    fn category(&self) -> &'static str {
+        "insights"
+    }
+
+    fn codes(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("ACME30", "gate never opened")]
+    }
+
+    fn check(&self, _spec: &SpecModel, diagnostics: &mut Diagnostics) {
+        let receiver = self.0.lock().unwrap();
+        if receiver.recv_timeout(Duration::from_secs(10)).is_err() {
+            diagnostics.report("ACME30", "the gate never opened", "");
+        }
+    }
+}
+
+#[test]
+fn validates_without_blocking_the_executor() {
+    let scratch = Scratch::new();
+    let spec = scratch.write("spec.yml", SPEC);
+    let (open, gate) = mpsc::channel();
+    // A single executor thread: were the rule run on it, the gate could not be opened.
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let validation = with_profile(Profile::new(), || {
+        let mut registry = registry().write().unwrap();
+        registry.register(Box::new(NoPlaceholderPhases)).unwrap();
+        registry.register(Box::new(Gate(Mutex::new(gate)))).unwrap();
+        drop(registry);
+        let validator = validator(&scratch);
+        runtime.block_on(async {
+            tokio::spawn(async move { open.send(()).unwrap() });
+            validator.validate_file(&spec).await
+        })
+    });
+    let codes: Vec<&str> = validation.findings.iter().map(|f| &f.code[..]).collect();
+    assert_eq!(codes, ["ACME10"], "{validation:?}");
+    assert_eq!(
+        validation.findings[0].file.as_deref(),
+        Some(spec.to_str().unwrap())
+    );
+
+    let missing = scratch.path("missing.yml");
+    let validation = runtime.block_on(validator(&scratch).validate_file(&missing));
+    assert!(!validation.valid);
+    assert!(
+        validation.errors[0].starts_with(&format!(
+            "Error: failed to read file {}: ",
+            missing.display()
+        )),
+        "{validation:?}"
+    );
+}
@@ -0,0 +1,50 @@
+use crate::support::Scratch;
+
+const YAML_SPEC: &str = "meta:\n  title: A\nalgorithm:\n  name: B\n";
+
+#[test]
+fn underlines_rule_findings_and_schema_errors() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "typed-schema.json",
+        r#"{"properties": {"meta": {"properties": {"title": {"type": "integer"}}}}}"#,
+    );
+    scratch.write("spec.yml", YAML_SPEC);
+
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.yml"]);
+    assert!(run.reports("(detected 'A')\n --> spec.yml:4:3\n  |\n4 |   name: B\n  |   ^^^^\n"));
+
+    let run = scratch.run(&["--schema", "typed-schema.json", "spec.yml"]);
+    assert!(run.reports("title/type)\n --> spec.yml:2:3\n  |\n2 |   title: A\n  |   ^^^^^\n"));
+}
+
+#[test]
+fn locates_json_and_missing_nodes() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.json",
+        "{\n  \"meta\": {},\n  \"algorithm\": {\"name\": \"B\"}\n}\n",
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.json"]);
+    // meta.title does not exist, so the finding points at meta.
+    assert!(
+        run.reports("[PV002]: Missing meta.title\n --> spec.json:2:3\n  |\n2 |   \"meta\": {},\n"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn toml_findings_have_no_location() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.toml",
+        "[meta]\ntitle = \"A\"\n[algorithm]\nname = \"B\"\n",
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.toml"]);
+    assert!(run.reports("[PV001]"));
+    assert!(!run.reports("-->"));
+}
@@ -0,0 +1,92 @@
+//! End-to-end tests: every module runs the `program-verify` binary on specs written to a scratch
+//! directory and checks what it reports; `library` does the same in-process through the crate.
+
+mod support;
+
+mod algorithm_outputs;
+mod assertions;
+mod baseline;
+mod becomes_error;
+mod changed_since;
+mod compat;
+mod config;
+mod contract_drift;
+mod cycles;
+mod data_classification;
+mod dataflow;
+mod dead_ports;
+mod defaults;
+mod diff;
+mod docs_generate;
+mod doctor;
+mod draft;
+mod duplicate_specs;
+mod encodings;
+mod fallback_chains;
+mod fmt;
+mod formats;
+mod graph_conditions;
+mod graph_edges;
+mod graph_export;
+mod graph_order;
+mod graph_terminals;
+mod html_report;
+mod idempotency_key;
+mod identifiers;
+mod input_format;
+mod introspect;
+mod libraries;
+mod library;
+mod locations;
+mod malformed_entries;
+mod migrate;
+mod multi_document;
+mod mutants;
+mod naming;
+mod observability;
+mod observations;
+mod offline;
+mod output;
+mod owners;
+mod parallel;
+mod phase_purity;
+mod port_examples;
+mod port_types;
+mod post_results;
+mod publish;
+mod query;
+mod readiness;
+mod reduce;
+mod report_formats;
+mod retry_policy;
+mod rule_ids;
+mod rules_report;
+mod schema_bundle;
+mod schema_cache;
+mod schema_completions;
+mod schema_coverage;
+mod schema_hover;
+mod schema_keywords;
+mod schema_refs;
+mod schema_resolvers;
+mod schema_show;
+mod schema_usage;
+mod scripted_rules;
+mod scrub;
+mod serve;
+mod severity;
+mod shared_phases;
+mod stdin;
+mod structure;
+mod suggestions;
+mod suppression_audit;
+mod suppressions;
+mod timeout;
+mod timeout_budget;
+mod timings;
+mod title_match;
+mod tui;
+mod verify_artifact;
+mod versions_check;
+mod watch;
+mod workspace_graph;
@@ -0,0 +1,54 @@
+use crate::support::Scratch;
+
+fn check(contracts: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}\nalgorithm: {{name: A, phases: [x, y]}}\n\
+             implementation:\n  phase_contracts:\n{contracts}"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_malformed_contract_entries() {
+    let run = check(
+        "    x:\n      inputs: [{schema: {}}]\n      outputs: [{name: out}]\n\
+         \x20     errors: [{message: boom}]\n      retry_policy: always\n\
+         \x20   y:\n      inputs:\n        - name: q\n          source: {phase: x, port: out}\n\
+         \x20       - name: r\n          source: {kind: phase_output, port: out}\n",
+    );
+    assert_eq!(run.code, Some(1));
+    for (message, location) in [
+        ("Phase 'x' input #0 has no name", "spec.yml:6:16"),
+        ("Phase 'x' error #0 has no code", "spec.yml:8:16"),
+        ("Phase 'x' retry_policy must be an object", "spec.yml:9:7"),
+        ("Phase 'y' input 'q' source has no kind", "spec.yml:13:11"),
+        (
+            "Phase 'y' input 'r' source of kind 'phase_output' names no phase",
+            "spec.yml:15:11",
+        ),
+    ] {
+        assert!(
+            run.reports(&format!("❌ Rule: phase contracts [PV019]: {message}\n")),
+            "{message}: {}",
+            run.stderr
+        );
+        assert!(run.reports(&format!("--> {location}\n")), "{location}");
+    }
+}
+
+#[test]
+fn passes_well_formed_contract_entries() {
+    let run = check(
+        "    x:\n      inputs: [{name: a}]\n      outputs: [{name: out}]\n\
+         \x20     errors: [{code: E1}]\n      retry_policy: {retryable: [E1]}\n\
+         \x20   y:\n      inputs:\n        - name: r\n\
+         \x20         source: {kind: phase_output, phase: x, port: out}\n",
+    );
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("[PV019]"));
+}
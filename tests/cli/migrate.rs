@@ -0,0 +1,90 @@
+use crate::support::Scratch;
+use std::fs;
+
+const V2: &str = "\
+spec_version: v2.1.0
+meta: {title: Support, version: v1}
+algorithm: {name: Support, phases: [collect, reply]}
+implementation:
+  source:
+    - name: collect
+      inputs: [{name: ticket, required: true, example: T-1}]
+      outputs: [{name: issue, required: true}]
+";
+
+#[test]
+fn migrates_v2_to_v3() {
+    let scratch = Scratch::new();
+    scratch.write("v2.yml", V2);
+    let run = scratch.run(&["migrate", "v2.yml", "-o", "v3.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .contains("🛠️ Wrote the migrated spec to v3.yml (v2.1.0 → v3.0.0, 4 follow-up(s))."));
+    assert!(run.stderr.starts_with("📝 4 manual follow-up(s):\n"));
+    for follow_up in [
+        "  • /implementation/phase_contracts/collect/inputs/0/example: Port 'ticket' of phase \
+         'collect' lost its example",
+        "  • /implementation/phase_contracts/reply: Phase 'reply' had no mini-program; declare its \
+         inputs and outputs",
+        "  • /implementation/return_contract/produced_by: Name the phase output",
+    ] {
+        assert!(run.stderr.contains(follow_up), "{}", run.stderr);
+    }
+
+    let migrated = fs::read_to_string(scratch.path("v3.yml")).unwrap();
+    assert!(migrated.contains(
+        "\
+    collect:
+      inputs:
+      - name: ticket
+        optional: false
+        source:
+          kind: instance
+          path: $.ticket
+      outputs:
+      - name: issue
+    reply:
+      description: 'TODO: describe phase reply'
+"
+    ));
+    assert!(migrated.ends_with("spec_version: v3.0.0\n"));
+    assert!(!migrated.contains("source:\n  - "));
+}
+
+#[test]
+fn custom_rules_replace_the_builtin_migration() {
+    let scratch = Scratch::new();
+    scratch.write("v2.yml", V2);
+    scratch.write(
+        "rules.yaml",
+        "from: v2\nto: v3.0.0\nsteps:\n  - op: set\n    at: /meta/owner\n    value: support\n",
+    );
+    let run = scratch.run(&["migrate", "v2.yml", "--rules", "rules.yaml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.contains("meta:\n  owner: support\n"));
+    assert!(run.stdout.contains("implementation:\n  source:\n"));
+    assert!(run.stdout.ends_with("spec_version: v3.0.0\n"));
+}
+
+#[test]
+fn rejects_what_it_cannot_migrate() {
+    let scratch = Scratch::new();
+    scratch.write("v2.yml", V2);
+    let run = scratch.run(&["migrate", "v2.yml", "--to", "v1"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("no migration path from v2 to v1 (available: v2 → v3.0.0)"));
+
+    scratch.write(
+        "rules.yaml",
+        "from: v2\nto: v3.0.0\nsteps:\n  - op: explode\n    at: /x\n",
+    );
+    let run = scratch.run(&["migrate", "v2.yml", "--rules", "rules.yaml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("invalid migration rules.yaml: steps[0].op: unknown variant `explode`"));
+
+    scratch.write("old.yml", "meta: {title: A}\n");
+    let run = scratch.run(&["migrate", "old.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("old.yml has no spec_version"));
+}
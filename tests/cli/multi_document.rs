@@ -0,0 +1,49 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+/// A YAML stream of one spec per title and algorithm name.
+fn stream(names: &[(&str, &str)]) -> String {
+    names
+        .iter()
+        .map(|(title, name)| {
+            json!({
+                "meta": { "title": title, "version": "v1" },
+                "algorithm": { "name": name, "phases": [] }
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n---\n")
+}
+
+#[test]
+fn validates_every_document() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("stream.yml", &stream(&[("Support", "Support"), ("Support", "Billing")]));
+
+    let run = scratch.run(&["--schema", "open-schema.json", "stream.yml"]);
+    assert!(!run.success());
+    assert!(run.stdout.contains("── Document #1 of 2 ──"));
+    assert!(run.stdout.contains("── Document #2 of 2 ──"));
+    assert!(run.reports("❌ 1 of 2 documents failed validation."));
+
+    scratch.write(
+        "stream.yml",
+        &format!(
+            "{}\n---\n",
+            stream(&[("Support", "Support"), ("Billing", "Billing")])
+        ),
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "stream.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn rejects_an_empty_stream() {
+    let scratch = Scratch::new();
+    scratch.write("stream.yml", "---\n");
+    let run = scratch.run(&["stream.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("the input contains no documents"));
+}
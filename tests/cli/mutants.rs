@@ -0,0 +1,66 @@
+use crate::support::Scratch;
+
+/// `outputs` is substituted in.
+fn fixture(outputs: &str) -> String {
+    format!(
+        "\
+meta:
+  title: Support
+  version: v1
+algorithm:
+  name: Support
+  phases: [collect, reply]
+implementation:
+  phase_contracts:
+    collect:
+      inputs: [{{name: ticket}}]
+      outputs: {outputs}
+    reply:
+      inputs: [{{name: issue, from: collect}}]
+"
+    )
+}
+
+#[test]
+fn known_good_fixtures_kill_every_mutant() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("fixtures/support.yml", &fixture("[{name: issue}]"));
+    let run = scratch.run(&["--schema", "open-schema.json", "report", "mutants", "fixtures"]);
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+    let header = run.stdout.lines().next().unwrap();
+    assert!(header.starts_with("── 1 spec(s), "), "{header}");
+    assert!(header.ends_with(" killed, 0 survived ──"), "{header}");
+    assert!(run.stdout.contains(
+        "       1        1  duplicate-output         PV013 duplicate input, output or error code"
+    ));
+    assert!(!run.stdout.contains("Survived:"));
+    assert!(run.stdout.contains(" PV011, PV012, PV014,"));
+}
+
+#[test]
+fn fails_on_a_surviving_mutant() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    // The output is already duplicated, so duplicating it again is not a new finding.
+    scratch.write(
+        "fixtures/support.yml",
+        &fixture("[{name: issue}, {name: issue}]"),
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "report", "mutants", "fixtures"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.stdout.contains(" killed, 1 survived ──\n"));
+    assert!(run.stdout.contains(
+        "  Survived:\n    fixtures/support.yml: duplicate-output at phase 'collect' (expected PV013)\n"
+    ));
+}
+
+#[test]
+fn needs_program_specs() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("fixtures/notes.yml", "title: notes\n");
+    let run = scratch.run(&["--schema", "open-schema.json", "report", "mutants", "fixtures"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("no program specs found in the given paths"));
+}
@@ -0,0 +1,68 @@
+use crate::support::Scratch;
+
+const SPEC: &str = "\
+meta: {title: A, version: v1}
+algorithm: {name: A, phases: [collect_issue, SendReply]}
+implementation:
+  phase_contracts:
+    collect_issue:
+      inputs: [{name: ticketId}]
+      outputs: [{name: issue}]
+      errors: [{code: COLLECT_TIMEOUT}, {code: E_INVALID}]
+    SendReply:
+      inputs: [{name: issue, source: {kind: phase_output, phase: collect_issue, port: issue}}]
+";
+
+const PATTERNS: &str = "\
+rules:
+  naming:
+    patterns:
+      phase: '^[a-z][a-z0-9_]*$'
+      port: '^[a-z][a-z0-9_]*$'
+      error_code: '^E[A-Z_]+$'
+";
+
+fn with_config(config: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", SPEC);
+    scratch.write(".program-verify.yaml", config);
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_nothing_until_configured() {
+    let run = with_config("rules: {}\n");
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("naming convention"));
+}
+
+#[test]
+fn reports_names_off_the_patterns() {
+    let run = with_config(PATTERNS);
+    assert!(run.success(), "{}", run.stderr);
+    for finding in [
+        "[PV220]: Phase name 'SendReply' does not match the phase naming pattern \
+         '^[a-z][a-z0-9_]*$'\n --> spec.yml:2:46",
+        "[PV221]: Phase 'collect_issue' input 'ticketId' does not match the port naming pattern \
+         '^[a-z][a-z0-9_]*$'\n --> spec.yml:6:17",
+        "[PV222]: Phase 'collect_issue' error code 'COLLECT_TIMEOUT' does not match the error \
+         code pattern '^E[A-Z_]+$'\n --> spec.yml:8:17",
+    ] {
+        let finding = format!("⚠️ Rule: naming convention {finding}");
+        assert!(run.reports(&finding), "{finding}\n{}", run.stderr);
+    }
+    assert!(!run.reports("'E_INVALID'"));
+    assert!(!run.reports("'collect_issue' does not match"));
+}
+
+#[test]
+fn rejects_invalid_patterns() {
+    let run = with_config("rules:\n  naming:\n    patterns:\n      phase: '(['\n");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("invalid phase pattern of rule 'naming'"));
+
+    let run = with_config("rules:\n  cycles:\n    patterns: {phase: x}\n");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("rule 'cycles' has no patterns setting (only naming does)"));
+}
@@ -0,0 +1,61 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+fn spec(collect: JsonValue, reply: JsonValue) -> JsonValue {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Support", "phases": ["collect", "reply"] },
+        "implementation": {
+            "phase_contracts": {
+                "collect": { "observability": collect },
+                "reply": { "observability": reply }
+            }
+        }
+    })
+}
+
+#[test]
+fn accepts_conventional_telemetry() {
+    let run = Scratch::new().check(&spec(
+        json!({
+            "metrics": [{ "name": "support.collect.latency_ms", "type": "histogram" }],
+            "log_events": ["support.collect.received"],
+            "spans": ["support.collect"]
+        }),
+        json!({ "alerts": [{ "metric": "support.collect.latency_ms", "threshold": 500 }] }),
+    ));
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_bad_names_and_types() {
+    let run = Scratch::new().check(&spec(
+        json!({
+            "metrics": [{ "name": "Support-Latency", "type": "timer" }],
+            "spans": ["support.collect", "support.collect"]
+        }),
+        json!({}),
+    ));
+    assert!(!run.success());
+    assert!(run
+        .reports("Phase 'collect' metric 'Support-Latency' does not match the naming convention"));
+    assert!(run.reports("metric 'Support-Latency' has unknown type 'timer'"));
+    assert!(run.reports("Phase 'collect' declares trace span 'support.collect' more than once"));
+}
+
+#[test]
+fn reports_shared_metrics_and_dangling_alerts() {
+    let run = Scratch::new().check(&spec(
+        json!({ "metrics": [{ "name": "support.latency_ms" }] }),
+        json!({
+            "metrics": [{ "name": "support.latency_ms" }],
+            "alerts": [{ "metric": "support.errors", "threshold": "high" }]
+        }),
+    ));
+    assert!(!run.success());
+    assert!(run.reports(
+        "Metric 'support.latency_ms' is declared by both phase 'collect' and phase 'reply'"
+    ));
+    assert!(run.reports("Phase 'reply' alert references undeclared metric 'support.errors'"));
+    assert!(run.reports("Phase 'reply' alert threshold must be a number"));
+}
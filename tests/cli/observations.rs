@@ -0,0 +1,71 @@
+use crate::support::Scratch;
+
+const SCHEMA: &str =
+    r#"{"properties": {"meta": {"properties": {"purpose": {"deprecated": true}}}}}"#;
+
+/// `reply.answer` feeds only the return_contract, `collect` is deprecated and `meta.purpose` is
+/// a deprecated field.
+const NOTEWORTHY: &str = "\
+meta: {title: Support, version: v1, purpose: old}
+algorithm: {name: Support, phases: [collect, reply]}
+implementation:
+  return_contract:
+    produced_by: {phase: reply, port: answer}
+  phase_contracts:
+    collect:
+      inputs: []
+      outputs: [{name: issue}]
+      deprecation_plan: {status: deprecated, replacement_phase: reply}
+    reply:
+      inputs: [{name: issue, source: {kind: phase_output, phase: collect, port: issue}}]
+      outputs: [{name: answer}]
+";
+
+fn validate(spec: &str, flags: &[&str]) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    scratch.write("spec.yml", spec);
+    scratch.run(&[&["--schema", "schema.json", "spec.yml"], flags].concat())
+}
+
+#[test]
+fn notes_show_at_verbose_only() {
+    let run = validate(NOTEWORTHY, &[]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("observation"), "{}", run.stdout);
+
+    let run = validate(NOTEWORTHY, &["--verbose"]);
+    assert!(run.success(), "{}", run.stderr);
+    for note in [
+        "ℹ️ Rule: observation [PV100]: Phase 'reply' output 'answer' is read by no other phase; it \
+         feeds the return_contract",
+        "ℹ️ Rule: observation [PV101]: Phase 'collect' is deprecated but still part of the \
+         algorithm; its replacement is 'reply'",
+        "ℹ️ Rule: observation [PV102]: '/meta/purpose' is deprecated by the schema",
+    ] {
+        assert!(run.reports(note), "{note}\n{}", run.stdout);
+    }
+    // `collect.issue` is read by `reply`.
+    assert!(!run.reports("output 'issue'"));
+
+    let run = validate(NOTEWORTHY, &["--fail-on", "info"]);
+    assert_eq!(run.code, Some(1));
+}
+
+#[test]
+fn no_notes_for_a_settled_spec() {
+    let settled = NOTEWORTHY
+        .replace(", purpose: old", "")
+        .replace("status: deprecated", "status: active")
+        .replace(
+            "return_contract:\n    produced_by: {phase: reply, port: answer}\n  ",
+            "",
+        );
+    // Without the return_contract, nothing consumes `reply.answer`.
+    let run = validate(
+        &settled,
+        &["--verbose", "--fail-on", "info", "--ignore", "PV140"],
+    );
+    assert!(run.success(), "{}", run.stdout);
+    assert!(!run.reports("observation"), "{}", run.stdout);
+}
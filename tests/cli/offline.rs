@@ -0,0 +1,52 @@
+use crate::support::{Scratch, Server};
+
+const SPEC: &str = "meta:\n  title: Support\nalgorithm:\n  name: Support\n";
+
+#[test]
+fn offline_runs_use_earlier_downloads() {
+    let server = Server::new(r#"{"properties": {"meta": {"type": "string"}}}"#);
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", SPEC);
+    let url = format!("{}/schema.json", server.url);
+
+    let run = scratch.run(&["--offline", "--schema", &url, "spec.yml"]);
+    assert!(!run.success());
+    assert!(
+        run.reports("--offline is set and it was never downloaded before"),
+        "{}",
+        run.stderr
+    );
+    assert_eq!(server.hits(), 0);
+
+    let run = scratch.run(&["--schema", &url, "spec.yml"]);
+    assert!(run.reports("is not of type \"string\""), "{}", run.stderr);
+    assert_eq!(server.hits(), 1);
+
+    let run = scratch.run(&["--offline", "--schema", &url, "spec.yml"]);
+    assert!(run.reports("is not of type \"string\""), "{}", run.stderr);
+    assert_eq!(server.hits(), 1);
+}
+
+#[test]
+fn falls_back_to_the_download_when_the_server_is_down() {
+    let server = Server::new(r#"{"properties": {"meta": {"type": "string"}}}"#);
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", SPEC);
+    let url = format!("{}/schema.json", server.url);
+    scratch.run(&["--schema", &url, "spec.yml"]);
+
+    server.go_down();
+    let run = scratch.run(&["--fetch-timeout", "2s", "--schema", &url, "spec.yml"]);
+    assert!(
+        run.reports(&format!("⚠️ Failed to fetch {url} (")),
+        "{}",
+        run.stderr
+    );
+    assert!(run.reports("using the copy downloaded earlier."));
+    assert!(run.reports("is not of type \"string\""));
+
+    let other = format!("{}/other.json", server.url);
+    let run = scratch.run(&["--fetch-timeout", "2s", "--schema", &other, "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(&other), "{}", run.stderr);
+}
@@ -0,0 +1,53 @@
+use crate::support::Scratch;
+
+/// A spec with a schema violation (a string title) and a PV001 finding.
+fn failing() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(
+        "schema.json",
+        r#"{"properties": {"meta": {"properties": {"title": {"type": "integer", "title": "Title", "description": "The name."}}}}}"#,
+    );
+    scratch.write("spec.yml", "meta:\n  title: A\nalgorithm:\n  name: B\n");
+    scratch
+}
+
+#[test]
+fn colors_only_when_asked() {
+    let scratch = failing();
+    let run = scratch.run(&["--color", "always", "--schema", "schema.json", "spec.yml"]);
+    assert!(run
+        .stderr
+        .contains("\x1b[31m❌ Rule: meta.title vs algorithm.name [PV001]"));
+    assert!(run.stderr.contains("(detected 'A')\x1b[0m"));
+
+    // Output to a pipe is not colored under the default `auto`.
+    let run = scratch.run(&["--schema", "schema.json", "spec.yml"]);
+    assert!(run.reports("[PV001]"));
+    assert!(!run.stderr.contains('\x1b'));
+
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", "meta:\n  title: A\nalgorithm:\n  name: A\n");
+    let run = scratch.run(&[
+        "--color",
+        "always",
+        "--schema",
+        "open-schema.json",
+        "spec.yml",
+    ]);
+    assert!(run.stdout.contains("\x1b[32m✅ OK"));
+}
+
+#[test]
+fn quiet_prints_nothing() {
+    let run = failing().run(&["-q", "--schema", "schema.json", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stdout, "");
+    assert_eq!(run.stderr, "");
+}
+
+#[test]
+fn verbose_names_the_schema_and_explains_violations() {
+    let run = failing().run(&["--verbose", "--schema", "schema.json", "spec.yml"]);
+    assert!(run.reports("ℹ️ Checking against schema.json."));
+    assert!(run.reports("  |   ^^^^^\n    ℹ️ Title: The name.\n"));
+}
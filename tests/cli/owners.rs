@@ -0,0 +1,121 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+use std::fs;
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "OWNERS",
+        "# pattern            owners\n*.yml                @org/platform\n\
+         support/             @org/support @alice\n/specs/llm/*.yml     @org/ml\n",
+    );
+    let spec = |title: &str, name: &str| {
+        format!("meta: {{title: {title}, version: v1}}\nalgorithm: {{name: {name}, phases: [x]}}\n")
+    };
+    scratch.write("specs/support/a.yml", &spec("A", "B"));
+    scratch.write("specs/llm/c.yml", &spec("C", "C"));
+    scratch.write("specs/d.yml", &spec("D", "D"));
+    scratch.write("specs/e.json", "{}");
+    scratch
+}
+
+#[test]
+fn writes_a_report_per_owner() {
+    let scratch = workspace();
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "specs",
+        "--split-report-by",
+        "owner",
+        "--owners",
+        "OWNERS",
+    ]);
+    // The exit code is that of the run.
+    assert_eq!(run.code, Some(1));
+    let mut written: Vec<String> = fs::read_dir(scratch.path("reports"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    written.sort();
+    assert_eq!(
+        written,
+        [
+            "alice.json",
+            "alice.md",
+            "org-ml.json",
+            "org-ml.md",
+            "org-platform.json",
+            "org-platform.md",
+            "org-support.json",
+            "org-support.md",
+            "unowned.json",
+            "unowned.md"
+        ]
+    );
+
+    let read = |name: &str| fs::read_to_string(scratch.path(&format!("reports/{name}"))).unwrap();
+    assert_eq!(
+        read("org-support.md"),
+        "# Validation report: @org/support\n\n1 file(s), 1 error(s), 0 warning(s), 0 info\n\n\
+         ## specs/support/a.yml\n\n- ❌ `PV001` (line 2, column 13): algorithm.name='B' does not \
+         match the base of meta.title='A' (detected 'A')\n"
+    );
+    assert_eq!(
+        read("org-ml.md"),
+        "# Validation report: @org/ml\n\n1 file(s), 0 error(s), 0 warning(s), 0 info\n\n\
+         ## specs/llm/c.yml\n\n✅ No findings.\n"
+    );
+    assert!(read("org-platform.md").contains("## specs/d.yml\n"));
+    assert!(read("unowned.md").contains("## specs/e.json\n"));
+
+    let alice: JsonValue = serde_json::from_str(&read("alice.json")).unwrap();
+    assert_eq!(alice["owner"], "@alice");
+    assert_eq!(alice["files"], json!(["specs/support/a.yml"]));
+    assert_eq!(
+        alice["summary"],
+        json!({ "error": 1, "info": 0, "warning": 0 })
+    );
+    assert_eq!(alice["findings"][0]["code"], "PV001");
+}
+
+#[test]
+fn reads_the_owners_file_from_the_configuration() {
+    let scratch = workspace();
+    scratch.write(
+        ".program-verify.yaml",
+        "schema: open-schema.json\nowners: OWNERS\n",
+    );
+    let run = scratch.run(&[
+        "specs/llm",
+        "--split-report-by",
+        "owner",
+        "--split-report-dir",
+        "out",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(scratch.path("out/org-ml.json").exists());
+    assert!(!scratch.path("reports").exists());
+}
+
+#[test]
+fn needs_an_owners_file() {
+    let scratch = workspace();
+    let args = [
+        "--schema",
+        "open-schema.json",
+        "specs",
+        "--split-report-by",
+        "owner",
+    ];
+    let run = scratch.run(&args);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "Error: --split-report-by owner needs an owners file (--owners or `owners` in the \
+         configuration file)"
+    ));
+
+    let run = scratch.run(&[&args[..], &["--owners", "NOPE"]].concat());
+    assert!(run.reports("Error: failed to read owners file NOPE: "));
+}
@@ -0,0 +1,30 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+#[test]
+fn output_does_not_depend_on_the_thread_count() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    for index in 0..8 {
+        let name = if index % 3 == 0 { "Billing" } else { "Support" };
+        let spec = json!({ "meta": { "title": "Support" }, "algorithm": { "name": name } });
+        scratch.write(&format!("specs/{index}.yml"), &spec.to_string());
+    }
+
+    let serial = scratch.run(&["--schema", "open-schema.json", "-j", "1", "specs"]);
+    assert!(!serial.success());
+    let headers: Vec<&str> = serial
+        .stdout
+        .lines()
+        .filter(|l| l.starts_with("── "))
+        .collect();
+    let expected: Vec<String> = (0..8).map(|i| format!("── specs/{i}.yml ──")).collect();
+    assert_eq!(headers, expected);
+
+    for jobs in ["2", "8"] {
+        let parallel = scratch.run(&["--schema", "open-schema.json", "--jobs", jobs, "specs"]);
+        assert_eq!(parallel.code, serial.code);
+        assert_eq!(parallel.stdout, serial.stdout);
+        assert_eq!(parallel.stderr, serial.stderr);
+    }
+}
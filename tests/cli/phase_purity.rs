@@ -0,0 +1,55 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+fn spec(contract: JsonValue) -> JsonValue {
+    json!({
+        "meta": { "title": "Payments", "version": "v1" },
+        "algorithm": { "name": "Payments", "phases": ["charge"] },
+        "implementation": { "phase_contracts": { "charge": contract } }
+    })
+}
+
+#[test]
+fn accepts_retries_on_pure_or_idempotent_phases() {
+    let scratch = Scratch::new();
+    for contract in [
+        json!({ "deterministic": true, "retry_policy": { "max_attempts": 3 } }),
+        json!({
+            "side_effects": ["charge_card"],
+            "idempotent": true,
+            "idempotency_key": "$.charge.id",
+            "retry_policy": { "max_attempts": 3 }
+        }),
+        json!({ "side_effects": ["charge_card"], "idempotent": false }),
+    ] {
+        let run = scratch.check(&spec(contract.clone()));
+        assert!(run.success(), "{contract}: {}", run.stderr);
+    }
+}
+
+#[test]
+fn reports_retries_on_non_idempotent_phases() {
+    let run = Scratch::new().check(&spec(json!({
+        "side_effects": ["charge_card"],
+        "idempotent": false,
+        "idempotency_key": "$.charge.id",
+        "retry_policy": { "max_attempts": 3 }
+    })));
+    assert!(!run.success());
+    assert!(run.reports("declares a retry_policy but is explicitly marked idempotent: false"));
+}
+
+#[test]
+fn reports_malformed_annotations() {
+    let run = Scratch::new().check(&spec(json!({
+        "deterministic": "yes",
+        "side_effects": ["charge_card", "charge_card"],
+        "idempotent": false
+    })));
+    assert!(!run.success());
+    assert!(run.reports("Phase 'charge' deterministic must be a boolean"));
+    assert!(run.reports("lists side effect 'charge_card' more than once"));
+
+    let run = Scratch::new().check(&spec(json!({ "idempotent": false })));
+    assert!(run.reports("is declared non-idempotent but lists no side_effects"));
+}
@@ -0,0 +1,48 @@
+use crate::support::Scratch;
+
+fn check(inputs: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}
+algorithm: {{name: A, phases: [analyze]}}
+implementation:
+  phase_contracts:
+    analyze:
+      inputs:
+{inputs}"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_examples_that_do_not_match_the_schema() {
+    let run = check(
+        "        - {name: ticket, schema: {properties: {id: {type: string}}}, example: {id: 5}}\n\
+         \x20       - {name: severity, schema: {type: integer, maximum: 5}, examples: [3, 7]}\n",
+    );
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports(
+        "⚠️ Rule: port examples [PV170]: Example of input 'ticket' of phase 'analyze' does not \
+         match its schema: 5 is not of type \"string\" (at /id)\n --> spec.yml:7:70\n"
+    ));
+    assert!(run.reports(
+        "⚠️ Rule: port examples [PV170]: Example of input 'severity' of phase 'analyze' does not \
+         match its schema: 7 is greater than the maximum of 5\n --> spec.yml:8:79\n"
+    ));
+}
+
+#[test]
+fn passes_matching_and_unchecked_examples() {
+    let run = check(
+        "        - {name: ok, schema: {type: string}, examples: [a, b]}\n\
+         \x20       - {name: named, schema: Ticket, example: 1}\n\
+         \x20       - {name: external, schema: {$ref: 'other.json'}, example: 1}\n\
+         \x20       - {name: broken, schema: {type: 5}, example: 1}\n",
+    );
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("[PV170]"), "{}", run.stderr);
+}
@@ -0,0 +1,81 @@
+use crate::support::Scratch;
+
+/// A spec in which input `severity` of `escalate` (with `input` added to it) reads the output
+/// `severity` of `analyze` (with `output` added to it).
+fn check(output: &str, input: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}
+algorithm: {{name: A, phases: [analyze, escalate]}}
+implementation:
+  phase_contracts:
+    analyze:
+      outputs: [{{name: severity, {output}}}]
+    escalate:
+      inputs:
+        - {{name: severity, source: {{kind: phase_output, phase: analyze, port: severity}}, {input}}}
+"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_inputs_that_do_not_accept_the_output() {
+    for (output, input, message) in [
+        (
+            "type: string",
+            "type: number",
+            "expects number but reads output 'severity' of phase 'analyze', which is string",
+        ),
+        (
+            "type: 'list<string>'",
+            "type: 'int[]'",
+            "expects array<integer> but reads output 'severity' of phase 'analyze', which is \
+             array<string>",
+        ),
+        (
+            "type: 'int | null'",
+            "type: int",
+            "expects integer but reads output 'severity' of phase 'analyze', which is integer | \
+             null",
+        ),
+        (
+            "schema: {enum: [1, 2]}",
+            "schema: {type: string}",
+            "expects string but reads output 'severity' of phase 'analyze', which is integer",
+        ),
+    ] {
+        let run = check(output, input);
+        assert_eq!(run.code, Some(1), "{output} → {input}");
+        assert!(
+            run.reports(&format!(
+                "❌ Rule: port types [PV160]: Input 'severity' of phase 'escalate' {message}\n \
+                 --> spec.yml:9:"
+            )),
+            "{output} → {input}: {}",
+            run.stderr
+        );
+    }
+}
+
+#[test]
+fn passes_inputs_that_accept_the_output() {
+    for (output, input) in [
+        ("schema: {type: integer}", "type: number"),
+        (
+            "type: 'array<int>'",
+            "schema: {type: array, items: {type: number}}",
+        ),
+        ("schema: {enum: [a, b]}", "type: string"),
+        ("type: int", "type: 'option<int>'"),
+        ("schema: {$ref: '#/x'}", "type: int"),
+        ("type: widget", "type: int"),
+    ] {
+        let run = check(output, input);
+        assert!(run.success(), "{output} → {input}: {}", run.stderr);
+    }
+}
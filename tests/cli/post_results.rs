@@ -0,0 +1,122 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+/// A local endpoint answering its requests with `statuses` in turn; every request it reads is
+/// sent to the receiver with its head and body.
+fn endpoint(statuses: &[u16]) -> (String, Receiver<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/runs", listener.local_addr().unwrap());
+    let (sender, requests) = mpsc::channel();
+    let statuses = statuses.to_vec();
+    thread::spawn(move || {
+        for status in statuses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let (mut head, mut line) = (String::new(), String::new());
+            while reader.read_line(&mut line).unwrap() > 2 {
+                head += &line.to_ascii_lowercase();
+                line.clear();
+            }
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length: "))
+                .map_or(0, |length| length.trim().parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let _ = sender.send((head, String::from_utf8(body).unwrap()));
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+        }
+    });
+    (url, requests)
+}
+
+fn corpus() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("good.yml", "meta: {title: A}\nalgorithm: {name: A}\n");
+    scratch.write("bad.yml", "meta: {title: Support}\nalgorithm: {name: B}\n");
+    scratch
+}
+
+#[test]
+fn posts_a_summary_of_the_run() {
+    let (url, requests) = endpoint(&[200]);
+    let run = corpus().run(&[
+        "--schema",
+        "open-schema.json",
+        "good.yml",
+        "bad.yml",
+        "--post-results",
+        &url,
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(&format!("📤 Posted the results of 2 file(s) to {url}.")));
+    let (head, body) = requests.recv().unwrap();
+    assert!(head.starts_with("post /runs http/1.1"), "{head}");
+    assert!(head.contains("content-type: application/json"));
+    assert!(!head.contains("authorization"));
+    let body: JsonValue = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["tool"], "program-verify");
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(body["files"], json!(["good.yml", "bad.yml"]));
+    assert_eq!(
+        body["summary"],
+        json!({"error": 1, "warning": 0, "info": 0})
+    );
+    assert_eq!(body["findings"][0]["code"], "PV001");
+    assert_eq!(body["findings"][0]["file"], "bad.yml");
+}
+
+#[test]
+fn retries_server_errors_only() {
+    let (url, requests) = endpoint(&[503, 200]);
+    let run = corpus().run(&[
+        "--schema",
+        "open-schema.json",
+        "good.yml",
+        "--post-results",
+        &url,
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("(the server answered 503 Service Unavailable); retrying in 1 s."));
+    assert!(run.reports("📤 Posted the results of 1 file(s)"));
+    assert_eq!(requests.iter().count(), 2);
+
+    // A failed post is an error on stderr, but the exit code follows the findings.
+    let (url, requests) = endpoint(&[400]);
+    let run = corpus().run(&[
+        "--schema",
+        "open-schema.json",
+        "good.yml",
+        "--post-results",
+        &url,
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stderr.contains(&format!(
+        "Error: failed to post the results to {url}: the server answered 400 Bad Request"
+    )));
+    assert_eq!(requests.iter().count(), 1);
+}
+
+#[test]
+fn refuses_to_post_offline() {
+    let run = corpus().run(&[
+        "--schema",
+        "open-schema.json",
+        "good.yml",
+        "--offline",
+        "--post-results",
+        "http://127.0.0.1:9/runs",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("Error: --post-results cannot be used with --offline"));
+}
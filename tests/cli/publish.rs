@@ -0,0 +1,155 @@
+use crate::support::Scratch;
+use serde_json::Value as JsonValue;
+use std::fs;
+
+/// The first Ed25519 test vector of RFC 8032.
+const SECRET: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn spec(version: &str, phases: &str) -> String {
+    format!("meta: {{title: Support, version: {version}}}\nalgorithm: {{name: Support, phases: {phases}}}\n")
+}
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(
+        ".program-verify.yaml",
+        "schema: open-schema.json\nregistry: registry\n",
+    );
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", &spec("v1.0.0", "[x]"));
+    scratch
+}
+
+#[test]
+fn publishes_canonical_specs_once() {
+    let scratch = workspace();
+    let run = scratch.run(&["publish", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .starts_with("📦 Published spec.yml as Support@v1.0.0 to "));
+    assert!(run.stdout.contains("/registry/Support/v1.0.0/ (sha256 "));
+    let stored = fs::read_to_string(scratch.path("registry/Support/v1.0.0/spec.json")).unwrap();
+    assert_eq!(
+        stored,
+        r#"{"algorithm":{"name":"Support","phases":["x"]},"meta":{"title":"Support","version":"v1.0.0"}}"#
+    );
+    let manifest: JsonValue = serde_json::from_str(
+        &fs::read_to_string(scratch.path("registry/Support/v1.0.0/manifest.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(manifest["name"], "Support");
+    assert_eq!(manifest["version"], "v1.0.0");
+    assert_eq!(manifest["size"], stored.len());
+    assert_eq!(manifest["tool"]["version"], env!("CARGO_PKG_VERSION"));
+
+    let run = scratch.run(&["publish", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("ℹ️ Support@v1.0.0 is already published with the same content"));
+
+    // The same spec as JSON, with its keys in another order.
+    scratch.write(
+        "deployed.json",
+        r#"{"algorithm": {"phases": ["x"], "name": "Support"}, "meta": {"version": "v1.0.0", "title": "Support"}}"#,
+    );
+    let run = scratch.run(&["verify-published", "deployed.json"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("✅ deployed.json matches Support@v1.0.0 as published (sha256 "));
+}
+
+#[test]
+fn refuses_to_change_a_published_version() {
+    let scratch = workspace();
+    assert!(scratch.run(&["publish", "spec.yml"]).success());
+    scratch.write("changed.yml", &spec("v1.0.0", "[x, y]"));
+
+    let run = scratch.run(&["publish", "changed.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("Support@v1.0.0 is already published with different content"));
+
+    let run = scratch.run(&["verify-published", "changed.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("❌ changed.yml does not match Support@v1.0.0 as published: sha256 "));
+    assert!(run
+        .reports("── Support@v1.0.0 (published) → changed.yml: 1 change(s) ──\nphases:\n  + y\n"));
+
+    let entry = scratch.path("registry/Support/v1.0.0/spec.json");
+    fs::write(
+        &entry,
+        fs::read_to_string(&entry)
+            .unwrap()
+            .replace("\"x\"", "\"q\""),
+    )
+    .unwrap();
+    let run = scratch.run(&["verify-published", "changed.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "/registry/Support/v1.0.0/spec.json does not match its manifest either; the registry \
+         entry was modified."
+    ));
+}
+
+#[test]
+fn publishes_only_valid_versioned_specs() {
+    let scratch = workspace();
+    scratch.write(
+        "unversioned.yml",
+        "meta: {title: Support}\nalgorithm: {name: Support, phases: [x]}\n",
+    );
+    let run = scratch.run(&["publish", "unversioned.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("unversioned.yml: a published spec needs a meta.version"));
+
+    scratch.write(
+        "invalid.yml",
+        "meta: {title: S, version: v1.0.0}\nalgorithm: {name: Support, phases: [x]}\n",
+    );
+    let run = scratch.run(&["publish", "invalid.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("❌ invalid.yml did not pass validation; it was not published."));
+    assert!(!scratch.path("registry").exists());
+}
+
+#[test]
+fn signs_and_verifies_publications() {
+    let scratch = workspace();
+    let pkcs8 = [hex("302e020100300506032b657004220420"), hex(SECRET)].concat();
+    fs::write(scratch.path("publish.der"), pkcs8).unwrap();
+    fs::write(scratch.path("publish.pub"), hex(PUBLIC)).unwrap();
+
+    let run = scratch.run(&["publish", "spec.yml", "--sign-key", "publish.der"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports(", signed)."));
+    let manifest: JsonValue = serde_json::from_str(
+        &fs::read_to_string(scratch.path("registry/Support/v1.0.0/manifest.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(manifest["signature"]["algorithm"], "ed25519");
+    assert_eq!(manifest["signature"]["public_key"], PUBLIC);
+
+    let run = scratch.run(&[
+        "verify-published",
+        "spec.yml",
+        "--verify-key",
+        "publish.pub",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("signed with the given key."));
+
+    let run = scratch.run(&["verify-published", "spec.yml"]);
+    assert!(run.reports("ℹ️ The publication is signed; pass --verify-key to check the signature."));
+
+    let mut other = hex(PUBLIC);
+    other[0] ^= 1;
+    fs::write(scratch.path("other.pub"), other).unwrap();
+    let run = scratch.run(&["verify-published", "spec.yml", "--verify-key", "other.pub"]);
+    assert_eq!(run.code, Some(1));
+}
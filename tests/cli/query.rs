@@ -0,0 +1,70 @@
+use crate::support::Scratch;
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(
+        "spec.yml",
+        "meta: {title: Support, version: v1}\nalgorithm: {name: Support, phases: [collect, reply]}\n\
+         implementation:\n  phase_contracts:\n    collect:\n      outputs: [{name: labels}]\n\
+         \x20   reply:\n      outputs: [{name: answer}, {name: labels}]\n",
+    );
+    scratch
+}
+
+#[test]
+fn prints_the_selected_values() {
+    let scratch = workspace();
+    for (args, expected) in [
+        (
+            &["query", "$.algorithm.phases[*]", "-r", "spec.yml"][..],
+            "collect\nreply\n",
+        ),
+        (
+            &["query", "$.algorithm.phases", "spec.yml"],
+            "[\"collect\",\"reply\"]\n",
+        ),
+        (
+            &["query", "$.algorithm['name']", "spec.yml"],
+            "\"Support\"\n",
+        ),
+        (
+            &["query", "$.algorithm.phases[1]", "--raw", "spec.yml"],
+            "reply\n",
+        ),
+        (
+            &[
+                "query",
+                "$.implementation.phase_contracts.*.outputs[*].name",
+                "spec.yml",
+            ],
+            "\"labels\"\n\"answer\"\n\"labels\"\n",
+        ),
+        (
+            &["query", "$..outputs[0].name", "-r", "spec.yml"],
+            "labels\nanswer\n",
+        ),
+    ] {
+        let run = scratch.run(args);
+        assert!(run.success(), "{args:?}: {}", run.stderr);
+        assert_eq!(run.stdout, expected, "{args:?}");
+    }
+
+    let run = scratch.run_with_input(&["query", "$.a", "-"], "{\"a\": 1}");
+    assert_eq!(run.stdout, "1\n");
+}
+
+#[test]
+fn fails_when_nothing_is_selected() {
+    let scratch = workspace();
+    let run = scratch.run(&["query", "$.algorithm.owner", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stdout, "");
+
+    let run = scratch.run(&["query", "algorithm", "spec.yml"]);
+    assert!(!run.success());
+    assert!(run.reports("algorithm"), "{}", run.stderr);
+
+    let run = scratch.run(&["query", "$", "missing.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("missing.yml"), "{}", run.stderr);
+}
@@ -0,0 +1,101 @@
+use crate::support::Scratch;
+
+const V3_SCHEMA: &str = r#"{"properties": {
+  "meta": {"required": ["owner"]},
+  "implementation": {"properties": {"phase_contracts": {"additionalProperties": {
+    "properties": {"outputs": {"items": {"required": ["schema"]}}}
+  }}}}
+}}"#;
+
+const TRIAGE: &str = "\
+spec_version: v2.0.0
+meta: {title: Triage, version: v1}
+algorithm: {name: Triage, phases: [fetch, store]}
+implementation:
+  phase_contracts:
+    fetch:
+      inputs: [{name: url, schema: {type: string}}]
+      outputs: [{name: body}]
+";
+
+const READY: &str = "\
+spec_version: v3.0.0
+meta: {title: Ready, version: v1, owner: search}
+algorithm: {name: Ready, phases: [fetch]}
+implementation:
+  phase_contracts:
+    fetch:
+      inputs: [{name: url, schema: {type: string}, source: {kind: instance, path: url}}]
+      outputs: [{name: body, schema: {type: string}}]
+";
+
+/// A version map with an open v2 schema and a stricter v3 one.
+fn versions() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("schemas/v2.json", "{}");
+    scratch.write("schemas/v3.json", V3_SCHEMA);
+    scratch.write(
+        "version_map.yaml",
+        "v2.0.0: schemas/v2.json\nv3.0.0: schemas/v3.json\n",
+    );
+    scratch
+}
+
+#[test]
+fn lists_what_is_missing_by_kind() {
+    let scratch = versions();
+    scratch.write("triage.yml", TRIAGE);
+    let run = scratch.run(&["readiness", "--target", "v3", "triage.yml"]);
+    assert_eq!(run.code, Some(1), "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "\
+── triage.yml: 5 item(s) to reach v3.0.0 ──
+Phase contracts to add:
+  - [ ] Add a phase_contracts entry for phase 'store'
+Port fields to add:
+  - [ ] Add `schema` to output 'body' of phase 'fetch' (/implementation/phase_contracts/fetch/outputs/0)
+Required fields to add:
+  - [ ] Add `owner` to /meta
+Other changes:
+  - [ ] Set spec_version from v2.0.0 to v3.0.0
+  - [ ] PV019 Phase 'fetch' input 'url' has no source
+── 1 spec(s): 0 ready for v3.0.0, 1 with 5 item(s) to do ──
+"
+    );
+
+    let run = scratch.run(&[
+        "readiness",
+        "--target",
+        "v3",
+        "triage.yml",
+        "--ignore",
+        "PV019",
+    ]);
+    assert!(!run.reports("PV019"));
+    assert!(run.reports("1 with 4 item(s) to do"));
+}
+
+#[test]
+fn ready_specs_pass() {
+    let scratch = versions();
+    scratch.write("ready.yml", READY);
+    let run = scratch.run(&["readiness", "--target", "v3", "ready.yml"]);
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+    assert!(run.reports("✅ ready.yml is ready for v3.0.0."));
+
+    scratch.write("triage.yml", TRIAGE);
+    let run = scratch.run(&["readiness", "--target", "v3", "ready.yml", "triage.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("── 2 spec(s): 1 ready for v3.0.0, 1 with 5 item(s) to do ──"));
+}
+
+#[test]
+fn rejects_unknown_targets() {
+    let scratch = versions();
+    scratch.write("triage.yml", TRIAGE);
+    let run = scratch.run(&["readiness", "--target", "v9", "triage.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("version 'v9.0.0' was not found"));
+    assert!(run.reports("Available versions: v2.0.0, v3.0.0"));
+}
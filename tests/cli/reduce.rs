@@ -0,0 +1,95 @@
+use crate::support::Scratch;
+
+const SPEC: &str = "\
+meta:
+  title: Support
+  version: v1
+algorithm:
+  name: Support
+  phases: [collect, escalate, reply]
+implementation:
+  phase_contracts:
+    collect:
+      inputs: [{name: ticket}]
+      outputs: [{name: issue}]
+    escalate:
+      side_effects: [crm_write]
+      retry_policy: {max_attempts: 3}
+      outputs: [{name: case}]
+    reply:
+      observability: {spans: [Reply]}
+";
+
+const REPRODUCER: &str = "\
+implementation:
+  phase_contracts:
+    escalate:
+      retry_policy: {}
+      side_effects:
+      - crm_write
+";
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", SPEC);
+    scratch
+}
+
+#[test]
+fn shrinks_to_the_target_finding() {
+    let scratch = workspace();
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "reduce",
+        "spec.yml",
+        "--code",
+        "PV040",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(run.stdout, REPRODUCER);
+
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "reduce",
+        "spec.yml",
+        "--code",
+        "PV050",
+        "-o",
+        "repro.yml",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.reports("✂️ Reduced spec.yml from 17 to 6 line(s); repro.yml still reports [PV050]."),
+        "{}",
+        run.stdout
+    );
+    let run = scratch.run(&["--schema", "open-schema.json", "repro.yml"]);
+    assert!(
+        run.reports("[PV050]") && !run.reports("[PV040]"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn fails_without_the_target_finding() {
+    let scratch = workspace();
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "reduce",
+        "spec.yml",
+        "--code",
+        "PV099",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("Error: spec.yml reports no [PV099] finding to reduce"));
+
+    scratch.write("good.yml", "meta:\n  title: A\nalgorithm:\n  name: A\n");
+    let run = scratch.run(&["--schema", "open-schema.json", "reduce", "good.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("Error: good.yml reports no findings to reduce"));
+}
@@ -0,0 +1,155 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// `bad.yml` has a PV001 finding and a schema violation, `good.yml` has neither.
+fn corpus() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(
+        "schema.json",
+        r#"{"properties": {"meta": {"properties": {"title": {"type": "string", "maxLength": 3}}}}}"#,
+    );
+    scratch.write(
+        "bad.yml",
+        "meta:\n  title: Support\nalgorithm:\n  name: B\n",
+    );
+    scratch.write("good.yml", "meta:\n  title: A\nalgorithm:\n  name: A\n");
+    scratch
+}
+
+#[test]
+fn json_prints_one_finding_per_line() {
+    let run = corpus().run(&[
+        "--format",
+        "json",
+        "--schema",
+        "schema.json",
+        "bad.yml",
+        "good.yml",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stderr, "");
+    let mut findings: Vec<JsonValue> = run
+        .stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(findings.pop().unwrap().get("provenance").is_some());
+    assert_eq!(findings.len(), 2, "{}", run.stdout);
+    assert_eq!(findings[0]["code"], "schema");
+    assert_eq!(findings[0]["pointer"], "/meta/title");
+    assert_eq!(
+        findings[0]["schema_path"],
+        "/properties/meta/properties/title/maxLength"
+    );
+    assert_eq!(
+        findings[1],
+        json!({
+            "code": "PV001",
+            "column": 3,
+            "file": "bad.yml",
+            "line": 4,
+            "message": "algorithm.name='B' does not match the base of meta.title='Support' (detected 'Support')",
+            "pointer": "/algorithm/name",
+            "rule": "meta.title vs algorithm.name",
+            "severity": "error"
+        })
+    );
+
+    let run = corpus().run(&["--format", "json", "--schema", "schema.json", "good.yml"]);
+    assert!(run.success());
+    assert!(run.stdout.starts_with("{\"provenance\":"));
+    assert_eq!(run.stdout.lines().count(), 1);
+}
+
+#[test]
+fn sarif_prints_a_log_at_the_end() {
+    let run = corpus().run(&[
+        "--format",
+        "sarif",
+        "--schema",
+        "schema.json",
+        "bad.yml",
+        "good.yml",
+    ]);
+    assert_eq!(run.code, Some(1));
+    let log: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    let sarif = &log["runs"][0];
+    assert_eq!(sarif["tool"]["driver"]["name"], "program-verify");
+    let results = sarif["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1]["ruleId"], "PV001");
+    assert_eq!(results[1]["level"], "error");
+    let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 4);
+    assert_eq!(region["startColumn"], 3);
+
+    let rules = sarif["tool"]["driver"]["rules"].as_array().unwrap();
+    assert!(rules.iter().any(|rule| rule["id"] == "PV001"));
+}
+
+#[test]
+fn sarif_rules_carry_category_and_default_level() {
+    let run = corpus().run(&["--format", "sarif", "--schema", "schema.json", "bad.yml"]);
+    let log: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    let rules = log["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap();
+    let rule = |id: &str| rules.iter().find(|rule| rule["id"] == id).unwrap().clone();
+    assert_eq!(
+        rule("PV001"),
+        json!({
+            "id": "PV001",
+            "shortDescription": {"text": "algorithm.name does not match the algorithm identity in meta.title"},
+            "defaultConfiguration": {"level": "error"},
+            "properties": {"tags": ["naming"]}
+        })
+    );
+    assert_eq!(rule("PV050")["properties"]["tags"], json!(["observability"]));
+    assert_eq!(rule("schema").get("properties"), None);
+}
+
+#[test]
+fn reports_record_their_provenance() {
+    let scratch = corpus();
+    scratch.write(
+        ".program-verify.yaml",
+        "rules:\n  title-vs-algorithm:\n    severity: warning\n",
+    );
+    let args = ["--schema", "schema.json", "bad.yml"];
+    let run = scratch.run(&[&args[..], &["--format", "json"]].concat());
+    let last = run.stdout.lines().last().unwrap();
+    let provenance = &serde_json::from_str::<JsonValue>(last).unwrap()["provenance"];
+    let version = env!("CARGO_PKG_VERSION");
+    assert_eq!(
+        provenance["tool"],
+        json!({ "name": "program-verify", "version": version })
+    );
+    assert!(provenance["config"]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with(".program-verify.yaml"));
+    assert_eq!(provenance["config"]["sha256"].as_str().unwrap().len(), 64);
+    assert_eq!(provenance["schemas"][0]["origin"], "schema.json");
+    assert_eq!(provenance["libraries"], json!([]));
+    assert_eq!(
+        provenance["rules"][0],
+        json!({
+            "builtin": true,
+            "enabled": true,
+            "id": "PV00",
+            "name": "title-vs-algorithm",
+            "severity": "warning",
+            "version": version
+        })
+    );
+
+    // The schema hash is taken over canonical JSON.
+    scratch.write(
+        "schema.json",
+        r#"{"properties":{"meta":{"properties":{"title":{"maxLength":3,"type":"string"}}}}}"#,
+    );
+    let run = scratch.run(&[&args[..], &["--format", "sarif"]].concat());
+    let log: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(
+        log["runs"][0]["properties"]["provenance"]["schemas"],
+        provenance["schemas"]
+    );
+}
@@ -0,0 +1,75 @@
+use crate::support::Scratch;
+
+fn check(policy: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: A, version: v1}}\nalgorithm: {{name: A, phases: [fetch]}}\n\
+             implementation:\n  phase_contracts:\n    fetch:\n      retry_policy: {policy}\n"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_malformed_retry_policies() {
+    for (policy, finding) in [
+        (
+            "{max_attempts: 0}",
+            "[PV180]: Phase 'fetch' retry_policy.max_attempts is 0; expected an integer of at \
+             least 1\n --> spec.yml:6:22",
+        ),
+        (
+            "{backoff: {strategy: random, initial_delay_ms: 10}}",
+            "[PV181]: Phase 'fetch' retry backoff strategy \"random\" is unknown (expected one of: \
+             fixed, linear, exponential, jittered)\n --> spec.yml:6:32",
+        ),
+        (
+            "{backoff: {strategy: linear, initial_delay_ms: -5}}",
+            "[PV182]: Phase 'fetch' retry backoff initial_delay_ms is -5; expected a non-negative \
+             integer\n --> spec.yml:6:50",
+        ),
+        (
+            "{backoff: {strategy: exponential, initial_delay_ms: 5000, max_delay_ms: 100}}",
+            "[PV182]: Phase 'fetch' retry backoff starts at initial_delay_ms 5000, above its \
+             max_delay_ms 100",
+        ),
+        (
+            "{backoff: {strategy: exponential, initial_delay_ms: 100}}",
+            "[PV183]: Phase 'fetch' retry backoff strategy 'exponential' needs max_delay_ms",
+        ),
+        (
+            "{backoff: {strategy: exponential, min_seconds: 2, max_seconds: 30}}",
+            "[PV183]: Phase 'fetch' retry backoff strategy 'exponential' needs initial_delay_ms \
+             and max_delay_ms (min_seconds is now initial_delay_ms, in milliseconds; max_seconds \
+             is now max_delay_ms, in milliseconds)",
+        ),
+        (
+            "{backoff: {initial_delay_ms: 100}}",
+            "[PV183]: Phase 'fetch' retry backoff has no strategy",
+        ),
+    ] {
+        let run = check(policy);
+        assert_eq!(run.code, Some(1), "{policy}");
+        assert!(
+            run.reports(&format!("❌ Rule: retry policy {finding}")),
+            "{policy}: {}",
+            run.stderr
+        );
+    }
+}
+
+#[test]
+fn passes_well_formed_retry_policies() {
+    for policy in [
+        "{max_attempts: 1}",
+        "{max_attempts: 3, backoff: {strategy: fixed, initial_delay_ms: 0}}",
+        "{backoff: {strategy: jittered, initial_delay_ms: 10}}",
+        "{backoff: {strategy: exponential, initial_delay_ms: 100, max_delay_ms: 100}}",
+    ] {
+        let run = check(policy);
+        assert!(run.success(), "{policy}: {}", run.stderr);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A spec with the phases `collect` and `reply` and the given phase contracts.
+fn spec(contracts: JsonValue) -> JsonValue {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Support", "phases": ["collect", "reply"] },
+        "implementation": { "phase_contracts": contracts }
+    })
+}
+
+/// Like `spec`, for a v3 spec where every phase needs a contract.
+fn v3(contracts: JsonValue) -> JsonValue {
+    let mut doc = spec(contracts);
+    doc["spec_version"] = "v3".into();
+    doc
+}
+
+/// A `collect` phase with the given contract next to an empty `reply` contract. Phase
+/// references are only followed in contracts that list inputs.
+fn collect(mut contract: JsonValue) -> JsonValue {
+    if contract.get("inputs").is_none() {
+        contract["inputs"] = json!([]);
+    }
+    spec(json!({ "collect": contract, "reply": {} }))
+}
+
+/// A `reply` phase reading `collect.answer` through an input with the given fields.
+fn reply_input(input: JsonValue) -> JsonValue {
+    let mut input_port = json!({ "name": "answer" });
+    input_port
+        .as_object_mut()
+        .unwrap()
+        .extend(input.as_object().unwrap().clone());
+    spec(json!({
+        "collect": { "outputs": [{ "name": "answer", "data_classification": "pii" }] },
+        "reply": { "inputs": [input_port] }
+    }))
+}
+
+/// `(code, failing spec, passing spec)` for every check of a single document.
+fn cases() -> Vec<(&'static str, JsonValue, JsonValue)> {
+    let retried = |extra: JsonValue| {
+        let mut contract =
+            json!({ "side_effects": ["email"], "retry_policy": { "max_attempts": 2 } });
+        contract
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        collect(contract)
+    };
+    let source = |port: &str| json!({ "kind": "phase_output", "phase": "collect", "port": port });
+    vec![
+        (
+            "PV001",
+            json!({ "meta": { "title": "Support" }, "algorithm": { "name": "Billing" } }),
+            json!({ "meta": { "title": "Support" }, "algorithm": { "name": "Support" } }),
+        ),
+        (
+            "PV002",
+            json!({ "meta": {}, "algorithm": { "name": "Support" } }),
+            json!({ "meta": { "title": "Support" }, "algorithm": { "name": "Support" } }),
+        ),
+        (
+            "PV003",
+            json!({ "meta": { "title": "Support" }, "algorithm": {} }),
+            json!({ "meta": { "title": "Support" }, "algorithm": { "name": "Support" } }),
+        ),
+        (
+            "PV010",
+            spec(json!({ "ghost": {} })),
+            spec(json!({ "collect": {} })),
+        ),
+        (
+            "PV011",
+            {
+                let mut doc = v3(json!({}));
+                doc["implementation"] = json!({});
+                doc
+            },
+            v3(json!({ "collect": {}, "reply": {} })),
+        ),
+        (
+            "PV012",
+            v3(json!({ "collect": {} })),
+            v3(json!({ "collect": {}, "reply": {} })),
+        ),
+        (
+            "PV013",
+            collect(json!({ "outputs": [{ "name": "a" }, { "name": "a" }] })),
+            collect(json!({ "outputs": [{ "name": "a" }, { "name": "b" }] })),
+        ),
+        (
+            "PV014",
+            collect(json!({ "fallback": { "phase": "ghost" } })),
+            collect(json!({ "fallback": { "phase": "reply" } })),
+        ),
+        (
+            "PV015",
+            spec(json!({ "collect": { "inputs": [], "fallback": { "phase": "reply" } } })),
+            collect(json!({ "fallback": { "phase": "reply" } })),
+        ),
+        (
+            "PV016",
+            reply_input(json!({ "source": source("question"), "data_classification": "pii" })),
+            reply_input(json!({ "source": source("answer"), "data_classification": "pii" })),
+        ),
+        (
+            "PV017",
+            collect(json!({ "inputs": [{ "name": "ticket", "source": { "kind": "instance" } }] })),
+            collect(json!({
+                "inputs": [{ "name": "ticket", "source": { "kind": "instance", "path": "$.ticket" } }]
+            })),
+        ),
+        (
+            "PV018",
+            collect(json!({
+                "errors": [{ "code": "rate_limited" }],
+                "retry_policy": { "retryable_errors": ["timeout"] }
+            })),
+            collect(json!({
+                "errors": [{ "code": "timeout" }],
+                "retry_policy": { "retryable_errors": ["timeout"] }
+            })),
+        ),
+        (
+            "PV020",
+            reply_input(json!({ "source": source("answer") })),
+            reply_input(json!({ "source": source("answer"), "data_classification": "pii" })),
+        ),
+        (
+            "PV021",
+            collect(json!({ "data_classification": "secret" })),
+            collect(json!({ "data_classification": "pii" })),
+        ),
+        (
+            "PV030",
+            collect(json!({ "deterministic": "yes" })),
+            collect(json!({ "deterministic": true })),
+        ),
+        (
+            "PV031",
+            collect(json!({ "side_effects": "email" })),
+            collect(json!({ "side_effects": ["email"] })),
+        ),
+        (
+            "PV032",
+            collect(json!({ "idempotent": false })),
+            collect(json!({ "idempotent": false, "side_effects": ["email"] })),
+        ),
+        (
+            "PV033",
+            retried(json!({ "idempotent": false, "idempotency_key": "$.id" })),
+            retried(json!({ "idempotent": true, "idempotency_key": "$.id" })),
+        ),
+        (
+            "PV040",
+            retried(json!({})),
+            retried(json!({ "idempotency_key": "$.id" })),
+        ),
+        (
+            "PV041",
+            collect(json!({ "idempotency_key": 5 })),
+            collect(json!({ "idempotency_key": "$.id" })),
+        ),
+        (
+            "PV042",
+            collect(json!({ "idempotency_key": "order" })),
+            collect(json!({ "idempotency_key": "$.order" })),
+        ),
+        (
+            "PV050",
+            collect(json!({ "observability": { "spans": ["Collect"] } })),
+            collect(json!({ "observability": { "spans": ["collect"] } })),
+        ),
+        (
+            "PV051",
+            collect(json!({ "observability": { "metrics": [{ "type": "counter" }] } })),
+            collect(
+                json!({ "observability": { "metrics": [{ "name": "calls", "type": "counter" }] } }),
+            ),
+        ),
+        (
+            "PV052",
+            collect(
+                json!({ "observability": { "metrics": [{ "name": "calls", "type": "timer" }] } }),
+            ),
+            collect(
+                json!({ "observability": { "metrics": [{ "name": "calls", "type": "counter" }] } }),
+            ),
+        ),
+        (
+            "PV053",
+            collect(
+                json!({ "observability": { "metrics": [{ "name": "calls" }, { "name": "calls" }] } }),
+            ),
+            collect(
+                json!({ "observability": { "metrics": [{ "name": "calls" }, { "name": "errors" }] } }),
+            ),
+        ),
+        (
+            "PV054",
+            collect(json!({ "observability": { "log_events": ["received", "received"] } })),
+            collect(json!({ "observability": { "log_events": ["received", "answered"] } })),
+        ),
+        (
+            "PV055",
+            collect(json!({ "observability": { "alerts": [{ "metric": "calls" }] } })),
+            collect(json!({
+                "observability": { "metrics": [{ "name": "calls" }], "alerts": [{ "metric": "calls" }] }
+            })),
+        ),
+        (
+            "PV056",
+            collect(json!({
+                "observability": {
+                    "metrics": [{ "name": "calls" }],
+                    "alerts": [{ "metric": "calls", "threshold": "high" }]
+                }
+            })),
+            collect(json!({
+                "observability": {
+                    "metrics": [{ "name": "calls" }],
+                    "alerts": [{ "metric": "calls", "threshold": 100 }]
+                }
+            })),
+        ),
+    ]
+}
+
+#[test]
+fn every_check_reports_its_id() {
+    let scratch = Scratch::new();
+    for (code, failing, passing) in cases() {
+        let tag = format!("[{code}]");
+        let run = scratch.check(&failing);
+        assert!(
+            run.reports(&tag),
+            "{code} not reported for {failing}: {}",
+            run.stderr
+        );
+        let run = scratch.check(&passing);
+        assert!(
+            !run.reports(&tag),
+            "{code} reported for {passing}: {}",
+            run.stderr
+        );
+    }
+}
+
+#[test]
+fn prints_the_id_next_to_the_rule_label() {
+    let run = Scratch::new().check(&cases()[0].1);
+    assert!(run.reports("❌ Rule: meta.title vs algorithm.name [PV001]: algorithm.name='Billing'"));
+}
+
+#[test]
+fn select_and_ignore_filter_by_prefix() {
+    let scratch = Scratch::new();
+    // PV001 and PV021 at once.
+    let mut doc = collect(json!({ "data_classification": "secret" }));
+    doc["algorithm"]["name"] = "Billing".into();
+    scratch.check(&doc);
+    let run = |filters: &[&str]| {
+        let mut args = filters.to_vec();
+        args.extend(["--schema", "open-schema.json", "spec.yml"]);
+        scratch.run(&args)
+    };
+
+    let found = run(&["--select", "PV02"]);
+    assert!(!found.success());
+    assert!(found.reports("[PV021]") && !found.reports("[PV001]"));
+
+    let found = run(&["--ignore", "PV001,PV02"]);
+    assert!(found.success(), "{}", found.stderr);
+
+    let found = run(&["--select", "PV0", "--ignore", "PV021"]);
+    assert!(found.reports("[PV001]") && !found.reports("[PV021]"));
+}
@@ -0,0 +1,55 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+/// Two specs with a PV001 finding each; `b.yml` also has a PV010 warning.
+fn corpus() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    let a = json!({ "meta": { "title": "A" }, "algorithm": { "name": "B" } });
+    let b = json!({
+        "meta": { "title": "A" },
+        "algorithm": { "name": "C", "phases": ["x"] },
+        "implementation": { "phase_contracts": { "y": {} } }
+    });
+    scratch.write("specs/a.yml", &a.to_string());
+    scratch.write("specs/b.yml", &b.to_string());
+    scratch
+}
+
+#[test]
+fn counts_findings_per_rule_id() {
+    let run = corpus().run(&["--schema", "open-schema.json", "report", "rules", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    for line in [
+        "── 2 spec(s), 3 finding(s) from 2 rule ID(s) ──",
+        "      2      2       2         0  PV001 algorithm.name does not match the algorithm identity in meta.title",
+        "      1         specs/a.yml",
+        "      1      1       0         1  PV010 phase_contracts entry for a phase",
+        "  never fired: PV002, PV003, ",
+    ] {
+        assert!(run.stdout.contains(line), "{line:?} missing from\n{}", run.stdout);
+    }
+    // Individual findings are not printed.
+    assert!(!run.reports("does not match the base of meta.title='A'"));
+}
+
+#[test]
+fn top_and_filters_apply() {
+    let scratch = corpus();
+    let run = scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "--ignore",
+        "PV010",
+        "report",
+        "rules",
+        "specs",
+        "--top",
+        "1",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .contains("── 2 spec(s), 2 finding(s) from 1 rule ID(s) ──"));
+    assert!(run.stdout.contains("specs/a.yml") && !run.stdout.contains("specs/b.yml"));
+}
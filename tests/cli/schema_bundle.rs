@@ -0,0 +1,66 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+use std::fs;
+
+#[test]
+fn inlines_every_ref() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "schemas/root.json",
+        r##"{
+            "definitions": {"name": {"type": "string"}},
+            "properties": {
+                "title": {"$ref": "#/definitions/name"},
+                "meta": {"$ref": "meta.yaml", "description": "Metadata."}
+            }
+        }"##,
+    );
+    scratch.write("schemas/meta.yaml", "type: object\nrequired: [title]\n");
+
+    let run = scratch.run(&[
+        "--schema",
+        "schemas/root.json",
+        "schema",
+        "bundle",
+        "-o",
+        "bundle.json",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    let bundle: JsonValue =
+        serde_json::from_str(&fs::read_to_string(scratch.path("bundle.json")).unwrap()).unwrap();
+    assert_eq!(bundle["properties"]["title"], json!({ "type": "string" }));
+    assert_eq!(
+        bundle["properties"]["meta"],
+        json!({ "description": "Metadata.", "allOf": [{ "type": "object", "required": ["title"] }] })
+    );
+    assert!(!bundle.to_string().contains("$ref"));
+
+    // The bundle validates like the schema it came from.
+    scratch.write("spec.yml", "title: A\nmeta: {}\n");
+    let run = scratch.run(&["--schema", "bundle.json", "spec.yml"]);
+    assert!(
+        run.reports("\"title\" is a required property"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[test]
+fn reports_circular_refs() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "tree.json",
+        r##"{
+            "definitions": {
+                "node": {"properties": {"child": {"$ref": "#/definitions/wrap"}}},
+                "wrap": {"$ref": "#/definitions/node"}
+            },
+            "$ref": "#/definitions/node"
+        }"##,
+    );
+    let run = scratch.run(&["--schema", "tree.json", "schema", "bundle"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "Error: circular $ref cannot be bundled: #/definitions/node → #/definitions/wrap → #/definitions/node"
+    ), "{}", run.stderr);
+}
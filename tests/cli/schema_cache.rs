@@ -0,0 +1,37 @@
+use crate::support::Scratch;
+use std::fs;
+
+fn cached(scratch: &Scratch) -> usize {
+    fs::read_dir(scratch.path(".cache/program-verify/schemas"))
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+#[test]
+fn caches_parsed_schemas_until_cleared() {
+    let scratch = Scratch::new();
+    scratch.write("schema.yaml", "properties:\n  meta:\n    type: object\n");
+    scratch.write("spec.yml", "meta:\n  title: A\nalgorithm:\n  name: A\n");
+
+    let run = scratch.run(&["--no-cache", "--schema", "schema.yaml", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(cached(&scratch), 0);
+
+    let run = scratch.run(&["--schema", "schema.yaml", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(cached(&scratch), 1);
+    let run = scratch.run(&["--schema", "schema.yaml", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(cached(&scratch), 1);
+
+    // An edited schema gets an entry of its own, and is not answered from the old one.
+    scratch.write("schema.yaml", "properties:\n  meta:\n    type: string\n");
+    let run = scratch.run(&["--schema", "schema.yaml", "spec.yml"]);
+    assert!(!run.success());
+    assert_eq!(cached(&scratch), 2);
+
+    let run = scratch.run(&["cache", "clear"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.contains("🧹 Removed 2 cached file(s)."));
+    assert_eq!(cached(&scratch), 0);
+}
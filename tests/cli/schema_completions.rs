@@ -0,0 +1,61 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+const SCHEMA: &str = r#"{
+    "type": "object",
+    "required": ["meta"],
+    "properties": {
+        "meta": {
+            "type": "object",
+            "title": "Meta",
+            "required": ["title"],
+            "properties": {
+                "title": {"type": "string", "description": "Name."},
+                "kind": {"enum": ["a", "b"]}
+            },
+            "additionalProperties": {"type": "integer"}
+        },
+        "tags": {"type": "array", "items": {"type": "string"}}
+    }
+}"#;
+
+#[test]
+fn describes_every_field_path() {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    let run = scratch.run(&["--schema", "schema.json", "schema", "completions"]);
+    assert!(run.success(), "{}", run.stderr);
+    let model: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    let paths = &model["paths"];
+    assert_eq!(paths[""]["required"], json!(["meta"]));
+    assert_eq!(
+        paths["meta"],
+        json!({
+            "keys": ["kind", "title"],
+            "other_keys": true,
+            "required": ["title"],
+            "title": "Meta",
+            "types": ["object"]
+        })
+    );
+    assert_eq!(paths["meta.*"]["types"], json!(["integer"]));
+    assert_eq!(paths["meta.kind"]["values"], json!(["a", "b"]));
+    assert_eq!(paths["meta.title"]["description"], "Name.");
+    assert_eq!(paths["tags[]"]["types"], json!(["string"]));
+}
+
+#[test]
+fn looks_versions_up_in_the_version_map() {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    scratch.write("version_map.yaml", "v2: schema.json\n");
+    let run = scratch.run(&["schema", "completions", "v2"]);
+    assert!(run.success(), "{}", run.stderr);
+    let model: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(model["spec_version"], "v2");
+    assert_eq!(model["paths"]["meta.kind"]["values"], json!(["a", "b"]));
+
+    let run = scratch.run(&["schema", "completions", "v9"]);
+    assert!(!run.success());
+    assert_eq!(run.stdout, "");
+}
@@ -0,0 +1,81 @@
+use crate::support::Scratch;
+
+const SCHEMA: &str = r##"{
+    "properties": {
+        "meta": {
+            "properties": {
+                "title": {"type": "string"},
+                "kind": {"enum": ["a", "b"]}
+            }
+        },
+        "tags": {"type": "array"},
+        "owner": {"$ref": "#/definitions/owner"}
+    },
+    "definitions": {
+        "owner": {"oneOf": [{"type": "string"}, {"type": "integer"}]}
+    }
+}"##;
+
+#[test]
+fn lists_regions_no_spec_exercises() {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    scratch.write("specs/a.yml", "meta:\n  title: A\n  kind: b\nowner: team\n");
+    scratch.write("specs/b.yml", "meta:\n  title: B\n");
+
+    let run = scratch.run(&["--schema", "schema.json", "schema", "coverage", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stdout,
+        "\
+── (no spec_version) — 2 spec(s), schema.json ──
+  properties:           4 of 5    exercised (80%)
+  enum values:          1 of 2    exercised (50%)
+  oneOf/anyOf arms:     1 of 2    exercised (50%)
+  Not exercised:
+    #/definitions/owner/oneOf/1 (arm of oneOf)
+    #/properties/meta/properties/kind/enum/0 (enum value \"a\")
+    #/properties/tags (property)
+"
+    );
+}
+
+#[test]
+fn full_coverage_lists_nothing() {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", r#"{"properties": {"meta": {"enum": [1]}}}"#);
+    scratch.write("spec.yml", "meta: 1\n");
+    let run = scratch.run(&["--schema", "schema.json", "schema", "coverage", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.contains("1 of 1    exercised (100%)"));
+    assert!(!run.stdout.contains("Not exercised"));
+}
+
+#[test]
+fn counts_arms_outside_definitions() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "schema.json",
+        r##"{"properties": {"priority": {"anyOf": [
+            {"type": "integer"},
+            {"enum": ["high", "low"]},
+            {"$ref": "#/definitions/level"}
+        ]}}, "definitions": {"level": {"type": "object"}}}"##,
+    );
+    scratch.write("specs/a.yml", "meta: {title: A}\npriority: high\n");
+    scratch.write("specs/b.yml", "meta: {title: B}\npriority: {level: 2}\n");
+    let run = scratch.run(&["--schema", "schema.json", "schema", "coverage", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.stdout
+            .contains("  oneOf/anyOf arms:     2 of 3    exercised (66%)\n"),
+        "{}",
+        run.stdout
+    );
+    assert!(run
+        .stdout
+        .contains("    #/properties/priority/anyOf/0 (arm of anyOf)\n"));
+    assert!(run
+        .stdout
+        .contains("    #/properties/priority/anyOf/1/enum/1 (enum value \"low\")\n"));
+}
@@ -0,0 +1,67 @@
+use crate::support::Scratch;
+
+const SCHEMA: &str = r#"{
+    "type": "object",
+    "required": ["meta"],
+    "properties": {
+        "meta": {
+            "type": "object",
+            "title": "Meta",
+            "required": ["title"],
+            "properties": {
+                "title": {"type": "string", "description": "Name."},
+                "kind": {"enum": ["a", "b"]}
+            }
+        }
+    }
+}"#;
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("v1.json", SCHEMA);
+    scratch.write(
+        "v2.json",
+        r#"{"properties": {"meta": {"description": "Version 2 metadata."}}}"#,
+    );
+    scratch.write("version_map.yaml", "v1: v1.json\nv2: v2.json\n");
+    scratch.write(
+        "spec.yml",
+        "spec_version: v1\nmeta:\n  title: A\n  kind: b\n---\nspec_version: v2\nmeta: {}\n",
+    );
+    scratch
+}
+
+#[test]
+fn describes_the_node_at_a_pointer() {
+    let scratch = workspace();
+    for (pointer, expected) in [
+        (
+            "/meta",
+            "Meta\n  required: yes\n  type: object\n  required keys: title\n",
+        ),
+        ("/meta/title", "Name.\n  required: yes\n  type: string\n"),
+        ("/meta/kind", "  required: no\n  enum: a, b\n"),
+    ] {
+        let run = scratch.run(&["schema", "hover", "spec.yml", pointer]);
+        assert!(run.success(), "{}", run.stderr);
+        assert_eq!(run.stdout, expected, "{pointer}");
+    }
+}
+
+#[test]
+fn picks_the_schema_of_the_chosen_document() {
+    let run = workspace().run(&["schema", "hover", "--document", "2", "spec.yml", "/meta"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.stdout.starts_with("Version 2 metadata.\n"),
+        "{}",
+        run.stdout
+    );
+}
+
+#[test]
+fn fails_for_undescribed_nodes() {
+    let run = workspace().run(&["schema", "hover", "spec.yml", "/nope"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("Error: the schema does not describe '/nope'"));
+}
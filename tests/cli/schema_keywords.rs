@@ -0,0 +1,59 @@
+use crate::support::Scratch;
+
+const SCHEMA: &str = r#"{
+    "properties": {
+        "algorithm": {
+            "x-max-phase-count": 2,
+            "properties": {
+                "outputs": {"x-unique-items-by": "name"}
+            }
+        }
+    }
+}"#;
+
+fn validate(scratch: &Scratch, spec: &str) -> crate::support::Run {
+    scratch.write("schema.json", SCHEMA);
+    scratch.write("spec.yml", &format!("meta: {{title: Support}}\n{spec}"));
+    scratch.run(&["--schema", "schema.json", "spec.yml"])
+}
+
+#[test]
+fn unique_items_by() {
+    let scratch = Scratch::new();
+    let run = validate(
+        &scratch,
+        "algorithm:\n  name: Support\n  phases: [a]\n  outputs: [{name: x}, {name: y}, {other: 1}, {other: 2}]\n",
+    );
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+
+    let run = validate(
+        &scratch,
+        "algorithm:\n  name: Support\n  phases: [a]\n  outputs: [{name: x}, {name: y}, {name: x}]\n",
+    );
+    assert!(!run.success());
+    assert!(
+        run.reports(
+            "Items 0 and 2 have the same name \"x\" (instance: /algorithm/outputs, schema: \
+             /properties/algorithm/properties/outputs/x-unique-items-by)"
+        ),
+        "{}{}",
+        run.stdout,
+        run.stderr
+    );
+}
+
+#[test]
+fn max_phase_count() {
+    let scratch = Scratch::new();
+    let run = validate(&scratch, "algorithm:\n  name: Support\n  phases: [a, b]\n");
+    assert!(run.success(), "{}{}", run.stdout, run.stderr);
+
+    let run = validate(&scratch, "algorithm:\n  name: Support\n  phases: [a, b, c]\n");
+    assert!(!run.success());
+    assert!(
+        run.reports("3 phases declared, more than the 2 allowed by x-max-phase-count"),
+        "{}{}",
+        run.stdout,
+        run.stderr
+    );
+}
@@ -0,0 +1,55 @@
+use crate::support::{Scratch, Server};
+
+const SPEC: &str = "meta:\n  title: Support\nalgorithm:\n  name: Support\n";
+
+#[test]
+fn resolves_refs_relative_to_the_referencing_file() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "schemas/root.json",
+        r#"{"properties": {"meta": {"$ref": "defs/meta.yaml#/definitions/meta"}}}"#,
+    );
+    scratch.write(
+        "schemas/defs/meta.yaml",
+        "definitions:\n  meta:\n    properties:\n      title:\n        $ref: ../common.json\n",
+    );
+    scratch.write(
+        "schemas/common.json",
+        r#"{"type": "string", "maxLength": 3}"#,
+    );
+    scratch.write("spec.yml", SPEC);
+
+    let run = scratch.run(&["--schema", "schemas/root.json", "spec.yml"]);
+    assert!(!run.success());
+    assert!(
+        run.reports("\"Support\" is longer than 3 characters"),
+        "{}",
+        run.stderr
+    );
+
+    scratch.write("schemas/common.json", r#"{"type": "string"}"#);
+    let run = scratch.run(&["--schema", "schemas/root.json", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn resolves_refs_relative_to_a_schema_url() {
+    let server = Server::new(r#"{"type": "integer"}"#);
+    let scratch = Scratch::new();
+    scratch.write(
+        "root.json",
+        &format!(
+            r#"{{"$id": "{}/schemas/root.json", "properties": {{"meta": {{"properties": {{"title": {{"$ref": "title.json"}}}}}}}}}}"#,
+            server.url
+        ),
+    );
+    scratch.write("spec.yml", SPEC);
+
+    let run = scratch.run(&["--schema", "root.json", "spec.yml"]);
+    assert!(
+        run.reports("\"Support\" is not of type \"integer\""),
+        "{}",
+        run.stderr
+    );
+    assert_eq!(server.hits(), 1);
+}
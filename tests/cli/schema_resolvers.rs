@@ -0,0 +1,59 @@
+use crate::support::{Scratch, Server};
+use serde_json::json;
+
+const STRICT_SCHEMA: &str =
+    r#"{"properties": {"meta": {"properties": {"title": {"type": "integer"}}}}}"#;
+
+fn spec(version: Option<&str>) -> String {
+    let mut doc = json!({ "meta": { "title": "Support" }, "algorithm": { "name": "Support" } });
+    if let Some(version) = version {
+        doc["spec_version"] = version.into();
+    }
+    doc.to_string()
+}
+
+#[test]
+fn fetches_a_schema_url_once_per_run() {
+    let server = Server::new(STRICT_SCHEMA);
+    let scratch = Scratch::new();
+    scratch.write("a.yml", &spec(None));
+    scratch.write("b.yml", &spec(None));
+    let url = format!("{}/schema.json", server.url);
+
+    let run = scratch.run(&["--schema", &url, "a.yml", "b.yml"]);
+    assert!(!run.success());
+    assert_eq!(
+        run.stderr.matches("is not of type \"integer\"").count(),
+        2,
+        "{}",
+        run.stderr
+    );
+    assert_eq!(server.hits(), 1);
+}
+
+#[test]
+fn version_map_entries_may_be_urls() {
+    let server = Server::new(STRICT_SCHEMA);
+    let scratch = Scratch::new();
+    scratch.write(
+        "version_map.yaml",
+        &format!("v9: {}/v9.json\nv8: v8.json\n", server.url),
+    );
+    scratch.write("v8.json", "{}");
+    scratch.write("remote.yml", &spec(Some("v9")));
+    scratch.write("local.yml", &spec(Some("v8")));
+
+    let run = scratch.run(&["remote.yml"]);
+    assert!(run.reports("is not of type \"integer\""), "{}", run.stderr);
+    let run = scratch.run(&["local.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_unreachable_schemas() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", &spec(None));
+    let run = scratch.run(&["--schema", "missing.json", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("missing.json"), "{}", run.stderr);
+}
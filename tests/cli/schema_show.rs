@@ -0,0 +1,53 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A workspace whose version map points `v2` to a schema with a local `$ref`, and a v2 spec.
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("version_map.yaml", "v2: schemas/v2.json\n");
+    scratch.write(
+        "schemas/v2.json",
+        r##"{"definitions": {"t": {"type": "string"}}, "properties": {"title": {"$ref": "#/definitions/t"}}}"##,
+    );
+    scratch.write("spec.yml", "spec_version: v2\ntitle: a\n");
+    scratch
+}
+
+#[test]
+fn prints_the_schema_a_spec_selects() {
+    let scratch = workspace();
+    let run = scratch.run(&["schema", "show", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    let schema: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(schema["properties"]["title"]["$ref"], "#/definitions/t");
+    assert!(run
+        .stderr
+        .ends_with("schemas/v2.json (selected by spec_version v2 of spec.yml)\n"));
+
+    let run = scratch.run(&["schema", "show", "--bundle", "--yaml", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(run.stdout, "properties:\n  title:\n    type: string\n");
+
+    let run = scratch.run(&["--spec-version", "v2", "schema", "show"]);
+    assert!(run.reports("(selected by --spec-version v2)"));
+}
+
+#[test]
+fn falls_back_to_the_embedded_schema() {
+    let run = workspace().run(&["schema", "show"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.stderr,
+        "ℹ️ embedded schema (selected by no spec_version)\n"
+    );
+    let schema: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(schema["required"], json!(["meta", "algorithm", "implementation"]));
+}
+
+#[test]
+fn rejects_an_unknown_version() {
+    let run = workspace().run(&["--spec-version", "v7", "schema", "show"]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("version 'v7' was not found in"));
+    assert_eq!(run.stdout, "");
+}
@@ -0,0 +1,66 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+#[test]
+fn counts_optional_fields_per_version() {
+    let scratch = Scratch::new();
+    let schema = json!({
+        "type": "object",
+        "required": ["meta"],
+        "properties": {
+            "meta": {
+                "type": "object",
+                "properties": { "title": { "type": "string" }, "tags": { "type": "array" } }
+            },
+            "implementation": {
+                "type": "object",
+                "properties": {
+                    "phase_contracts": {
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": "object",
+                            "properties": { "retry_policy": { "type": "object" } }
+                        }
+                    }
+                }
+            }
+        }
+    });
+    scratch.write("schema.json", &schema.to_string());
+    scratch.write("version_map.yaml", "v1: schema.json\n");
+    let contracts = json!({ "a": { "retry_policy": {} }, "b": { "retry_policy": {} } });
+    scratch.write(
+        "specs/a.yml",
+        &json!({ "spec_version": "v1", "meta": { "title": "A" }, "implementation": { "phase_contracts": contracts } })
+            .to_string(),
+    );
+    scratch.write(
+        "specs/b.yml",
+        &json!({ "spec_version": "v1", "meta": { "title": "B" } }).to_string(),
+    );
+
+    let run = scratch.run(&["report", "schema-usage", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stdout.contains("── v1 — 2 spec(s), "));
+    for row in [
+        "      2      2  meta.title",
+        "      1      2  implementation.phase_contracts.*.retry_policy",
+        "      1      1  implementation.phase_contracts",
+        "      0      0  meta.tags",
+        "  1 of 5 optional field(s) unused",
+    ] {
+        assert!(
+            run.stdout.contains(row),
+            "{row:?} missing from\n{}",
+            run.stdout
+        );
+    }
+}
+
+#[test]
+fn fails_without_specs() {
+    let scratch = Scratch::new();
+    scratch.write("specs/notes.md", "");
+    let run = scratch.run(&["report", "schema-usage", "specs"]);
+    assert!(!run.success());
+}
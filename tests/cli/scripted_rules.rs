@@ -0,0 +1,111 @@
+use crate::support::Scratch;
+
+const OWNERS: &str = r#"
+const ID = "ACME01";
+const CODES = #{ ACME010: "phase without an owner" };
+const SEVERITY = "warning";
+
+fn check(doc) {
+    let findings = [];
+    for name in doc.implementation.phase_contracts.keys() {
+        if !("owner" in doc.implementation.phase_contracts[name]) {
+            findings.push(#{ code: "ACME010", message: `phase ${name} has no owner`,
+                             at: `/implementation/phase_contracts/${name}` });
+        }
+    }
+    findings
+}
+"#;
+
+fn workspace(script: &str) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(".program-verify.yaml", "schema: open-schema.json\n");
+    scratch.write("open-schema.json", "{}");
+    scratch.write("rules/owners.rhai", script);
+    scratch.write(
+        "spec.yml",
+        "meta: {title: Support, version: v1}\nalgorithm: {name: Support, phases: [triage, reply]}\n\
+         implementation:\n  phase_contracts:\n    triage: {owner: support}\n    reply: {}\n",
+    );
+    scratch
+}
+
+#[test]
+fn runs_scripts_from_the_rules_directory() {
+    let scratch = workspace(OWNERS);
+    let run = scratch.run(&["spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("[ACME010]"), "{}", run.stdout);
+    assert!(run.reports("phase reply has no owner"));
+    assert!(!run.reports("phase triage has no owner"));
+
+    let run = scratch.run(&["--format", "json", "spec.yml"]);
+    assert!(run
+        .stdout
+        .contains(r#""pointer":"/implementation/phase_contracts/reply""#));
+
+    let run = scratch.run(&["report", "rules", "spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("ACME01"), "{}", run.stdout);
+
+    scratch.write(
+        ".program-verify.yaml",
+        "schema: open-schema.json\nrules:\n  owners:\n    enabled: false\n",
+    );
+    let run = scratch.run(&["spec.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("ACME010"));
+}
+
+#[test]
+fn reports_failing_scripts_under_their_id() {
+    for (body, message) in [
+        (
+            "throw \"boom\";",
+            "rule script owners failed: Runtime error: boom",
+        ),
+        (
+            "42",
+            "check(doc) must return an array of #{code, message, at}",
+        ),
+        (
+            "[#{ code: \"ACME019\", message: \"?\" }]",
+            "rule script owners reported ACME019, which its CODES do not declare",
+        ),
+    ] {
+        let scratch = workspace(&format!(
+            "const ID = \"ACME01\";\nconst CODES = #{{ ACME010: \"x\" }};\nfn check(doc) {{ {body} }}\n"
+        ));
+        let run = scratch.run(&["spec.yml"]);
+        assert_eq!(run.code, Some(1), "{body}");
+        assert!(
+            run.reports("❌ Rule: owners [ACME01]: "),
+            "{body}: {}",
+            run.stderr
+        );
+        assert!(run.reports(message), "{body}: {}", run.stderr);
+    }
+}
+
+#[test]
+fn rejects_invalid_scripts() {
+    for (script, error) in [
+        (
+            "const ID = \"ACME01\";\nconst CODES = #{};\n",
+            "it does not define check(doc)",
+        ),
+        (
+            "fn check(doc) { [] }\n",
+            "(it must declare const ID and const CODES)",
+        ),
+        (
+            "const ID = \"ACME01\";\nconst CODES = #{ OTHER1: \"x\" };\nfn check(doc) { [] }\n",
+            "OTHER1 does not start with the rule ID ACME01",
+        ),
+        ("fn check(doc) {", "Error: invalid rule script "),
+    ] {
+        let run = workspace(script).run(&["spec.yml"]);
+        assert!(!run.success(), "{script}");
+        assert!(run.reports(error), "{script}: {}", run.stderr);
+    }
+}
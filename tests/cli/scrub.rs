@@ -0,0 +1,103 @@
+use crate::support::Scratch;
+use serde_json::Value as JsonValue;
+
+const SCHEMA: &str = r#"{"properties": {"meta": {"properties": {
+    "title": {"type": "string"},
+    "level": {"enum": ["low", "high"]},
+    "count": {"type": "integer", "minimum": 2},
+    "label": {"type": "string", "pattern": "^[A-Z]+$"}
+}}}}"#;
+
+const SPEC: &str = "\
+meta:
+  title: acme billing 42
+  level: high
+  count: 9
+  label: ACME
+implementation:
+  phase_contracts:
+    charge:
+      enabled: true
+      inputs:
+        - name: acme
+";
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("schema.json", SCHEMA);
+    scratch.write("spec.yml", SPEC);
+    scratch
+}
+
+#[test]
+fn replaces_values_at_sensitive_paths() {
+    let scratch = workspace();
+    let run = scratch.run(&[
+        "--schema",
+        "schema.json",
+        "scrub",
+        "spec.yml",
+        "--path",
+        "/meta/**",
+        "--path",
+        "/implementation/phase_contracts/*/inputs/**",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    let scrubbed: JsonValue = serde_yaml::from_str(&run.stdout).unwrap();
+    let meta = &scrubbed["meta"];
+
+    let title: Vec<&str> = meta["title"].as_str().unwrap().split(' ').collect();
+    assert!(title[0].starts_with("redacted_") && title[1].starts_with("redacted_"));
+    assert!(title[2].len() == 2 && title[2].chars().all(|c| c.is_ascii_digit()));
+    let contract = &scrubbed["implementation"]["phase_contracts"]["charge"];
+    // Undeclared keys under the pattern are replaced too; the same word gets the same placeholder.
+    let input = contract["inputs"][0].as_object().unwrap();
+    assert_eq!(input.values().next().unwrap(), title[0]);
+    assert_eq!(contract["enabled"], true);
+    assert_eq!(meta["level"], "high");
+    assert_eq!(meta["count"], 2);
+    assert!(!run.stdout.contains("acme") && !run.stdout.contains("billing"));
+}
+
+#[test]
+fn map_keys_and_config_paths() {
+    let scratch = workspace();
+    scratch.write(
+        ".program-verify.yaml",
+        "scrub_paths: [/implementation/phase_contracts/*]\n",
+    );
+    scratch.run(&[
+        "--schema",
+        "schema.json",
+        "scrub",
+        "spec.yml",
+        "-o",
+        "scrubbed.yml",
+    ]);
+    let scrubbed: JsonValue =
+        serde_yaml::from_str(&std::fs::read_to_string(scratch.path("scrubbed.yml")).unwrap())
+            .unwrap();
+    let contracts = scrubbed["implementation"]["phase_contracts"]
+        .as_object()
+        .unwrap();
+    assert!(
+        contracts.keys().all(|name| name.starts_with("redacted_")),
+        "{contracts:?}"
+    );
+    assert_eq!(scrubbed["meta"]["title"], "acme billing 42");
+}
+
+#[test]
+fn warns_when_the_scrubbed_spec_breaks_the_schema() {
+    let run = workspace().run(&[
+        "--schema",
+        "schema.json",
+        "scrub",
+        "spec.yml",
+        "--path",
+        "/meta/label",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ Document 1 no longer passes schema validation after scrubbing:"));
+    assert!(run.reports("does not match \"^[A-Z]+$\" (instance: /meta/label)"));
+}
@@ -0,0 +1,490 @@
+use crate::support::{Running, Scratch};
+use serde_json::{json, Value as JsonValue};
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+/// Starts `serve` on a free port with `args` before the subcommand and `options` after it; returns
+/// the process and the address it listens on.
+fn serve(scratch: &Scratch, args: &[&str], options: &[&str]) -> (Running, String) {
+    let mut running = scratch.spawn(&[args, &["serve", "--port", "0"], options].concat());
+    let line = running.wait_for("Listening on http://");
+    let address = line
+        .split("http://")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap()
+        .to_string();
+    (running, address)
+}
+
+/// Starts `serve` with `args` on a port picked up front, for runs that do not print the address.
+fn serve_silently(scratch: &Scratch, args: &[&str]) -> (Running, String) {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+        .to_string();
+    let running = scratch.spawn(&[args, &["serve", "--port", &port]].concat());
+    let address = format!("127.0.0.1:{port}");
+    for _ in 0..200 {
+        if TcpStream::connect(&address).is_ok() {
+            return (running, address);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    panic!("the server never listened on {address}");
+}
+
+/// Sends `raw` as the whole request and returns the status and the body of the response.
+fn send(address: &str, raw: &[u8]) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(raw).unwrap();
+    stream.shutdown(Shutdown::Write).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+    (status, body)
+}
+
+/// POSTs `body` to `target` with the given extra header lines.
+fn post(address: &str, target: &str, headers: &str, body: &str) -> (u16, JsonValue) {
+    let raw = format!(
+        "POST {target} HTTP/1.1\r\nHost: localhost\r\n{headers}Content-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    let (status, body) = send(address, raw.as_bytes());
+    (status, serde_json::from_str(&body).unwrap())
+}
+
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch
+}
+
+#[test]
+fn validates_posted_specs() {
+    let scratch = workspace();
+    let (_server, address) = serve(&scratch, &["--schema", "open-schema.json"], &[]);
+
+    let (status, response) = post(
+        &address,
+        "/validate?name=specs%2Fsupport.yml",
+        "",
+        "meta: {title: A, version: v1}\nalgorithm: {name: B, phases: [x]}\n",
+    );
+    assert_eq!(status, 200);
+    assert_eq!(
+        response,
+        json!({
+            "cached": false,
+            "documents": 1,
+            "errors": [],
+            "findings": [{
+                "code": "PV001",
+                "column": 13,
+                "file": "specs/support.yml",
+                "line": 2,
+                "message": "algorithm.name='B' does not match the base of meta.title='A' (detected 'A')",
+                "pointer": "/algorithm/name",
+                "rule": "meta.title vs algorithm.name",
+                "severity": "error"
+            }],
+            "valid": false
+        })
+    );
+
+    let spec =
+        r#"{"meta": {"title": "A", "version": "v1"}, "algorithm": {"name": "A", "phases": ["x"]}}"#;
+    // Both name the JSON format, so the second is answered from the cache.
+    for (target, headers, cached) in [
+        ("/validate", "Content-Type: Application/JSON\r\n", false),
+        ("/validate?format=json", "", true),
+    ] {
+        let (status, response) = post(&address, target, headers, spec);
+        assert_eq!(status, 200);
+        assert_eq!(
+            response,
+            json!({ "cached": cached, "documents": 1, "errors": [], "findings": [], "valid": true })
+        );
+    }
+}
+
+#[test]
+fn reports_errors_whatever_the_output_settings() {
+    let scratch = workspace();
+    scratch.write("version_map.yaml", "v1.0.0: open-schema.json\n");
+    let spec = "spec_version: v9.0.0\nmeta: {title: A, version: v1}\nalgorithm: {name: A}\n";
+    for args in [&["--color", "always"][..], &["-q"], &["--format", "json"]] {
+        let (_server, address) = serve_silently(&scratch, args);
+        let (status, response) = post(&address, "/validate", "", spec);
+        assert_eq!(status, 200, "{args:?}");
+        assert_eq!(response["valid"], false, "{args:?}");
+        let errors = response["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1, "{args:?}: {response}");
+        assert_eq!(
+            errors[0],
+            "Error: version 'v9.0.0' was not found in version_map.yaml.\nAvailable versions: v1.0.0",
+            "{args:?}"
+        );
+    }
+}
+
+#[test]
+fn answers_health_and_metrics() {
+    let scratch = workspace();
+    let (_server, address) = serve(&scratch, &["--schema", "open-schema.json"], &[]);
+    assert_eq!(
+        send(&address, b"GET /health HTTP/1.1\n\n"),
+        (200, "{\"status\":\"ok\",\"tenants\":[]}".to_string())
+    );
+    let (status, metrics) = send(&address, b"GET /metrics HTTP/1.1\r\n\r\n");
+    assert_eq!(status, 200);
+    assert!(metrics.contains("# TYPE program_verify_cache_hits_total counter\n"));
+    assert!(metrics.contains("program_verify_cache_hits_total{cache=\"compiled schemas\"} "));
+    assert_eq!(send(&address, b"GET /nope HTTP/1.1\r\n\r\n").0, 404);
+    assert_eq!(send(&address, b"PUT /validate HTTP/1.1\r\n\r\n").0, 405);
+}
+
+#[test]
+fn rejects_malformed_requests() {
+    let scratch = workspace();
+    let (_server, address) = serve(&scratch, &["--schema", "open-schema.json"], &[]);
+    let too_large = format!(
+        "POST /validate HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+        10 * 1024 * 1024 + 1
+    );
+    let long_target = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(9000));
+    let long_header = format!(
+        "GET /health HTTP/1.1\r\nX-Pad: {}\r\n\r\n",
+        "a".repeat(9000)
+    );
+    let many_headers = format!("GET /health HTTP/1.1\r\n{}\r\n", "X-Pad: a\r\n".repeat(33));
+    for (raw, status, error) in [
+        ("GET\r\n\r\n", 400, "malformed request line"),
+        (
+            "GET /health HTTP/1.1\r\nno colon\r\n\r\n",
+            400,
+            "malformed header",
+        ),
+        (
+            "GET /health HTTP/1.1\r\nHost: x\r\n",
+            400,
+            "the connection closed before the request was complete",
+        ),
+        (
+            "",
+            400,
+            "the connection closed before the request was complete",
+        ),
+        (
+            "POST /validate HTTP/1.1\r\n\r\n",
+            411,
+            "a Content-Length is required",
+        ),
+        (
+            "POST /validate HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            411,
+            "chunked requests are not supported; send a Content-Length",
+        ),
+        (
+            "POST /validate HTTP/1.1\r\nContent-Length: -1\r\n\r\n",
+            400,
+            "invalid Content-Length",
+        ),
+        (&too_large, 413, "the spec is larger than 10485760 bytes"),
+        (
+            &long_target,
+            400,
+            "the request line is longer than 8192 bytes",
+        ),
+        (&long_header, 431, "a header line is longer than 8192 bytes"),
+        (&many_headers, 431, "the request has more than 32 headers"),
+        (
+            "POST /validate HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort",
+            400,
+            "failed to read the request body",
+        ),
+    ] {
+        let (got, body) = send(&address, raw.as_bytes());
+        assert_eq!(got, status, "{raw:?}: {body}");
+        assert!(body.contains(error), "{raw:?}: {body}");
+    }
+}
+
+/// A spec that passes the open schema but lacks the `owner` the strict one requires.
+const OWNERLESS: &str = "meta: {title: A, version: v1}\nalgorithm: {name: A, phases: [x]}\n";
+
+#[test]
+fn validates_with_the_selected_tenant() {
+    let scratch = workspace();
+    scratch.write("strict-schema.json", r#"{"required": ["owner"]}"#);
+    scratch.write("teams/strict.yaml", "schema: ../strict-schema.json\n");
+    // The tenant's configuration replaces the server's, but not its command-line flags.
+    scratch.write(".program-verify.yaml", "schema: open-schema.json\n");
+    let (_server, address) = serve(&scratch, &[], &["--tenant", "strict=teams/strict.yaml"]);
+    assert_eq!(
+        send(&address, b"GET /health HTTP/1.1\r\n\r\n"),
+        (
+            200,
+            "{\"status\":\"ok\",\"tenants\":[\"strict\"]}".to_string()
+        )
+    );
+
+    let (status, response) = post(&address, "/validate", "", OWNERLESS);
+    assert_eq!(status, 200);
+    assert_eq!(response["valid"], true);
+    for (target, headers) in [
+        ("/tenants/strict/validate", ""),
+        ("/validate", "X-Tenant: strict\r\n"),
+        ("/tenants/strict/validate", "X-Tenant: strict\r\n"),
+    ] {
+        let (status, response) = post(&address, target, headers, OWNERLESS);
+        assert_eq!(status, 200, "{target} {headers:?}");
+        assert_eq!(response["valid"], false, "{target} {headers:?}");
+    }
+
+    for (target, headers, expected, error) in [
+        ("/tenants/other/validate", "", 404, "unknown tenant 'other'"),
+        (
+            "/validate",
+            "X-Tenant: other\r\n",
+            404,
+            "unknown tenant 'other'",
+        ),
+        (
+            "/tenants/strict/validate",
+            "X-Tenant: other\r\n",
+            400,
+            "the X-Tenant header names another tenant than the path",
+        ),
+    ] {
+        let (status, response) = post(&address, target, headers, OWNERLESS);
+        assert_eq!(status, expected, "{target} {headers:?}");
+        assert!(response.to_string().contains(error), "{response}");
+    }
+    assert_eq!(
+        send(&address, b"GET /tenants/strict/validate HTTP/1.1\r\n\r\n").0,
+        405
+    );
+}
+
+#[test]
+fn reports_paths_relative_to_the_tenant() {
+    let scratch = workspace();
+    scratch.write("teams/strict.yaml", "versions_map: versions.yaml\n");
+    scratch.write("teams/versions.yaml", "v1.0.0: ../missing-schema.json\n");
+    let (_server, address) = serve(&scratch, &[], &["--tenant", "strict=teams/strict.yaml"]);
+    let spec = format!("spec_version: v1.0.0\n{OWNERLESS}");
+    let (status, response) = post(&address, "/tenants/strict/validate", "", &spec);
+    assert_eq!(status, 200);
+    let error = response["errors"][0].as_str().unwrap();
+    assert!(
+        error.starts_with("Error: failed to read schema ../missing-schema.json: "),
+        "{error}"
+    );
+}
+
+#[test]
+fn rejects_bad_tenants() {
+    let scratch = workspace();
+    scratch.write("teams/payments.yaml", "fail_on: warning\n");
+    for (tenant, error) in [
+        ("payments", "expected NAME=CONFIG"),
+        ("=teams/payments.yaml", "invalid tenant name ''"),
+        ("../x=teams/payments.yaml", "invalid tenant name '../x'"),
+    ] {
+        let run = scratch.run(&["serve", "--port", "0", "--tenant", tenant]);
+        assert_eq!(run.code, Some(2), "{tenant}");
+        assert!(run.reports(error), "{tenant}: {}", run.stderr);
+    }
+
+    let run = scratch.run(&[
+        "serve",
+        "--port",
+        "0",
+        "--tenant",
+        "payments=teams/payments.yaml",
+        "--tenant",
+        "payments=teams/payments.yaml",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("tenant 'payments' is given more than once"));
+
+    let run = scratch.run(&[
+        "serve",
+        "--port",
+        "0",
+        "--tenant",
+        "search=teams/search.yaml",
+    ]);
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("(tenant 'search')"), "{}", run.stderr);
+}
+
+fn audit_entries(scratch: &Scratch, name: &str) -> Vec<JsonValue> {
+    fs::read_to_string(scratch.path(name))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn records_decisions_in_the_audit_log() {
+    let scratch = workspace();
+    let (_server, address) = serve(
+        &scratch,
+        &["--schema", "open-schema.json"],
+        &["--audit-log", "audit.jsonl"],
+    );
+    let valid = "meta: {title: A, version: v1}\nalgorithm: {name: A, phases: [x]}\n";
+    post(&address, "/validate?name=a.yml", "X-User: alice\r\n", valid);
+    post(
+        &address,
+        "/validate",
+        "",
+        "meta: {title: A}\nalgorithm: {name: B}\n",
+    );
+    post(&address, "/tenants/other/validate", "", valid);
+
+    let entries = audit_entries(&scratch, "audit.jsonl");
+    assert_eq!(entries.len(), 3);
+    let first = &entries[0];
+    assert_eq!(first["outcome"], "valid");
+    assert_eq!(first["cached"], false);
+    assert_eq!(first["status"], 200);
+    assert_eq!(first["user"], "alice");
+    assert_eq!(first["name"], "a.yml");
+    assert_eq!(first["tenant"], JsonValue::Null);
+    assert_eq!(first["size"], valid.len());
+    assert_eq!(first["sha256"].as_str().unwrap().len(), 64);
+    assert_eq!(
+        first["findings"],
+        json!({ "error": 0, "info": 0, "warning": 0 })
+    );
+    assert!(first["client"].as_str().unwrap().starts_with("127.0.0.1:"));
+    assert_eq!(entries[1]["outcome"], "invalid");
+    assert_eq!(entries[1]["user"], JsonValue::Null);
+    assert!(entries[1]["findings"]["error"].as_u64().unwrap() > 0);
+    assert_eq!(entries[2]["outcome"], "rejected");
+    assert_eq!(entries[2]["status"], 404);
+    assert_eq!(entries[2]["tenant"], "other");
+}
+
+#[test]
+fn rotates_the_audit_log() {
+    let scratch = workspace();
+    let (_server, address) = serve(
+        &scratch,
+        &["--schema", "open-schema.json"],
+        &[
+            "--audit-log",
+            "audit.jsonl",
+            "--audit-log-max-size",
+            "1",
+            "--audit-log-keep",
+            "2",
+        ],
+    );
+    for name in ["a", "b", "c", "d"] {
+        post(
+            &address,
+            &format!("/validate?name={name}"),
+            "",
+            "meta: {}\n",
+        );
+    }
+    for (file, name) in [
+        ("audit.jsonl", "d"),
+        ("audit.jsonl.1", "c"),
+        ("audit.jsonl.2", "b"),
+    ] {
+        let entries = audit_entries(&scratch, file);
+        assert_eq!(entries.len(), 1, "{file}");
+        assert_eq!(entries[0]["name"], name, "{file}");
+    }
+    assert!(!scratch.path("audit.jsonl.3").exists());
+}
+
+#[test]
+fn rejects_bad_audit_log_options() {
+    let scratch = workspace();
+    for (options, code, error) in [
+        (
+            &["--audit-log", "audit.jsonl", "--audit-log-max-size", "10X"][..],
+            2,
+            "unknown size unit 'X' in '10X' (expected K, M or G)",
+        ),
+        (
+            &["--audit-log", "audit.jsonl", "--audit-log-max-size", "0"],
+            2,
+            "invalid size '0'",
+        ),
+        (&["--audit-log-keep", "3"], 2, "--audit-log <FILE>"),
+        (
+            &["--audit-log", "missing/audit.jsonl"],
+            1,
+            "Error: cannot write audit log missing/audit.jsonl: ",
+        ),
+    ] {
+        let run = scratch.run(&[&["serve", "--port", "0"], options].concat());
+        assert_eq!(run.code, Some(code), "{options:?}");
+        assert!(run.reports(error), "{options:?}: {}", run.stderr);
+    }
+}
+
+#[test]
+fn answers_repeated_uploads_from_the_cache() {
+    let scratch = workspace();
+    let (_server, address) = serve(
+        &scratch,
+        &["--schema", "open-schema.json"],
+        &["--audit-log", "audit.jsonl"],
+    );
+    let spec = "meta: {title: A, version: v1}\nalgorithm: {name: A, phases: [x]}\n";
+    let cached = |target: &str, body: &str| post(&address, target, "", body).1["cached"].clone();
+    assert_eq!(cached("/validate", spec), false);
+    assert_eq!(cached("/validate", spec), true);
+    assert_eq!(cached("/validate?name=other.yml", spec), false);
+    assert_eq!(cached("/validate", &format!("{spec}# edited\n")), false);
+
+    scratch.write("open-schema.json", r#"{"type": "object"}"#);
+    assert_eq!(cached("/validate", spec), false);
+    assert_eq!(cached("/validate", spec), true);
+
+    let entries = audit_entries(&scratch, "audit.jsonl");
+    assert_eq!(entries[1]["cached"], true);
+    let (_, metrics) = send(&address, b"GET /metrics HTTP/1.1\r\n\r\n");
+    assert!(
+        metrics.contains("program_verify_cache_hits_total{cache=\"validation results\"} 2\n"),
+        "{metrics}"
+    );
+}
+
+#[test]
+fn expires_and_bounds_cached_results() {
+    let scratch = workspace();
+    let spec = |title: &str| format!("meta: {{title: {title}}}\n");
+    for (options, expected) in [
+        (&["--result-cache-size", "0"][..], [false, false, false]),
+        (&["--result-cache-size", "1"], [false, false, false]),
+        (&["--result-cache-ttl", "100ms"], [false, false, false]),
+        (&[], [false, false, true]),
+    ] {
+        let (_server, address) = serve(&scratch, &["--schema", "open-schema.json"], options);
+        let mut cached = Vec::new();
+        for title in ["A", "B", "A"] {
+            std::thread::sleep(std::time::Duration::from_millis(150));
+            cached.push(post(&address, "/validate", "", &spec(title)).1["cached"] == true);
+        }
+        assert_eq!(cached, expected, "{options:?}");
+    }
+}
@@ -0,0 +1,44 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+/// A spec whose only finding is a warning about a span name.
+fn spec() -> serde_json::Value {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Support", "phases": ["collect"] },
+        "implementation": {
+            "phase_contracts": { "collect": { "observability": { "spans": ["Collect"] } } }
+        }
+    })
+}
+
+#[test]
+fn warnings_pass_by_default() {
+    let run = Scratch::new().check(&spec());
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ Rule: observability [PV050]: Phase 'collect' trace span 'Collect'"));
+    assert!(run
+        .stdout
+        .contains("✅ OK — the document matches the specification (1 warning(s))."));
+}
+
+#[test]
+fn fail_on_warning_fails_on_warnings() {
+    let scratch = Scratch::new();
+    scratch.check(&spec());
+    let run = scratch.run(&[
+        "--fail-on",
+        "warning",
+        "--schema",
+        "open-schema.json",
+        "spec.yml",
+    ]);
+    assert!(!run.success());
+
+    let run = scratch.check(&json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Billing", "phases": [] }
+    }));
+    assert!(!run.success());
+    assert!(run.reports("❌ Rule: meta.title vs algorithm.name"));
+}
@@ -0,0 +1,57 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A spec named `name` whose `collect` phase has the given contract.
+fn spec(name: &str, collect: JsonValue) -> String {
+    json!({
+        "meta": { "title": name, "version": "v1" },
+        "algorithm": { "name": name, "phases": ["collect"] },
+        "implementation": { "phase_contracts": { "collect": collect } }
+    })
+    .to_string()
+}
+
+#[test]
+fn validates_directories_and_files_together() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("specs/a.yml", &spec("A", json!({})));
+    scratch.write("specs/nested/b.json", &spec("B", json!({})));
+    scratch.write("specs/notes.txt", "not a spec");
+    scratch.write("c.yml", &spec("C", json!({})));
+
+    let run = scratch.run(&["--schema", "open-schema.json", "specs", "c.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .contains("✅ All 3 files match the specification."));
+
+    scratch.write("empty/notes.txt", "");
+    let run = scratch.run(&["empty"]);
+    assert!(!run.success());
+    assert!(run.reports("contains no spec files"));
+}
+
+#[test]
+fn reports_drifting_shared_contracts() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    let contract = |timeout: u64| json!({ "contract_version": "2.0", "timeout_ms": timeout });
+    scratch.write("a.yml", &spec("A", contract(100)));
+    scratch.write("b.yml", &spec("B", contract(100)));
+    let run = scratch.run(&["--schema", "open-schema.json", "a.yml", "b.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+
+    scratch.write("b.yml", &spec("B", contract(200)));
+    let run = scratch.run(&["--schema", "open-schema.json", "a.yml", "b.yml"]);
+    assert!(!run.success());
+    assert!(run.reports(
+        "[PV060]: Phase 'collect' (contract_version 2.0) differs between a.yml and b.yml in: timeout_ms"
+    ));
+
+    let mut distinct = contract(200);
+    distinct["distinct"] = true.into();
+    scratch.write("b.yml", &spec("B", distinct));
+    let run = scratch.run(&["--schema", "open-schema.json", "a.yml", "b.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+}
@@ -0,0 +1,30 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+fn spec(name: &str) -> String {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": name, "phases": [] }
+    })
+    .to_string()
+}
+
+#[test]
+fn reads_the_spec_from_stdin() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    let args = ["--schema", "open-schema.json", "-"];
+
+    let run = scratch.run_with_input(&args, &spec("Support"));
+    assert!(run.success(), "{}", run.stderr);
+    let run = scratch.run_with_input(&args, &spec("Billing"));
+    assert!(!run.success());
+    assert!(run.reports("meta.title vs algorithm.name"));
+}
+
+#[test]
+fn refuses_to_watch_stdin() {
+    let run = Scratch::new().run_with_input(&["--watch", "-"], &spec("Support"));
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("--watch cannot be combined with reading the spec from stdin"));
+}
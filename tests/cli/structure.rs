@@ -0,0 +1,42 @@
+use crate::support::Scratch;
+
+fn check(implementation: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "meta: {{title: Support, version: v1}}\nalgorithm: {{name: Helpdesk, phases: [x, y]}}\n\
+             implementation:\n{implementation}"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_sections_of_the_wrong_type_once() {
+    let run = check("  phase_contracts:\n    x: oops\n    y: {outputs: {q: {}}}\n");
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stderr.matches("[PV120]").count(), 2, "{}", run.stderr);
+    assert!(run.reports(
+        "❌ Rule: structure [PV120]: phase contract 'x' must be an object, not a string; rules \
+         that read it skipped it\n --> spec.yml:5:5"
+    ));
+    assert!(run.reports(
+        "[PV120]: outputs of phase contract 'y' must be an array, not an object; rules that read \
+         it skipped it\n --> spec.yml:6:9"
+    ));
+    // The other rules still check the rest of the spec.
+    assert!(run.reports("[PV001]"), "{}", run.stderr);
+    assert!(!run.reports("[PV016]"), "{}", run.stderr);
+
+    let run = check("  phase_contracts: [x]\n");
+    assert_eq!(run.stderr.matches("[PV120]").count(), 1, "{}", run.stderr);
+    assert!(run.reports("implementation.phase_contracts must be an object, not an array"));
+}
+
+#[test]
+fn passes_sections_of_the_right_type() {
+    let run = check("  phase_contracts:\n    x: {}\n    y: {outputs: []}\n");
+    assert!(!run.reports("[PV120]"), "{}", run.stderr);
+}
@@ -0,0 +1,48 @@
+use crate::support::Scratch;
+
+const SPEC: &str = "\
+meta: {title: A, version: v1}
+algorithm: {name: A, phases: [collect_issue, reply]}
+implementation:
+  phase_contracts:
+    collect_issue:
+      outputs: [{name: issue}]
+      errors: [{code: TIMEOUT}]
+      retry_policy: {retryable_errors: [TIMEOUTS, RATE_LIMITED]}
+    reply:
+      inputs:
+        - {name: a, source: {kind: phase_output, phase: collect_isue, port: issue}}
+        - {name: b, source: {kind: phase_output, phase: collect_issue, port: isue}}
+        - {name: c, source: {kind: phase_output, phase: something, port: issue}}
+";
+
+#[test]
+fn suggests_the_nearest_declared_names() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", SPEC);
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.yml"]);
+    assert_eq!(run.code, Some(1));
+    for finding in [
+        "[PV014]: Phase 'reply' references unknown producing phase 'collect_isue' in input 'a'; \
+         did you mean 'collect_issue'?",
+        "[PV016]: Phase 'reply' expects output 'isue' from phase 'collect_issue' in input 'b', but \
+         it is not declared; did you mean 'issue'?",
+        "[PV018]: Phase 'collect_issue' retry_policy references unknown error code 'TIMEOUTS'; did \
+         you mean 'TIMEOUT'?",
+    ] {
+        assert!(run.reports(finding), "{finding}\n{}", run.stderr);
+    }
+}
+
+#[test]
+fn suggests_nothing_for_distant_names() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", SPEC);
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.yml"]);
+    assert!(run.reports(
+        "[PV014]: Phase 'reply' references unknown producing phase 'something' in input 'c'\n"
+    ));
+    assert!(run.reports("unknown error code 'RATE_LIMITED'\n"));
+}
@@ -0,0 +1,229 @@
+//! Running the binary on specs in a scratch directory.
+
+use serde_json::Value as JsonValue;
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Lines, Read, Write},
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+/// A directory of its own for one test, removed when dropped.
+pub struct Scratch {
+    dir: PathBuf,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = env::temp_dir().join(format!(
+            "program-verify-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Writes `text` to the file `name`, creating its directory.
+    pub fn write(&self, name: &str, text: &str) -> PathBuf {
+        let path = self.dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    /// `program-verify` with `args` in the directory, with a schema cache of its own.
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_program-verify"));
+        command
+            .args(args)
+            .current_dir(&self.dir)
+            .env("XDG_CACHE_HOME", self.dir.join(".cache"));
+        command
+    }
+
+    /// Runs `program-verify` with `args` in the directory.
+    pub fn run(&self, args: &[&str]) -> Run {
+        self.run_with_input(args, "")
+    }
+
+    /// Runs `program-verify` with `args` in the directory, writing `input` to its stdin.
+    pub fn run_with_input(&self, args: &[&str], input: &str) -> Run {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        // The binary may exit before reading everything, so a broken pipe is fine.
+        let _ = stdin.write_all(input.as_bytes());
+        drop(stdin);
+        let output = child.wait_with_output().unwrap();
+        Run {
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    /// Starts `program-verify` with `args` in the directory, reading its stdout line by line.
+    pub fn spawn(&self, args: &[&str]) -> Running {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+        Running { child, stdout }
+    }
+
+    /// Validates `spec` against a schema that accepts anything, so that only the domain rules
+    /// report.
+    pub fn check(&self, spec: &JsonValue) -> Run {
+        self.write("open-schema.json", "{}");
+        // JSON is YAML too.
+        self.write("spec.yml", &serde_json::to_string_pretty(spec).unwrap());
+        self.run(&["--schema", "open-schema.json", "spec.yml"])
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// What a run printed and how it exited.
+pub struct Run {
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Run {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    /// Whether stdout or stderr contains `text`.
+    pub fn reports(&self, text: &str) -> bool {
+        self.stdout.contains(text) || self.stderr.contains(text)
+    }
+}
+
+/// A process started by [`Scratch::spawn`], killed when dropped.
+pub struct Running {
+    child: Child,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl Running {
+    /// Reads stdout up to the first line containing `text` and returns that line.
+    pub fn wait_for(&mut self, text: &str) -> String {
+        for line in &mut self.stdout {
+            let line = line.unwrap();
+            if line.contains(text) {
+                return line;
+            }
+        }
+        panic!("the process exited before printing {text:?}");
+    }
+
+    /// Sends the signal `name` (`INT`, `TERM`) to the process.
+    pub fn signal(&self, name: &str) {
+        let status = Command::new("kill")
+            .arg(format!("-{name}"))
+            .arg(self.child.id().to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    /// Waits for the process to exit; `stdout` holds what [`Running::wait_for`] did not read.
+    pub fn finish(&mut self) -> Run {
+        let stdout: Vec<String> = (&mut self.stdout).map(Result::unwrap).collect();
+        let mut stderr = String::new();
+        self.child
+            .stderr
+            .take()
+            .unwrap()
+            .read_to_string(&mut stderr)
+            .unwrap();
+        let status = self.child.wait().unwrap();
+        Run {
+            code: status.code(),
+            stdout: stdout.join("\n"),
+            stderr,
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A local HTTP server answering every request with `200 OK` and the same body.
+pub struct Server {
+    pub url: String,
+    hits: Arc<AtomicUsize>,
+    down: Arc<AtomicBool>,
+}
+
+impl Server {
+    pub fn new(body: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let down = Arc::new(AtomicBool::new(false));
+        let body = body.to_string();
+        let (counter, unreachable) = (hits.clone(), down.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 2 {
+                    line.clear();
+                }
+                if unreachable.load(Ordering::SeqCst) {
+                    continue;
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        Self { url, hits, down }
+    }
+
+    /// From now on, closes every connection without answering.
+    pub fn go_down(&self) {
+        self.down.store(true, Ordering::SeqCst);
+    }
+
+    /// Number of requests answered so far.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
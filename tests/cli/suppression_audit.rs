@@ -0,0 +1,90 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// `spec.yml` has a PV001 finding and a PV010 finding suppressed inline.
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        "\
+meta: {title: Support, version: v1}
+algorithm: {name: Billing, phases: [collect]}
+implementation:
+  phase_contracts:
+    collect: {}
+    legacy: {x-verify-ignore: [PV010]}
+",
+    );
+    scratch
+}
+
+const PV001: &str = "  [PV001] spec.yml /algorithm/name: algorithm.name='Billing' does not match the \
+                     base of meta.title='Support' (detected 'Support')\n";
+const PV010: &str = "x-verify-ignore at /implementation/phase_contracts/legacy (added today)\n  \
+                     [PV010] spec.yml /implementation/phase_contracts/legacy: phase_contracts \
+                     contains unknown phase 'legacy' (not listed in algorithm.phases)\n";
+
+#[test]
+fn lists_what_waived_each_finding() {
+    let scratch = workspace();
+    let args = ["--schema", "open-schema.json", "spec.yml"];
+    let run = scratch.run(&[&args[..], &["--write-baseline", "baseline.json"]].concat());
+    assert!(run.success(), "{}", run.stderr);
+
+    let run = scratch.run(
+        &[
+            &args[..],
+            &["--baseline", "baseline.json", "--report-suppressed"],
+        ]
+        .concat(),
+    );
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.stdout.ends_with(&format!(
+            "── 2 suppressed finding(s) ──\nbaseline baseline.json (added today)\n{PV001}{PV010}"
+        )),
+        "{}",
+        run.stdout
+    );
+
+    let run = scratch.run(&[&args[..], &["--ignore", "PV001", "--report-suppressed"]].concat());
+    assert!(run.stdout.ends_with(&format!("{PV010}--ignore PV001\n{PV001}")));
+
+    scratch.write(
+        ".program-verify.yaml",
+        "rules:\n  title-vs-algorithm:\n    enabled: false\n",
+    );
+    let run = scratch.run(&[&args[..], &["--report-suppressed"]].concat());
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run
+        .stdout
+        .contains("rules.title-vs-algorithm.enabled: false in "));
+}
+
+#[test]
+fn emits_json_and_refuses_sarif() {
+    let scratch = workspace();
+    let args = ["--schema", "open-schema.json", "spec.yml", "--report-suppressed"];
+    let run = scratch.run(&[&args[..], &["--ignore", "PV001", "--format", "json"]].concat());
+    assert!(run.success(), "{}", run.stderr);
+    let audit: Vec<JsonValue> = run
+        .stdout
+        .lines()
+        .map(|line| serde_json::from_str::<JsonValue>(line).unwrap())
+        .filter(|line| line.get("provenance").is_none())
+        .collect();
+    assert_eq!(audit.len(), 2, "{}", run.stdout);
+    assert_eq!(audit[0]["code"], "PV001");
+    assert_eq!(
+        audit[0]["suppressed"],
+        json!({ "kind": "selection", "source": "--ignore PV001" })
+    );
+    assert_eq!(audit[1]["code"], "PV010");
+    assert_eq!(audit[1]["suppressed"]["kind"], "inline");
+    assert!(audit[1]["suppressed"]["since"].is_u64());
+
+    let run = scratch.run(&[&args[..], &["--format", "sarif"]].concat());
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("--report-suppressed needs --format human or --format json"));
+}
@@ -0,0 +1,58 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A spec with a contract for the undeclared phase `legacy` (PV010) and the given suppressions.
+fn spec(document: JsonValue, node: JsonValue) -> JsonValue {
+    let mut legacy = json!({});
+    if !node.is_null() {
+        legacy["x-verify-ignore"] = node;
+    }
+    let mut meta = json!({ "title": "Support", "version": "v1" });
+    if !document.is_null() {
+        meta["verify_ignore"] = document;
+    }
+    json!({
+        "meta": meta,
+        "algorithm": { "name": "Support", "phases": ["collect"] },
+        "implementation": { "phase_contracts": { "collect": {}, "legacy": legacy } }
+    })
+}
+
+#[test]
+fn silences_findings_below_the_node_or_in_the_document() {
+    let scratch = Scratch::new();
+    let run = scratch.check(&spec(JsonValue::Null, JsonValue::Null));
+    assert!(run.reports("[PV010]"));
+
+    for (document, node) in [
+        (JsonValue::Null, json!(["PV010"])),
+        (json!(["pv01"]), JsonValue::Null),
+    ] {
+        let run = scratch.check(&spec(document, node));
+        assert!(run.success(), "{}", run.stderr);
+        assert!(!run.reports("[PV010]"), "{}", run.stderr);
+        assert!(!run.reports("[PV091]"), "{}", run.stderr);
+    }
+}
+
+#[test]
+fn reports_malformed_suppressions() {
+    let scratch = Scratch::new();
+    let run = scratch.check(&spec(json!(["PV999", 7]), JsonValue::Null));
+    assert!(!run.success());
+    assert!(run.reports("[PV090]: suppression 'PV999' does not match any rule ID"));
+    assert!(run.reports("[PV090]: suppressions must list rule IDs as strings"));
+}
+
+#[test]
+fn reports_suppressions_that_silence_nothing() {
+    let run = Scratch::new().check(&spec(json!(["PV010", "PV050"]), JsonValue::Null));
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports(
+        "⚠️ Rule: suppressions [PV091]: meta.verify_ignore entry 'PV050' never matched a finding"
+    ));
+    assert!(!run.reports("entry 'PV010' never matched"));
+
+    let run = Scratch::new().check(&spec(JsonValue::Null, json!(["PV020"])));
+    assert!(run.reports("[PV091]: x-verify-ignore entry 'PV020' at '/implementation/phase_contracts/legacy' never matched a finding"));
+}
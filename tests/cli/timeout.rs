@@ -0,0 +1,126 @@
+use crate::support::Scratch;
+use serde_json::json;
+use std::{net::TcpListener, thread, time::Duration};
+
+/// Writes `ok.yml`, `hangs.yml`, which imports a library from `listener` (which never answers),
+/// and `later.yml`.
+fn hanging_corpus(scratch: &Scratch, listener: &TcpListener) {
+    let url = format!("http://{}/common.yml", listener.local_addr().unwrap());
+    let spec = |uses: serde_json::Value| {
+        json!({
+            "meta": { "title": "Support" },
+            "algorithm": { "name": "Support", "phases": [] },
+            "implementation": { "uses": uses }
+        })
+        .to_string()
+    };
+    scratch.write("open-schema.json", "{}");
+    scratch.write("ok.yml", &spec(json!([])));
+    scratch.write(
+        "hangs.yml",
+        &spec(json!([{ "library": "common", "url": url }])),
+    );
+    scratch.write("later.yml", &spec(json!([])));
+}
+
+const SUMMARY: &str = "after 1 of 3 file(s) (0 failed).\n  • hangs.yml (interrupted)\n  • later.yml (not validated)\n";
+
+#[test]
+fn cancels_a_hung_run() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let scratch = Scratch::new();
+    hanging_corpus(&scratch, &listener);
+
+    for (limit, scope) in [
+        ("--timeout", "the run exceeded"),
+        ("--file-timeout", "hangs.yml exceeded"),
+    ] {
+        let run = scratch.run(&[
+            "-j",
+            "1",
+            "--schema",
+            "open-schema.json",
+            limit,
+            "300ms",
+            "ok.yml",
+            "hangs.yml",
+            "later.yml",
+        ]);
+        assert_eq!(run.code, Some(124), "{}", run.stderr);
+        assert!(run.stdout.contains("── ok.yml ──\n✅ OK"));
+        assert!(
+            run.reports(&format!("⏹️ Cancelled: {scope} {limit} 300ms {SUMMARY}")),
+            "{}",
+            run.stderr
+        );
+    }
+}
+
+#[test]
+fn lists_every_file_in_progress() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let scratch = Scratch::new();
+    hanging_corpus(&scratch, &listener);
+
+    let run = scratch.run(&[
+        "-j",
+        "3",
+        "--schema",
+        "open-schema.json",
+        "--timeout",
+        "300ms",
+        "ok.yml",
+        "hangs.yml",
+        "later.yml",
+    ]);
+    assert_eq!(run.code, Some(124), "{}", run.stderr);
+    assert!(
+        run.reports("after 2 of 3 file(s) (0 failed).\n  • hangs.yml (interrupted)\n"),
+        "{}",
+        run.stderr
+    );
+    assert!(!run.reports("later.yml"));
+}
+
+#[test]
+fn summarizes_an_interrupted_run() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let scratch = Scratch::new();
+    hanging_corpus(&scratch, &listener);
+
+    for (signal, code) in [("INT", 130), ("TERM", 143)] {
+        let mut running = scratch.spawn(&[
+            "-j",
+            "1",
+            "--schema",
+            "open-schema.json",
+            "ok.yml",
+            "hangs.yml",
+            "later.yml",
+        ]);
+        // Results are printed in input order, so the run is now stuck in hangs.yml.
+        running.wait_for("── ok.yml ──");
+        thread::sleep(Duration::from_millis(200));
+        running.signal(signal);
+        let run = running.finish();
+        assert_eq!(run.code, Some(code), "{}", run.stderr);
+        assert!(
+            run.reports(&format!("⏹️ Interrupted by SIG{signal} {SUMMARY}")),
+            "{}",
+            run.stderr
+        );
+    }
+}
+
+#[test]
+fn rejects_bad_durations_and_watch() {
+    let scratch = Scratch::new();
+    scratch.write("spec.yml", "meta: {}\n");
+    let run = scratch.run(&["--timeout", "soon", "spec.yml"]);
+    assert!(!run.success());
+    assert!(run.reports("invalid duration 'soon'"));
+
+    let run = scratch.run(&["--timeout", "1s", "--watch", "spec.yml"]);
+    assert!(!run.success());
+    assert!(run.reports("--timeout cannot be combined with --watch"));
+}
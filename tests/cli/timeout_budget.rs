@@ -0,0 +1,69 @@
+use crate::support::Scratch;
+
+/// `fetch` → `parse` → `store`, with the timeouts of the first two phases and `budget` under
+/// `implementation`.
+fn check(budget: &str, fetch: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "spec.yml",
+        &format!(
+            "\
+meta: {{title: A, version: v1, sla: 5m}}
+algorithm: {{name: A, phases: [fetch, parse, store]}}
+implementation:
+  {budget}
+  phase_contracts:
+    fetch:
+      timeout: {fetch}
+      outputs: [{{name: body}}]
+    parse:
+      timeout: 45s
+      inputs: [{{name: body, source: {{kind: phase_output, phase: fetch, port: body}}}}]
+      outputs: [{{name: doc}}]
+    store:
+      inputs: [{{name: doc, source: {{kind: phase_output, phase: parse, port: doc}}}}]
+"
+        ),
+    );
+    scratch.run(&["--schema", "open-schema.json", "spec.yml"])
+}
+
+#[test]
+fn reports_critical_paths_over_the_budget() {
+    let run = check("total_timeout: 90s", "60");
+    assert_eq!(run.code, Some(1));
+    assert!(
+        run.reports(
+            "❌ Rule: timeout budget [PV190]: Phase timeouts along 'fetch' (60s) → \
+             'parse' (45s) → 'store' add up to 105s, more than \
+             implementation.total_timeout (90s)\n --> spec.yml:4:3"
+        ),
+        "{}",
+        run.stderr
+    );
+    // meta.sla is a budget of its own.
+    let run = check("total_timeout: 10m", "5m");
+    assert!(run.reports("add up to 345s, more than meta.sla (5m)"));
+    assert!(!run.reports("implementation.total_timeout"));
+}
+
+#[test]
+fn passes_paths_within_the_budget() {
+    let run = check("total_timeout: 2m", "60");
+    assert!(run.success(), "{}", run.stderr);
+    let run = check("owner: search", "1500ms");
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_malformed_durations() {
+    let run = check("total_timeout: -1", "soon");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "[PV191]: Phase 'fetch' timeout is \"soon\"; expected a positive number of seconds or a \
+         duration such as 30s, 500ms, 2m or 1h\n --> spec.yml:7:7"
+    ));
+    assert!(run.reports("[PV191]: implementation.total_timeout is -1; expected a positive number"));
+    assert!(!run.reports("PV190"));
+}
@@ -0,0 +1,62 @@
+use crate::support::{Scratch, Server};
+
+fn corpus() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    for name in ["a", "b"] {
+        scratch.write(
+            &format!("{name}.yml"),
+            &format!("meta: {{title: {name}, version: v1}}\nalgorithm: {{name: {name}, phases: [x]}}\n"),
+        );
+    }
+    scratch
+}
+
+#[test]
+fn prints_cache_statistics() {
+    let scratch = corpus();
+    let args = ["--schema", "open-schema.json", "--timings", "a.yml", "b.yml"];
+    let run = scratch.run(&args);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.stderr.contains("⏱️ Validated 2 file(s) in "), "{}", run.stderr);
+    for line in [
+        "   compiled schemas            1 hit(s)      1 miss(es)  50% hits\n",
+        "   fetched schemas             0 hit(s)      0 miss(es)  unused\n",
+        "   downloads (disk)            0 hit(s)      0 miss(es)  unused\n",
+    ] {
+        assert!(run.stderr.contains(line), "{line}\n{}", run.stderr);
+    }
+
+    // The parsed schema is on disk now.
+    let run = scratch.run(&args);
+    assert!(run
+        .stderr
+        .contains("   parsed schemas (disk)       2 hit(s)      0 miss(es)  100% hits\n"));
+}
+
+#[test]
+fn fetches_a_remote_schema_once() {
+    let server = Server::new("{}");
+    let scratch = corpus();
+    let schema = format!("{}/schema.json", server.url);
+    let run = scratch.run(&["--schema", &schema, "--timings", "a.yml", "b.yml"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(server.hits(), 1);
+    assert!(run
+        .stderr
+        .contains("   fetched schemas             1 hit(s)      1 miss(es)  50% hits\n"));
+}
+
+#[test]
+fn leaves_statistics_out_of_machine_output() {
+    let run = corpus().run(&[
+        "--schema",
+        "open-schema.json",
+        "--timings",
+        "--format",
+        "json",
+        "a.yml",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(!run.reports("compiled schemas"));
+}
@@ -0,0 +1,95 @@
+use crate::support::Scratch;
+
+/// Validates a spec with `meta` entries `meta` and `algorithm.name` `name`, configured with
+/// `match: <how>`.
+fn validate(how: &str, meta: &str, name: &str) -> crate::support::Run {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        ".program-verify.yaml",
+        &format!("schema: open-schema.json\nrules:\n  title-vs-algorithm:\n    match: {how}\n"),
+    );
+    scratch.write(
+        "spec.yml",
+        &format!("meta: {{version: v1, {meta}}}\nalgorithm: {{name: {name}, phases: [a]}}\n"),
+    );
+    scratch.run(&["spec.yml"])
+}
+
+#[test]
+fn matches_each_way() {
+    for (how, meta, name) in [
+        ("prefix", "title: 'Support (v1)'", "Support"),
+        ("exact", "title: 'Support (v1)'", "'Support (v1)'"),
+        ("slug", "title: 'Customer Support'", "customer_support"),
+        ("{regex: '^(.+?) — '}", "title: 'Support — v1'", "Support"),
+        (
+            "{regex: '^v\\d+ (?P<name>.+)$'}",
+            "title: 'v1 Support'",
+            "Support",
+        ),
+        (
+            "meta-slug",
+            "title: Anything, slug: customer-support",
+            "Customer_Support",
+        ),
+    ] {
+        let run = validate(how, meta, name);
+        assert!(run.success(), "{how}: {}{}", run.stdout, run.stderr);
+    }
+}
+
+#[test]
+fn reports_mismatches() {
+    for (how, meta, name, finding) in [
+        (
+            "exact",
+            "title: 'Support (v1)'",
+            "Support",
+            "[PV001]: algorithm.name='Support' does not match meta.title='Support (v1)'",
+        ),
+        (
+            "slug",
+            "title: 'Customer Support'",
+            "customer",
+            "[PV001]: algorithm.name='customer' (slug 'customer') does not match \
+             meta.title='Customer Support' (slug 'customer-support')",
+        ),
+        (
+            "{regex: '^(.+?) — '}",
+            "title: 'Support v1'",
+            "Support",
+            "[PV005]: meta.title='Support v1' does not match the identity pattern '^(.+?) — '",
+        ),
+        (
+            "meta-slug",
+            "title: Anything",
+            "Support",
+            "[PV004]: Missing meta.slug, which identifies the algorithm",
+        ),
+        (
+            "meta-slug",
+            "title: Anything, slug: other",
+            "Customer_Support",
+            "[PV001]: algorithm.name='Customer_Support' (slug 'customer-support') does not match \
+             meta.slug='other'",
+        ),
+    ] {
+        let run = validate(how, meta, name);
+        assert_eq!(run.code, Some(1), "{how}");
+        assert!(run.reports(finding), "{how}: {}", run.stdout);
+    }
+}
+
+#[test]
+fn rejects_bad_settings() {
+    let run = validate("{regex: '(unclosed'}", "title: A", "A");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("invalid match regex of rule 'title-vs-algorithm'"));
+
+    let run = validate("fuzzy", "title: A", "A");
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports(
+        "unknown match 'fuzzy', expected prefix, exact, slug, meta-slug or {regex: PATTERN}"
+    ));
+}
@@ -0,0 +1,26 @@
+use crate::support::Scratch;
+
+#[test]
+fn needs_a_terminal() {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", "meta: {title: A}\nalgorithm: {name: A}\n");
+    // The output of the test run is a pipe.
+    let run = scratch.run(&["--schema", "open-schema.json", "spec.yml", "--tui"]);
+    assert_eq!(run.code, Some(1));
+    assert_eq!(run.stdout, "");
+    assert!(run.reports("Error: --tui needs a terminal"));
+}
+
+#[test]
+fn conflicts_with_other_outputs() {
+    let scratch = Scratch::new();
+    for option in ["--quiet", "--watch", "--format=json"] {
+        let run = scratch.run(&["--schema", "open-schema.json", "spec.yml", "--tui", option]);
+        assert_eq!(run.code, Some(2), "{option}");
+        assert!(
+            run.reports("the argument '--tui' cannot be used with"),
+            "{option}"
+        );
+    }
+}
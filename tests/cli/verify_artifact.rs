@@ -0,0 +1,113 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+
+/// The canonical hash `publish` and `verify-artifact` take: of the compact JSON with sorted keys.
+fn canonical_sha256(doc: &JsonValue) -> String {
+    format!("{:x}", Sha256::digest(serde_json::to_vec(doc).unwrap()))
+}
+
+fn compiled() -> JsonValue {
+    json!({
+        "meta": { "title": "Support", "version": "v1" },
+        "algorithm": { "name": "Support", "phases": ["collect", "reply"] }
+    })
+}
+
+/// A workspace with the source spec, and `artifact` with `lineage` added as `build/support.json`.
+fn workspace(artifact: &JsonValue, lineage: JsonValue) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("open-schema.json", "{}");
+    scratch.write(
+        "support.yml",
+        "# reformatted\nalgorithm: {name: Support, phases: [collect, reply]}\n\
+         meta: {title: Support, version: v1}\n",
+    );
+    let mut artifact = artifact.clone();
+    artifact["lineage"] = lineage;
+    scratch.write("build/support.json", &artifact.to_string());
+    scratch
+}
+
+fn verify(scratch: &Scratch) -> crate::support::Run {
+    scratch.run(&[
+        "--schema",
+        "open-schema.json",
+        "verify-artifact",
+        "build/support.json",
+        "--source",
+        "support.yml",
+    ])
+}
+
+#[test]
+fn traces_artifacts_to_their_source() {
+    let source = canonical_sha256(&compiled());
+    for lineage in [
+        json!({ "source": "support.yml", "source_sha256": source }),
+        json!({ "source_sha256": source, "sha256": canonical_sha256(&compiled()) }),
+    ] {
+        let run = verify(&workspace(&compiled(), lineage));
+        assert!(run.success(), "{}", run.stderr);
+        assert!(run.reports("✅ OK — the document matches the specification."));
+        assert!(run.reports(&format!(
+            "✅ build/support.json was built from support.yml (sha256 {source})."
+        )));
+    }
+}
+
+#[test]
+fn rejects_artifacts_that_do_not_trace_to_the_source() {
+    let source = canonical_sha256(&compiled());
+    let mut edited = compiled();
+    edited["algorithm"]["phases"] = json!(["collect"]);
+
+    for (artifact, lineage, error) in [
+        (compiled(), JsonValue::Null, "lineage.source_sha256 is missing or not a string."),
+        (
+            compiled(),
+            json!({ "source_sha256": "0".repeat(64) }),
+            &format!(
+                "❌ build/support.json was not built from support.yml: lineage.source_sha256 \
+                 is {}, the source hashes to {source}.",
+                "0".repeat(64)
+            ),
+        ),
+        (
+            edited.clone(),
+            json!({ "source_sha256": source, "sha256": canonical_sha256(&compiled()) }),
+            &format!(
+                "❌ build/support.json was modified after it was built: lineage.sha256 is {}, the \
+                 artifact hashes to {}.",
+                canonical_sha256(&compiled()),
+                canonical_sha256(&edited)
+            ),
+        ),
+    ] {
+        let run = verify(&workspace(&artifact, lineage));
+        assert_eq!(run.code, Some(1), "{error}");
+        assert!(run.reports(error), "{error}: {}", run.stderr);
+    }
+
+    // Without a lineage at all.
+    let scratch = workspace(&compiled(), JsonValue::Null);
+    scratch.write("build/support.json", &compiled().to_string());
+    let run = verify(&scratch);
+    assert_eq!(run.code, Some(1));
+    assert!(
+        run.reports("❌ build/support.json has no lineage; it cannot be traced to a source spec.")
+    );
+}
+
+#[test]
+fn validates_the_artifact_without_its_lineage() {
+    let mut invalid = compiled();
+    invalid["algorithm"]["name"] = json!("Helpdesk");
+    let run = verify(&workspace(
+        &invalid,
+        json!({ "source_sha256": canonical_sha256(&compiled()) }),
+    ));
+    assert_eq!(run.code, Some(1));
+    assert!(run.reports("[PV001]"), "{}", run.stderr);
+    assert!(run.reports("✅ build/support.json was built from support.yml"));
+}
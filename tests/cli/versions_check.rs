@@ -0,0 +1,57 @@
+use crate::support::Scratch;
+use serde_json::json;
+
+/// A workspace whose version map lists `versions` (each pointing to `schemas/<version>.json`,
+/// which exists for v1 and v2) and whose specs use `used`.
+fn workspace(versions: &[&str], used: &[&str]) -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("schemas/v1.json", "{}");
+    scratch.write("schemas/v2.json", "{}");
+    let map: String = versions
+        .iter()
+        .map(|v| format!("{v}: schemas/{v}.json\n"))
+        .collect();
+    scratch.write("version_map.yaml", &map);
+    for version in used {
+        let spec = json!({
+            "spec_version": version,
+            "meta": { "title": "Support", "version": "v1" },
+            "algorithm": { "name": "Support", "phases": [] }
+        });
+        scratch.write(&format!("specs/{version}.yml"), &spec.to_string());
+    }
+    scratch
+}
+
+#[test]
+fn passes_a_tidy_workspace() {
+    let run = workspace(&["v1", "v2"], &["v1", "v2"]).run(&["versions", "check", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+}
+
+#[test]
+fn reports_broken_and_missing_versions() {
+    let run = workspace(&["v1", "v3"], &["v1", "v3", "v9"]).run(&["versions", "check", "specs"]);
+    assert!(!run.success());
+    assert!(run.reports("❌ versions check [PV080]: version 'v3' points to an unusable schema"));
+    assert!(run.reports("❌ versions check [PV082]: spec_version 'v9' (used by"));
+}
+
+#[test]
+fn unused_entries_are_warnings() {
+    let scratch = workspace(&["v1", "v2"], &["v1"]);
+    let run = scratch.run(&["versions", "check", "specs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(run.reports("⚠️ versions check [PV081]: version 'v2' in"));
+    assert!(run.reports("is not used by any spec"));
+
+    scratch.write(
+        ".program-verify.yaml",
+        "exclude: [drafts/**]\nlibrary_paths: [libs]\nrules:\n  observability:\n    enabled: true\n",
+    );
+    let run = scratch.run(&["--fail-on", "warning", "versions", "check", "specs"]);
+    assert!(!run.success());
+    assert!(run.reports("[PV083]: exclude pattern 'drafts/**' in"));
+    assert!(run.reports("rules.observability in"));
+    assert!(run.reports("does not exist"));
+}
@@ -0,0 +1,75 @@
+use crate::support::Scratch;
+use serde_json::json;
+use std::{thread, time::Duration};
+
+#[test]
+fn revalidates_when_the_spec_changes() {
+    let scratch = Scratch::new();
+    let spec = |name: &str| {
+        json!({
+            "meta": { "title": "Support", "version": "v1" },
+            "algorithm": { "name": name, "phases": [] }
+        })
+        .to_string()
+    };
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", &spec("Billing"));
+
+    let mut running = scratch.spawn(&["--watch", "--schema", "open-schema.json", "spec.yml"]);
+    let line = running.wait_for("file(s) for changes");
+    assert!(line.contains("failed; watching"), "{line}");
+
+    scratch.write("spec.yml", &spec("Support"));
+    let line = running.wait_for("file(s) for changes");
+    assert!(line.contains("passed; watching"), "{line}");
+}
+
+#[test]
+fn starts_over_when_the_spec_changes_mid_run() {
+    let scratch = Scratch::new();
+    // A pipeline long enough for the validation to take a while.
+    let phases: Vec<String> = (0..10_000).map(|i| format!("p{i}")).collect();
+    let contracts: serde_json::Map<String, serde_json::Value> = phases
+        .iter()
+        .enumerate()
+        .map(|(i, phase)| {
+            let inputs = match i {
+                0 => json!([]),
+                _ => json!([{
+                    "name": "in",
+                    "source": { "kind": "phase_output", "phase": format!("p{}", i - 1), "port": "out" }
+                }]),
+            };
+            (phase.clone(), json!({ "outputs": [{ "name": "out" }], "inputs": inputs }))
+        })
+        .collect();
+    let slow = json!({
+        "meta": { "title": "Support" },
+        "algorithm": { "name": "Support", "phases": phases },
+        "implementation": { "phase_contracts": contracts }
+    });
+    scratch.write("open-schema.json", "{}");
+    scratch.write("spec.yml", &slow.to_string());
+
+    let mut running = scratch.spawn(&[
+        "--watch",
+        "-j",
+        "1",
+        "--schema",
+        "open-schema.json",
+        "spec.yml",
+    ]);
+    thread::sleep(Duration::from_millis(300));
+    scratch.write(
+        "spec.yml",
+        r#"{"meta": {"title": "Support"}, "algorithm": {"name": "Billing"}}"#,
+    );
+
+    let line = running.wait_for("] ");
+    assert!(
+        line.ends_with("inputs changed during validation; starting over…"),
+        "{line}"
+    );
+    let line = running.wait_for("file(s) for changes");
+    assert!(line.contains("failed; watching"), "{line}");
+}
@@ -0,0 +1,179 @@
+use crate::support::Scratch;
+use serde_json::{json, Value as JsonValue};
+
+/// A spec importing the `common` library and a missing one, both referencing a shared fragment.
+fn workspace() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write(
+        "libs/common/2.3.1.yml",
+        &json!({
+            "library": "common",
+            "version": "2.3.1",
+            "phase_contracts": {
+                "collect": {
+                    "outputs": [{
+                        "name": "issue",
+                        "schema": { "$ref": "../../shared/issue.json" }
+                    }]
+                }
+            }
+        })
+        .to_string(),
+    );
+    scratch.write("shared/issue.json", r#"{"type": "object"}"#);
+    scratch.write(
+        "specs/support.yml",
+        "meta: {title: S, version: v1}\nalgorithm: {name: S, phases: [collect]}\n\
+         implementation:\n  uses: [{library: common, version: \"^2\"}, {library: missing, version: \"^1\"}]\n\
+         \x20 phase_contracts:\n    collect:\n\
+         \x20     inputs: [{name: q, schema: {$ref: \"../shared/issue.json\"}}]\n",
+    );
+    scratch
+}
+
+const MISSING: &str =
+    "⚠️ library missing is imported, but Library 'missing' was not found (searched: libs)\n";
+
+#[test]
+fn renders_the_dependencies_of_a_workspace() {
+    let scratch = workspace();
+    let run = scratch.run(&["workspace", "graph", "specs", "--library-path", "libs"]);
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(run.stderr, MISSING);
+    assert_eq!(
+        run.stdout,
+        r#"digraph workspace {
+  rankdir=LR;
+  node [shape=box, fontname="Helvetica"];
+  edge [fontname="Helvetica", fontsize=10];
+  n0 [label="specs/support.yml", style=bold];
+  n1 [label="shared/issue.json", shape=note];
+  n2 [label="common 2.3.1\nlibs/common/2.3.1.yml", shape=component];
+  n3 [label="library missing", style=dashed, color=red, fontcolor=red];
+  n0 -> n1 [label="$ref", style=dotted];
+  n0 -> n2 [label="uses ^2"];
+  n0 -> n3 [label="uses"];
+  n2 -> n1 [label="$ref", style=dotted];
+}
+"#
+    );
+
+    let run = scratch.run(&[
+        "workspace",
+        "graph",
+        "specs",
+        "--library-path",
+        "libs",
+        "--format",
+        "mermaid",
+    ]);
+    assert_eq!(
+        run.stdout,
+        r#"```mermaid
+flowchart LR
+  n0["specs/support.yml"]
+  n1[/"shared/issue.json"/]
+  n2[["common 2.3.1<br/>libs/common/2.3.1.yml"]]
+  n3(["library missing"])
+  n0 -.->|"$ref"| n1
+  n0 -->|"uses ^2"| n2
+  n0 -->|"uses"| n3
+  n2 -.->|"$ref"| n1
+  classDef missing stroke:red,color:red,stroke-dasharray:4
+  class n3 missing
+```
+"#
+    );
+}
+
+#[test]
+fn lists_the_specs_that_depend_on_each_file() {
+    let scratch = workspace();
+    let run = scratch.run(&[
+        "workspace",
+        "graph",
+        "specs",
+        "--library-path",
+        "libs",
+        "--format",
+        "json",
+    ]);
+    let graph: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(graph["cycles"], json!([]));
+    assert_eq!(graph["edges"].as_array().unwrap().len(), 4);
+    assert_eq!(
+        graph["nodes"][2],
+        json!({
+            "dependent_specs": ["specs/support.yml"],
+            "id": "libs/common/2.3.1.yml",
+            "kind": "library",
+            "library": "common 2.3.1"
+        })
+    );
+    assert_eq!(
+        graph["nodes"][1]["dependent_specs"],
+        json!(["specs/support.yml"])
+    );
+    assert_eq!(graph["nodes"][3]["kind"], "missing");
+}
+
+#[test]
+fn reports_import_cycles() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "shared/labels.json",
+        r#"{"properties": {"t": {"$ref": "tags.json"}}}"#,
+    );
+    scratch.write(
+        "shared/tags.json",
+        r#"{"items": {"$ref": "labels.json#/properties"}}"#,
+    );
+    scratch.write(
+        "specs/cyc.yml",
+        "meta: {title: S, version: v1}\nalgorithm: {name: S, phases: [collect]}\n\
+         implementation:\n  phase_contracts:\n    collect:\n\
+         \x20     inputs: [{name: q, schema: {$ref: \"../shared/labels.json\"}}]\n",
+    );
+    let run = scratch.run(&["workspace", "graph", "specs"]);
+    assert_eq!(run.code, Some(1));
+    assert_eq!(
+        run.stderr,
+        "❌ Files import each other in a cycle: shared/labels.json → shared/tags.json → \
+         shared/labels.json\n"
+    );
+    assert!(run.stdout.contains(
+        "  n1 -> n2 [label=\"$ref\", style=dotted, color=red, fontcolor=red, penwidth=2];\n"
+    ));
+
+    let run = scratch.run(&["workspace", "graph", "specs", "--format", "json"]);
+    let graph: JsonValue = serde_json::from_str(&run.stdout).unwrap();
+    assert_eq!(
+        graph["cycles"],
+        json!([["shared/labels.json", "shared/tags.json"]])
+    );
+}
+
+#[test]
+fn keeps_mermaid_labels_on_one_line() {
+    let scratch = workspace();
+    scratch.write(
+        "specs/support.yml",
+        "meta: {title: S, version: v1}\nalgorithm: {name: S, phases: []}\n\
+         implementation:\n  uses: [{library: common, version: \">=2,\\n<3\"}]\n",
+    );
+    let run = scratch.run(&[
+        "workspace",
+        "graph",
+        "specs",
+        "--library-path",
+        "libs",
+        "--format",
+        "mermaid",
+    ]);
+    assert!(run.success(), "{}", run.stderr);
+    assert!(
+        run.stdout.contains("  n0 -->|\"uses #gt;=2, #lt;3\"| n1\n"),
+        "{}",
+        run.stdout
+    );
+}